@@ -71,6 +71,7 @@ func (r *RunnerService) execute(ctx context.Context) {
 	cmd := exec.CommandContext(
 		ctx,
 		r.cfg.InstanceBuilderBinary,
+		"generate",
 		"-s",
 		r.cfg.SpecFile,
 		r.cfg.GeneratedDir,
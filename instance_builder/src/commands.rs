@@ -0,0 +1,319 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use log::debug;
+use shared::{
+    checksums::CHECKSUMS_FILENAME,
+    compression::get_zstd_path,
+    files::remove_empty_dirs,
+    paths::{
+        get_rel_extra_metadata_path, get_rel_metadata_path, get_rel_minecraft_dir,
+        get_rel_versions_dir, get_rel_versions_extra_dir,
+    },
+    signing::get_signature_path,
+    version::{
+        asset_metadata::AssetsMetadata, extra_version_metadata::ExtraVersionMetadata,
+        version_manifest::VersionInfo, version_manifest::VersionManifest,
+        version_metadata::VersionMetadata,
+    },
+};
+
+use crate::spec::get_manifest_path;
+
+async fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> anyhow::Result<T> {
+    let data = tokio::fs::read(path)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to read {}: {e}", path.display()))?;
+    Ok(serde_json::from_slice(&data)?)
+}
+
+// paths are built by hand to avoid creating directories in the output dir
+async fn get_version_files(
+    output_dir: &Path,
+    version: &VersionInfo,
+) -> anyhow::Result<Vec<PathBuf>> {
+    let versions_dir = output_dir.join(get_rel_versions_dir());
+    let libraries_dir = output_dir.join("libraries");
+    let assets_dir = output_dir.join("assets");
+
+    let mut files = vec![];
+    for metadata_info in version.get_metadata_info() {
+        let metadata_path = versions_dir.join(get_rel_metadata_path(&metadata_info.id));
+        let metadata: VersionMetadata = read_json(&metadata_path).await?;
+        files.push(metadata_path.with_extension("jar"));
+        files.push(metadata_path);
+
+        for library in &metadata.libraries {
+            files.extend(
+                library
+                    .get_check_entries(&libraries_dir, None)
+                    .into_iter()
+                    .map(|entry| entry.path),
+            );
+        }
+
+        if let Some(asset_index) = &metadata.asset_index {
+            let asset_index_path = assets_dir
+                .join("indexes")
+                .join(format!("{}.json", asset_index.id));
+            if asset_index_path.exists() {
+                let assets_metadata: AssetsMetadata = read_json(&asset_index_path).await?;
+                files.extend(
+                    assets_metadata
+                        .get_check_entries(&assets_dir, "", false)?
+                        .into_iter()
+                        .map(|entry| entry.path),
+                );
+            }
+            files.push(asset_index_path);
+        }
+    }
+
+    if version.extra_metadata_url.is_some() {
+        let name = version.get_name();
+        let extra_metadata_path = output_dir
+            .join(get_rel_versions_extra_dir())
+            .join(get_rel_extra_metadata_path(&name));
+        let extra_metadata: ExtraVersionMetadata = read_json(&extra_metadata_path).await?;
+        files.push(get_signature_path(&extra_metadata_path));
+        files.push(extra_metadata_path);
+
+        let minecraft_dir = output_dir.join(get_rel_minecraft_dir(&name));
+        for include in &extra_metadata.include {
+            files.extend(
+                include
+                    .objects
+                    .iter()
+                    .map(|object| minecraft_dir.join(&object.path)),
+            );
+        }
+        for library in &extra_metadata.extra_forge_libs {
+            files.extend(
+                library
+                    .get_check_entries(&libraries_dir, None)
+                    .into_iter()
+                    .map(|entry| entry.path),
+            );
+        }
+    }
+
+    files.retain(|path| path.is_file());
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+pub async fn get_version_size(output_dir: &Path, version: &VersionInfo) -> anyhow::Result<u64> {
+    let mut size = 0;
+    for path in get_version_files(output_dir, version).await? {
+        size += tokio::fs::metadata(&path).await?.len();
+    }
+    Ok(size)
+}
+
+pub async fn list(output_dir: &Path) -> anyhow::Result<()> {
+    let manifest = VersionManifest::read_local(&get_manifest_path(output_dir)).await?;
+    for version in &manifest.versions {
+        let mut ids: Vec<String> = version
+            .get_metadata_info()
+            .into_iter()
+            .map(|info| info.id)
+            .collect();
+        ids.dedup();
+        let files = get_version_files(output_dir, version).await?;
+        let mut size = 0;
+        for path in &files {
+            size += tokio::fs::metadata(path).await?.len();
+        }
+        println!(
+            "{}\t{}\t{} files\t{:.1} MiB",
+            version.get_name(),
+            ids.join(", "),
+            files.len(),
+            size as f64 / 1024.0 / 1024.0
+        );
+    }
+    Ok(())
+}
+
+fn get_all_files(dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    for entry in walkdir::WalkDir::new(dir) {
+        let entry = entry?;
+        if entry.file_type().is_file() {
+            files.push(entry.into_path());
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+// how many versions of the manifest reference each file of the output dir,
+// a file shared by several versions stays reachable until the last of them is removed
+pub async fn get_file_references(
+    output_dir: &Path,
+    manifest: &VersionManifest,
+) -> anyhow::Result<HashMap<PathBuf, usize>> {
+    let manifest_path = get_manifest_path(output_dir);
+    let mut references = HashMap::new();
+    for path in [
+        get_signature_path(&manifest_path),
+        get_zstd_path(&manifest_path),
+        manifest_path,
+        output_dir.join(CHECKSUMS_FILENAME),
+    ] {
+        references.insert(path, 1);
+    }
+
+    let versions_dir = output_dir.join(get_rel_versions_dir());
+    for version in &manifest.versions {
+        // remote versions that were not generated into this output dir
+        if !versions_dir
+            .join(get_rel_metadata_path(&version.id))
+            .is_file()
+        {
+            debug!("Version {} is not in the output dir", version.get_name());
+            continue;
+        }
+        for path in get_version_files(output_dir, version).await? {
+            *references.entry(path).or_insert(0) += 1;
+        }
+    }
+    Ok(references)
+}
+
+// removes files that are not referenced by any version of the manifest,
+// returns the removed paths relative to the output dir
+pub async fn prune(
+    output_dir: &Path,
+    manifest: &VersionManifest,
+    dry_run: bool,
+) -> anyhow::Result<Vec<PathBuf>> {
+    let references = get_file_references(output_dir, manifest).await?;
+    let unreachable: Vec<PathBuf> = get_all_files(output_dir)?
+        .into_iter()
+        .filter(|path| !references.contains_key(path))
+        .collect();
+
+    if !dry_run {
+        for path in &unreachable {
+            tokio::fs::remove_file(path).await?;
+        }
+        remove_empty_dirs(output_dir).await?;
+    }
+
+    Ok(unreachable
+        .into_iter()
+        .map(|path| path.strip_prefix(output_dir).unwrap_or(&path).to_path_buf())
+        .collect())
+}
+
+// removes files that are not referenced by the manifest, returns the removed paths
+pub async fn clean(output_dir: &Path, dry_run: bool) -> anyhow::Result<Vec<PathBuf>> {
+    let manifest = VersionManifest::read_local(&get_manifest_path(output_dir)).await?;
+    prune(output_dir, &manifest, dry_run).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version_info(id: &str) -> VersionInfo {
+        VersionInfo {
+            id: id.to_string(),
+            url: format!("https://example.com/versions/{id}/{id}.json"),
+            sha1: "0".repeat(40),
+            name: None,
+            inherits_from: vec![],
+            extra_metadata_url: None,
+            extra_metadata_sha1: None,
+            rollout_percent: None,
+            server_address: None,
+        }
+    }
+
+    fn write_file(path: &Path, data: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, data).unwrap();
+    }
+
+    fn write_metadata(output_dir: &Path, id: &str, libraries: &[&str]) {
+        let libraries: Vec<String> = libraries
+            .iter()
+            .map(|name| format!(r#"{{"name":"org.example:{name}:1.0"}}"#))
+            .collect();
+        write_file(
+            &output_dir
+                .join(get_rel_versions_dir())
+                .join(get_rel_metadata_path(id)),
+            &format!(
+                r#"{{"id":"{id}","mainClass":"Main","libraries":[{}]}}"#,
+                libraries.join(",")
+            ),
+        );
+        for name in ["shared", "a", "b"] {
+            let library_path = get_library_path(output_dir, name);
+            if !library_path.exists() {
+                write_file(&library_path, name);
+            }
+        }
+    }
+
+    fn get_library_path(output_dir: &Path, name: &str) -> PathBuf {
+        output_dir.join(format!("libraries/org/example/{name}/1.0/{name}-1.0.jar"))
+    }
+
+    #[tokio::test]
+    async fn test_prune() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let output_dir = temp_dir.path();
+        write_metadata(output_dir, "a", &["shared", "a"]);
+        write_metadata(output_dir, "b", &["shared", "b"]);
+        write_file(&output_dir.join("stale.txt"), "stale");
+
+        // remote isn't generated into this output dir and is skipped
+        let mut manifest = VersionManifest {
+            versions: vec![version_info("a"), version_info("b"), version_info("remote")],
+        };
+        manifest
+            .save_to_file(&get_manifest_path(output_dir))
+            .await
+            .unwrap();
+
+        let references = get_file_references(output_dir, &manifest).await.unwrap();
+        assert_eq!(references[&get_library_path(output_dir, "shared")], 2);
+        assert_eq!(references[&get_library_path(output_dir, "a")], 1);
+
+        assert_eq!(
+            prune(output_dir, &manifest, true).await.unwrap(),
+            vec![PathBuf::from("stale.txt")]
+        );
+        assert!(output_dir.join("stale.txt").exists());
+
+        // deleting a keeps the library it shares with b
+        manifest.versions.retain(|version| version.id != "a");
+        let removed = prune(output_dir, &manifest, false).await.unwrap();
+        assert_eq!(
+            removed,
+            vec![
+                PathBuf::from("libraries/org/example/a/1.0/a-1.0.jar"),
+                PathBuf::from("stale.txt"),
+                PathBuf::from("versions/a/a.json"),
+            ]
+        );
+        assert!(get_library_path(output_dir, "shared").exists());
+        assert!(get_library_path(output_dir, "b").exists());
+        assert!(!output_dir.join("versions/a").exists());
+        assert!(get_manifest_path(output_dir).exists());
+
+        // the last version referencing the shared library is gone too
+        manifest.versions.clear();
+        let removed = prune(output_dir, &manifest, false).await.unwrap();
+        assert!(removed.contains(&PathBuf::from(
+            "libraries/org/example/shared/1.0/shared-1.0.jar"
+        )));
+        assert!(!output_dir.join("libraries").exists());
+    }
+}
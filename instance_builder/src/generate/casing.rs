@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+
+use shared::version::extra_version_metadata::Include;
+
+#[derive(thiserror::Error, Debug)]
+pub enum CasingError {
+    #[error(
+        "Include paths {first} and {second} of version {version} differ only by case, \
+         they are the same path on Windows and macOS"
+    )]
+    CaseConflict {
+        version: String,
+        first: String,
+        second: String,
+    },
+}
+
+// every directory on the way is compared too, Config/a.json and config/b.json collide as well
+pub fn check_path_casing(version_name: &str, include: &[Include]) -> Result<(), CasingError> {
+    let mut seen_paths: HashMap<String, String> = HashMap::new();
+    for object in include.iter().flat_map(|include| &include.objects) {
+        let mut path = String::new();
+        for part in object.path.split('/') {
+            if !path.is_empty() {
+                path.push('/');
+            }
+            path.push_str(part);
+            match seen_paths.entry(path.to_lowercase()) {
+                Entry::Occupied(entry) if *entry.get() != path => {
+                    return Err(CasingError::CaseConflict {
+                        version: version_name.to_string(),
+                        first: entry.get().clone(),
+                        second: path,
+                    });
+                }
+                Entry::Occupied(_) => {}
+                Entry::Vacant(entry) => {
+                    entry.insert(path.clone());
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use shared::version::extra_version_metadata::Object;
+
+    use super::*;
+
+    fn include(paths: &[&str]) -> Vec<Include> {
+        let objects = paths
+            .iter()
+            .map(|path| Object {
+                path: path.to_string(),
+                sha1: "0".repeat(40),
+                url: format!("https://example.com/{path}"),
+                size: None,
+            })
+            .collect();
+        vec![Include {
+            path: String::new(),
+            overwrite: true,
+            delete_extra: true,
+            recursive: false,
+            objects,
+        }]
+    }
+
+    #[test]
+    fn test_check_path_casing() {
+        check_path_casing(
+            "Pack",
+            &include(&["config/a.json", "config/b.json", "mods/a.jar"]),
+        )
+        .unwrap();
+
+        for (paths, first, second) in [
+            (["Config/a.json", "config/b.json"], "Config", "config"),
+            (["config/a.json", "Config/b.json"], "config", "Config"),
+            (
+                ["mods/Sodium.jar", "mods/sodium.jar"],
+                "mods/Sodium.jar",
+                "mods/sodium.jar",
+            ),
+        ] {
+            let result = check_path_casing("Pack", &include(&paths));
+            assert!(
+                matches!(
+                    &result,
+                    Err(CasingError::CaseConflict { version, first: f, second: s })
+                        if version == "Pack" && f == first && s == second
+                ),
+                "{paths:?}"
+            );
+        }
+    }
+}
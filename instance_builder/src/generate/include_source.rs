@@ -0,0 +1,218 @@
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+use flate2::read::GzDecoder;
+use log::info;
+use reqwest::StatusCode;
+use reqwest::header::{ETAG, IF_NONE_MATCH};
+use sha1::{Digest, Sha1};
+use shared::utils::get_safe_path;
+use tokio::fs;
+
+use crate::utils::get_path_lock;
+
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+
+#[derive(thiserror::Error, Debug)]
+pub enum IncludeSourceError {
+    #[error("Failed to fetch include_from of version {0}")]
+    FetchFailed(String),
+    #[error("Include source {0} is neither a zip nor a tar.gz archive")]
+    UnsupportedArchive(String),
+    #[error("Archive entry {0} points outside of the extraction directory")]
+    UnsafeEntryPath(String),
+    #[error("Archive entry {0} is a link")]
+    LinkEntry(String),
+}
+
+pub fn is_remote(include_from: &str) -> bool {
+    include_from.starts_with("http://") || include_from.starts_with("https://")
+}
+
+fn extract_zip(archive_path: &Path, target_dir: &Path) -> anyhow::Result<()> {
+    let mut archive = zip::ZipArchive::new(File::open(archive_path)?)?;
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        let path = entry
+            .enclosed_name()
+            .and_then(|name| get_safe_path(target_dir, &name))
+            .ok_or_else(|| IncludeSourceError::UnsafeEntryPath(entry.name().to_string()))?;
+        if entry.is_symlink() {
+            return Err(IncludeSourceError::LinkEntry(entry.name().to_string()).into());
+        }
+        if entry.is_dir() {
+            std::fs::create_dir_all(&path)?;
+        } else {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            io::copy(&mut entry, &mut File::create(&path)?)?;
+        }
+    }
+    Ok(())
+}
+
+fn extract_tar_gz(archive_path: &Path, target_dir: &Path) -> anyhow::Result<()> {
+    let mut archive = tar::Archive::new(GzDecoder::new(File::open(archive_path)?));
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let entry_path = entry.path()?.to_path_buf();
+        let entry_name = entry_path.display().to_string();
+        let path = get_safe_path(target_dir, &entry_path)
+            .ok_or_else(|| IncludeSourceError::UnsafeEntryPath(entry_name.clone()))?;
+        let entry_type = entry.header().entry_type();
+        if entry_type.is_symlink() || entry_type.is_hard_link() {
+            return Err(IncludeSourceError::LinkEntry(entry_name).into());
+        }
+        if entry_type.is_dir() {
+            std::fs::create_dir_all(&path)?;
+        } else if entry_type.is_file() {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            entry.unpack(&path)?;
+        }
+    }
+    Ok(())
+}
+
+// the format is taken from the content, urls of artifact stores rarely end with an extension
+fn extract(url: &str, archive_path: &Path, target_dir: &Path) -> anyhow::Result<()> {
+    let mut magic = [0u8; 4];
+    let read = io::Read::read(&mut File::open(archive_path)?, &mut magic)?;
+    let magic = &magic[..read];
+    if magic.starts_with(ZIP_MAGIC) {
+        extract_zip(archive_path, target_dir)
+    } else if magic.starts_with(GZIP_MAGIC) {
+        extract_tar_gz(archive_path, target_dir)
+    } else {
+        Err(IncludeSourceError::UnsupportedArchive(url.to_string()).into())
+    }
+}
+
+async fn fetch_and_extract(url: &str, work_dir: &Path) -> anyhow::Result<PathBuf> {
+    let cache_dir = work_dir
+        .join("include_sources")
+        .join(format!("{:x}", Sha1::digest(url.as_bytes())));
+    let etag_path = cache_dir.join("etag");
+    let archive_path = cache_dir.join("archive");
+    let extracted_dir = cache_dir.join("extracted");
+    // versions including the same source fetch it one after another
+    let _guard = get_path_lock(&cache_dir).lock_owned().await;
+    fs::create_dir_all(&cache_dir).await?;
+
+    let cached_etag = if extracted_dir.exists() {
+        fs::read_to_string(&etag_path).await.ok()
+    } else {
+        None
+    };
+    let mut request = reqwest::Client::new().get(url);
+    if let Some(etag) = &cached_etag {
+        request = request.header(IF_NONE_MATCH, etag);
+    }
+    let response = request.send().await?;
+    if cached_etag.is_some() && response.status() == StatusCode::NOT_MODIFIED {
+        info!("Include source {url} is not modified, using the cached copy");
+        return Ok(extracted_dir);
+    }
+    let response = response.error_for_status()?;
+    let etag = response
+        .headers()
+        .get(ETAG)
+        .and_then(|etag| etag.to_str().ok())
+        .map(str::to_string);
+    info!("Downloading include source {url}");
+    let data = response.bytes().await?;
+
+    // the etag is only valid for a complete extraction
+    if etag_path.exists() {
+        fs::remove_file(&etag_path).await?;
+    }
+    fs::write(&archive_path, &data).await?;
+    let temp_dir = cache_dir.join("extracted.tmp");
+    if temp_dir.exists() {
+        fs::remove_dir_all(&temp_dir).await?;
+    }
+    let (url_clone, archive_path_clone, temp_dir_clone) =
+        (url.to_string(), archive_path.clone(), temp_dir.clone());
+    tokio::task::spawn_blocking(move || extract(&url_clone, &archive_path_clone, &temp_dir_clone))
+        .await??;
+    if extracted_dir.exists() {
+        fs::remove_dir_all(&extracted_dir).await?;
+    }
+    fs::rename(&temp_dir, &extracted_dir).await?;
+    if let Some(etag) = etag {
+        fs::write(&etag_path, etag).await?;
+    }
+    Ok(extracted_dir)
+}
+
+// downloads a zip or tar.gz include source into the work dir and extracts it,
+// returns the directory to include the files from
+pub async fn fetch_include_source(
+    url: &str,
+    version_name: &str,
+    work_dir: &Path,
+) -> anyhow::Result<PathBuf> {
+    fetch_and_extract(url, work_dir)
+        .await
+        .with_context(|| IncludeSourceError::FetchFailed(version_name.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write as _;
+
+    use super::*;
+
+    fn write_tar_gz(path: &Path, entry_name: &[u8]) {
+        let mut header = tar::Header::new_old();
+        header.as_old_mut().name[..entry_name.len()].copy_from_slice(entry_name);
+        header.set_size(4);
+        header.set_entry_type(tar::EntryType::Regular);
+        header.set_cksum();
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+            File::create(path).unwrap(),
+            Default::default(),
+        ));
+        builder.append(&header, &b"data"[..]).unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+    }
+
+    #[test]
+    fn test_extract_rejects_path_traversal() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+
+        let zip_path = dir.join("source.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&zip_path).unwrap());
+        zip.start_file("config/mod.toml", zip::write::SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(b"key = 1").unwrap();
+        zip.finish().unwrap();
+        extract("zip", &zip_path, &dir.join("zip")).unwrap();
+        assert_eq!(
+            std::fs::read(dir.join("zip/config/mod.toml")).unwrap(),
+            b"key = 1"
+        );
+
+        let tar_path = dir.join("source.tar.gz");
+        write_tar_gz(&tar_path, b"config/mod.toml");
+        extract("tar", &tar_path, &dir.join("tar")).unwrap();
+        assert_eq!(
+            std::fs::read(dir.join("tar/config/mod.toml")).unwrap(),
+            b"data"
+        );
+
+        write_tar_gz(&tar_path, b"../escaped");
+        let err = extract("tar", &tar_path, &dir.join("evil")).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<IncludeSourceError>(),
+            Some(IncludeSourceError::UnsafeEntryPath(_))
+        ));
+        assert!(!dir.join("escaped").exists());
+    }
+}
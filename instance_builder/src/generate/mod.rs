@@ -1,3 +1,6 @@
+pub mod casing;
+pub mod include_source;
 pub mod mapping;
 pub mod patch;
 pub mod sync;
+pub mod variant;
@@ -1,19 +1,17 @@
-use std::{
-    path::{Path, PathBuf},
-    sync::Arc,
-};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use log::{debug, info};
 use rand::{SeedableRng as _, rngs::StdRng, seq::SliceRandom as _};
 use shared::{
-    adaptive_download::download_files,
-    files::{CheckEntry, get_download_entries},
+    adaptive_download::download_files_with_max_connections,
+    files::{CheckEntry, DownloadEntry, get_download_entries},
     paths::{get_client_jar_path, get_libraries_dir},
-    progress::ProgressBar,
+    progress::{ProgressBar, ProgressFormat, terminal_progress_bar},
     version::{asset_metadata::AssetsMetadata, version_metadata::VersionMetadata},
 };
 
-use crate::{progress::TerminalProgressBar, utils::get_assets_dir};
+use crate::utils::{get_assets_dir, get_path_lock};
 
 pub fn get_libraries_check_downloads(
     version_metadata: &VersionMetadata,
@@ -42,9 +40,55 @@ pub struct SyncResult {
     pub paths_to_copy: Vec<PathBuf>,
 }
 
+// files another version is already downloading into the same dir are left to it
+// and checked again once it's done, a failed download is retried here
+async fn download_unclaimed(
+    mut download_entries: Vec<DownloadEntry>,
+    progress_bar: Arc<dyn ProgressBar<&'static str>>,
+    max_connections: usize,
+) -> anyhow::Result<()> {
+    while !download_entries.is_empty() {
+        let mut guards = vec![];
+        let mut claimed = vec![];
+        let mut busy = vec![];
+        for entry in download_entries {
+            match get_path_lock(&entry.path).try_lock_owned() {
+                Ok(guard) => {
+                    guards.push(guard);
+                    claimed.push(entry);
+                }
+                Err(_) => busy.push(entry),
+            }
+        }
+        if !claimed.is_empty() {
+            download_files_with_max_connections(claimed, progress_bar.clone(), max_connections)
+                .await?;
+        }
+        drop(guards);
+
+        if !busy.is_empty() {
+            info!(
+                "Waiting for {} files downloaded by other versions",
+                busy.len()
+            );
+        }
+        for entry in &busy {
+            drop(get_path_lock(&entry.path).lock().await);
+        }
+        download_entries = busy
+            .into_iter()
+            .filter(|entry| !entry.path.exists())
+            .collect();
+    }
+    Ok(())
+}
+
 pub async fn sync_version(
     version_metadata: &VersionMetadata,
     output_dir: &Path,
+    progress_format: ProgressFormat,
+    max_connections: usize,
+    version_name: &str,
 ) -> anyhow::Result<SyncResult> {
     let libraries_dir = get_libraries_dir(output_dir);
     let mut check_entries = get_libraries_check_downloads(version_metadata, &libraries_dir);
@@ -52,7 +96,11 @@ pub async fn sync_version(
 
     if let Some(asset_index) = &version_metadata.asset_index {
         let assets_dir = get_assets_dir(output_dir);
-        let assets_metadata = AssetsMetadata::read_or_download(asset_index, &assets_dir).await?;
+        let assets_metadata = {
+            let asset_index_path = AssetsMetadata::get_path(&assets_dir, &asset_index.id).await?;
+            let _guard = get_path_lock(&asset_index_path).lock_owned().await;
+            AssetsMetadata::read_or_download(asset_index, &assets_dir).await?
+        };
         let asset_check_entries =
             assets_metadata.get_check_entries(&assets_dir, RESOURCES_URL_BASE, true)?;
 
@@ -76,7 +124,7 @@ pub async fn sync_version(
         check_entries.push(client_entry);
     }
 
-    let progress_bar = Arc::new(TerminalProgressBar::new());
+    let progress_bar = terminal_progress_bar(progress_format, "sync", version_name);
 
     let all_paths = check_entries
         .iter()
@@ -91,9 +139,69 @@ pub async fn sync_version(
 
     progress_bar.reset();
     progress_bar.set_message("Downloading files...");
-    download_files(download_entries, progress_bar).await?;
+    download_unclaimed(download_entries, progress_bar, max_connections).await?;
 
     Ok(SyncResult {
         paths_to_copy: all_paths,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use axum::{Router, extract::State, routing::get};
+
+    use super::*;
+
+    async fn serve_library(State(requests): State<Arc<AtomicUsize>>) -> &'static str {
+        requests.fetch_add(1, Ordering::SeqCst);
+        // keeps the first download in progress while the other version checks its files
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        "library"
+    }
+
+    fn get_metadata(id: &str, base_url: &str) -> VersionMetadata {
+        serde_json::from_str(&format!(
+            r#"{{
+                "id": "{id}",
+                "mainClass": "Main",
+                "libraries": [{{"name": "org.lwjgl:lwjgl:3.3.3", "url": "{base_url}"}}]
+            }}"#
+        ))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_versions_share_library() {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let app = Router::new()
+            .route("/org/lwjgl/lwjgl/3.3.3/lwjgl-3.3.3.jar", get(serve_library))
+            .with_state(requests.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let work_dir = temp_dir.path();
+        let first = get_metadata("1.20.1", &base_url);
+        let second = get_metadata("1.21", &base_url);
+        let (first_result, second_result) = tokio::join!(
+            sync_version(&first, work_dir, ProgressFormat::Json, 4, "first"),
+            sync_version(&second, work_dir, ProgressFormat::Json, 4, "second"),
+        );
+
+        let library_path = work_dir.join("libraries/org/lwjgl/lwjgl/3.3.3/lwjgl-3.3.3.jar");
+        assert_eq!(
+            first_result.unwrap().paths_to_copy,
+            vec![library_path.clone()]
+        );
+        assert_eq!(
+            second_result.unwrap().paths_to_copy,
+            vec![library_path.clone()]
+        );
+        assert_eq!(std::fs::read(&library_path).unwrap(), b"library");
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+}
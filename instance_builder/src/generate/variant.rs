@@ -0,0 +1,236 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use serde::Deserialize;
+use shared::{
+    generate::extra::IncludeRule,
+    version::extra_version_metadata::{Include, matches_pattern},
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum VariantError {
+    #[error("Variant name {0} is already used by another version")]
+    DuplicateName(String),
+    #[error(
+        "Include path {path} of variant {variant} overlaps an include path of the base version"
+    )]
+    IncludePathCollision { variant: String, path: String },
+}
+
+// another edition of a version, files of the base version are shared with it
+#[derive(Deserialize)]
+pub struct Variant {
+    pub name: String,
+
+    // files of these rules are served from the directory of the variant
+    #[serde(default)]
+    pub include: Vec<IncludeRule>,
+
+    // paths of base files to leave out, relative to include_from
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+impl Variant {
+    pub fn get_version_name(&self, base_name: &str) -> String {
+        format!("{base_name} ({})", self.name)
+    }
+
+    // the base include without the excluded files, urls still point to the base version
+    pub fn get_shared_include(&self, base_include: &[Include]) -> Vec<Include> {
+        base_include
+            .iter()
+            .map(|include| {
+                let mut include = include.clone();
+                include.objects.retain(|object| {
+                    !self
+                        .exclude
+                        .iter()
+                        .any(|pattern| matches_pattern(pattern, &object.path))
+                });
+                include
+            })
+            .collect()
+    }
+
+    // two rules for one directory would make the launcher delete the files of each other
+    pub fn check_include_paths(
+        &self,
+        base_name: &str,
+        base_include: &[Include],
+    ) -> Result<(), VariantError> {
+        for rule in &self.include {
+            let path = Path::new(&rule.path);
+            if base_include.iter().any(|include| {
+                let base_path = Path::new(&include.path);
+                path.starts_with(base_path) || base_path.starts_with(path)
+            }) {
+                return Err(VariantError::IncludePathCollision {
+                    variant: self.get_version_name(base_name),
+                    path: rule.path.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+// the files of versions with the same name would be mixed in the output dir
+pub fn check_variant_names<'a>(
+    versions: impl Iterator<Item = (&'a str, &'a [Variant])>,
+) -> Result<(), VariantError> {
+    let versions: Vec<_> = versions.collect();
+    let mut names: HashSet<String> = versions.iter().map(|(name, _)| name.to_string()).collect();
+    for (base_name, variants) in versions {
+        for variant in variants {
+            let name = variant.get_version_name(base_name);
+            if !names.insert(name.clone()) {
+                return Err(VariantError::DuplicateName(name));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use shared::generate::extra::{ExtraMetadataGenerator, IncludeConfig};
+    use shared::version::extra_version_metadata::InstanceDescription;
+
+    use super::*;
+
+    const DOWNLOAD_SERVER_BASE: &str = "https://example.com/data";
+
+    fn include_rule(path: &str) -> IncludeRule {
+        serde_json::from_value(serde_json::json!({ "path": path })).unwrap()
+    }
+
+    fn include_config(include: Vec<IncludeRule>, include_from: &Path) -> Option<IncludeConfig> {
+        Some(IncludeConfig {
+            include,
+            include_from: include_from.display().to_string(),
+            download_server_base: DOWNLOAD_SERVER_BASE.to_string(),
+            resources_url_base: None,
+        })
+    }
+
+    fn variant_from_name(name: &str) -> Variant {
+        serde_json::from_value(serde_json::json!({ "name": name })).unwrap()
+    }
+
+    fn get_urls(include: &[Include]) -> Vec<(String, String)> {
+        let mut urls: Vec<_> = include
+            .iter()
+            .flat_map(|include| &include.objects)
+            .map(|object| (object.path.clone(), object.url.clone()))
+            .collect();
+        urls.sort();
+        urls
+    }
+
+    #[tokio::test]
+    async fn test_variant_shares_files() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        let include_from = dir.join("include_from");
+        let work_dir = dir.join("work");
+        for path in [
+            "mods/fabric-api.jar",
+            "mods/shaders.jar",
+            "config/sodium.json",
+            "shaderpacks/pack.zip",
+        ] {
+            std::fs::create_dir_all(include_from.join(path).parent().unwrap()).unwrap();
+            std::fs::write(include_from.join(path), path).unwrap();
+        }
+
+        let base = ExtraMetadataGenerator::new(
+            "Pack".to_string(),
+            include_config(
+                vec![include_rule("mods"), include_rule("config")],
+                &include_from,
+            ),
+            vec![],
+            None,
+            None,
+            None,
+            InstanceDescription::default(),
+        )
+        .generate(&work_dir)
+        .await
+        .unwrap();
+        let base_include = base.extra_metadata.include;
+
+        let variant: Variant = serde_json::from_value(serde_json::json!({
+            "name": "Full",
+            "include": [{"path": "shaderpacks"}],
+            "exclude": ["mods/shader*"],
+        }))
+        .unwrap();
+        assert_eq!(variant.get_version_name("Pack"), "Pack (Full)");
+        variant.check_include_paths("Pack", &base_include).unwrap();
+        let shared_include = variant.get_shared_include(&base_include);
+        let variant_result = ExtraMetadataGenerator::new(
+            variant.get_version_name("Pack"),
+            include_config(variant.include, &include_from),
+            vec![],
+            None,
+            None,
+            None,
+            InstanceDescription::default(),
+        )
+        .with_shared_include(shared_include)
+        .generate(&work_dir)
+        .await
+        .unwrap();
+
+        let base_urls = get_urls(&base_include);
+        let variant_urls = get_urls(&variant_result.extra_metadata.include);
+        assert_eq!(
+            variant_urls,
+            vec![
+                (
+                    "config/sodium.json".to_string(),
+                    format!("{DOWNLOAD_SERVER_BASE}/instances/Pack/minecraft/config/sodium.json")
+                ),
+                (
+                    "mods/fabric-api.jar".to_string(),
+                    format!("{DOWNLOAD_SERVER_BASE}/instances/Pack/minecraft/mods/fabric-api.jar")
+                ),
+                (
+                    "shaderpacks/pack.zip".to_string(),
+                    format!(
+                        "{DOWNLOAD_SERVER_BASE}/instances/Pack%20(Full)/minecraft/shaderpacks/pack.zip"
+                    )
+                ),
+            ]
+        );
+        // every shared file is downloaded from the same url as for the base version
+        for url in &variant_urls {
+            if !url.0.starts_with("shaderpacks/") {
+                assert!(base_urls.contains(url));
+            }
+        }
+        assert_eq!(
+            variant_result.include_mapping.keys().collect::<Vec<_>>(),
+            vec!["shaderpacks"]
+        );
+
+        let variants = [variant_from_name("Lite"), variant_from_name("Full")];
+        check_variant_names([("Pack", &variants[..])].into_iter()).unwrap();
+        assert!(matches!(
+            check_variant_names([("Pack", &variants[..]), ("Pack (Lite)", &[][..])].into_iter()),
+            Err(VariantError::DuplicateName(name)) if name == "Pack (Lite)"
+        ));
+
+        let colliding: Variant = serde_json::from_value(serde_json::json!({
+            "name": "Lite",
+            "include": [{"path": "mods/lite"}],
+        }))
+        .unwrap();
+        assert!(matches!(
+            colliding.check_include_paths("Pack", &base_include),
+            Err(VariantError::IncludePathCollision { .. })
+        ));
+    }
+}
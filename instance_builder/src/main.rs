@@ -1,14 +1,29 @@
+mod commands;
 mod generate;
-mod progress;
+mod metrics;
+mod notify;
+mod serve;
 mod spec;
+mod spec_file;
+mod upload;
 mod utils;
+mod validate;
+mod verify;
 
-use clap::{Arg, Command};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use shared::adaptive_download::DEFAULT_MAX_CONNECTIONS;
+use shared::checksums::CHECKSUMS_FILENAME;
+use shared::loader_generator::forge::{
+    ABANDONED_INSTALLER_DIR_AGE, find_abandoned_installer_dirs, remove_abandoned_installer_dirs,
+};
 use shared::logs::setup_logger;
+use shared::progress::ProgressFormat;
 use spec::Spec;
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 use tokio::runtime::Runtime;
+use validate::{SpecError, validate_spec};
 
 fn parse_path(v: &str) -> anyhow::Result<PathBuf> {
     let path = PathBuf::from(v);
@@ -21,6 +36,11 @@ fn parse_path(v: &str) -> anyhow::Result<PathBuf> {
 
 const LOGS_FILENAME: &str = "builder.log";
 
+const EXIT_ERROR: u8 = 1;
+// also used by clap for invalid arguments
+const EXIT_INVALID_INPUT: u8 = 2;
+const EXIT_HAS_CHANGES: u8 = 3;
+
 pub fn get_logs_path(logs_dir: &Path) -> PathBuf {
     if !logs_dir.exists() {
         std::fs::create_dir_all(logs_dir).expect("Failed to create logs directory");
@@ -28,58 +48,373 @@ pub fn get_logs_path(logs_dir: &Path) -> PathBuf {
     logs_dir.join(LOGS_FILENAME)
 }
 
-fn main() -> anyhow::Result<()> {
+fn output_dir_arg() -> Arg {
+    Arg::new("output_dir")
+        .help("Output directory")
+        .default_value("./generated")
+}
+
+fn work_dir_arg() -> Arg {
+    Arg::new("work_dir")
+        .help("Working directory")
+        .default_value("./workdir")
+}
+
+fn spec_args() -> Vec<Arg> {
+    vec![
+        Arg::new("spec_file")
+            .help("Path to the specification file")
+            .required(true)
+            .short('s')
+            .value_parser(parse_path),
+        output_dir_arg(),
+        work_dir_arg(),
+        Arg::new("delete_remote_instances")
+            .help("Comma-separated remote instance names to delete from fetched manifest")
+            .long("delete-remote")
+            .num_args(1..)
+            .use_value_delimiter(true)
+            .value_delimiter(',')
+            .value_name("NAME"),
+        Arg::new("progress_format")
+            .help("Progress output format, json prints line-delimited events to stdout")
+            .long("progress-format")
+            .value_parser(["human", "json"])
+            .default_value("human"),
+        Arg::new("jobs")
+            .help("Number of versions generated concurrently, their hooks may run at the same time")
+            .long("jobs")
+            .short('j')
+            .value_name("N")
+            .value_parser(clap::value_parser!(u16).range(1..))
+            .default_value("1"),
+        Arg::new("max_connections")
+            .help("Maximum number of parallel download connections of every version [default: 50]")
+            .long("max-connections")
+            .value_name("N")
+            .value_parser(clap::value_parser!(u16).range(1..)),
+    ]
+}
+
+fn get_output_dir(matches: &ArgMatches) -> PathBuf {
+    PathBuf::from(matches.get_one::<String>("output_dir").unwrap())
+}
+
+fn get_work_dir(matches: &ArgMatches) -> PathBuf {
+    PathBuf::from(matches.get_one::<String>("work_dir").unwrap())
+}
+
+fn check_manifest_exists(output_dir: &Path) -> bool {
+    let manifest_path = spec::get_manifest_path(output_dir);
+    if !manifest_path.exists() {
+        eprintln!("{} does not exist", manifest_path.display());
+        return false;
+    }
+    true
+}
+
+async fn run_spec_command(matches: &ArgMatches, diff: bool) -> anyhow::Result<ExitCode> {
+    let spec_file = matches.get_one::<PathBuf>("spec_file").unwrap();
+    let output_dir = get_output_dir(matches);
+    let work_dir = get_work_dir(matches);
+    let progress_format = match matches
+        .get_one::<String>("progress_format")
+        .unwrap()
+        .as_str()
+    {
+        "json" => ProgressFormat::Json,
+        _ => ProgressFormat::Human,
+    };
+    let jobs = *matches.get_one::<u16>("jobs").unwrap() as usize;
+    let max_connections = matches
+        .get_one::<u16>("max_connections")
+        .map_or(DEFAULT_MAX_CONNECTIONS, |max_connections| {
+            *max_connections as usize
+        });
+    let delete_remote_set: Option<HashSet<String>> = matches
+        .get_many::<String>("delete_remote_instances")
+        .map(|vals| vals.map(|s| s.to_string()).collect());
+
+    setup_logger(&get_logs_path(&work_dir));
+
+    let spec = Spec::from_file(spec_file)
+        .await?
+        .with_jobs(jobs)
+        .with_max_connections(max_connections);
+    // a dry run is a diff that doesn't fail on changes
+    let dry_run = !diff && matches.get_flag("dry_run");
+    if !diff && !dry_run {
+        let metrics_file = matches.get_one::<PathBuf>("metrics_file");
+        let spec = if matches.get_flag("skip_upload") {
+            spec.without_upload()
+        } else {
+            spec
+        };
+        spec.generate(
+            &output_dir,
+            &work_dir,
+            delete_remote_set.as_ref(),
+            progress_format,
+            metrics_file.map(PathBuf::as_path),
+            matches.get_flag("prune"),
+        )
+        .await?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let output_diff = spec
+        .diff(
+            &output_dir,
+            &work_dir,
+            delete_remote_set.as_ref(),
+            progress_format,
+        )
+        .await?;
+    output_diff.print();
+    if diff && output_diff.has_changes() {
+        Ok(ExitCode::from(EXIT_HAS_CHANGES))
+    } else {
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+async fn run_validate(matches: &ArgMatches) -> anyhow::Result<ExitCode> {
+    let spec_file = matches.get_one::<PathBuf>("spec_file").unwrap();
+    let result = match Spec::from_file(spec_file).await {
+        Ok(spec) => validate_spec(&spec),
+        Err(e) => Err(e.downcast::<SpecError>()?),
+    };
+    match result {
+        Ok(()) => {
+            println!("{} is valid", spec_file.display());
+            Ok(ExitCode::SUCCESS)
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            Ok(ExitCode::from(EXIT_INVALID_INPUT))
+        }
+    }
+}
+
+async fn run_list(matches: &ArgMatches) -> anyhow::Result<ExitCode> {
+    let output_dir = get_output_dir(matches);
+    if !check_manifest_exists(&output_dir) {
+        return Ok(ExitCode::from(EXIT_INVALID_INPUT));
+    }
+    commands::list(&output_dir).await?;
+    Ok(ExitCode::SUCCESS)
+}
+
+async fn run_clean(matches: &ArgMatches) -> anyhow::Result<ExitCode> {
+    let output_dir = get_output_dir(matches);
+    if !check_manifest_exists(&output_dir) {
+        return Ok(ExitCode::from(EXIT_INVALID_INPUT));
+    }
+    let dry_run = matches.get_flag("dry_run");
+    let removed = commands::clean(&output_dir, dry_run).await?;
+    for path in &removed {
+        println!("- {}", path.display());
+    }
+    if dry_run {
+        println!("{} file(s) would be removed", removed.len());
+    } else {
+        println!("Removed {} file(s)", removed.len());
+    }
+
+    let work_dir = get_work_dir(matches);
+    let installer_dirs = if dry_run {
+        find_abandoned_installer_dirs(&work_dir, ABANDONED_INSTALLER_DIR_AGE)?
+    } else {
+        remove_abandoned_installer_dirs(&work_dir, ABANDONED_INSTALLER_DIR_AGE)?
+    };
+    for path in &installer_dirs {
+        println!("- {}", path.display());
+    }
+    if !installer_dirs.is_empty() {
+        if dry_run {
+            println!(
+                "{} abandoned installer dir(s) would be removed",
+                installer_dirs.len()
+            );
+        } else {
+            println!(
+                "Removed {} abandoned installer dir(s)",
+                installer_dirs.len()
+            );
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+async fn run_serve(matches: &ArgMatches) -> anyhow::Result<ExitCode> {
+    let output_dir = get_output_dir(matches);
+    if !check_manifest_exists(&output_dir) {
+        return Ok(ExitCode::from(EXIT_INVALID_INPUT));
+    }
+    let port = *matches.get_one::<u16>("port").unwrap();
+    let rewrite_urls = !matches.get_flag("no_rewrite");
+
+    env_logger::Builder::new()
+        .filter_level(log::LevelFilter::Info)
+        .parse_default_env()
+        .init();
+
+    serve::serve(&output_dir, port, rewrite_urls).await?;
+    Ok(ExitCode::SUCCESS)
+}
+
+async fn run_verify_remote(matches: &ArgMatches) -> anyhow::Result<ExitCode> {
+    let output_dir = get_output_dir(matches);
+    if !output_dir.join(CHECKSUMS_FILENAME).exists() {
+        eprintln!(
+            "{} does not exist, set publish_checksums in the spec",
+            output_dir.join(CHECKSUMS_FILENAME).display()
+        );
+        return Ok(ExitCode::from(EXIT_INVALID_INPUT));
+    }
+    let url = matches.get_one::<String>("url").unwrap();
+    let count = *matches.get_one::<usize>("count").unwrap();
+
+    env_logger::Builder::new()
+        .filter_level(log::LevelFilter::Info)
+        .parse_default_env()
+        .init();
+
+    let drift = verify::verify_remote(&output_dir, url, count).await?;
+    for entry in &drift {
+        println!("! {}: {}", entry.path, entry.reason);
+    }
+    if drift.is_empty() {
+        println!("The remote files match the output directory");
+        Ok(ExitCode::SUCCESS)
+    } else {
+        println!("{} problem(s) found", drift.len());
+        Ok(ExitCode::from(EXIT_HAS_CHANGES))
+    }
+}
+
+fn main() -> ExitCode {
     unsafe {
         std::env::set_var("RUST_LIB_BACKTRACE", "1");
     }
 
-    let matches = Command::new("generate-instance")
-        .about("Generates instances based on a specification file")
-        .arg(
-            Arg::new("spec_file")
-                .help("Path to the specification file")
-                .required(true)
-                .short('s')
-                .value_parser(parse_path),
+    let matches = Command::new("instance_builder")
+        .about("Generates and manages instances based on a specification file")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(
+            Command::new("generate")
+                .about("Generates instances and syncs them to the output directory")
+                .args(spec_args())
+                .arg(
+                    Arg::new("metrics_file")
+                        .help("Write build metrics in the Prometheus text format to this file")
+                        .long("metrics-file")
+                        .value_name("PATH")
+                        .value_parser(clap::value_parser!(PathBuf)),
+                )
+                .arg(
+                    Arg::new("prune")
+                        .help("Delete output files that no version of the manifest references")
+                        .long("prune")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("skip_upload")
+                        .help("Don't push the output directory to the upload target of the spec")
+                        .long("skip-upload")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("dry_run")
+                        .help("Only print what would change in the output directory, like diff")
+                        .long("dry-run")
+                        .action(ArgAction::SetTrue),
+                ),
         )
-        .arg(
-            Arg::new("output_dir")
-                .help("Output directory")
-                .default_value("./generated"),
+        .subcommand(
+            Command::new("diff")
+                .about("Shows what generate would change, exits with 3 if there are changes")
+                .args(spec_args()),
         )
-        .arg(
-            Arg::new("work_dir")
-                .help("Working directory")
-                .default_value("./workdir"),
+        .subcommand(
+            Command::new("validate")
+                .about("Checks the specification file without generating, exits with 2 on problems")
+                .arg(spec_args().remove(0)),
         )
-        .arg(
-            Arg::new("delete_remote_instances")
-                .help("Comma-separated remote instance names to delete from fetched manifest")
-                .long("delete-remote")
-                .num_args(1..)
-                .use_value_delimiter(true)
-                .value_delimiter(',')
-                .value_name("NAME"),
+        .subcommand(
+            Command::new("list")
+                .about("Lists versions in the output directory")
+                .arg(output_dir_arg()),
+        )
+        .subcommand(
+            Command::new("clean")
+                .about("Removes files not referenced by the version manifest and abandoned installer work dirs")
+                .arg(output_dir_arg())
+                .arg(work_dir_arg())
+                .arg(
+                    Arg::new("dry_run")
+                        .help("Only print the files that would be removed")
+                        .long("dry-run")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("serve")
+                .about("Serves the output directory over HTTP for testing")
+                .arg(output_dir_arg())
+                .arg(
+                    Arg::new("port")
+                        .help("Port to listen on")
+                        .short('p')
+                        .long("port")
+                        .value_parser(clap::value_parser!(u16))
+                        .default_value("8000"),
+                )
+                .arg(
+                    Arg::new("no_rewrite")
+                        .help("Don't rewrite download_server_base to the local address")
+                        .long("no-rewrite")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("verify-remote")
+                .about("Spot-checks the files served from the public url against checksums.json, exits with 3 on drift")
+                .arg(output_dir_arg())
+                .arg(
+                    Arg::new("url")
+                        .help("Public url of the output directory, usually download_server_base")
+                        .long("url")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("count")
+                        .help("Number of random files to check")
+                        .short('n')
+                        .long("count")
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("20"),
+                ),
         )
         .get_matches();
 
-    let spec_file = matches.get_one::<PathBuf>("spec_file").unwrap();
-    let output_dir = matches.get_one::<String>("output_dir").unwrap();
-    let output_dir = PathBuf::from(output_dir);
-    let work_dir = matches.get_one::<String>("work_dir").unwrap();
-    let work_dir = PathBuf::from(work_dir);
-
-    let spec_file_path = spec_file.clone();
-    let output_dir_path = output_dir.clone();
-    let work_dir_path = work_dir.clone();
-
-    setup_logger(&get_logs_path(&work_dir));
-
     let rt = Runtime::new().unwrap();
-    let spec = rt.block_on(Spec::from_file(&spec_file_path))?;
-    let delete_remote_set: Option<HashSet<String>> = matches
-        .get_many::<String>("delete_remote_instances")
-        .map(|vals| vals.map(|s| s.to_string()).collect());
-
-    rt.block_on(spec.generate(&output_dir_path, &work_dir_path, delete_remote_set.as_ref()))
+    let result = match matches.subcommand() {
+        Some(("generate", matches)) => rt.block_on(run_spec_command(matches, false)),
+        Some(("diff", matches)) => rt.block_on(run_spec_command(matches, true)),
+        Some(("validate", matches)) => rt.block_on(run_validate(matches)),
+        Some(("list", matches)) => rt.block_on(run_list(matches)),
+        Some(("clean", matches)) => rt.block_on(run_clean(matches)),
+        Some(("serve", matches)) => rt.block_on(run_serve(matches)),
+        Some(("verify-remote", matches)) => rt.block_on(run_verify_remote(matches)),
+        _ => unreachable!("subcommand is required"),
+    };
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("Error: {e:?}");
+            ExitCode::from(EXIT_ERROR)
+        }
+    }
 }
@@ -0,0 +1,186 @@
+use std::fmt::Write as _;
+use std::path::Path;
+use std::time::Duration;
+
+use shared::files::SyncMappingStats;
+
+enum MetricType {
+    Gauge,
+    Counter,
+}
+
+impl MetricType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            MetricType::Gauge => "gauge",
+            MetricType::Counter => "counter",
+        }
+    }
+}
+
+// label values may contain anything the spec allows in instance names
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+// (version label, value) pairs, None for a metric without labels
+fn render_metric(
+    out: &mut String,
+    name: &str,
+    help: &str,
+    metric_type: MetricType,
+    samples: &[(Option<&str>, String)],
+) {
+    writeln!(out, "# HELP {name} {help}").unwrap();
+    writeln!(out, "# TYPE {name} {}", metric_type.as_str()).unwrap();
+    for (version, value) in samples {
+        match version {
+            Some(version) => writeln!(
+                out,
+                "{name}{{version=\"{}\"}} {value}",
+                escape_label_value(version)
+            ),
+            None => writeln!(out, "{name} {value}"),
+        }
+        .unwrap();
+    }
+}
+
+// collected during generate, written in the Prometheus text exposition format
+#[derive(Default)]
+pub struct Metrics {
+    version_generate_seconds: Vec<(String, f64)>,
+    output_bytes: Vec<(String, u64)>,
+    sync_stats: Option<SyncMappingStats>,
+}
+
+impl Metrics {
+    pub fn add_version_duration(&mut self, version: &str, duration: Duration) {
+        self.version_generate_seconds
+            .push((version.to_string(), duration.as_secs_f64()));
+    }
+
+    pub fn add_output_bytes(&mut self, version: &str, bytes: u64) {
+        self.output_bytes.push((version.to_string(), bytes));
+    }
+
+    pub fn set_sync_stats(&mut self, stats: SyncMappingStats) {
+        self.sync_stats = Some(stats);
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let version_generate_seconds: Vec<_> = self
+            .version_generate_seconds
+            .iter()
+            .map(|(version, seconds)| (Some(version.as_str()), format!("{seconds:.3}")))
+            .collect();
+        render_metric(
+            &mut out,
+            "builder_version_generate_seconds",
+            "Time spent generating a version",
+            MetricType::Gauge,
+            &version_generate_seconds,
+        );
+        let output_bytes: Vec<_> = self
+            .output_bytes
+            .iter()
+            .map(|(version, bytes)| (Some(version.as_str()), bytes.to_string()))
+            .collect();
+        render_metric(
+            &mut out,
+            "builder_output_bytes",
+            "Size of the files of a version in the output directory",
+            MetricType::Gauge,
+            &output_bytes,
+        );
+
+        let stats = self.sync_stats.unwrap_or_default();
+        for (name, help, value) in [
+            (
+                "builder_files_copied_total",
+                "Files copied to the output directory",
+                stats.copied_files as u64,
+            ),
+            (
+                "builder_bytes_copied_total",
+                "Bytes copied to the output directory",
+                stats.copied_bytes,
+            ),
+            (
+                "builder_files_deleted_total",
+                "Files deleted from the output directory",
+                stats.deleted_files as u64,
+            ),
+            (
+                "builder_cache_hits_total",
+                "Files that were already up to date in the output directory",
+                (stats.total_files - stats.copied_files) as u64,
+            ),
+        ] {
+            render_metric(
+                &mut out,
+                name,
+                help,
+                MetricType::Counter,
+                &[(None, value.to_string())],
+            );
+        }
+        out
+    }
+
+    pub async fn write(&self, path: &Path) -> anyhow::Result<()> {
+        tokio::fs::write(path, self.render()).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_metrics() {
+        let mut metrics = Metrics::default();
+        metrics.add_version_duration("main", Duration::from_millis(12345));
+        metrics.add_version_duration("\"test\" \\ 2", Duration::from_millis(500));
+        metrics.add_output_bytes("main", 1048576);
+        metrics.set_sync_stats(SyncMappingStats {
+            total_files: 10,
+            copied_files: 3,
+            copied_bytes: 4096,
+            deleted_files: 1,
+        });
+
+        assert_eq!(
+            metrics.render(),
+            "# HELP builder_version_generate_seconds Time spent generating a version\n\
+             # TYPE builder_version_generate_seconds gauge\n\
+             builder_version_generate_seconds{version=\"main\"} 12.345\n\
+             builder_version_generate_seconds{version=\"\\\"test\\\" \\\\ 2\"} 0.500\n\
+             # HELP builder_output_bytes Size of the files of a version in the output directory\n\
+             # TYPE builder_output_bytes gauge\n\
+             builder_output_bytes{version=\"main\"} 1048576\n\
+             # HELP builder_files_copied_total Files copied to the output directory\n\
+             # TYPE builder_files_copied_total counter\n\
+             builder_files_copied_total 3\n\
+             # HELP builder_bytes_copied_total Bytes copied to the output directory\n\
+             # TYPE builder_bytes_copied_total counter\n\
+             builder_bytes_copied_total 4096\n\
+             # HELP builder_files_deleted_total Files deleted from the output directory\n\
+             # TYPE builder_files_deleted_total counter\n\
+             builder_files_deleted_total 1\n\
+             # HELP builder_cache_hits_total Files that were already up to date in the output directory\n\
+             # TYPE builder_cache_hits_total counter\n\
+             builder_cache_hits_total 7\n"
+        );
+
+        // every metric is declared even without samples
+        let empty = Metrics::default().render();
+        assert!(empty.contains("# TYPE builder_output_bytes gauge\n# HELP"));
+        assert!(empty.ends_with("builder_cache_hits_total 0\n"));
+    }
+}
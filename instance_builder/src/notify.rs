@@ -0,0 +1,191 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use log::{info, warn};
+use serde::Serialize;
+use shared::version::content_hash::get_short_content_hash;
+
+const WEBHOOK_ATTEMPTS: u32 = 3;
+const WEBHOOK_RETRY_DELAY: Duration = Duration::from_secs(2);
+const DEFAULT_TEMPLATE: &str = "Updated instances: {versions}";
+
+#[derive(Serialize, Clone, Debug)]
+pub struct GeneratedVersion {
+    pub name: String,
+    pub content_hash: String,
+    // unix timestamp in seconds
+    pub generated_at: u64,
+}
+
+impl GeneratedVersion {
+    pub fn new(name: String, content_hash: String) -> Self {
+        let generated_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        Self {
+            name,
+            content_hash,
+            generated_at,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct WebhookPayload<'a> {
+    // the field discord displays
+    content: String,
+    versions: &'a [GeneratedVersion],
+}
+
+// {versions} is replaced with all names, a template with {name} is rendered once per version
+pub fn render_template(template: &str, versions: &[GeneratedVersion]) -> String {
+    let names = versions
+        .iter()
+        .map(|version| version.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let template = template.replace("{versions}", &names);
+    if !template.contains("{name}") {
+        return template;
+    }
+    versions
+        .iter()
+        .map(|version| {
+            template.replace("{name}", &version.name).replace(
+                "{content_hash}",
+                get_short_content_hash(&version.content_hash),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+async fn post_with_retries<T: Serialize>(
+    url: &str,
+    payload: &T,
+    retry_delay: Duration,
+) -> anyhow::Result<()> {
+    let client = reqwest::Client::new();
+    let mut attempt = 1;
+    loop {
+        let result = client
+            .post(url)
+            .json(payload)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        match result {
+            Ok(_) => return Ok(()),
+            Err(e) if attempt < WEBHOOK_ATTEMPTS => {
+                warn!("Webhook attempt {attempt}/{WEBHOOK_ATTEMPTS} failed: {e}");
+                attempt += 1;
+                tokio::time::sleep(retry_delay).await;
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+async fn send_webhook_with_delay(
+    url: &str,
+    template: Option<&str>,
+    versions: &[GeneratedVersion],
+    retry_delay: Duration,
+) {
+    let payload = WebhookPayload {
+        content: render_template(template.unwrap_or(DEFAULT_TEMPLATE), versions),
+        versions,
+    };
+    match post_with_retries(url, &payload, retry_delay).await {
+        Ok(()) => info!("Sent webhook notification"),
+        Err(e) => warn!("Failed to send webhook notification: {e}"),
+    }
+}
+
+// never fails, delivery errors are only logged
+pub async fn send_webhook(url: &str, template: Option<&str>, versions: &[GeneratedVersion]) {
+    send_webhook_with_delay(url, template, versions, WEBHOOK_RETRY_DELAY).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Json, Router, extract::State, http::StatusCode, routing::post};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Captured {
+        payloads: Arc<Mutex<Vec<serde_json::Value>>>,
+        failures_left: Arc<Mutex<u32>>,
+    }
+
+    async fn capture(
+        State(captured): State<Captured>,
+        Json(payload): Json<serde_json::Value>,
+    ) -> StatusCode {
+        captured.payloads.lock().unwrap().push(payload);
+        let mut failures_left = captured.failures_left.lock().unwrap();
+        if *failures_left > 0 {
+            *failures_left -= 1;
+            StatusCode::INTERNAL_SERVER_ERROR
+        } else {
+            StatusCode::NO_CONTENT
+        }
+    }
+
+    async fn start_server(captured: Captured) -> String {
+        let app = Router::new()
+            .route("/hook", post(capture))
+            .with_state(captured);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{addr}/hook")
+    }
+
+    fn versions() -> Vec<GeneratedVersion> {
+        vec![
+            GeneratedVersion::new("smp".to_string(), "0123456789abcdef".to_string()),
+            GeneratedVersion::new("creative".to_string(), "fedcba9876543210".to_string()),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_webhook_payload_and_retries() {
+        let captured = Captured::default();
+        *captured.failures_left.lock().unwrap() = 2;
+        let url = start_server(captured.clone()).await;
+
+        send_webhook_with_delay(
+            &url,
+            Some("{name} ({content_hash}) is out"),
+            &versions(),
+            Duration::ZERO,
+        )
+        .await;
+
+        let payloads = captured.payloads.lock().unwrap();
+        assert_eq!(payloads.len(), 3);
+        let payload = &payloads[2];
+        assert_eq!(
+            payload["content"],
+            "smp (01234567) is out\ncreative (fedcba98) is out"
+        );
+        assert_eq!(payload["versions"][0]["name"], "smp");
+        assert_eq!(payload["versions"][1]["content_hash"], "fedcba9876543210");
+        assert!(payload["versions"][0]["generated_at"].as_u64().unwrap() > 0);
+    }
+
+    #[tokio::test]
+    async fn test_webhook_gives_up_after_attempts() {
+        let captured = Captured::default();
+        *captured.failures_left.lock().unwrap() = 5;
+        let url = start_server(captured.clone()).await;
+
+        send_webhook_with_delay(&url, None, &versions(), Duration::ZERO).await;
+
+        let payloads = captured.payloads.lock().unwrap();
+        assert_eq!(payloads.len(), WEBHOOK_ATTEMPTS as usize);
+        assert_eq!(payloads[0]["content"], "Updated instances: smp, creative");
+    }
+}
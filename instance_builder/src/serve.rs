@@ -0,0 +1,169 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use axum::{
+    Router,
+    extract::{Request, State},
+    http::header::CONTENT_TYPE,
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+};
+use log::{info, warn};
+use sha1::{Digest, Sha1};
+use shared::{
+    compression,
+    files::get_files_in_dir,
+    paths::{get_rel_metadata_path, get_rel_versions_dir},
+    signing::get_signature_path,
+    utils::url_from_rel_path,
+    version::version_manifest::VersionManifest,
+};
+use tower::ServiceExt as _;
+use tower_http::services::ServeDir;
+
+use crate::spec::get_manifest_path;
+
+#[derive(Clone)]
+struct ServeState {
+    output_dir: PathBuf,
+    // url path -> content, served instead of the files on disk
+    overrides: Arc<HashMap<String, Vec<u8>>>,
+}
+
+fn get_url_path(output_dir: &Path, path: &Path) -> anyhow::Result<String> {
+    url_from_rel_path(path.strip_prefix(output_dir)?, "")
+}
+
+fn get_sha1(data: &[u8]) -> String {
+    format!("{:x}", Sha1::digest(data))
+}
+
+// versions urls are built as {download_server_base}/versions/{id}/{id}.json
+fn get_download_server_base(manifest: &VersionManifest) -> Option<String> {
+    let version = manifest.versions.first()?;
+    let rel_path = get_rel_versions_dir().join(get_rel_metadata_path(&version.id));
+    let suffix = url_from_rel_path(&rel_path, "").ok()?;
+    version
+        .url
+        .strip_suffix(&suffix)
+        .map(|base| base.to_string())
+}
+
+async fn get_rewritten_files(
+    output_dir: &Path,
+    from: &str,
+    to: &str,
+) -> anyhow::Result<HashMap<String, Vec<u8>>> {
+    let manifest_path = get_manifest_path(output_dir);
+    let mut overrides = HashMap::new();
+    for path in get_files_in_dir(output_dir)? {
+        if path == manifest_path || path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        let content = tokio::fs::read_to_string(&path).await?;
+        if content.contains(from) {
+            overrides.insert(
+                get_url_path(output_dir, &path)?,
+                content.replace(from, to).into_bytes(),
+            );
+        }
+    }
+
+    // rewritten files have different hashes, so the manifest has to point to them
+    let mut manifest = VersionManifest::read_local(&manifest_path).await?;
+    let rewrite = |url: &mut String, sha1: &mut String| {
+        *url = url.replace(from, to);
+        if let Some(data) = url
+            .strip_prefix(to)
+            .and_then(|url_path| overrides.get(url_path))
+        {
+            *sha1 = get_sha1(data);
+        }
+    };
+    for version in &mut manifest.versions {
+        rewrite(&mut version.url, &mut version.sha1);
+        for metadata_info in &mut version.inherits_from {
+            rewrite(&mut metadata_info.url, &mut metadata_info.sha1);
+        }
+        if let (Some(url), Some(sha1)) = (
+            &mut version.extra_metadata_url,
+            &mut version.extra_metadata_sha1,
+        ) {
+            rewrite(url, sha1);
+        }
+    }
+    let manifest_data = serde_json::to_vec(&manifest)?;
+    // the launcher prefers the compressed manifest, it must not point to the original urls
+    let zstd_manifest_path = compression::get_zstd_path(&manifest_path);
+    if zstd_manifest_path.exists() {
+        overrides.insert(
+            get_url_path(output_dir, &zstd_manifest_path)?,
+            compression::compress_zstd(&manifest_data)?,
+        );
+    }
+    overrides.insert(get_url_path(output_dir, &manifest_path)?, manifest_data);
+
+    Ok(overrides)
+}
+
+async fn handle_request(State(state): State<ServeState>, request: Request) -> Response {
+    if let Some(data) = state.overrides.get(request.uri().path()) {
+        return ([(CONTENT_TYPE, "application/json")], data.clone()).into_response();
+    }
+    match ServeDir::new(&state.output_dir).oneshot(request).await {
+        Ok(response) => response.into_response(),
+        Err(e) => match e {},
+    }
+}
+
+async fn log_request(request: Request, next: Next) -> Response {
+    let method = request.method().clone();
+    let uri = request.uri().clone();
+    let response = next.run(request).await;
+    let status = response.status();
+    if status.is_client_error() || status.is_server_error() {
+        warn!("{method} {uri} {status}");
+    } else {
+        info!("{method} {uri} {status}");
+    }
+    response
+}
+
+pub async fn serve(output_dir: &Path, port: u16, rewrite_urls: bool) -> anyhow::Result<()> {
+    let local_base = format!("http://localhost:{port}");
+
+    let mut overrides = HashMap::new();
+    if rewrite_urls {
+        let manifest = VersionManifest::read_local(&get_manifest_path(output_dir)).await?;
+        match get_download_server_base(&manifest) {
+            Some(base) if base != local_base => {
+                info!("Rewriting {base} to {local_base}");
+                overrides = get_rewritten_files(output_dir, &base, &local_base).await?;
+                if get_signature_path(&get_manifest_path(output_dir)).exists() {
+                    warn!(
+                        "Rewritten files are served with the original signatures, which won't match"
+                    );
+                }
+            }
+            Some(_) => {}
+            None => warn!("Failed to detect download_server_base, serving files as is"),
+        }
+    }
+
+    let state = ServeState {
+        output_dir: output_dir.to_path_buf(),
+        overrides: Arc::new(overrides),
+    };
+    let app = Router::new()
+        .fallback(handle_request)
+        .layer(middleware::from_fn(log_request))
+        .with_state(state);
+
+    let listener = tokio::net::TcpListener::bind(("127.0.0.1", port)).await?;
+    info!("Serving {} on {local_base}", output_dir.display());
+    axum::serve(listener, app).await?;
+    Ok(())
+}
@@ -3,12 +3,19 @@ use serde::Deserialize;
 use std::{
     collections::{HashMap, HashSet},
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
 };
-use tokio::fs;
+use tokio::{fs, task::JoinSet};
 
 use shared::{
-    files::sync_mapping,
+    adaptive_download::DEFAULT_MAX_CONNECTIONS,
+    checksums::{CHECKSUMS_FILENAME, Checksums},
+    compression,
+    files::{plan_sync_mapping, sync_mapping},
     generate::{
         extra::{ExtraMetadataGenerator, IncludeConfig, IncludeRule},
         manifest::get_version_info,
@@ -20,20 +27,39 @@ use shared::{
         vanilla::VanillaGenerator,
     },
     paths::{
-        get_extra_metadata_path, get_metadata_path, get_minecraft_dir, get_versions_dir,
-        get_versions_extra_dir,
+        get_extra_metadata_path, get_metadata_path, get_minecraft_dir, get_rel_extra_metadata_path,
+        get_rel_versions_extra_dir, get_versions_dir, get_versions_extra_dir,
     },
-    utils::{VANILLA_MANIFEST_URL, get_vanilla_version_info},
+    progress::{ProgressEvent, ProgressFormat, emit_progress_event, terminal_progress_bar},
+    signing::{get_public_key, read_signing_key, sign_file},
+    utils::{VANILLA_MANIFEST_URL, get_vanilla_version_info, url_from_rel_path},
     version::{
-        asset_metadata::AssetsMetadata, extra_version_metadata::AuthBackend,
-        version_manifest::VersionManifest,
+        asset_metadata::AssetsMetadata,
+        content_hash::get_content_hash,
+        extra_version_metadata::{
+            AuthBackend, AuthlibInjector, DownloadMirrors, InstanceDescription, LibraryOverride,
+            WindowBranding,
+        },
+        version_manifest::{VersionInfo, VersionManifest},
     },
 };
 
 use crate::{
-    generate::{mapping::get_mapping, patch::replace_download_urls, sync::sync_version},
-    progress::TerminalProgressBar,
+    commands::{self, get_version_size},
+    generate::{
+        casing::check_path_casing,
+        include_source,
+        mapping::get_mapping,
+        patch::replace_download_urls,
+        sync::sync_version,
+        variant::{Variant, check_variant_names},
+    },
+    metrics::Metrics,
+    notify::{GeneratedVersion, send_webhook},
+    spec_file::read_spec,
+    upload::UploadTarget,
     utils::{exec_string_command, get_assets_dir, get_replaced_metadata_dir},
+    validate::validate_spec,
 };
 
 fn vanilla() -> String {
@@ -57,15 +83,43 @@ pub struct Instances {
 
     pub auth_backend: Option<AuthBackend>,
 
+    // the launcher's own authlib-injector is used if not set
+    pub authlib_injector: Option<AuthlibInjector>,
+
     pub recommended_xmx: Option<String>,
 
+    #[serde(default)]
+    pub recommended_jvm_args: Vec<String>,
+
+    pub min_launcher_version: Option<String>,
+
+    pub rollout_percent: Option<u8>,
+
+    pub server_address: Option<String>,
+
+    #[serde(flatten)]
+    pub description: InstanceDescription,
+
+    #[serde(default)]
+    pub overrides: Vec<LibraryOverride>,
+
+    #[serde(flatten)]
+    pub branding: WindowBranding,
+
     pub exec_before: Option<String>,
     pub exec_after: Option<String>,
+
+    // generated as separate versions named `<name> (<variant>)`
+    #[serde(default)]
+    pub variants: Vec<Variant>,
 }
 
 #[derive(Deserialize)]
 pub struct Spec {
     pub download_server_base: String,
+    // other servers with the same files, the launcher picks the fastest one
+    #[serde(default)]
+    pub download_server_mirrors: Vec<String>,
     pub resources_url_base: Option<String>,
 
     #[serde(default)]
@@ -73,30 +127,436 @@ pub struct Spec {
 
     pub version_manifest_url: Option<String>,
 
+    pub signing_key_file: Option<PathBuf>,
+
+    #[serde(default)]
+    pub compress_manifest: bool,
+
+    // writes checksums.json for checking the download server with verify-remote
+    #[serde(default)]
+    pub publish_checksums: bool,
+
     pub instances: Vec<Instances>,
     pub exec_before_all: Option<String>,
     pub exec_after_all: Option<String>,
+
+    pub notify_webhook_url: Option<String>,
+    pub notify_template: Option<String>,
+
+    // pushes the output dir after generating
+    pub upload: Option<UploadTarget>,
+
+    // versions generated concurrently, set from the command line
+    #[serde(skip, default = "default_jobs")]
+    pub jobs: usize,
+
+    // download connections of every version, set from the command line
+    #[serde(skip, default = "default_max_connections")]
+    pub max_connections: usize,
+}
+
+fn default_jobs() -> usize {
+    1
+}
+
+fn default_max_connections() -> usize {
+    DEFAULT_MAX_CONNECTIONS
 }
 
 pub fn get_manifest_path(data_dir: &Path) -> PathBuf {
     data_dir.join("version_manifest.json")
 }
 
+async fn run_hook(command: &str, run_hooks: bool) -> anyhow::Result<()> {
+    if run_hooks {
+        exec_string_command(command).await
+    } else {
+        info!("Skipping command: {command}");
+        Ok(())
+    }
+}
+
+async fn sign_output(
+    output_dir: &Path,
+    version_manifest: &VersionManifest,
+    signing_key_file: &Path,
+) -> anyhow::Result<()> {
+    let signing_key = read_signing_key(&fs::read_to_string(signing_key_file).await?)?;
+    info!(
+        "Signing manifest with public key {}",
+        get_public_key(&signing_key)
+    );
+
+    sign_file(&get_manifest_path(output_dir), &signing_key).await?;
+    for version_info in &version_manifest.versions {
+        let extra_metadata_path = output_dir
+            .join(get_rel_versions_extra_dir())
+            .join(get_rel_extra_metadata_path(&version_info.get_name()));
+        if extra_metadata_path.exists() {
+            sign_file(&extra_metadata_path, &signing_key).await?;
+        }
+    }
+
+    Ok(())
+}
+
+// shared by the versions generated concurrently
+struct BuildContext {
+    output_dir: PathBuf,
+    work_dir: PathBuf,
+    vanilla_manifest: VersionManifest,
+    download_server_base: String,
+    download_mirrors: Option<DownloadMirrors>,
+    resources_url_base: Option<String>,
+    replace_download_urls: bool,
+    checksums_url: Option<String>,
+    progress_format: ProgressFormat,
+    max_connections: usize,
+    run_hooks: bool,
+    versions_count: u64,
+    finished_count: AtomicU64,
+    // metadata ids shared by several versions are synced once
+    synced_metadata: Mutex<HashMap<String, Arc<tokio::sync::Mutex<bool>>>>,
+    mapping: Mutex<HashMap<PathBuf, PathBuf>>,
+}
+
+#[derive(Default)]
+struct VersionOutput {
+    // the base version first, then its variants
+    version_infos: Vec<VersionInfo>,
+    generated_versions: Vec<GeneratedVersion>,
+    generate_durations: Vec<(String, Duration)>,
+}
+
+// None if the loader is not supported
+async fn generate_version(
+    ctx: &BuildContext,
+    index: usize,
+    version: Instances,
+) -> anyhow::Result<Option<VersionOutput>> {
+    let output_dir = ctx.output_dir.as_path();
+    let work_dir = ctx.work_dir.as_path();
+    let mut output = VersionOutput::default();
+
+    let started_at = Instant::now();
+    emit_progress_event(
+        ctx.progress_format,
+        ProgressEvent::new(
+            "version_started",
+            index as u64,
+            ctx.versions_count,
+            &version.name,
+        ),
+    );
+
+    if let Some(command) = &version.exec_before {
+        run_hook(command, ctx.run_hooks).await?;
+    }
+
+    let vanilla_version_info =
+        get_vanilla_version_info(&ctx.vanilla_manifest, &version.minecraft_version)?;
+
+    let progress_bar = terminal_progress_bar(ctx.progress_format, "generate", &version.name);
+
+    let generator: Box<dyn VersionGenerator> = match version.loader_name.as_str() {
+        "vanilla" => {
+            if version.loader_version.is_some() {
+                warn!("Ignoring loader version for vanilla version");
+            }
+
+            Box::new(VanillaGenerator::new(
+                version.name.clone(),
+                vanilla_version_info,
+            ))
+        }
+
+        "fabric" => Box::new(FabricGenerator::new(
+            version.name.clone(),
+            vanilla_version_info,
+            version.loader_version.clone(),
+        )),
+
+        "forge" => Box::new(ForgeGenerator::new(
+            version.name.clone(),
+            vanilla_version_info,
+            Loader::Forge,
+            version.loader_version.clone(),
+            progress_bar.clone(),
+        )),
+
+        "neoforge" => Box::new(ForgeGenerator::new(
+            version.name.clone(),
+            vanilla_version_info,
+            Loader::Neoforge,
+            version.loader_version.clone(),
+            progress_bar.clone(),
+        )),
+
+        _ => {
+            error!("Unsupported loader name: {}", version.loader_name);
+            return Ok(None);
+        }
+    };
+
+    let mut workdir_paths_to_copy = vec![];
+
+    let mut result = generator.generate(work_dir).await?;
+    let mut replaced_metadata = HashMap::new();
+    if ctx.replace_download_urls {
+        let versions_dir = get_versions_dir(output_dir);
+        let replaced_metadata_dir = get_replaced_metadata_dir(work_dir);
+
+        for metadata in result.metadata.iter_mut() {
+            // versions sharing the metadata wait for the first one to sync it
+            let sync_lock = ctx
+                .synced_metadata
+                .lock()
+                .unwrap()
+                .entry(metadata.id.clone())
+                .or_default()
+                .clone();
+            let mut synced = sync_lock.lock().await;
+            if *synced {
+                info!("Skipping {}, it is already synced", &metadata.id);
+                continue;
+            }
+            info!("Syncing {}", &metadata.id);
+
+            let sync_result = sync_version(
+                metadata,
+                work_dir,
+                ctx.progress_format,
+                ctx.max_connections,
+                &version.name,
+            )
+            .await?;
+            if let Some(asset_index) = &metadata.asset_index {
+                let assets_dir = get_assets_dir(work_dir);
+                let asset_index_path =
+                    AssetsMetadata::get_path(&assets_dir, &asset_index.id).await?;
+                workdir_paths_to_copy.push(asset_index_path);
+            }
+            workdir_paths_to_copy.extend(sync_result.paths_to_copy);
+
+            replace_download_urls(metadata, &ctx.download_server_base, work_dir).await?;
+            metadata.save(&replaced_metadata_dir).await?;
+
+            *synced = true;
+
+            let replaced_metadata_path = get_metadata_path(&replaced_metadata_dir, &metadata.id);
+            replaced_metadata.insert(metadata.id.clone(), replaced_metadata_path.clone());
+            ctx.mapping.lock().unwrap().insert(
+                get_metadata_path(&versions_dir, &metadata.id),
+                replaced_metadata_path,
+            );
+        }
+    } else {
+        let versions_dir = get_versions_dir(work_dir);
+        for metadata in result.metadata.iter_mut() {
+            workdir_paths_to_copy.push(get_metadata_path(&versions_dir, &metadata.id));
+        }
+    }
+    workdir_paths_to_copy.extend(result.extra_libs_paths.clone());
+
+    let resources_url_base = if ctx.replace_download_urls {
+        ctx.resources_url_base.clone()
+    } else {
+        None
+    };
+
+    let include_from = match version.include_from {
+        Some(include_from) if include_source::is_remote(&include_from) => Some(
+            include_source::fetch_include_source(&include_from, &version.name, work_dir)
+                .await?
+                .display()
+                .to_string(),
+        ),
+        include_from => include_from,
+    };
+    if include_from.is_none()
+        && (!version.include.is_empty() || version.variants.iter().any(|v| !v.include.is_empty()))
+    {
+        warn!("Ignoring include, include_from is not set");
+    }
+    let get_include_config = |include: Vec<IncludeRule>| {
+        include_from.as_ref().map(|include_from| IncludeConfig {
+            include,
+            include_from: include_from.clone(),
+            download_server_base: ctx.download_server_base.clone(),
+            resources_url_base: resources_url_base.clone(),
+        })
+    };
+
+    let extra_generator = ExtraMetadataGenerator::new(
+        version.name.clone(),
+        get_include_config(version.include),
+        result.extra_libs_paths.clone(),
+        version.auth_backend.clone(),
+        version.recommended_xmx.clone(),
+        version.min_launcher_version.clone(),
+        version.description.clone(),
+    )
+    .with_overrides(version.overrides.clone())
+    .with_recommended_jvm_args(version.recommended_jvm_args.clone())
+    .with_branding(version.branding.clone())
+    .with_checksums_url(ctx.checksums_url.clone())
+    .with_authlib_injector(version.authlib_injector.clone())
+    .with_download_mirrors(ctx.download_mirrors.clone());
+    let extra_generator_result = extra_generator.generate(work_dir).await?;
+    check_path_casing(
+        &version.name,
+        &extra_generator_result.extra_metadata.include,
+    )?;
+    let metadata_ids: Vec<&str> = result.metadata.iter().map(|m| m.id.as_str()).collect();
+    let content_hash =
+        get_content_hash(&metadata_ids, Some(&extra_generator_result.extra_metadata));
+    info!("Content hash for {}: {}", &version.name, &content_hash);
+    output
+        .generated_versions
+        .push(GeneratedVersion::new(version.name.clone(), content_hash));
+    ctx.mapping
+        .lock()
+        .unwrap()
+        .extend(extra_generator_result.include_mapping.into_iter().map(
+            |(include_entry, source_path)| {
+                let minecraft_dir = get_minecraft_dir(output_dir, &version.name);
+                (minecraft_dir.join(include_entry), source_path)
+            },
+        ));
+
+    let versions_extra_dir = get_versions_extra_dir(work_dir);
+    workdir_paths_to_copy.push(get_extra_metadata_path(&versions_extra_dir, &version.name));
+
+    info!("Getting version info for {}", &version.name);
+    let mut version_info = get_version_info(
+        work_dir,
+        &result.metadata,
+        &version.name,
+        Some(ctx.download_server_base.as_str()),
+        &replaced_metadata,
+    )
+    .await?;
+    version_info.rollout_percent = version.rollout_percent;
+    version_info.server_address = version.server_address.clone();
+
+    output.version_infos.push(version_info);
+
+    // same loader metadata, the base files keep their urls so switching is cheap
+    for variant in version.variants {
+        let variant_started_at = Instant::now();
+        let variant_name = variant.get_version_name(&version.name);
+        let base_include = &extra_generator_result.extra_metadata.include;
+        variant.check_include_paths(&version.name, base_include)?;
+        let shared_include = variant.get_shared_include(base_include);
+        let variant_result = ExtraMetadataGenerator::new(
+            variant_name.clone(),
+            get_include_config(variant.include),
+            result.extra_libs_paths.clone(),
+            version.auth_backend.clone(),
+            version.recommended_xmx.clone(),
+            version.min_launcher_version.clone(),
+            version.description.clone(),
+        )
+        .with_overrides(version.overrides.clone())
+        .with_recommended_jvm_args(version.recommended_jvm_args.clone())
+        .with_branding(version.branding.clone())
+        .with_shared_include(shared_include)
+        .with_checksums_url(ctx.checksums_url.clone())
+        .with_authlib_injector(version.authlib_injector.clone())
+        .with_download_mirrors(ctx.download_mirrors.clone())
+        .generate(work_dir)
+        .await?;
+        check_path_casing(&variant_name, &variant_result.extra_metadata.include)?;
+        let content_hash = get_content_hash(&metadata_ids, Some(&variant_result.extra_metadata));
+        info!("Content hash for {}: {}", &variant_name, &content_hash);
+        output
+            .generated_versions
+            .push(GeneratedVersion::new(variant_name.clone(), content_hash));
+        // the variant rules don't overlap the base ones, so they get their own directory
+        let variant_minecraft_dir = get_minecraft_dir(output_dir, &variant_name);
+        ctx.mapping
+            .lock()
+            .unwrap()
+            .extend(variant_result.include_mapping.into_iter().map(
+                |(include_entry, source_path)| {
+                    (variant_minecraft_dir.join(include_entry), source_path)
+                },
+            ));
+        workdir_paths_to_copy.push(get_extra_metadata_path(&versions_extra_dir, &variant_name));
+
+        let mut variant_info = get_version_info(
+            work_dir,
+            &result.metadata,
+            &variant_name,
+            Some(ctx.download_server_base.as_str()),
+            &replaced_metadata,
+        )
+        .await?;
+        variant_info.rollout_percent = version.rollout_percent;
+        variant_info.server_address = version.server_address.clone();
+        output.version_infos.push(variant_info);
+        output
+            .generate_durations
+            .push((variant_name, variant_started_at.elapsed()));
+    }
+
+    let workdir_mapping = get_mapping(output_dir, work_dir, &workdir_paths_to_copy)?;
+    ctx.mapping.lock().unwrap().extend(workdir_mapping);
+
+    if let Some(command) = &version.exec_after {
+        run_hook(command, ctx.run_hooks).await?;
+    }
+
+    output
+        .generate_durations
+        .push((version.name.clone(), started_at.elapsed()));
+    info!("Finished generating version {}", &version.name);
+    emit_progress_event(
+        ctx.progress_format,
+        ProgressEvent::new(
+            "version_finished",
+            ctx.finished_count.fetch_add(1, Ordering::SeqCst) + 1,
+            ctx.versions_count,
+            &version.name,
+        ),
+    );
+    Ok(Some(output))
+}
+
 impl Spec {
     pub async fn from_file(path: &Path) -> anyhow::Result<Spec> {
-        let content = fs::read_to_string(path).await?;
-        let spec = serde_json::from_str(&content)?;
-        Ok(spec)
+        let path = path.to_path_buf();
+        tokio::task::spawn_blocking(move || read_spec(&path)).await?
     }
 
-    pub async fn generate(
+    pub fn with_jobs(mut self, jobs: usize) -> Self {
+        self.jobs = jobs;
+        self
+    }
+
+    pub fn with_max_connections(mut self, max_connections: usize) -> Self {
+        self.max_connections = max_connections;
+        self
+    }
+
+    pub fn without_upload(mut self) -> Self {
+        self.upload = None;
+        self
+    }
+
+    // generates all versions in the work dir, without touching the output dir
+    async fn build(
         self,
         output_dir: &Path,
         work_dir: &Path,
         delete_remote_instances: Option<&HashSet<String>>,
-    ) -> anyhow::Result<()> {
+        progress_format: ProgressFormat,
+        run_hooks: bool,
+    ) -> anyhow::Result<BuildResult> {
+        validate_spec(&self)?;
+
         if let Some(command) = &self.exec_before_all {
-            exec_string_command(command).await?;
+            run_hook(command, run_hooks).await?;
         }
 
         info!("Fetching version manifest");
@@ -143,180 +603,327 @@ impl Spec {
         } else {
             VersionManifest { versions: vec![] }
         };
-        let mut synced_metadata = HashSet::new();
-        let mut mapping = HashMap::new();
+        check_variant_names(
+            self.instances
+                .iter()
+                .map(|version| (version.name.as_str(), version.variants.as_slice())),
+        )?;
+
+        let checksums_url = if self.publish_checksums {
+            Some(url_from_rel_path(
+                Path::new(CHECKSUMS_FILENAME),
+                &self.download_server_base,
+            )?)
+        } else {
+            None
+        };
 
-        for version in self.instances {
-            if let Some(command) = &version.exec_before {
-                exec_string_command(command).await?;
+        let download_mirrors =
+            (!self.download_server_mirrors.is_empty()).then(|| DownloadMirrors {
+                download_server_base: self.download_server_base.clone(),
+                mirrors: self.download_server_mirrors,
+            });
+
+        let versions_count = self.instances.len() as u64;
+        let ctx = Arc::new(BuildContext {
+            output_dir: output_dir.to_path_buf(),
+            work_dir: work_dir.to_path_buf(),
+            vanilla_manifest,
+            download_server_base: self.download_server_base,
+            download_mirrors,
+            resources_url_base: self.resources_url_base,
+            replace_download_urls: self.replace_download_urls,
+            checksums_url,
+            progress_format,
+            max_connections: self.max_connections,
+            run_hooks,
+            versions_count,
+            finished_count: AtomicU64::new(0),
+            synced_metadata: Mutex::new(HashMap::new()),
+            mapping: Mutex::new(HashMap::new()),
+        });
+
+        // results are kept by the spec order, so the manifest doesn't depend on timing
+        let mut outputs: Vec<Option<VersionOutput>> = (0..versions_count).map(|_| None).collect();
+        let mut tasks = JoinSet::new();
+        let mut instances = self.instances.into_iter().enumerate();
+        loop {
+            while tasks.len() < self.jobs
+                && let Some((index, version)) = instances.next()
+            {
+                let ctx = ctx.clone();
+                tasks.spawn(async move { (index, generate_version(&ctx, index, version).await) });
             }
-
-            let vanilla_version_info =
-                get_vanilla_version_info(&vanilla_manifest, &version.minecraft_version)?;
-
-            let progress_bar = Arc::new(TerminalProgressBar::new());
-
-            let generator: Box<dyn VersionGenerator> = match version.loader_name.as_str() {
-                "vanilla" => {
-                    if version.loader_version.is_some() {
-                        warn!("Ignoring loader version for vanilla version");
-                    }
-
-                    Box::new(VanillaGenerator::new(
-                        version.name.clone(),
-                        vanilla_version_info,
-                    ))
-                }
-
-                "fabric" => Box::new(FabricGenerator::new(
-                    version.name.clone(),
-                    vanilla_version_info,
-                    version.loader_version.clone(),
-                )),
-
-                "forge" => Box::new(ForgeGenerator::new(
-                    version.name.clone(),
-                    vanilla_version_info,
-                    Loader::Forge,
-                    version.loader_version.clone(),
-                    progress_bar.clone(),
-                )),
-
-                "neoforge" => Box::new(ForgeGenerator::new(
-                    version.name.clone(),
-                    vanilla_version_info,
-                    Loader::Neoforge,
-                    version.loader_version.clone(),
-                    progress_bar.clone(),
-                )),
-
-                _ => {
-                    error!("Unsupported loader name: {}", version.loader_name);
-                    continue;
-                }
+            let Some(joined) = tasks.join_next().await else {
+                break;
             };
+            let (index, output) = joined?;
+            outputs[index] = output?;
+        }
 
-            let mut workdir_paths_to_copy = vec![];
+        let mut generated_versions = vec![];
+        let mut generate_durations = vec![];
+        for output in outputs.into_iter().flatten() {
+            for version_info in output.version_infos {
+                version_manifest
+                    .versions
+                    .retain(|v| v.get_name() != version_info.get_name());
+                version_manifest.versions.push(version_info);
+            }
+            generated_versions.extend(output.generated_versions);
+            generate_durations.extend(output.generate_durations);
+        }
+        let mapping = std::mem::take(&mut *ctx.mapping.lock().unwrap());
+
+        Ok(BuildResult {
+            version_manifest,
+            mapping,
+            signing_key_file: self.signing_key_file,
+            compress_manifest: self.compress_manifest,
+            publish_checksums: self.publish_checksums,
+            exec_after_all: self.exec_after_all,
+            notify_webhook_url: self.notify_webhook_url,
+            notify_template: self.notify_template,
+            upload: self.upload,
+            generated_versions,
+            generate_durations,
+        })
+    }
 
-            let mut result = generator.generate(work_dir).await?;
-            let mut replaced_metadata = HashMap::new();
-            if self.replace_download_urls {
-                let versions_dir = get_versions_dir(output_dir);
-                let replaced_metadata_dir = get_replaced_metadata_dir(work_dir);
+    pub async fn generate(
+        self,
+        output_dir: &Path,
+        work_dir: &Path,
+        delete_remote_instances: Option<&HashSet<String>>,
+        progress_format: ProgressFormat,
+        metrics_file: Option<&Path>,
+        prune: bool,
+    ) -> anyhow::Result<()> {
+        let BuildResult {
+            version_manifest,
+            mapping,
+            signing_key_file,
+            compress_manifest,
+            publish_checksums,
+            exec_after_all,
+            notify_webhook_url,
+            notify_template,
+            upload,
+            generated_versions,
+            generate_durations,
+        } = self
+            .build(
+                output_dir,
+                work_dir,
+                delete_remote_instances,
+                progress_format,
+                true,
+            )
+            .await?;
 
-                for metadata in result.metadata.iter_mut() {
-                    if synced_metadata.contains(&metadata.id) {
-                        info!("Skipping {}, it is already synced", &metadata.id);
-                        continue;
-                    }
-                    info!("Syncing {}", &metadata.id);
-
-                    let sync_result = sync_version(metadata, work_dir).await?;
-                    if let Some(asset_index) = &metadata.asset_index {
-                        let assets_dir = get_assets_dir(work_dir);
-                        let asset_index_path =
-                            AssetsMetadata::get_path(&assets_dir, &asset_index.id).await?;
-                        workdir_paths_to_copy.push(asset_index_path);
-                    }
-                    workdir_paths_to_copy.extend(sync_result.paths_to_copy);
+        info!("Syncing {} entries", mapping.len());
+        debug!("Sync mapping (target->source): {mapping:?}");
+        let stats = sync_mapping(output_dir, &mapping).await?;
+        info!(
+            "Synced {} files (copied {}, deleted {})",
+            stats.total_files, stats.copied_files, stats.deleted_files
+        );
 
-                    replace_download_urls(metadata, &self.download_server_base, work_dir).await?;
-                    metadata.save(&replaced_metadata_dir).await?;
+        let manifest_path = get_manifest_path(output_dir);
+        version_manifest.save_to_file(&manifest_path).await?;
+        if compress_manifest {
+            // compressed from the saved file, so the signature of the plain manifest matches it
+            let data = compression::compress_zstd(&fs::read(&manifest_path).await?)?;
+            fs::write(compression::get_zstd_path(&manifest_path), data).await?;
+        }
 
-                    synced_metadata.insert(metadata.id.clone());
+        if let Some(signing_key_file) = &signing_key_file {
+            sign_output(output_dir, &version_manifest, signing_key_file).await?;
+        }
 
-                    let replaced_metadata_path =
-                        get_metadata_path(&replaced_metadata_dir, &metadata.id);
-                    replaced_metadata.insert(metadata.id.clone(), replaced_metadata_path.clone());
-                    mapping.insert(
-                        get_metadata_path(&versions_dir, &metadata.id),
-                        replaced_metadata_path,
-                    );
-                }
-            } else {
-                let versions_dir = get_versions_dir(work_dir);
-                for metadata in result.metadata.iter_mut() {
-                    workdir_paths_to_copy.push(get_metadata_path(&versions_dir, &metadata.id));
-                }
-            }
-            workdir_paths_to_copy.extend(result.extra_libs_paths.clone());
+        // files of deleted versions that no remaining version references
+        let unreachable = commands::prune(output_dir, &version_manifest, !prune).await?;
+        for path in &unreachable {
+            info!("Unreachable: {}", path.display());
+        }
+        if prune {
+            info!("Pruned {} unreachable file(s)", unreachable.len());
+        } else if !unreachable.is_empty() {
+            warn!(
+                "{} unreachable file(s) in the output dir, pass --prune to delete them",
+                unreachable.len()
+            );
+        }
 
-            let resources_url_base = if self.replace_download_urls {
-                self.resources_url_base.clone()
-            } else {
-                None
-            };
+        if publish_checksums {
+            let checksums = Checksums::from_dir(output_dir).await?;
+            checksums.save(&output_dir.join(CHECKSUMS_FILENAME)).await?;
+            info!("Wrote checksums of {} files", checksums.files.len());
+        }
 
-            let include_config = if let Some(include_from) = version.include_from {
-                Some(IncludeConfig {
-                    include: version.include,
-                    include_from,
-                    download_server_base: self.download_server_base.clone(),
-                    resources_url_base,
-                })
-            } else {
-                if !version.include.is_empty() {
-                    warn!("Ignoring include, include_from is not set");
+        if let Some(metrics_file) = metrics_file {
+            let mut metrics = Metrics::default();
+            metrics.set_sync_stats(stats);
+            for (name, duration) in &generate_durations {
+                metrics.add_version_duration(name, *duration);
+                if let Some(version_info) = version_manifest
+                    .versions
+                    .iter()
+                    .find(|version_info| &version_info.get_name() == name)
+                {
+                    metrics
+                        .add_output_bytes(name, get_version_size(output_dir, version_info).await?);
                 }
-                None
-            };
+            }
+            metrics.write(metrics_file).await?;
+            info!("Metrics written to {}", metrics_file.display());
+        }
 
-            let extra_generator = ExtraMetadataGenerator::new(
-                version.name.clone(),
-                include_config,
-                result.extra_libs_paths,
-                version.auth_backend,
-                version.recommended_xmx,
+        if let Some(UploadTarget::S3(target)) = &upload {
+            let stats = target.upload(output_dir, progress_format).await?;
+            info!(
+                "Uploaded {} file(s), deleted {} object(s)",
+                stats.uploaded_files, stats.deleted_files
             );
-            let extra_generator_result = extra_generator.generate(work_dir).await?;
-            mapping.extend(extra_generator_result.include_mapping.into_iter().map(
-                |(include_entry, source_path)| {
-                    let minecraft_dir = get_minecraft_dir(output_dir, &version.name);
-                    (minecraft_dir.join(include_entry), source_path)
-                },
-            ));
+        }
+
+        if let Some(command) = &exec_after_all {
+            exec_string_command(command).await?;
+        }
 
-            let versions_extra_dir = get_versions_extra_dir(work_dir);
-            workdir_paths_to_copy.push(get_extra_metadata_path(&versions_extra_dir, &version.name));
+        if let Some(url) = &notify_webhook_url {
+            send_webhook(url, notify_template.as_deref(), &generated_versions).await;
+        }
+        Ok(())
+    }
 
-            info!("Getting version info for {}", &version.name);
-            let version_info = get_version_info(
+    // what generate would change in the output dir, hooks are not run
+    pub async fn diff(
+        self,
+        output_dir: &Path,
+        work_dir: &Path,
+        delete_remote_instances: Option<&HashSet<String>>,
+        progress_format: ProgressFormat,
+    ) -> anyhow::Result<OutputDiff> {
+        let result = self
+            .build(
+                output_dir,
                 work_dir,
-                &result.metadata,
-                &version.name,
-                Some(self.download_server_base.as_str()),
-                &replaced_metadata,
+                delete_remote_instances,
+                progress_format,
+                false,
             )
             .await?;
 
-            version_manifest
+        let old_manifest = VersionManifest::read_local_safe(&get_manifest_path(output_dir)).await;
+        let mut diff = OutputDiff::default();
+        for version in &result.version_manifest.versions {
+            let name = version.get_name();
+            match old_manifest
                 .versions
-                .retain(|v| v.get_name() != version_info.get_name());
-            version_manifest.versions.push(version_info);
-
-            mapping.extend(get_mapping(output_dir, work_dir, &workdir_paths_to_copy)?);
-
-            if let Some(command) = &version.exec_after {
-                exec_string_command(command).await?;
+                .iter()
+                .find(|old| old.get_name() == name)
+            {
+                None => diff.added_versions.push(name),
+                Some(old) if old != version => diff.changed_versions.push(name),
+                Some(_) => {}
+            }
+        }
+        for old in &old_manifest.versions {
+            let name = old.get_name();
+            if !result
+                .version_manifest
+                .versions
+                .iter()
+                .any(|version| version.get_name() == name)
+            {
+                diff.removed_versions.push(name);
             }
+        }
 
-            info!("Finished generating version {}", &version.name);
+        let plan = plan_sync_mapping(output_dir, &result.mapping).await?;
+        let rel_path = |path: &Path| -> PathBuf {
+            path.strip_prefix(output_dir).unwrap_or(path).to_path_buf()
+        };
+        for copy in &plan.to_copy {
+            if copy.target_size.is_some() {
+                diff.files_to_change.push(rel_path(&copy.target));
+            } else {
+                diff.files_to_add.push(rel_path(&copy.target));
+            }
         }
+        diff.files_to_delete = plan.to_delete.iter().map(|path| rel_path(path)).collect();
+        diff.bytes_delta = plan.get_bytes_delta();
 
-        info!("Syncing {} entries", mapping.len());
-        debug!("Sync mapping (target->source): {mapping:?}");
-        let stats = sync_mapping(output_dir, &mapping).await?;
-        info!(
-            "Synced {} files (copied {}, deleted {})",
-            stats.total_files, stats.copied_files, stats.deleted_files
-        );
+        Ok(diff)
+    }
+}
 
-        let manifest_path = get_manifest_path(output_dir);
-        version_manifest.save_to_file(&manifest_path).await?;
+struct BuildResult {
+    version_manifest: VersionManifest,
+    mapping: HashMap<PathBuf, PathBuf>,
+    signing_key_file: Option<PathBuf>,
+    compress_manifest: bool,
+    publish_checksums: bool,
+    exec_after_all: Option<String>,
+    notify_webhook_url: Option<String>,
+    notify_template: Option<String>,
+    upload: Option<UploadTarget>,
+    generated_versions: Vec<GeneratedVersion>,
+    // versions generated in this run
+    generate_durations: Vec<(String, Duration)>,
+}
 
-        if let Some(command) = &self.exec_after_all {
-            exec_string_command(command).await?;
+#[derive(Default)]
+pub struct OutputDiff {
+    pub added_versions: Vec<String>,
+    pub changed_versions: Vec<String>,
+    pub removed_versions: Vec<String>,
+    pub files_to_add: Vec<PathBuf>,
+    pub files_to_change: Vec<PathBuf>,
+    pub files_to_delete: Vec<PathBuf>,
+    // how much the output dir grows, negative if it shrinks
+    pub bytes_delta: i64,
+}
+
+impl OutputDiff {
+    pub fn has_changes(&self) -> bool {
+        !self.added_versions.is_empty()
+            || !self.changed_versions.is_empty()
+            || !self.removed_versions.is_empty()
+            || !self.files_to_add.is_empty()
+            || !self.files_to_change.is_empty()
+            || !self.files_to_delete.is_empty()
+    }
+
+    pub fn print(&self) {
+        for name in &self.added_versions {
+            println!("+ version {name}");
         }
-        Ok(())
+        for name in &self.changed_versions {
+            println!("~ version {name}");
+        }
+        for name in &self.removed_versions {
+            println!("- version {name}");
+        }
+        for path in &self.files_to_add {
+            println!("+ {}", path.display());
+        }
+        for path in &self.files_to_change {
+            println!("~ {}", path.display());
+        }
+        for path in &self.files_to_delete {
+            println!("- {}", path.display());
+        }
+        println!(
+            "{} version(s) added, {} changed, {} removed; {} file(s) to add, {} to change, {} to delete, {:+.1} MiB",
+            self.added_versions.len(),
+            self.changed_versions.len(),
+            self.removed_versions.len(),
+            self.files_to_add.len(),
+            self.files_to_change.len(),
+            self.files_to_delete.len(),
+            self.bytes_delta as f64 / 1024.0 / 1024.0
+        );
     }
 }
@@ -0,0 +1,314 @@
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+use crate::spec::Spec;
+use crate::validate::SpecError;
+
+const EXTENDS_KEY: &str = "extends";
+const INSTANCES_KEY: &str = "instances";
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SpecFormat {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl SpecFormat {
+    // json for unknown extensions, like before other formats were supported
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("yaml" | "yml") => SpecFormat::Yaml,
+            Some("toml") => SpecFormat::Toml,
+            _ => SpecFormat::Json,
+        }
+    }
+}
+
+fn get_position(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset.min(content.len())];
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    (
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
+
+// the position is printed separately
+fn strip_position(message: &str) -> &str {
+    message.split(" at line ").next().unwrap_or(message)
+}
+
+pub fn deserialize<T: DeserializeOwned>(
+    content: &str,
+    format: SpecFormat,
+    file: &Path,
+) -> Result<T, SpecError> {
+    let parse_error = |field: String, position, message: &str| SpecError::Parse {
+        file: file.to_path_buf(),
+        position,
+        field,
+        message: message.to_string(),
+    };
+    match format {
+        SpecFormat::Json => {
+            let deserializer = &mut serde_json::Deserializer::from_str(content);
+            serde_path_to_error::deserialize(deserializer).map_err(|e| {
+                let field = e.path().to_string();
+                let inner = e.into_inner();
+                let position = (inner.line() > 0).then(|| (inner.line(), inner.column()));
+                parse_error(field, position, strip_position(&inner.to_string()))
+            })
+        }
+        SpecFormat::Yaml => {
+            let deserializer = serde_yaml_ng::Deserializer::from_str(content);
+            serde_path_to_error::deserialize(deserializer).map_err(|e| {
+                let field = e.path().to_string();
+                let inner = e.into_inner();
+                let position = inner
+                    .location()
+                    .map(|location| (location.line(), location.column()));
+                // the yaml message already starts with the field
+                let message = inner.to_string();
+                let message = message
+                    .strip_prefix(&format!("{field}: "))
+                    .unwrap_or(&message);
+                parse_error(field, position, strip_position(message))
+            })
+        }
+        SpecFormat::Toml => {
+            let deserializer = toml::Deserializer::parse(content).map_err(|e| {
+                let position = e.span().map(|span| get_position(content, span.start));
+                parse_error(".".to_string(), position, e.message())
+            })?;
+            serde_path_to_error::deserialize(deserializer).map_err(|e| {
+                let field = e.path().to_string();
+                let inner = e.into_inner();
+                let position = inner.span().map(|span| get_position(content, span.start));
+                parse_error(field, position, inner.message())
+            })
+        }
+    }
+}
+
+fn get_instance_name(instance: &Value) -> Option<&str> {
+    instance.get("name").and_then(Value::as_str)
+}
+
+// keys of the spec replace the base ones, instances are matched by name,
+// so an instance of the spec only has to list the fields that differ
+fn merge_spec_values(mut base: Map<String, Value>, spec: Map<String, Value>) -> Value {
+    for (key, value) in spec {
+        let (Some(Value::Array(base_instances)), Value::Array(instances)) =
+            (base.get_mut(&key).filter(|_| key == INSTANCES_KEY), &value)
+        else {
+            base.insert(key, value);
+            continue;
+        };
+        for instance in instances {
+            let base_instance = base_instances.iter_mut().find(|base_instance| {
+                get_instance_name(base_instance).is_some()
+                    && get_instance_name(base_instance) == get_instance_name(instance)
+            });
+            match (base_instance, instance) {
+                (Some(Value::Object(base_instance)), Value::Object(instance)) => {
+                    base_instance.extend(instance.clone());
+                }
+                _ => base_instances.push(instance.clone()),
+            }
+        }
+    }
+    Value::Object(base)
+}
+
+// the spec with the chain of its bases merged in
+fn read_spec_value(file: &Path, chain: &mut Vec<PathBuf>) -> anyhow::Result<Value> {
+    let content = std::fs::read_to_string(file)?;
+    let mut value: Value = deserialize(&content, SpecFormat::from_path(file), file)?;
+    let Some(Value::Object(spec)) = Some(&mut value) else {
+        return Ok(value);
+    };
+    let Some(extends) = spec.remove(EXTENDS_KEY) else {
+        return Ok(value);
+    };
+
+    let Value::String(base_file) = extends else {
+        return Err(SpecError::Parse {
+            file: file.to_path_buf(),
+            position: None,
+            field: EXTENDS_KEY.to_string(),
+            message: "expected a path to the base spec".to_string(),
+        }
+        .into());
+    };
+    // relative to the spec that extends it
+    let base_file = file.parent().unwrap_or(Path::new("")).join(base_file);
+    let canonical_file = base_file.canonicalize()?;
+    if chain.contains(&canonical_file) {
+        return Err(SpecError::ExtendsCycle(base_file).into());
+    }
+    chain.push(canonical_file);
+    match read_spec_value(&base_file, chain)? {
+        Value::Object(base) => Ok(merge_spec_values(base, spec.clone())),
+        _ => Ok(value),
+    }
+}
+
+pub fn read_spec(file: &Path) -> anyhow::Result<Spec> {
+    let content = std::fs::read_to_string(file)?;
+    let format = SpecFormat::from_path(file);
+    let value: Value = deserialize(&content, format, file)?;
+    if value.get(EXTENDS_KEY).is_none() {
+        // straight from the file, so the errors have positions
+        return Ok(deserialize(&content, format, file)?);
+    }
+
+    let mut chain = vec![file.canonicalize()?];
+    let merged = read_spec_value(file, &mut chain)?;
+    serde_path_to_error::deserialize(merged).map_err(|e| {
+        SpecError::Parse {
+            file: file.to_path_buf(),
+            position: None,
+            field: e.path().to_string(),
+            message: e.into_inner().to_string(),
+        }
+        .into()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use shared::generate::extra::IncludeRule;
+    use shared::version::extra_version_metadata::{AuthBackend, ElyByAuthBackend};
+
+    use super::*;
+
+    #[derive(Deserialize, Serialize, PartialEq, Debug)]
+    struct Snippet {
+        include: Vec<IncludeRule>,
+        auth_backend: AuthBackend,
+    }
+
+    fn serialize(snippet: &Snippet, format: SpecFormat) -> String {
+        match format {
+            SpecFormat::Json => serde_json::to_string_pretty(snippet).unwrap(),
+            SpecFormat::Yaml => serde_yaml_ng::to_string(snippet).unwrap(),
+            SpecFormat::Toml => toml::to_string(snippet).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_spec_formats() {
+        let file = Path::new("spec");
+        let expected = Snippet {
+            include: vec![IncludeRule {
+                path: "mods".to_string(),
+                overwrite: true,
+                delete_extra: false,
+                recursive: false,
+            }],
+            auth_backend: AuthBackend::ElyBy(ElyByAuthBackend {
+                client_id: "launcher".to_string(),
+                client_secret: "secret".to_string(),
+            }),
+        };
+        let snippets = [
+            (
+                SpecFormat::Json,
+                r#"{
+                    "include": [{"path": "mods", "delete_extra": false}],
+                    "auth_backend": {"type": "ely.by", "client_id": "launcher", "client_secret": "secret"}
+                }"#,
+            ),
+            (
+                SpecFormat::Yaml,
+                "# comments are allowed\n\
+                 include:\n  - path: mods\n    delete_extra: false\n\
+                 auth_backend:\n  type: ely.by\n  client_id: launcher\n  client_secret: secret\n",
+            ),
+            (
+                SpecFormat::Toml,
+                "auth_backend = { type = \"ely.by\", client_id = \"launcher\", client_secret = \"secret\" }\n\
+                 # comments are allowed\n\
+                 [[include]]\npath = \"mods\"\ndelete_extra = false\n",
+            ),
+        ];
+        for (format, content) in snippets {
+            let snippet: Snippet = deserialize(content, format, file).unwrap();
+            assert_eq!(snippet, expected, "{format:?}");
+            let serialized = serialize(&expected, format);
+            let snippet: Snippet = deserialize(&serialized, format, file).unwrap();
+            assert_eq!(snippet, expected, "{format:?}");
+        }
+
+        // the offending key in every format
+        let invalid = [
+            (SpecFormat::Json, "{\"include\": [{\"path\": 1}]}", (1, 23)),
+            (SpecFormat::Yaml, "include:\n  - path: [1]\n", (2, 11)),
+            (SpecFormat::Toml, "[[include]]\npath = 1\n", (2, 8)),
+        ];
+        for (format, content, position) in invalid {
+            match deserialize::<Snippet>(content, format, file) {
+                Err(SpecError::Parse {
+                    field,
+                    position: error_position,
+                    ..
+                }) => {
+                    assert_eq!(field, "include[0].path", "{format:?}");
+                    assert_eq!(error_position, Some(position), "{format:?}");
+                }
+                _ => panic!("expected a parse error for {format:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_extends() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        std::fs::write(
+            dir.join("base.yaml"),
+            "download_server_base: https://staging.example.com/data\n\
+             instances:\n\
+             \x20 - name: survival\n    minecraft_version: '1.21'\n    rollout_percent: 10\n\
+             \x20 - name: creative\n    minecraft_version: '1.20.1'\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("prod.toml"),
+            "extends = \"base.yaml\"\n\
+             download_server_base = \"https://example.com/data\"\n\
+             [[instances]]\nname = \"survival\"\nrollout_percent = 100\n\
+             [[instances]]\nname = \"skyblock\"\nminecraft_version = \"1.21\"\n",
+        )
+        .unwrap();
+
+        let spec = read_spec(&dir.join("prod.toml")).unwrap();
+        assert_eq!(spec.download_server_base, "https://example.com/data");
+        let instances: Vec<_> = spec
+            .instances
+            .iter()
+            .map(|instance| {
+                (
+                    instance.name.as_str(),
+                    instance.minecraft_version.as_str(),
+                    instance.rollout_percent,
+                )
+            })
+            .collect();
+        assert_eq!(
+            instances,
+            vec![
+                ("survival", "1.21", Some(100)),
+                ("creative", "1.20.1", None),
+                ("skyblock", "1.21", None),
+            ]
+        );
+
+        std::fs::write(dir.join("cycle.json"), r#"{"extends": "cycle.json"}"#).unwrap();
+        assert!(read_spec(&dir.join("cycle.json")).is_err());
+    }
+}
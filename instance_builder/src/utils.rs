@@ -1,4 +1,20 @@
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+
+// versions generated concurrently share the work dir, so writers of the same
+// library, asset or include source are serialized by its path here
+static PATH_LOCKS: LazyLock<Mutex<HashMap<PathBuf, Arc<tokio::sync::Mutex<()>>>>> =
+    LazyLock::new(Default::default);
+
+pub fn get_path_lock(path: &Path) -> Arc<tokio::sync::Mutex<()>> {
+    PATH_LOCKS
+        .lock()
+        .unwrap()
+        .entry(path.to_path_buf())
+        .or_default()
+        .clone()
+}
 
 pub fn get_assets_dir(output_dir: &Path) -> PathBuf {
     let assets_dir = output_dir.join("assets");
@@ -0,0 +1,207 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use reqwest::Url;
+use shared::generate::extra::IncludeRule;
+
+use crate::generate::include_source;
+use crate::spec::Spec;
+
+pub const SUPPORTED_LOADERS: [&str; 4] = ["vanilla", "fabric", "forge", "neoforge"];
+
+#[derive(thiserror::Error, Debug)]
+pub enum SpecError {
+    #[error("{}: {field}: {message}", format_location(file, position))]
+    Parse {
+        file: PathBuf,
+        // line and column, unknown for merged specs
+        position: Option<(usize, usize)>,
+        // e.g. instances[0].loader_name
+        field: String,
+        message: String,
+    },
+    #[error("{} problem(s) in the spec:\n{}", .0.len(), .0.join("\n"))]
+    Invalid(Vec<String>),
+    #[error("{} extends itself", .0.display())]
+    ExtendsCycle(PathBuf),
+}
+
+fn format_location(file: &Path, position: &Option<(usize, usize)>) -> String {
+    match position {
+        Some((line, column)) => format!("{}:{line}:{column}", file.display()),
+        None => file.display().to_string(),
+    }
+}
+
+fn check_url(problems: &mut Vec<String>, field: &str, url: &str) {
+    match Url::parse(url) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") => {}
+        Ok(_) => problems.push(format!("{field}: {url} is not an http(s) url")),
+        Err(e) => problems.push(format!("{field}: invalid url {url}: {e}")),
+    }
+}
+
+fn check_include_rules(
+    problems: &mut Vec<String>,
+    field: &str,
+    include_from: &Path,
+    include: &[IncludeRule],
+) {
+    for (index, rule) in include.iter().enumerate() {
+        if !include_from.join(&rule.path).exists() {
+            problems.push(format!(
+                "{field}[{index}].path: {} does not exist in {}",
+                rule.path,
+                include_from.display()
+            ));
+        }
+    }
+}
+
+// all problems at once, checked before any network work
+pub fn validate_spec(spec: &Spec) -> Result<(), SpecError> {
+    let mut problems = vec![];
+
+    check_url(
+        &mut problems,
+        "download_server_base",
+        &spec.download_server_base,
+    );
+    for (index, mirror) in spec.download_server_mirrors.iter().enumerate() {
+        check_url(
+            &mut problems,
+            &format!("download_server_mirrors[{index}]"),
+            mirror,
+        );
+    }
+    if let Some(resources_url_base) = &spec.resources_url_base {
+        check_url(&mut problems, "resources_url_base", resources_url_base);
+    }
+    if let Some(version_manifest_url) = &spec.version_manifest_url {
+        check_url(&mut problems, "version_manifest_url", version_manifest_url);
+    }
+
+    let mut names = HashSet::new();
+    for (index, instance) in spec.instances.iter().enumerate() {
+        let field = format!("instances[{index}]");
+        let version_names = std::iter::once(instance.name.clone()).chain(
+            instance
+                .variants
+                .iter()
+                .map(|variant| variant.get_version_name(&instance.name)),
+        );
+        for name in version_names {
+            if !names.insert(name.clone()) {
+                problems.push(format!("{field}: duplicate version name {name}"));
+            }
+        }
+
+        if !SUPPORTED_LOADERS.contains(&instance.loader_name.as_str()) {
+            problems.push(format!(
+                "{field}.loader_name: unsupported loader {}, expected one of {}",
+                instance.loader_name,
+                SUPPORTED_LOADERS.join(", ")
+            ));
+        }
+
+        // remote sources are only fetched while generating
+        let Some(include_from) = instance
+            .include_from
+            .as_ref()
+            .filter(|include_from| !include_source::is_remote(include_from))
+        else {
+            continue;
+        };
+        let include_from = Path::new(include_from);
+        if !include_from.is_dir() {
+            problems.push(format!(
+                "{field}.include_from: {} is not a directory",
+                include_from.display()
+            ));
+            continue;
+        }
+        check_include_rules(
+            &mut problems,
+            &format!("{field}.include"),
+            include_from,
+            &instance.include,
+        );
+        for (variant_index, variant) in instance.variants.iter().enumerate() {
+            check_include_rules(
+                &mut problems,
+                &format!("{field}.variants[{variant_index}].include"),
+                include_from,
+                &variant.include,
+            );
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(SpecError::Invalid(problems))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spec_file::{SpecFormat, deserialize};
+
+    fn parse_spec(content: &str, file: &Path) -> Result<Spec, SpecError> {
+        deserialize(content, SpecFormat::Json, file)
+    }
+
+    #[test]
+    fn test_validate_spec() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let include_from = temp_dir.path();
+        std::fs::create_dir_all(include_from.join("mods")).unwrap();
+        let file = Path::new("spec.json");
+
+        let spec = parse_spec(
+            r#"{
+                "download_server_base": "https://example.com/data",
+                "instances": [{"name": "survival", "minecraft_version": 1.21}]
+            }"#,
+            file,
+        );
+        match spec {
+            Err(SpecError::Parse {
+                position, field, ..
+            }) => {
+                assert_eq!(position.map(|(line, _)| line), Some(3));
+                assert_eq!(field, "instances[0].minecraft_version");
+            }
+            _ => panic!("expected a parse error"),
+        }
+
+        let content = format!(
+            r#"{{
+                "download_server_base": "example.com/data",
+                "instances": [
+                    {{
+                        "name": "survival",
+                        "minecraft_version": "1.21",
+                        "loader_name": "quilt",
+                        "include_from": {include_from:?},
+                        "include": [{{"path": "mods"}}, {{"path": "config"}}],
+                        "variants": [{{"name": "Lite"}}]
+                    }},
+                    {{"name": "survival (Lite)", "minecraft_version": "1.21"}}
+                ]
+            }}"#
+        );
+        let spec = parse_spec(&content, file).unwrap();
+        match validate_spec(&spec) {
+            Err(SpecError::Invalid(problems)) => {
+                assert_eq!(problems.len(), 4, "{problems:?}");
+                assert!(problems[0].starts_with("download_server_base"));
+                assert!(problems[1].starts_with("instances[0].loader_name"));
+                assert!(problems[2].starts_with("instances[0].include[1].path: config"));
+                assert!(problems[3].contains("duplicate version name survival (Lite)"));
+            }
+            _ => panic!("expected problems"),
+        }
+    }
+}
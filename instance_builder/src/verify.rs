@@ -0,0 +1,175 @@
+use std::path::Path;
+
+use log::info;
+use rand::seq::IteratorRandom as _;
+use reqwest::{
+    Client,
+    header::{CONTENT_LENGTH, RANGE},
+};
+use sha1::{Digest, Sha1};
+use shared::{
+    checksums::{CHECKSUMS_FILENAME, Checksums, FileChecksum},
+    utils::url_from_rel_path,
+};
+
+// larger files only have their size checked
+const MAX_HASHED_SIZE: u64 = 1024 * 1024;
+
+pub struct Drift {
+    pub path: String,
+    pub reason: String,
+}
+
+// None if the served file matches the local checksum
+async fn check_file(
+    client: &Client,
+    url: &str,
+    checksum: &FileChecksum,
+) -> anyhow::Result<Option<String>> {
+    let response = client.head(url).send().await?;
+    if !response.status().is_success() {
+        return Ok(Some(format!("HEAD returned {}", response.status())));
+    }
+    // the body of a HEAD response is empty, so the header is read directly
+    let size = response
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok());
+    match size {
+        Some(size) if size != checksum.size => {
+            return Ok(Some(format!("size is {size}, expected {}", checksum.size)));
+        }
+        Some(_) => {}
+        None => return Ok(Some("no Content-Length".to_string())),
+    }
+
+    if checksum.size == 0 || checksum.size > MAX_HASHED_SIZE {
+        return Ok(None);
+    }
+    let data = client
+        .get(url)
+        .header(RANGE, format!("bytes=0-{}", checksum.size - 1))
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    let sha1 = format!("{:x}", Sha1::digest(&data));
+    if sha1 != checksum.sha1 {
+        return Ok(Some(format!("sha1 is {sha1}, expected {}", checksum.sha1)));
+    }
+    Ok(None)
+}
+
+// compares the files served from base_url with the checksums of the output dir
+pub async fn verify_remote(
+    output_dir: &Path,
+    base_url: &str,
+    sample_size: usize,
+) -> anyhow::Result<Vec<Drift>> {
+    let local = Checksums::read_local(&output_dir.join(CHECKSUMS_FILENAME)).await?;
+    let base_url = base_url.trim_end_matches('/');
+    let checksums_url = format!("{base_url}/{CHECKSUMS_FILENAME}");
+    info!("Fetching {checksums_url}");
+    let remote = Checksums::fetch(&checksums_url).await?;
+
+    let mut drift = vec![];
+    let changed = local
+        .files
+        .iter()
+        .filter(|(path, checksum)| remote.files.get(*path) != Some(checksum))
+        .count()
+        + remote
+            .files
+            .keys()
+            .filter(|path| !local.files.contains_key(*path))
+            .count();
+    if changed > 0 {
+        drift.push(Drift {
+            path: CHECKSUMS_FILENAME.to_string(),
+            reason: format!("{changed} file(s) differ from the local build"),
+        });
+    }
+
+    // compressed responses would have a different length
+    let client = Client::builder().no_gzip().build()?;
+    let sample = local
+        .files
+        .iter()
+        .choose_multiple(&mut rand::rng(), sample_size);
+    info!("Checking {} file(s)", sample.len());
+    for (path, checksum) in sample {
+        let url = url_from_rel_path(Path::new(path), base_url)?;
+        let reason = match check_file(&client, &url, checksum).await {
+            Ok(reason) => reason,
+            Err(e) => Some(format!("request failed: {e}")),
+        };
+        if let Some(reason) = reason {
+            drift.push(Drift {
+                path: path.clone(),
+                reason,
+            });
+        }
+    }
+    Ok(drift)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::Router;
+    use tower_http::services::ServeDir;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_verify_remote() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let test_dir = temp_dir.path();
+        let output_dir = test_dir.join("output");
+        let remote_dir = test_dir.join("remote");
+        for dir in [&output_dir, &remote_dir] {
+            std::fs::create_dir_all(dir.join("mods")).unwrap();
+            std::fs::write(dir.join("mods/a b.jar"), "a").unwrap();
+            std::fs::write(dir.join("mods/changed.jar"), "old").unwrap();
+            std::fs::write(dir.join("mods/truncated.jar"), "full").unwrap();
+        }
+        Checksums::from_dir(&output_dir)
+            .await
+            .unwrap()
+            .save(&output_dir.join(CHECKSUMS_FILENAME))
+            .await
+            .unwrap();
+        std::fs::copy(
+            output_dir.join(CHECKSUMS_FILENAME),
+            remote_dir.join(CHECKSUMS_FILENAME),
+        )
+        .unwrap();
+        // the cdn serves stale copies
+        std::fs::write(remote_dir.join("mods/changed.jar"), "new").unwrap();
+        std::fs::write(remote_dir.join("mods/truncated.jar"), "ful").unwrap();
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let app = Router::new().fallback_service(ServeDir::new(&remote_dir));
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let drift = verify_remote(&output_dir, &base_url, 10).await.unwrap();
+        let mut drift: Vec<(&str, &str)> = drift
+            .iter()
+            .map(|drift| (drift.path.as_str(), drift.reason.as_str()))
+            .collect();
+        drift.sort();
+        assert_eq!(
+            drift,
+            vec![
+                (
+                    "mods/changed.jar",
+                    "sha1 is c2a6b03f190dfb2b4aa91f8af8d477a9bc3401dc, expected \
+                     c00dbbc9dadfbe1e232e93a729dd4752fade0abf"
+                ),
+                ("mods/truncated.jar", "size is 3, expected 4"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,261 @@
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use egui::RichText;
+use log::error;
+use shared::progress::{ProgressBar, Unit};
+use tokio::runtime::Runtime;
+
+use crate::config::runtime_config::Config;
+use crate::lang::LangMessage;
+use crate::version::archive;
+use crate::version::import;
+use crate::version::instance_storage::{InstanceStatus, InstanceStorage, LocalInstance};
+use crate::version::sync;
+
+use super::background_task::{BackgroundTask, BackgroundTaskResult};
+use super::colors;
+use super::progress_bar::GuiProgressBar;
+use super::task_registry::{TaskKey, TaskRegistry};
+
+#[derive(Clone, Copy, PartialEq)]
+enum ArchiveAction {
+    Archive,
+    Restore,
+    Export,
+}
+
+struct ArchiveTask {
+    action: ArchiveAction,
+    instance_name: String,
+    task: BackgroundTask<anyhow::Result<()>>,
+}
+
+fn get_task_key(action: ArchiveAction, instance_name: &str) -> TaskKey {
+    match action {
+        ArchiveAction::Archive => TaskKey::ArchiveInstance(instance_name.to_string()),
+        ArchiveAction::Restore => TaskKey::RestoreInstance(instance_name.to_string()),
+        ArchiveAction::Export => TaskKey::ExportInstance(instance_name.to_string()),
+    }
+}
+
+pub struct ArchiveState {
+    task: Option<ArchiveTask>,
+    progress_bar: Arc<GuiProgressBar>,
+    // the failed action and its instance
+    error: Option<(ArchiveAction, String)>,
+    task_registry: TaskRegistry,
+}
+
+impl ArchiveState {
+    pub fn new(ctx: &egui::Context, task_registry: TaskRegistry) -> Self {
+        ArchiveState {
+            task: None,
+            progress_bar: Arc::new(GuiProgressBar::new(ctx)),
+            error: None,
+            task_registry,
+        }
+    }
+
+    pub fn is_busy(&self) -> bool {
+        self.task.is_some()
+    }
+
+    // work gets the progress bar of the task
+    fn start<F>(
+        &mut self,
+        runtime: &Runtime,
+        ctx: &egui::Context,
+        action: ArchiveAction,
+        instance_name: &str,
+        work: impl FnOnce(Arc<GuiProgressBar>) -> F,
+    ) where
+        F: Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        if self.task.is_some() {
+            return;
+        }
+        self.error = None;
+        self.progress_bar = Arc::new(GuiProgressBar::new(ctx));
+        self.progress_bar.set_unit(Unit {
+            name: "MB".to_string(),
+            size: 1024 * 1024,
+        });
+
+        let fut = work(self.progress_bar.clone());
+        let progress_bar = self.progress_bar.clone();
+        let task = BackgroundTask::with_callback(
+            fut,
+            runtime,
+            Box::new(move || {
+                progress_bar.finish();
+            }),
+        );
+        self.task_registry.register(
+            get_task_key(action, instance_name),
+            &task,
+            Some(self.progress_bar.clone()),
+        );
+        self.task = Some(ArchiveTask {
+            action,
+            instance_name: instance_name.to_string(),
+            task,
+        });
+    }
+
+    pub fn start_archive(
+        &mut self,
+        runtime: &Runtime,
+        config: &Config,
+        ctx: &egui::Context,
+        instance_name: &str,
+    ) {
+        let launcher_dir = config.get_launcher_dir();
+        let name = instance_name.to_string();
+        self.start(
+            runtime,
+            ctx,
+            ArchiveAction::Archive,
+            instance_name,
+            |progress_bar| async move {
+                sync::ensure_not_running(&launcher_dir, &name)?;
+                archive::archive_instance(&launcher_dir, &name, progress_bar).await?;
+                Ok(())
+            },
+        );
+    }
+
+    fn start_restore(
+        &mut self,
+        runtime: &Runtime,
+        config: &Config,
+        ctx: &egui::Context,
+        instance_name: &str,
+    ) {
+        let launcher_dir = config.get_launcher_dir();
+        let name = instance_name.to_string();
+        self.start(
+            runtime,
+            ctx,
+            ArchiveAction::Restore,
+            instance_name,
+            |progress_bar| async move {
+                sync::ensure_not_running(&launcher_dir, &name)?;
+                archive::restore_instance(&launcher_dir, &name, progress_bar).await
+            },
+        );
+    }
+
+    pub fn start_export(
+        &mut self,
+        runtime: &Runtime,
+        config: &Config,
+        ctx: &egui::Context,
+        instance: LocalInstance,
+        archive_path: PathBuf,
+    ) {
+        let launcher_dir = config.get_launcher_dir();
+        let instance_name = instance.version_info.get_name();
+        self.start(
+            runtime,
+            ctx,
+            ArchiveAction::Export,
+            &instance_name,
+            |progress_bar| async move {
+                import::export_instance(&launcher_dir, instance, &archive_path, progress_bar).await
+            },
+        );
+    }
+
+    // marks the instance once its files are archived or restored,
+    // returns true if the status of an instance has changed
+    pub fn update(
+        &mut self,
+        runtime: &Runtime,
+        config: &Config,
+        instance_storage: &mut InstanceStorage,
+    ) -> bool {
+        if !self
+            .task
+            .as_ref()
+            .is_some_and(|task| task.task.has_result())
+        {
+            return false;
+        }
+        let ArchiveTask {
+            action,
+            instance_name,
+            task,
+        } = self.task.take().unwrap();
+        match task.take_result() {
+            BackgroundTaskResult::Finished(Ok(())) => {
+                match action {
+                    ArchiveAction::Archive => {
+                        runtime.block_on(instance_storage.mark_archived(config, &instance_name));
+                    }
+                    ArchiveAction::Restore => {
+                        runtime.block_on(instance_storage.mark_restored(config, &instance_name));
+                    }
+                    ArchiveAction::Export => return false,
+                }
+                true
+            }
+            BackgroundTaskResult::Finished(Err(e)) => {
+                error!("Error archiving, restoring or exporting instance {instance_name}:\n{e:?}");
+                self.error = Some((action, instance_name));
+                false
+            }
+            BackgroundTaskResult::Cancelled => false,
+        }
+    }
+
+    // takes the place of the launch button for archived instances and while archiving,
+    // returns true if it did
+    pub fn render_ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        runtime: &Runtime,
+        config: &Config,
+        selected_instance: &LocalInstance,
+    ) -> bool {
+        let lang = config.lang;
+        let dark_mode = ui.style().visuals.dark_mode;
+        let instance_name = selected_instance.version_info.get_name();
+
+        if let Some((action, failed_instance_name)) = &self.error
+            && failed_instance_name == &instance_name
+        {
+            let message = match action {
+                ArchiveAction::Archive => LangMessage::ArchiveInstanceError,
+                ArchiveAction::Restore => LangMessage::RestoreInstanceError,
+                ArchiveAction::Export => LangMessage::ExportInstanceError,
+            };
+            ui.label(RichText::new(message.to_string(lang)).color(colors::error(dark_mode)));
+        }
+
+        if self
+            .task
+            .as_ref()
+            .is_some_and(|task| task.instance_name == instance_name)
+        {
+            self.progress_bar.render(ui, lang);
+            return true;
+        }
+        if selected_instance.status != InstanceStatus::Archived {
+            return false;
+        }
+
+        ui.label(LangMessage::InstanceArchived.to_string(lang));
+        if ui
+            .add_enabled(
+                self.task.is_none(),
+                egui::Button::new(LangMessage::RestoreInstance.to_string(lang)),
+            )
+            .clicked()
+        {
+            self.start_restore(runtime, config, ui.ctx(), &instance_name);
+        }
+        true
+    }
+}
@@ -1,18 +1,17 @@
 use egui::ComboBox;
 use egui::RichText;
 use egui::Window;
-use image::Luma;
 use log::error;
-use qrcode::QrCode;
-use shared::utils::is_connect_error;
+use shared::utils::{is_clock_skew_error, is_connect_error};
 use shared::version::extra_version_metadata::AuthBackend;
 use shared::version::extra_version_metadata::ElyByAuthBackend;
 use shared::version::extra_version_metadata::TelegramAuthBackend;
 use std::hash::DefaultHasher;
 use std::hash::Hash as _;
 use std::hash::Hasher as _;
-use std::io::Cursor;
+use std::path::Path;
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::runtime::Runtime;
 
 use crate::auth::auth_flow::AuthMessageProvider;
@@ -22,13 +21,20 @@ use crate::auth::auth_storage::AuthStorage;
 use crate::auth::auth_storage::StorageEntry;
 use crate::auth::base::get_auth_provider;
 use crate::auth::user_info::AuthData;
+use crate::auth::{
+    AuthError, CREATE_PROFILE_URL, FAMILY_SETTINGS_URL, OfflineAccount, OfflineUuidStrategy,
+    get_offline_account,
+};
 use crate::config::runtime_config::AuthProfile;
 use crate::config::runtime_config::Config;
 use crate::lang::{Lang, LangMessage};
 use crate::utils::is_valid_minecraft_username;
 
+use super::auth_window::AuthWindow;
+use super::avatar_state::AvatarState;
 use super::background_task::{BackgroundTask, BackgroundTaskResult};
 use super::colors;
+use super::widgets::{TrailingRow, icon_button};
 
 #[derive(Clone, PartialEq)]
 enum AuthStatus {
@@ -37,6 +43,9 @@ enum AuthStatus {
     AuthorizeError,
     AuthorizeErrorOffline,
     AuthorizeErrorTimeout,
+    AuthorizeErrorClockSkew,
+    AuthorizeErrorNoProfile,
+    AuthorizeErrorFamilyConsent,
 }
 
 struct AuthResult {
@@ -45,6 +54,11 @@ struct AuthResult {
     auth_data: Option<AuthData>,
 }
 
+fn new_auth_message_provider(ctx: &egui::Context) -> Arc<AuthMessageProvider> {
+    let ctx = ctx.clone();
+    Arc::new(AuthMessageProvider::new(move || ctx.request_repaint()))
+}
+
 fn authenticate(
     runtime: &Runtime,
     auth_data: Option<AuthData>,
@@ -55,6 +69,7 @@ fn authenticate(
     let ctx = ctx.clone();
     let auth_backend = auth_backend.clone();
     let auth_provider = get_auth_provider(&auth_backend);
+    let cancellation_token = auth_message_provider.get_cancellation_token();
 
     let fut = async move {
         match perform_auth(auth_data, auth_provider, auth_message_provider).await {
@@ -78,7 +93,17 @@ fn authenticate(
 
                 AuthResult {
                     auth_backend,
-                    status: if connect_error {
+                    // tls reports a wrong clock as a connection error
+                    status: if is_clock_skew_error(&e) {
+                        error!("Auth error caused by a wrong system clock:\n{e:?}");
+                        AuthStatus::AuthorizeErrorClockSkew
+                    } else if let Some(AuthError::NoMinecraftProfile) = e.downcast_ref() {
+                        error!("Auth error:\n{e:?}");
+                        AuthStatus::AuthorizeErrorNoProfile
+                    } else if let Some(AuthError::FamilyConsentRequired) = e.downcast_ref() {
+                        error!("Auth error:\n{e:?}");
+                        AuthStatus::AuthorizeErrorFamilyConsent
+                    } else if connect_error {
                         AuthStatus::AuthorizeErrorOffline
                     } else if timeout_error {
                         AuthStatus::AuthorizeErrorTimeout
@@ -92,12 +117,13 @@ fn authenticate(
         }
     };
 
-    BackgroundTask::with_callback(
+    BackgroundTask::with_cancellation_token(
         fut,
         runtime,
         Box::new(move || {
             ctx.request_repaint();
         }),
+        cancellation_token,
     )
 }
 
@@ -113,6 +139,7 @@ pub struct AuthState {
     auth_status: AuthStatus,
     auth_task: Option<BackgroundTask<AuthResult>>,
     auth_message_provider: Arc<AuthMessageProvider>,
+    auth_window: AuthWindow,
     auth_storage: AuthStorage,
 
     show_add_account: bool,
@@ -125,8 +152,22 @@ pub struct AuthState {
     telegram_auth_base_url: String,
 
     offline_nickname: String,
+    offline_uuid_strategy: OfflineUuidStrategy,
+    offline_manual_uuid: String,
+    // the nickname the strategy of an existing account was last looked up for
+    offline_checked_nickname: String,
 
     last_auth_profile: Option<AuthProfile>,
+
+    // the account waiting for confirmation and the instances using it
+    account_to_remove: Option<(AuthProfile, Vec<String>)>,
+    show_accounts: bool,
+    // set while auth_task re-authenticates an account from the accounts window,
+    // the selected account stays then
+    reauth_profile: Option<AuthProfile>,
+    reauth_status: Option<(AuthProfile, AuthStatus)>,
+
+    avatars: AvatarState,
 }
 
 impl AuthState {
@@ -134,7 +175,8 @@ impl AuthState {
         AuthState {
             auth_status: AuthStatus::NotAuthorized,
             auth_task: None,
-            auth_message_provider: Arc::new(AuthMessageProvider::new(ctx)),
+            auth_message_provider: new_auth_message_provider(ctx),
+            auth_window: AuthWindow::default(),
             auth_storage: AuthStorage::load(config),
 
             show_add_account: false,
@@ -147,18 +189,48 @@ impl AuthState {
             telegram_auth_base_url: String::new(),
 
             offline_nickname: String::new(),
+            offline_uuid_strategy: OfflineUuidStrategy::Mojang,
+            offline_manual_uuid: String::new(),
+            offline_checked_nickname: String::new(),
 
             last_auth_profile: None,
+
+            account_to_remove: None,
+            show_accounts: false,
+            reauth_profile: None,
+            reauth_status: None,
+
+            avatars: AvatarState::new(),
         }
     }
 
     pub fn update(&mut self, runtime: &Runtime, config: &mut Config) -> bool {
+        self.auth_storage.apply_backend(config);
         if let Some(task) = self.auth_task.as_ref()
             && task.has_result()
         {
             runtime.block_on(self.auth_message_provider.clear());
             let task = self.auth_task.take().unwrap();
             let result = task.take_result();
+            if let Some(auth_profile) = self.reauth_profile.take() {
+                let status = match result {
+                    BackgroundTaskResult::Finished(result) => {
+                        if result.status == AuthStatus::Authorized
+                            && let Some(auth_data) = result.auth_data
+                        {
+                            self.auth_storage
+                                .insert(config, &result.auth_backend, auth_data);
+                        }
+                        result.status
+                    }
+                    BackgroundTaskResult::Cancelled => AuthStatus::NotAuthorized,
+                };
+                if config.get_selected_auth_profile() == Some(&auth_profile) {
+                    self.auth_status = status.clone();
+                }
+                self.reauth_status = Some((auth_profile, status));
+                return true;
+            }
             match result {
                 BackgroundTaskResult::Finished(result) => {
                     if result.status == AuthStatus::Authorized
@@ -188,44 +260,56 @@ impl AuthState {
         false
     }
 
-    fn render_auth_window(&mut self, config: &mut Config, runtime: &Runtime, ui: &mut egui::Ui) {
-        if let Some(message) = runtime.block_on(self.auth_message_provider.get_message()) {
-            let lang = config.lang;
-            let ctx = ui.ctx();
+    fn cancel_auth(&mut self, config: &mut Config, runtime: &Runtime, ctx: &egui::Context) {
+        self.auth_status = AuthStatus::NotAuthorized;
+        self.auth_task = None;
+        self.reauth_profile = None;
+        self.auth_message_provider = new_auth_message_provider(ctx);
+        self.on_instance_changed(config, runtime, ctx);
+    }
 
-            egui::Window::new(LangMessage::Authorization.to_string(lang)).show(ctx, |ui| {
-                ui.label(message.to_string(lang));
-                let url = match message {
-                    LangMessage::AuthMessage { url } => Some(url),
-                    LangMessage::DeviceAuthMessage { url, .. } => Some(url),
-                    _ => None,
-                }
-                .unwrap();
+    fn is_auth_in_progress(&self, runtime: &Runtime) -> bool {
+        runtime
+            .block_on(self.auth_message_provider.get_message())
+            .is_some()
+            || runtime.block_on(self.auth_message_provider.need_offline_account())
+    }
 
-                ui.hyperlink(&url);
-                let code = QrCode::new(url).unwrap();
-                let image = code.render::<Luma<u8>>().build();
+    pub fn is_window_open(&self, runtime: &Runtime) -> bool {
+        self.show_add_account
+            || self.account_to_remove.is_some()
+            || self.show_accounts
+            || self.is_auth_in_progress(runtime)
+    }
 
-                let mut png_bytes: Vec<u8> = Vec::new();
-                let mut cursor = Cursor::new(&mut png_bytes);
-                image::DynamicImage::ImageLuma8(image)
-                    .write_to(&mut cursor, image::ImageFormat::Png)
-                    .unwrap();
+    pub fn close_window(&mut self, config: &mut Config, runtime: &Runtime, ctx: &egui::Context) {
+        if self.is_auth_in_progress(runtime) {
+            self.cancel_auth(config, runtime, ctx);
+        } else if self.account_to_remove.is_some() {
+            self.account_to_remove = None;
+        } else if self.show_add_account {
+            self.show_add_account = false;
+        } else {
+            self.show_accounts = false;
+        }
+    }
 
-                let uri = "bytes://auth_qr.png";
-                ctx.include_bytes(uri, png_bytes.clone());
-                ui.add(egui::Image::from_bytes(uri.to_string(), png_bytes));
+    fn render_auth_window(&mut self, config: &mut Config, runtime: &Runtime, ui: &mut egui::Ui) {
+        if let Some(message) = runtime.block_on(self.auth_message_provider.get_message()) {
+            let lang = config.lang;
+            let ctx = ui.ctx();
+            let expires_at = runtime.block_on(self.auth_message_provider.get_expires_at());
+            let connection_lost = runtime.block_on(self.auth_message_provider.is_connection_lost());
 
-                if ui.button(LangMessage::Cancel.to_string(lang)).clicked() {
-                    self.auth_status = AuthStatus::NotAuthorized;
-                    self.auth_task = None;
-                    self.auth_message_provider = Arc::new(AuthMessageProvider::new(ctx));
-                    self.on_instance_changed(config, runtime, ctx);
-                }
-            });
+            if self
+                .auth_window
+                .render(ctx, lang, &message, expires_at, connection_lost)
+            {
+                self.cancel_auth(config, runtime, ctx);
+            }
         }
 
-        if runtime.block_on(self.auth_message_provider.need_offline_nickname()) {
+        if runtime.block_on(self.auth_message_provider.need_offline_account()) {
             let lang = config.lang;
             let ctx = ui.ctx();
 
@@ -239,29 +323,92 @@ impl AuthState {
                             ui.text_edit_singleline(&mut self.offline_nickname);
                         });
 
+                        // an account with the same nickname keeps its strategy and uuid
+                        let prior_account = self
+                            .auth_storage
+                            .get_by_id(&AuthBackend::Offline.get_id(), &self.offline_nickname)
+                            .map(|entry| get_offline_account(&entry.auth_data));
+                        if self.offline_checked_nickname != self.offline_nickname {
+                            self.offline_checked_nickname = self.offline_nickname.clone();
+                            if let Some(account) = &prior_account {
+                                self.offline_uuid_strategy = account.strategy;
+                                if account.strategy == OfflineUuidStrategy::Manual {
+                                    self.offline_manual_uuid = account.uuid.to_string();
+                                }
+                            }
+                        }
+                        ui.horizontal(|ui| {
+                            ui.label(LangMessage::OfflineUuid.to_string(lang));
+                            ComboBox::from_id_salt("offline_uuid_strategy")
+                                .selected_text(
+                                    Self::get_uuid_strategy_message(self.offline_uuid_strategy)
+                                        .to_string(lang),
+                                )
+                                .show_ui(ui, |ui| {
+                                    for strategy in [
+                                        OfflineUuidStrategy::Mojang,
+                                        OfflineUuidStrategy::Random,
+                                        OfflineUuidStrategy::Manual,
+                                    ] {
+                                        ui.selectable_value(
+                                            &mut self.offline_uuid_strategy,
+                                            strategy,
+                                            Self::get_uuid_strategy_message(strategy)
+                                                .to_string(lang),
+                                        );
+                                    }
+                                });
+                        });
+                        if self.offline_uuid_strategy == OfflineUuidStrategy::Manual {
+                            ui.text_edit_singleline(&mut self.offline_manual_uuid);
+                        }
+
+                        let prior_uuid = prior_account.map(|account| account.uuid.to_string());
+                        let uuid = self.offline_uuid_strategy.get_uuid(
+                            &self.offline_nickname,
+                            &self.offline_manual_uuid,
+                            prior_uuid.as_deref(),
+                        );
+                        if uuid.is_none() && !self.offline_manual_uuid.is_empty() {
+                            ui.colored_label(
+                                colors::error(ui.style().visuals.dark_mode),
+                                LangMessage::InvalidUuid.to_string(lang),
+                            );
+                        }
+
                         if ui
                             .add_enabled(
-                                is_valid_minecraft_username(&self.offline_nickname),
+                                is_valid_minecraft_username(&self.offline_nickname)
+                                    && uuid.is_some(),
                                 egui::Button::new(LangMessage::AddAccount.to_string(lang)),
                             )
                             .clicked()
+                            && let Some(uuid) = uuid
                         {
-                            runtime.block_on(
-                                self.auth_message_provider
-                                    .set_offline_nickname(self.offline_nickname.clone()),
-                            );
+                            runtime.block_on(self.auth_message_provider.set_offline_account(
+                                OfflineAccount {
+                                    nickname: self.offline_nickname.clone(),
+                                    uuid,
+                                    strategy: self.offline_uuid_strategy,
+                                },
+                            ));
                         }
                     });
                 });
             if !open {
-                self.auth_status = AuthStatus::NotAuthorized;
-                self.auth_task = None;
-                self.auth_message_provider = Arc::new(AuthMessageProvider::new(ctx));
-                self.on_instance_changed(config, runtime, ctx);
+                self.cancel_auth(config, runtime, ctx);
             }
         }
     }
 
+    fn get_uuid_strategy_message(strategy: OfflineUuidStrategy) -> LangMessage {
+        match strategy {
+            OfflineUuidStrategy::Mojang => LangMessage::OfflineUuidMojang,
+            OfflineUuidStrategy::Random => LangMessage::OfflineUuidRandom,
+            OfflineUuidStrategy::Manual => LangMessage::OfflineUuidManual,
+        }
+    }
+
     fn get_type_display_name(lang: Lang, new_account_type: NewAccountType) -> String {
         match new_account_type {
             NewAccountType::Microsoft => "Microsoft".to_string(),
@@ -338,7 +485,9 @@ impl AuthState {
                     };
 
                     self.auth_status = AuthStatus::NotAuthorized;
-                    self.auth_message_provider = Arc::new(AuthMessageProvider::new(ctx));
+                    self.auth_message_provider = new_auth_message_provider(ctx);
+
+                    self.reauth_profile = None;
                     self.auth_task = Some(authenticate(
                         runtime,
                         None,
@@ -377,7 +526,7 @@ impl AuthState {
         let storage_entry = self.get_selected_storage_entry(config);
         if let Some(storage_entry) = &storage_entry {
             if storage_entry.source == AuthDataSource::Persistent && self.auth_task.is_none() {
-                self.auth_message_provider = Arc::new(AuthMessageProvider::new(ctx));
+                self.auth_message_provider = new_auth_message_provider(ctx);
                 self.auth_task = Some(authenticate(
                     runtime,
                     Some(storage_entry.auth_data.clone()),
@@ -401,25 +550,14 @@ impl AuthState {
     ) {
         let mut auth_profile = config.get_selected_auth_profile().cloned();
 
-        if ui
-            .add_enabled(auth_profile.is_some(), egui::Button::new("-"))
-            .clicked()
-            && let Some(auth_profile) = auth_profile.take()
-        {
-            self.auth_storage.delete_by_id(
-                config,
-                &auth_profile.auth_backend_id,
-                &auth_profile.username,
-            );
-            config.clear_selected_auth_profile();
-        }
-
-        if ui.button("+").clicked() {
+        if icon_button(ui, "+", LangMessage::AddAccount, config.lang, true).clicked() {
             if let Some(instance_auth_backend) = instance_auth_backend {
                 let ctx = ui.ctx();
 
                 self.auth_status = AuthStatus::NotAuthorized;
-                self.auth_message_provider = Arc::new(AuthMessageProvider::new(ctx));
+                self.auth_message_provider = new_auth_message_provider(ctx);
+
+                self.reauth_profile = None;
                 self.auth_task = Some(authenticate(
                     runtime,
                     None,
@@ -438,7 +576,210 @@ impl AuthState {
                 self.telegram_auth_base_url = String::new();
 
                 self.offline_nickname = String::new();
+                self.offline_uuid_strategy = OfflineUuidStrategy::Mojang;
+                self.offline_manual_uuid = String::new();
+                self.offline_checked_nickname = String::new();
+            }
+        }
+
+        if icon_button(
+            ui,
+            "-",
+            LangMessage::RemoveAccount,
+            config.lang,
+            auth_profile.is_some(),
+        )
+        .clicked()
+            && let Some(auth_profile) = auth_profile.take()
+        {
+            let instance_names = config.get_instances_using_account(&auth_profile);
+            self.account_to_remove = Some((auth_profile, instance_names));
+        }
+
+        if icon_button(ui, "👤", LangMessage::Accounts, config.lang, true).clicked() {
+            self.show_accounts = true;
+        }
+    }
+
+    fn render_remove_account_window(&mut self, ctx: &egui::Context, config: &mut Config) {
+        let Some((auth_profile, instance_names)) = &self.account_to_remove else {
+            return;
+        };
+        let lang = config.lang;
+
+        let mut open = true;
+        let mut confirmed = false;
+        let mut cancelled = false;
+        Window::new(LangMessage::RemoveAccount.to_string(lang))
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(
+                    RichText::new(Self::get_account_display_name(&(
+                        auth_profile.auth_backend_id.clone(),
+                        auth_profile.username.clone(),
+                    )))
+                    .strong(),
+                );
+                if !instance_names.is_empty() {
+                    ui.label(LangMessage::AccountUsedByInstances.to_string(lang));
+                    for instance_name in instance_names {
+                        ui.label(format!("• {instance_name}"));
+                    }
+                }
+                ui.horizontal(|ui| {
+                    if ui.button(LangMessage::Delete.to_string(lang)).clicked() {
+                        confirmed = true;
+                    }
+                    if ui.button(LangMessage::Cancel.to_string(lang)).clicked() {
+                        cancelled = true;
+                    }
+                });
+            });
+
+        if confirmed {
+            let (auth_profile, _) = self.account_to_remove.take().unwrap();
+            self.auth_storage.delete_by_id(
+                config,
+                &auth_profile.auth_backend_id,
+                &auth_profile.username,
+            );
+            // leaving the references would make on_instance_changed authenticate a removed account
+            config.clear_auth_profile(&auth_profile);
+        } else if cancelled || !open {
+            self.account_to_remove = None;
+        }
+    }
+
+    fn render_accounts_window(&mut self, ctx: &egui::Context, config: &Config, runtime: &Runtime) {
+        let lang = config.lang;
+        let dark_mode = ctx.style().visuals.dark_mode;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+
+        let mut show_accounts = self.show_accounts;
+        let mut to_reauth = None;
+        let mut to_remove = None;
+        Window::new(LangMessage::Accounts.to_string(lang))
+            .open(&mut show_accounts)
+            .show(ctx, |ui| {
+                let entries = self.auth_storage.get_all_storage_entries();
+                if entries.is_empty() {
+                    ui.label(LangMessage::NoAccounts.to_string(lang));
+                    return;
+                }
+                for (i, (auth_backend, entry)) in entries.into_iter().enumerate() {
+                    if i != 0 {
+                        ui.separator();
+                    }
+                    let auth_data = entry.auth_data;
+                    let auth_profile = AuthProfile {
+                        auth_backend_id: auth_backend.get_id(),
+                        username: auth_data.user_info.username.clone(),
+                    };
+                    let display_name = Self::get_account_display_name(&(
+                        auth_profile.auth_backend_id.clone(),
+                        auth_profile.username.clone(),
+                    ));
+
+                    // the result of the last re-authentication of this account
+                    match &self.reauth_status {
+                        Some((profile, status)) if *profile == auth_profile => {
+                            ui.label(
+                                Self::get_combobox_text(&display_name, status, lang, dark_mode)
+                                    .strong(),
+                            );
+                        }
+                        _ => {
+                            ui.label(RichText::new(display_name).strong());
+                        }
+                    }
+                    ui.label(
+                        match entry.source {
+                            AuthDataSource::Runtime => LangMessage::AccountSessionActive,
+                            AuthDataSource::Persistent => LangMessage::AccountSessionSaved,
+                        }
+                        .to_string(lang),
+                    );
+                    if let Some(expiry) = auth_data.get_token_expiry() {
+                        ui.label(
+                            LangMessage::AccountTokenExpiry {
+                                expires_in_secs: expiry as i64 - now as i64,
+                            }
+                            .to_string(lang),
+                        );
+                    }
+                    let instance_names = config.get_instances_using_account(&auth_profile);
+                    if instance_names.is_empty() {
+                        ui.label(LangMessage::AccountNotUsed.to_string(lang));
+                    } else {
+                        ui.label(
+                            LangMessage::AccountInstances {
+                                instance_names: instance_names.join(", "),
+                            }
+                            .to_string(lang),
+                        );
+                    }
+                    ui.label(
+                        LangMessage::AccountLastUsed {
+                            days_ago: auth_data
+                                .last_used
+                                .map(|t| now.saturating_sub(t) / (24 * 60 * 60)),
+                        }
+                        .to_string(lang),
+                    );
+                    // servers whitelist by uuid
+                    ui.label(
+                        LangMessage::AccountUuid {
+                            uuid: auth_data.user_info.uuid.clone(),
+                        }
+                        .to_string(lang),
+                    );
+                    ui.horizontal(|ui| {
+                        // offline accounts have nothing to re-authenticate with
+                        if !matches!(auth_backend, AuthBackend::Offline)
+                            && ui
+                                .add_enabled(
+                                    self.auth_task.is_none(),
+                                    egui::Button::new(LangMessage::Reauthenticate.to_string(lang)),
+                                )
+                                .clicked()
+                        {
+                            to_reauth = Some((auth_backend.clone(), auth_data.clone()));
+                        }
+                        if ui
+                            .button(LangMessage::RemoveAccount.to_string(lang))
+                            .clicked()
+                        {
+                            to_remove = Some((auth_profile.clone(), instance_names.clone()));
+                        }
+                    });
+                }
+            });
+        self.show_accounts = show_accounts;
+
+        if let Some((auth_backend, auth_data)) = to_reauth {
+            let auth_profile = AuthProfile {
+                auth_backend_id: auth_backend.get_id(),
+                username: auth_data.user_info.username.clone(),
+            };
+            if config.get_selected_auth_profile() == Some(&auth_profile) {
+                self.auth_status = AuthStatus::NotAuthorized;
             }
+            self.reauth_status = Some((auth_profile.clone(), AuthStatus::NotAuthorized));
+            self.reauth_profile = Some(auth_profile);
+            self.auth_message_provider = new_auth_message_provider(ctx);
+            self.auth_task = Some(authenticate(
+                runtime,
+                Some(auth_data),
+                &auth_backend,
+                self.auth_message_provider.clone(),
+                ctx,
+            ));
+        }
+        if to_remove.is_some() {
+            self.account_to_remove = to_remove;
         }
     }
 
@@ -455,6 +796,26 @@ impl AuthState {
         format!("{} ({} #{})", username, provider_name, &hex[0..4])
     }
 
+    fn get_avatar(
+        &mut self,
+        runtime: &Runtime,
+        ctx: &egui::Context,
+        launcher_dir: &Path,
+        (id, username): (&str, &str),
+    ) -> egui::Image<'static> {
+        let user_info = self
+            .auth_storage
+            .get_by_id(id, username)
+            .map(|entry| entry.auth_data.user_info);
+        self.avatars.get_avatar(
+            runtime,
+            ctx,
+            launcher_dir,
+            &AuthBackend::from_id(id),
+            user_info.as_ref(),
+        )
+    }
+
     fn get_combobox_text(
         nickname: &str,
         status: &AuthStatus,
@@ -466,30 +827,63 @@ impl AuthState {
             AuthStatus::NotAuthorized => RichText::new(format!(
                 "{} ({})",
                 nickname,
-                LangMessage::Authorizing.to_string(lang)
+                LangMessage::Authorizing.to_cached_string(lang)
             ))
             .color(colors::in_progress(dark_mode)),
             AuthStatus::AuthorizeError => RichText::new(format!(
                 "{} ({})",
                 nickname,
-                LangMessage::UnknownAuthError.to_string(lang)
+                LangMessage::UnknownAuthError.to_cached_string(lang)
             ))
             .color(colors::error(dark_mode)),
             AuthStatus::AuthorizeErrorOffline => RichText::new(format!(
                 "{} ({})",
                 nickname,
-                LangMessage::Offline.to_string(lang)
+                LangMessage::Offline.to_cached_string(lang)
             ))
             .color(colors::offline(dark_mode)),
             AuthStatus::AuthorizeErrorTimeout => RichText::new(format!(
                 "{} ({})",
                 nickname,
-                LangMessage::AuthTimeout.to_string(lang)
+                LangMessage::AuthTimeout.to_cached_string(lang)
             ))
             .color(colors::timeout(dark_mode)),
+            AuthStatus::AuthorizeErrorClockSkew => RichText::new(format!(
+                "{} ({})",
+                nickname,
+                LangMessage::ClockSkewAuthError.to_cached_string(lang)
+            ))
+            .color(colors::error(dark_mode)),
+            AuthStatus::AuthorizeErrorNoProfile => RichText::new(format!(
+                "{} ({})",
+                nickname,
+                LangMessage::NoMinecraftProfile.to_cached_string(lang)
+            ))
+            .color(colors::error(dark_mode)),
+            AuthStatus::AuthorizeErrorFamilyConsent => RichText::new(format!(
+                "{} ({})",
+                nickname,
+                LangMessage::FamilyConsentRequired.to_cached_string(lang)
+            ))
+            .color(colors::error(dark_mode)),
         }
     }
 
+    // errors the user can fix on the account website
+    fn render_auth_error_hint(&self, ui: &mut egui::Ui, lang: Lang) {
+        let (hint, url) = match self.auth_status {
+            AuthStatus::AuthorizeErrorNoProfile => {
+                (LangMessage::NoMinecraftProfileHint, CREATE_PROFILE_URL)
+            }
+            AuthStatus::AuthorizeErrorFamilyConsent => {
+                (LangMessage::FamilyConsentRequiredHint, FAMILY_SETTINGS_URL)
+            }
+            _ => return,
+        };
+        ui.label(hint.to_string(lang));
+        ui.hyperlink(url);
+    }
+
     pub fn render_ui(
         &mut self,
         ui: &mut egui::Ui,
@@ -522,40 +916,71 @@ impl AuthState {
         }
 
         let dark_mode = ui.style().visuals.dark_mode;
+        let launcher_dir = config.get_launcher_dir();
+        self.avatars.update(ctx);
 
         let auth_profile = config.get_selected_auth_profile().cloned();
+        let selected_avatar = auth_profile.as_ref().map(|profile| {
+            self.get_avatar(
+                runtime,
+                ctx,
+                &launcher_dir,
+                (&profile.auth_backend_id, &profile.username),
+            )
+        });
         if let Some(instance_auth_backend) = instance_auth_backend {
-            let mut entries = self
-                .auth_storage
-                .get_id_nicknames(&instance_auth_backend.get_id());
+            let backend_id = instance_auth_backend.get_id();
+            let mut entries = self.auth_storage.get_id_nicknames(&backend_id);
+            entries.sort();
+            let entries: Vec<_> = entries
+                .into_iter()
+                .map(|username| {
+                    let avatar =
+                        self.get_avatar(runtime, ctx, &launcher_dir, (&backend_id, &username));
+                    (username, avatar)
+                })
+                .collect();
 
             if !entries.is_empty() {
-                self.render_buttons(ui, config, runtime, Some(instance_auth_backend));
-
+                let row = TrailingRow::new(ui, "account_row");
                 let mut selected_username = auth_profile.as_ref().map(|x| x.username.to_string());
-                ComboBox::from_id_salt("select_account")
-                    .selected_text(match &selected_username {
-                        Some(username) => {
-                            Self::get_combobox_text(username, &self.auth_status, lang, dark_mode)
-                        }
-                        None => RichText::new(LangMessage::SelectAccount.to_string(lang))
-                            .color(colors::action(dark_mode)),
-                    })
-                    .width(ui.available_width())
-                    .show_ui(ui, |ui| {
-                        if config.selected_instance_name.is_none() {
-                            ui.disable();
-                            return;
-                        }
-                        entries.sort();
-                        for username in entries {
-                            ui.selectable_value(
-                                &mut selected_username,
-                                Some(username.clone()),
-                                username,
-                            );
+                row.show_fill(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        if let Some(avatar) = selected_avatar {
+                            ui.add(avatar);
                         }
+                        ComboBox::from_id_salt("select_account")
+                            .selected_text(match &selected_username {
+                                Some(username) => Self::get_combobox_text(
+                                    username,
+                                    &self.auth_status,
+                                    lang,
+                                    dark_mode,
+                                ),
+                                None => RichText::new(
+                                    &*LangMessage::SelectAccount.to_cached_string(lang),
+                                )
+                                .color(colors::action(dark_mode)),
+                            })
+                            .width(ui.available_width())
+                            .show_ui(ui, |ui| {
+                                if config.selected_instance_name.is_none() {
+                                    ui.disable();
+                                    return;
+                                }
+                                for (username, avatar) in entries {
+                                    ui.selectable_value(
+                                        &mut selected_username,
+                                        Some(username.clone()),
+                                        (avatar, username),
+                                    );
+                                }
+                            });
                     });
+                });
+                row.show_trailing(ui, |ui| {
+                    self.render_buttons(ui, config, runtime, Some(instance_auth_backend));
+                });
                 if let Some(selected_username) = selected_username
                     && auth_profile.as_ref().map(|x| &x.username) != Some(&selected_username)
                 {
@@ -570,10 +995,11 @@ impl AuthState {
                     let auth_provider = get_auth_provider(instance_auth_backend);
                     let button_text = if !matches!(instance_auth_backend, AuthBackend::Offline) {
                         egui::RichText::new(
-                            LangMessage::AuthorizeUsing(auth_provider.get_name()).to_string(lang),
+                            &*LangMessage::AuthorizeUsing(auth_provider.get_name())
+                                .to_cached_string(lang),
                         )
                     } else {
-                        egui::RichText::new(LangMessage::AddOfflineAccount.to_string(lang))
+                        egui::RichText::new(&*LangMessage::AddOfflineAccount.to_cached_string(lang))
                     }
                     .size(20.0);
 
@@ -584,7 +1010,9 @@ impl AuthState {
                         let storage_entry = self.get_selected_storage_entry(config);
 
                         self.auth_status = AuthStatus::NotAuthorized;
-                        self.auth_message_provider = Arc::new(AuthMessageProvider::new(ctx));
+                        self.auth_message_provider = new_auth_message_provider(ctx);
+
+                        self.reauth_profile = None;
                         self.auth_task = Some(authenticate(
                             runtime,
                             storage_entry.as_ref().map(|x| x.auth_data.clone()),
@@ -596,36 +1024,57 @@ impl AuthState {
                 });
             }
         } else {
-            self.render_buttons(ui, config, runtime, instance_auth_backend);
-
             let mut all_entries = self.auth_storage.get_all_entries();
+            all_entries.sort();
+            let all_entries: Vec<_> = all_entries
+                .into_iter()
+                .map(|(id, username)| {
+                    let avatar = self.get_avatar(runtime, ctx, &launcher_dir, (&id, &username));
+                    (id, username, avatar)
+                })
+                .collect();
 
             let mut selected_account = auth_profile
                 .as_ref()
                 .map(|x| (x.auth_backend_id.clone(), x.username.clone()));
-            ComboBox::from_id_salt("select_account")
-                .selected_text(match &selected_account {
-                    Some((_, username)) => {
-                        Self::get_combobox_text(username, &self.auth_status, lang, dark_mode)
-                    }
-                    None => RichText::new(LangMessage::SelectAccount.to_string(lang))
-                        .color(colors::action(dark_mode)),
-                })
-                .width(ui.available_width())
-                .show_ui(ui, |ui| {
-                    if config.selected_instance_name.is_none() {
-                        ui.disable();
-                        return;
-                    }
-                    all_entries.sort();
-                    for (id, username) in all_entries {
-                        ui.selectable_value(
-                            &mut selected_account,
-                            Some((id.clone(), username.clone())),
-                            Self::get_account_display_name(&(id, username)),
-                        );
+            let row = TrailingRow::new(ui, "account_row");
+            row.show_fill(ui, |ui| {
+                ui.horizontal(|ui| {
+                    if let Some(avatar) = selected_avatar {
+                        ui.add(avatar);
                     }
+                    ComboBox::from_id_salt("select_account")
+                        .selected_text(match &selected_account {
+                            Some((_, username)) => Self::get_combobox_text(
+                                username,
+                                &self.auth_status,
+                                lang,
+                                dark_mode,
+                            ),
+                            None => {
+                                RichText::new(&*LangMessage::SelectAccount.to_cached_string(lang))
+                                    .color(colors::action(dark_mode))
+                            }
+                        })
+                        .width(ui.available_width())
+                        .show_ui(ui, |ui| {
+                            if config.selected_instance_name.is_none() {
+                                ui.disable();
+                                return;
+                            }
+                            for (id, username, avatar) in all_entries {
+                                ui.selectable_value(
+                                    &mut selected_account,
+                                    Some((id.clone(), username.clone())),
+                                    (avatar, Self::get_account_display_name(&(id, username))),
+                                );
+                            }
+                        });
                 });
+            });
+            row.show_trailing(ui, |ui| {
+                self.render_buttons(ui, config, runtime, instance_auth_backend);
+            });
             if let Some(selected_account) = selected_account
                 && auth_profile
                     .as_ref()
@@ -640,8 +1089,27 @@ impl AuthState {
             }
         }
 
+        self.render_auth_error_hint(ui, lang);
         self.render_new_account_window(ui, ctx, runtime, lang);
         self.render_auth_window(config, runtime, ui);
+        self.render_remove_account_window(ctx, config);
+        self.render_accounts_window(ctx, config, runtime);
+    }
+
+    // the account refreshed outside of the auth state, e.g. before a restart after a crash
+    pub fn save_refreshed_auth(
+        &mut self,
+        config: &Config,
+        auth_backend: &AuthBackend,
+        auth_data: AuthData,
+    ) {
+        self.auth_storage.insert(config, auth_backend, auth_data);
+    }
+
+    pub fn has_account(&self, auth_profile: &AuthProfile) -> bool {
+        self.auth_storage
+            .get_by_id(&auth_profile.auth_backend_id, &auth_profile.username)
+            .is_some()
     }
 
     pub fn get_auth_data(&self, config: &Config) -> Option<AuthData> {
@@ -670,6 +1138,9 @@ impl AuthState {
             self.auth_status,
             AuthStatus::AuthorizeErrorOffline
                 | AuthStatus::AuthorizeErrorTimeout
+                | AuthStatus::AuthorizeErrorClockSkew
+                | AuthStatus::AuthorizeErrorNoProfile
+                | AuthStatus::AuthorizeErrorFamilyConsent
                 | AuthStatus::AuthorizeError
         )
     }
@@ -0,0 +1,122 @@
+use std::time::{Duration, Instant};
+
+use image::Luma;
+use qrcode::QrCode;
+
+use crate::lang::{Lang, LangMessage};
+
+use super::colors;
+
+pub fn get_auth_url(message: &LangMessage) -> Option<&str> {
+    match message {
+        LangMessage::AuthMessage { url } => Some(url),
+        LangMessage::DeviceAuthMessage { url, .. } => Some(url),
+        _ => None,
+    }
+}
+
+pub fn get_auth_code(message: &LangMessage) -> Option<&str> {
+    match message {
+        LangMessage::DeviceAuthMessage { code, .. } => Some(code),
+        _ => None,
+    }
+}
+
+pub fn format_remaining(remaining: Duration) -> String {
+    let secs = remaining.as_secs();
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+fn render_qr_image(url: &str) -> egui::ColorImage {
+    let image = QrCode::new(url).unwrap().render::<Luma<u8>>().build();
+    let size = [image.width() as usize, image.height() as usize];
+    egui::ColorImage::from_gray(size, image.as_raw())
+}
+
+#[derive(Default)]
+pub struct AuthWindow {
+    qr_texture: Option<(String, egui::TextureHandle)>,
+}
+
+impl AuthWindow {
+    fn get_qr_texture(&mut self, ctx: &egui::Context, url: &str) -> &egui::TextureHandle {
+        if self
+            .qr_texture
+            .as_ref()
+            .is_none_or(|(cached_url, _)| cached_url != url)
+        {
+            let texture = ctx.load_texture(
+                "auth_qr",
+                render_qr_image(url),
+                egui::TextureOptions::NEAREST,
+            );
+            self.qr_texture = Some((url.to_string(), texture));
+        }
+        &self.qr_texture.as_ref().unwrap().1
+    }
+
+    // returns true if cancel was clicked
+    pub fn render(
+        &mut self,
+        ctx: &egui::Context,
+        lang: Lang,
+        message: &LangMessage,
+        expires_at: Option<Instant>,
+        connection_lost: bool,
+    ) -> bool {
+        let url = get_auth_url(message).unwrap();
+        let code = get_auth_code(message);
+
+        let mut cancel_clicked = false;
+        egui::Window::new(LangMessage::Authorization.to_string(lang)).show(ctx, |ui| {
+            ui.label(message.to_string(lang));
+            ui.hyperlink(url);
+            if connection_lost {
+                let dark_mode = ui.style().visuals.dark_mode;
+                ui.colored_label(
+                    colors::offline(dark_mode),
+                    LangMessage::AuthConnectionLost.to_string(lang),
+                );
+            }
+
+            ui.horizontal(|ui| {
+                if ui.button(LangMessage::CopyLink.to_string(lang)).clicked() {
+                    ui.ctx().copy_text(url.to_string());
+                }
+                if let Some(code) = code
+                    && ui.button(LangMessage::CopyCode.to_string(lang)).clicked()
+                {
+                    ui.ctx().copy_text(code.to_string());
+                }
+            });
+
+            if let Some(expires_at) = expires_at {
+                let remaining = expires_at.saturating_duration_since(Instant::now());
+                ui.label(
+                    LangMessage::AuthCodeExpiresIn(format_remaining(remaining)).to_string(lang),
+                );
+                ctx.request_repaint_after(Duration::from_secs(1));
+            }
+
+            let texture = self.get_qr_texture(ctx, url);
+            ui.add(egui::Image::new(texture));
+
+            if ui.button(LangMessage::Cancel.to_string(lang)).clicked() {
+                cancel_clicked = true;
+            }
+        });
+        cancel_clicked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_remaining() {
+        assert_eq!(format_remaining(Duration::from_secs(0)), "0:00");
+        assert_eq!(format_remaining(Duration::from_millis(65_900)), "1:05");
+        assert_eq!(format_remaining(Duration::from_secs(900)), "15:00");
+    }
+}
@@ -0,0 +1,260 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use image::{GenericImageView as _, imageops};
+use log::{debug, warn};
+use reqwest::Client;
+use shared::paths::get_skin_cache_path;
+use shared::version::extra_version_metadata::AuthBackend;
+use tokio::runtime::Runtime;
+
+use crate::auth::skins;
+use crate::auth::user_info::UserInfo;
+
+use super::background_task::{BackgroundTask, BackgroundTaskResult};
+
+pub const AVATAR_SIZE: egui::Vec2 = egui::Vec2::splat(16.0);
+const PLACEHOLDER_COLOR: egui::Color32 = egui::Color32::from_gray(110);
+const PLACEHOLDER_BORDER_COLOR: egui::Color32 = egui::Color32::from_gray(80);
+
+// the face with the hat layer on top, hd skins keep their resolution
+fn extract_head(skin: &image::DynamicImage) -> anyhow::Result<egui::ColorImage> {
+    let (width, height) = skin.dimensions();
+    if width < 64 || width % 64 != 0 || height < width / 2 {
+        anyhow::bail!("Invalid skin size {width}x{height}");
+    }
+    let scale = width / 64;
+    let mut head = skin
+        .crop_imm(8 * scale, 8 * scale, 8 * scale, 8 * scale)
+        .to_rgba8();
+    let hat = skin
+        .crop_imm(40 * scale, 8 * scale, 8 * scale, 8 * scale)
+        .to_rgba8();
+    imageops::overlay(&mut head, &hat, 0, 0);
+    Ok(egui::ColorImage::from_rgba_unmultiplied(
+        [head.width() as usize, head.height() as usize],
+        head.as_raw(),
+    ))
+}
+
+fn decode_head(data: &[u8]) -> anyhow::Result<egui::ColorImage> {
+    extract_head(&image::load_from_memory_with_format(
+        data,
+        image::ImageFormat::Png,
+    )?)
+}
+
+fn get_placeholder() -> egui::ColorImage {
+    let mut image = egui::ColorImage::filled([8, 8], PLACEHOLDER_COLOR);
+    for i in 0..8 {
+        for (x, y) in [(i, 0), (i, 7), (0, i), (7, i)] {
+            image[(x, y)] = PLACEHOLDER_BORDER_COLOR;
+        }
+    }
+    image
+}
+
+enum AvatarSource {
+    Cache,
+    Server,
+}
+
+struct AvatarResult {
+    uuid: String,
+    source: AvatarSource,
+    // None if there is no skin, the placeholder is shown then
+    image: Option<egui::ColorImage>,
+}
+
+fn load_cached_avatar(
+    runtime: &Runtime,
+    ctx: &egui::Context,
+    uuid: String,
+    cache_path: PathBuf,
+) -> BackgroundTask<Option<AvatarResult>> {
+    let fut = async move {
+        let data = tokio::fs::read(&cache_path).await.ok()?;
+        let image = decode_head(&data)
+            .inspect_err(|e| warn!("Failed to decode the cached skin {cache_path:?}:\n{e:?}"))
+            .ok();
+        Some(AvatarResult {
+            uuid,
+            source: AvatarSource::Cache,
+            image,
+        })
+    };
+
+    let ctx = ctx.clone();
+    BackgroundTask::with_callback(fut, runtime, Box::new(move || ctx.request_repaint()))
+}
+
+fn fetch_avatar(
+    runtime: &Runtime,
+    ctx: &egui::Context,
+    auth_backend: AuthBackend,
+    user_info: UserInfo,
+    cache_path: PathBuf,
+) -> BackgroundTask<Option<AvatarResult>> {
+    let fut = async move {
+        // offline, the cached skin stays
+        let skin = skins::fetch_skin(&Client::new(), &auth_backend, &user_info)
+            .await
+            .inspect_err(|e| debug!("Failed to fetch the skin of {}:\n{e:?}", user_info.username))
+            .ok()?;
+        let image = match skin {
+            Some(data) => {
+                let image = decode_head(&data)
+                    .inspect_err(|e| warn!("Invalid skin of {}:\n{e:?}", user_info.username))
+                    .ok()?;
+                if let Err(e) = tokio::fs::write(&cache_path, &data).await {
+                    warn!("Failed to cache the skin {cache_path:?}:\n{e:?}");
+                }
+                Some(image)
+            }
+            None => {
+                let _ = tokio::fs::remove_file(&cache_path).await;
+                None
+            }
+        };
+        Some(AvatarResult {
+            uuid: user_info.uuid,
+            source: AvatarSource::Server,
+            image,
+        })
+    };
+
+    let ctx = ctx.clone();
+    BackgroundTask::with_callback(fut, runtime, Box::new(move || ctx.request_repaint()))
+}
+
+// heads of the account skins, keyed by the account uuid
+pub struct AvatarState {
+    // None if the account has no skin
+    avatars: HashMap<String, Option<egui::TextureHandle>>,
+    // skins are fetched once per session, the disk cache covers the rest
+    requested: HashSet<String>,
+    tasks: Vec<BackgroundTask<Option<AvatarResult>>>,
+    placeholder: Option<egui::TextureHandle>,
+}
+
+impl AvatarState {
+    pub fn new() -> Self {
+        AvatarState {
+            avatars: HashMap::new(),
+            requested: HashSet::new(),
+            tasks: vec![],
+            placeholder: None,
+        }
+    }
+
+    pub fn update(&mut self, ctx: &egui::Context) {
+        let finished: Vec<_> = self
+            .tasks
+            .extract_if(.., |task| task.has_result())
+            .collect();
+        for task in finished {
+            let BackgroundTaskResult::Finished(Some(result)) = task.take_result() else {
+                continue;
+            };
+            // the cache loads faster, but a fetched skin may have won the race
+            if matches!(result.source, AvatarSource::Cache)
+                && self.avatars.contains_key(&result.uuid)
+            {
+                continue;
+            }
+            let texture = result.image.map(|image| {
+                ctx.load_texture(
+                    format!("avatar_{}", result.uuid),
+                    image,
+                    egui::TextureOptions::NEAREST,
+                )
+            });
+            self.avatars.insert(result.uuid, texture);
+        }
+    }
+
+    fn request(
+        &mut self,
+        runtime: &Runtime,
+        ctx: &egui::Context,
+        launcher_dir: &Path,
+        auth_backend: &AuthBackend,
+        user_info: &UserInfo,
+    ) {
+        if !self.requested.insert(user_info.uuid.clone()) {
+            return;
+        }
+        if matches!(auth_backend, AuthBackend::Offline) {
+            return;
+        }
+        let cache_path = get_skin_cache_path(launcher_dir, &user_info.uuid.replace('-', ""));
+        self.tasks.push(load_cached_avatar(
+            runtime,
+            ctx,
+            user_info.uuid.clone(),
+            cache_path.clone(),
+        ));
+        self.tasks.push(fetch_avatar(
+            runtime,
+            ctx,
+            auth_backend.clone(),
+            user_info.clone(),
+            cache_path,
+        ));
+    }
+
+    // the placeholder until the skin is loaded
+    pub fn get_avatar(
+        &mut self,
+        runtime: &Runtime,
+        ctx: &egui::Context,
+        launcher_dir: &Path,
+        auth_backend: &AuthBackend,
+        user_info: Option<&UserInfo>,
+    ) -> egui::Image<'static> {
+        if let Some(user_info) = user_info {
+            self.request(runtime, ctx, launcher_dir, auth_backend, user_info);
+        }
+        let texture = user_info
+            .and_then(|user_info| self.avatars.get(&user_info.uuid))
+            .and_then(|texture| texture.as_ref());
+        let texture = match texture {
+            Some(texture) => texture,
+            None => self.placeholder.get_or_insert_with(|| {
+                ctx.load_texture(
+                    "avatar_placeholder",
+                    get_placeholder(),
+                    egui::TextureOptions::NEAREST,
+                )
+            }),
+        };
+        egui::Image::new(texture).fit_to_exact_size(AVATAR_SIZE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_head() {
+        let mut skin = image::RgbaImage::new(64, 64);
+        for (x, y, pixel) in skin.enumerate_pixels_mut() {
+            *pixel = match (x, y) {
+                // the hat covers the top row of the face
+                (40..48, 8) => image::Rgba([0, 0, 255, 255]),
+                (8..16, 8..16) => image::Rgba([255, 0, 0, 255]),
+                _ => image::Rgba([0, 0, 0, 0]),
+            };
+        }
+        let head = extract_head(&image::DynamicImage::ImageRgba8(skin)).unwrap();
+        assert_eq!(head.size, [8, 8]);
+        assert_eq!(head[(3, 0)], egui::Color32::BLUE);
+        assert_eq!(head[(3, 1)], egui::Color32::RED);
+
+        // legacy 64x32 skins have the head at the same place
+        let legacy = image::DynamicImage::new_rgba8(64, 32);
+        assert!(extract_head(&legacy).is_ok());
+        assert!(extract_head(&image::DynamicImage::new_rgba8(16, 16)).is_err());
+    }
+}
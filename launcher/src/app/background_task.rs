@@ -1,4 +1,5 @@
 use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use tokio_util::sync::CancellationToken;
 
@@ -12,9 +13,29 @@ where
     T: Send + 'static,
 {
     result: Arc<Mutex<Option<BackgroundTaskResult<T>>>>, // nice
+    finished: Arc<AtomicBool>,
     cancellation_token: CancellationToken,
 }
 
+// cancel hook of a task that doesn't depend on its result type
+#[derive(Clone)]
+pub struct TaskHandle {
+    finished: Arc<AtomicBool>,
+    cancellation_token: CancellationToken,
+}
+
+impl TaskHandle {
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::SeqCst)
+    }
+
+    // the owner receives BackgroundTaskResult::Cancelled
+    pub fn cancel(&self) {
+        self.finished.store(true, Ordering::SeqCst);
+        self.cancellation_token.cancel();
+    }
+}
+
 impl<T> BackgroundTask<T>
 where
     T: Send + 'static,
@@ -24,13 +45,27 @@ where
         runtime: &tokio::runtime::Runtime,
         callback: Box<dyn FnOnce() + Send>,
     ) -> Self
+    where
+        F: Future<Output = T> + Send + 'static,
+    {
+        Self::with_cancellation_token(future, runtime, callback, CancellationToken::new())
+    }
+
+    // for futures that watch the token themselves, cancel() cancels it
+    pub fn with_cancellation_token<F>(
+        future: F,
+        runtime: &tokio::runtime::Runtime,
+        callback: Box<dyn FnOnce() + Send>,
+        cancellation_token: CancellationToken,
+    ) -> Self
     where
         F: Future<Output = T> + Send + 'static,
     {
         let result = Arc::new(Mutex::new(None));
-        let cancellation_token = CancellationToken::new();
+        let finished = Arc::new(AtomicBool::new(false));
 
         let result_clone = result.clone();
+        let finished_clone = finished.clone();
         let cancellation_token_clone = cancellation_token.clone();
 
         runtime.spawn(async move {
@@ -44,11 +79,13 @@ where
                             *result_lock = Some(BackgroundTaskResult::Finished(res));
                         }
                     }
+                    finished_clone.store(true, Ordering::SeqCst);
                     callback();
                 }
                 _ = cancellation_token_clone.cancelled() => {
                     let mut result_lock = result_clone.lock().unwrap();
                     *result_lock = Some(BackgroundTaskResult::Cancelled);
+                    finished_clone.store(true, Ordering::SeqCst);
                     callback();
                 }
             }
@@ -56,10 +93,18 @@ where
 
         BackgroundTask {
             result,
+            finished,
             cancellation_token,
         }
     }
 
+    pub fn get_handle(&self) -> TaskHandle {
+        TaskHandle {
+            finished: self.finished.clone(),
+            cancellation_token: self.cancellation_token.clone(),
+        }
+    }
+
     pub fn has_result(&self) -> bool {
         self.result.lock().unwrap().is_some()
     }
@@ -74,6 +119,7 @@ where
     pub fn cancel(&mut self) {
         let mut result_lock = self.result.lock().unwrap();
         *result_lock = Some(BackgroundTaskResult::Cancelled);
+        self.finished.store(true, Ordering::SeqCst);
         self.cancellation_token.cancel();
     }
 }
@@ -22,6 +22,14 @@ pub fn offline(dark_mode: bool) -> egui::Color32 {
     }
 }
 
+pub fn warning(dark_mode: bool) -> egui::Color32 {
+    if dark_mode {
+        egui::Color32::from_rgb(255, 200, 0)
+    } else {
+        egui::Color32::from_rgb(160, 112, 0)
+    }
+}
+
 pub fn in_progress(dark_mode: bool) -> egui::Color32 {
     if dark_mode {
         egui::Color32::LIGHT_BLUE
@@ -49,3 +57,35 @@ pub fn action(dark_mode: bool) -> egui::Color32 {
 pub fn ok(_dark_mode: bool) -> egui::Color32 {
     egui::Color32::PLACEHOLDER
 }
+
+pub fn up_to_date(dark_mode: bool) -> egui::Color32 {
+    if dark_mode {
+        egui::Color32::LIGHT_GREEN
+    } else {
+        egui::Color32::from_rgb(0, 128, 0)
+    }
+}
+
+pub fn outdated(dark_mode: bool) -> egui::Color32 {
+    if dark_mode {
+        egui::Color32::from_rgb(255, 165, 0)
+    } else {
+        egui::Color32::from_rgb(192, 96, 0)
+    }
+}
+
+pub fn not_installed(dark_mode: bool) -> egui::Color32 {
+    if dark_mode {
+        egui::Color32::GRAY
+    } else {
+        egui::Color32::DARK_GRAY
+    }
+}
+
+pub fn local_only(dark_mode: bool) -> egui::Color32 {
+    if dark_mode {
+        egui::Color32::from_rgb(100, 150, 255)
+    } else {
+        egui::Color32::from_rgb(0, 0, 192)
+    }
+}
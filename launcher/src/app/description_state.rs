@@ -0,0 +1,230 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use egui::RichText;
+use log::{error, info, warn};
+use shared::paths::get_description_cache_path;
+use shared::utils::is_connect_error;
+use shared::version::extra_version_metadata::InstanceDescription;
+use tokio::runtime::Runtime;
+
+use crate::utils::is_web_url;
+use crate::{
+    config::runtime_config::Config, lang::LangMessage,
+    version::complete_version_metadata::CompleteVersionMetadata,
+};
+
+use super::{
+    background_task::{BackgroundTask, BackgroundTaskResult},
+    colors,
+    markdown::{Block, parse_markdown, render_markdown},
+};
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+enum DescriptionStatus {
+    Loaded,
+    CachedOffline,
+    ErrorGetting,
+}
+
+struct DescriptionFetchResult {
+    instance_name: String,
+    description: InstanceDescription,
+    status: DescriptionStatus,
+    markdown: Option<String>,
+}
+
+struct LoadedDescription {
+    description: InstanceDescription,
+    status: DescriptionStatus,
+    blocks: Vec<Block>,
+}
+
+async fn download_description(url: &str) -> anyhow::Result<String> {
+    Ok(reqwest::get(url).await?.error_for_status()?.text().await?)
+}
+
+// falls back to the last downloaded copy if the url is unreachable
+async fn fetch_description(url: &str, cache_path: PathBuf) -> (DescriptionStatus, Option<String>) {
+    match download_description(url).await {
+        Ok(markdown) => {
+            if let Err(e) = tokio::fs::write(&cache_path, &markdown).await {
+                warn!("Failed to cache instance description:\n{e:?}");
+            }
+            (DescriptionStatus::Loaded, Some(markdown))
+        }
+        Err(e) => {
+            if is_connect_error(&e) {
+                info!("Instance description offline mode");
+            } else {
+                error!("Error getting instance description:\n{e:?}");
+            }
+            match tokio::fs::read_to_string(&cache_path).await {
+                Ok(markdown) => (DescriptionStatus::CachedOffline, Some(markdown)),
+                Err(_) => (DescriptionStatus::ErrorGetting, None),
+            }
+        }
+    }
+}
+
+fn get_description(
+    runtime: &Runtime,
+    instance_name: String,
+    description: InstanceDescription,
+    cache_path: PathBuf,
+    ctx: &egui::Context,
+) -> BackgroundTask<DescriptionFetchResult> {
+    let fut = async move {
+        let (status, markdown) = match &description.description_url {
+            Some(url) => fetch_description(url, cache_path).await,
+            None => (DescriptionStatus::Loaded, None),
+        };
+        let markdown = markdown.or_else(|| description.description_markdown.clone());
+        DescriptionFetchResult {
+            instance_name,
+            description,
+            status,
+            markdown,
+        }
+    };
+
+    let ctx = ctx.clone();
+    BackgroundTask::with_callback(fut, runtime, Box::new(move || ctx.request_repaint()))
+}
+
+pub struct DescriptionState {
+    fetch_task: Option<BackgroundTask<DescriptionFetchResult>>,
+    descriptions: HashMap<String, LoadedDescription>,
+}
+
+impl DescriptionState {
+    pub fn new() -> Self {
+        DescriptionState {
+            fetch_task: None,
+            descriptions: HashMap::new(),
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        runtime: &Runtime,
+        config: &Config,
+        version_metadata: Option<&CompleteVersionMetadata>,
+        ctx: &egui::Context,
+    ) {
+        if let Some(task) = self.fetch_task.as_ref()
+            && task.has_result()
+        {
+            let task = self.fetch_task.take().unwrap();
+            if let BackgroundTaskResult::Finished(result) = task.take_result() {
+                let blocks = result
+                    .markdown
+                    .as_deref()
+                    .map(parse_markdown)
+                    .unwrap_or_default();
+                self.descriptions.insert(
+                    result.instance_name,
+                    LoadedDescription {
+                        description: result.description,
+                        status: result.status,
+                        blocks,
+                    },
+                );
+            }
+        }
+
+        let Some(instance_name) = config.selected_instance_name.as_ref() else {
+            return;
+        };
+        let Some(description) = version_metadata.and_then(|metadata| metadata.get_description())
+        else {
+            self.descriptions.remove(instance_name);
+            return;
+        };
+        if self
+            .descriptions
+            .get(instance_name)
+            .is_some_and(|loaded| &loaded.description == description)
+        {
+            return;
+        }
+
+        if description.description_url.is_none() {
+            // a changelog link alone has nothing to parse
+            let blocks = description
+                .description_markdown
+                .as_deref()
+                .map(parse_markdown)
+                .unwrap_or_default();
+            self.descriptions.insert(
+                instance_name.clone(),
+                LoadedDescription {
+                    description: description.clone(),
+                    status: DescriptionStatus::Loaded,
+                    blocks,
+                },
+            );
+        } else if self.fetch_task.is_none() {
+            // a task for the previously selected instance finishes first
+            let cache_path = get_description_cache_path(&config.get_launcher_dir(), instance_name);
+            self.fetch_task = Some(get_description(
+                runtime,
+                instance_name.clone(),
+                description.clone(),
+                cache_path,
+                ctx,
+            ));
+        }
+    }
+
+    pub fn render_ui(&self, ui: &mut egui::Ui, config: &Config) {
+        let Some(loaded) = config
+            .selected_instance_name
+            .as_ref()
+            .and_then(|name| self.descriptions.get(name))
+        else {
+            return;
+        };
+        let lang = config.lang;
+        let dark_mode = ui.style().visuals.dark_mode;
+
+        ui.add_space(5.0);
+        egui::CollapsingHeader::new(LangMessage::InstanceDescription.to_string(lang))
+            .id_salt("instance_description")
+            .default_open(true)
+            .show(ui, |ui| {
+                match loaded.status {
+                    DescriptionStatus::Loaded => {}
+                    DescriptionStatus::CachedOffline => {
+                        ui.label(
+                            RichText::new(LangMessage::DescriptionOffline.to_string(lang))
+                                .color(colors::offline(dark_mode)),
+                        );
+                    }
+                    DescriptionStatus::ErrorGetting => {
+                        ui.label(
+                            RichText::new(LangMessage::ErrorGettingDescription.to_string(lang))
+                                .color(colors::error(dark_mode)),
+                        );
+                    }
+                }
+
+                if let Some(changelog_url) = &loaded.description.changelog_url
+                    && is_web_url(changelog_url)
+                {
+                    ui.hyperlink_to(LangMessage::Changelog.to_string(lang), changelog_url);
+                }
+
+                // takes the space left in the central panel instead of growing the window
+                egui::ScrollArea::vertical()
+                    .max_height(ui.available_height())
+                    .auto_shrink([false, true])
+                    .show(ui, |ui| {
+                        render_markdown(ui, &loaded.blocks);
+                    });
+            });
+    }
+
+    pub fn clear(&mut self) {
+        self.descriptions.clear();
+    }
+}
@@ -1,19 +1,27 @@
 use egui::RichText;
-use log::error;
+use log::{error, info, warn};
+use shared::adaptive_download::{DownloadHealth, MirrorRanking};
+use shared::paths::get_minecraft_dir;
 use shared::progress::ProgressBar;
 use shared::utils::is_connect_error;
-use std::path::Path;
+use shared::version::version_manifest::VersionInfo;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 use tokio::runtime::Runtime;
+use tokio::sync::mpsc;
 
 use crate::config::runtime_config::Config;
 use crate::lang::{Lang, LangMessage};
+use crate::utils::windows::{self, AntivirusSignals};
 use crate::version::complete_version_metadata::CompleteVersionMetadata;
-use crate::version::sync;
+use crate::version::dedup;
+use crate::version::instance_storage::InstanceStorage;
+use crate::version::sync::{self, DeleteExtraRequest, SyncError, SyncPlan};
 
 use super::background_task::{BackgroundTask, BackgroundTaskResult};
 use super::colors;
 use super::progress_bar::GuiProgressBar;
+use super::task_registry::{TaskKey, TaskRegistry};
 
 #[derive(Clone, PartialEq)]
 enum InstanceSyncStatus {
@@ -21,31 +29,181 @@ enum InstanceSyncStatus {
     Synced,
     SyncError,
     SyncErrorOffline,
+    // running in another launcher process
+    InstanceRunning,
+}
+
+// a sync requested while the game of the same instance is running
+struct BlockedSync {
+    metadata: Arc<CompleteVersionMetadata>,
+    force_overwrite: bool,
+    kill_clicked: bool,
+    killing: bool,
+}
+
+// an outdated instance synced without user interaction, possibly not the selected one
+#[derive(Clone)]
+pub struct AutoSyncRequest {
+    pub version_info: VersionInfo,
+    pub public_key: Option<String>,
+}
+
+// result of "Check only", its plan is reused by the next sync of the instance
+enum UpdateCheck {
+    Checked(Box<SyncPlan>),
+    Error {
+        instance_name: String,
+        offline: bool,
+    },
+}
+
+struct SyncOutcome {
+    content_hash: String,
+    downloaded_files: usize,
+    defender_excluded: Option<bool>,
+    // the download server base and its mirror ranking, cached in the config
+    mirror_ranking: Option<(String, MirrorRanking)>,
+}
+
+enum SyncSource {
+    Metadata(Arc<CompleteVersionMetadata>),
+    // metadata is fetched by the sync task
+    Auto(Box<AutoSyncRequest>),
+}
+
+// what the sync task reports back to the state besides its progress
+struct SyncFeedback {
+    delete_extra_sender: mpsc::UnboundedSender<DeleteExtraRequest>,
+    download_health: DownloadHealth,
 }
 
 fn sync_instance(
+    runtime: &Runtime,
+    source: SyncSource,
+    force_overwrite: bool,
+    config: &Config,
+    progress_bar: Arc<dyn ProgressBar<LangMessage>>,
+    feedback: SyncFeedback,
+    cached_plan: Option<SyncPlan>,
+) -> BackgroundTask<anyhow::Result<SyncOutcome>> {
+    let launcher_dir = config.get_launcher_dir();
+    let assets_dir = config.get_assets_dir();
+    let hash_limits = config.get_hash_limits();
+    let sync_settings = config.get_sync_settings();
+    let deduplicate_files = config.deduplicate_files;
+    let mirror_rankings = config.mirror_rankings.clone();
+
+    let progress_bar_clone = progress_bar.clone();
+    let fut = async move {
+        let defender_excluded = if cfg!(windows) {
+            windows::is_excluded_from_defender(&launcher_dir)
+        } else {
+            None
+        };
+        let instance_metadata = match source {
+            SyncSource::Metadata(metadata) => metadata,
+            SyncSource::Auto(request) => {
+                progress_bar_clone.set_message(LangMessage::GettingMetadata);
+                Arc::new(
+                    CompleteVersionMetadata::read_or_download(
+                        &request.version_info,
+                        &launcher_dir,
+                        request.public_key.as_deref(),
+                    )
+                    .await?,
+                )
+            }
+        };
+        let plan = match cached_plan
+            .filter(|plan| plan.is_valid_for(&instance_metadata, force_overwrite))
+        {
+            Some(plan) => {
+                info!(
+                    "Reusing the last update check of {}",
+                    plan.get_instance_name()
+                );
+                plan
+            }
+            None => {
+                sync::ensure_not_running(&launcher_dir, instance_metadata.get_name())?;
+                progress_bar_clone.set_message(LangMessage::CheckingFiles);
+                sync::plan_sync(
+                    &instance_metadata,
+                    force_overwrite,
+                    &launcher_dir,
+                    &assets_dir,
+                    progress_bar_clone.clone(),
+                    hash_limits,
+                    &mirror_rankings,
+                )
+                .await?
+            }
+        };
+        let downloaded_files = plan.get_file_count();
+        let mirror_ranking = plan
+            .get_mirror_ranking()
+            .map(|(base, ranking)| (base.to_string(), ranking.clone()));
+        let content_hash = sync::execute_sync(
+            &instance_metadata,
+            plan,
+            &launcher_dir,
+            progress_bar_clone.clone(),
+            feedback.delete_extra_sender,
+            &feedback.download_health,
+            sync_settings,
+        )
+        .await?;
+
+        if deduplicate_files {
+            progress_bar_clone.set_message(LangMessage::DeduplicatingFiles);
+            if let Err(e) = dedup::deduplicate_files(&launcher_dir, hash_limits).await {
+                warn!("Failed to deduplicate files:\n{e:?}");
+            }
+        }
+        Ok(SyncOutcome {
+            content_hash,
+            downloaded_files,
+            defender_excluded,
+            mirror_ranking,
+        })
+    };
+
+    BackgroundTask::with_callback(
+        fut,
+        runtime,
+        Box::new(move || {
+            progress_bar.finish();
+        }),
+    )
+}
+
+// checks which files a sync would download without downloading them
+fn check_instance(
     runtime: &Runtime,
     instance_metadata: Arc<CompleteVersionMetadata>,
     force_overwrite: bool,
-    launcher_dir: &Path,
-    assets_dir: &Path,
+    config: &Config,
     progress_bar: Arc<dyn ProgressBar<LangMessage>>,
-) -> BackgroundTask<anyhow::Result<()>> {
-    let launcher_dir = launcher_dir.to_path_buf();
-    let assets_dir = assets_dir.to_path_buf();
+) -> BackgroundTask<(String, anyhow::Result<SyncPlan>)> {
+    let launcher_dir = config.get_launcher_dir();
+    let assets_dir = config.get_assets_dir();
+    let hash_limits = config.get_hash_limits();
+    let mirror_rankings = config.mirror_rankings.clone();
 
-    let instance_metadata = instance_metadata.clone();
     let progress_bar_clone = progress_bar.clone();
     let fut = async move {
         progress_bar_clone.set_message(LangMessage::CheckingFiles);
-        sync::sync_instance(
+        let result = sync::plan_sync(
             &instance_metadata,
             force_overwrite,
             &launcher_dir,
             &assets_dir,
             progress_bar_clone,
+            hash_limits,
+            &mirror_rankings,
         )
-        .await
+        .await;
+        (instance_metadata.get_name().to_string(), result)
     };
 
     BackgroundTask::with_callback(
@@ -59,15 +217,40 @@ fn sync_instance(
 
 pub struct InstanceSyncState {
     status: InstanceSyncStatus,
-    instance_sync_task: Option<BackgroundTask<anyhow::Result<()>>>,
+    instance_sync_task: Option<BackgroundTask<anyhow::Result<SyncOutcome>>>,
     instance_sync_progress_bar: Arc<GuiProgressBar>,
 
     instance_sync_window_open: bool,
     force_overwrite_checked: bool,
+
+    check_task: Option<BackgroundTask<(String, anyhow::Result<SyncPlan>)>>,
+    check_progress_bar: Arc<GuiProgressBar>,
+    update_check: Option<UpdateCheck>,
+
+    syncing_instance_name: Option<String>,
+    delete_extra_receiver: Option<mpsc::UnboundedReceiver<DeleteExtraRequest>>,
+    pending_delete_extra: Option<DeleteExtraRequest>,
+    download_health: DownloadHealth,
+    always_allow_delete_extra_checked: bool,
+
+    running_instance_name: Option<String>,
+    blocked_sync: Option<BlockedSync>,
+
+    auto_sync_queue: VecDeque<AutoSyncRequest>,
+    // set while the running task is an auto-sync
+    current_auto_sync: Option<AutoSyncRequest>,
+
+    // content hashes synced in this session
+    synced_content_hashes: HashMap<String, String>,
+    antivirus_notice_open: bool,
+    // shown at most once per session
+    antivirus_notice_shown: bool,
+
+    task_registry: TaskRegistry,
 }
 
 impl InstanceSyncState {
-    pub fn new(ctx: &egui::Context) -> Self {
+    pub fn new(ctx: &egui::Context, task_registry: TaskRegistry) -> Self {
         let instance_sync_progress_bar = Arc::new(GuiProgressBar::new(ctx));
 
         InstanceSyncState {
@@ -77,40 +260,126 @@ impl InstanceSyncState {
 
             instance_sync_window_open: false,
             force_overwrite_checked: false,
+
+            check_task: None,
+            check_progress_bar: Arc::new(GuiProgressBar::new(ctx)),
+            update_check: None,
+
+            syncing_instance_name: None,
+            delete_extra_receiver: None,
+            pending_delete_extra: None,
+            download_health: DownloadHealth::new(),
+            always_allow_delete_extra_checked: false,
+
+            running_instance_name: None,
+            blocked_sync: None,
+
+            auto_sync_queue: VecDeque::new(),
+            current_auto_sync: None,
+
+            synced_content_hashes: HashMap::new(),
+            antivirus_notice_open: false,
+            antivirus_notice_shown: false,
+
+            task_registry,
         }
     }
 
-    pub fn update(&mut self) -> bool {
+    // returns the instance name and its content hash once it has been synced,
+    // the status is only changed if the synced instance is the selected one
+    pub fn update(&mut self, config: &mut Config) -> Option<(String, String)> {
+        let selected_instance_name = config.selected_instance_name.clone();
+        let selected_instance_name = selected_instance_name.as_deref();
+        if let Some(task) = self.check_task.as_ref()
+            && task.has_result()
+        {
+            let task = self.check_task.take().unwrap();
+            if let BackgroundTaskResult::Finished((instance_name, result)) = task.take_result() {
+                self.update_check = Some(match result {
+                    Ok(plan) => {
+                        if let Some((base, ranking)) = plan.get_mirror_ranking() {
+                            config.cache_mirror_ranking(base, ranking);
+                        }
+                        UpdateCheck::Checked(Box::new(plan))
+                    }
+                    Err(e) => {
+                        let offline = is_connect_error(&e);
+                        if !offline {
+                            error!("Error checking instance {instance_name}:\n{e:?}");
+                        }
+                        UpdateCheck::Error {
+                            instance_name,
+                            offline,
+                        }
+                    }
+                });
+            }
+        }
+
         if let Some(task) = self.instance_sync_task.as_ref()
             && task.has_result()
         {
             self.instance_sync_window_open = false;
+            self.delete_extra_receiver = None;
+            self.pending_delete_extra = None;
+            self.current_auto_sync = None;
+            let instance_name = self.syncing_instance_name.clone().unwrap_or_default();
+            let selected = selected_instance_name == Some(instance_name.as_str());
             let task = self.instance_sync_task.take();
-            match task.unwrap().take_result() {
-                BackgroundTaskResult::Finished(result) => {
-                    self.status = match result {
-                        Ok(()) => InstanceSyncStatus::Synced,
-                        Err(e) => {
-                            if is_connect_error(&e) {
-                                InstanceSyncStatus::SyncErrorOffline
-                            } else {
-                                error!("Error syncing instance:\n{e:?}");
-                                InstanceSyncStatus::SyncError
-                            }
+            let (status, synced) = match task.unwrap().take_result() {
+                BackgroundTaskResult::Finished(result) => match result {
+                    Ok(outcome) => {
+                        self.check_antivirus(&instance_name, &outcome);
+                        if let Some((base, ranking)) = &outcome.mirror_ranking {
+                            config.cache_mirror_ranking(base, ranking);
                         }
-                    };
-                }
-                BackgroundTaskResult::Cancelled => {
-                    self.status = InstanceSyncStatus::NotSynced;
-                }
-            }
-
-            if self.status == InstanceSyncStatus::Synced {
-                return true;
+                        self.synced_content_hashes
+                            .insert(instance_name.clone(), outcome.content_hash.clone());
+                        (
+                            InstanceSyncStatus::Synced,
+                            Some((instance_name, outcome.content_hash)),
+                        )
+                    }
+                    Err(e) => {
+                        let status = if matches!(
+                            e.downcast_ref::<SyncError>(),
+                            Some(SyncError::InstanceRunning(_))
+                        ) {
+                            InstanceSyncStatus::InstanceRunning
+                        } else if is_connect_error(&e) {
+                            InstanceSyncStatus::SyncErrorOffline
+                        } else {
+                            error!("Error syncing instance {instance_name}:\n{e:?}");
+                            InstanceSyncStatus::SyncError
+                        };
+                        (status, None)
+                    }
+                },
+                BackgroundTaskResult::Cancelled => (InstanceSyncStatus::NotSynced, None),
+            };
+            if selected {
+                self.status = status;
             }
+            return synced;
         }
 
-        false
+        None
+    }
+
+    fn check_antivirus(&mut self, instance_name: &str, outcome: &SyncOutcome) {
+        if !cfg!(windows) || self.antivirus_notice_shown {
+            return;
+        }
+        let signals = AntivirusSignals {
+            downloaded_files: outcome.downloaded_files,
+            synced_before: self.synced_content_hashes.get(instance_name)
+                == Some(&outcome.content_hash),
+            excluded: outcome.defender_excluded,
+        };
+        if windows::should_warn_about_antivirus(&signals) {
+            self.antivirus_notice_open = true;
+            self.antivirus_notice_shown = true;
+        }
     }
 
     pub fn reset_status(&mut self) {
@@ -129,18 +398,154 @@ impl InstanceSyncState {
         config: &Config,
         ctx: &egui::Context,
     ) {
-        self.instance_sync_progress_bar = Arc::new(GuiProgressBar::new(ctx));
+        if self.running_instance_name.as_deref() == Some(selected_version_metadata.get_name()) {
+            self.blocked_sync = Some(BlockedSync {
+                metadata: selected_version_metadata,
+                force_overwrite,
+                kill_clicked: false,
+                killing: false,
+            });
+            return;
+        }
+
+        // an interrupted auto-sync is retried after this one
+        if let Some(request) = self.current_auto_sync.take() {
+            self.auto_sync_queue.push_front(request);
+        }
+        let instance_name = selected_version_metadata.get_name().to_string();
+        self.start_sync(
+            runtime,
+            SyncSource::Metadata(selected_version_metadata),
+            instance_name,
+            force_overwrite,
+            config,
+            ctx,
+        );
+    }
+
+    fn start_sync(
+        &mut self,
+        runtime: &Runtime,
+        source: SyncSource,
+        instance_name: String,
+        force_overwrite: bool,
+        config: &Config,
+        ctx: &egui::Context,
+    ) {
         if let Some(mut task) = self.instance_sync_task.take() {
             task.cancel();
         }
-        self.instance_sync_task = Some(sync_instance(
+        let key = TaskKey::SyncInstance(instance_name.clone());
+        if self.task_registry.is_running(&key) {
+            warn!("{instance_name} is already being synced");
+            self.current_auto_sync = None;
+            return;
+        }
+        self.instance_sync_progress_bar = Arc::new(GuiProgressBar::new(ctx));
+        let (delete_extra_sender, delete_extra_receiver) = mpsc::unbounded_channel();
+        let cached_plan = self.take_sync_plan(&instance_name);
+        self.syncing_instance_name = Some(instance_name);
+        self.delete_extra_receiver = Some(delete_extra_receiver);
+        self.pending_delete_extra = None;
+        self.download_health = DownloadHealth::new();
+        let task = sync_instance(
             runtime,
-            selected_version_metadata,
+            source,
             force_overwrite,
-            &config.get_launcher_dir(),
-            &config.get_assets_dir(),
+            config,
             self.instance_sync_progress_bar.clone(),
-        ));
+            SyncFeedback {
+                delete_extra_sender,
+                download_health: self.download_health.clone(),
+            },
+            cached_plan,
+        );
+        self.task_registry
+            .register(key, &task, Some(self.instance_sync_progress_bar.clone()));
+        self.instance_sync_task = Some(task);
+    }
+
+    fn take_sync_plan(&mut self, instance_name: &str) -> Option<SyncPlan> {
+        match self.update_check.take() {
+            Some(UpdateCheck::Checked(plan)) if plan.get_instance_name() == instance_name => {
+                Some(*plan)
+            }
+            update_check => {
+                self.update_check = update_check;
+                None
+            }
+        }
+    }
+
+    fn check_updates(
+        &mut self,
+        runtime: &Runtime,
+        selected_version_metadata: Arc<CompleteVersionMetadata>,
+        config: &Config,
+        ctx: &egui::Context,
+    ) {
+        let key = TaskKey::CheckInstance(selected_version_metadata.get_name().to_string());
+        if self.task_registry.is_running(&key) {
+            return;
+        }
+        self.update_check = None;
+        self.check_progress_bar = Arc::new(GuiProgressBar::new(ctx));
+        let task = check_instance(
+            runtime,
+            selected_version_metadata,
+            self.force_overwrite_checked,
+            config,
+            self.check_progress_bar.clone(),
+        );
+        self.task_registry
+            .register(key, &task, Some(self.check_progress_bar.clone()));
+        self.check_task = Some(task);
+    }
+
+    pub fn queue_auto_sync(&mut self, requests: Vec<AutoSyncRequest>) {
+        for request in requests {
+            let name = request.version_info.get_name();
+            let queued = self
+                .auto_sync_queue
+                .iter()
+                .chain(self.current_auto_sync.iter())
+                .any(|queued| queued.version_info.get_name() == name);
+            if !queued {
+                self.auto_sync_queue.push_back(request);
+            }
+        }
+    }
+
+    // starts the next queued auto-sync, one at a time
+    pub fn update_auto_sync(&mut self, runtime: &Runtime, config: &Config, ctx: &egui::Context) {
+        if config.pause_auto_sync {
+            self.auto_sync_queue.clear();
+            return;
+        }
+        if self.instance_sync_task.is_some()
+            || self.blocked_sync.is_some()
+            || self.instance_sync_window_open
+        {
+            return;
+        }
+        while let Some(request) = self.auto_sync_queue.pop_front() {
+            let instance_name = request.version_info.get_name();
+            if self.running_instance_name.as_ref() == Some(&instance_name) {
+                info!("Skipping auto-sync of {instance_name}, the game is running");
+                continue;
+            }
+            info!("Auto-syncing {instance_name}");
+            self.current_auto_sync = Some(request.clone());
+            self.start_sync(
+                runtime,
+                SyncSource::Auto(Box::new(request)),
+                instance_name,
+                false,
+                config,
+                ctx,
+            );
+            break;
+        }
     }
 
     pub fn schedule_sync_if_needed(
@@ -154,7 +559,8 @@ impl InstanceSyncState {
         match &self.status {
             InstanceSyncStatus::NotSynced
             | InstanceSyncStatus::SyncError
-            | InstanceSyncStatus::SyncErrorOffline => {
+            | InstanceSyncStatus::SyncErrorOffline
+            | InstanceSyncStatus::InstanceRunning => {
                 self.schedule_sync(
                     runtime,
                     selected_version_metadata,
@@ -173,21 +579,25 @@ impl InstanceSyncState {
 
         ui.label(match &self.status {
             InstanceSyncStatus::NotSynced => {
-                RichText::new(LangMessage::InstanceNotSynced.to_string(lang))
+                RichText::new(&*LangMessage::InstanceNotSynced.to_cached_string(lang))
                     .color(colors::action(dark_mode))
             }
             InstanceSyncStatus::Synced => {
-                RichText::new(LangMessage::InstanceSynced.to_string(lang))
+                RichText::new(&*LangMessage::InstanceSynced.to_cached_string(lang))
                     .color(colors::ok(dark_mode))
             }
             InstanceSyncStatus::SyncError => {
-                RichText::new(LangMessage::InstanceSyncError.to_string(lang))
+                RichText::new(&*LangMessage::InstanceSyncError.to_cached_string(lang))
                     .color(colors::error(dark_mode))
             }
             InstanceSyncStatus::SyncErrorOffline => {
-                RichText::new(LangMessage::NoConnectionToSyncServer.to_string(lang))
+                RichText::new(&*LangMessage::NoConnectionToSyncServer.to_cached_string(lang))
                     .color(colors::offline(dark_mode))
             }
+            InstanceSyncStatus::InstanceRunning => {
+                RichText::new(&*LangMessage::InstanceRunningElsewhere.to_cached_string(lang))
+                    .color(colors::error(dark_mode))
+            }
         });
     }
 
@@ -197,9 +607,59 @@ impl InstanceSyncState {
         runtime: &Runtime,
         config: &Config,
         selected_version_metadata: Option<Arc<CompleteVersionMetadata>>,
+        instance_storage: &mut InstanceStorage,
     ) {
         self.render_sync_window(ui, runtime, config, selected_version_metadata);
+        self.render_blocked_sync_window(ui, config.lang);
         self.render_progress_bar_window(ui, config.lang);
+        self.render_delete_extra_window(ui, runtime, config, instance_storage);
+        self.render_antivirus_notice(ui, config);
+    }
+
+    pub fn is_window_open(&self) -> bool {
+        self.instance_sync_window_open || self.blocked_sync.is_some()
+    }
+
+    pub fn close_window(&mut self) {
+        if self.blocked_sync.is_some() {
+            self.blocked_sync = None;
+        } else {
+            self.instance_sync_window_open = false;
+        }
+    }
+
+    // starts a sync blocked by the game once the game is killed
+    pub fn set_running_instance(
+        &mut self,
+        runtime: &Runtime,
+        config: &Config,
+        ctx: &egui::Context,
+        running_instance_name: Option<&str>,
+    ) {
+        self.running_instance_name = running_instance_name.map(String::from);
+        if let Some(blocked_sync) = self.blocked_sync.take_if(|blocked_sync| {
+            blocked_sync.killing && running_instance_name != Some(blocked_sync.metadata.get_name())
+        }) {
+            self.schedule_sync(
+                runtime,
+                blocked_sync.metadata,
+                blocked_sync.force_overwrite,
+                config,
+                ctx,
+            );
+        }
+    }
+
+    // true once after "Kill and sync" is clicked
+    pub fn take_kill_request(&mut self) -> bool {
+        match self.blocked_sync.as_mut() {
+            Some(blocked_sync) if blocked_sync.kill_clicked => {
+                blocked_sync.kill_clicked = false;
+                blocked_sync.killing = true;
+                true
+            }
+            _ => false,
+        }
     }
 
     pub fn render_sync_button(
@@ -208,9 +668,17 @@ impl InstanceSyncState {
         runtime: &Runtime,
         config: &Config,
         selected_version_metadata: Option<Arc<CompleteVersionMetadata>>,
+        pinned: bool,
     ) {
         let lang = config.lang;
 
+        if pinned {
+            ui.add_enabled(
+                false,
+                egui::Button::new(LangMessage::PinnedUnpinToUpdate.to_string(lang)),
+            );
+            return;
+        }
         if ui
             .add_enabled(
                 self.instance_sync_task.is_none()
@@ -223,7 +691,8 @@ impl InstanceSyncState {
             match &self.status {
                 InstanceSyncStatus::NotSynced
                 | InstanceSyncStatus::SyncError
-                | InstanceSyncStatus::SyncErrorOffline => {
+                | InstanceSyncStatus::SyncErrorOffline
+                | InstanceSyncStatus::InstanceRunning => {
                     self.schedule_sync(
                         runtime,
                         selected_version_metadata.clone().unwrap(),
@@ -259,22 +728,43 @@ impl InstanceSyncState {
                     );
                     ui.label(LangMessage::ForceOverwriteWarning.to_string(lang));
 
+                    let enabled = selected_version_metadata.is_some() && self.check_task.is_none();
                     if ui
                         .add_enabled(
-                            selected_version_metadata.is_some(),
+                            enabled,
                             egui::Button::new(LangMessage::SyncInstance.to_string(lang)),
                         )
                         .clicked()
                     {
                         self.schedule_sync(
                             runtime,
-                            selected_version_metadata.unwrap(),
+                            selected_version_metadata.clone().unwrap(),
                             self.force_overwrite_checked,
                             config,
                             ui.ctx(),
                         );
                         close_sync_window = true;
                     }
+                    if ui
+                        .add_enabled(
+                            enabled,
+                            egui::Button::new(LangMessage::CheckOnly.to_string(lang)),
+                        )
+                        .clicked()
+                    {
+                        self.check_updates(
+                            runtime,
+                            selected_version_metadata.clone().unwrap(),
+                            config,
+                            ui.ctx(),
+                        );
+                    }
+
+                    if self.check_task.is_some() {
+                        self.check_progress_bar.render(ui, lang);
+                    } else if let Some(metadata) = &selected_version_metadata {
+                        self.render_update_check(ui, metadata.get_name(), lang);
+                    }
                 });
             });
         self.instance_sync_window_open = instance_sync_window_open;
@@ -283,13 +773,102 @@ impl InstanceSyncState {
         }
     }
 
+    fn render_update_check(&self, ui: &mut egui::Ui, instance_name: &str, lang: Lang) {
+        let dark_mode = ui.style().visuals.dark_mode;
+        match &self.update_check {
+            Some(UpdateCheck::Checked(plan)) if plan.get_instance_name() == instance_name => {
+                ui.label(if plan.get_file_count() == 0 {
+                    RichText::new(LangMessage::NoUpdatesFound.to_string(lang))
+                        .color(colors::ok(dark_mode))
+                } else {
+                    RichText::new(
+                        LangMessage::UpdateAvailable {
+                            files: plan.get_file_count(),
+                            size: plan.get_download_size(),
+                            size_unknown: plan.has_unknown_sizes(),
+                        }
+                        .to_string(lang),
+                    )
+                    .color(colors::action(dark_mode))
+                });
+            }
+            Some(UpdateCheck::Error {
+                instance_name: error_instance_name,
+                offline,
+            }) if error_instance_name == instance_name => {
+                ui.label(if *offline {
+                    RichText::new(LangMessage::NoConnectionToSyncServer.to_string(lang))
+                        .color(colors::offline(dark_mode))
+                } else {
+                    RichText::new(LangMessage::InstanceCheckError.to_string(lang))
+                        .color(colors::error(dark_mode))
+                });
+            }
+            _ => {}
+        }
+    }
+
+    fn render_blocked_sync_window(&mut self, ui: &mut egui::Ui, lang: Lang) {
+        let Some(blocked_sync) = self.blocked_sync.as_mut() else {
+            return;
+        };
+        let mut cancel_clicked = false;
+        egui::Window::new(LangMessage::SyncInstance.to_string(lang))
+            .collapsible(false)
+            .show(ui.ctx(), |ui| {
+                ui.label(LangMessage::InstanceRunningSync.to_string(lang));
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(
+                            !blocked_sync.kill_clicked && !blocked_sync.killing,
+                            egui::Button::new(LangMessage::KillAndSync.to_string(lang)),
+                        )
+                        .clicked()
+                    {
+                        blocked_sync.kill_clicked = true;
+                    }
+                    if ui.button(LangMessage::Cancel.to_string(lang)).clicked() {
+                        cancel_clicked = true;
+                    }
+                });
+            });
+        if cancel_clicked {
+            self.blocked_sync = None;
+        }
+    }
+
     fn render_progress_bar_window(&mut self, ui: &mut egui::Ui, lang: Lang) {
         if self.instance_sync_task.is_some() {
             egui::Window::new(LangMessage::InstanceSyncProgress.to_string(lang)).show(
                 ui.ctx(),
                 |ui| {
                     ui.vertical_centered(|ui| {
-                        self.instance_sync_progress_bar.render(ui, lang);
+                        if self.current_auto_sync.is_some()
+                            && let Some(instance_name) = &self.syncing_instance_name
+                        {
+                            ui.label(
+                                LangMessage::AutoSyncingInstance(instance_name.clone())
+                                    .to_string(lang),
+                            );
+                        }
+                        if let Some(instance_name) = &self.syncing_instance_name {
+                            let key = TaskKey::SyncInstance(instance_name.clone());
+                            self.task_registry.render_progress(ui, &key, lang);
+                        }
+                        if let Some(mirror) = self.download_health.get_mirror() {
+                            ui.label(LangMessage::DownloadingFrom(mirror).to_string(lang));
+                        }
+                        if let Some(host) = self.download_health.get_unstable_host() {
+                            let failure_percent = (host.get_failure_rate() * 100.0).round() as u64;
+                            ui.colored_label(
+                                colors::offline(ui.style().visuals.dark_mode),
+                                LangMessage::DownloadServerUnstable {
+                                    host: host.host,
+                                    failure_percent,
+                                }
+                                .to_string(lang),
+                            );
+                        }
                         self.render_cancel_button(ui, lang);
                     });
                 },
@@ -297,16 +876,124 @@ impl InstanceSyncState {
         }
     }
 
+    fn render_delete_extra_window(
+        &mut self,
+        ui: &mut egui::Ui,
+        runtime: &Runtime,
+        config: &Config,
+        instance_storage: &mut InstanceStorage,
+    ) {
+        if self.pending_delete_extra.is_none()
+            && let Some(receiver) = self.delete_extra_receiver.as_mut()
+            && let Ok(request) = receiver.try_recv()
+        {
+            self.pending_delete_extra = Some(request);
+            self.always_allow_delete_extra_checked = false;
+        }
+
+        let Some(request) = self.pending_delete_extra.take() else {
+            return;
+        };
+        let Some(instance_name) = self.syncing_instance_name.clone() else {
+            return;
+        };
+
+        let always_allowed = instance_storage
+            .get_instance(&instance_name)
+            .is_some_and(|instance| instance.always_allow_delete_extra);
+        if always_allowed {
+            let _ = request.response.send(true);
+            return;
+        }
+
+        let lang = config.lang;
+        let minecraft_dir = get_minecraft_dir(&config.get_launcher_dir(), &instance_name);
+        let mut confirmed = None;
+        egui::Window::new(LangMessage::DeleteExtraFiles.to_string(lang))
+            .collapsible(false)
+            .show(ui.ctx(), |ui| {
+                ui.label(LangMessage::DeleteExtraFilesWarning.to_string(lang));
+                egui::ScrollArea::vertical()
+                    .max_height(200.0)
+                    .show(ui, |ui| {
+                        for preview in &request.previews {
+                            ui.label(RichText::new(&preview.rule_path).strong());
+                            for path in &preview.paths {
+                                let path = path.strip_prefix(&minecraft_dir).unwrap_or(path);
+                                ui.label(path.to_string_lossy());
+                            }
+                        }
+                    });
+                ui.checkbox(
+                    &mut self.always_allow_delete_extra_checked,
+                    LangMessage::AlwaysAllowForInstance.to_string(lang),
+                );
+                ui.horizontal(|ui| {
+                    if ui.button(LangMessage::Delete.to_string(lang)).clicked() {
+                        confirmed = Some(true);
+                    }
+                    if ui.button(LangMessage::KeepFiles.to_string(lang)).clicked() {
+                        confirmed = Some(false);
+                    }
+                });
+            });
+
+        match confirmed {
+            Some(confirmed) => {
+                if confirmed && self.always_allow_delete_extra_checked {
+                    runtime.block_on(
+                        instance_storage.set_always_allow_delete_extra(config, &instance_name),
+                    );
+                }
+                let _ = request.response.send(confirmed);
+            }
+            None => {
+                self.pending_delete_extra = Some(request);
+            }
+        }
+    }
+
+    fn render_antivirus_notice(&mut self, ui: &mut egui::Ui, config: &Config) {
+        let lang = config.lang;
+        let mut open = self.antivirus_notice_open;
+        egui::Window::new(LangMessage::AntivirusNoticeTitle.to_string(lang))
+            .id(egui::Id::new("antivirus_notice"))
+            .collapsible(false)
+            .open(&mut open)
+            .show(ui.ctx(), |ui| {
+                ui.label(LangMessage::AntivirusNotice.to_string(lang));
+                ui.code(config.get_launcher_dir().to_string_lossy());
+                if ui
+                    .button(LangMessage::OpenWindowsSecurity.to_string(lang))
+                    .clicked()
+                    && let Err(e) = windows::open_windows_security()
+                {
+                    error!("Failed to open Windows Security:\n{e:?}");
+                }
+            });
+        self.antivirus_notice_open = open;
+    }
+
     fn render_cancel_button(&mut self, ui: &mut egui::Ui, lang: Lang) {
         if ui
             .button(LangMessage::CancelDownload.to_string(lang))
             .clicked()
         {
-            self.cancel_sync();
+            self.cancel_all_syncs();
         }
     }
 
+    // auto-syncs aren't tied to the selected instance and keep running
     pub fn cancel_sync(&mut self) {
+        if self.current_auto_sync.is_none()
+            && let Some(task) = self.instance_sync_task.as_mut()
+        {
+            task.cancel();
+        }
+    }
+
+    pub fn cancel_all_syncs(&mut self) {
+        self.auto_sync_queue.clear();
         if let Some(task) = self.instance_sync_task.as_mut() {
             task.cancel();
         }
@@ -0,0 +1,214 @@
+use log::error;
+use shared::java::{self, DownloadedJava, JavaInstallation};
+use shared::paths::get_java_dir;
+use tokio::runtime::Runtime;
+
+use crate::config::runtime_config::Config;
+use crate::lang::LangMessage;
+
+use super::background_task::{BackgroundTask, BackgroundTaskResult};
+use super::widgets::icon_button;
+
+// deletes a runtime first if requested, then lists the remaining ones
+fn get_runtimes_task(
+    runtime: &Runtime,
+    config: &Config,
+    delete: Option<DownloadedJava>,
+    ctx: &egui::Context,
+) -> BackgroundTask<Vec<DownloadedJava>> {
+    let java_dir = get_java_dir(&config.get_launcher_dir());
+    let fut = async move {
+        if let Some(delete) = delete
+            && let Err(e) = tokio::fs::remove_dir_all(&delete.dir).await
+        {
+            error!("Failed to delete {}:\n{e:?}", delete.dir.display());
+        }
+        java::list_downloaded_java(&java_dir).await
+    };
+
+    let ctx = ctx.clone();
+    BackgroundTask::with_callback(fut, runtime, Box::new(move || ctx.request_repaint()))
+}
+
+fn get_system_java_task(
+    runtime: &Runtime,
+    ctx: &egui::Context,
+) -> BackgroundTask<Vec<JavaInstallation>> {
+    let ctx = ctx.clone();
+    BackgroundTask::with_callback(
+        java::find_system_java(),
+        runtime,
+        Box::new(move || ctx.request_repaint()),
+    )
+}
+
+// instances whose java path points into the runtime
+fn get_referencing_instances(config: &Config, runtime: &DownloadedJava) -> Vec<String> {
+    let mut instances: Vec<String> = config
+        .java_paths
+        .iter()
+        .filter(|(_, path)| path.starts_with(&runtime.dir))
+        .map(|(instance_name, _)| instance_name.clone())
+        .collect();
+    instances.sort();
+    instances
+}
+
+pub struct JavaManager {
+    opened: bool,
+    // None until listed for the opened window
+    runtimes: Option<Vec<DownloadedJava>>,
+    runtimes_task: Option<BackgroundTask<Vec<DownloadedJava>>>,
+    // None until detected
+    system_java: Option<Vec<JavaInstallation>>,
+    system_java_task: Option<BackgroundTask<Vec<JavaInstallation>>>,
+}
+
+impl JavaManager {
+    pub fn new() -> Self {
+        Self {
+            opened: false,
+            runtimes: None,
+            runtimes_task: None,
+            system_java: None,
+            system_java_task: None,
+        }
+    }
+
+    // offered by the java path picker of the instance settings
+    pub fn get_system_java(&self) -> &[JavaInstallation] {
+        self.system_java.as_deref().unwrap_or_default()
+    }
+
+    fn update(&mut self) {
+        if let Some(task) = self.runtimes_task.as_ref()
+            && task.has_result()
+        {
+            let task = self.runtimes_task.take().unwrap();
+            if let BackgroundTaskResult::Finished(runtimes) = task.take_result() {
+                self.runtimes = Some(runtimes);
+            }
+        }
+        if let Some(task) = self.system_java_task.as_ref()
+            && task.has_result()
+        {
+            let task = self.system_java_task.take().unwrap();
+            if let BackgroundTaskResult::Finished(system_java) = task.take_result() {
+                self.system_java = Some(system_java);
+            }
+        }
+    }
+
+    pub fn render_button(&mut self, ui: &mut egui::Ui, config: &Config) {
+        if ui
+            .button(LangMessage::ManageJava.to_string(config.lang))
+            .clicked()
+        {
+            self.opened = true;
+            self.runtimes = None;
+        }
+    }
+
+    pub fn render_window(
+        &mut self,
+        ui: &mut egui::Ui,
+        config: &Config,
+        runtime: &Runtime,
+        ctx: &egui::Context,
+    ) {
+        self.update();
+        if self.opened && self.runtimes.is_none() && self.runtimes_task.is_none() {
+            self.runtimes_task = Some(get_runtimes_task(runtime, config, None, ctx));
+        }
+
+        let lang = config.lang;
+        let mut opened = self.opened;
+        let mut delete = None;
+        egui::Window::new(LangMessage::JavaRuntimes.to_string(lang))
+            .open(&mut opened)
+            .show(ui.ctx(), |ui| {
+                ui.label(LangMessage::DownloadedJava.to_string(lang));
+                match &self.runtimes {
+                    Some(runtimes) if self.runtimes_task.is_none() => {
+                        if runtimes.is_empty() {
+                            ui.label(LangMessage::NoDownloadedJava.to_string(lang));
+                        }
+                        for java_runtime in runtimes {
+                            let instances = get_referencing_instances(config, java_runtime);
+                            ui.horizontal(|ui| {
+                                ui.label(
+                                    LangMessage::JavaRuntime {
+                                        name: java_runtime
+                                            .dir
+                                            .file_name()
+                                            .unwrap_or_default()
+                                            .to_string_lossy()
+                                            .to_string(),
+                                        version: java_runtime.version.clone(),
+                                        valid: java_runtime.valid,
+                                    }
+                                    .to_string(lang),
+                                )
+                                .on_hover_text(java_runtime.path.display().to_string());
+                                if icon_button(
+                                    ui,
+                                    "🗑",
+                                    LangMessage::DeleteJava,
+                                    lang,
+                                    instances.is_empty(),
+                                )
+                                .clicked()
+                                {
+                                    delete = Some(java_runtime.clone());
+                                }
+                            });
+                            ui.label(
+                                LangMessage::JavaUsedBy {
+                                    instances: instances.join(", "),
+                                }
+                                .to_string(lang),
+                            );
+                        }
+                    }
+                    _ => {
+                        ui.spinner();
+                    }
+                }
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label(LangMessage::SystemJava.to_string(lang));
+                    if icon_button(
+                        ui,
+                        "🔄",
+                        LangMessage::RedetectSystemJava,
+                        lang,
+                        self.system_java_task.is_none(),
+                    )
+                    .clicked()
+                    {
+                        self.system_java_task = Some(get_system_java_task(runtime, ctx));
+                    }
+                });
+                if self.system_java_task.is_some() {
+                    ui.spinner();
+                } else if let Some(system_java) = &self.system_java {
+                    if system_java.is_empty() {
+                        ui.label(LangMessage::NoSystemJava.to_string(lang));
+                    }
+                    for installation in system_java {
+                        ui.label(format!(
+                            "{}: {}",
+                            installation.version,
+                            installation.path.display()
+                        ));
+                    }
+                }
+            });
+        self.opened = opened;
+
+        if let Some(delete) = delete {
+            self.runtimes_task = Some(get_runtimes_task(runtime, config, Some(delete), ctx));
+        }
+    }
+}
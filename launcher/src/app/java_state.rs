@@ -1,5 +1,5 @@
 use egui::RichText;
-use log::error;
+use log::{error, warn};
 use shared::paths::get_java_dir;
 use shared::utils::is_connect_error;
 use std::path::{Path, PathBuf};
@@ -8,6 +8,7 @@ use tokio::runtime::Runtime;
 
 use crate::config::runtime_config::Config;
 use crate::lang::{Lang, LangMessage};
+use crate::utils;
 use crate::version::complete_version_metadata::CompleteVersionMetadata;
 
 use shared::java;
@@ -16,6 +17,7 @@ use shared::progress::{ProgressBar, Unit};
 use super::background_task::{BackgroundTask, BackgroundTaskResult};
 use super::colors;
 use super::progress_bar::GuiProgressBar;
+use super::task_registry::{TaskKey, TaskRegistry};
 
 #[derive(Clone, PartialEq)]
 pub enum JavaDownloadStatus {
@@ -28,25 +30,28 @@ pub enum JavaDownloadStatus {
 
 struct JavaCheckResult {
     java_path: Option<PathBuf>,
+    is_32_bit: bool,
 }
 
 fn check_java(
     runtime: &Runtime,
     java_version: &str,
     java_dir: &Path,
-    existing_path: Option<&str>,
+    existing_path: Option<&Path>,
+    other_instance_paths: Vec<PathBuf>,
     ctx: &egui::Context,
 ) -> BackgroundTask<JavaCheckResult> {
     let java_version = java_version.to_string();
     let java_dir = java_dir.to_path_buf();
-    let existing_path = existing_path.map(|s| s.to_string());
+    let existing_path = existing_path.map(|path| path.to_path_buf());
     let ctx = ctx.clone();
 
     let fut = async move {
-        if let Some(path) = existing_path {
-            let path = PathBuf::from(path);
+        // instances of the same minecraft version usually share the java, no need to scan again
+        for path in existing_path.into_iter().chain(other_instance_paths) {
             if java::check_java(&java_version, &path).await {
                 return JavaCheckResult {
+                    is_32_bit: java::is_java_32_bit(&path).await,
                     java_path: Some(path),
                 };
             }
@@ -54,7 +59,14 @@ fn check_java(
         let java_path = java::get_java(&java_version, &java_dir)
             .await
             .map(|j| j.path);
-        JavaCheckResult { java_path }
+        let is_32_bit = match &java_path {
+            Some(path) => java::is_java_32_bit(path).await,
+            None => false,
+        };
+        JavaCheckResult {
+            java_path,
+            is_32_bit,
+        }
     };
 
     BackgroundTask::with_callback(
@@ -114,11 +126,15 @@ pub struct JavaState {
     check_java_task: Option<BackgroundTask<JavaCheckResult>>,
     java_download_task: Option<BackgroundTask<JavaDownloadResult>>,
     java_download_progress_bar: Arc<GuiProgressBar>,
+    // key of the running download in the task registry
+    java_download_key: Option<TaskKey>,
     settings_opened: bool,
+    java_32_bit: bool,
+    task_registry: TaskRegistry,
 }
 
 impl JavaState {
-    pub fn new(ctx: &egui::Context) -> Self {
+    pub fn new(ctx: &egui::Context, task_registry: TaskRegistry) -> Self {
         let java_download_progress_bar = Arc::new(GuiProgressBar::new(ctx));
         java_download_progress_bar.set_unit(Unit {
             name: "MB".to_string(),
@@ -129,7 +145,10 @@ impl JavaState {
             check_java_task: None,
             java_download_task: None,
             java_download_progress_bar,
+            java_download_key: None,
             settings_opened: false,
+            java_32_bit: false,
+            task_registry,
         }
     }
 
@@ -139,17 +158,33 @@ impl JavaState {
         metadata: &CompleteVersionMetadata,
         config: &mut Config,
     ) {
+        let key = TaskKey::DownloadJava(metadata.get_java_version());
+        // another instance can need the same java version
+        if self.task_registry.is_running(&key) {
+            warn!(
+                "Java {} is already being downloaded",
+                metadata.get_java_version()
+            );
+            return;
+        }
         let launcher_dir = config.get_launcher_dir();
         let java_dir = get_java_dir(&launcher_dir);
 
         self.java_download_progress_bar.reset();
 
-        self.java_download_task = Some(download_java(
+        let task = download_java(
             runtime,
             &metadata.get_java_version(),
             &java_dir,
             self.java_download_progress_bar.clone(),
-        ));
+        );
+        self.task_registry.register(
+            key.clone(),
+            &task,
+            Some(self.java_download_progress_bar.clone()),
+        );
+        self.java_download_task = Some(task);
+        self.java_download_key = Some(key);
     }
 
     pub fn set_check_java_task(
@@ -158,23 +193,46 @@ impl JavaState {
         metadata: &CompleteVersionMetadata,
         config: &Config,
         ctx: &egui::Context,
+        offline: bool,
     ) {
+        self.settings_opened = false;
+
+        let existing_path = config
+            .java_paths
+            .get(metadata.get_name())
+            .map(|path| path.as_path());
+
+        // the path was validated on a previous launch, trust it while offline
+        if offline && existing_path.is_some_and(|path| path.is_file()) {
+            self.check_java_task = None;
+            self.status = JavaDownloadStatus::Downloaded;
+            return;
+        }
+
         self.status = JavaDownloadStatus::CheckingJava;
         let launcher_dir = config.get_launcher_dir();
         let java_dir = get_java_dir(&launcher_dir);
 
-        self.check_java_task = Some(check_java(
+        let mut other_instance_paths: Vec<PathBuf> = config
+            .java_paths
+            .iter()
+            .filter(|(instance_name, _)| *instance_name != metadata.get_name())
+            .map(|(_, path)| path.clone())
+            .collect();
+        other_instance_paths.sort();
+        other_instance_paths.dedup();
+
+        let task = check_java(
             runtime,
             &metadata.get_java_version(),
             &java_dir,
-            config
-                .java_paths
-                .get(metadata.get_name())
-                .map(|s| s.as_str()),
+            existing_path,
+            other_instance_paths,
             ctx,
-        ));
-
-        self.settings_opened = false;
+        );
+        self.task_registry
+            .register(TaskKey::CheckJava(metadata.get_java_version()), &task, None);
+        self.check_java_task = Some(task);
     }
 
     pub fn update(
@@ -185,7 +243,7 @@ impl JavaState {
         ctx: &egui::Context,
     ) {
         if self.check_java_task.is_none() && self.status == JavaDownloadStatus::CheckingJava {
-            self.set_check_java_task(runtime, metadata, config, ctx);
+            self.set_check_java_task(runtime, metadata, config, ctx, false);
         }
 
         if let Some(task) = self.check_java_task.as_ref()
@@ -196,11 +254,23 @@ impl JavaState {
 
             match result {
                 BackgroundTaskResult::Finished(result) => {
+                    self.java_32_bit = result.is_32_bit;
                     if let Some(java_path) = result.java_path {
-                        config.java_paths.insert(
-                            metadata.get_name().to_string(),
-                            java_path.to_string_lossy().to_string(),
-                        );
+                        config
+                            .java_paths
+                            .insert(metadata.get_name().to_string(), java_path.clone());
+                        if result.is_32_bit
+                            && let Some(xmx) = config.xmx.get_mut(metadata.get_name())
+                        {
+                            let clamped_xmx = utils::clamp_xmx(xmx, true);
+                            if *xmx != clamped_xmx {
+                                warn!(
+                                    "32-bit Java at {}, lowering Xmx from {xmx} to {clamped_xmx}",
+                                    java_path.display()
+                                );
+                                *xmx = clamped_xmx;
+                            }
+                        }
                         config.save();
                         self.status = JavaDownloadStatus::Downloaded;
                     } else {
@@ -226,11 +296,11 @@ impl JavaState {
                 BackgroundTaskResult::Finished(result) => {
                     self.status = result.status;
                     if self.status == JavaDownloadStatus::Downloaded {
+                        self.java_32_bit = false;
                         let path = result.java_installation.as_ref().unwrap().path.clone();
-                        config.java_paths.insert(
-                            metadata.get_name().to_string(),
-                            path.to_string_lossy().to_string(),
-                        );
+                        config
+                            .java_paths
+                            .insert(metadata.get_name().to_string(), path);
                         config.save();
                     }
                 }
@@ -241,6 +311,10 @@ impl JavaState {
         }
     }
 
+    pub fn is_java_32_bit(&self) -> bool {
+        self.java_32_bit
+    }
+
     fn is_download_needed(&self) -> bool {
         if self.java_download_task.is_some() {
             return false;
@@ -276,29 +350,29 @@ impl JavaState {
         ui.label(if let Some(selected_metadata) = selected_metadata {
             match self.status {
                 JavaDownloadStatus::CheckingJava => {
-                    RichText::new(LangMessage::CheckingJava.to_string(lang))
+                    RichText::new(&*LangMessage::CheckingJava.to_cached_string(lang))
                         .color(colors::in_progress(dark_mode))
                 }
                 JavaDownloadStatus::NotDownloaded => {
                     if self.java_download_task.is_none() {
                         RichText::new(
-                            LangMessage::NeedJava {
+                            &*LangMessage::NeedJava {
                                 version: selected_metadata.get_java_version().clone(),
                             }
-                            .to_string(lang),
+                            .to_cached_string(lang),
                         )
                         .color(colors::action(dark_mode))
                     } else {
-                        RichText::new(LangMessage::DownloadingJava.to_string(lang))
+                        RichText::new(&*LangMessage::DownloadingJava.to_cached_string(lang))
                             .color(colors::in_progress(dark_mode))
                     }
                 }
                 JavaDownloadStatus::UnknownDownloadError => {
-                    RichText::new(LangMessage::UnknownErrorDownloadingJava.to_string(lang))
+                    RichText::new(&*LangMessage::UnknownErrorDownloadingJava.to_cached_string(lang))
                         .color(colors::error(dark_mode))
                 }
                 JavaDownloadStatus::DownloadErrorOffline => {
-                    RichText::new(LangMessage::NoConnectionToJavaServer.to_string(lang))
+                    RichText::new(&*LangMessage::NoConnectionToJavaServer.to_cached_string(lang))
                         .color(colors::offline(dark_mode))
                 }
                 JavaDownloadStatus::Downloaded => RichText::new(
@@ -310,7 +384,7 @@ impl JavaState {
                 .color(colors::ok(dark_mode)),
             }
         } else {
-            RichText::new(LangMessage::UnknownJavaVersion.to_string(lang))
+            RichText::new(&*LangMessage::UnknownJavaVersion.to_cached_string(lang))
                 .color(colors::in_progress(dark_mode))
         });
 
@@ -318,10 +392,12 @@ impl JavaState {
     }
 
     fn render_progress_bar_window(&mut self, ui: &mut egui::Ui, lang: Lang) {
-        if self.java_download_task.is_some() {
+        if self.java_download_task.is_some()
+            && let Some(key) = self.java_download_key.clone()
+        {
             egui::Window::new(LangMessage::DownloadingJava.to_string(lang)).show(ui.ctx(), |ui| {
                 ui.vertical_centered(|ui| {
-                    self.java_download_progress_bar.render(ui, lang);
+                    self.task_registry.render_progress(ui, &key, lang);
                     self.render_cancel_button(ui, lang);
                 });
             });
@@ -0,0 +1,49 @@
+use egui::RichText;
+use log::error;
+use shared::json::JsonParseError;
+
+use crate::lang::{Lang, LangMessage};
+use crate::utils::is_web_url;
+
+use super::colors;
+
+// collapsed by default, shown under the status label of a manifest or metadata that failed to parse
+pub fn render_json_error_panel(ui: &mut egui::Ui, error: &JsonParseError, lang: Lang) {
+    let dark_mode = ui.style().visuals.dark_mode;
+    egui::CollapsingHeader::new(
+        RichText::new(LangMessage::ErrorDetails.to_string(lang)).color(colors::error(dark_mode)),
+    )
+    .id_salt(("json_error", &error.url))
+    .show(ui, |ui| {
+        egui::Grid::new(("json_error_grid", &error.url))
+            .num_columns(2)
+            .show(ui, |ui| {
+                for (label, value) in [
+                    (LangMessage::SourceUrl, &error.url),
+                    (LangMessage::JsonPath, &error.path),
+                    (LangMessage::ErrorMessage, &error.message),
+                ] {
+                    ui.label(label.to_string(lang));
+                    ui.add(egui::Label::new(RichText::new(value).monospace()).wrap());
+                    ui.end_row();
+                }
+            });
+        ui.horizontal(|ui| {
+            if ui
+                .button(LangMessage::CopyDetails.to_string(lang))
+                .clicked()
+            {
+                ui.ctx().copy_text(error.get_details());
+            }
+            // files read from disk have a local path instead
+            if is_web_url(&error.url)
+                && ui
+                    .button(LangMessage::OpenInBrowser.to_string(lang))
+                    .clicked()
+                && let Err(e) = open::that(&error.url)
+            {
+                error!("Error opening {}:\n{e:?}", error.url);
+            }
+        });
+    });
+}
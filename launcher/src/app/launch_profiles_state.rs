@@ -0,0 +1,193 @@
+use egui::RichText;
+
+use crate::config::runtime_config::{Config, LaunchProfile};
+use crate::constants;
+use crate::lang::LangMessage;
+
+use super::colors;
+use super::widgets::{TrailingRow, icon_button};
+
+pub struct LaunchProfilesState {
+    new_profile_window_open: bool,
+    new_profile_name: String,
+    override_xmx: bool,
+    xmx: String,
+    override_window_size: bool,
+    window_size: (u32, u32),
+}
+
+impl LaunchProfilesState {
+    pub fn new() -> Self {
+        LaunchProfilesState {
+            new_profile_window_open: false,
+            new_profile_name: String::new(),
+            override_xmx: false,
+            xmx: String::new(),
+            override_window_size: false,
+            window_size: constants::DEFAULT_WINDOW_SIZE,
+        }
+    }
+
+    pub fn is_window_open(&self) -> bool {
+        self.new_profile_window_open
+    }
+
+    pub fn close_window(&mut self) {
+        self.new_profile_window_open = false;
+    }
+
+    // returns the name of the profile picked by the user,
+    // profiles whose instance or account no longer exists can't be picked
+    pub fn render_ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        config: &mut Config,
+        is_valid: impl Fn(&LaunchProfile) -> bool,
+    ) -> Option<String> {
+        let lang = config.lang;
+        let dark_mode = ui.style().visuals.dark_mode;
+        let profiles: Vec<LaunchProfile> = config
+            .launch_profiles
+            .iter()
+            .filter(|profile| profile.manifest_url == config.selected_version_manifest_url)
+            .cloned()
+            .collect();
+        let active_profile = config.get_active_launch_profile().map(|p| p.name.clone());
+
+        let mut picked = None;
+        let mut to_delete = None;
+        let row = TrailingRow::new(ui, "launch_profile_row");
+        row.show_fill(ui, |ui| {
+            let selected_text = match &active_profile {
+                Some(name) => RichText::new(name),
+                None => RichText::new(LangMessage::SelectLaunchProfile.to_string(lang))
+                    .color(colors::action(dark_mode)),
+            };
+            egui::ComboBox::from_id_salt("launch_profiles")
+                .width(ui.available_width())
+                .selected_text(selected_text)
+                .show_ui(ui, |ui| {
+                    if profiles.is_empty() {
+                        ui.label(LangMessage::NoLaunchProfiles.to_string(lang));
+                    }
+                    for profile in &profiles {
+                        ui.horizontal(|ui| {
+                            let valid = is_valid(profile);
+                            let text = if valid {
+                                RichText::new(&profile.name)
+                            } else {
+                                RichText::new(format!(
+                                    "{} ({})",
+                                    profile.name,
+                                    LangMessage::InvalidLaunchProfile.to_string(lang)
+                                ))
+                                .color(colors::error(dark_mode))
+                            };
+                            let selected = active_profile.as_ref() == Some(&profile.name);
+                            if ui
+                                .add_enabled(valid, egui::Button::selectable(selected, text))
+                                .clicked()
+                            {
+                                picked = Some(profile.name.clone());
+                            }
+                            if icon_button(ui, "✖", LangMessage::DeleteLaunchProfile, lang, true)
+                                .clicked()
+                            {
+                                to_delete = Some(profile.name.clone());
+                            }
+                        });
+                    }
+                });
+        });
+        row.show_trailing(ui, |ui| {
+            let can_save = config.selected_instance_name.is_some()
+                && config.get_selected_auth_profile().is_some();
+            if icon_button(ui, "+", LangMessage::NewLaunchProfile, lang, can_save).clicked() {
+                self.open_new_profile_window(config);
+            }
+        });
+
+        if let Some(name) = to_delete {
+            config.remove_launch_profile(&name);
+        }
+        self.render_new_profile_window(ui, config);
+        picked
+    }
+
+    fn open_new_profile_window(&mut self, config: &Config) {
+        self.new_profile_window_open = true;
+        self.new_profile_name = String::new();
+        self.override_xmx = false;
+        self.xmx = config
+            .selected_instance_name
+            .as_ref()
+            .and_then(|name| config.xmx.get(name))
+            .cloned()
+            .unwrap_or_else(|| format!("{}M", constants::XMX_DEFAULT));
+        self.override_window_size = false;
+        self.window_size = constants::DEFAULT_WINDOW_SIZE;
+    }
+
+    fn render_new_profile_window(&mut self, ui: &mut egui::Ui, config: &mut Config) {
+        let lang = config.lang;
+        let mut window_open = self.new_profile_window_open;
+        let mut saved = false;
+        egui::Window::new(LangMessage::NewLaunchProfile.to_string(lang))
+            .open(&mut window_open)
+            .collapsible(false)
+            .show(ui.ctx(), |ui| {
+                ui.label(LangMessage::LaunchProfileName.to_string(lang));
+                ui.text_edit_singleline(&mut self.new_profile_name);
+
+                ui.checkbox(
+                    &mut self.override_xmx,
+                    LangMessage::AllocatedMemory.to_string(lang),
+                );
+                if self.override_xmx {
+                    ui.text_edit_singleline(&mut self.xmx);
+                }
+                ui.checkbox(
+                    &mut self.override_window_size,
+                    LangMessage::WindowSize.to_string(lang),
+                );
+                if self.override_window_size {
+                    ui.horizontal(|ui| {
+                        ui.add(egui::DragValue::new(&mut self.window_size.0).range(1..=16384));
+                        ui.label("×");
+                        ui.add(egui::DragValue::new(&mut self.window_size.1).range(1..=16384));
+                    });
+                }
+
+                let name = self.new_profile_name.trim();
+                let name_exists = config.get_launch_profile(name).is_some();
+                if name_exists {
+                    ui.label(
+                        RichText::new(LangMessage::LaunchProfileNameExists.to_string(lang))
+                            .color(colors::error(ui.style().visuals.dark_mode)),
+                    );
+                }
+                if ui
+                    .add_enabled(
+                        !name.is_empty() && !name_exists,
+                        egui::Button::new(LangMessage::Add.to_string(lang)),
+                    )
+                    .clicked()
+                    && let Some(instance_name) = config.selected_instance_name.clone()
+                    && let Some(auth_profile) = config.get_selected_auth_profile().cloned()
+                {
+                    config.launch_profiles.push(LaunchProfile {
+                        name: name.to_string(),
+                        manifest_url: config.selected_version_manifest_url.clone(),
+                        instance_name,
+                        auth_profile,
+                        xmx: self.override_xmx.then(|| self.xmx.trim().to_string()),
+                        window_size: self.override_window_size.then_some(self.window_size),
+                    });
+                    config.last_launch_profile = Some(name.to_string());
+                    config.save();
+                    saved = true;
+                }
+            });
+        self.new_profile_window_open = window_open && !saved;
+    }
+}
@@ -1,30 +1,115 @@
 use std::{
+    path::PathBuf,
     process::{ExitStatus, exit},
     sync::Arc,
+    time::{Duration, Instant, SystemTime},
 };
 
-use log::error;
-use shared::paths::get_logs_dir;
+use log::{error, warn};
+use shared::paths::{get_launch_log_path, get_logs_dir, get_minecraft_dir, get_running_lock_path};
+use shared::version::extra_version_metadata::AuthBackend;
 use tokio::{process::Child, runtime::Runtime, sync::Mutex};
+use tokio_util::sync::CancellationToken;
 
 use crate::{
-    auth::user_info::AuthData, config::runtime_config::Config, lang::LangMessage, launcher::launch,
+    auth::{auth_flow::refresh_auth, base::get_auth_provider, user_info::AuthData},
+    config::{build_config::USE_NATIVE_GLFW_DEFAULT, runtime_config::Config},
+    constants::{RESTART_AUTH_TIMEOUT_SECS, RESTART_BUDGET_RESET_SECS},
+    lang::LangMessage,
+    launcher::{
+        crash_report,
+        discord::{DiscordPresence, Presence},
+        game_output::{self, GameOutput},
+        gpu_errors, launch, playtime,
+        running_lock::remove_running_lock,
+    },
     version::complete_version_metadata::CompleteVersionMetadata,
 };
 
+use super::background_task::{BackgroundTask, BackgroundTaskResult};
+use super::colors;
+
 enum LauncherStatus {
     NotLaunched,
-    Running { child: Arc<Mutex<Child>> },
+    Running {
+        child: Arc<Mutex<Child>>,
+        instance_name: String,
+        started_at: Instant,
+        // wall clock time for the discord activity
+        launched_at: SystemTime,
+    },
+    // the account is refreshed before a restart after a crash
+    Restarting {
+        instance_name: String,
+    },
     Error,
     ProcessErrorCode(String),
 }
 
+// offered when the game couldn't create its window
+struct GpuErrorDialog {
+    instance_name: String,
+    signature: &'static str,
+    disable_threaded_optimizations: bool,
+}
+
+const CRASH_LOG_LINES: usize = 100;
+
+// what a restart after a crash launches again
+#[derive(Clone)]
+struct LastLaunch {
+    instance: Arc<CompleteVersionMetadata>,
+    auth_data: AuthData,
+    // None if the account can't be refreshed, then the saved data is used
+    auth_backend: Option<AuthBackend>,
+    online: bool,
+}
+
+// what is shown instead of the restart if the account can't be refreshed
+struct Crash {
+    exit_code: String,
+    crash_dialog: CrashDialog,
+    gpu_error_dialog: Option<GpuErrorDialog>,
+}
+
+struct PendingRestart {
+    last_launch: LastLaunch,
+    crash: Crash,
+    task: BackgroundTask<anyhow::Result<AuthData>>,
+}
+
+// shown when the game exits with an error
+struct CrashDialog {
+    exit_code: String,
+    log_tail: String,
+    crash_report: Option<PathBuf>,
+    open: bool,
+}
+
 pub struct LaunchState {
     status: LauncherStatus,
     force_launch: bool,
     launch_from_start: bool,
+    relaunch: bool,
     ctx: egui::Context,
     watcher_handle: Option<tokio::task::JoinHandle<ExitStatus>>,
+    gpu_error_dialog: Option<GpuErrorDialog>,
+    crash_dialog: Option<CrashDialog>,
+    discord_presence: DiscordPresence,
+    // filled from the launch log while the game runs, kept after it exits
+    game_output: GameOutput,
+    game_output_cancellation_token: Option<CancellationToken>,
+    // hiding the console doesn't stop the capture
+    console_opened: bool,
+    console_filter: String,
+    last_launch: Option<LastLaunch>,
+    pending_restart: Option<PendingRestart>,
+    // the account refreshed for a restart, to be saved by the auth state
+    refreshed_auth: Option<(AuthBackend, AuthData)>,
+    // restarts after a crash since the last launch by the user
+    restart_attempt: u32,
+    // a killed game isn't restarted
+    killed: bool,
 }
 
 pub enum ForceLaunchResultSelect {
@@ -36,6 +121,7 @@ pub enum ForceLaunchResultSelect {
 pub struct RenderUiParams {
     pub online: bool,
     pub disabled: bool,
+    pub launch_requested: bool,
 }
 
 impl LaunchState {
@@ -44,8 +130,21 @@ impl LaunchState {
             status: LauncherStatus::NotLaunched,
             force_launch: false,
             launch_from_start,
+            relaunch: false,
             ctx,
             watcher_handle: None,
+            gpu_error_dialog: None,
+            crash_dialog: None,
+            discord_presence: DiscordPresence::new(),
+            game_output: GameOutput::new(),
+            game_output_cancellation_token: None,
+            console_opened: false,
+            console_filter: String::new(),
+            last_launch: None,
+            pending_restart: None,
+            refreshed_auth: None,
+            restart_attempt: 0,
+            killed: false,
         }
     }
 
@@ -74,15 +173,21 @@ impl LaunchState {
         }
     }
 
-    fn launch(
-        &mut self,
-        runtime: &Runtime,
-        config: &Config,
-        selected_instance: &CompleteVersionMetadata,
-        auth_data: &AuthData,
-        online: bool,
-    ) {
-        match runtime.block_on(launch::launch(selected_instance, config, auth_data, online)) {
+    fn launch(&mut self, runtime: &Runtime, config: &Config, last_launch: LastLaunch) {
+        self.crash_dialog = None;
+        self.killed = false;
+        let LastLaunch {
+            instance: selected_instance,
+            auth_data,
+            online,
+            ..
+        } = &last_launch;
+        match runtime.block_on(launch::launch(
+            selected_instance,
+            config,
+            auth_data,
+            *online,
+        )) {
             Ok(child) => {
                 let arc_child = Arc::new(Mutex::new(child));
                 if config.hide_launcher_after_launch {
@@ -98,9 +203,20 @@ impl LaunchState {
                 }
                 self.watcher_handle =
                     Some(runtime.spawn(Self::child_watcher(arc_child.clone(), self.ctx.clone())));
+                self.follow_game_output(runtime, config, selected_instance.get_name());
+                let launched_at = SystemTime::now();
+                playtime::start_session(
+                    &config.get_launcher_dir(),
+                    selected_instance.get_name(),
+                    launched_at,
+                );
                 self.status = LauncherStatus::Running {
                     child: arc_child.clone(),
+                    instance_name: selected_instance.get_name().to_string(),
+                    started_at: Instant::now(),
+                    launched_at,
                 };
+                self.last_launch = Some(last_launch);
             }
             Err(e) => {
                 error!("Error launching Minecraft:\n{e:?}");
@@ -109,23 +225,395 @@ impl LaunchState {
         }
     }
 
+    fn follow_game_output(&mut self, runtime: &Runtime, config: &Config, instance_name: &str) {
+        if let Some(token) = self.game_output_cancellation_token.take() {
+            token.cancel();
+        }
+        self.game_output.clear();
+        let cancellation_token = CancellationToken::new();
+        self.game_output_cancellation_token = Some(cancellation_token.clone());
+
+        let log_path = get_launch_log_path(&config.get_launcher_dir(), instance_name);
+        let output = self.game_output.clone();
+        let ctx = self.ctx.clone();
+        runtime.spawn(async move {
+            if let Err(e) = game_output::follow_log(log_path, output, cancellation_token, || {
+                ctx.request_repaint()
+            })
+            .await
+            {
+                error!("Failed to read the game output:\n{e:?}");
+            }
+        });
+    }
+
+    // the tokens of the last launch may have expired while the game ran
+    fn restart(
+        &mut self,
+        runtime: &Runtime,
+        config: &Config,
+        last_launch: LastLaunch,
+        crash: Crash,
+    ) {
+        let Some(auth_backend) = last_launch
+            .auth_backend
+            .clone()
+            .filter(|_| last_launch.online)
+        else {
+            self.launch(runtime, config, last_launch);
+            return;
+        };
+        let fut = refresh_auth(
+            last_launch.auth_data.clone(),
+            get_auth_provider(&auth_backend),
+            Duration::from_secs(RESTART_AUTH_TIMEOUT_SECS),
+        );
+        let ctx = self.ctx.clone();
+        let task = BackgroundTask::with_callback(
+            fut,
+            runtime,
+            Box::new(move || {
+                ctx.request_repaint();
+            }),
+        );
+        self.status = LauncherStatus::Restarting {
+            instance_name: last_launch.instance.get_name().to_string(),
+        };
+        self.pending_restart = Some(PendingRestart {
+            last_launch,
+            crash,
+            task,
+        });
+    }
+
+    fn show_crash(&mut self, crash: Crash) {
+        let Crash {
+            exit_code,
+            crash_dialog,
+            gpu_error_dialog,
+        } = crash;
+        self.status = LauncherStatus::ProcessErrorCode(exit_code);
+        // the gpu error dialog has the fix, the crash details are still one click away
+        self.crash_dialog = Some(CrashDialog {
+            open: gpu_error_dialog.is_none(),
+            ..crash_dialog
+        });
+        self.gpu_error_dialog = gpu_error_dialog;
+    }
+
+    fn update_pending_restart(&mut self, runtime: &Runtime, config: &Config) {
+        let Some(PendingRestart {
+            mut last_launch,
+            crash,
+            task,
+        }) = self
+            .pending_restart
+            .take_if(|restart| restart.task.has_result())
+        else {
+            return;
+        };
+        match task.take_result() {
+            BackgroundTaskResult::Finished(Ok(auth_data)) => {
+                if let Some(auth_backend) = &last_launch.auth_backend {
+                    self.refreshed_auth = Some((auth_backend.clone(), auth_data.clone()));
+                }
+                last_launch.auth_data = auth_data;
+                self.launch(runtime, config, last_launch);
+            }
+            BackgroundTaskResult::Finished(Err(e)) => {
+                error!("Failed to refresh the account before a restart:\n{e:?}");
+                self.show_crash(crash);
+            }
+            BackgroundTaskResult::Cancelled => self.show_crash(crash),
+        }
+    }
+
+    pub fn take_refreshed_auth(&mut self) -> Option<(AuthBackend, AuthData)> {
+        self.refreshed_auth.take()
+    }
+
     pub fn update(&mut self, runtime: &Runtime, config: &Config) {
+        self.update_pending_restart(runtime, config);
         match self.watcher_handle.take_if(|handle| handle.is_finished()) {
             None => {}
             Some(handle) => {
                 let exit_status = runtime.block_on(handle).unwrap_or_default();
+                let LauncherStatus::Running {
+                    instance_name,
+                    started_at,
+                    launched_at,
+                    ..
+                } = &self.status
+                else {
+                    return;
+                };
+                let instance_name = instance_name.clone();
+                let run_time = started_at.elapsed();
+                let launched_at = *launched_at;
+                if let Some(token) = self.game_output_cancellation_token.take() {
+                    token.cancel();
+                }
+                let launcher_dir = config.get_launcher_dir();
+                remove_running_lock(&get_running_lock_path(&launcher_dir, &instance_name));
+                playtime::end_session(&launcher_dir, &instance_name, SystemTime::now());
+                let max_restarts = *config.restart_on_crash.get(&instance_name).unwrap_or(&0);
+                if run_time >= Duration::from_secs(RESTART_BUDGET_RESET_SECS) {
+                    self.restart_attempt = 0;
+                }
                 if exit_status.success() {
                     if config.hide_launcher_after_launch {
+                        config.save_pending();
                         exit(0);
                     }
                     self.status = LauncherStatus::NotLaunched;
                 } else {
-                    self.status = LauncherStatus::ProcessErrorCode(
-                        exit_status.code().unwrap_or(-1).to_string(),
-                    );
+                    let exit_code = exit_status.code().unwrap_or(-1).to_string();
+                    let crash = Crash {
+                        exit_code: exit_code.clone(),
+                        crash_dialog: Self::get_crash_dialog(
+                            config,
+                            &instance_name,
+                            exit_code,
+                            launched_at,
+                        ),
+                        gpu_error_dialog: Self::detect_gpu_error(config, instance_name, run_time),
+                    };
+                    if !self.killed
+                        && self.restart_attempt < max_restarts
+                        && let Some(last_launch) = self.last_launch.clone()
+                    {
+                        self.restart_attempt += 1;
+                        warn!(
+                            "The game exited with code {}, restarting (attempt {}/{max_restarts})",
+                            exit_status.code().unwrap_or(-1),
+                            self.restart_attempt
+                        );
+                        self.restart(runtime, config, last_launch, crash);
+                    } else {
+                        self.show_crash(crash);
+                    }
                 }
             }
         }
+
+        let presence = match &self.status {
+            LauncherStatus::Running {
+                instance_name,
+                launched_at,
+                ..
+            } => Presence::Playing {
+                instance_name: instance_name.clone(),
+                started_at: *launched_at,
+            },
+            _ => Presence::Idle,
+        };
+        self.discord_presence
+            .update(config.discord_presence, presence);
+    }
+
+    // only the native GLFW option of linux fixes these errors
+    fn detect_gpu_error(
+        config: &Config,
+        instance_name: String,
+        run_time: Duration,
+    ) -> Option<GpuErrorDialog> {
+        let use_native_glfw = *config
+            .use_native_glfw
+            .get(&instance_name)
+            .unwrap_or(&USE_NATIVE_GLFW_DEFAULT);
+        if !cfg!(target_os = "linux") || use_native_glfw {
+            return None;
+        }
+        let log = std::fs::read(get_launch_log_path(
+            &config.get_launcher_dir(),
+            &instance_name,
+        ))
+        .ok()?;
+        let signature = gpu_errors::find_gpu_error(&String::from_utf8_lossy(&log), run_time)?;
+        Some(GpuErrorDialog {
+            instance_name,
+            signature,
+            disable_threaded_optimizations: false,
+        })
+    }
+
+    fn get_crash_dialog(
+        config: &Config,
+        instance_name: &str,
+        exit_code: String,
+        launched_at: SystemTime,
+    ) -> CrashDialog {
+        let launcher_dir = config.get_launcher_dir();
+        let log_path = get_launch_log_path(&launcher_dir, instance_name);
+        let minecraft_dir = get_minecraft_dir(&launcher_dir, instance_name);
+        CrashDialog {
+            exit_code,
+            log_tail: crash_report::read_log_tail(&log_path, CRASH_LOG_LINES),
+            crash_report: crash_report::find_crash_report(&minecraft_dir, launched_at),
+            open: true,
+        }
+    }
+
+    fn render_crash_dialog(&mut self, ctx: &egui::Context, config: &Config) {
+        let Some(dialog) = &mut self.crash_dialog else {
+            return;
+        };
+        let lang = config.lang;
+        egui::Window::new(LangMessage::GameCrashed.to_string(lang))
+            .id(egui::Id::new("crash_dialog"))
+            .collapsible(false)
+            .open(&mut dialog.open)
+            .show(ctx, |ui| {
+                ui.label(LangMessage::ProcessErrorCode(dialog.exit_code.clone()).to_string(lang));
+                ui.label(LangMessage::GameLogTail.to_string(lang));
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .stick_to_bottom(true)
+                    .show(ui, |ui| {
+                        ui.add(
+                            egui::TextEdit::multiline(&mut dialog.log_tail.as_str())
+                                .font(egui::TextStyle::Monospace)
+                                .desired_width(f32::INFINITY),
+                        );
+                    });
+                match &dialog.crash_report {
+                    Some(crash_report) => {
+                        ui.horizontal(|ui| {
+                            if ui
+                                .button(LangMessage::OpenCrashReport.to_string(lang))
+                                .clicked()
+                                && let Err(e) = open::that(crash_report)
+                            {
+                                error!("Failed to open {}:\n{e:?}", crash_report.display());
+                            }
+                            if ui
+                                .button(LangMessage::CopyCrashReport.to_string(lang))
+                                .clicked()
+                            {
+                                match std::fs::read(crash_report) {
+                                    Ok(data) => ui
+                                        .ctx()
+                                        .copy_text(String::from_utf8_lossy(&data).to_string()),
+                                    Err(e) => {
+                                        error!("Failed to read {}:\n{e:?}", crash_report.display())
+                                    }
+                                }
+                            }
+                        });
+                    }
+                    None => {
+                        ui.label(LangMessage::NoCrashReport.to_string(lang));
+                    }
+                }
+                if ui.button(LangMessage::OpenLogs.to_string(lang)).clicked() {
+                    open::that(get_logs_dir(&config.get_launcher_dir())).unwrap();
+                }
+            });
+    }
+
+    fn render_console(&mut self, ctx: &egui::Context, config: &Config) {
+        let lang = config.lang;
+        let dark_mode = ctx.style().visuals.dark_mode;
+        let filter = self.console_filter.to_lowercase();
+        egui::Window::new(LangMessage::GameOutput.to_string(lang))
+            .id(egui::Id::new("game_output"))
+            .default_size([700.0, 400.0])
+            .open(&mut self.console_opened)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(LangMessage::FilterGameOutput.to_string(lang));
+                    ui.text_edit_singleline(&mut self.console_filter);
+                });
+                self.game_output.with_lines(|lines| {
+                    let shown: Vec<&String> = lines
+                        .iter()
+                        .filter(|line| filter.is_empty() || line.to_lowercase().contains(&filter))
+                        .collect();
+                    let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+                    egui::ScrollArea::both()
+                        .auto_shrink(false)
+                        .stick_to_bottom(true)
+                        .show_rows(ui, row_height, shown.len(), |ui, range| {
+                            for line in &shown[range] {
+                                let color = if line.contains("ERROR") || line.contains("FATAL") {
+                                    colors::error(dark_mode)
+                                } else if line.contains("WARN") {
+                                    colors::warning(dark_mode)
+                                } else {
+                                    colors::ok(dark_mode)
+                                };
+                                ui.add(
+                                    egui::Label::new(
+                                        egui::RichText::new(line.as_str()).monospace().color(color),
+                                    )
+                                    .extend(),
+                                );
+                            }
+                        });
+                });
+            });
+    }
+
+    fn render_gpu_error_dialog(&mut self, ctx: &egui::Context, config: &mut Config) {
+        let Some(dialog) = &mut self.gpu_error_dialog else {
+            return;
+        };
+        let lang = config.lang;
+        let mut open = true;
+        let mut relaunch = false;
+        egui::Window::new(LangMessage::GpuErrorTitle.to_string(lang))
+            .id(egui::Id::new("gpu_error_dialog"))
+            .collapsible(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(
+                    LangMessage::GpuErrorDescription(dialog.signature.to_string()).to_string(lang),
+                );
+                ui.checkbox(
+                    &mut dialog.disable_threaded_optimizations,
+                    LangMessage::DisableGlThreadedOptimizations.to_string(lang),
+                );
+                relaunch = ui
+                    .button(LangMessage::EnableNativeGlfwAndRelaunch.to_string(lang))
+                    .clicked();
+            });
+        if relaunch {
+            config
+                .use_native_glfw
+                .insert(dialog.instance_name.clone(), true);
+            if dialog.disable_threaded_optimizations {
+                config
+                    .disable_gl_threaded_optimizations
+                    .insert(dialog.instance_name.clone(), true);
+            }
+            config.save();
+            // the selected instance may have changed while the dialog was open
+            self.relaunch = config.selected_instance_name.as_ref() == Some(&dialog.instance_name);
+        }
+        if relaunch || !open {
+            self.gpu_error_dialog = None;
+        }
+    }
+
+    pub fn get_running_instance(&self) -> Option<&str> {
+        match &self.status {
+            LauncherStatus::Running { instance_name, .. }
+            | LauncherStatus::Restarting { instance_name } => Some(instance_name),
+            _ => None,
+        }
+    }
+
+    pub fn kill(&mut self, runtime: &Runtime) {
+        if let LauncherStatus::Running { child, .. } = &self.status {
+            self.killed = true;
+            let mut child_lock = runtime.block_on(child.lock());
+            let _ = runtime.block_on(child_lock.kill());
+        }
+        // the restart is cancelled, the crash is shown instead
+        if let Some(restart) = self.pending_restart.take() {
+            self.killed = true;
+            self.show_crash(restart.crash);
+        }
     }
 
     fn big_button_clicked(ui: &mut egui::Ui, text: &str) -> bool {
@@ -145,19 +633,56 @@ impl LaunchState {
         auth_data: Option<AuthData>,
         params: RenderUiParams,
     ) {
-        let RenderUiParams { online, disabled } = params;
+        let RenderUiParams {
+            online,
+            disabled,
+            launch_requested,
+        } = params;
 
         let lang = config.lang;
 
+        self.render_gpu_error_dialog(ui.ctx(), config);
+        self.render_crash_dialog(ui.ctx(), config);
+        self.render_console(ui.ctx(), config);
+
         match &mut self.status {
-            LauncherStatus::Running { child } => {
-                ui.label(LangMessage::Running.to_string(lang));
+            LauncherStatus::Running { instance_name, .. } => {
+                let max_restarts = *config.restart_on_crash.get(instance_name).unwrap_or(&0);
+                if self.restart_attempt > 0 {
+                    ui.label(
+                        LangMessage::RestartedAfterCrash {
+                            attempt: self.restart_attempt,
+                            max: max_restarts,
+                        }
+                        .to_string(lang),
+                    );
+                } else {
+                    ui.label(&*LangMessage::Running.to_cached_string(lang));
+                }
+                if ui
+                    .button(&*LangMessage::KillMinecraft.to_cached_string(lang))
+                    .clicked()
+                {
+                    self.kill(runtime);
+                }
+            }
+            LauncherStatus::Restarting { instance_name } => {
+                let max_restarts = *config.restart_on_crash.get(instance_name).unwrap_or(&0);
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(
+                        LangMessage::RestartingAfterCrash {
+                            attempt: self.restart_attempt,
+                            max: max_restarts,
+                        }
+                        .to_string(lang),
+                    );
+                });
                 if ui
-                    .button(LangMessage::KillMinecraft.to_string(lang))
+                    .button(&*LangMessage::KillMinecraft.to_cached_string(lang))
                     .clicked()
                 {
-                    let mut child_lock = runtime.block_on(child.lock());
-                    let _ = runtime.block_on(child_lock.kill());
+                    self.kill(runtime);
                 }
             }
             _ => {
@@ -166,24 +691,39 @@ impl LaunchState {
                 } else {
                     format!(
                         "{} ({})",
-                        LangMessage::Launch.to_string(lang),
-                        LangMessage::Offline.to_string(lang)
+                        LangMessage::Launch.to_cached_string(lang),
+                        LangMessage::Offline.to_cached_string(lang)
                     )
                 };
                 let enabled = selected_instance.is_some() && auth_data.is_some() && !disabled;
                 ui.add_enabled_ui(enabled, |ui| {
                     if Self::big_button_clicked(ui, &button_text)
-                        || (enabled && (self.force_launch || self.launch_from_start))
+                        || (enabled
+                            && (self.force_launch
+                                || self.launch_from_start
+                                || self.relaunch
+                                || launch_requested))
                     {
                         self.launch_from_start = false;
+                        self.relaunch = false;
 
                         self.force_launch = false;
+                        self.restart_attempt = 0;
+                        let instance = selected_instance.unwrap();
+                        let auth_backend = instance.get_auth_backend().cloned().or_else(|| {
+                            config
+                                .get_selected_auth_profile()
+                                .map(|profile| AuthBackend::from_id(&profile.auth_backend_id))
+                        });
                         self.launch(
                             runtime,
                             config,
-                            &selected_instance.unwrap(),
-                            &auth_data.unwrap(),
-                            online,
+                            LastLaunch {
+                                instance,
+                                auth_data: auth_data.unwrap(),
+                                auth_backend,
+                                online,
+                            },
                         );
                     }
                 });
@@ -192,16 +732,35 @@ impl LaunchState {
 
         match &self.status {
             LauncherStatus::Error => {
-                ui.label(LangMessage::LaunchError.to_string(lang));
+                ui.label(&*LangMessage::LaunchError.to_cached_string(lang));
             }
             LauncherStatus::ProcessErrorCode(e) => {
-                ui.label(LangMessage::ProcessErrorCode(e.clone()).to_string(lang));
-                if ui.button(LangMessage::OpenLogs.to_string(lang)).clicked() {
+                ui.label(&*LangMessage::ProcessErrorCode(e.clone()).to_cached_string(lang));
+                if let Some(dialog) = &mut self.crash_dialog {
+                    if ui
+                        .button(&*LangMessage::ShowCrashDetails.to_cached_string(lang))
+                        .clicked()
+                    {
+                        dialog.open = true;
+                    }
+                } else if ui
+                    .button(&*LangMessage::OpenLogs.to_cached_string(lang))
+                    .clicked()
+                {
                     open::that(get_logs_dir(&config.get_launcher_dir())).unwrap();
                 }
             }
             _ => {}
         }
+
+        if self.game_output_cancellation_token.is_some()
+            || self.game_output.with_lines(|lines| !lines.is_empty())
+        {
+            ui.toggle_value(
+                &mut self.console_opened,
+                &*LangMessage::GameOutput.to_cached_string(lang),
+            );
+        }
     }
 
     pub fn render_download_ui(
@@ -209,6 +768,7 @@ impl LaunchState {
         ui: &mut egui::Ui,
         config: &mut Config,
         disabled: bool,
+        launch_requested: bool,
     ) -> ForceLaunchResultSelect {
         let lang = config.lang;
 
@@ -218,7 +778,7 @@ impl LaunchState {
                 if LaunchState::big_button_clicked(
                     ui,
                     &LangMessage::DownloadAndLaunch.to_string(lang),
-                ) || (!disabled && self.launch_from_start)
+                ) || (!disabled && (self.launch_from_start || launch_requested))
                 {
                     self.launch_from_start = false;
 
@@ -1,19 +1,37 @@
 use std::collections::HashSet;
 
 use eframe::egui;
+use log::error;
 use tokio::runtime::Runtime;
 
+use super::archive_state::ArchiveState;
 use super::auth_state::AuthState;
-use super::instance_sync_state::InstanceSyncState;
+use super::colors;
+use super::description_state::DescriptionState;
+use super::instance_sync_state::{AutoSyncRequest, InstanceSyncState};
 use super::java_state::JavaState;
+use super::launch_profiles_state::LaunchProfilesState;
 use super::launch_state::ForceLaunchResultSelect;
 use super::launch_state::LaunchState;
 use super::launch_state::RenderUiParams;
 use super::manifest_state::ManifestState;
-use super::metadata_state::MetadataState;
+use super::metadata_state::{MetadataState, can_read_local_only};
 use super::new_instance_state::NewInstanceState;
+use super::news_state::NewsState;
+use super::screenshots_state::ScreenshotsState;
+use super::server_status_state::ServerStatusState;
 use super::settings::SettingsState;
-use crate::config::runtime_config::Config;
+use super::shortcuts::{
+    KeyPresses, LauncherWindow, ShortcutAction, ShortcutState, get_shortcut_action,
+    poll_key_presses,
+};
+use super::task_registry::TaskRegistry;
+use super::widgets::{TrailingRow, icon_button};
+use crate::config::runtime_config::{Config, SAVE_INTERVAL};
+use crate::constants;
+use crate::lang::LangMessage;
+use crate::launcher::{launch, playtime};
+use crate::update_app::app::should_check_updates;
 use crate::utils;
 use crate::version::instance_storage::InstanceStatus;
 use crate::version::instance_storage::InstanceStorage;
@@ -31,15 +49,37 @@ pub struct LauncherApp {
     auth_state: AuthState,
     manifest_state: ManifestState,
     metadata_state: MetadataState,
+    description_state: DescriptionState,
     java_state: JavaState,
     instance_sync_state: InstanceSyncState,
     launch_state: LaunchState,
     new_instance_state: NewInstanceState,
+    launch_profiles_state: LaunchProfilesState,
+    archive_state: ArchiveState,
+    news_state: NewsState,
+    screenshots_state: ScreenshotsState,
+    server_status_state: ServerStatusState,
+
+    task_registry: TaskRegistry,
+    task_list_open: bool,
+
+    update_requested: bool,
+    // launch action requested by a shortcut in the current frame
+    launch_shortcut: Option<ShortcutAction>,
 }
 
 impl eframe::App for LauncherApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         self.ui(ctx);
+
+        self.config.save_if_due();
+        if self.config.has_pending_save() {
+            ctx.request_repaint_after(SAVE_INTERVAL);
+        }
+    }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.config.save_pending();
     }
 
     fn clear_color(&self, visuals: &egui::Visuals) -> [f32; 4] {
@@ -50,29 +90,165 @@ impl eframe::App for LauncherApp {
 impl LauncherApp {
     pub fn new(config: Config, ctx: &egui::Context, launch: bool) -> Self {
         let runtime = Runtime::new().unwrap();
+        let task_registry = TaskRegistry::new();
+
+        // leftovers of instance deletions interrupted by a previous exit
+        runtime.spawn_blocking(|| {
+            utils::temp_files::remove_temp_files(
+                &utils::get_temp_dir(),
+                utils::temp_files::ORPHANED_TEMP_FILES_AGE,
+            );
+        });
+        // sessions of the games that outlived the previous launcher run
+        let launcher_dir = config.get_launcher_dir();
+        runtime.spawn_blocking(move || playtime::reconcile_sessions(&launcher_dir));
 
         LauncherApp {
             settings_state: SettingsState::new(),
             auth_state: AuthState::new(ctx, &config),
-            manifest_state: ManifestState::new(&runtime, ctx, &config),
-            metadata_state: MetadataState::new(),
-            java_state: JavaState::new(ctx),
-            instance_sync_state: InstanceSyncState::new(ctx),
+            manifest_state: ManifestState::new(&runtime, ctx, &config, task_registry.clone()),
+            metadata_state: MetadataState::new(task_registry.clone()),
+            description_state: DescriptionState::new(),
+            java_state: JavaState::new(ctx, task_registry.clone()),
+            instance_sync_state: InstanceSyncState::new(ctx, task_registry.clone()),
             launch_state: LaunchState::new(launch, ctx.clone()),
             new_instance_state: NewInstanceState::new(&runtime, ctx),
+            launch_profiles_state: LaunchProfilesState::new(),
+            archive_state: ArchiveState::new(ctx, task_registry.clone()),
+            news_state: NewsState::new(),
+            screenshots_state: ScreenshotsState::new(),
+            server_status_state: ServerStatusState::new(),
+            task_registry,
+            task_list_open: false,
             instance_storage: runtime.block_on(InstanceStorage::load(&config)),
             config,
             runtime,
+            update_requested: false,
+            launch_shortcut: None,
+        }
+    }
+
+    pub fn update_requested(&self) -> bool {
+        self.update_requested
+    }
+
+    pub fn into_config(self) -> Config {
+        self.config.save_pending();
+        self.config
+    }
+
+    fn ready_for_launch(&self) -> bool {
+        self.java_state.ready_for_launch()
+            && self
+                .get_selected_instance(&self.config)
+                .is_some_and(|instance| instance.status == InstanceStatus::UpToDate)
+    }
+
+    fn refresh(&mut self, ctx: &egui::Context) {
+        self.auth_state.reset(&mut self.config, &self.runtime, ctx);
+        self.manifest_state
+            .retry_fetch(&self.runtime, &self.config, ctx);
+        self.metadata_state.clear();
+        self.description_state.clear();
+        self.news_state.refresh();
+
+        // metadata is checked after manifest is fetched
+        // java is checked after metadata is fetched
+    }
+
+    fn get_open_windows(&self) -> Vec<LauncherWindow> {
+        let mut open_windows = vec![];
+        if self.task_list_open {
+            open_windows.push(LauncherWindow::Tasks);
+        }
+        if self.news_state.is_window_open() {
+            open_windows.push(LauncherWindow::News);
+        }
+        if self.screenshots_state.is_window_open() {
+            open_windows.push(LauncherWindow::Screenshots);
+        }
+        if self.auth_state.is_window_open(&self.runtime) {
+            open_windows.push(LauncherWindow::Auth);
+        }
+        if self.instance_sync_state.is_window_open() {
+            open_windows.push(LauncherWindow::Sync);
+        }
+        if self.new_instance_state.is_window_open() {
+            open_windows.push(LauncherWindow::NewInstance);
+        }
+        if self.settings_state.is_window_open() {
+            open_windows.push(LauncherWindow::Settings);
+        }
+        if self.launch_profiles_state.is_window_open() {
+            open_windows.push(LauncherWindow::LaunchProfile);
+        }
+        open_windows
+    }
+
+    fn handle_shortcuts(&mut self, ctx: &egui::Context, keys: KeyPresses) {
+        let state = ShortcutState {
+            text_field_focused: ctx.wants_keyboard_input(),
+            ready_for_launch: self.ready_for_launch(),
+            open_windows: self.get_open_windows(),
+        };
+        self.launch_shortcut = None;
+        match get_shortcut_action(keys, &state) {
+            Some(action @ (ShortcutAction::Launch | ShortcutAction::DownloadAndLaunch)) => {
+                self.launch_shortcut = Some(action);
+            }
+            Some(ShortcutAction::Refresh) => self.refresh(ctx),
+            Some(ShortcutAction::OpenSettings) => self.settings_state.open_settings(),
+            Some(ShortcutAction::CloseWindow(window)) => match window {
+                LauncherWindow::Auth => {
+                    self.auth_state
+                        .close_window(&mut self.config, &self.runtime, ctx)
+                }
+                LauncherWindow::Sync => self.instance_sync_state.close_window(),
+                LauncherWindow::NewInstance => self.new_instance_state.close_window(),
+                LauncherWindow::Settings => self.settings_state.close_window(),
+                LauncherWindow::Tasks => self.task_list_open = false,
+                LauncherWindow::LaunchProfile => self.launch_profiles_state.close_window(),
+                LauncherWindow::News => self.news_state.close_window(),
+                LauncherWindow::Screenshots => self.screenshots_state.close_window(),
+            },
+            None => {}
         }
     }
 
     pub fn ui(&mut self, ctx: &egui::Context) {
+        self.handle_shortcuts(ctx, poll_key_presses(ctx));
+        self.news_state.update(&self.runtime, &mut self.config, ctx);
+        self.screenshots_state.update(&self.config, ctx);
+
+        // known after the first response from the manifest server or the updater
+        if let Some(skew_secs) = shared::clock::get_clock_skew()
+            && skew_secs.abs() > constants::CLOCK_SKEW_WARNING_SECS
+        {
+            egui::TopBottomPanel::top("clock_skew_banner")
+                .resizable(false)
+                .show(ctx, |ui| {
+                    ui.add_space(5.0);
+                    ui.label(
+                        egui::RichText::new(
+                            LangMessage::ClockSkewWarning(skew_secs).to_string(self.config.lang),
+                        )
+                        .color(colors::offline(ui.style().visuals.dark_mode)),
+                    );
+                    ui.add_space(5.0);
+                });
+        }
+
         egui::TopBottomPanel::bottom("bottom_panel")
             .resizable(false)
             .show(ctx, |ui| {
                 ui.add_space(5.0);
                 ui.horizontal(|ui| {
-                    let selected_metadata = self.metadata_state.get_version_metadata(&self.config);
+                    let selected_metadata = self
+                        .metadata_state
+                        .get_version_metadata(&self.config)
+                        .filter(|_| !self.metadata_state.launcher_outdated(&self.config));
+                    self.settings_state
+                        .set_syncing(self.instance_sync_state.is_syncing());
                     self.settings_state.render_settings(
                         ui,
                         &mut self.config,
@@ -81,22 +257,35 @@ impl LauncherApp {
                         ctx,
                         &self.instance_storage,
                     );
+                    if let Some(url) = self.settings_state.take_removed_manifest_url() {
+                        self.metadata_state.remove_manifest_url(&url);
+                    }
 
+                    let selected_instance = self.get_selected_instance(&self.config);
+                    let pinned = selected_instance
+                        .as_ref()
+                        .is_some_and(|instance| instance.pinned);
+                    // syncing is blocked while the game files are being moved around
+                    let archived = self.archive_state.is_busy()
+                        || selected_instance
+                            .as_ref()
+                            .is_some_and(|instance| instance.status == InstanceStatus::Archived);
                     self.instance_sync_state.render_sync_button(
                         ui,
                         &self.runtime,
                         &self.config,
-                        selected_metadata,
+                        selected_metadata.filter(|_| !archived),
+                        pinned,
                     );
 
-                    if ui.button("🔄").clicked() {
-                        self.auth_state.reset(&mut self.config, &self.runtime, ctx);
-                        self.manifest_state
-                            .retry_fetch(&self.runtime, &self.config, ctx);
-                        self.metadata_state.clear();
+                    if icon_button(ui, "🔄", LangMessage::Refresh, self.config.lang, true).clicked()
+                    {
+                        self.refresh(ctx);
+                    }
+                    self.news_state.render_button(ui, &mut self.config);
 
-                        // metadata is checked after manifest is fetched
-                        // java is checked after metadata is fetched
+                    if self.task_registry.render_spinner(ui, self.config.lang) {
+                        self.task_list_open = !self.task_list_open;
                     }
                 });
                 ui.add_space(5.0);
@@ -113,6 +302,32 @@ impl LauncherApp {
             .show(ctx, |ui| {
                 self.render_central_elements(ui, ctx);
             });
+
+        self.task_registry
+            .render_task_list(ctx, self.config.lang, &mut self.task_list_open);
+        self.news_state.render_window(ctx, &self.config);
+        self.screenshots_state
+            .render_window(ctx, &self.runtime, &self.config);
+        self.render_config_reset_notice(ctx);
+    }
+
+    fn render_config_reset_notice(&mut self, ctx: &egui::Context) {
+        let Some(backup_path) = &self.config.reset_config_backup else {
+            return;
+        };
+        let lang = self.config.lang;
+        let mut open = true;
+        egui::Window::new(LangMessage::ConfigResetTitle.to_string(lang))
+            .id(egui::Id::new("config_reset_notice"))
+            .collapsible(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(LangMessage::ConfigResetNotice.to_string(lang));
+                ui.code(backup_path.to_string_lossy());
+            });
+        if !open {
+            self.config.reset_config_backup = None;
+        }
     }
 
     fn get_selected_instance(&self, config: &Config) -> Option<LocalInstance> {
@@ -122,21 +337,67 @@ impl LauncherApp {
 
     fn set_metadata_task(&mut self, ctx: &egui::Context) {
         if let Some(selected_instance) = self.get_selected_instance(&self.config) {
+            let local_only =
+                can_read_local_only(self.manifest_state.offline(), selected_instance.status);
+            let public_key = selected_instance
+                .manifest_url
+                .as_deref()
+                .and_then(|url| self.config.get_manifest_public_key(url));
             self.metadata_state.set_metadata_task(
                 &self.runtime,
                 &self.config,
                 &selected_instance.version_info,
                 ctx,
+                local_only,
+                public_key,
             );
         }
     }
 
+    fn copy_launch_command(&self, ctx: &egui::Context) {
+        let selected_metadata = self.metadata_state.get_version_metadata(&self.config);
+        let auth_data = self.auth_state.get_auth_data(&self.config);
+        let (Some(selected_metadata), Some(auth_data)) = (selected_metadata, auth_data) else {
+            return;
+        };
+        match self.runtime.block_on(launch::build_launch_command(
+            &selected_metadata,
+            &self.config,
+            &auth_data,
+            !self.auth_state.offline(),
+        )) {
+            Ok(launch_command) => ctx.copy_text(launch_command.to_sanitized_string()),
+            Err(e) => error!("Failed to build the launch command:\n{e:?}"),
+        }
+    }
+
+    fn on_instance_selected(&mut self, ctx: &egui::Context) {
+        self.instance_sync_state.cancel_sync();
+        self.set_metadata_task(ctx);
+    }
+
     fn render_central_elements(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
         let (manifest, updated) = self.manifest_state.take_manifest(&mut self.config);
         if let Some(manifest) = manifest {
-            self.instance_sync_state.cancel_sync();
+            self.instance_sync_state.cancel_all_syncs();
             let url = self.config.get_effective_version_manifest_url();
             self.instance_storage.set_remote_manifest(manifest, url);
+            // the cached manifest is shown until the fetched one replaces it
+            if !self.config.pause_auto_sync && self.manifest_state.online() {
+                let requests = self
+                    .instance_storage
+                    .get_auto_sync_instances()
+                    .into_iter()
+                    .map(|instance| AutoSyncRequest {
+                        public_key: instance
+                            .manifest_url
+                            .as_deref()
+                            .and_then(|url| self.config.get_manifest_public_key(url)),
+                        version_info: instance.version_info,
+                    })
+                    .collect();
+                self.instance_sync_state.queue_auto_sync(requests);
+            }
         }
         if updated {
             let (local_instance_names, remote_instance_names) = self
@@ -163,59 +424,170 @@ impl LauncherApp {
                     .add_local_instance(&self.config, version_info),
             );
         }
+        if let Some(instance) = self.new_instance_state.take_imported_instance() {
+            self.runtime.block_on(
+                self.instance_storage
+                    .add_imported_instance(&self.config, instance),
+            );
+        }
 
         ui.horizontal(|ui| {
-            ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
-                let (local_instance_names, remote_instance_names) =
-                    self.instance_storage.get_all_names_for_manifest_url(
-                        self.config.get_effective_version_manifest_url(),
+            let (local_instance_names, remote_instance_names) = self
+                .instance_storage
+                .get_all_names_for_manifest_url(self.config.get_effective_version_manifest_url());
+
+            self.server_status_state.update(
+                &self.runtime,
+                ctx,
+                self.config.server_status,
+                &local_instance_names
+                    .iter()
+                    .chain(&remote_instance_names)
+                    .collect::<Vec<_>>(),
+                &self.instance_storage,
+            );
+
+            let row = TrailingRow::new(ui, "instance_row");
+            let selected_version_changed = row.show_fill(ui, |ui| {
+                self.manifest_state.render_combo_box(
+                    ui,
+                    &mut self.config,
+                    &local_instance_names,
+                    &remote_instance_names,
+                    &self.instance_storage,
+                    &self.server_status_state,
+                )
+            });
+            if selected_version_changed {
+                self.on_instance_selected(ctx);
+            }
+
+            row.show_trailing(ui, |ui| {
+                let selected_metadata = self.metadata_state.get_version_metadata(&self.config);
+                let selected_instance = self.get_selected_instance(&self.config);
+                self.settings_state.render_instance_settings(
+                    ui,
+                    &self.runtime,
+                    &mut self.config,
+                    selected_metadata.as_ref(),
+                    selected_instance.as_ref(),
+                    self.java_state.is_java_32_bit(),
+                );
+                if let Some((instance_name, auto_sync)) =
+                    self.settings_state.take_auto_sync_change()
+                {
+                    self.runtime.block_on(self.instance_storage.set_auto_sync(
+                        &self.config,
+                        &instance_name,
+                        auto_sync,
+                    ));
+                }
+                if let Some((instance_name, pinned)) = self.settings_state.take_pin_change() {
+                    self.runtime.block_on(self.instance_storage.set_pinned(
+                        &self.config,
+                        &instance_name,
+                        pinned,
+                    ));
+                    // the selected version changes between the installed and the remote one
+                    self.on_instance_selected(ctx);
+                }
+                if self.settings_state.take_copy_launch_command_request() {
+                    self.copy_launch_command(ctx);
+                }
+                if self.settings_state.take_archive_request()
+                    && let Some(instance_name) = self.config.selected_instance_name.clone()
+                    && !self.instance_sync_state.is_syncing()
+                    && self.launch_state.get_running_instance() != Some(instance_name.as_str())
+                {
+                    self.archive_state.start_archive(
+                        &self.runtime,
+                        &self.config,
+                        ctx,
+                        &instance_name,
                     );
+                }
+                if let Some(export_path) = self.settings_state.take_export_path()
+                    && let Some(instance_name) = self.config.selected_instance_name.clone()
+                    && let Some(instance) = self.instance_storage.get_instance(&instance_name)
+                    && !self.instance_sync_state.is_syncing()
+                {
+                    self.archive_state.start_export(
+                        &self.runtime,
+                        &self.config,
+                        ctx,
+                        instance,
+                        export_path,
+                    );
+                }
 
                 let mut all_names: HashSet<String> =
                     local_instance_names.clone().into_iter().collect();
                 all_names.extend(remote_instance_names.clone());
+                // the files can't be moved while the game or a sync uses them
+                let can_rename_selected = self
+                    .config
+                    .selected_instance_name
+                    .as_deref()
+                    .is_some_and(|name| {
+                        self.instance_storage.can_rename(name)
+                            && !self.instance_sync_state.is_syncing()
+                            && self.launch_state.get_running_instance() != Some(name)
+                    });
                 let new_instance_result = self.new_instance_state.render_ui(
                     &self.runtime,
                     ui,
                     &mut self.config,
                     &all_names,
                     &local_instance_names,
+                    can_rename_selected,
                 );
+                self.screenshots_state.render_button(ui, &self.config);
 
                 if let Some(instance_to_delete) = new_instance_result.instance_to_delete {
                     self.config.auth_profiles.remove(&instance_to_delete);
-                    self.config.save();
+                    self.config.shared_data.remove(&instance_to_delete);
+                    self.config.env_vars.remove(&instance_to_delete);
+                    self.config.save_now();
                     self.runtime.block_on(
                         self.instance_storage
                             .delete_instance(&self.config, &instance_to_delete),
                     );
                     self.instance_sync_state.reset_status();
                 }
-
-                let selected_instance = self.metadata_state.get_version_metadata(&self.config);
-                self.settings_state.render_instance_settings(
-                    ui,
-                    &self.runtime,
-                    &mut self.config,
-                    selected_instance.as_deref(),
-                );
-
-                let selected_version_changed = self.manifest_state.render_combo_box(
-                    ui,
-                    &mut self.config,
-                    &local_instance_names,
-                    &remote_instance_names,
-                );
-                if selected_version_changed {
-                    self.instance_sync_state.cancel_sync();
-                    self.set_metadata_task(ctx);
+                if let Some((old_name, new_name)) = new_instance_result.instance_to_rename {
+                    match self.runtime.block_on(self.instance_storage.rename_instance(
+                        &mut self.config,
+                        &old_name,
+                        &new_name,
+                    )) {
+                        Ok(()) => {
+                            self.config.save_now();
+                            self.on_instance_selected(ctx);
+                        }
+                        Err(e) => error!("Failed to rename instance {old_name}:\n{e:?}"),
+                    }
                 }
             });
         });
 
+        if self
+            .config
+            .selected_instance_name
+            .as_deref()
+            .is_some_and(|name| self.instance_storage.is_rollout_pending(name))
+        {
+            ui.label(
+                egui::RichText::new(LangMessage::UpdatePendingRollout.to_string(self.config.lang))
+                    .small()
+                    .weak(),
+            );
+        }
+
         self.auth_state.update(&self.runtime, &mut self.config);
 
         ui.vertical_centered(|ui| {
+            self.manifest_state.render_parse_error(ui, self.config.lang);
+            self.manifest_state.render_cache_note(ui, self.config.lang);
             if !self.metadata_state.render_status(ui, &self.config) {
                 self.instance_sync_state.render_status(ui, &self.config);
             }
@@ -225,24 +597,40 @@ impl LauncherApp {
                 &self.runtime,
                 &self.config,
                 selected_instance,
+                &mut self.instance_storage,
             );
         });
 
         ui.horizontal(|ui| {
-            ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
-                let version_metadata = self.metadata_state.get_version_metadata(&self.config);
-                let auth_backend =
-                    version_metadata.and_then(|metadata| metadata.get_auth_backend().cloned());
-                self.auth_state.render_ui(
-                    ui,
-                    &mut self.config,
-                    &self.runtime,
-                    ctx,
-                    auth_backend.as_ref(),
-                );
-            });
+            let version_metadata = self.metadata_state.get_version_metadata(&self.config);
+            let auth_backend =
+                version_metadata.and_then(|metadata| metadata.get_auth_backend().cloned());
+            self.auth_state.render_ui(
+                ui,
+                &mut self.config,
+                &self.runtime,
+                ctx,
+                auth_backend.as_ref(),
+            );
         });
 
+        if let Some((instance_name, content_hash)) =
+            self.instance_sync_state.update(&mut self.config)
+        {
+            self.runtime.block_on(self.instance_storage.mark_downloaded(
+                &self.config,
+                &instance_name,
+                content_hash,
+            ));
+        }
+        if self
+            .archive_state
+            .update(&self.runtime, &self.config, &mut self.instance_storage)
+        {
+            self.instance_sync_state.reset_status();
+            self.set_metadata_task(ctx);
+        }
+
         if let Some(selected_instance) = self.get_selected_instance(&self.config) {
             if self.metadata_state.update() {
                 if self.manifest_state.online()
@@ -262,10 +650,12 @@ impl LauncherApp {
                         &version_metadata,
                         &self.config,
                         ctx,
+                        self.manifest_state.offline(),
                     );
                     if !self.config.xmx.contains_key(version_metadata.get_name()) {
                         self.config.xmx.insert(
                             version_metadata.get_name().to_string(),
+                            // capped to what this machine can spare
                             utils::format_xmx(version_metadata.get_recommended_xmx()),
                         );
                     }
@@ -273,13 +663,6 @@ impl LauncherApp {
             }
 
             if let Some(version_metadata) = self.metadata_state.get_version_metadata(&self.config) {
-                if self.instance_sync_state.update() {
-                    self.runtime.block_on(
-                        self.instance_storage
-                            .mark_downloaded(&self.config, version_metadata.get_name()),
-                    );
-                }
-
                 self.java_state
                     .update(&self.runtime, &version_metadata, &mut self.config, ctx);
             }
@@ -291,20 +674,75 @@ impl LauncherApp {
                 .render_ui(ui, &mut self.config, selected_instance.as_deref());
 
             self.launch_state.update(&self.runtime, &self.config);
+            if let Some((auth_backend, auth_data)) = self.launch_state.take_refreshed_auth() {
+                self.auth_state
+                    .save_refreshed_auth(&self.config, &auth_backend, auth_data);
+            }
+            if self.instance_sync_state.take_kill_request() {
+                self.launch_state.kill(&self.runtime);
+            }
+            self.instance_sync_state.set_running_instance(
+                &self.runtime,
+                &self.config,
+                ctx,
+                self.launch_state.get_running_instance(),
+            );
+            self.instance_sync_state
+                .update_auto_sync(&self.runtime, &self.config, ctx);
+
+            if self.metadata_state.launcher_outdated(&self.config) {
+                if should_check_updates()
+                    && ui
+                        .button(LangMessage::UpdateLauncher.to_string(self.config.lang))
+                        .clicked()
+                {
+                    self.update_requested = true;
+                }
+                return;
+            }
+
+            let picked_profile =
+                self.launch_profiles_state
+                    .render_ui(ui, &mut self.config, |profile| {
+                        self.instance_storage
+                            .get_instance(&profile.instance_name)
+                            .is_some()
+                            && self.auth_state.has_account(&profile.auth_profile)
+                    });
+            if let Some(name) = picked_profile {
+                let previous_instance_name = self.config.selected_instance_name.clone();
+                if self.config.select_launch_profile(&name)
+                    && self.config.selected_instance_name != previous_instance_name
+                {
+                    self.on_instance_selected(ctx);
+                }
+            }
 
-            if self.java_state.ready_for_launch()
+            if let Some(selected_instance) = self.get_selected_instance(&self.config)
                 && self
-                    .get_selected_instance(&self.config)
-                    .is_some_and(|instance| instance.status == InstanceStatus::UpToDate)
+                    .archive_state
+                    .render_ui(ui, &self.runtime, &self.config, &selected_instance)
             {
+                return;
+            }
+
+            if self.ready_for_launch() {
                 let auth_data = self.auth_state.get_auth_data(&self.config);
                 let selected_instance = self.metadata_state.get_version_metadata(&self.config);
 
+                if self.manifest_state.offline() {
+                    ui.label(
+                        egui::RichText::new(LangMessage::OfflineMode.to_string(self.config.lang))
+                            .color(colors::offline(ui.style().visuals.dark_mode)),
+                    );
+                }
+
                 let params = RenderUiParams {
                     online: !self.auth_state.offline(),
                     disabled: self.instance_sync_state.is_syncing()
                         || self.manifest_state.is_fetching()
                         || self.metadata_state.is_getting(),
+                    launch_requested: self.launch_shortcut == Some(ShortcutAction::Launch),
                 };
                 self.launch_state.render_ui(
                     &self.runtime,
@@ -326,6 +764,7 @@ impl LauncherApp {
                         || self.java_state.checking_java()
                         || !some_version_selected
                         || !have_some_auth_data,
+                    self.launch_shortcut == Some(ShortcutAction::DownloadAndLaunch),
                 );
                 match force_launch_result {
                     ForceLaunchResultSelect::ForceLaunch => {
@@ -354,5 +793,14 @@ impl LauncherApp {
                 }
             }
         });
+
+        let version_metadata = self.metadata_state.get_version_metadata(&self.config);
+        self.description_state.update(
+            &self.runtime,
+            &self.config,
+            version_metadata.as_deref(),
+            ctx,
+        );
+        self.description_state.render_ui(ui, &self.config);
     }
 }
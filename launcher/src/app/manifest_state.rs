@@ -1,14 +1,29 @@
-use crate::{config::runtime_config::Config, lang::LangMessage};
+use crate::{
+    config::runtime_config::Config,
+    lang::{Lang, LangMessage},
+    version::{
+        instance_storage::{InstanceStatus, InstanceStorage},
+        manifest_cache,
+    },
+};
 
 use egui::RichText;
-use log::error;
+use egui::text::LayoutJob;
+use log::{error, warn};
+use shared::json::{JsonParseError, get_json_parse_error};
+use shared::signing::is_signature_error;
 use shared::utils::is_connect_error;
 use shared::version::version_manifest::VersionManifest;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::runtime::Runtime;
 
 use super::{
     background_task::{BackgroundTask, BackgroundTaskResult},
     colors,
+    json_error_panel::render_json_error_panel,
+    server_status_state::{ServerState, ServerStatusState},
+    task_registry::{TaskKey, TaskRegistry},
 };
 
 #[derive(PartialEq)]
@@ -17,6 +32,8 @@ enum FetchStatus {
     Fetched,
     FetchErrorOffline,
     FetchError(String),
+    ParseError(JsonParseError),
+    SignatureError,
 }
 
 struct ManifestFetchResult {
@@ -26,22 +43,37 @@ struct ManifestFetchResult {
 
 fn fetch_manifest<Callback>(
     runtime: &tokio::runtime::Runtime,
+    launcher_dir: PathBuf,
     url: String,
+    public_key: Option<String>,
     callback: Callback,
 ) -> BackgroundTask<ManifestFetchResult>
 where
     Callback: FnOnce() + Send + 'static,
 {
     let fut = async move {
-        let result = VersionManifest::fetch(&url).await;
+        let result = VersionManifest::fetch_verified(&url, public_key.as_deref()).await;
         match result {
-            Ok(manifest) => ManifestFetchResult {
-                status: FetchStatus::Fetched,
-                manifest: Some(manifest),
-            },
+            Ok(manifest) => {
+                if let Err(e) =
+                    manifest_cache::write_cached_manifest(&launcher_dir, &url, &manifest).await
+                {
+                    warn!("Failed to cache the version manifest:\n{e:?}");
+                }
+                ManifestFetchResult {
+                    status: FetchStatus::Fetched,
+                    manifest: Some(manifest),
+                }
+            }
             Err(e) => ManifestFetchResult {
                 status: if is_connect_error(&e) {
                     FetchStatus::FetchErrorOffline
+                } else if is_signature_error(&e) {
+                    error!("Version manifest signature check failed:\n{e:?}");
+                    FetchStatus::SignatureError
+                } else if let Some(parse_error) = get_json_parse_error(&e) {
+                    error!("Invalid version manifest:\n{e:?}");
+                    FetchStatus::ParseError(parse_error.clone())
                 } else {
                     error!("Error fetching version manifest:\n{e:?}");
                     FetchStatus::FetchError(e.to_string())
@@ -57,28 +89,64 @@ where
 pub struct ManifestState {
     status: FetchStatus,
     fetch_task: Option<BackgroundTask<ManifestFetchResult>>,
+    task_registry: TaskRegistry,
+    // the cached manifest of the selected url, handed out before the fetch finishes
+    cached_manifest: Option<VersionManifest>,
+    // unix time the shown manifest was fetched at, None once it's fetched again
+    cached_at: Option<u64>,
 }
 
 impl ManifestState {
+    fn load_cached_manifest(&mut self, config: &Config) {
+        let cached = manifest_cache::read_cached_manifest(
+            &config.get_launcher_dir(),
+            config.get_effective_version_manifest_url(),
+        );
+        self.cached_at = cached.as_ref().map(|cached| cached.fetched_at);
+        self.cached_manifest = cached.map(|cached| cached.manifest);
+    }
+
     fn set_fetch_task(&mut self, runtime: &Runtime, config: &Config, ctx: &egui::Context) {
         let ctx = ctx.clone();
         let url = config.get_effective_version_manifest_url();
-        self.fetch_task = Some(fetch_manifest(runtime, url.to_string(), move || {
-            ctx.request_repaint();
-        }));
+        let public_key = config.get_manifest_public_key(url);
+        let task = fetch_manifest(
+            runtime,
+            config.get_launcher_dir(),
+            url.to_string(),
+            public_key,
+            move || {
+                ctx.request_repaint();
+            },
+        );
+        self.task_registry
+            .register(TaskKey::FetchManifest, &task, None);
+        self.fetch_task = Some(task);
     }
 
-    pub fn new(runtime: &Runtime, ctx: &egui::Context, config: &Config) -> ManifestState {
+    pub fn new(
+        runtime: &Runtime,
+        ctx: &egui::Context,
+        config: &Config,
+        task_registry: TaskRegistry,
+    ) -> ManifestState {
         let mut result = ManifestState {
             status: FetchStatus::NotFetched,
             fetch_task: None,
+            task_registry,
+            cached_manifest: None,
+            cached_at: None,
         };
+        result.load_cached_manifest(config);
         result.set_fetch_task(runtime, config, ctx);
 
         result
     }
 
     pub fn take_manifest(&mut self, config: &mut Config) -> (Option<VersionManifest>, bool) {
+        if let Some(manifest) = self.cached_manifest.take() {
+            return (Some(manifest), true);
+        }
         if let Some(task) = self.fetch_task.as_ref()
             && task.has_result()
         {
@@ -95,6 +163,9 @@ impl ManifestState {
                         config.save();
                     }
                     self.status = result.status;
+                    if self.status == FetchStatus::Fetched {
+                        self.cached_at = None;
+                    }
 
                     return (result.manifest, true);
                 }
@@ -107,23 +178,135 @@ impl ManifestState {
         (None, false)
     }
 
+    // (glyph, color, tooltip), archived instances are shown without one
+    fn get_status_glyph(
+        instance_storage: &InstanceStorage,
+        instance_name: &str,
+        dark_mode: bool,
+    ) -> Option<(&'static str, egui::Color32, LangMessage)> {
+        let status = instance_storage.get_status(instance_name)?;
+        if status != InstanceStatus::Archived && instance_storage.is_local_only(instance_name) {
+            return Some((
+                "●",
+                colors::local_only(dark_mode),
+                LangMessage::InstanceLocalOnly,
+            ));
+        }
+        match status {
+            InstanceStatus::UpToDate => Some((
+                "✔",
+                colors::up_to_date(dark_mode),
+                LangMessage::InstanceUpToDate,
+            )),
+            InstanceStatus::Outdated => Some((
+                "⬆",
+                colors::outdated(dark_mode),
+                LangMessage::InstanceOutdated,
+            )),
+            InstanceStatus::Missing => Some((
+                "⬇",
+                colors::not_installed(dark_mode),
+                LangMessage::InstanceNotInstalled,
+            )),
+            InstanceStatus::Archived => None,
+        }
+    }
+
+    fn render_instance_entry(
+        ui: &mut egui::Ui,
+        selected_instance_name: &mut Option<String>,
+        instance_name: &str,
+        text: RichText,
+        instance_storage: &InstanceStorage,
+        server_state: ServerState,
+        lang: Lang,
+    ) {
+        let dark_mode = ui.style().visuals.dark_mode;
+        let glyph = Self::get_status_glyph(instance_storage, instance_name, dark_mode);
+        let (text, badge) = match server_state {
+            ServerState::Unknown => (text, None),
+            ServerState::Online(status) => (
+                text,
+                Some(
+                    RichText::new(format!(
+                        " ({})",
+                        LangMessage::ServerPlayers {
+                            online: status.players.online,
+                            max: status.players.max,
+                        }
+                        .to_string(lang)
+                    ))
+                    .color(colors::ok(dark_mode)),
+                ),
+            ),
+            ServerState::Offline => (
+                text.weak(),
+                Some(
+                    RichText::new(format!(
+                        " ({})",
+                        LangMessage::ServerOffline.to_cached_string(lang)
+                    ))
+                    .weak(),
+                ),
+            ),
+        };
+
+        let mut job = LayoutJob::default();
+        if let Some((glyph, color, _)) = &glyph {
+            RichText::new(format!("{glyph} ")).color(*color).append_to(
+                &mut job,
+                ui.style(),
+                egui::FontSelection::Default,
+                egui::Align::Center,
+            );
+        }
+        text.append_to(
+            &mut job,
+            ui.style(),
+            egui::FontSelection::Default,
+            egui::Align::Center,
+        );
+        if let Some(badge) = badge {
+            badge.append_to(
+                &mut job,
+                ui.style(),
+                egui::FontSelection::Default,
+                egui::Align::Center,
+            );
+        }
+
+        let response =
+            ui.selectable_value(selected_instance_name, Some(instance_name.to_string()), job);
+        if let Some((_, _, message)) = glyph {
+            response.on_hover_text(&*message.to_cached_string(lang));
+        }
+    }
+
     pub fn render_combo_box(
         &mut self,
         ui: &mut egui::Ui,
         config: &mut Config,
-        local_instance_names: &Vec<String>,
-        remote_instance_names: &Vec<String>,
+        local_instance_names: &[String],
+        remote_instance_names: &[String],
+        instance_storage: &InstanceStorage,
+        server_status_state: &ServerStatusState,
     ) -> bool {
         let mut selected_instance_name = config.selected_instance_name.clone();
         let dark_mode = ui.style().visuals.dark_mode;
 
+        // playable servers first, the order doesn't change while the feature is disabled
+        let mut local_instance_names = local_instance_names.to_vec();
+        server_status_state.sort_names(&mut local_instance_names, instance_storage);
+        let mut remote_instance_names = remote_instance_names.to_vec();
+        server_status_state.sort_names(&mut remote_instance_names, instance_storage);
+
         ui.horizontal(|ui| {
             let selected_text = if let Some(instance_text) = config.selected_instance_name.clone() {
                 match self.status {
                     FetchStatus::NotFetched => RichText::new(format!(
                         "{} ({})",
                         instance_text,
-                        LangMessage::FetchingRemote.to_string(config.lang)
+                        LangMessage::FetchingRemote.to_cached_string(config.lang)
                     ))
                     .color(colors::in_progress(dark_mode)),
                     FetchStatus::Fetched => {
@@ -132,18 +315,26 @@ impl ManifestState {
                     FetchStatus::FetchErrorOffline => RichText::new(format!(
                         "{} ({})",
                         instance_text,
-                        LangMessage::Offline.to_string(config.lang)
+                        LangMessage::Offline.to_cached_string(config.lang)
                     ))
                     .color(colors::offline(dark_mode)),
-                    FetchStatus::FetchError(_) => RichText::new(format!(
+                    FetchStatus::FetchError(_) | FetchStatus::ParseError(_) => {
+                        RichText::new(format!(
+                            "{} ({})",
+                            instance_text,
+                            LangMessage::ErrorFetchingRemote.to_cached_string(config.lang)
+                        ))
+                        .color(colors::error(dark_mode))
+                    }
+                    FetchStatus::SignatureError => RichText::new(format!(
                         "{} ({})",
                         instance_text,
-                        LangMessage::ErrorFetchingRemote.to_string(config.lang)
+                        LangMessage::InvalidManifestSignature.to_cached_string(config.lang)
                     ))
                     .color(colors::error(dark_mode)),
                 }
             } else {
-                RichText::new(LangMessage::SelectInstance.to_string(config.lang))
+                RichText::new(&*LangMessage::SelectInstance.to_cached_string(config.lang))
                     .color(colors::action(dark_mode))
             };
 
@@ -152,22 +343,42 @@ impl ManifestState {
                 .selected_text(selected_text)
                 .show_ui(ui, |ui| {
                     if !local_instance_names.is_empty() || !remote_instance_names.is_empty() {
-                        for instance_name in local_instance_names {
-                            ui.selectable_value(
+                        for instance_name in &local_instance_names {
+                            let text = if instance_storage.get_status(instance_name)
+                                == Some(InstanceStatus::Archived)
+                            {
+                                RichText::new(format!(
+                                    "{} ({})",
+                                    instance_name,
+                                    LangMessage::Archived.to_cached_string(config.lang)
+                                ))
+                                .weak()
+                            } else {
+                                RichText::new(instance_name)
+                            };
+                            Self::render_instance_entry(
+                                ui,
                                 &mut selected_instance_name,
-                                Some(instance_name.clone()),
                                 instance_name,
+                                text,
+                                instance_storage,
+                                server_status_state.get_state(instance_storage, instance_name),
+                                config.lang,
                             );
                         }
-                        for instance_name in remote_instance_names {
-                            ui.selectable_value(
+                        for instance_name in &remote_instance_names {
+                            Self::render_instance_entry(
+                                ui,
                                 &mut selected_instance_name,
-                                Some(instance_name.clone()),
-                                egui::WidgetText::from(instance_name).italics(),
+                                instance_name,
+                                RichText::new(instance_name).italics(),
+                                instance_storage,
+                                server_status_state.get_state(instance_storage, instance_name),
+                                config.lang,
                             );
                         }
                     } else {
-                        ui.label(LangMessage::NoInstances.to_string(config.lang));
+                        ui.label(&*LangMessage::NoInstances.to_cached_string(config.lang));
                     }
                 });
         });
@@ -181,8 +392,39 @@ impl ManifestState {
         }
     }
 
+    pub fn render_parse_error(&self, ui: &mut egui::Ui, lang: Lang) {
+        if let FetchStatus::ParseError(parse_error) = &self.status {
+            render_json_error_panel(ui, parse_error, lang);
+        }
+    }
+
+    // shown once the fetch failed and the cached manifest stays in use
+    pub fn render_cache_note(&self, ui: &mut egui::Ui, lang: Lang) {
+        let Some(cached_at) = self.cached_at else {
+            return;
+        };
+        if matches!(self.status, FetchStatus::NotFetched | FetchStatus::Fetched) {
+            return;
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+        ui.label(
+            RichText::new(
+                LangMessage::CachedManifest {
+                    hours_ago: now.saturating_sub(cached_at) / 3600,
+                }
+                .to_string(lang),
+            )
+            .small()
+            .weak(),
+        );
+    }
+
+    // also switches to the cached manifest of a newly selected url
     pub fn retry_fetch(&mut self, runtime: &Runtime, config: &Config, ctx: &egui::Context) {
         self.status = FetchStatus::NotFetched;
+        self.load_cached_manifest(config);
         self.set_fetch_task(runtime, config, ctx);
     }
 
@@ -190,6 +432,10 @@ impl ManifestState {
         self.status == FetchStatus::Fetched
     }
 
+    pub fn offline(&self) -> bool {
+        self.status == FetchStatus::FetchErrorOffline
+    }
+
     pub fn is_fetching(&self) -> bool {
         self.fetch_task.is_some()
     }
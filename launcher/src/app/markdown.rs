@@ -0,0 +1,315 @@
+use egui::RichText;
+use log::warn;
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+
+use crate::utils::is_web_url;
+
+const LIST_INDENT: f32 = 16.0;
+
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Span {
+    pub text: String,
+    pub strong: bool,
+    pub italic: bool,
+    pub code: bool,
+    pub link: Option<String>,
+}
+
+impl Span {
+    fn same_style(&self, other: &Span) -> bool {
+        self.strong == other.strong
+            && self.italic == other.italic
+            && self.code == other.code
+            && self.link == other.link
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub enum BlockKind {
+    Paragraph,
+    Heading(u8),
+    ListItem { depth: usize, marker: String },
+    Quote,
+    CodeBlock,
+    Rule,
+}
+
+#[derive(PartialEq, Debug)]
+pub struct Block {
+    pub kind: BlockKind,
+    pub spans: Vec<Span>,
+}
+
+#[derive(Default)]
+struct MarkdownParser {
+    blocks: Vec<Block>,
+    current: Option<Block>,
+    strong: usize,
+    italic: usize,
+    // None for links that aren't opened, their text is shown as is
+    links: Vec<Option<String>>,
+    // next item number for ordered lists
+    lists: Vec<Option<u64>>,
+    quote_depth: usize,
+    in_code_block: bool,
+}
+
+impl MarkdownParser {
+    fn start_block(&mut self, kind: BlockKind) {
+        self.flush();
+        self.current = Some(Block {
+            kind,
+            spans: vec![],
+        });
+    }
+
+    fn flush(&mut self) {
+        if let Some(mut block) = self.current.take() {
+            if block.kind == BlockKind::CodeBlock
+                && let Some(span) = block.spans.last_mut()
+            {
+                span.text.truncate(span.text.trim_end().len());
+            }
+            if !block.spans.is_empty() || matches!(block.kind, BlockKind::ListItem { .. }) {
+                self.blocks.push(block);
+            }
+        }
+    }
+
+    fn push_text(&mut self, text: &str, code: bool) {
+        let span = Span {
+            text: text.to_string(),
+            strong: self.strong > 0,
+            italic: self.italic > 0,
+            code: code || self.in_code_block,
+            link: self.links.last().cloned().flatten(),
+        };
+        if self.current.is_none() {
+            let kind = if self.quote_depth > 0 {
+                BlockKind::Quote
+            } else {
+                BlockKind::Paragraph
+            };
+            self.start_block(kind);
+        }
+        let spans = &mut self.current.as_mut().unwrap().spans;
+        match spans.last_mut() {
+            Some(last) if last.same_style(&span) => last.text.push_str(&span.text),
+            _ => spans.push(span),
+        }
+    }
+
+    fn handle(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start_tag(tag),
+            Event::End(tag) => self.end_tag(tag),
+            Event::Text(text) => self.push_text(&text, false),
+            Event::Code(text) => self.push_text(&text, true),
+            Event::SoftBreak => self.push_text(" ", false),
+            Event::HardBreak => self.push_text("\n", false),
+            Event::Rule => {
+                self.flush();
+                self.blocks.push(Block {
+                    kind: BlockKind::Rule,
+                    spans: vec![],
+                });
+            }
+            _ => {}
+        }
+    }
+
+    fn start_tag(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => {
+                // loose list items wrap their text in a paragraph
+                let in_empty_item = self.current.as_ref().is_some_and(|block| {
+                    matches!(block.kind, BlockKind::ListItem { .. }) && block.spans.is_empty()
+                });
+                if !in_empty_item {
+                    self.flush();
+                }
+            }
+            Tag::Heading { level, .. } => self.start_block(BlockKind::Heading(level as u8)),
+            Tag::BlockQuote(_) => {
+                self.flush();
+                self.quote_depth += 1;
+            }
+            Tag::CodeBlock(_) => {
+                self.start_block(BlockKind::CodeBlock);
+                self.in_code_block = true;
+            }
+            Tag::List(start) => {
+                self.flush();
+                self.lists.push(start);
+            }
+            Tag::Item => {
+                let depth = self.lists.len().saturating_sub(1);
+                let marker = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}.", *number - 1)
+                    }
+                    _ => "•".to_string(),
+                };
+                self.start_block(BlockKind::ListItem { depth, marker });
+            }
+            Tag::Emphasis => self.italic += 1,
+            Tag::Strong => self.strong += 1,
+            Tag::Link { dest_url, .. } | Tag::Image { dest_url, .. } => self
+                .links
+                .push(is_web_url(&dest_url).then(|| dest_url.to_string())),
+            _ => {}
+        }
+    }
+
+    fn end_tag(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph | TagEnd::Heading(_) | TagEnd::Item => self.flush(),
+            TagEnd::BlockQuote(_) => {
+                self.flush();
+                self.quote_depth = self.quote_depth.saturating_sub(1);
+            }
+            TagEnd::CodeBlock => {
+                self.flush();
+                self.in_code_block = false;
+            }
+            TagEnd::List(_) => {
+                self.flush();
+                self.lists.pop();
+            }
+            TagEnd::Emphasis => self.italic = self.italic.saturating_sub(1),
+            TagEnd::Strong => self.strong = self.strong.saturating_sub(1),
+            TagEnd::Link | TagEnd::Image => {
+                self.links.pop();
+            }
+            _ => {}
+        }
+    }
+}
+
+pub fn parse_markdown(text: &str) -> Vec<Block> {
+    let mut parser = MarkdownParser::default();
+    for event in Parser::new(text) {
+        parser.handle(event);
+    }
+    parser.flush();
+    parser.blocks
+}
+
+fn render_span(ui: &mut egui::Ui, span: &Span, weak: bool) {
+    let mut text = RichText::new(&span.text);
+    if span.strong {
+        text = text.strong();
+    }
+    if span.italic {
+        text = text.italics();
+    }
+    if span.code {
+        text = text.code();
+    }
+    if weak {
+        text = text.weak();
+    }
+
+    if let Some(url) = &span.link {
+        if ui.link(text).on_hover_text(url).clicked()
+            && let Err(e) = open::that(url)
+        {
+            warn!("Failed to open link {url}: {e}");
+        }
+    } else {
+        ui.label(text);
+    }
+}
+
+fn render_spans(ui: &mut egui::Ui, spans: &[Span], weak: bool) {
+    ui.horizontal_wrapped(|ui| {
+        ui.spacing_mut().item_spacing.x = 0.0;
+        for span in spans {
+            render_span(ui, span, weak);
+        }
+    });
+}
+
+pub fn render_markdown(ui: &mut egui::Ui, blocks: &[Block]) {
+    for block in blocks {
+        match &block.kind {
+            BlockKind::Paragraph => render_spans(ui, &block.spans, false),
+            BlockKind::Heading(level) => {
+                let text: String = block.spans.iter().map(|span| span.text.as_str()).collect();
+                let text = match level {
+                    1 => RichText::new(text).heading(),
+                    2 => RichText::new(text).size(16.0).strong(),
+                    _ => RichText::new(text).strong(),
+                };
+                ui.label(text);
+            }
+            BlockKind::ListItem { depth, marker } => {
+                ui.horizontal(|ui| {
+                    ui.add_space(*depth as f32 * LIST_INDENT);
+                    ui.label(marker);
+                    render_spans(ui, &block.spans, false);
+                });
+            }
+            BlockKind::Quote => {
+                ui.horizontal(|ui| {
+                    ui.add_space(LIST_INDENT);
+                    render_spans(ui, &block.spans, true);
+                });
+            }
+            BlockKind::CodeBlock => {
+                let text: String = block.spans.iter().map(|span| span.text.as_str()).collect();
+                ui.label(RichText::new(text).code());
+            }
+            BlockKind::Rule => {
+                ui.separator();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_markdown() {
+        let blocks = parse_markdown(
+            "# Rules\n\nBe **nice**, see [the site](https://example.com).\n\n1. No griefing\n2. No *cheats*\n",
+        );
+
+        assert_eq!(blocks.len(), 4);
+        assert_eq!(blocks[0].kind, BlockKind::Heading(1));
+        assert_eq!(blocks[0].spans[0].text, "Rules");
+
+        let spans = &blocks[1].spans;
+        assert_eq!(spans[1].text, "nice");
+        assert!(spans[1].strong);
+        assert_eq!(spans[3].text, "the site");
+        assert_eq!(spans[3].link.as_deref(), Some("https://example.com"));
+
+        assert_eq!(
+            blocks[3].kind,
+            BlockKind::ListItem {
+                depth: 0,
+                marker: "2.".to_string()
+            }
+        );
+        assert_eq!(blocks[3].spans[1].text, "cheats");
+        assert!(blocks[3].spans[1].italic);
+    }
+
+    #[test]
+    fn test_local_links_not_opened() {
+        let blocks = parse_markdown(
+            "[run](file:///C:/Windows/System32/calc.exe) ![icon](javascript:alert(1)) [ok](http://example.com)",
+        );
+        let links: Vec<_> = blocks[0]
+            .spans
+            .iter()
+            .map(|span| span.link.as_deref())
+            .collect();
+        assert_eq!(links, [None, Some("http://example.com")]);
+        assert_eq!(blocks[0].spans[0].text, "run icon ");
+    }
+}
@@ -1,19 +1,31 @@
-use std::{collections::HashMap, path::Path, sync::Arc};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use egui::RichText;
 use log::{error, info};
+use shared::json::{JsonParseError, get_json_parse_error};
+use shared::signing::is_signature_error;
 use shared::utils::is_connect_error;
 use shared::version::version_manifest::VersionInfo;
 use tokio::runtime::Runtime;
 
 use crate::{
-    config::runtime_config::Config, lang::LangMessage,
-    version::complete_version_metadata::CompleteVersionMetadata,
+    config::{build_config, runtime_config::Config},
+    lang::LangMessage,
+    launcher::update::is_launcher_version_supported,
+    version::{
+        complete_version_metadata::CompleteVersionMetadata, instance_storage::InstanceStatus,
+    },
 };
 
 use super::{
     background_task::{BackgroundTask, BackgroundTaskResult},
     colors,
+    json_error_panel::render_json_error_panel,
+    task_registry::{TaskKey, TaskRegistry},
 };
 
 #[derive(PartialEq, Clone)]
@@ -23,12 +35,127 @@ enum GetStatus {
     ReadLocalRemoteError,
     ReadLocalOffline,
     ErrorGetting,
+    SignatureError,
+    LauncherOutdated,
 }
 
 struct MetadataFetchResult {
     status: GetStatus,
     version_info: VersionInfo,
     metadata: Option<Arc<CompleteVersionMetadata>>,
+    parse_error: Option<JsonParseError>,
+}
+
+async fn fetch_metadata(
+    version_info: VersionInfo,
+    data_dir: PathBuf,
+    local_only: bool,
+    public_key: Option<String>,
+) -> MetadataFetchResult {
+    let mut result = read_or_fetch_metadata(version_info, data_dir, local_only, public_key).await;
+    if let Some(min_launcher_version) = result
+        .metadata
+        .as_ref()
+        .and_then(|metadata| metadata.get_min_launcher_version())
+        && !is_launcher_version_supported(
+            build_config::get_version().as_deref(),
+            min_launcher_version,
+        )
+    {
+        info!("Instance requires launcher version {min_launcher_version} or newer");
+        result.status = GetStatus::LauncherOutdated;
+    }
+    result
+}
+
+async fn read_or_fetch_metadata(
+    version_info: VersionInfo,
+    data_dir: PathBuf,
+    local_only: bool,
+    public_key: Option<String>,
+) -> MetadataFetchResult {
+    let public_key = public_key.as_deref();
+    if local_only {
+        // manifest is unreachable, don't wait for metadata downloads to time out
+        return match CompleteVersionMetadata::read_local(&version_info, &data_dir, public_key).await
+        {
+            Ok(metadata) => MetadataFetchResult {
+                status: GetStatus::ReadLocalOffline,
+                version_info,
+                metadata: Some(Arc::new(metadata)),
+                parse_error: None,
+            },
+            Err(e) => {
+                error!("Error reading local metadata:\n{e:?}");
+                MetadataFetchResult {
+                    status: if is_signature_error(&e) {
+                        GetStatus::SignatureError
+                    } else {
+                        GetStatus::ErrorGetting
+                    },
+                    version_info,
+                    metadata: None,
+                    parse_error: get_json_parse_error(&e).cloned(),
+                }
+            }
+        };
+    }
+
+    let result =
+        CompleteVersionMetadata::read_or_download(&version_info, &data_dir, public_key).await;
+    match result {
+        Ok(metadata) => MetadataFetchResult {
+            status: GetStatus::UpToDate,
+            version_info,
+            metadata: Some(Arc::new(metadata)),
+            parse_error: None,
+        },
+        Err(e) if is_signature_error(&e) => {
+            error!("Metadata signature check failed:\n{e:?}");
+            MetadataFetchResult {
+                status: GetStatus::SignatureError,
+                version_info,
+                metadata: None,
+                parse_error: None,
+            }
+        }
+        Err(e) => {
+            let local_metadata =
+                CompleteVersionMetadata::read_local(&version_info, &data_dir, public_key).await;
+            if let Err(local_error) = &local_metadata
+                && is_signature_error(local_error)
+            {
+                error!("Local metadata signature check failed:\n{local_error:?}");
+                return MetadataFetchResult {
+                    status: GetStatus::SignatureError,
+                    version_info,
+                    metadata: None,
+                    parse_error: None,
+                };
+            }
+            // the downloaded metadata is parsed locally, so both errors can point at the same file
+            let parse_error = get_json_parse_error(&e)
+                .or_else(|| local_metadata.as_ref().err().and_then(get_json_parse_error))
+                .cloned();
+            MetadataFetchResult {
+                status: if is_connect_error(&e) {
+                    info!("Metadata offline mode");
+                    GetStatus::ReadLocalOffline
+                } else if let Some(local_error) = local_metadata.as_ref().err() {
+                    error!(
+                        "Error getting metadata:\n{e:?}\nlocal metadata error:\n{local_error:?}"
+                    );
+                    GetStatus::ErrorGetting
+                } else {
+                    error!("Error getting metadata (reading local instead):\n{e:?}");
+                    GetStatus::ReadLocalRemoteError
+                },
+                version_info,
+                metadata: local_metadata.ok().map(Arc::new),
+                parse_error,
+            }
+        }
+    }
 }
 
 fn get_metadata(
@@ -37,6 +164,8 @@ fn get_metadata(
     data_dir: &Path,
     ctx: &egui::Context,
     existing_metadata: Option<(Arc<CompleteVersionMetadata>, GetStatus)>,
+    local_only: bool,
+    public_key: Option<String>,
 ) -> BackgroundTask<MetadataFetchResult> {
     let version_info = version_info.clone();
     let data_dir = data_dir.to_path_buf();
@@ -47,52 +176,50 @@ fn get_metadata(
                 status: metadata.1,
                 version_info,
                 metadata: Some(metadata.0),
+                parse_error: None,
             };
         }
-        let result = CompleteVersionMetadata::read_or_download(&version_info, &data_dir).await;
-        match result {
-            Ok(metadata) => MetadataFetchResult {
-                status: GetStatus::UpToDate,
-                version_info,
-                metadata: Some(Arc::new(metadata)),
-            },
-            Err(e) => {
-                let local_metadata =
-                    CompleteVersionMetadata::read_local(&version_info, &data_dir).await;
-                MetadataFetchResult {
-                    status: if is_connect_error(&e) {
-                        info!("Metadata offline mode");
-                        GetStatus::ReadLocalOffline
-                    } else if let Some(local_error) = local_metadata.as_ref().err() {
-                        error!(
-                            "Error getting metadata:\n{e:?}\nlocal metadata error:\n{local_error:?}"
-                        );
-                        GetStatus::ErrorGetting
-                    } else {
-                        error!("Error getting metadata (reading local instead):\n{e:?}");
-                        GetStatus::ReadLocalRemoteError
-                    },
-                    version_info,
-                    metadata: local_metadata.ok().map(Arc::new),
-                }
-            }
-        }
+        fetch_metadata(version_info, data_dir, local_only, public_key).await
     };
 
     let ctx = ctx.clone();
     BackgroundTask::with_callback(fut, runtime, Box::new(move || ctx.request_repaint()))
 }
 
+// the same instance name can exist in several manifests
+type MetadataKey = (String, String); // (manifest url, instance name)
+
+fn get_metadata_key(config: &Config, instance_name: String) -> MetadataKey {
+    (
+        config.get_effective_version_manifest_url().to_string(),
+        instance_name,
+    )
+}
+
+// a synced instance can be played without reaching any server, its metadata is read from disk
+pub fn can_read_local_only(manifest_offline: bool, instance_status: InstanceStatus) -> bool {
+    manifest_offline
+        && matches!(
+            instance_status,
+            InstanceStatus::UpToDate | InstanceStatus::Archived
+        )
+}
+
 pub struct MetadataState {
-    get_task: Option<BackgroundTask<MetadataFetchResult>>,
-    metadata_storage: HashMap<String, (Arc<CompleteVersionMetadata>, GetStatus)>,
+    // manifest url the running task was started for
+    get_task: Option<(String, BackgroundTask<MetadataFetchResult>)>,
+    metadata_storage: HashMap<MetadataKey, (Arc<CompleteVersionMetadata>, GetStatus)>,
+    parse_errors: HashMap<MetadataKey, JsonParseError>,
+    task_registry: TaskRegistry,
 }
 
 impl MetadataState {
-    pub fn new() -> Self {
+    pub fn new(task_registry: TaskRegistry) -> Self {
         MetadataState {
             get_task: None,
             metadata_storage: HashMap::new(),
+            parse_errors: HashMap::new(),
+            task_registry,
         }
     }
 
@@ -102,28 +229,60 @@ impl MetadataState {
         config: &Config,
         version_info: &VersionInfo,
         ctx: &egui::Context,
+        local_only: bool,
+        public_key: Option<String>,
     ) {
-        let name = version_info.get_name();
-        let existing_metadata = self.metadata_storage.get(&name).cloned();
+        let key = get_metadata_key(config, version_info.get_name());
+        let existing_metadata = self.metadata_storage.get(&key).cloned();
         let launcher_dir = config.get_launcher_dir();
-        self.get_task = Some(get_metadata(
+        let task = get_metadata(
             runtime,
             version_info,
             &launcher_dir,
             ctx,
             existing_metadata,
-        ));
+            local_only,
+            public_key,
+        );
+        self.task_registry
+            .register(TaskKey::FetchMetadata(key.1), &task, None);
+        self.get_task = Some((key.0, task));
+    }
+
+    fn get_selected(&self, config: &Config) -> Option<&(Arc<CompleteVersionMetadata>, GetStatus)> {
+        let name = config.selected_instance_name.clone()?;
+        self.metadata_storage.get(&get_metadata_key(config, name))
+    }
+
+    fn store_result(&mut self, manifest_url: String, result: MetadataFetchResult) {
+        let key = (manifest_url, result.version_info.get_name());
+        if let Some(parse_error) = result.parse_error {
+            self.parse_errors.insert(key.clone(), parse_error);
+        } else {
+            self.parse_errors.remove(&key);
+        }
+        if let Some(metadata) = result.metadata {
+            self.metadata_storage.insert(key, (metadata, result.status));
+        } else {
+            self.metadata_storage.remove(&key);
+        }
     }
 
     pub fn render_status(&self, ui: &mut egui::Ui, config: &Config) -> bool {
         let dark_mode = ui.style().visuals.dark_mode;
 
-        let status = config
+        let parse_error = config
             .selected_instance_name
-            .as_ref()
-            .and_then(|name| self.metadata_storage.get(name).cloned())
-            .map(|(_, status)| status)
-            .unwrap_or(GetStatus::NoMetadata);
+            .clone()
+            .and_then(|name| self.parse_errors.get(&get_metadata_key(config, name)));
+        let status = self
+            .get_selected(config)
+            .map(|(_, status)| status.clone())
+            .unwrap_or(if parse_error.is_some() {
+                GetStatus::ErrorGetting
+            } else {
+                GetStatus::NoMetadata
+            });
         ui.label(match status {
             GetStatus::NoMetadata => RichText::new(
                 if self.get_task.is_some() {
@@ -149,27 +308,35 @@ impl MetadataState {
                 RichText::new(LangMessage::ErrorGettingMetadata.to_string(config.lang))
                     .color(colors::error(dark_mode))
             }
+            GetStatus::SignatureError => {
+                RichText::new(LangMessage::InvalidMetadataSignature.to_string(config.lang))
+                    .color(colors::error(dark_mode))
+            }
+            GetStatus::LauncherOutdated => {
+                let min_launcher_version = self
+                    .get_version_metadata(config)
+                    .and_then(|metadata| metadata.get_min_launcher_version().map(String::from))
+                    .unwrap_or_default();
+                RichText::new(
+                    LangMessage::LauncherOutdated(min_launcher_version).to_string(config.lang),
+                )
+                .color(colors::error(dark_mode))
+            }
         });
+        if let Some(parse_error) = parse_error {
+            render_json_error_panel(ui, parse_error, config.lang);
+        }
 
         true
     }
 
     pub fn update(&mut self) -> bool {
-        if let Some(task) = self.get_task.as_ref()
+        if let Some((_, task)) = self.get_task.as_ref()
             && task.has_result()
         {
-            let task = self.get_task.take().unwrap();
-            let result = task.take_result();
-            match result {
-                BackgroundTaskResult::Finished(result) => {
-                    let name = result.version_info.get_name();
-                    if let Some(metadata) = result.metadata {
-                        self.metadata_storage
-                            .insert(name, (metadata, result.status));
-                    } else {
-                        self.metadata_storage.remove(&name);
-                    }
-                }
+            let (manifest_url, task) = self.get_task.take().unwrap();
+            match task.take_result() {
+                BackgroundTaskResult::Finished(result) => self.store_result(manifest_url, result),
                 BackgroundTaskResult::Cancelled => {}
             }
 
@@ -180,20 +347,18 @@ impl MetadataState {
     }
 
     pub fn get_version_metadata(&self, config: &Config) -> Option<Arc<CompleteVersionMetadata>> {
-        self.metadata_storage
-            .get(config.selected_instance_name.as_ref()?)
+        self.get_selected(config)
             .map(|(metadata, _)| metadata.clone())
     }
 
     pub fn online(&self, config: &Config) -> bool {
-        if let Some(selected_instance_name) = config.selected_instance_name.as_ref() {
-            self.metadata_storage
-                .get(selected_instance_name)
-                .map(|(_, status)| *status == GetStatus::UpToDate)
-                .unwrap_or(false)
-        } else {
-            false
-        }
+        self.get_selected(config)
+            .is_some_and(|(_, status)| *status == GetStatus::UpToDate)
+    }
+
+    pub fn launcher_outdated(&self, config: &Config) -> bool {
+        self.get_selected(config)
+            .is_some_and(|(_, status)| *status == GetStatus::LauncherOutdated)
     }
 
     pub fn is_getting(&self) -> bool {
@@ -202,5 +367,256 @@ impl MetadataState {
 
     pub fn clear(&mut self) {
         self.metadata_storage.clear();
+        self.parse_errors.clear();
+    }
+
+    pub fn remove_manifest_url(&mut self, manifest_url: &str) {
+        self.metadata_storage
+            .retain(|(url, _), _| url != manifest_url);
+        self.parse_errors.retain(|(url, _), _| url != manifest_url);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use shared::paths::{
+        get_extra_metadata_path, get_metadata_path, get_versions_dir, get_versions_extra_dir,
+    };
+    use shared::version::extra_version_metadata::AuthBackend;
+
+    use super::*;
+    use crate::app::java_state::JavaState;
+    use crate::app::manifest_state::ManifestState;
+
+    // nothing listens on port 1, so every request fails with a connect error
+    const UNREACHABLE_URL: &str = "http://127.0.0.1:1/version.json";
+
+    // the data dir is removed when the returned TempDir is dropped
+    async fn setup_local_instance() -> (tempfile::TempDir, VersionInfo) {
+        let data_dir = tempfile::tempdir().unwrap();
+        let metadata_path = get_metadata_path(&get_versions_dir(data_dir.path()), "1.20.1");
+        tokio::fs::create_dir_all(metadata_path.parent().unwrap())
+            .await
+            .unwrap();
+        tokio::fs::write(
+            &metadata_path,
+            r#"{"id": "1.20.1", "libraries": [], "mainClass": "net.minecraft.client.main.Main"}"#,
+        )
+        .await
+        .unwrap();
+
+        let version_info = VersionInfo {
+            id: "1.20.1".to_string(),
+            url: UNREACHABLE_URL.to_string(),
+            sha1: "0".repeat(40),
+            name: None,
+            inherits_from: vec![],
+            extra_metadata_url: None,
+            extra_metadata_sha1: None,
+            rollout_percent: None,
+            server_address: None,
+        };
+        (data_dir, version_info)
+    }
+
+    #[tokio::test]
+    async fn test_offline_reads_local_metadata() {
+        let (data_dir, version_info) = setup_local_instance().await;
+
+        let result = fetch_metadata(version_info, data_dir.path().to_path_buf(), true, None).await;
+        assert!(result.status == GetStatus::ReadLocalOffline);
+        assert_eq!(result.metadata.unwrap().get_id(), "1.20.1");
+    }
+
+    #[tokio::test]
+    async fn test_offline_fallback_after_failed_download() {
+        let (data_dir, version_info) = setup_local_instance().await;
+
+        let result = fetch_metadata(version_info, data_dir.path().to_path_buf(), false, None).await;
+        assert!(result.status == GetStatus::ReadLocalOffline);
+        assert!(result.metadata.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_offline_without_local_metadata() {
+        let (data_dir, version_info) = setup_local_instance().await;
+        tokio::fs::remove_dir_all(data_dir.path()).await.unwrap();
+
+        let result = fetch_metadata(version_info, data_dir.path().to_path_buf(), true, None).await;
+        assert!(result.status == GetStatus::ErrorGetting);
+        assert!(result.metadata.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_local_metadata_parse_error() {
+        let (data_dir, version_info) = setup_local_instance().await;
+        let metadata_path = get_metadata_path(&get_versions_dir(data_dir.path()), "1.20.1");
+        tokio::fs::write(&metadata_path, r#"{"id": "1.20.1", "libraries": {}}"#)
+            .await
+            .unwrap();
+
+        let result = fetch_metadata(version_info, data_dir.path().to_path_buf(), true, None).await;
+        assert!(result.status == GetStatus::ErrorGetting);
+        let parse_error = result.parse_error.unwrap();
+        assert_eq!(parse_error.url, UNREACHABLE_URL);
+        assert_eq!(parse_error.path, "libraries");
+    }
+
+    fn test_config(manifest_url: &str) -> Config {
+        Config {
+            java_paths: HashMap::new(),
+            assets_dir: None,
+            data_dir: None,
+            xmx: HashMap::new(),
+            use_native_glfw: HashMap::new(),
+            selected_instance_name: Some("server".to_string()),
+            lang: crate::constants::DEFAULT_LANG,
+            hide_launcher_after_launch: true,
+            auth_profiles: HashMap::new(),
+            extra_version_manifest_urls: vec![],
+            selected_version_manifest_url: manifest_url.to_string(),
+            manifest_public_keys: HashMap::new(),
+            manifest_news_urls: HashMap::new(),
+            read_news: HashMap::new(),
+            rotational_disk: false,
+            shared_data: HashMap::new(),
+            pause_auto_sync: false,
+            deduplicate_files: false,
+            use_file_store: false,
+            launch_profiles: vec![],
+            last_launch_profile: None,
+            disable_gl_threaded_optimizations: HashMap::new(),
+            advanced_mode: false,
+            rollout_id: String::new(),
+            discord_presence: false,
+            hash_concurrency: None,
+            hash_buffer_size_kb: None,
+            jvm_args: HashMap::new(),
+            disable_recommended_jvm_args: HashMap::new(),
+            server_status: false,
+            env_vars: HashMap::new(),
+            display_server: HashMap::new(),
+            prefer_discrete_gpu: HashMap::new(),
+            encrypt_auth_data: false,
+            max_connections: None,
+            restart_on_crash: HashMap::new(),
+            mirror_rankings: HashMap::new(),
+            config_version: 1,
+            save_state: Default::default(),
+            reset_config_backup: None,
+        }
+    }
+
+    async fn fetch_with_auth_backend(auth_backend: &str) -> MetadataFetchResult {
+        let (data_dir, mut version_info) = setup_local_instance().await;
+        version_info.name = Some("server".to_string());
+        version_info.extra_metadata_url = Some(UNREACHABLE_URL.to_string());
+        version_info.extra_metadata_sha1 = Some("0".repeat(40));
+        let extra_metadata_path =
+            get_extra_metadata_path(&get_versions_extra_dir(data_dir.path()), "server");
+        tokio::fs::create_dir_all(extra_metadata_path.parent().unwrap())
+            .await
+            .unwrap();
+        tokio::fs::write(
+            &extra_metadata_path,
+            format!(r#"{{"auth_backend": {{"type": "{auth_backend}"}}}}"#),
+        )
+        .await
+        .unwrap();
+
+        fetch_metadata(version_info, data_dir.path().to_path_buf(), true, None).await
+    }
+
+    #[tokio::test]
+    async fn test_metadata_keyed_by_manifest_url() {
+        let first_url = "https://first.example.com/manifest.json";
+        let second_url = "https://second.example.com/manifest.json";
+        let mut state = MetadataState::new(TaskRegistry::new());
+        state.store_result(
+            first_url.to_string(),
+            fetch_with_auth_backend("microsoft").await,
+        );
+        state.store_result(
+            second_url.to_string(),
+            fetch_with_auth_backend("offline").await,
+        );
+
+        let get_auth_backend = |state: &MetadataState, url: &str| {
+            state
+                .get_version_metadata(&test_config(url))
+                .and_then(|metadata| metadata.get_auth_backend().cloned())
+        };
+        assert_eq!(
+            get_auth_backend(&state, first_url),
+            Some(AuthBackend::Microsoft)
+        );
+        assert_eq!(
+            get_auth_backend(&state, second_url),
+            Some(AuthBackend::Offline)
+        );
+
+        state.remove_manifest_url(first_url);
+        assert_eq!(get_auth_backend(&state, first_url), None);
+        assert_eq!(
+            get_auth_backend(&state, second_url),
+            Some(AuthBackend::Offline)
+        );
+    }
+
+    // the chain launcher_app goes through when no server can be reached
+    #[test]
+    fn test_offline_launch_transitions() {
+        let runtime = Runtime::new().unwrap();
+        let ctx = egui::Context::default();
+        let (data_dir, version_info) = runtime.block_on(setup_local_instance());
+        let java_path = data_dir.path().join("java");
+        std::fs::write(&java_path, b"").unwrap();
+        let mut config = test_config(UNREACHABLE_URL);
+        config.data_dir = Some(data_dir.path().to_path_buf());
+        config.selected_instance_name = Some(version_info.get_name());
+        config.java_paths.insert(version_info.get_name(), java_path);
+        let deadline = Instant::now() + Duration::from_secs(30);
+
+        // the manifest fetch fails with a connect error
+        let mut manifest_state = ManifestState::new(&runtime, &ctx, &config, TaskRegistry::new());
+        while !manifest_state.take_manifest(&mut config).1 {
+            assert!(Instant::now() < deadline);
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(manifest_state.offline());
+
+        // only a synced instance skips the download
+        assert!(!can_read_local_only(
+            manifest_state.offline(),
+            InstanceStatus::Outdated
+        ));
+        let local_only = can_read_local_only(manifest_state.offline(), InstanceStatus::UpToDate);
+        assert!(local_only);
+        let mut metadata_state = MetadataState::new(TaskRegistry::new());
+        metadata_state.set_metadata_task(&runtime, &config, &version_info, &ctx, local_only, None);
+        while !metadata_state.update() {
+            assert!(Instant::now() < deadline);
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(
+            metadata_state
+                .get_selected(&config)
+                .is_some_and(|(_, status)| *status == GetStatus::ReadLocalOffline)
+        );
+        let metadata = metadata_state.get_version_metadata(&config).unwrap();
+
+        // the java path validated on a previous launch is used without checking it again
+        let mut java_state = JavaState::new(&ctx, TaskRegistry::new());
+        java_state.set_check_java_task(
+            &runtime,
+            &metadata,
+            &config,
+            &ctx,
+            manifest_state.offline(),
+        );
+        assert!(!java_state.checking_java());
+        assert!(java_state.ready_for_launch());
     }
 }
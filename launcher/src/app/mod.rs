@@ -1,14 +1,28 @@
+mod archive_state;
 mod auth_state;
+mod auth_window;
+mod avatar_state;
 mod background_task;
 mod colors;
+mod description_state;
 mod instance_sync_state;
+mod java_manager;
 mod java_state;
+mod json_error_panel;
 mod language_selector;
+mod launch_profiles_state;
 mod launch_state;
 pub mod launcher_app;
 mod manifest_state;
+mod markdown;
 mod metadata_state;
 mod new_instance_state;
+mod news_state;
 pub mod progress_bar;
+mod screenshots_state;
+mod server_status_state;
 mod settings;
+mod shortcuts;
+mod task_registry;
 pub mod unified_app;
+mod widgets;
@@ -1,5 +1,5 @@
 use std::collections::{HashMap, HashSet};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use log::error;
@@ -11,19 +11,27 @@ use shared::loader_generator::forge::{
     ForgeGenerator, ForgeMavenMetadata, ForgePromotions, Loader, NeoforgeMavenMetadata,
 };
 use shared::loader_generator::generator::VersionGenerator;
+use shared::loader_generator::mrpack::{self, MrpackError, MrpackGenerator, MrpackIndex};
+use shared::loader_generator::url::{UrlGenerator, UrlGeneratorError};
 use shared::loader_generator::vanilla::VanillaGenerator;
 use shared::paths::get_minecraft_dir;
 use shared::progress::NoProgressBar;
 use shared::utils::{VANILLA_MANIFEST_URL, get_vanilla_version_info, is_connect_error};
+use shared::version::extra_version_metadata::Include;
 use shared::version::version_manifest::{VersionInfo, VersionManifest};
 use tokio::runtime::Runtime;
 
 use crate::{
     config::runtime_config::Config,
     lang::{Lang, LangMessage},
+    version::{
+        import::{self, ImportSource},
+        instance_storage::{self, LocalInstance},
+    },
 };
 
 use super::background_task::{BackgroundTask, BackgroundTaskResult};
+use super::widgets::icon_button;
 
 struct AllVersionsMetadata {
     vanilla_manifest: VersionManifest,
@@ -150,6 +158,8 @@ where
 
 pub struct RenderUIResult {
     pub instance_to_delete: Option<String>,
+    // old and new name
+    pub instance_to_rename: Option<(String, String)>,
 }
 
 const VANILLA_LOADER: &str = "Vanilla";
@@ -157,6 +167,36 @@ const FABRIC_LOADER: &str = "Fabric";
 const FORGE_LOADER: &str = "Forge";
 const NEOFORGE_LOADER: &str = "NeoForge";
 
+async fn generate_instance(
+    launcher_dir: &Path,
+    instance_name: &str,
+    generator: Box<dyn VersionGenerator + Send>,
+    shared_include: Vec<Include>,
+) -> anyhow::Result<VersionInfo> {
+    let generator_result = generator.generate(launcher_dir).await?;
+
+    let extra_generator = ExtraMetadataGenerator::new(
+        instance_name.to_string(),
+        None,
+        generator_result.extra_libs_paths,
+        None,
+        None,
+        None,
+        Default::default(),
+    )
+    .with_shared_include(shared_include);
+    let _ = extra_generator.generate(launcher_dir).await?;
+
+    get_version_info(
+        launcher_dir,
+        &generator_result.metadata,
+        instance_name,
+        None,
+        &HashMap::new(),
+    )
+    .await
+}
+
 struct NewInstanceParams {
     instance_name: String,
     minecraft_version: String,
@@ -222,26 +262,70 @@ fn create_new_instance(
             }
         };
 
-        let generator_result = generator.generate(&launcher_dir).await?;
+        generate_instance(&launcher_dir, &instance_name, generator, vec![]).await
+    };
 
-        let extra_generator = ExtraMetadataGenerator::new(
-            instance_name.to_string(),
-            None,
-            generator_result.extra_libs_paths,
-            None,
-            None,
-        );
-        let _ = extra_generator.generate(&launcher_dir).await?;
+    let ctx = ctx.clone();
+    BackgroundTask::with_callback(
+        fut,
+        runtime,
+        Box::new(move || {
+            ctx.request_repaint();
+        }),
+    )
+}
+
+fn create_instance_from_url(
+    runtime: &Runtime,
+    ctx: &egui::Context,
+    launcher_dir: &Path,
+    version_manifest: &VersionManifest,
+    instance_name: &str,
+    metadata_url: &str,
+) -> BackgroundTask<anyhow::Result<VersionInfo>> {
+    let launcher_dir = launcher_dir.to_path_buf();
+    let instance_name = instance_name.to_string();
+    let generator = Box::new(UrlGenerator::new(
+        instance_name.clone(),
+        metadata_url.trim().to_string(),
+        version_manifest.clone(),
+    ));
+    let fut =
+        async move { generate_instance(&launcher_dir, &instance_name, generator, vec![]).await };
+
+    let ctx = ctx.clone();
+    BackgroundTask::with_callback(
+        fut,
+        runtime,
+        Box::new(move || {
+            ctx.request_repaint();
+        }),
+    )
+}
 
-        let version_info = get_version_info(
-            &launcher_dir,
-            &generator_result.metadata,
-            &instance_name,
-            None,
-            &HashMap::new(),
-        )
-        .await?;
+// the listed files are downloaded by the first sync, like the files of remote instances
+fn create_instance_from_mrpack(
+    runtime: &Runtime,
+    ctx: &egui::Context,
+    launcher_dir: &Path,
+    version_manifest: &VersionManifest,
+    instance_name: &str,
+    pack_path: &Path,
+) -> BackgroundTask<anyhow::Result<VersionInfo>> {
+    let launcher_dir = launcher_dir.to_path_buf();
+    let instance_name = instance_name.to_string();
+    let pack_path = pack_path.to_path_buf();
+    let version_manifest = version_manifest.clone();
+    let fut = async move {
+        let index = MrpackIndex::read(&pack_path)?;
+        let generator = MrpackGenerator::new(instance_name.clone(), index, version_manifest);
+        let include = generator.get_include()?;
+        let version_info =
+            generate_instance(&launcher_dir, &instance_name, Box::new(generator), include).await?;
 
+        let minecraft_dir = get_minecraft_dir(&launcher_dir, &instance_name);
+        tokio::task::spawn_blocking(move || mrpack::extract_overrides(&pack_path, &minecraft_dir))
+            .await??;
         Ok(version_info)
     };
 
@@ -259,6 +343,110 @@ enum NewInstanceGenerateState {
     NoError,
     Offline,
     UnknownError,
+    IncompleteMetadata(String),
+    InvalidModpack(String),
+}
+
+fn render_generate_status(
+    ui: &mut egui::Ui,
+    lang: Lang,
+    generate_task: &mut Option<BackgroundTask<anyhow::Result<VersionInfo>>>,
+    generate_state: &NewInstanceGenerateState,
+) {
+    if generate_task.is_some() {
+        ui.label(LangMessage::CreatingInstance.to_string(lang));
+        if ui.button(LangMessage::Cancel.to_string(lang)).clicked() {
+            *generate_task = None;
+        }
+        return;
+    }
+    match generate_state {
+        NewInstanceGenerateState::Offline => {
+            ui.label(LangMessage::InstanceGenerateErrorOffline.to_string(lang));
+        }
+        NewInstanceGenerateState::UnknownError => {
+            ui.label(LangMessage::InstanceGenerateError.to_string(lang));
+        }
+        NewInstanceGenerateState::IncompleteMetadata(missing_fields) => {
+            ui.label(
+                LangMessage::IncompleteVersionMetadata {
+                    missing_fields: missing_fields.clone(),
+                }
+                .to_string(lang),
+            );
+        }
+        NewInstanceGenerateState::InvalidModpack(error) => {
+            ui.label(LangMessage::InvalidModpack(error.clone()).to_string(lang));
+        }
+        NewInstanceGenerateState::NoError => {}
+    }
+}
+
+// None if the dialog was closed
+fn pick_mrpack() -> Option<anyhow::Result<(PathBuf, MrpackIndex)>> {
+    let path = rfd::FileDialog::new()
+        .add_filter("Modrinth modpack", &["mrpack"])
+        .pick_file()?;
+    Some(MrpackIndex::read(&path).map(|index| (path, index)))
+}
+
+// which section of the new instance window started the last generate task
+#[derive(PartialEq)]
+enum GenerateSource {
+    Versions,
+    Url,
+    Mrpack,
+}
+
+fn import_instance(
+    runtime: &Runtime,
+    ctx: &egui::Context,
+    launcher_dir: &Path,
+    source: ImportSource,
+    instance_name: &str,
+) -> BackgroundTask<anyhow::Result<LocalInstance>> {
+    let launcher_dir = launcher_dir.to_path_buf();
+    let instance_name = instance_name.to_string();
+    let fut = async move { import::import_instance(&launcher_dir, source, &instance_name).await };
+
+    let ctx = ctx.clone();
+    BackgroundTask::with_callback(
+        fut,
+        runtime,
+        Box::new(move || {
+            ctx.request_repaint();
+        }),
+    )
+}
+
+enum ImportSourceState {
+    Archive(PathBuf),
+    Folder {
+        path: PathBuf,
+        version_ids: Vec<String>,
+        version_id: String,
+    },
+}
+
+impl ImportSourceState {
+    fn get_path(&self) -> &Path {
+        match self {
+            ImportSourceState::Archive(path) => path,
+            ImportSourceState::Folder { path, .. } => path,
+        }
+    }
+
+    fn to_import_source(&self) -> ImportSource {
+        match self {
+            ImportSourceState::Archive(path) => ImportSource::Archive(path.clone()),
+            ImportSourceState::Folder {
+                path, version_id, ..
+            } => ImportSource::Folder {
+                path: path.clone(),
+                version_id: version_id.clone(),
+            },
+        }
+    }
 }
 
 pub struct NewInstanceState {
@@ -267,6 +455,7 @@ pub struct NewInstanceState {
     instance_version: String,
     instance_loader: String,
     instance_loader_version: String,
+    version_json_url: String,
 
     instance_metadata_task: Option<BackgroundTask<anyhow::Result<AllVersionsMetadata>>>,
     all_metadata_state: NewInstanceMetadataState<AllVersionsMetadata>,
@@ -275,9 +464,21 @@ pub struct NewInstanceState {
 
     instance_generate_task: Option<BackgroundTask<anyhow::Result<VersionInfo>>>,
     instance_generate_state: NewInstanceGenerateState,
+    generate_source: GenerateSource,
+    mrpack_path: Option<PathBuf>,
+    mrpack_error: Option<String>,
     delete_window_open: bool,
     selected_instance_to_delete: String,
     confirm_delete: bool,
+    rename_window_open: bool,
+    instance_to_rename: String,
+    rename_new_name: String,
+
+    import_window_open: bool,
+    import_source: Option<ImportSourceState>,
+    import_instance_name: String,
+    import_task: Option<BackgroundTask<anyhow::Result<LocalInstance>>>,
+    import_error: Option<LangMessage>,
 }
 
 impl NewInstanceState {
@@ -288,6 +489,7 @@ impl NewInstanceState {
             instance_version: String::new(),
             instance_loader: String::new(),
             instance_loader_version: String::new(),
+            version_json_url: String::new(),
 
             instance_metadata_task: Some(fetch_all_metadata(runtime, ctx)),
             all_metadata_state: NewInstanceMetadataState::NotFetched,
@@ -296,9 +498,40 @@ impl NewInstanceState {
 
             instance_generate_task: None,
             instance_generate_state: NewInstanceGenerateState::NoError,
+            generate_source: GenerateSource::Versions,
+            mrpack_path: None,
+            mrpack_error: None,
             delete_window_open: false,
             selected_instance_to_delete: String::new(),
             confirm_delete: false,
+            rename_window_open: false,
+            instance_to_rename: String::new(),
+            rename_new_name: String::new(),
+
+            import_window_open: false,
+            import_source: None,
+            import_instance_name: String::new(),
+            import_task: None,
+            import_error: None,
+        }
+    }
+
+    pub fn is_window_open(&self) -> bool {
+        self.window_open
+            || self.delete_window_open
+            || self.import_window_open
+            || self.rename_window_open
+    }
+
+    pub fn close_window(&mut self) {
+        if self.delete_window_open {
+            self.delete_window_open = false;
+        } else if self.rename_window_open {
+            self.rename_window_open = false;
+        } else if self.import_window_open {
+            self.import_window_open = false;
+        } else {
+            self.window_open = false;
         }
     }
 
@@ -318,6 +551,12 @@ impl NewInstanceState {
                         error!("Error creating instance:\n{e:?}");
                         self.instance_generate_state = if is_connect_error(&e) {
                             NewInstanceGenerateState::Offline
+                        } else if let Some(UrlGeneratorError::IncompleteMetadata(missing_fields)) =
+                            e.downcast_ref()
+                        {
+                            NewInstanceGenerateState::IncompleteMetadata(missing_fields.join(", "))
+                        } else if let Some(e) = e.downcast_ref::<MrpackError>() {
+                            NewInstanceGenerateState::InvalidModpack(e.to_string())
                         } else {
                             NewInstanceGenerateState::UnknownError
                         };
@@ -331,6 +570,157 @@ impl NewInstanceState {
         None
     }
 
+    pub fn take_imported_instance(&mut self) -> Option<LocalInstance> {
+        if let Some(task) = self.import_task.as_ref()
+            && task.has_result()
+        {
+            let task = self.import_task.take().unwrap();
+            match task.take_result() {
+                BackgroundTaskResult::Finished(Ok(instance)) => {
+                    self.import_window_open = false;
+                    self.import_source = None;
+                    self.import_instance_name.clear();
+                    self.import_error = None;
+                    return Some(instance);
+                }
+                BackgroundTaskResult::Finished(Err(e)) => {
+                    error!("Error importing instance:\n{e:?}");
+                    self.import_error = Some(LangMessage::ImportError);
+                }
+                BackgroundTaskResult::Cancelled => {}
+            }
+        }
+        None
+    }
+
+    fn pick_import_source(&mut self, folder: bool) {
+        let path = if folder {
+            rfd::FileDialog::new().pick_folder()
+        } else {
+            rfd::FileDialog::new()
+                .add_filter("zip", &["zip"])
+                .pick_file()
+        };
+        let Some(path) = path else {
+            return;
+        };
+        self.import_source = None;
+        self.import_error = None;
+        if folder {
+            let version_ids = import::list_folder_versions(&path);
+            let Some(version_id) = version_ids.last().cloned() else {
+                self.import_error = Some(LangMessage::NotMinecraftFolder);
+                return;
+            };
+            self.import_source = Some(ImportSourceState::Folder {
+                path,
+                version_ids,
+                version_id,
+            });
+        } else {
+            match import::read_archive_instance(&path) {
+                Ok(instance) => {
+                    self.import_instance_name = instance.version_info.get_name();
+                    self.import_source = Some(ImportSourceState::Archive(path));
+                }
+                Err(e) => {
+                    error!("Error reading exported instance:\n{e:?}");
+                    self.import_error = Some(LangMessage::NotExportedInstance);
+                }
+            }
+        }
+    }
+
+    fn render_import_window(
+        &mut self,
+        runtime: &Runtime,
+        ui: &mut egui::Ui,
+        config: &Config,
+        existing_names: &HashSet<String>,
+    ) {
+        let lang = config.lang;
+        let mut import_window_open = self.import_window_open;
+        egui::Window::new(LangMessage::ImportInstance.to_string(lang))
+            .open(&mut import_window_open)
+            .show(ui.ctx(), |ui| {
+                ui.add_enabled_ui(self.import_task.is_none(), |ui| {
+                    ui.horizontal(|ui| {
+                        if ui
+                            .button(LangMessage::ImportFromArchive.to_string(lang))
+                            .clicked()
+                        {
+                            self.pick_import_source(false);
+                        }
+                        if ui
+                            .button(LangMessage::ImportFromFolder.to_string(lang))
+                            .clicked()
+                        {
+                            self.pick_import_source(true);
+                        }
+                    });
+                });
+
+                if let Some(source) = &mut self.import_source {
+                    ui.label(source.get_path().display().to_string());
+                    if let ImportSourceState::Folder {
+                        version_ids,
+                        version_id,
+                        ..
+                    } = source
+                    {
+                        ui.horizontal(|ui| {
+                            ui.label(LangMessage::GameVersion.to_string(lang));
+                            egui::ComboBox::from_id_salt("import_versions")
+                                .selected_text(version_id.clone())
+                                .show_ui(ui, |ui| {
+                                    for id in version_ids.iter() {
+                                        ui.selectable_value(version_id, id.clone(), id);
+                                    }
+                                });
+                        });
+                    }
+                    ui.horizontal(|ui| {
+                        ui.label(LangMessage::NewInstanceName.to_string(lang));
+                        ui.text_edit_singleline(&mut self.import_instance_name);
+                    });
+                }
+
+                if let Some(source) = &self.import_source
+                    && !self.import_instance_name.is_empty()
+                {
+                    if existing_names.contains(&self.import_instance_name) {
+                        ui.label(LangMessage::InstanceNameExists.to_string(lang));
+                    } else {
+                        ui.horizontal(|ui| {
+                            if self.import_task.is_none()
+                                && ui.button(LangMessage::Import.to_string(lang)).clicked()
+                            {
+                                self.import_error = None;
+                                self.import_task = Some(import_instance(
+                                    runtime,
+                                    ui.ctx(),
+                                    &config.get_launcher_dir(),
+                                    source.to_import_source(),
+                                    &self.import_instance_name,
+                                ));
+                            }
+                            if self.import_task.is_some() {
+                                ui.label(LangMessage::ImportingInstance.to_string(lang));
+                                if ui.button(LangMessage::Cancel.to_string(lang)).clicked() {
+                                    self.import_task = None;
+                                }
+                            }
+                        });
+                    }
+                }
+
+                if let Some(import_error) = &self.import_error {
+                    ui.label(import_error.to_string(lang));
+                }
+            });
+        self.import_window_open = import_window_open;
+    }
+
     pub fn render_ui(
         &mut self,
         runtime: &Runtime,
@@ -338,6 +728,7 @@ impl NewInstanceState {
         config: &mut Config,
         existing_names: &HashSet<String>,
         local_instance_names: &Vec<String>,
+        can_rename_selected: bool,
     ) -> RenderUIResult {
         let lang = config.lang;
 
@@ -355,20 +746,37 @@ impl NewInstanceState {
             self.instance_loader = VANILLA_LOADER.to_string();
         }
 
+        if icon_button(ui, "+", LangMessage::NewInstance, lang, true).clicked() {
+            self.window_open = true;
+        }
+        if icon_button(ui, "📥", LangMessage::ImportInstance, lang, true).clicked() {
+            self.import_window_open = true;
+        }
+        if icon_button(ui, "-", LangMessage::DeleteInstance, lang, true).clicked() {
+            self.delete_window_open = true;
+        }
         if let Some(selected_instance_name) = &config.selected_instance_name
-            && ui.button("📂").clicked()
+            && icon_button(
+                ui,
+                "✏",
+                LangMessage::RenameInstance,
+                lang,
+                can_rename_selected && !self.rename_window_open,
+            )
+            .on_disabled_hover_text(LangMessage::RenameOnlyLocalInstances.to_string(lang))
+            .clicked()
+        {
+            self.rename_window_open = true;
+            self.instance_to_rename = selected_instance_name.clone();
+            self.rename_new_name = selected_instance_name.clone();
+        }
+        if let Some(selected_instance_name) = &config.selected_instance_name
+            && icon_button(ui, "📂", LangMessage::OpenInstanceFolder, lang, true).clicked()
         {
             let launcher_dir = config.get_launcher_dir();
             let _ = open::that(get_minecraft_dir(&launcher_dir, selected_instance_name));
         }
 
-        if ui.button("-").clicked() {
-            self.delete_window_open = true;
-        }
-        if ui.button("+").clicked() {
-            self.window_open = true;
-        }
-
         let mut new_instance_window_open = self.window_open;
         egui::Window::new(LangMessage::NewInstance.to_string(lang))
             .open(&mut new_instance_window_open)
@@ -387,6 +795,82 @@ impl NewInstanceState {
                     return;
                 };
 
+                egui::CollapsingHeader::new(LangMessage::FromVersionJsonUrl.to_string(lang)).show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(LangMessage::VersionJsonUrl.to_string(lang));
+                        ui.text_edit_singleline(&mut self.version_json_url);
+                    });
+                    ui.horizontal(|ui| {
+                        let can_create = !self.new_instance_name.is_empty()
+                            && !existing_names.contains(&self.new_instance_name)
+                            && !self.version_json_url.trim().is_empty();
+                        if self.instance_generate_task.is_none()
+                            && ui.add_enabled(can_create, egui::Button::new(LangMessage::CreateInstance.to_string(lang))).clicked()
+                        {
+                            self.instance_generate_task = Some(create_instance_from_url(
+                                runtime,
+                                ui.ctx(),
+                                &config.get_launcher_dir(),
+                                &all_metadata.vanilla_manifest,
+                                &self.new_instance_name,
+                                &self.version_json_url,
+                            ));
+                            self.generate_source = GenerateSource::Url;
+                        }
+                        if self.generate_source == GenerateSource::Url {
+                            render_generate_status(ui, lang, &mut self.instance_generate_task, &self.instance_generate_state);
+                        }
+                    });
+                });
+
+                egui::CollapsingHeader::new(LangMessage::FromMrpack.to_string(lang)).show(ui, |ui| {
+                    if ui.button(LangMessage::SelectMrpack.to_string(lang)).clicked()
+                        && let Some(result) = pick_mrpack()
+                    {
+                        self.mrpack_path = None;
+                        self.mrpack_error = None;
+                        match result {
+                            Ok((path, index)) => {
+                                if self.new_instance_name.is_empty() {
+                                    self.new_instance_name = index.name;
+                                }
+                                self.mrpack_path = Some(path);
+                            }
+                            Err(e) => {
+                                error!("Error reading modpack:\n{e:?}");
+                                self.mrpack_error = Some(e.to_string());
+                            }
+                        }
+                    }
+                    if let Some(mrpack_path) = &self.mrpack_path {
+                        ui.label(mrpack_path.display().to_string());
+                    }
+                    if let Some(mrpack_error) = &self.mrpack_error {
+                        ui.label(LangMessage::InvalidModpack(mrpack_error.clone()).to_string(lang));
+                    }
+                    ui.horizontal(|ui| {
+                        let can_create = !self.new_instance_name.is_empty()
+                            && !existing_names.contains(&self.new_instance_name);
+                        if let Some(mrpack_path) = &self.mrpack_path
+                            && self.instance_generate_task.is_none()
+                            && ui.add_enabled(can_create, egui::Button::new(LangMessage::CreateInstance.to_string(lang))).clicked()
+                        {
+                            self.instance_generate_task = Some(create_instance_from_mrpack(
+                                runtime,
+                                ui.ctx(),
+                                &config.get_launcher_dir(),
+                                &all_metadata.vanilla_manifest,
+                                &self.new_instance_name,
+                                mrpack_path,
+                            ));
+                            self.generate_source = GenerateSource::Mrpack;
+                        }
+                        if self.generate_source == GenerateSource::Mrpack {
+                            render_generate_status(ui, lang, &mut self.instance_generate_task, &self.instance_generate_state);
+                        }
+                    });
+                });
+
                 ui.horizontal(|ui| {
                     ui.label(LangMessage::GameVersion.to_string(lang));
                     let versions = all_metadata
@@ -484,6 +968,17 @@ impl NewInstanceState {
                                 }
                             }
                         }
+                        if self.instance_loader == FABRIC_LOADER {
+                            for version in versions.iter() {
+                                if !current_metadata.fabric_metadata.is_stable(version) {
+                                    version_name.insert(version.to_string(), format!("{version} (beta)"));
+                                }
+                            }
+                            if self.instance_loader_version.is_empty()
+                                && let Some(version) = current_metadata.fabric_metadata.get_latest_stable_version() {
+                                    self.instance_loader_version = version.to_string();
+                                }
+                        }
                         if self.instance_loader_version.is_empty()
                             && let Some(version) = versions.first() {
                                 self.instance_loader_version = version.to_string();
@@ -505,40 +1000,28 @@ impl NewInstanceState {
                         ui.label(LangMessage::InstanceNameExists.to_string(lang));
                     } else {
                         ui.horizontal(|ui| {
-                            if self.instance_generate_task.is_none() {
-                                if ui.button(LangMessage::CreateInstance.to_string(lang)).clicked() {
-                                    let params = NewInstanceParams {
-                                        instance_name: self.new_instance_name.clone(),
-                                        minecraft_version: self.instance_version.clone(),
-                                        loader: self.instance_loader.clone(),
-                                        loader_version: self.instance_loader_version.clone(),
-                                    };
-                                    let task = create_new_instance(
-                                        runtime,
-                                        ui.ctx(),
-                                        &config.get_launcher_dir(),
-                                        &all_metadata.vanilla_manifest,
-                                        params,
-                                    );
-                                    self.instance_generate_task = Some(task);
-                                }
-                                match self.instance_generate_state {
-                                    NewInstanceGenerateState::Offline => {
-                                        ui.label(LangMessage::InstanceGenerateErrorOffline.to_string(lang));
-                                    }
-                                    NewInstanceGenerateState::UnknownError => {
-                                        ui.label(LangMessage::InstanceGenerateError.to_string(lang));
-                                    }
-                                    _ => {}
-                                }
-                            } else {
-                                ui.label(LangMessage::CreatingInstance.to_string(lang));
-                                if ui.button(LangMessage::Cancel.to_string(lang)).clicked() {
-                                    self.instance_generate_task = None;
-                                }
+                            if self.instance_generate_task.is_none() && ui.button(LangMessage::CreateInstance.to_string(lang)).clicked() {
+                                let params = NewInstanceParams {
+                                    instance_name: self.new_instance_name.clone(),
+                                    minecraft_version: self.instance_version.clone(),
+                                    loader: self.instance_loader.clone(),
+                                    loader_version: self.instance_loader_version.clone(),
+                                };
+                                let task = create_new_instance(
+                                    runtime,
+                                    ui.ctx(),
+                                    &config.get_launcher_dir(),
+                                    &all_metadata.vanilla_manifest,
+                                    params,
+                                );
+                                self.instance_generate_task = Some(task);
+                                self.generate_source = GenerateSource::Versions;
+                            }
+                            if self.generate_source == GenerateSource::Versions {
+                                render_generate_status(ui, lang, &mut self.instance_generate_task, &self.instance_generate_state);
                             }
                         });
-                        if self.instance_generate_task.is_some() && [FORGE_LOADER, NEOFORGE_LOADER].contains(&self.instance_loader.as_str()) {
+                        if self.generate_source == GenerateSource::Versions && self.instance_generate_task.is_some() && [FORGE_LOADER, NEOFORGE_LOADER].contains(&self.instance_loader.as_str()) {
                             ui.label(LangMessage::LongTimeWarning.to_string(lang));
                         }
                     }
@@ -546,6 +1029,8 @@ impl NewInstanceState {
             });
         self.window_open = new_instance_window_open;
 
+        self.render_import_window(runtime, ui, config, existing_names);
+
         let mut delete_window_open = self.delete_window_open;
         let mut close_delete_window = false;
         let mut instance_to_delete = None;
@@ -598,6 +1083,55 @@ impl NewInstanceState {
             self.delete_window_open = delete_window_open;
         }
 
-        RenderUIResult { instance_to_delete }
+        let instance_to_rename = self.render_rename_window(ui, lang, existing_names);
+
+        RenderUIResult {
+            instance_to_delete,
+            instance_to_rename,
+        }
+    }
+
+    fn render_rename_window(
+        &mut self,
+        ui: &mut egui::Ui,
+        lang: Lang,
+        existing_names: &HashSet<String>,
+    ) -> Option<(String, String)> {
+        let mut rename_window_open = self.rename_window_open;
+        let mut instance_to_rename = None;
+        egui::Window::new(LangMessage::RenameInstance.to_string(lang))
+            .open(&mut rename_window_open)
+            .show(ui.ctx(), |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(LangMessage::NewInstanceName.to_string(lang));
+                    ui.text_edit_singleline(&mut self.rename_new_name);
+                });
+
+                let changed = self.rename_new_name != self.instance_to_rename;
+                let error = if !instance_storage::is_valid_instance_name(&self.rename_new_name) {
+                    Some(LangMessage::InvalidInstanceName)
+                } else if changed && existing_names.contains(&self.rename_new_name) {
+                    Some(LangMessage::InstanceNameExists)
+                } else {
+                    None
+                };
+                if let Some(error) = &error {
+                    ui.label(error.to_string(lang));
+                }
+                if ui
+                    .add_enabled(
+                        changed && error.is_none(),
+                        egui::Button::new(LangMessage::Rename.to_string(lang)),
+                    )
+                    .clicked()
+                {
+                    instance_to_rename = Some((
+                        self.instance_to_rename.clone(),
+                        self.rename_new_name.clone(),
+                    ));
+                }
+            });
+        self.rename_window_open = rename_window_open && instance_to_rename.is_none();
+        instance_to_rename
     }
 }
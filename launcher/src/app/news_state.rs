@@ -0,0 +1,244 @@
+use std::collections::HashSet;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use egui::RichText;
+use log::{error, info};
+use shared::utils::is_connect_error;
+use tokio::runtime::Runtime;
+
+use crate::config::runtime_config::Config;
+use crate::lang::LangMessage;
+use crate::launcher::news::{NewsEntry, fetch_news};
+
+use super::background_task::{BackgroundTask, BackgroundTaskResult};
+use super::colors;
+use super::markdown::{Block, parse_markdown, render_markdown};
+use super::widgets::icon_button;
+
+struct NewsFetchResult {
+    url: String,
+    entries: Option<Vec<NewsEntry>>,
+}
+
+struct LoadedEntry {
+    entry: NewsEntry,
+    blocks: Vec<Block>,
+}
+
+// failures are only logged, the feed is not essential for playing
+fn get_news(
+    runtime: &Runtime,
+    url: String,
+    ctx: &egui::Context,
+) -> BackgroundTask<NewsFetchResult> {
+    let fut = async move {
+        let entries = match fetch_news(&url).await {
+            Ok(entries) => Some(entries),
+            Err(e) => {
+                if is_connect_error(&e) {
+                    info!("News feed offline mode");
+                } else {
+                    error!("Error getting news feed {url}:\n{e:?}");
+                }
+                None
+            }
+        };
+        NewsFetchResult { url, entries }
+    };
+
+    let ctx = ctx.clone();
+    BackgroundTask::with_callback(fut, runtime, Box::new(move || ctx.request_repaint()))
+}
+
+pub struct NewsState {
+    fetch_task: Option<BackgroundTask<NewsFetchResult>>,
+    // feed of the selected manifest, None hides the news button
+    url: Option<String>,
+    needs_fetch: bool,
+    entries: Vec<LoadedEntry>,
+    window_open: bool,
+    // entries that were unread when the window was opened
+    new_ids: HashSet<String>,
+}
+
+impl NewsState {
+    pub fn new() -> Self {
+        NewsState {
+            fetch_task: None,
+            url: None,
+            needs_fetch: false,
+            entries: vec![],
+            window_open: false,
+            new_ids: HashSet::new(),
+        }
+    }
+
+    pub fn update(&mut self, runtime: &Runtime, config: &mut Config, ctx: &egui::Context) {
+        if let Some(task) = self.fetch_task.as_ref()
+            && task.has_result()
+        {
+            let task = self.fetch_task.take().unwrap();
+            if let BackgroundTaskResult::Finished(result) = task.take_result()
+                && self.url.as_ref() == Some(&result.url)
+                && let Some(entries) = result.entries
+            {
+                self.entries = entries
+                    .into_iter()
+                    .map(|entry| LoadedEntry {
+                        blocks: parse_markdown(&entry.body),
+                        entry,
+                    })
+                    .collect();
+                if self.window_open {
+                    self.mark_read(config);
+                }
+            }
+        }
+
+        let url = config.get_news_url(config.get_effective_version_manifest_url());
+        if url != self.url {
+            self.url = url;
+            self.entries.clear();
+            self.new_ids.clear();
+            self.window_open = false;
+            self.needs_fetch = self.url.is_some();
+        }
+
+        // a task for the previous feed finishes first
+        if self.needs_fetch
+            && self.fetch_task.is_none()
+            && let Some(url) = &self.url
+        {
+            self.fetch_task = Some(get_news(runtime, url.clone(), ctx));
+            self.needs_fetch = false;
+        }
+    }
+
+    pub fn refresh(&mut self) {
+        self.needs_fetch = self.url.is_some();
+    }
+
+    fn get_unread_count(&self, config: &Config) -> usize {
+        let Some(url) = &self.url else {
+            return 0;
+        };
+        let read = config.read_news.get(url);
+        self.entries
+            .iter()
+            .filter(|loaded| read.is_none_or(|read| !read.contains(&loaded.entry.id)))
+            .count()
+    }
+
+    // only ids of the current feed are kept, so the list doesn't grow forever
+    fn mark_read(&mut self, config: &mut Config) {
+        let Some(url) = &self.url else {
+            return;
+        };
+        let read = config.read_news.get(url);
+        self.new_ids.extend(
+            self.entries
+                .iter()
+                .map(|loaded| &loaded.entry.id)
+                .filter(|id| read.is_none_or(|read| !read.contains(*id)))
+                .cloned(),
+        );
+        let ids: HashSet<_> = self
+            .entries
+            .iter()
+            .map(|loaded| loaded.entry.id.clone())
+            .collect();
+        if read != Some(&ids) {
+            config.read_news.insert(url.clone(), ids);
+            config.save();
+        }
+    }
+
+    pub fn render_button(&mut self, ui: &mut egui::Ui, config: &mut Config) {
+        if self.url.is_none() {
+            return;
+        }
+        let unread = self.get_unread_count(config);
+        let icon = if unread > 0 {
+            format!("📰 {unread}")
+        } else {
+            "📰".to_string()
+        };
+        if icon_button(ui, &icon, LangMessage::News, config.lang, true).clicked() {
+            self.window_open = !self.window_open;
+            if self.window_open {
+                self.new_ids.clear();
+                self.mark_read(config);
+            }
+        }
+    }
+
+    pub fn render_window(&mut self, ctx: &egui::Context, config: &Config) {
+        let lang = config.lang;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let mut open = self.window_open;
+        egui::Window::new(LangMessage::News.to_string(lang))
+            .id(egui::Id::new("news"))
+            .collapsible(false)
+            .default_width(400.0)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                let dark_mode = ui.style().visuals.dark_mode;
+                if icon_button(
+                    ui,
+                    "🔄",
+                    LangMessage::Refresh,
+                    lang,
+                    self.fetch_task.is_none(),
+                )
+                .clicked()
+                {
+                    self.refresh();
+                }
+                if self.entries.is_empty() {
+                    ui.label(LangMessage::NoNews.to_string(lang));
+                }
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .auto_shrink([false, true])
+                    .show(ui, |ui| {
+                        for (i, loaded) in self.entries.iter().enumerate() {
+                            if i > 0 {
+                                ui.separator();
+                            }
+                            ui.horizontal(|ui| {
+                                ui.label(RichText::new(&loaded.entry.title).strong());
+                                if self.new_ids.contains(&loaded.entry.id) {
+                                    ui.label(
+                                        RichText::new(LangMessage::NewsUnread.to_string(lang))
+                                            .color(colors::action(dark_mode)),
+                                    );
+                                }
+                            });
+                            ui.label(
+                                RichText::new(
+                                    LangMessage::NewsPublished {
+                                        days_ago: now.saturating_sub(loaded.entry.timestamp)
+                                            / (24 * 60 * 60),
+                                    }
+                                    .to_string(lang),
+                                )
+                                .weak(),
+                            );
+                            render_markdown(ui, &loaded.blocks);
+                        }
+                    });
+            });
+        self.window_open = open && self.window_open;
+    }
+
+    pub fn is_window_open(&self) -> bool {
+        self.window_open
+    }
+
+    pub fn close_window(&mut self) {
+        self.window_open = false;
+    }
+}
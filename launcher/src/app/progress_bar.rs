@@ -18,6 +18,9 @@ pub struct ProgressBarState {
     pub message: Option<LangMessage>,
     pub finished: bool,
     pub unit: Option<Unit>,
+    // reported by hashing, shown as the throughput
+    pub bytes: u64,
+    pub started_at: std::time::Instant,
 }
 
 impl GuiProgressBar {
@@ -29,6 +32,8 @@ impl GuiProgressBar {
                 message: None,
                 finished: false,
                 unit: None,
+                bytes: 0,
+                started_at: std::time::Instant::now(),
             })),
             ctx: ctx.clone(),
             last_update: Arc::new(Mutex::new(std::time::Instant::now())),
@@ -60,6 +65,8 @@ impl ProgressBar<LangMessage> for GuiProgressBar {
         state.total = length;
         state.progress = 0;
         state.finished = false;
+        state.bytes = 0;
+        state.started_at = std::time::Instant::now();
         self.update_gui_if_needed();
     }
 
@@ -69,6 +76,11 @@ impl ProgressBar<LangMessage> for GuiProgressBar {
         self.update_gui_if_needed();
     }
 
+    fn inc_bytes(&self, bytes: u64) {
+        let mut state = self.state.lock().unwrap();
+        state.bytes += bytes;
+    }
+
     fn finish(&self) {
         let mut state = self.state.lock().unwrap();
         state.finished = true;
@@ -110,5 +122,24 @@ impl GuiProgressBar {
         )
         .text(progress_string)
         .ui(ui);
+
+        if progress_bar_state.bytes > 0 && !progress_bar_state.finished {
+            let elapsed = progress_bar_state.started_at.elapsed().as_secs_f64();
+            let bytes_per_sec = if elapsed > 0.0 {
+                (progress_bar_state.bytes as f64 / elapsed) as u64
+            } else {
+                0
+            };
+            ui.label(
+                egui::RichText::new(
+                    LangMessage::HashingProgress {
+                        files: progress_bar_state.progress,
+                        bytes_per_sec,
+                    }
+                    .to_string(lang),
+                )
+                .weak(),
+            );
+        }
     }
 }
@@ -0,0 +1,537 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+use egui::RichText;
+use log::{error, warn};
+use shared::paths::{get_minecraft_dir, get_screenshot_thumbnails_dir};
+use tokio::runtime::Runtime;
+
+use crate::config::runtime_config::Config;
+use crate::lang::LangMessage;
+
+use super::background_task::{BackgroundTask, BackgroundTaskResult};
+use super::widgets::icon_button;
+
+const SCREENSHOTS_DIR: &str = "screenshots";
+const THUMBNAIL_SIZE: egui::Vec2 = egui::Vec2::new(192.0, 108.0);
+// screenshots taken while the window is open show up after at most this long
+const SCAN_INTERVAL: Duration = Duration::from_secs(2);
+const MAX_THUMBNAIL_TASKS: usize = 4;
+
+#[derive(Clone, PartialEq, Debug)]
+struct ScreenshotFile {
+    path: PathBuf,
+    file_name: String,
+    // unix seconds, part of the thumbnail cache key
+    modified: u64,
+}
+
+// png files only, the game doesn't write anything else there; newest first
+fn scan_screenshots(screenshots_dir: &Path) -> Vec<ScreenshotFile> {
+    let Ok(read_dir) = std::fs::read_dir(screenshots_dir) else {
+        return vec![];
+    };
+    let mut screenshots: Vec<_> = read_dir
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            let file_name = path.file_name()?.to_str()?.to_string();
+            if !file_name.to_lowercase().ends_with(".png") {
+                return None;
+            }
+            let metadata = entry.metadata().ok().filter(|m| m.is_file())?;
+            let modified = metadata
+                .modified()
+                .ok()?
+                .duration_since(UNIX_EPOCH)
+                .ok()?
+                .as_secs();
+            Some(ScreenshotFile {
+                path,
+                file_name,
+                modified,
+            })
+        })
+        .collect();
+    screenshots.sort_by(|a, b| {
+        b.modified
+            .cmp(&a.modified)
+            .then_with(|| b.file_name.cmp(&a.file_name))
+    });
+    screenshots
+}
+
+fn get_thumbnail_cache_name(screenshot: &ScreenshotFile) -> String {
+    format!("{}.{}.png", screenshot.file_name, screenshot.modified)
+}
+
+// removes thumbnails of deleted and overwritten screenshots
+fn prune_thumbnail_cache(cache_dir: &Path, screenshots: &[ScreenshotFile]) {
+    let Ok(read_dir) = std::fs::read_dir(cache_dir) else {
+        return;
+    };
+    let names: HashSet<_> = screenshots.iter().map(get_thumbnail_cache_name).collect();
+    for entry in read_dir.filter_map(|entry| entry.ok()) {
+        if !names.contains(entry.file_name().to_string_lossy().as_ref())
+            && let Err(e) = std::fs::remove_file(entry.path())
+        {
+            warn!("Failed to remove thumbnail {:?}:\n{e:?}", entry.path());
+        }
+    }
+}
+
+fn to_color_image(image: &image::DynamicImage) -> egui::ColorImage {
+    let rgba = image.to_rgba8();
+    egui::ColorImage::from_rgba_unmultiplied(
+        [rgba.width() as usize, rgba.height() as usize],
+        rgba.as_raw(),
+    )
+}
+
+// decodes the cached thumbnail, creating it from the screenshot if needed
+fn load_thumbnail(
+    screenshot: &ScreenshotFile,
+    cache_dir: &Path,
+) -> anyhow::Result<egui::ColorImage> {
+    let cache_path = cache_dir.join(get_thumbnail_cache_name(screenshot));
+    let thumbnail = match image::open(&cache_path) {
+        Ok(thumbnail) => thumbnail,
+        Err(_) => {
+            let thumbnail = image::open(&screenshot.path)?
+                .thumbnail(THUMBNAIL_SIZE.x as u32, THUMBNAIL_SIZE.y as u32);
+            if let Err(e) = thumbnail.save(&cache_path) {
+                warn!(
+                    "Failed to cache thumbnail of {}:\n{e:?}",
+                    screenshot.file_name
+                );
+            }
+            thumbnail
+        }
+    };
+    Ok(to_color_image(&thumbnail))
+}
+
+enum Thumbnail {
+    Loading,
+    Loaded(egui::TextureHandle),
+    Failed,
+}
+
+struct ThumbnailResult {
+    screenshot: ScreenshotFile,
+    image: Option<egui::ColorImage>,
+}
+
+fn get_thumbnail(
+    runtime: &Runtime,
+    screenshot: ScreenshotFile,
+    cache_dir: PathBuf,
+    ctx: &egui::Context,
+) -> BackgroundTask<ThumbnailResult> {
+    let fut = async move {
+        let screenshot_clone = screenshot.clone();
+        let image =
+            tokio::task::spawn_blocking(move || load_thumbnail(&screenshot_clone, &cache_dir))
+                .await
+                .map_err(anyhow::Error::from)
+                .and_then(|result| result);
+        let image = match image {
+            Ok(image) => Some(image),
+            Err(e) => {
+                error!(
+                    "Error loading thumbnail of {}:\n{e:?}",
+                    screenshot.file_name
+                );
+                None
+            }
+        };
+        ThumbnailResult { screenshot, image }
+    };
+
+    let ctx = ctx.clone();
+    BackgroundTask::with_callback(fut, runtime, Box::new(move || ctx.request_repaint()))
+}
+
+fn get_full_image(
+    runtime: &Runtime,
+    path: PathBuf,
+    ctx: &egui::Context,
+) -> BackgroundTask<Option<egui::ColorImage>> {
+    let fut = async move {
+        let image = tokio::task::spawn_blocking(move || -> anyhow::Result<_> {
+            Ok(to_color_image(&image::open(&path)?))
+        })
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(|result| result);
+        match image {
+            Ok(image) => Some(image),
+            Err(e) => {
+                error!("Error decoding screenshot:\n{e:?}");
+                None
+            }
+        }
+    };
+
+    let ctx = ctx.clone();
+    BackgroundTask::with_callback(fut, runtime, Box::new(move || ctx.request_repaint()))
+}
+
+enum ScreenshotAction {
+    Open,
+    Copy,
+    Delete,
+}
+
+pub struct ScreenshotsState {
+    window_open: bool,
+    instance_name: Option<String>,
+    screenshots: Vec<ScreenshotFile>,
+    last_scan: Option<Instant>,
+    thumbnails: HashMap<PathBuf, (u64, Thumbnail)>,
+    thumbnail_tasks: Vec<BackgroundTask<ThumbnailResult>>,
+    copy_task: Option<BackgroundTask<Option<egui::ColorImage>>>,
+    screenshot_to_delete: Option<ScreenshotFile>,
+}
+
+impl ScreenshotsState {
+    pub fn new() -> Self {
+        ScreenshotsState {
+            window_open: false,
+            instance_name: None,
+            screenshots: vec![],
+            last_scan: None,
+            thumbnails: HashMap::new(),
+            thumbnail_tasks: vec![],
+            copy_task: None,
+            screenshot_to_delete: None,
+        }
+    }
+
+    fn scan(&mut self, config: &Config, instance_name: &str) {
+        let launcher_dir = config.get_launcher_dir();
+        self.screenshots = scan_screenshots(
+            &get_minecraft_dir(&launcher_dir, instance_name).join(SCREENSHOTS_DIR),
+        );
+        prune_thumbnail_cache(
+            &get_screenshot_thumbnails_dir(&launcher_dir, instance_name),
+            &self.screenshots,
+        );
+        let current: HashSet<_> = self.screenshots.iter().map(|s| &s.path).collect();
+        self.thumbnails.retain(|path, _| current.contains(path));
+        self.last_scan = Some(Instant::now());
+    }
+
+    pub fn update(&mut self, config: &Config, ctx: &egui::Context) {
+        let finished: Vec<_> = self
+            .thumbnail_tasks
+            .extract_if(.., |task| task.has_result())
+            .collect();
+        for task in finished {
+            if let BackgroundTaskResult::Finished(result) = task.take_result() {
+                let ScreenshotFile { path, modified, .. } = result.screenshot;
+                let thumbnail = match result.image {
+                    Some(image) => Thumbnail::Loaded(ctx.load_texture(
+                        path.display().to_string(),
+                        image,
+                        egui::TextureOptions::LINEAR,
+                    )),
+                    None => Thumbnail::Failed,
+                };
+                self.thumbnails.insert(path, (modified, thumbnail));
+            }
+        }
+
+        if let Some(task) = self.copy_task.as_ref()
+            && task.has_result()
+        {
+            let task = self.copy_task.take().unwrap();
+            if let BackgroundTaskResult::Finished(Some(image)) = task.take_result() {
+                ctx.copy_image(image);
+            }
+        }
+
+        if config.selected_instance_name != self.instance_name {
+            self.instance_name = config.selected_instance_name.clone();
+            self.screenshots.clear();
+            self.thumbnails.clear();
+            self.last_scan = None;
+            self.screenshot_to_delete = None;
+            if self.instance_name.is_none() {
+                self.window_open = false;
+            }
+        }
+
+        if !self.window_open {
+            return;
+        }
+        let Some(instance_name) = self.instance_name.clone() else {
+            return;
+        };
+        if self
+            .last_scan
+            .is_none_or(|last_scan| last_scan.elapsed() >= SCAN_INTERVAL)
+        {
+            self.scan(config, &instance_name);
+        }
+        ctx.request_repaint_after(SCAN_INTERVAL);
+    }
+
+    fn request_thumbnail(
+        &mut self,
+        runtime: &Runtime,
+        config: &Config,
+        screenshot: &ScreenshotFile,
+        ctx: &egui::Context,
+    ) {
+        if self.thumbnail_tasks.len() >= MAX_THUMBNAIL_TASKS {
+            return;
+        }
+        let Some(instance_name) = &self.instance_name else {
+            return;
+        };
+        let cache_dir = get_screenshot_thumbnails_dir(&config.get_launcher_dir(), instance_name);
+        self.thumbnails.insert(
+            screenshot.path.clone(),
+            (screenshot.modified, Thumbnail::Loading),
+        );
+        self.thumbnail_tasks
+            .push(get_thumbnail(runtime, screenshot.clone(), cache_dir, ctx));
+    }
+
+    pub fn render_button(&mut self, ui: &mut egui::Ui, config: &Config) {
+        if config.selected_instance_name.is_some()
+            && icon_button(ui, "🖼", LangMessage::Screenshots, config.lang, true).clicked()
+        {
+            self.window_open = !self.window_open;
+            self.last_scan = None;
+        }
+    }
+
+    fn render_screenshot(
+        &mut self,
+        ui: &mut egui::Ui,
+        runtime: &Runtime,
+        config: &Config,
+        screenshot: &ScreenshotFile,
+    ) -> Option<ScreenshotAction> {
+        let lang = config.lang;
+        let mut action = None;
+        ui.vertical(|ui| {
+            let (rect, _) = ui.allocate_exact_size(THUMBNAIL_SIZE, egui::Sense::hover());
+            let thumbnail = self
+                .thumbnails
+                .get(&screenshot.path)
+                .filter(|(modified, _)| *modified == screenshot.modified);
+            match thumbnail.map(|(_, thumbnail)| thumbnail) {
+                Some(Thumbnail::Loaded(texture)) => {
+                    // keeps the aspect ratio of screenshots with an unusual window size
+                    let size =
+                        texture.size_vec2() * (THUMBNAIL_SIZE / texture.size_vec2()).min_elem();
+                    egui::Image::new((texture.id(), size))
+                        .paint_at(ui, egui::Rect::from_center_size(rect.center(), size));
+                }
+                Some(Thumbnail::Failed) => {
+                    ui.painter().text(
+                        rect.center(),
+                        egui::Align2::CENTER_CENTER,
+                        "⚠",
+                        egui::FontId::proportional(24.0),
+                        ui.visuals().weak_text_color(),
+                    );
+                }
+                Some(Thumbnail::Loading) => {
+                    egui::Spinner::new().paint_at(
+                        ui,
+                        egui::Rect::from_center_size(rect.center(), egui::Vec2::splat(16.0)),
+                    );
+                }
+                None => {
+                    // only the thumbnails scrolled into view are decoded
+                    if ui.is_rect_visible(rect) {
+                        self.request_thumbnail(runtime, config, screenshot, ui.ctx());
+                    }
+                }
+            }
+            ui.set_max_width(THUMBNAIL_SIZE.x);
+            ui.add(egui::Label::new(RichText::new(&screenshot.file_name).small()).truncate());
+            ui.horizontal(|ui| {
+                if icon_button(ui, "🔍", LangMessage::OpenScreenshot, lang, true).clicked() {
+                    action = Some(ScreenshotAction::Open);
+                }
+                if icon_button(
+                    ui,
+                    "📋",
+                    LangMessage::CopyScreenshot,
+                    lang,
+                    self.copy_task.is_none(),
+                )
+                .clicked()
+                {
+                    action = Some(ScreenshotAction::Copy);
+                }
+                if icon_button(ui, "🗑", LangMessage::DeleteScreenshot, lang, true).clicked() {
+                    action = Some(ScreenshotAction::Delete);
+                }
+            });
+        });
+        action
+    }
+
+    pub fn render_window(&mut self, ctx: &egui::Context, runtime: &Runtime, config: &Config) {
+        let lang = config.lang;
+        let mut open = self.window_open;
+        let mut action = None;
+        egui::Window::new(LangMessage::Screenshots.to_string(lang))
+            .id(egui::Id::new("screenshots"))
+            .collapsible(false)
+            .default_size([640.0, 400.0])
+            .open(&mut open)
+            .show(ctx, |ui| {
+                if self.screenshots.is_empty() {
+                    ui.label(LangMessage::NoScreenshots.to_string(lang));
+                    return;
+                }
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false, false])
+                    .show(ui, |ui| {
+                        ui.horizontal_wrapped(|ui| {
+                            for screenshot in self.screenshots.clone() {
+                                if let Some(screenshot_action) =
+                                    self.render_screenshot(ui, runtime, config, &screenshot)
+                                {
+                                    action = Some((screenshot_action, screenshot));
+                                }
+                            }
+                        });
+                    });
+            });
+        self.window_open = open && self.window_open;
+
+        match action {
+            Some((ScreenshotAction::Open, screenshot)) => {
+                if let Err(e) = open::that(&screenshot.path) {
+                    error!("Failed to open screenshot {}:\n{e:?}", screenshot.file_name);
+                }
+            }
+            Some((ScreenshotAction::Copy, screenshot)) => {
+                self.copy_task = Some(get_full_image(runtime, screenshot.path, ctx));
+            }
+            Some((ScreenshotAction::Delete, screenshot)) => {
+                self.screenshot_to_delete = Some(screenshot);
+            }
+            None => {}
+        }
+
+        self.render_delete_window(ctx, config);
+    }
+
+    fn render_delete_window(&mut self, ctx: &egui::Context, config: &Config) {
+        let Some(screenshot) = &self.screenshot_to_delete else {
+            return;
+        };
+        let lang = config.lang;
+
+        let mut open = true;
+        let mut confirmed = false;
+        let mut cancelled = false;
+        egui::Window::new(LangMessage::DeleteScreenshot.to_string(lang))
+            .id(egui::Id::new("delete_screenshot"))
+            .collapsible(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(RichText::new(&screenshot.file_name).strong());
+                ui.horizontal(|ui| {
+                    if ui.button(LangMessage::Delete.to_string(lang)).clicked() {
+                        confirmed = true;
+                    }
+                    if ui.button(LangMessage::Cancel.to_string(lang)).clicked() {
+                        cancelled = true;
+                    }
+                });
+            });
+
+        if confirmed {
+            let screenshot = self.screenshot_to_delete.take().unwrap();
+            if let Err(e) = std::fs::remove_file(&screenshot.path) {
+                error!(
+                    "Failed to delete screenshot {}:\n{e:?}",
+                    screenshot.file_name
+                );
+            }
+            // the thumbnail is pruned by the rescan
+            self.last_scan = None;
+        }
+        if cancelled || !open {
+            self.screenshot_to_delete = None;
+        }
+    }
+
+    pub fn is_window_open(&self) -> bool {
+        self.window_open
+    }
+
+    pub fn close_window(&mut self) {
+        if self.screenshot_to_delete.is_some() {
+            self.screenshot_to_delete = None;
+        } else {
+            self.window_open = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thumbnail_cache() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        let screenshots_dir = dir.join("screenshots");
+        let cache_dir = dir.join("thumbnails");
+        std::fs::create_dir_all(&screenshots_dir).unwrap();
+        std::fs::create_dir_all(&cache_dir).unwrap();
+
+        image::RgbaImage::from_pixel(960, 540, image::Rgba([255, 0, 0, 255]))
+            .save(screenshots_dir.join("2024-05-01_12.00.00.png"))
+            .unwrap();
+        image::RgbaImage::new(800, 600)
+            .save(screenshots_dir.join("2024-05-02_12.00.00.png"))
+            .unwrap();
+        std::fs::write(screenshots_dir.join("notes.txt"), "not an image").unwrap();
+        std::fs::write(screenshots_dir.join("broken.png"), "not an image").unwrap();
+
+        let mut screenshots = scan_screenshots(&screenshots_dir);
+        assert_eq!(screenshots.len(), 3);
+        let screenshot = screenshots
+            .iter()
+            .find(|s| s.file_name == "2024-05-01_12.00.00.png")
+            .unwrap()
+            .clone();
+
+        let thumbnail = load_thumbnail(&screenshot, &cache_dir).unwrap();
+        assert_eq!(thumbnail.size, [192, 108]);
+        let cache_path = cache_dir.join(get_thumbnail_cache_name(&screenshot));
+        assert!(cache_path.exists());
+        // the cached copy is used while the screenshot is unchanged
+        std::fs::remove_file(&screenshot.path).unwrap();
+        assert_eq!(
+            load_thumbnail(&screenshot, &cache_dir).unwrap().size,
+            [192, 108]
+        );
+
+        let broken = screenshots
+            .iter()
+            .find(|s| s.file_name == "broken.png")
+            .unwrap();
+        assert!(load_thumbnail(broken, &cache_dir).is_err());
+
+        screenshots.retain(|s| s.path != screenshot.path);
+        prune_thumbnail_cache(&cache_dir, &screenshots);
+        assert!(!cache_path.exists());
+        assert_eq!(scan_screenshots(&screenshots_dir).len(), 2);
+        assert!(scan_screenshots(&dir.join("missing")).is_empty());
+    }
+}
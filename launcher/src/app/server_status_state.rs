@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use log::debug;
+use shared::mcping::{self, ServerStatus};
+use tokio::runtime::Runtime;
+use tokio::sync::Semaphore;
+
+use crate::version::instance_storage::InstanceStorage;
+
+use super::background_task::{BackgroundTask, BackgroundTaskResult};
+
+const STATUS_CACHE_DURATION: Duration = Duration::from_secs(30);
+const PING_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_CONCURRENT_PINGS: usize = 4;
+
+pub enum ServerState<'a> {
+    // no server address, disabled or not pinged yet
+    Unknown,
+    Online(&'a ServerStatus),
+    Offline,
+}
+
+impl ServerState<'_> {
+    // online servers are listed first, offline ones last
+    fn get_sort_rank(&self) -> u8 {
+        match self {
+            ServerState::Online(_) => 0,
+            ServerState::Unknown => 1,
+            ServerState::Offline => 2,
+        }
+    }
+}
+
+struct CachedStatus {
+    // None if the server didn't respond
+    status: Option<ServerStatus>,
+    updated_at: Instant,
+}
+
+fn ping_server(
+    runtime: &Runtime,
+    ctx: &egui::Context,
+    address: &str,
+    semaphore: Arc<Semaphore>,
+) -> BackgroundTask<Option<ServerStatus>> {
+    let address = address.to_string();
+    let fut = async move {
+        let _permit = semaphore.acquire_owned().await.ok()?;
+        mcping::ping(&address, PING_TIMEOUT)
+            .await
+            .inspect_err(|e| debug!("Server {address} is not available: {e}"))
+            .ok()
+    };
+
+    let ctx = ctx.clone();
+    BackgroundTask::with_callback(fut, runtime, Box::new(move || ctx.request_repaint()))
+}
+
+// statuses of the servers of the listed instances, keyed by the server address
+pub struct ServerStatusState {
+    statuses: HashMap<String, CachedStatus>,
+    ping_tasks: HashMap<String, BackgroundTask<Option<ServerStatus>>>,
+    semaphore: Arc<Semaphore>,
+}
+
+impl ServerStatusState {
+    pub fn new() -> Self {
+        Self {
+            statuses: HashMap::new(),
+            ping_tasks: HashMap::new(),
+            semaphore: Arc::new(Semaphore::new(MAX_CONCURRENT_PINGS)),
+        }
+    }
+
+    // cheap to call every frame, stale servers are pinged in the background
+    pub fn update(
+        &mut self,
+        runtime: &Runtime,
+        ctx: &egui::Context,
+        enabled: bool,
+        instance_names: &[&String],
+        instance_storage: &InstanceStorage,
+    ) {
+        if !enabled {
+            self.statuses.clear();
+            self.ping_tasks.clear();
+            return;
+        }
+
+        let finished: Vec<String> = self
+            .ping_tasks
+            .iter()
+            .filter(|(_, task)| task.has_result())
+            .map(|(address, _)| address.clone())
+            .collect();
+        for address in finished {
+            let task = self.ping_tasks.remove(&address).unwrap();
+            if let BackgroundTaskResult::Finished(status) = task.take_result() {
+                self.statuses.insert(
+                    address,
+                    CachedStatus {
+                        status,
+                        updated_at: Instant::now(),
+                    },
+                );
+            }
+        }
+
+        let mut next_update = STATUS_CACHE_DURATION;
+        for instance_name in instance_names {
+            let Some(address) = instance_storage.get_server_address(instance_name) else {
+                continue;
+            };
+            if self.ping_tasks.contains_key(address) {
+                continue;
+            }
+            let age = self
+                .statuses
+                .get(address)
+                .map(|cached| cached.updated_at.elapsed());
+            match age {
+                Some(age) if age < STATUS_CACHE_DURATION => {
+                    next_update = next_update.min(STATUS_CACHE_DURATION - age);
+                }
+                _ => {
+                    let task = ping_server(runtime, ctx, address, self.semaphore.clone());
+                    self.ping_tasks.insert(address.to_string(), task);
+                }
+            }
+        }
+        if !self.statuses.is_empty() {
+            ctx.request_repaint_after(next_update);
+        }
+    }
+
+    pub fn get_state(
+        &self,
+        instance_storage: &InstanceStorage,
+        instance_name: &str,
+    ) -> ServerState<'_> {
+        let cached = instance_storage
+            .get_server_address(instance_name)
+            .and_then(|address| self.statuses.get(address));
+        match cached {
+            None => ServerState::Unknown,
+            Some(CachedStatus {
+                status: Some(status),
+                ..
+            }) => ServerState::Online(status),
+            Some(CachedStatus { status: None, .. }) => ServerState::Offline,
+        }
+    }
+
+    // stable, so instances with the same state keep their order
+    pub fn sort_names(&self, instance_names: &mut [String], instance_storage: &InstanceStorage) {
+        instance_names.sort_by_key(|name| self.get_state(instance_storage, name).get_sort_rank());
+    }
+}
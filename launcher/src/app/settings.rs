@@ -1,20 +1,37 @@
+use super::background_task::{BackgroundTask, BackgroundTaskResult};
+use super::colors;
+use super::java_manager::JavaManager;
 use super::language_selector::LanguageSelector;
 use super::manifest_state::ManifestState;
+use super::widgets::icon_button;
 use crate::config::build_config;
 use crate::config::build_config::USE_NATIVE_GLFW_DEFAULT;
 use crate::config::runtime_config::Config;
-use crate::constants::{XMX_DEFAULT, XMX_MAX, XMX_MIN, XMX_STEP};
+use crate::constants::{MAX_RESTARTS_ON_CRASH, XMX_DEFAULT, XMX_MAX_32_BIT, XMX_MIN, XMX_STEP};
 use crate::lang::LangMessage;
+use crate::launcher::discord;
+#[cfg(target_os = "linux")]
+use crate::launcher::display::DisplayServer;
+use crate::launcher::game_options::{self, GameOptions};
+use crate::launcher::launch_command;
+use crate::launcher::playtime;
+use crate::launcher::shared_data::{SharedDataConfig, SharedEntry};
 use crate::utils;
+use crate::utils::temp_files;
+use crate::version::archive;
 use crate::version::complete_version_metadata::CompleteVersionMetadata;
-use crate::version::instance_storage::InstanceStorage;
+use crate::version::disk_usage::{self, DiskUsage};
+use crate::version::file_store;
+use crate::version::instance_storage::{InstanceStatus, InstanceStorage, LocalInstance};
+use log::error;
+use shared::adaptive_download::DEFAULT_MAX_CONNECTIONS;
 use shared::java;
+use shared::paths::get_minecraft_dir;
+use shared::version::content_hash::get_short_content_hash;
+use std::path::PathBuf;
+use std::sync::Arc;
 use tokio::runtime::Runtime;
 
-fn get_xmx_max() -> f64 {
-    utils::get_total_memory().map_or(XMX_MAX, |total| total / 1024) as f64
-}
-
 pub struct SettingsState {
     language_selector: LanguageSelector,
     settings_opened: bool,
@@ -22,21 +39,104 @@ pub struct SettingsState {
     picked_java_path: Option<String>,
     xmx_slider_value: f64,
     use_native_glfw: bool,
+    jvm_args: String,
     add_manifest_opened: bool,
     new_manifest_url: String,
+    new_manifest_public_key: String,
+    new_manifest_news_url: String,
+    java_32_bit: bool,
+    removed_manifest_url: Option<String>,
+    auto_sync_change: Option<(String, bool)>,
+    pin_change: Option<(String, bool)>,
+    copy_launch_command_requested: bool,
+    archive_requested: bool,
+    // the zip picked for an export of the selected instance
+    export_path: Option<PathBuf>,
+    // None if options.txt could not be read
+    game_options: Option<GameOptions>,
+    game_max_fps: u32,
+    // None until computed for the opened settings window
+    temp_files_size: Option<u64>,
+    temp_files_task: Option<BackgroundTask<u64>>,
+    // None until the store is cleaned
+    file_store_freed: Option<u64>,
+    file_store_task: Option<BackgroundTask<anyhow::Result<u64>>>,
+    // updated every frame, the store can't be cleaned while an instance syncs
+    is_syncing: bool,
+    // the instance disk_usage is computed for, reset when the window is opened
+    disk_usage_instance: Option<String>,
+    // read when the instance settings are opened
+    playtime: std::time::Duration,
+    disk_usage: Option<DiskUsage>,
+    disk_usage_task: Option<BackgroundTask<anyhow::Result<DiskUsage>>>,
+    allow_clear_folders: bool,
+    java_manager: JavaManager,
 }
 
-fn map_xmx_slider_value(value: f64) -> String {
-    let mb = utils::map_range(value, 0.0, 1.0, XMX_MIN as f64, get_xmx_max()) as u64;
-    format!("{}M", ((mb + XMX_STEP / 2) / XMX_STEP) * XMX_STEP)
+// purges all launcher temp files first if requested, returns the remaining size
+fn get_temp_files_task(runtime: &Runtime, purge: bool, ctx: &egui::Context) -> BackgroundTask<u64> {
+    let fut = async move {
+        tokio::task::spawn_blocking(move || {
+            let temp_dir = utils::get_temp_dir();
+            if purge {
+                temp_files::remove_temp_files(&temp_dir, std::time::Duration::ZERO);
+            }
+            temp_files::get_temp_files_size(&temp_dir)
+        })
+        .await
+        .unwrap_or(0)
+    };
+
+    let ctx = ctx.clone();
+    BackgroundTask::with_callback(fut, runtime, Box::new(move || ctx.request_repaint()))
 }
 
-fn map_xmx_slider_value_reverse(value: &str) -> f64 {
+fn get_clean_file_store_task(
+    runtime: &Runtime,
+    config: &Config,
+    ctx: &egui::Context,
+) -> BackgroundTask<anyhow::Result<u64>> {
+    let launcher_dir = config.get_launcher_dir();
+    let fut = async move { file_store::clean_unused_objects(&launcher_dir).await };
+
+    let ctx = ctx.clone();
+    BackgroundTask::with_callback(fut, runtime, Box::new(move || ctx.request_repaint()))
+}
+
+// clears a folder of the instance first if requested, then measures the instance
+fn get_disk_usage_task(
+    runtime: &Runtime,
+    config: &Config,
+    metadata: Arc<CompleteVersionMetadata>,
+    clear_dir: Option<&'static str>,
+    ctx: &egui::Context,
+) -> BackgroundTask<anyhow::Result<DiskUsage>> {
+    let launcher_dir = config.get_launcher_dir();
+    let assets_dir = config.get_assets_dir();
+    let fut = async move {
+        if let Some(dir) = clear_dir
+            && let Err(e) = disk_usage::clean_dir(&launcher_dir, metadata.get_name(), dir).await
+        {
+            error!("Failed to clear {dir}:\n{e:?}");
+        }
+        disk_usage::get_disk_usage(&metadata, &launcher_dir, &assets_dir).await
+    };
+
+    let ctx = ctx.clone();
+    BackgroundTask::with_callback(fut, runtime, Box::new(move || ctx.request_repaint()))
+}
+
+fn map_xmx_slider_value(value: f64, xmx_max: u64) -> u64 {
+    let mb = utils::map_range(value, 0.0, 1.0, XMX_MIN as f64, xmx_max as f64) as u64;
+    ((mb + XMX_STEP / 2) / XMX_STEP) * XMX_STEP
+}
+
+fn map_xmx_slider_value_reverse(value: &str, xmx_max: u64) -> f64 {
     let xmx = value
         .trim_end_matches('M')
         .parse::<u64>()
         .unwrap_or(XMX_DEFAULT);
-    utils::map_range(xmx as f64, XMX_MIN as f64, get_xmx_max(), 0.0, 1.0)
+    utils::map_range(xmx as f64, XMX_MIN as f64, xmx_max as f64, 0.0, 1.0)
 }
 
 impl SettingsState {
@@ -48,8 +148,81 @@ impl SettingsState {
             picked_java_path: None,
             xmx_slider_value: 0.0,
             use_native_glfw: false,
+            jvm_args: String::new(),
             add_manifest_opened: false,
             new_manifest_url: String::new(),
+            new_manifest_public_key: String::new(),
+            new_manifest_news_url: String::new(),
+            java_32_bit: false,
+            removed_manifest_url: None,
+            auto_sync_change: None,
+            pin_change: None,
+            copy_launch_command_requested: false,
+            archive_requested: false,
+            export_path: None,
+            game_options: None,
+            game_max_fps: game_options::MAX_FPS_UNLIMITED,
+            temp_files_size: None,
+            temp_files_task: None,
+            file_store_freed: None,
+            is_syncing: false,
+            file_store_task: None,
+            disk_usage_instance: None,
+            playtime: std::time::Duration::ZERO,
+            disk_usage: None,
+            disk_usage_task: None,
+            allow_clear_folders: false,
+            java_manager: JavaManager::new(),
+        }
+    }
+
+    // (instance name, auto-sync) once the instance setting is toggled
+    pub fn take_auto_sync_change(&mut self) -> Option<(String, bool)> {
+        self.auto_sync_change.take()
+    }
+
+    // (instance name, pinned) once the instance setting is toggled
+    pub fn take_pin_change(&mut self) -> Option<(String, bool)> {
+        self.pin_change.take()
+    }
+
+    // true once after the debug action is clicked
+    pub fn take_copy_launch_command_request(&mut self) -> bool {
+        std::mem::take(&mut self.copy_launch_command_requested)
+    }
+
+    pub fn take_archive_request(&mut self) -> bool {
+        std::mem::take(&mut self.archive_requested)
+    }
+
+    pub fn set_syncing(&mut self, is_syncing: bool) {
+        self.is_syncing = is_syncing;
+    }
+
+    pub fn take_export_path(&mut self) -> Option<PathBuf> {
+        self.export_path.take()
+    }
+
+    pub fn take_removed_manifest_url(&mut self) -> Option<String> {
+        self.removed_manifest_url.take()
+    }
+
+    pub fn open_settings(&mut self) {
+        self.settings_opened = true;
+        self.temp_files_size = None;
+    }
+
+    pub fn is_window_open(&self) -> bool {
+        self.settings_opened || self.instance_settings_opened || self.add_manifest_opened
+    }
+
+    pub fn close_window(&mut self) {
+        if self.add_manifest_opened {
+            self.add_manifest_opened = false;
+        } else if self.instance_settings_opened {
+            self.instance_settings_opened = false;
+        } else {
+            self.settings_opened = false;
         }
     }
 
@@ -62,15 +235,13 @@ impl SettingsState {
         ctx: &egui::Context,
         instance_storage: &InstanceStorage,
     ) {
-        if ui.button("📂").clicked() {
+        let lang = config.lang;
+        if icon_button(ui, "📂", LangMessage::OpenLauncherFolder, lang, true).clicked() {
             open::that(config.get_launcher_dir()).unwrap();
         }
 
-        if ui
-            .add_enabled(!self.settings_opened, egui::Button::new("⚙"))
-            .clicked()
-        {
-            self.settings_opened = true;
+        if icon_button(ui, "⚙", LangMessage::Settings, lang, !self.settings_opened).clicked() {
+            self.open_settings();
         }
 
         self.language_selector.render_ui(ui, config);
@@ -94,6 +265,20 @@ impl SettingsState {
             .open(&mut settings_opened)
             .show(ui.ctx(), |ui| {
                 self.render_close_launcher_checkbox(ui, config);
+                self.render_rotational_disk_checkbox(ui, config);
+                self.render_pause_auto_sync_checkbox(ui, config);
+                self.render_deduplicate_files_checkbox(ui, config);
+                Self::render_use_file_store_checkbox(ui, config);
+                self.render_advanced_mode_checkbox(ui, config);
+                Self::render_discord_presence_checkbox(ui, config);
+                Self::render_server_status_checkbox(ui, config);
+                Self::render_encrypt_auth_data_checkbox(ui, config);
+                Self::render_max_connections(ui, config);
+                self.java_manager.render_button(ui, config);
+                ui.separator();
+                Self::render_archive_sizes(ui, config);
+                self.render_temp_files(ui, config, runtime, ctx);
+                self.render_clean_file_store(ui, config, runtime, ctx);
                 ui.separator();
                 self.render_manifest_controls(
                     ui,
@@ -105,6 +290,7 @@ impl SettingsState {
                 );
                 self.render_add_manifest_window(ui, config);
             });
+        self.java_manager.render_window(ui, config, runtime, ctx);
 
         self.settings_opened = settings_opened;
     }
@@ -124,6 +310,8 @@ impl SettingsState {
         {
             self.add_manifest_opened = true;
             self.new_manifest_url.clear();
+            self.new_manifest_public_key.clear();
+            self.new_manifest_news_url.clear();
         }
 
         if !config.extra_version_manifest_urls.is_empty() {
@@ -173,9 +361,14 @@ impl SettingsState {
                     ui.label(url);
                     let in_use = instance_storage.count_instances_with_manifest_url(url) > 0;
                     let delete_enabled = !in_use;
-                    if ui
-                        .add_enabled(delete_enabled, egui::Button::new("🗑"))
-                        .clicked()
+                    if icon_button(
+                        ui,
+                        "🗑",
+                        LangMessage::RemoveManifest,
+                        config.lang,
+                        delete_enabled,
+                    )
+                    .clicked()
                     {
                         to_remove = Some(url.clone());
                     }
@@ -188,6 +381,7 @@ impl SettingsState {
                 if before != after {
                     manifest_state.retry_fetch(runtime, config, ctx);
                 }
+                self.removed_manifest_url = Some(url);
             }
         }
     }
@@ -203,6 +397,17 @@ impl SettingsState {
         is_http && not_default && not_duplicate
     }
 
+    fn check_public_key(public_key: &str) -> bool {
+        let trimmed = public_key.trim();
+        trimmed.is_empty()
+            || (trimmed.len() == 64 && trimmed.chars().all(|c| c.is_ascii_hexdigit()))
+    }
+
+    fn check_news_url(news_url: &str) -> bool {
+        let trimmed = news_url.trim();
+        trimmed.is_empty() || trimmed.starts_with("http://") || trimmed.starts_with("https://")
+    }
+
     fn render_add_manifest_window(&mut self, ui: &mut egui::Ui, config: &mut Config) {
         if !self.add_manifest_opened {
             return;
@@ -213,16 +418,28 @@ impl SettingsState {
             .show(ui.ctx(), |ui| {
                 ui.label(LangMessage::EnterManifestUrl.to_string(config.lang));
                 ui.text_edit_singleline(&mut self.new_manifest_url);
+                ui.label(LangMessage::ManifestPublicKey.to_string(config.lang));
+                ui.text_edit_singleline(&mut self.new_manifest_public_key);
+                ui.label(LangMessage::NewsUrl.to_string(config.lang));
+                ui.text_edit_singleline(&mut self.new_manifest_news_url);
                 ui.horizontal(|ui| {
                     if ui
                         .add_enabled(
-                            Self::check_manifest_url(&self.new_manifest_url, config),
+                            Self::check_manifest_url(&self.new_manifest_url, config)
+                                && Self::check_public_key(&self.new_manifest_public_key)
+                                && Self::check_news_url(&self.new_manifest_news_url),
                             egui::Button::new(LangMessage::Add.to_string(config.lang)),
                         )
                         .clicked()
                     {
-                        config.add_version_manifest_url(self.new_manifest_url.clone());
+                        config.add_version_manifest_url(
+                            self.new_manifest_url.clone(),
+                            self.new_manifest_public_key.clone(),
+                            self.new_manifest_news_url.clone(),
+                        );
                         self.new_manifest_url.clear();
+                        self.new_manifest_public_key.clear();
+                        self.new_manifest_news_url.clear();
                         self.add_manifest_opened = false;
                     }
                     if ui
@@ -243,32 +460,57 @@ impl SettingsState {
         ui: &mut egui::Ui,
         runtime: &Runtime,
         config: &mut Config,
-        selected_metadata: Option<&CompleteVersionMetadata>,
+        selected_metadata: Option<&Arc<CompleteVersionMetadata>>,
+        selected_instance: Option<&LocalInstance>,
+        java_32_bit: bool,
     ) {
-        if ui
-            .add_enabled(
-                selected_metadata.is_some() && !self.instance_settings_opened,
-                egui::Button::new("⚙"),
-            )
-            .clicked()
+        self.java_32_bit = java_32_bit;
+        if icon_button(
+            ui,
+            "⚙",
+            LangMessage::InstanceSettings,
+            config.lang,
+            selected_metadata.is_some() && !self.instance_settings_opened,
+        )
+        .clicked()
         {
             self.instance_settings_opened = true;
             let selected_metadata = selected_metadata.unwrap();
-            self.picked_java_path = config.java_paths.get(selected_metadata.get_name()).cloned();
+            self.picked_java_path = config
+                .java_paths
+                .get(selected_metadata.get_name())
+                .map(|path| path.display().to_string());
             self.xmx_slider_value = map_xmx_slider_value_reverse(
                 config
                     .xmx
                     .get(selected_metadata.get_name())
                     .unwrap_or(&XMX_DEFAULT.to_string()),
+                utils::get_xmx_max(java_32_bit),
             );
             self.use_native_glfw = *config
                 .use_native_glfw
                 .get(selected_metadata.get_name())
                 .unwrap_or(&USE_NATIVE_GLFW_DEFAULT);
+            self.jvm_args = config
+                .jvm_args
+                .get(selected_metadata.get_name())
+                .cloned()
+                .unwrap_or_default();
+            self.load_game_options(runtime, config, selected_metadata.get_name());
+            self.playtime =
+                playtime::get_total(&config.get_launcher_dir(), selected_metadata.get_name());
+            self.disk_usage_instance = None;
+            self.allow_clear_folders = false;
         }
 
         if let Some(selected_metadata) = selected_metadata {
-            self.render_instance_settings_window(ui, runtime, config, selected_metadata);
+            self.render_instance_settings_window(
+                ui,
+                runtime,
+                config,
+                selected_metadata,
+                selected_instance,
+            );
         } else {
             self.instance_settings_opened = false;
         }
@@ -295,12 +537,341 @@ impl SettingsState {
         }
     }
 
-    fn render_instance_settings_window(
+    #[cfg(target_os = "linux")]
+    fn render_display_settings(
+        ui: &mut egui::Ui,
+        config: &mut Config,
+        selected_metadata: &CompleteVersionMetadata,
+    ) {
+        let lang = config.lang;
+        let instance_name = selected_metadata.get_name();
+        let get_label = |display_server: DisplayServer| match display_server {
+            DisplayServer::Auto => LangMessage::DisplayServerAuto.to_string(lang),
+            DisplayServer::X11 => "X11".to_string(),
+            DisplayServer::Wayland => "Wayland".to_string(),
+        };
+        let mut changed = false;
+
+        let mut display_server = config
+            .display_server
+            .get(instance_name)
+            .copied()
+            .unwrap_or_default();
+        ui.horizontal(|ui| {
+            ui.label(LangMessage::DisplayServer.to_string(lang));
+            egui::ComboBox::from_id_salt("display_server")
+                .selected_text(get_label(display_server))
+                .show_ui(ui, |ui| {
+                    for option in DisplayServer::ALL {
+                        changed |= ui
+                            .selectable_value(&mut display_server, option, get_label(option))
+                            .changed();
+                    }
+                });
+        });
+        if changed {
+            config
+                .display_server
+                .insert(instance_name.to_string(), display_server);
+        }
+
+        let mut prefer_discrete_gpu = *config
+            .prefer_discrete_gpu
+            .get(instance_name)
+            .unwrap_or(&false);
+        if ui
+            .checkbox(
+                &mut prefer_discrete_gpu,
+                LangMessage::PreferDiscreteGpu.to_string(lang),
+            )
+            .changed()
+        {
+            config
+                .prefer_discrete_gpu
+                .insert(instance_name.to_string(), prefer_discrete_gpu);
+            changed = true;
+        }
+
+        if changed {
+            config.save();
+        }
+    }
+
+    fn render_jvm_args_settings(
+        &mut self,
+        ui: &mut egui::Ui,
+        config: &mut Config,
+        selected_metadata: &CompleteVersionMetadata,
+    ) {
+        let lang = config.lang;
+        let instance_name = selected_metadata.get_name();
+
+        let recommended_jvm_args = selected_metadata.get_recommended_jvm_args();
+        if !recommended_jvm_args.is_empty() {
+            ui.label(LangMessage::RecommendedJvmArgs.to_string(lang));
+            ui.code(recommended_jvm_args.join(" "));
+            let mut use_recommended = !*config
+                .disable_recommended_jvm_args
+                .get(instance_name)
+                .unwrap_or(&false);
+            if ui
+                .checkbox(
+                    &mut use_recommended,
+                    LangMessage::UseRecommendedJvmArgs.to_string(lang),
+                )
+                .changed()
+            {
+                config
+                    .disable_recommended_jvm_args
+                    .insert(instance_name.to_string(), !use_recommended);
+                config.save();
+            }
+        }
+
+        ui.label(LangMessage::JvmArgs.to_string(lang));
+        if ui.text_edit_singleline(&mut self.jvm_args).changed() {
+            config
+                .jvm_args
+                .insert(instance_name.to_string(), self.jvm_args.clone());
+            config.save();
+        }
+    }
+
+    fn render_restart_on_crash(ui: &mut egui::Ui, config: &mut Config, instance_name: &str) {
+        let mut restarts = *config.restart_on_crash.get(instance_name).unwrap_or(&0);
+        let changed = ui
+            .horizontal(|ui| {
+                ui.label(LangMessage::RestartOnCrash.to_string(config.lang));
+                ui.add(egui::DragValue::new(&mut restarts).range(0..=MAX_RESTARTS_ON_CRASH))
+                    .on_hover_text(LangMessage::RestartOnCrashHint.to_string(config.lang))
+                    .changed()
+            })
+            .inner;
+        if changed {
+            if restarts == 0 {
+                config.restart_on_crash.remove(instance_name);
+            } else {
+                config
+                    .restart_on_crash
+                    .insert(instance_name.to_string(), restarts);
+            }
+            config.save();
+        }
+    }
+
+    fn render_env_vars_settings(
+        ui: &mut egui::Ui,
+        config: &mut Config,
+        selected_metadata: &CompleteVersionMetadata,
+    ) {
+        let lang = config.lang;
+        let instance_name = selected_metadata.get_name();
+        let dark_mode = ui.style().visuals.dark_mode;
+        let mut changed = false;
+
+        ui.label(LangMessage::EnvVars.to_string(lang));
+        let env_vars = config
+            .env_vars
+            .entry(instance_name.to_string())
+            .or_default();
+        let mut removed_index = None;
+        egui::Grid::new(("env_vars_grid", instance_name))
+            .num_columns(3)
+            .show(ui, |ui| {
+                for (index, (name, value)) in env_vars.iter_mut().enumerate() {
+                    changed |= ui.text_edit_singleline(name).changed();
+                    changed |= ui.text_edit_singleline(value).changed();
+                    if icon_button(ui, "✖", LangMessage::RemoveEnvVar, lang, true).clicked() {
+                        removed_index = Some(index);
+                    }
+                    ui.end_row();
+                }
+            });
+        if env_vars
+            .iter()
+            .any(|(name, _)| launch_command::check_env_var_name(name).is_err())
+        {
+            ui.colored_label(
+                colors::error(dark_mode),
+                LangMessage::InvalidEnvVarName.to_string(lang),
+            );
+        }
+        if let Some(index) = removed_index {
+            env_vars.remove(index);
+            changed = true;
+        }
+        if ui.button(LangMessage::AddEnvVar.to_string(lang)).clicked() {
+            env_vars.push((String::new(), String::new()));
+            changed = true;
+        }
+        if env_vars.is_empty() {
+            config.env_vars.remove(instance_name);
+        }
+
+        if changed {
+            config.save();
+        }
+    }
+
+    fn load_game_options(&mut self, runtime: &Runtime, config: &Config, instance_name: &str) {
+        let minecraft_dir = get_minecraft_dir(&config.get_launcher_dir(), instance_name);
+        self.game_options = runtime
+            .block_on(GameOptions::read(&minecraft_dir))
+            .inspect_err(|e| error!("Error reading options.txt of {instance_name}:\n{e:?}"))
+            .ok();
+        self.game_max_fps = self
+            .game_options
+            .as_ref()
+            .and_then(|options| options.get_u32(game_options::MAX_FPS_KEY))
+            .unwrap_or(game_options::MAX_FPS_UNLIMITED);
+    }
+
+    fn render_game_options(
         &mut self,
         ui: &mut egui::Ui,
         runtime: &Runtime,
+        config: &Config,
+        instance_name: &str,
+    ) {
+        let lang = config.lang;
+        egui::CollapsingHeader::new(LangMessage::GameOptions.to_string(lang)).show(ui, |ui| {
+            let Some(options) = self.game_options.as_mut() else {
+                ui.label(LangMessage::GameOptionsError.to_string(lang));
+                return;
+            };
+            let mut changed = false;
+
+            let current_lang = options.get_lang();
+            let mut selected_lang = current_lang.clone();
+            ui.horizontal(|ui| {
+                ui.label(LangMessage::GameLanguage.to_string(lang));
+                let selected_text = match &selected_lang {
+                    Some(code) => game_options::GAME_LANGUAGES
+                        .iter()
+                        .find(|(c, _)| c == code)
+                        .map(|(_, name)| name.to_string())
+                        .unwrap_or(code.clone()),
+                    None => LangMessage::NotSelected.to_string(lang),
+                };
+                egui::ComboBox::from_id_salt("game_language")
+                    .selected_text(selected_text)
+                    .show_ui(ui, |ui| {
+                        for (code, name) in game_options::GAME_LANGUAGES {
+                            ui.selectable_value(&mut selected_lang, Some(code.to_string()), *name);
+                        }
+                    });
+            });
+            if selected_lang != current_lang
+                && let Some(selected_lang) = &selected_lang
+            {
+                options.set_lang(selected_lang);
+                changed = true;
+            }
+
+            ui.label(LangMessage::MaxFps.to_string(lang));
+            let response = ui.add(
+                egui::Slider::new(&mut self.game_max_fps, 10..=game_options::MAX_FPS_UNLIMITED)
+                    .step_by(10.0)
+                    .custom_formatter(|value, _| {
+                        if value as u32 >= game_options::MAX_FPS_UNLIMITED {
+                            LangMessage::Unlimited.to_string(lang)
+                        } else {
+                            value.to_string()
+                        }
+                    }),
+            );
+            // written once the slider is released
+            if (response.drag_stopped() || (response.changed() && !response.dragged()))
+                && options.get_u32(game_options::MAX_FPS_KEY) != Some(self.game_max_fps)
+            {
+                options.set(game_options::MAX_FPS_KEY, &self.game_max_fps.to_string());
+                changed = true;
+            }
+
+            for (key, message) in [
+                (game_options::FULLSCREEN_KEY, LangMessage::Fullscreen),
+                (game_options::VSYNC_KEY, LangMessage::Vsync),
+            ] {
+                let mut value = options.get_bool(key).unwrap_or_default();
+                if ui.checkbox(&mut value, message.to_string(lang)).changed() {
+                    options.set_bool(key, value);
+                    changed = true;
+                }
+            }
+
+            if changed {
+                let minecraft_dir = get_minecraft_dir(&config.get_launcher_dir(), instance_name);
+                if let Err(e) = runtime.block_on(options.save(&minecraft_dir)) {
+                    error!("Error writing options.txt of {instance_name}:\n{e:?}");
+                    self.game_options = None;
+                }
+            }
+        });
+    }
+
+    fn render_shared_data_settings(
+        ui: &mut egui::Ui,
         config: &mut Config,
         selected_metadata: &CompleteVersionMetadata,
+    ) {
+        let lang = config.lang;
+        let instance_name = selected_metadata.get_name();
+
+        ui.label(LangMessage::SharedDataDir.to_string(lang));
+        let mut changed = false;
+        match config.shared_data.get_mut(instance_name) {
+            Some(shared_data) => {
+                ui.code(shared_data.dir.display().to_string());
+                for entry in SharedEntry::ALL {
+                    let mut shared = shared_data.entries.contains(&entry);
+                    if ui.checkbox(&mut shared, entry.get_rel_path()).changed() {
+                        if shared {
+                            shared_data.entries.push(entry);
+                        } else {
+                            shared_data.entries.retain(|x| *x != entry);
+                        }
+                        changed = true;
+                    }
+                }
+            }
+            None => {
+                ui.label(LangMessage::SharedDataDirNotSet.to_string(lang));
+            }
+        }
+
+        ui.horizontal(|ui| {
+            if ui
+                .button(LangMessage::SelectSharedDataDir.to_string(lang))
+                .clicked()
+                && let Some(path) = rfd::FileDialog::new().pick_folder()
+            {
+                config
+                    .shared_data
+                    .insert(instance_name.to_string(), SharedDataConfig::new(path));
+                changed = true;
+            }
+            if config.shared_data.contains_key(instance_name)
+                && ui
+                    .button(LangMessage::StopSharingData.to_string(lang))
+                    .clicked()
+            {
+                config.shared_data.remove(instance_name);
+                changed = true;
+            }
+        });
+
+        if changed {
+            config.save();
+        }
+    }
+
+    fn render_instance_settings_window(
+        &mut self,
+        ui: &mut egui::Ui,
+        runtime: &Runtime,
+        config: &mut Config,
+        selected_metadata: &Arc<CompleteVersionMetadata>,
+        selected_instance: Option<&LocalInstance>,
     ) {
         let lang = config.lang;
         let mut settings_opened = self.instance_settings_opened;
@@ -308,6 +879,15 @@ impl SettingsState {
         egui::Window::new(LangMessage::InstanceSettings.to_string(lang))
             .open(&mut settings_opened)
             .show(ui.ctx(), |ui| {
+                let played_secs = self.playtime.as_secs();
+                ui.label(
+                    LangMessage::Playtime {
+                        hours: played_secs / 3600,
+                        minutes: played_secs % 3600 / 60,
+                    }
+                    .to_string(lang),
+                );
+
                 if let Some(picked_java_path) = &self.picked_java_path {
                     ui.label(LangMessage::SelectedJavaPath.to_string(lang));
                     ui.code(picked_java_path);
@@ -315,20 +895,39 @@ impl SettingsState {
                     ui.label(LangMessage::NoJavaPath.to_string(lang));
                 }
 
+                let mut picked_path = None;
                 if ui
                     .button(LangMessage::SelectJavaPath.to_string(lang))
                     .clicked()
-                    && let Some(path) = rfd::FileDialog::new().pick_file()
                 {
+                    picked_path = rfd::FileDialog::new().pick_file();
+                }
+                let system_java = self.java_manager.get_system_java();
+                if !system_java.is_empty() {
+                    egui::ComboBox::from_label(LangMessage::DetectedJava.to_string(lang))
+                        .selected_text("")
+                        .show_ui(ui, |ui| {
+                            for installation in system_java {
+                                let text = format!(
+                                    "{}: {}",
+                                    installation.version,
+                                    installation.path.display()
+                                );
+                                if ui.selectable_label(false, text).clicked() {
+                                    picked_path = Some(installation.path.clone());
+                                }
+                            }
+                        });
+                }
+                if let Some(path) = picked_path {
                     if runtime.block_on(java::check_java(
                         &selected_metadata.get_java_version(),
                         &path,
                     )) {
                         self.picked_java_path = Some(path.display().to_string());
-                        config.java_paths.insert(
-                            selected_metadata.get_name().to_string(),
-                            path.display().to_string(),
-                        );
+                        config
+                            .java_paths
+                            .insert(selected_metadata.get_name().to_string(), path);
                         config.save();
                     } else {
                         self.picked_java_path =
@@ -337,43 +936,334 @@ impl SettingsState {
                 }
 
                 ui.label(LangMessage::AllocatedMemory.to_string(lang));
+                let xmx_max = utils::get_xmx_max(self.java_32_bit);
                 let old_xmx = self.xmx_slider_value;
+                let xmx_too_high =
+                    utils::is_xmx_too_high(map_xmx_slider_value(self.xmx_slider_value, xmx_max));
+                let dark_mode = ui.style().visuals.dark_mode;
                 let xmx_slider = egui::Slider::new(&mut self.xmx_slider_value, 0.0..=1.0)
+                    .trailing_fill(true)
                     .custom_formatter(|value, _| {
-                        format!(
-                            "{}M",
-                            ((utils::map_range(value, 0.0, 1.0, XMX_MIN as f64, get_xmx_max())
-                                as u64
-                                + XMX_STEP / 2)
-                                / XMX_STEP)
-                                * XMX_STEP
-                        )
+                        format!("{}M", map_xmx_slider_value(value, xmx_max))
                     })
                     .custom_parser(|value| {
-                        let mb = utils::format_xmx(Some(value))
-                            .trim_end_matches('M')
-                            .parse::<u64>()
-                            .unwrap_or(XMX_DEFAULT);
+                        let mb = utils::parse_xmx_mb(Some(value));
                         let value_unclamped =
-                            utils::map_range(mb as f64, XMX_MIN as f64, get_xmx_max(), 0.0, 1.0);
+                            utils::map_range(mb as f64, XMX_MIN as f64, xmx_max as f64, 0.0, 1.0);
                         Some(value_unclamped.clamp(0.0, 1.0))
                     });
-                ui.add(xmx_slider);
+                ui.scope(|ui| {
+                    if xmx_too_high {
+                        ui.visuals_mut().selection.bg_fill = colors::error(dark_mode);
+                    }
+                    ui.add(xmx_slider);
+                });
+                if xmx_too_high {
+                    ui.colored_label(
+                        colors::error(dark_mode),
+                        LangMessage::XmxTooHigh.to_string(lang),
+                    );
+                }
+                if self.java_32_bit {
+                    ui.colored_label(
+                        colors::offline(dark_mode),
+                        LangMessage::Java32BitXmxLimit(format!("{XMX_MAX_32_BIT}M"))
+                            .to_string(lang),
+                    );
+                }
                 if old_xmx != self.xmx_slider_value {
                     config.xmx.insert(
                         selected_metadata.get_name().to_string(),
-                        map_xmx_slider_value(self.xmx_slider_value),
+                        format!("{}M", map_xmx_slider_value(self.xmx_slider_value, xmx_max)),
                     );
                     config.save();
                 }
 
+                self.render_jvm_args_settings(ui, config, selected_metadata);
+                Self::render_env_vars_settings(ui, config, selected_metadata);
+                Self::render_restart_on_crash(ui, config, selected_metadata.get_name());
+
                 #[cfg(target_os = "linux")]
                 self.render_use_native_glfw_checkbox(ui, config, selected_metadata);
+                #[cfg(target_os = "linux")]
+                Self::render_display_settings(ui, config, selected_metadata);
+
+                Self::render_shared_data_settings(ui, config, selected_metadata);
+
+                // archived instances have no game dir
+                if selected_instance
+                    .is_none_or(|instance| instance.status != InstanceStatus::Archived)
+                {
+                    self.render_game_options(ui, runtime, config, selected_metadata.get_name());
+                    self.render_disk_usage(ui, runtime, config, selected_metadata);
+                }
+
+                // missing instances have no meta to store the flag in
+                if let Some(instance) =
+                    selected_instance.filter(|instance| instance.status != InstanceStatus::Missing)
+                {
+                    let mut auto_sync = instance.auto_sync;
+                    ui.add_enabled_ui(!config.pause_auto_sync, |ui| {
+                        if ui
+                            .checkbox(
+                                &mut auto_sync,
+                                LangMessage::AutoSyncOnStartup.to_string(lang),
+                            )
+                            .changed()
+                        {
+                            self.auto_sync_change =
+                                Some((selected_metadata.get_name().to_string(), auto_sync));
+                        }
+                    });
+
+                    let mut pinned = instance.pinned;
+                    if ui
+                        .checkbox(&mut pinned, LangMessage::PinVersion.to_string(lang))
+                        .changed()
+                    {
+                        self.pin_change = Some((selected_metadata.get_name().to_string(), pinned));
+                    }
+
+                    if instance.status != InstanceStatus::Archived
+                        && ui
+                            .button(LangMessage::ArchiveInstance.to_string(lang))
+                            .on_hover_text(LangMessage::ArchiveInstanceHint.to_string(lang))
+                            .clicked()
+                    {
+                        self.archive_requested = true;
+                    }
+
+                    if instance.status != InstanceStatus::Archived
+                        && ui
+                            .button(LangMessage::ExportInstance.to_string(lang))
+                            .on_hover_text(LangMessage::ExportInstanceHint.to_string(lang))
+                            .clicked()
+                    {
+                        self.export_path = rfd::FileDialog::new()
+                            .add_filter("zip", &["zip"])
+                            .set_file_name(format!("{}.zip", selected_metadata.get_name()))
+                            .save_file();
+                    }
+                }
+
+                if let Some(content_hash) =
+                    selected_instance.and_then(|instance| instance.content_hash.as_deref())
+                {
+                    ui.horizontal(|ui| {
+                        ui.label(LangMessage::ContentHash.to_string(lang));
+                        ui.code(get_short_content_hash(content_hash));
+                        if icon_button(ui, "📋", LangMessage::CopyContentHash, lang, true).clicked()
+                        {
+                            ui.ctx().copy_text(content_hash.to_string());
+                        }
+                    });
+                }
+
+                if config.advanced_mode
+                    && ui
+                        .button(LangMessage::CopyLaunchCommand.to_string(lang))
+                        .on_hover_text(LangMessage::CopyLaunchCommandHint.to_string(lang))
+                        .clicked()
+                {
+                    self.copy_launch_command_requested = true;
+                }
             });
 
         self.instance_settings_opened = settings_opened;
     }
 
+    fn render_disk_usage(
+        &mut self,
+        ui: &mut egui::Ui,
+        runtime: &Runtime,
+        config: &Config,
+        selected_metadata: &Arc<CompleteVersionMetadata>,
+    ) {
+        if let Some(task) = self.disk_usage_task.as_ref()
+            && task.has_result()
+        {
+            let task = self.disk_usage_task.take().unwrap();
+            if let BackgroundTaskResult::Finished(result) = task.take_result() {
+                self.disk_usage = result
+                    .inspect_err(|e| error!("Failed to compute the disk usage:\n{e:?}"))
+                    .ok();
+            }
+        }
+
+        let lang = config.lang;
+        let instance_name = selected_metadata.get_name();
+        egui::CollapsingHeader::new(LangMessage::DiskUsage.to_string(lang)).show(ui, |ui| {
+            let mut clear_dir = None;
+            if self.disk_usage_task.is_some() {
+                ui.spinner();
+            } else if self.disk_usage_instance.as_deref() == Some(instance_name)
+                && let Some(usage) = &self.disk_usage
+            {
+                ui.label(
+                    LangMessage::InstanceSize {
+                        size: usage.instance,
+                    }
+                    .to_string(lang),
+                );
+                ui.label(
+                    LangMessage::LibrariesSize {
+                        size: usage.libraries,
+                    }
+                    .to_string(lang),
+                )
+                .on_hover_text(LangMessage::SharedFilesHint.to_string(lang));
+                ui.label(
+                    LangMessage::AssetsSize {
+                        size: usage.assets,
+                        total: usage.assets_total,
+                    }
+                    .to_string(lang),
+                )
+                .on_hover_text(LangMessage::SharedFilesHint.to_string(lang));
+
+                if !usage.cleanable.is_empty() {
+                    ui.checkbox(
+                        &mut self.allow_clear_folders,
+                        LangMessage::ConfirmClearFolders.to_string(lang),
+                    );
+                }
+                for (dir, size) in &usage.cleanable {
+                    ui.horizontal(|ui| {
+                        ui.label(
+                            LangMessage::FolderSize {
+                                dir: dir.to_string(),
+                                size: *size,
+                            }
+                            .to_string(lang),
+                        );
+                        if icon_button(
+                            ui,
+                            "🗑",
+                            LangMessage::ClearFolder,
+                            lang,
+                            self.allow_clear_folders,
+                        )
+                        .clicked()
+                        {
+                            clear_dir = Some(*dir);
+                        }
+                    });
+                }
+            }
+
+            if self.disk_usage_task.is_none()
+                && (clear_dir.is_some()
+                    || self.disk_usage_instance.as_deref() != Some(instance_name))
+            {
+                self.allow_clear_folders = false;
+                self.disk_usage_instance = Some(instance_name.to_string());
+                self.disk_usage_task = Some(get_disk_usage_task(
+                    runtime,
+                    config,
+                    selected_metadata.clone(),
+                    clear_dir,
+                    ui.ctx(),
+                ));
+            }
+        });
+    }
+
+    fn render_archive_sizes(ui: &mut egui::Ui, config: &Config) {
+        let lang = config.lang;
+        ui.label(LangMessage::ArchivedInstances.to_string(lang));
+        let archive_sizes = archive::get_archive_sizes(&config.get_launcher_dir());
+        if archive_sizes.is_empty() {
+            ui.label(LangMessage::NoArchivedInstances.to_string(lang));
+        }
+        for (instance_name, size) in archive_sizes {
+            ui.label(
+                LangMessage::ArchiveSize {
+                    instance_name,
+                    size,
+                }
+                .to_string(lang),
+            );
+        }
+    }
+
+    fn render_temp_files(
+        &mut self,
+        ui: &mut egui::Ui,
+        config: &Config,
+        runtime: &Runtime,
+        ctx: &egui::Context,
+    ) {
+        if let Some(task) = self.temp_files_task.as_ref()
+            && task.has_result()
+        {
+            let task = self.temp_files_task.take().unwrap();
+            if let BackgroundTaskResult::Finished(size) = task.take_result() {
+                self.temp_files_size = Some(size);
+            }
+        }
+        if self.temp_files_size.is_none() && self.temp_files_task.is_none() {
+            self.temp_files_task = Some(get_temp_files_task(runtime, false, ctx));
+        }
+
+        let lang = config.lang;
+        ui.horizontal(|ui| {
+            match self.temp_files_size {
+                Some(size) if self.temp_files_task.is_none() => {
+                    ui.label(LangMessage::TemporaryFiles { size }.to_string(lang));
+                }
+                _ => {
+                    ui.spinner();
+                }
+            }
+            let can_purge =
+                self.temp_files_task.is_none() && self.temp_files_size.is_some_and(|size| size > 0);
+            if icon_button(ui, "🗑", LangMessage::PurgeTemporaryFiles, lang, can_purge).clicked()
+            {
+                self.temp_files_task = Some(get_temp_files_task(runtime, true, ctx));
+            }
+        });
+    }
+
+    fn render_clean_file_store(
+        &mut self,
+        ui: &mut egui::Ui,
+        config: &Config,
+        runtime: &Runtime,
+        ctx: &egui::Context,
+    ) {
+        if let Some(task) = self.file_store_task.as_ref()
+            && task.has_result()
+        {
+            let task = self.file_store_task.take().unwrap();
+            if let BackgroundTaskResult::Finished(result) = task.take_result() {
+                match result {
+                    Ok(size) => self.file_store_freed = Some(size),
+                    Err(e) => error!("Failed to clean the file store:\n{e:?}"),
+                }
+            }
+        }
+
+        let lang = config.lang;
+        ui.horizontal(|ui| {
+            let is_cleaning = self.file_store_task.is_some();
+            if ui
+                // a running sync may still need the files that aren't linked yet
+                .add_enabled(
+                    !is_cleaning && !self.is_syncing,
+                    egui::Button::new(LangMessage::CleanFileStore.to_string(lang)),
+                )
+                .clicked()
+            {
+                self.file_store_task = Some(get_clean_file_store_task(runtime, config, ctx));
+            }
+            if is_cleaning {
+                ui.spinner();
+            } else if let Some(size) = self.file_store_freed {
+                ui.label(LangMessage::FileStoreCleaned { size }.to_string(lang));
+            }
+        });
+    }
+
     fn render_close_launcher_checkbox(&mut self, ui: &mut egui::Ui, config: &mut Config) {
         let old_close_launcher_after_launch = config.hide_launcher_after_launch;
         ui.checkbox(
@@ -384,4 +1274,125 @@ impl SettingsState {
             config.save();
         }
     }
+
+    fn render_pause_auto_sync_checkbox(&mut self, ui: &mut egui::Ui, config: &mut Config) {
+        let old_pause_auto_sync = config.pause_auto_sync;
+        ui.checkbox(
+            &mut config.pause_auto_sync,
+            LangMessage::PauseAutoSync.to_string(config.lang),
+        );
+        if old_pause_auto_sync != config.pause_auto_sync {
+            config.save();
+        }
+    }
+
+    fn render_use_file_store_checkbox(ui: &mut egui::Ui, config: &mut Config) {
+        let old_use_file_store = config.use_file_store;
+        ui.checkbox(
+            &mut config.use_file_store,
+            LangMessage::UseFileStore.to_string(config.lang),
+        )
+        .on_hover_text(LangMessage::UseFileStoreHint.to_string(config.lang));
+        if old_use_file_store != config.use_file_store {
+            config.save();
+        }
+    }
+
+    fn render_deduplicate_files_checkbox(&mut self, ui: &mut egui::Ui, config: &mut Config) {
+        let old_deduplicate_files = config.deduplicate_files;
+        ui.checkbox(
+            &mut config.deduplicate_files,
+            LangMessage::DeduplicateFiles.to_string(config.lang),
+        )
+        .on_hover_text(LangMessage::DeduplicateFilesHint.to_string(config.lang));
+        if old_deduplicate_files != config.deduplicate_files {
+            config.save();
+        }
+    }
+
+    fn render_advanced_mode_checkbox(&mut self, ui: &mut egui::Ui, config: &mut Config) {
+        if ui
+            .checkbox(
+                &mut config.advanced_mode,
+                LangMessage::AdvancedMode.to_string(config.lang),
+            )
+            .changed()
+        {
+            config.save();
+        }
+    }
+
+    // hidden in builds without a discord application
+    fn render_discord_presence_checkbox(ui: &mut egui::Ui, config: &mut Config) {
+        if !discord::is_available() {
+            return;
+        }
+        if ui
+            .checkbox(
+                &mut config.discord_presence,
+                LangMessage::DiscordPresence.to_string(config.lang),
+            )
+            .changed()
+        {
+            config.save();
+        }
+    }
+
+    fn render_server_status_checkbox(ui: &mut egui::Ui, config: &mut Config) {
+        if ui
+            .checkbox(
+                &mut config.server_status,
+                LangMessage::ShowServerStatus.to_string(config.lang),
+            )
+            .changed()
+        {
+            config.save();
+        }
+    }
+
+    // the saved accounts are moved by AuthState on the next frame
+    fn render_encrypt_auth_data_checkbox(ui: &mut egui::Ui, config: &mut Config) {
+        if ui
+            .checkbox(
+                &mut config.encrypt_auth_data,
+                LangMessage::EncryptAuthData.to_string(config.lang),
+            )
+            .on_hover_text(LangMessage::EncryptAuthDataHint.to_string(config.lang))
+            .changed()
+        {
+            config.save();
+        }
+    }
+
+    fn render_max_connections(ui: &mut egui::Ui, config: &mut Config) {
+        let mut max_connections = config.get_max_connections();
+        let changed = ui
+            .horizontal(|ui| {
+                ui.label(LangMessage::MaxConnections.to_string(config.lang));
+                ui.add(
+                    egui::DragValue::new(&mut max_connections).range(1..=DEFAULT_MAX_CONNECTIONS),
+                )
+                .on_hover_text(LangMessage::MaxConnectionsHint.to_string(config.lang))
+                .changed()
+            })
+            .inner;
+        if changed {
+            // the default is kept unset, so it follows the launcher updates
+            config.max_connections =
+                (max_connections != DEFAULT_MAX_CONNECTIONS).then_some(max_connections);
+            config.save();
+        }
+    }
+
+    fn render_rotational_disk_checkbox(&mut self, ui: &mut egui::Ui, config: &mut Config) {
+        let old_rotational_disk = config.rotational_disk;
+        ui.checkbox(
+            &mut config.rotational_disk,
+            LangMessage::RotationalDisk.to_string(config.lang),
+        )
+        .on_hover_text(LangMessage::RotationalDiskHint.to_string(config.lang));
+        if old_rotational_disk != config.rotational_disk {
+            config.save();
+        }
+    }
 }
@@ -0,0 +1,137 @@
+use egui::{Key, Modifiers};
+
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct KeyPresses {
+    pub enter: bool,
+    pub refresh: bool,
+    pub settings: bool,
+    pub escape: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LauncherWindow {
+    Auth,
+    Sync,
+    NewInstance,
+    Settings,
+    Tasks,
+    LaunchProfile,
+    News,
+    Screenshots,
+}
+
+pub struct ShortcutState {
+    pub text_field_focused: bool,
+    pub ready_for_launch: bool,
+    // ordered from topmost
+    pub open_windows: Vec<LauncherWindow>,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ShortcutAction {
+    Launch,
+    DownloadAndLaunch,
+    Refresh,
+    OpenSettings,
+    CloseWindow(LauncherWindow),
+}
+
+pub fn get_shortcut_action(keys: KeyPresses, state: &ShortcutState) -> Option<ShortcutAction> {
+    if state.text_field_focused {
+        return None;
+    }
+
+    if keys.escape {
+        return state
+            .open_windows
+            .first()
+            .map(|window| ShortcutAction::CloseWindow(*window));
+    }
+    if keys.refresh {
+        return Some(ShortcutAction::Refresh);
+    }
+    if keys.settings {
+        return Some(ShortcutAction::OpenSettings);
+    }
+    if keys.enter && state.open_windows.is_empty() {
+        return Some(if state.ready_for_launch {
+            ShortcutAction::Launch
+        } else {
+            ShortcutAction::DownloadAndLaunch
+        });
+    }
+    None
+}
+
+pub fn poll_key_presses(ctx: &egui::Context) -> KeyPresses {
+    // text edits handle these keys themselves
+    if ctx.wants_keyboard_input() {
+        return KeyPresses::default();
+    }
+    ctx.input_mut(|input| KeyPresses {
+        enter: input.consume_key(Modifiers::NONE, Key::Enter),
+        refresh: input.consume_key(Modifiers::COMMAND, Key::R),
+        settings: input.consume_key(Modifiers::COMMAND, Key::Comma),
+        escape: input.consume_key(Modifiers::NONE, Key::Escape),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(ready_for_launch: bool, open_windows: Vec<LauncherWindow>) -> ShortcutState {
+        ShortcutState {
+            text_field_focused: false,
+            ready_for_launch,
+            open_windows,
+        }
+    }
+
+    #[test]
+    fn test_shortcut_actions() {
+        let enter = KeyPresses {
+            enter: true,
+            ..Default::default()
+        };
+        let escape = KeyPresses {
+            escape: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            get_shortcut_action(enter, &state(true, vec![])),
+            Some(ShortcutAction::Launch)
+        );
+        assert_eq!(
+            get_shortcut_action(enter, &state(false, vec![])),
+            Some(ShortcutAction::DownloadAndLaunch)
+        );
+        assert_eq!(
+            get_shortcut_action(enter, &state(true, vec![LauncherWindow::Sync])),
+            None
+        );
+        assert_eq!(
+            get_shortcut_action(
+                escape,
+                &state(true, vec![LauncherWindow::Auth, LauncherWindow::Settings])
+            ),
+            Some(ShortcutAction::CloseWindow(LauncherWindow::Auth))
+        );
+        assert_eq!(get_shortcut_action(escape, &state(true, vec![])), None);
+        assert_eq!(
+            get_shortcut_action(
+                KeyPresses {
+                    refresh: true,
+                    ..Default::default()
+                },
+                &state(false, vec![LauncherWindow::NewInstance])
+            ),
+            Some(ShortcutAction::Refresh)
+        );
+
+        let mut focused = state(true, vec![]);
+        focused.text_field_focused = true;
+        assert_eq!(get_shortcut_action(enter, &focused), None);
+    }
+}
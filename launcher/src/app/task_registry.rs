@@ -0,0 +1,193 @@
+use std::sync::{Arc, Mutex};
+
+use egui::RichText;
+
+use crate::lang::{Lang, LangMessage};
+
+use super::background_task::{BackgroundTask, TaskHandle};
+use super::progress_bar::GuiProgressBar;
+use super::widgets::icon_button;
+
+// what a task works on, two running tasks with the same key conflict
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum TaskKey {
+    FetchManifest,
+    FetchMetadata(String),
+    SyncInstance(String),
+    CheckInstance(String),
+    CheckJava(String),
+    DownloadJava(String),
+    ArchiveInstance(String),
+    RestoreInstance(String),
+    ExportInstance(String),
+}
+
+impl TaskKey {
+    fn get_message(&self) -> LangMessage {
+        match self {
+            TaskKey::FetchManifest => LangMessage::TaskFetchManifest,
+            TaskKey::FetchMetadata(instance_name) => {
+                LangMessage::TaskFetchMetadata(instance_name.clone())
+            }
+            TaskKey::SyncInstance(instance_name) => {
+                LangMessage::TaskSyncInstance(instance_name.clone())
+            }
+            TaskKey::CheckInstance(instance_name) => {
+                LangMessage::TaskCheckInstance(instance_name.clone())
+            }
+            TaskKey::CheckJava(version) => LangMessage::TaskCheckJava(version.clone()),
+            TaskKey::DownloadJava(version) => LangMessage::TaskDownloadJava(version.clone()),
+            TaskKey::ArchiveInstance(instance_name) => {
+                LangMessage::TaskArchiveInstance(instance_name.clone())
+            }
+            TaskKey::RestoreInstance(instance_name) => {
+                LangMessage::TaskRestoreInstance(instance_name.clone())
+            }
+            TaskKey::ExportInstance(instance_name) => {
+                LangMessage::TaskExportInstance(instance_name.clone())
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+struct RegisteredTask {
+    key: TaskKey,
+    progress_bar: Option<Arc<GuiProgressBar>>,
+    handle: TaskHandle,
+}
+
+// every running background task, shared by the states that own them
+#[derive(Clone, Default)]
+pub struct TaskRegistry {
+    tasks: Arc<Mutex<Vec<RegisteredTask>>>,
+}
+
+impl TaskRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // finished and dropped tasks are removed on the next access
+    fn get_tasks(&self) -> Vec<RegisteredTask> {
+        let mut tasks = self.tasks.lock().unwrap();
+        tasks.retain(|task| !task.handle.is_finished());
+        tasks.clone()
+    }
+
+    pub fn register<T>(
+        &self,
+        key: TaskKey,
+        task: &BackgroundTask<T>,
+        progress_bar: Option<Arc<GuiProgressBar>>,
+    ) where
+        T: Send + 'static,
+    {
+        let mut tasks = self.tasks.lock().unwrap();
+        tasks.retain(|task| !task.handle.is_finished());
+        tasks.push(RegisteredTask {
+            key,
+            progress_bar,
+            handle: task.get_handle(),
+        });
+    }
+
+    pub fn is_running(&self, key: &TaskKey) -> bool {
+        self.get_tasks().iter().any(|task| &task.key == key)
+    }
+
+    fn render_progress_bar(ui: &mut egui::Ui, task: &RegisteredTask, lang: Lang) {
+        match &task.progress_bar {
+            Some(progress_bar) => progress_bar.render(ui, lang),
+            None => {
+                ui.spinner();
+            }
+        }
+    }
+
+    // progress of a single task for the windows of its owner
+    pub fn render_progress(&self, ui: &mut egui::Ui, key: &TaskKey, lang: Lang) {
+        if let Some(task) = self.get_tasks().iter().find(|task| &task.key == key) {
+            Self::render_progress_bar(ui, task, lang);
+        }
+    }
+
+    // a clickable spinner shown while any task is running, true when clicked
+    pub fn render_spinner(&self, ui: &mut egui::Ui, lang: Lang) -> bool {
+        let tasks = self.get_tasks();
+        if tasks.is_empty() {
+            return false;
+        }
+        let label = LangMessage::BackgroundTasks(tasks.len()).to_string(lang);
+        let response = ui
+            .add(egui::Spinner::new())
+            .interact(egui::Sense::click())
+            .on_hover_text(&label);
+        response.widget_info(|| egui::WidgetInfo::labeled(egui::WidgetType::Button, true, &label));
+        response.clicked()
+    }
+
+    pub fn render_task_list(&self, ctx: &egui::Context, lang: Lang, open: &mut bool) {
+        let tasks = self.get_tasks();
+        egui::Window::new(LangMessage::BackgroundTasks(tasks.len()).to_string(lang))
+            .id(egui::Id::new("task_list"))
+            .collapsible(false)
+            .open(open)
+            .show(ctx, |ui| {
+                if tasks.is_empty() {
+                    ui.label(LangMessage::NoBackgroundTasks.to_string(lang));
+                }
+                for (i, task) in tasks.iter().enumerate() {
+                    if i > 0 {
+                        ui.separator();
+                    }
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(task.key.get_message().to_string(lang)).strong());
+                        if icon_button(ui, "✖", LangMessage::Cancel, lang, true).clicked() {
+                            task.handle.cancel();
+                        }
+                    });
+                    Self::render_progress_bar(ui, task, lang);
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::background_task::BackgroundTaskResult;
+
+    #[test]
+    fn test_cancel_registered_task() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let registry = TaskRegistry::new();
+        let key = TaskKey::SyncInstance("test".to_string());
+        let task =
+            BackgroundTask::with_callback(std::future::pending::<()>(), &runtime, Box::new(|| {}));
+        registry.register(key.clone(), &task, None);
+        assert!(registry.is_running(&key));
+        assert!(!registry.is_running(&TaskKey::SyncInstance("other".to_string())));
+
+        // what the cancel button of the task list does
+        registry.get_tasks()[0].handle.cancel();
+        assert!(!registry.is_running(&key));
+        for _ in 0..100 {
+            if task.has_result() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert!(matches!(
+            task.take_result(),
+            BackgroundTaskResult::Cancelled
+        ));
+
+        // dropping a task removes it too
+        let task =
+            BackgroundTask::with_callback(std::future::pending::<()>(), &runtime, Box::new(|| {}));
+        registry.register(key.clone(), &task, None);
+        drop(task);
+        assert!(!registry.is_running(&key));
+    }
+}
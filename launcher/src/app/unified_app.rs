@@ -79,6 +79,10 @@ impl eframe::App for UnifiedApp {
             AppState::Launcher => {
                 if let Some(launcher_app) = &mut self.launcher_app {
                     launcher_app.update(ctx, frame);
+
+                    if launcher_app.update_requested() {
+                        self.transition_to_updater(ctx);
+                    }
                 }
             }
         }
@@ -181,4 +185,30 @@ impl UnifiedApp {
 
         self.initialize_launcher_app(ctx);
     }
+
+    fn transition_to_updater(&mut self, ctx: &egui::Context) {
+        self.app_state = AppState::Updating;
+        if let Some(launcher_app) = self.launcher_app.take() {
+            self.config = Some(launcher_app.into_config());
+        }
+        self.launch_flag = false;
+
+        let current_position = ctx
+            .input(|input| input.viewport().outer_rect)
+            .map(|rect| rect.min);
+
+        if let Some(position) = current_position {
+            let delta = LAUNCHER_APP_SIZE - UPDATE_APP_SIZE;
+            let position = egui::Pos2::new(position.x + delta.x / 2.0, position.y + delta.y / 2.0);
+            ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(position));
+        }
+        ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(UPDATE_APP_SIZE));
+
+        ctx.send_viewport_cmd(egui::ViewportCommand::Title(format!(
+            "{} Updater",
+            build_config::get_launcher_name()
+        )));
+
+        self.initialize_update_app(ctx);
+    }
 }
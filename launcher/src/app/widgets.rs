@@ -0,0 +1,111 @@
+use crate::lang::{Lang, LangMessage};
+
+// icon-only buttons must go through this to get a hover text and a screen reader label
+pub fn icon_button(
+    ui: &mut egui::Ui,
+    icon: &str,
+    label: LangMessage,
+    lang: Lang,
+    enabled: bool,
+) -> egui::Response {
+    let label = label.to_string(lang);
+    let response = ui
+        .add_enabled(enabled, egui::Button::new(icon))
+        .on_hover_text(&label)
+        .on_disabled_hover_text(&label);
+    response.widget_info(|| egui::WidgetInfo::labeled(egui::WidgetType::Button, enabled, &label));
+    response
+}
+
+// A row with a widget filling the free space followed by widgets on the right.
+// Unlike a right-to-left layout, widgets are added in visual order, so tab
+// focus follows it. The width of the right part is taken from the previous frame.
+pub struct TrailingRow {
+    id: egui::Id,
+    trailing_width: Option<f32>,
+}
+
+impl TrailingRow {
+    pub fn new(ui: &egui::Ui, id_salt: &str) -> Self {
+        let id = ui.id().with(id_salt);
+        let trailing_width = ui.ctx().data(|data| data.get_temp::<f32>(id));
+        Self { id, trailing_width }
+    }
+
+    pub fn show_fill<R>(
+        &self,
+        ui: &mut egui::Ui,
+        add_contents: impl FnOnce(&mut egui::Ui) -> R,
+    ) -> R {
+        let trailing_width = self.trailing_width.unwrap_or_default();
+        let width = (ui.available_width() - trailing_width - ui.spacing().item_spacing.x).max(0.0);
+        let size = egui::vec2(width, ui.spacing().interact_size.y);
+        ui.allocate_ui(size, add_contents).inner
+    }
+
+    pub fn show_trailing<R>(
+        self,
+        ui: &mut egui::Ui,
+        add_contents: impl FnOnce(&mut egui::Ui) -> R,
+    ) -> R {
+        let inner = ui.horizontal(add_contents);
+        let width = inner.response.rect.width();
+        if self
+            .trailing_width
+            .is_none_or(|trailing_width| (trailing_width - width).abs() > 0.5)
+        {
+            ui.ctx().data_mut(|data| data.insert_temp(self.id, width));
+            ui.ctx().request_discard("Trailing row width changed");
+        }
+        inner.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui::accesskit::Role;
+
+    #[test]
+    fn test_icon_button_accessibility() {
+        let ctx = egui::Context::default();
+        ctx.enable_accesskit();
+        let output = ctx.run(Default::default(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                icon_button(ui, "⚙", LangMessage::Settings, Lang::English, true);
+            });
+        });
+
+        let update = output.platform_output.accesskit_update.unwrap();
+        assert!(
+            update.nodes.iter().any(|(_, node)| {
+                node.role() == Role::Button && node.label() == Some("Settings")
+            })
+        );
+    }
+
+    #[test]
+    fn test_icon_buttons_use_helper() {
+        let sources = [
+            include_str!("launcher_app.rs"),
+            include_str!("settings.rs"),
+            include_str!("new_instance_state.rs"),
+            include_str!("auth_state.rs"),
+            include_str!("instance_sync_state.rs"),
+            include_str!("java_state.rs"),
+            include_str!("launch_state.rs"),
+        ];
+        for source in sources {
+            for pattern in ["button(\"", "Button::new(\""] {
+                for (index, _) in source.match_indices(pattern) {
+                    let text = &source[index + pattern.len()..];
+                    let text = &text[..text.find('"').unwrap()];
+                    assert!(
+                        text.chars().any(char::is_alphanumeric),
+                        "icon button {text:?} must be created with icon_button"
+                    );
+                }
+            }
+        }
+    }
+}
@@ -1,8 +0,0 @@
-pub mod auth_flow;
-pub mod auth_storage;
-pub mod base;
-mod elyby;
-mod microsoft;
-mod offline;
-mod telegram;
-pub mod user_info;
@@ -1,8 +0,0 @@
-use crate::lang::Lang;
-
-pub const DEFAULT_LANG: Lang = Lang::English;
-
-pub const XMX_MIN: u64 = 512;
-pub const XMX_MAX: u64 = 65536;
-pub const XMX_STEP: u64 = 512;
-pub const XMX_DEFAULT: u64 = 4096;
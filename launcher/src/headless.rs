@@ -0,0 +1,393 @@
+use std::fmt;
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use anyhow::{Context as _, anyhow, bail};
+use log::{error, warn};
+use shared::adaptive_download::DownloadHealth;
+use shared::java;
+use shared::paths::{get_java_dir, get_running_lock_path};
+use shared::progress::{ProgressBar, TerminalProgressBar, Unit};
+use shared::utils::is_connect_error;
+use shared::version::extra_version_metadata::AuthBackend;
+use shared::version::version_manifest::VersionManifest;
+use tokio::sync::mpsc;
+
+use crate::auth::auth_flow::{AuthMessageProvider, perform_auth};
+use crate::auth::auth_storage::AuthStorage;
+use crate::auth::base::get_auth_provider;
+use crate::auth::user_info::AuthData;
+use crate::config::build_config;
+use crate::config::runtime_config::{AuthProfile, Config};
+use crate::lang::{Lang, LangMessage};
+use crate::launcher::running_lock::remove_running_lock;
+use crate::launcher::update::is_launcher_version_supported;
+use crate::launcher::{launch, playtime};
+use crate::utils;
+use crate::version::complete_version_metadata::CompleteVersionMetadata;
+use crate::version::dedup;
+use crate::version::instance_storage::{InstanceStatus, InstanceStorage};
+use crate::version::sync::{self, DeleteExtraRequest};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum HeadlessCommand {
+    Sync,
+    Launch,
+}
+
+impl HeadlessCommand {
+    pub const NAMES: [&str; 2] = ["sync", "launch"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "sync" => Some(HeadlessCommand::Sync),
+            "launch" => Some(HeadlessCommand::Launch),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for HeadlessCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HeadlessCommand::Sync => write!(f, "sync"),
+            HeadlessCommand::Launch => write!(f, "launch"),
+        }
+    }
+}
+
+// every message on its own line, so the output is readable when piped
+struct HeadlessProgressBar {
+    bar: TerminalProgressBar,
+    lang: Lang,
+}
+
+impl HeadlessProgressBar {
+    fn new(lang: Lang) -> Self {
+        Self {
+            bar: TerminalProgressBar::new(),
+            lang,
+        }
+    }
+}
+
+impl ProgressBar<LangMessage> for HeadlessProgressBar {
+    fn set_message(&self, message: LangMessage) {
+        let message = message.to_string(self.lang);
+        self.bar.println(&message);
+        self.bar.set_message(message.as_str());
+    }
+
+    fn set_length(&self, length: u64) {
+        self.bar.set_length(length);
+    }
+
+    fn inc(&self, amount: u64) {
+        self.bar.inc(amount);
+    }
+
+    fn finish(&self) {
+        self.bar.finish();
+    }
+
+    fn reset(&self) {
+        self.bar.reset();
+    }
+
+    fn set_unit(&self, unit: Unit) {
+        self.bar.set_unit(unit);
+    }
+}
+
+async fn sync_instance(
+    config: &Config,
+    metadata: &CompleteVersionMetadata,
+    always_allow_delete_extra: bool,
+) -> anyhow::Result<String> {
+    let launcher_dir = config.get_launcher_dir();
+    let progress_bar: Arc<dyn ProgressBar<LangMessage> + Send + Sync> =
+        Arc::new(HeadlessProgressBar::new(config.lang));
+
+    sync::ensure_not_running(&launcher_dir, metadata.get_name())?;
+    progress_bar.set_message(LangMessage::CheckingFiles);
+    let plan = sync::plan_sync(
+        metadata,
+        false,
+        &launcher_dir,
+        &config.get_assets_dir(),
+        progress_bar.clone(),
+        config.get_hash_limits(),
+        &config.mirror_rankings,
+    )
+    .await?;
+
+    // nobody can confirm the deletion, so extra files are kept unless the instance allows it
+    let (delete_extra_sender, mut delete_extra_receiver) =
+        mpsc::unbounded_channel::<DeleteExtraRequest>();
+    tokio::spawn(async move {
+        while let Some(request) = delete_extra_receiver.recv().await {
+            let _ = request.response.send(always_allow_delete_extra);
+        }
+    });
+
+    let content_hash = sync::execute_sync(
+        metadata,
+        plan,
+        &launcher_dir,
+        progress_bar.clone(),
+        delete_extra_sender,
+        &DownloadHealth::new(),
+        config.get_sync_settings(),
+    )
+    .await?;
+
+    if config.deduplicate_files {
+        progress_bar.set_message(LangMessage::DeduplicatingFiles);
+        if let Err(e) = dedup::deduplicate_files(&launcher_dir, config.get_hash_limits()).await {
+            warn!("Failed to deduplicate files:\n{e:?}");
+        }
+    }
+    progress_bar.finish();
+    Ok(content_hash)
+}
+
+// the saved java path if it still works, otherwise a found or downloaded one
+async fn prepare_java(
+    config: &mut Config,
+    metadata: &CompleteVersionMetadata,
+) -> anyhow::Result<()> {
+    let instance_name = metadata.get_name().to_string();
+    let java_version = metadata.get_java_version();
+    if let Some(path) = config.java_paths.get(&instance_name)
+        && java::check_java(&java_version, path).await
+    {
+        return Ok(());
+    }
+
+    let java_dir = get_java_dir(&config.get_launcher_dir());
+    let java_path = match java::get_java(&java_version, &java_dir).await {
+        Some(installation) => installation.path,
+        None => {
+            let progress_bar = Arc::new(HeadlessProgressBar::new(config.lang));
+            progress_bar.set_unit(Unit {
+                name: "MB".to_string(),
+                size: 1024 * 1024,
+            });
+            progress_bar.set_message(LangMessage::DownloadingJava);
+            java::download_java(&java_version, &java_dir, progress_bar)
+                .await?
+                .path
+        }
+    };
+    if java::is_java_32_bit(&java_path).await
+        && let Some(xmx) = config.xmx.get_mut(&instance_name)
+    {
+        *xmx = utils::clamp_xmx(xmx, true);
+    }
+    config.java_paths.insert(instance_name, java_path);
+    Ok(())
+}
+
+fn print_auth_message(message: &LangMessage, lang: Lang) {
+    let (LangMessage::AuthMessage { url } | LangMessage::DeviceAuthMessage { url, .. }) = message
+    else {
+        return;
+    };
+    println!("{}\n{url}", message.to_string(lang));
+}
+
+// refreshes the saved account of the instance, or authorizes a new one with the printed link.
+// The second value is false if the auth server is unreachable and the saved data is used as is
+async fn get_auth_data(
+    config: &mut Config,
+    metadata: &CompleteVersionMetadata,
+) -> anyhow::Result<(AuthData, bool)> {
+    let mut auth_storage = AuthStorage::load(config);
+    let instance_auth_backend = metadata.get_auth_backend().cloned();
+    let auth_profile = config
+        .get_selected_auth_profile()
+        .cloned()
+        .filter(|profile| {
+            instance_auth_backend
+                .as_ref()
+                .is_none_or(|backend| backend.get_id() == profile.auth_backend_id)
+        });
+    let saved_auth_data = auth_profile
+        .as_ref()
+        .and_then(|profile| auth_storage.get_by_id(&profile.auth_backend_id, &profile.username))
+        .map(|entry| entry.auth_data);
+
+    let auth_backend = match (instance_auth_backend, &auth_profile) {
+        (Some(backend), _) => backend,
+        (None, Some(profile)) => AuthBackend::from_id(&profile.auth_backend_id),
+        (None, None) => bail!(
+            "No account is selected for {}, add one in the launcher",
+            metadata.get_name()
+        ),
+    };
+    if saved_auth_data.is_none() && auth_backend == AuthBackend::Offline {
+        bail!("Offline accounts can only be added in the launcher");
+    }
+
+    let lang = config.lang;
+    let (change_sender, mut change_receiver) = mpsc::unbounded_channel();
+    let auth_message_provider = Arc::new(AuthMessageProvider::new(move || {
+        let _ = change_sender.send(());
+    }));
+    let message_printer = {
+        let auth_message_provider = auth_message_provider.clone();
+        tokio::spawn(async move {
+            let mut last_message = None;
+            while change_receiver.recv().await.is_some() {
+                let message = auth_message_provider.get_message().await;
+                if message != last_message {
+                    if let Some(message) = &message {
+                        print_auth_message(message, lang);
+                    }
+                    last_message = message;
+                }
+            }
+        })
+    };
+    let result = perform_auth(
+        saved_auth_data.clone(),
+        get_auth_provider(&auth_backend),
+        auth_message_provider,
+    )
+    .await;
+    message_printer.abort();
+
+    let auth_data = match (result, saved_auth_data) {
+        (Ok(auth_data), _) => auth_data,
+        (Err(e), Some(saved_auth_data)) if is_connect_error(&e) => {
+            warn!("Auth server is unreachable, launching in offline mode:\n{e:?}");
+            return Ok((saved_auth_data, false));
+        }
+        (Err(e), _) => return Err(e.context("Failed to authorize")),
+    };
+    auth_storage.insert(config, &auth_backend, auth_data.clone());
+    // set_selected_auth_profile would save the config with the selected instance of this run
+    config.auth_profiles.insert(
+        metadata.get_name().to_string(),
+        AuthProfile {
+            auth_backend_id: auth_backend.get_id(),
+            username: auth_data.user_info.username.clone(),
+        },
+    );
+    Ok((auth_data, true))
+}
+
+// saves the java path and the account without the selected instance of this run
+fn save_config(config: &mut Config, selected_instance_name: &Option<String>) {
+    let instance_name = std::mem::replace(
+        &mut config.selected_instance_name,
+        selected_instance_name.clone(),
+    );
+    config.save_now();
+    config.selected_instance_name = instance_name;
+}
+
+async fn run_command(
+    mut config: Config,
+    command: HeadlessCommand,
+    instance_name: &str,
+) -> anyhow::Result<ExitCode> {
+    let launcher_dir = config.get_launcher_dir();
+    let mut instance_storage = InstanceStorage::load(&config).await;
+
+    let manifest_url = config.get_effective_version_manifest_url().to_string();
+    let public_key = config.get_manifest_public_key(&manifest_url);
+    let online = match VersionManifest::fetch_verified(&manifest_url, public_key.as_deref()).await {
+        Ok(manifest) => {
+            instance_storage.set_remote_manifest(manifest, &manifest_url);
+            true
+        }
+        Err(e) if is_connect_error(&e) => {
+            warn!("Failed to fetch the version manifest, using local instances:\n{e:?}");
+            println!("{}", LangMessage::OfflineMode.to_string(config.lang));
+            false
+        }
+        Err(e) => return Err(e.context("Failed to fetch the version manifest")),
+    };
+
+    let instance = instance_storage
+        .get_instance(instance_name)
+        .ok_or_else(|| anyhow!("Instance {instance_name} not found"))?;
+    // the account and the per-instance settings are looked up by the selected instance,
+    // the selection in the launcher stays as it was
+    let selected_instance_name = config
+        .selected_instance_name
+        .replace(instance_name.to_string());
+
+    let public_key = instance
+        .manifest_url
+        .as_deref()
+        .and_then(|url| config.get_manifest_public_key(url));
+    let metadata = if online {
+        CompleteVersionMetadata::read_or_download(
+            &instance.version_info,
+            &launcher_dir,
+            public_key.as_deref(),
+        )
+        .await
+    } else {
+        CompleteVersionMetadata::read_local(
+            &instance.version_info,
+            &launcher_dir,
+            public_key.as_deref(),
+        )
+        .await
+    }
+    .context("Failed to get the instance metadata")?;
+    if let Some(min_launcher_version) = metadata.get_min_launcher_version()
+        && !is_launcher_version_supported(
+            build_config::get_version().as_deref(),
+            min_launcher_version,
+        )
+    {
+        bail!("{instance_name} requires launcher version {min_launcher_version} or newer");
+    }
+
+    if command == HeadlessCommand::Sync || instance.status != InstanceStatus::UpToDate {
+        let content_hash =
+            sync_instance(&config, &metadata, instance.always_allow_delete_extra).await?;
+        instance_storage
+            .mark_downloaded(&config, instance_name, content_hash)
+            .await;
+        println!("{}", LangMessage::InstanceUpToDate.to_string(config.lang));
+    }
+    if command == HeadlessCommand::Sync {
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    prepare_java(&mut config, &metadata).await?;
+    let auth_result = get_auth_data(&mut config, &metadata).await;
+    save_config(&mut config, &selected_instance_name);
+    let (auth_data, auth_online) = auth_result?;
+    let mut child = launch::launch(&metadata, &config, &auth_data, auth_online).await?;
+    playtime::start_session(&launcher_dir, instance_name, SystemTime::now());
+    println!("{}", LangMessage::Running.to_string(config.lang));
+
+    let exit_status = child.wait().await?;
+    remove_running_lock(&get_running_lock_path(&launcher_dir, instance_name));
+    playtime::end_session(&launcher_dir, instance_name, SystemTime::now());
+    if exit_status.success() {
+        Ok(ExitCode::SUCCESS)
+    } else {
+        error!("Minecraft exited with {exit_status}");
+        Ok(ExitCode::FAILURE)
+    }
+}
+
+// syncs or launches an instance without any windows, the exit code is nonzero on failure
+pub fn run(config: Config, command: HeadlessCommand, instance_name: &str) -> ExitCode {
+    let runtime = tokio::runtime::Runtime::new().expect("Failed to create the tokio runtime");
+    match runtime.block_on(run_command(config, command, instance_name)) {
+        Ok(exit_code) => exit_code,
+        Err(e) => {
+            error!("Headless {command} of {instance_name} failed:\n{e:?}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -1,3 +0,0 @@
-pub mod compat;
-pub mod launch;
-pub mod update;
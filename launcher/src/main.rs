@@ -1,23 +1,23 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod app;
-mod auth;
-mod config;
-mod constants;
-mod lang;
-mod launcher;
+mod headless;
 mod update_app;
 mod utils;
-mod vendor;
-mod version;
+
+use launcher_core::{auth, config, constants, lang, launcher, version};
+
+use std::process::ExitCode;
 
 use clap::{Arg, ArgAction, Command};
 use config::runtime_config::{Config, get_logs_path};
+use headless::HeadlessCommand;
+use log::warn;
 use utils::set_sigint_handler;
 
 use shared::logs::setup_logger;
 
-fn main() {
+fn main() -> ExitCode {
     unsafe {
         std::env::set_var("RUST_LIB_BACKTRACE", "1");
         // for some reason this is needed on macOS for minecraft process not to crash with
@@ -37,8 +37,55 @@ fn main() {
                 .short('l')
                 .action(ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("profile")
+                .help("Launch the game with the given launch profile")
+                .long("profile")
+                .short('p')
+                .value_name("NAME"),
+        )
+        .arg(
+            Arg::new("headless")
+                .help(
+                    "Sync or launch an instance without the GUI, e.g. --headless launch <INSTANCE>",
+                )
+                .long("headless")
+                .action(ArgAction::SetTrue)
+                .requires_all(["command", "instance"]),
+        )
+        .arg(
+            Arg::new("command")
+                .help("What to do in headless mode")
+                .value_name("COMMAND")
+                .value_parser(HeadlessCommand::NAMES)
+                .requires("headless"),
+        )
+        .arg(
+            Arg::new("instance")
+                .help("The instance name for headless mode")
+                .value_name("INSTANCE")
+                .requires("headless"),
+        )
         .get_matches();
 
-    let config = Config::load();
-    app::unified_app::run_gui(config, matches.get_flag("launch"));
+    let mut config = Config::load();
+    if matches.get_flag("headless") {
+        launcher_core::utils::windows::attach_console();
+        let command = matches
+            .get_one::<String>("command")
+            .and_then(|name| HeadlessCommand::from_name(name))
+            .unwrap();
+        let instance_name = matches.get_one::<String>("instance").unwrap();
+        return headless::run(config, command, instance_name);
+    }
+    let mut launch = matches.get_flag("launch");
+    if let Some(profile) = matches.get_one::<String>("profile") {
+        if config.select_launch_profile(profile) {
+            launch = true;
+        } else {
+            warn!("Launch profile {profile} not found");
+        }
+    }
+    app::unified_app::run_gui(config, launch);
+    ExitCode::SUCCESS
 }
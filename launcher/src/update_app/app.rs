@@ -9,6 +9,7 @@ use tokio::runtime::Runtime;
 
 use crate::app::progress_bar::GuiProgressBar;
 use crate::config::build_config;
+use crate::config::runtime_config::write_unsaved_config;
 use crate::lang::Lang;
 use crate::lang::LangMessage;
 use crate::launcher::update::download_new_launcher;
@@ -74,6 +75,7 @@ impl eframe::App for UpdateApp {
 
     fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
         if self.exit_on_close {
+            write_unsaved_config();
             std::process::exit(0);
         }
     }
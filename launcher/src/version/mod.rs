@@ -1,5 +0,0 @@
-pub mod complete_version_metadata;
-pub mod instance_storage;
-pub mod os;
-pub mod overrides;
-pub mod sync;
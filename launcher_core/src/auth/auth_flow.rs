@@ -0,0 +1,323 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::sync::{Mutex, mpsc};
+use tokio_util::sync::CancellationToken;
+
+use crate::lang::LangMessage;
+
+use super::base::{AuthProvider, AuthResultData, AuthState};
+use super::offline::OfflineAccount;
+use super::user_info::AuthData;
+
+struct AuthMessageState {
+    auth_message: Option<LangMessage>,
+    expires_at: Option<Instant>,
+    connection_lost: bool,
+    need_offline_account: u32,
+}
+
+pub struct AuthMessageProvider {
+    state: Arc<Mutex<AuthMessageState>>,
+    offline_account_sender: mpsc::UnboundedSender<OfflineAccount>,
+    offline_account_receiver: Arc<Mutex<mpsc::UnboundedReceiver<OfflineAccount>>>,
+    // lets the frontend redraw when the auth message changes
+    on_change: Arc<dyn Fn() + Send + Sync>,
+    // cancelled with the task running the auth, checked by the polling providers
+    cancellation_token: CancellationToken,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum AuthError {
+    #[error("Auth loop exceeded max iterations")]
+    InfiniteAuthLoop,
+    #[error("Authentication was cancelled")]
+    Cancelled,
+    #[error("The account has to be authorized again by the user")]
+    UserRequired,
+}
+
+impl AuthMessageProvider {
+    pub fn new(on_change: impl Fn() + Send + Sync + 'static) -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        Self {
+            state: Arc::new(Mutex::new(AuthMessageState {
+                auth_message: None,
+                expires_at: None,
+                connection_lost: false,
+                need_offline_account: 0,
+            })),
+            offline_account_sender: sender,
+            offline_account_receiver: Arc::new(Mutex::new(receiver)),
+            on_change: Arc::new(on_change),
+            cancellation_token: CancellationToken::new(),
+        }
+    }
+
+    pub fn get_cancellation_token(&self) -> CancellationToken {
+        self.cancellation_token.clone()
+    }
+
+    pub async fn cancelled(&self) {
+        self.cancellation_token.cancelled().await;
+    }
+
+    async fn set_message_inner(&self, message: LangMessage, expires_in: Option<Duration>) {
+        if matches!(
+            message,
+            LangMessage::AuthMessage { .. } | LangMessage::DeviceAuthMessage { .. }
+        ) {
+            let mut state = self.state.lock().await;
+            state.auth_message = Some(message);
+            state.expires_at = expires_in.map(|expires_in| Instant::now() + expires_in);
+            (self.on_change)();
+        } else {
+            panic!("Expected AuthMessage, got {message:?}");
+        }
+    }
+
+    pub async fn set_message(&self, message: LangMessage) {
+        self.set_message_inner(message, None).await;
+    }
+
+    pub async fn set_message_with_expiry(&self, message: LangMessage, expires_in: Duration) {
+        self.set_message_inner(message, Some(expires_in)).await;
+    }
+
+    pub async fn get_message(&self) -> Option<LangMessage> {
+        let state = self.state.lock().await;
+        state.auth_message.clone()
+    }
+
+    pub async fn get_expires_at(&self) -> Option<Instant> {
+        let state = self.state.lock().await;
+        state.expires_at
+    }
+
+    pub async fn clear(&self) {
+        let mut state = self.state.lock().await;
+        state.auth_message = None;
+        state.expires_at = None;
+        state.connection_lost = false;
+        (self.on_change)();
+    }
+
+    pub async fn set_connection_lost(&self, connection_lost: bool) {
+        let mut state = self.state.lock().await;
+        if state.connection_lost != connection_lost {
+            state.connection_lost = connection_lost;
+            (self.on_change)();
+        }
+    }
+
+    pub async fn is_connection_lost(&self) -> bool {
+        let state = self.state.lock().await;
+        state.connection_lost
+    }
+
+    pub async fn request_offline_account(&self) -> OfflineAccount {
+        {
+            let mut state = self.state.lock().await;
+            state.need_offline_account += 1;
+        }
+
+        self.offline_account_receiver
+            .lock()
+            .await
+            .recv()
+            .await
+            .unwrap()
+    }
+
+    pub async fn need_offline_account(&self) -> bool {
+        let state = self.state.lock().await;
+        state.need_offline_account > 0
+    }
+
+    pub async fn set_offline_account(&self, account: OfflineAccount) {
+        let mut state = self.state.lock().await;
+        state.need_offline_account -= 1;
+        self.offline_account_sender.send(account).unwrap();
+    }
+}
+
+pub async fn perform_auth(
+    auth_data: Option<AuthData>,
+    auth_provider: Box<dyn AuthProvider + Send + Sync>,
+    auth_message_provider: Arc<AuthMessageProvider>,
+) -> anyhow::Result<AuthData> {
+    let mut auth_result_data = auth_data.map(|data| AuthResultData {
+        access_token: data.access_token,
+        refresh_token: data.refresh_token,
+    });
+    let mut auth_state = auth_result_data
+        .clone()
+        .map_or(AuthState::Auth, AuthState::UserInfo);
+
+    for _ in 0..10 {
+        match auth_state {
+            AuthState::Auth => {
+                auth_state = auth_provider
+                    .authenticate(auth_message_provider.as_ref())
+                    .await?;
+            }
+
+            AuthState::Refresh => {
+                let refresh_token = auth_result_data
+                    .as_ref()
+                    .and_then(|data| data.refresh_token.clone());
+                auth_state = match refresh_token {
+                    Some(refresh_token) => auth_provider.refresh(refresh_token).await?,
+                    None => AuthState::Auth,
+                };
+            }
+
+            AuthState::UserInfo(data) => {
+                auth_result_data = Some(data.clone());
+                auth_state = auth_provider
+                    .get_user_info(&data.access_token)
+                    .await
+                    .or_else(|e| {
+                        let is_client_error = e
+                            .downcast_ref::<reqwest::Error>()
+                            .and_then(|re| re.status())
+                            .map(|status| status.is_client_error())
+                            .unwrap_or(false);
+                        if is_client_error {
+                            Ok(AuthState::Refresh)
+                        } else {
+                            Err(e)
+                        }
+                    })?;
+            }
+
+            AuthState::Success(info) => {
+                let auth_result_data = auth_result_data.unwrap();
+                return Ok(AuthData {
+                    access_token: auth_result_data.access_token,
+                    refresh_token: auth_result_data.refresh_token,
+                    user_info: info,
+                    last_used: None,
+                });
+            }
+        }
+    }
+
+    Err(AuthError::InfiniteAuthLoop.into())
+}
+
+// renews the tokens of a saved account without the user, fails instead of asking them to log in
+pub async fn refresh_auth(
+    auth_data: AuthData,
+    auth_provider: Box<dyn AuthProvider + Send + Sync>,
+    timeout: Duration,
+) -> anyhow::Result<AuthData> {
+    let user_required = CancellationToken::new();
+    let auth_message_provider = Arc::new(AuthMessageProvider::new({
+        let user_required = user_required.clone();
+        move || user_required.cancel()
+    }));
+    tokio::select! {
+        result = tokio::time::timeout(
+            timeout,
+            perform_auth(Some(auth_data), auth_provider, auth_message_provider),
+        ) => result?,
+        _ = user_required.cancelled() => Err(AuthError::UserRequired.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+
+    use super::*;
+    use crate::auth::user_info::UserInfo;
+
+    // the access token "expired" is rejected, a refresh gives "fresh" if the refresh token works
+    struct MockProvider {
+        refresh_works: bool,
+    }
+
+    #[async_trait]
+    impl AuthProvider for MockProvider {
+        async fn authenticate(
+            &self,
+            message_provider: &AuthMessageProvider,
+        ) -> anyhow::Result<AuthState> {
+            message_provider
+                .set_message(LangMessage::AuthMessage {
+                    url: "https://example.com".to_string(),
+                })
+                .await;
+            std::future::pending().await
+        }
+
+        async fn refresh(&self, refresh_token: String) -> anyhow::Result<AuthState> {
+            Ok(if self.refresh_works {
+                AuthState::UserInfo(AuthResultData {
+                    access_token: "fresh".to_string(),
+                    refresh_token: Some(refresh_token),
+                })
+            } else {
+                AuthState::Auth
+            })
+        }
+
+        async fn get_user_info(&self, token: &str) -> anyhow::Result<AuthState> {
+            Ok(match token {
+                "fresh" => AuthState::Success(UserInfo {
+                    uuid: "uuid".to_string(),
+                    username: "Steve".to_string(),
+                }),
+                _ => AuthState::Refresh,
+            })
+        }
+
+        fn get_auth_url(&self) -> Option<String> {
+            None
+        }
+
+        fn get_name(&self) -> String {
+            "Mock".to_string()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_refresh_auth() {
+        let auth_data = AuthData {
+            access_token: "expired".to_string(),
+            refresh_token: Some("refresh".to_string()),
+            user_info: UserInfo {
+                uuid: "uuid".to_string(),
+                username: "Steve".to_string(),
+            },
+            last_used: None,
+        };
+        let timeout = Duration::from_secs(5);
+
+        let refreshed = refresh_auth(
+            auth_data.clone(),
+            Box::new(MockProvider {
+                refresh_works: true,
+            }),
+            timeout,
+        )
+        .await
+        .unwrap();
+        assert_eq!(refreshed.access_token, "fresh");
+
+        // a new login would wait for the user
+        let result = refresh_auth(
+            auth_data,
+            Box::new(MockProvider {
+                refresh_works: false,
+            }),
+            timeout,
+        )
+        .await;
+        assert!(matches!(
+            result.unwrap_err().downcast_ref(),
+            Some(AuthError::UserRequired)
+        ));
+    }
+}
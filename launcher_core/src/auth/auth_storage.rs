@@ -0,0 +1,356 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::warn;
+use shared::paths::{get_auth_data_path, get_encrypted_auth_data_path};
+use shared::version::extra_version_metadata::AuthBackend;
+
+use crate::config::runtime_config::Config;
+
+use super::encryption;
+use super::user_info::AuthData;
+
+type StoredAuthData = HashMap<String, HashMap<String, AuthData>>; // backend id -> username -> auth data
+
+fn read_plaintext(path: &Path) -> Option<StoredAuthData> {
+    let data = std::fs::read_to_string(path).ok()?;
+    Some(serde_json::from_str(&data).unwrap_or_default())
+}
+
+// the next save would overwrite it, and the key may come back, e.g. with a restored keyring
+fn back_up_unreadable(path: &Path) -> std::io::Result<()> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_secs())
+        .unwrap_or(0);
+    let mut backup_name = path.file_name().unwrap_or_default().to_os_string();
+    backup_name.push(format!(".{timestamp}.bak"));
+    let backup_path = path.with_file_name(backup_name);
+    std::fs::rename(path, &backup_path)?;
+    warn!(
+        "Moved the saved accounts that can't be read to {}",
+        backup_path.display()
+    );
+    Ok(())
+}
+
+// accounts that can't be decrypted are treated as missing and their file is moved aside.
+// Err if it couldn't be moved, then the file must not be written
+fn read_encrypted(path: &Path) -> std::io::Result<Option<StoredAuthData>> {
+    let Ok(encrypted) = std::fs::read(path) else {
+        return Ok(None);
+    };
+    let data = encryption::decrypt_with_stored_key(&encrypted)
+        .inspect_err(|e| warn!("Failed to unlock the saved accounts: {e}"))
+        .ok()
+        .and_then(|data| serde_json::from_slice(&data).ok());
+    match data {
+        Some(data) => Ok(Some(data)),
+        None => back_up_unreadable(path).map(|()| None),
+    }
+}
+
+#[derive(PartialEq)]
+pub enum AuthDataSource {
+    Persistent,
+    Runtime,
+}
+
+pub struct StorageEntry {
+    pub auth_data: AuthData,
+    pub source: AuthDataSource,
+}
+
+pub struct AuthStorage {
+    // saved auth data from previous launches, may not be up to date
+    persistent_storage: StoredAuthData,
+    // auth data from current launch, up to date
+    runtime_storage: StoredAuthData,
+    // the backend of the last save, see apply_backend
+    encrypted: bool,
+    // the encrypted file couldn't be read or moved aside, so it's never written
+    keep_encrypted_file: bool,
+}
+
+impl AuthStorage {
+    pub fn load(config: &Config) -> Self {
+        let launcher_dir = config.get_launcher_dir();
+        let plaintext = read_plaintext(&get_auth_data_path(&launcher_dir));
+        let (encrypted, keep_encrypted_file) =
+            match read_encrypted(&get_encrypted_auth_data_path(&launcher_dir)) {
+                Ok(encrypted) => (encrypted, false),
+                Err(e) => {
+                    warn!("Failed to back up the saved accounts, keeping them as is: {e}");
+                    (None, true)
+                }
+            };
+        let (selected, other) = if config.encrypt_auth_data {
+            (encrypted, plaintext)
+        } else {
+            (plaintext, encrypted)
+        };
+
+        // accounts saved with the other backend are moved to the selected one
+        let migrate = other.is_some();
+        let mut persistent_storage = other.unwrap_or_default();
+        for (id, user_map) in selected.unwrap_or_default() {
+            persistent_storage.entry(id).or_default().extend(user_map);
+        }
+
+        let mut storage = Self {
+            persistent_storage,
+            runtime_storage: HashMap::new(),
+            encrypted: config.encrypt_auth_data,
+            keep_encrypted_file,
+        };
+        if migrate {
+            storage.save(config);
+        }
+        storage
+    }
+
+    // moves the saved accounts after the setting was changed
+    pub fn apply_backend(&mut self, config: &Config) {
+        if self.encrypted != config.encrypt_auth_data {
+            self.save(config);
+        }
+    }
+
+    pub fn get_by_id(&self, id: &str, username: &str) -> Option<StorageEntry> {
+        if let Some(user_map) = self.runtime_storage.get(id)
+            && let Some(auth_data) = user_map.get(username)
+        {
+            Some(StorageEntry {
+                auth_data: auth_data.clone(),
+                source: AuthDataSource::Runtime,
+            })
+        } else if let Some(user_map) = self.persistent_storage.get(id)
+            && let Some(auth_data) = user_map.get(username)
+        {
+            Some(StorageEntry {
+                auth_data: auth_data.clone(),
+                source: AuthDataSource::Persistent,
+            })
+        } else {
+            None
+        }
+    }
+
+    pub fn get_id_nicknames(&self, id: &str) -> Vec<String> {
+        let mut nicknames = HashSet::new();
+        if let Some(user_map) = self.runtime_storage.get(id) {
+            nicknames.extend(user_map.keys().cloned());
+        }
+        if let Some(user_map) = self.persistent_storage.get(id) {
+            nicknames.extend(user_map.keys().cloned());
+        }
+        nicknames.into_iter().collect()
+    }
+
+    // the file of the other backend is removed only after a successful write
+    fn save(&mut self, config: &Config) {
+        self.encrypted = config.encrypt_auth_data;
+        let launcher_dir = config.get_launcher_dir();
+        let plaintext_path = get_auth_data_path(&launcher_dir);
+        let encrypted_path = get_encrypted_auth_data_path(&launcher_dir);
+        let Ok(data) = serde_json::to_vec(&self.persistent_storage) else {
+            return;
+        };
+        if config.encrypt_auth_data {
+            if self.keep_encrypted_file {
+                warn!("Saved accounts are kept only for this session, the encrypted file is kept");
+                return;
+            }
+            match encryption::encrypt_with_stored_key(&data) {
+                Ok(encrypted) => {
+                    if std::fs::write(&encrypted_path, encrypted).is_ok() {
+                        let _ = std::fs::remove_file(&plaintext_path);
+                    }
+                }
+                Err(e) => warn!("Saved accounts are kept only for this session: {e}"),
+            }
+        } else if std::fs::write(&plaintext_path, data).is_ok() && !self.keep_encrypted_file {
+            let _ = std::fs::remove_file(&encrypted_path);
+        }
+    }
+
+    pub fn insert(&mut self, config: &Config, backend: &AuthBackend, mut auth_data: AuthData) {
+        auth_data.last_used = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .map(|d| d.as_secs());
+        let id = backend.get_id();
+        let username = auth_data.user_info.username.clone();
+
+        self.runtime_storage
+            .entry(id.clone())
+            .or_default()
+            .insert(username.clone(), auth_data.clone());
+        self.persistent_storage
+            .entry(id)
+            .or_default()
+            .insert(username, auth_data);
+
+        self.save(config);
+    }
+
+    pub fn delete_by_id(&mut self, config: &Config, id: &str, username: &str) {
+        if let Some(user_map) = self.runtime_storage.get_mut(id) {
+            user_map.remove(username);
+            if user_map.is_empty() {
+                self.runtime_storage.remove(id);
+            }
+        }
+        if let Some(user_map) = self.persistent_storage.get_mut(id) {
+            user_map.remove(username);
+            if user_map.is_empty() {
+                self.persistent_storage.remove(id);
+            }
+        }
+
+        self.save(config);
+    }
+
+    // sorted (backend id, username) pairs
+    pub fn get_all_entries(&self) -> Vec<(String, String)> {
+        let mut entries = BTreeSet::new();
+
+        let mut collect_entries = |storage: &HashMap<String, HashMap<String, AuthData>>| {
+            for (id, user_map) in storage {
+                for username in user_map.keys() {
+                    entries.insert((id.clone(), username.clone()));
+                }
+            }
+        };
+
+        collect_entries(&self.persistent_storage);
+        collect_entries(&self.runtime_storage);
+
+        entries.into_iter().collect()
+    }
+
+    // every account with its backend, sorted like get_all_entries
+    pub fn get_all_storage_entries(&self) -> Vec<(AuthBackend, StorageEntry)> {
+        self.get_all_entries()
+            .into_iter()
+            .filter_map(|(id, username)| {
+                let entry = self.get_by_id(&id, &username)?;
+                Some((AuthBackend::from_id(&id), entry))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::user_info::UserInfo;
+
+    fn auth_data(username: &str) -> AuthData {
+        AuthData {
+            access_token: String::new(),
+            refresh_token: None,
+            user_info: UserInfo {
+                uuid: String::new(),
+                username: username.to_string(),
+            },
+            last_used: None,
+        }
+    }
+
+    #[test]
+    fn test_get_all_entries() {
+        let storage = AuthStorage {
+            persistent_storage: HashMap::from([(
+                "microsoft".to_string(),
+                HashMap::from([
+                    ("alice".to_string(), auth_data("alice")),
+                    ("bob".to_string(), auth_data("bob")),
+                ]),
+            )]),
+            runtime_storage: HashMap::from([(
+                "microsoft".to_string(),
+                HashMap::from([("alice".to_string(), auth_data("alice"))]),
+            )]),
+            encrypted: false,
+            keep_encrypted_file: false,
+        };
+        assert_eq!(
+            storage.get_all_entries(),
+            vec![
+                ("microsoft".to_string(), "alice".to_string()),
+                ("microsoft".to_string(), "bob".to_string()),
+            ]
+        );
+
+        // the data of the current launch wins
+        let sources: Vec<_> = storage
+            .get_all_storage_entries()
+            .into_iter()
+            .map(|(backend, entry)| (backend.get_id(), entry.source))
+            .collect();
+        assert!(
+            sources
+                == vec![
+                    ("microsoft".to_string(), AuthDataSource::Runtime),
+                    ("microsoft".to_string(), AuthDataSource::Persistent),
+                ]
+        );
+    }
+
+    #[test]
+    fn test_backend_migration() {
+        // the key stays in memory instead of the keyring of whoever runs the tests
+        keyring::set_default_credential_builder(keyring::mock::default_credential_builder());
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        let config_json = |encrypt: bool| {
+            serde_json::json!({
+                "java_paths": {},
+                "data_dir": dir,
+                "xmx": {},
+                "use_native_glfw": {},
+                "lang": "English",
+                "hide_launcher_after_launch": true,
+                "auth_profiles": {},
+                "encrypt_auth_data": encrypt,
+            })
+        };
+        let plaintext_config: Config = serde_json::from_value(config_json(false)).unwrap();
+        let encrypted_config: Config = serde_json::from_value(config_json(true)).unwrap();
+        let plaintext_path = get_auth_data_path(dir);
+        let encrypted_path = get_encrypted_auth_data_path(dir);
+
+        let mut storage = AuthStorage::load(&plaintext_config);
+        storage.insert(
+            &plaintext_config,
+            &AuthBackend::Microsoft,
+            auth_data("alice"),
+        );
+        assert!(plaintext_path.exists());
+
+        // the plaintext accounts are moved on the first load with the encryption on
+        let storage = AuthStorage::load(&encrypted_config);
+        assert!(storage.get_by_id("microsoft", "alice").is_some());
+        assert!(!plaintext_path.exists());
+        let encrypted = std::fs::read(&encrypted_path).unwrap();
+        assert!(!String::from_utf8_lossy(&encrypted).contains("alice"));
+
+        // an encrypted file from another machine is treated as missing and moved aside
+        let foreign = b"not encrypted with this machine key";
+        std::fs::write(&encrypted_path, foreign).unwrap();
+        let mut storage = AuthStorage::load(&encrypted_config);
+        assert!(storage.get_all_entries().is_empty());
+        storage.insert(&encrypted_config, &AuthBackend::Microsoft, auth_data("bob"));
+        let backups: Vec<_> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "bak"))
+            .collect();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(std::fs::read(&backups[0]).unwrap(), foreign);
+        let storage = AuthStorage::load(&encrypted_config);
+        assert!(storage.get_by_id("microsoft", "bob").is_some());
+    }
+}
@@ -0,0 +1,204 @@
+use std::sync::OnceLock;
+
+use log::warn;
+use ring::aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey};
+use ring::hkdf;
+
+use crate::config::build_config;
+
+const KEY_SALT: &[u8] = b"potato_launcher auth storage";
+const KEY_INFO: &[u8] = b"aes-256-gcm v1";
+const KEYRING_USER: &str = "auth data key";
+const KEYRING_SECRET_LEN: usize = 32;
+
+#[derive(thiserror::Error, Debug)]
+pub enum EncryptionError {
+    #[error("Failed to get the machine id")]
+    NoMachineId,
+    #[error("Failed to encrypt the data")]
+    Encrypt,
+    // the file was copied from another machine or user, or it was modified
+    #[error("Failed to decrypt the data")]
+    Decrypt,
+}
+
+#[cfg(target_os = "linux")]
+fn get_machine_id() -> Option<String> {
+    ["/etc/machine-id", "/var/lib/dbus/machine-id"]
+        .iter()
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .map(|id| id.trim().to_string())
+        .find(|id| !id.is_empty())
+}
+
+#[cfg(target_os = "windows")]
+fn get_machine_id() -> Option<String> {
+    use winreg::RegKey;
+    use winreg::enums::*;
+
+    RegKey::predef(HKEY_LOCAL_MACHINE)
+        .open_subkey_with_flags(r"SOFTWARE\Microsoft\Cryptography", KEY_READ)
+        .ok()?
+        .get_value("MachineGuid")
+        .ok()
+}
+
+#[cfg(target_os = "macos")]
+fn get_machine_id() -> Option<String> {
+    let output = std::process::Command::new("ioreg")
+        .args(["-rd1", "-c", "IOPlatformExpertDevice"])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find(|line| line.contains("IOPlatformUUID"))?
+        .split('"')
+        .nth(3)
+        .map(str::to_string)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+fn get_machine_id() -> Option<String> {
+    None
+}
+
+// bound to the machine and the user, not stored anywhere. Anyone who can read the machine id
+// and knows the home path can derive it, so it's only used where there is no keyring
+fn get_machine_secret() -> Result<Vec<u8>, EncryptionError> {
+    let machine_id = get_machine_id().ok_or(EncryptionError::NoMachineId)?;
+    let mut secret = machine_id.into_bytes();
+    if let Some(home_dir) = dirs::home_dir() {
+        secret.extend_from_slice(home_dir.to_string_lossy().as_bytes());
+    }
+    Ok(secret)
+}
+
+// created once, every new entry would open its own connection to the keyring service
+fn get_keyring_entry() -> Option<&'static keyring::Entry> {
+    static ENTRY: OnceLock<Option<keyring::Entry>> = OnceLock::new();
+    ENTRY
+        .get_or_init(|| {
+            keyring::Entry::new(&build_config::get_lower_launcher_name(), KEYRING_USER)
+                .inspect_err(|e| warn!("Failed to open the OS keyring: {e}"))
+                .ok()
+        })
+        .as_ref()
+}
+
+// None if the keyring works but doesn't have the key yet
+fn read_keyring_secret(entry: &keyring::Entry) -> keyring::Result<Option<Vec<u8>>> {
+    match entry.get_secret() {
+        Ok(secret) => Ok(Some(secret)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+// a random key kept in the os keyring, created on the first save.
+// a keyring that fails to read is never given a new key, it may still have the old one
+fn get_keyring_secret() -> Option<Vec<u8>> {
+    let entry = get_keyring_entry()?;
+    match read_keyring_secret(entry) {
+        Ok(Some(secret)) => Some(secret),
+        Ok(None) => {
+            let secret: [u8; KEYRING_SECRET_LEN] = rand::random();
+            match entry.set_secret(&secret) {
+                Ok(()) => Some(secret.to_vec()),
+                Err(e) => {
+                    warn!("Failed to save the key to the OS keyring: {e}");
+                    None
+                }
+            }
+        }
+        Err(e) => {
+            warn!("Failed to read the key from the OS keyring: {e}");
+            None
+        }
+    }
+}
+
+// the keyring key, or the machine-bound one on systems without a keyring
+pub fn encrypt_with_stored_key(data: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+    let secret = match get_keyring_secret() {
+        Some(secret) => secret,
+        None => get_machine_secret()?,
+    };
+    encrypt(&secret, data)
+}
+
+// files written without a keyring are encrypted with the machine-bound key
+pub fn decrypt_with_stored_key(encrypted: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+    let keyring_secret = get_keyring_entry().and_then(|entry| {
+        read_keyring_secret(entry)
+            .inspect_err(|e| warn!("Failed to read the key from the OS keyring: {e}"))
+            .ok()
+            .flatten()
+    });
+    keyring_secret
+        .into_iter()
+        .chain(get_machine_secret().ok())
+        .find_map(|secret| decrypt(&secret, encrypted).ok())
+        .ok_or(EncryptionError::Decrypt)
+}
+
+fn derive_key(secret: &[u8]) -> LessSafeKey {
+    let prk = hkdf::Salt::new(hkdf::HKDF_SHA256, KEY_SALT).extract(secret);
+    let okm = prk
+        .expand(&[KEY_INFO], &AES_256_GCM)
+        .expect("AES-256 key length is valid for HKDF-SHA256");
+    LessSafeKey::new(UnboundKey::from(okm))
+}
+
+// the random nonce followed by the ciphertext and the tag
+fn encrypt(secret: &[u8], data: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+    let nonce_bytes: [u8; NONCE_LEN] = rand::random();
+    let mut in_out = data.to_vec();
+    derive_key(secret)
+        .seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce_bytes),
+            Aad::empty(),
+            &mut in_out,
+        )
+        .map_err(|_| EncryptionError::Encrypt)?;
+    let mut encrypted = nonce_bytes.to_vec();
+    encrypted.extend(in_out);
+    Ok(encrypted)
+}
+
+fn decrypt(secret: &[u8], encrypted: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+    if encrypted.len() < NONCE_LEN {
+        return Err(EncryptionError::Decrypt);
+    }
+    let (nonce_bytes, ciphertext) = encrypted.split_at(NONCE_LEN);
+    let nonce =
+        Nonce::try_assume_unique_for_key(nonce_bytes).map_err(|_| EncryptionError::Decrypt)?;
+    let mut in_out = ciphertext.to_vec();
+    let data = derive_key(secret)
+        .open_in_place(nonce, Aad::empty(), &mut in_out)
+        .map_err(|_| EncryptionError::Decrypt)?;
+    Ok(data.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let data = br#"{"microsoft":{}}"#;
+        let encrypted = encrypt(b"machine-a", data).unwrap();
+        assert_ne!(&encrypted[NONCE_LEN..data.len() + NONCE_LEN], data);
+        assert_eq!(decrypt(b"machine-a", &encrypted).unwrap(), data);
+        // a fresh nonce every time
+        assert_ne!(encrypt(b"machine-a", data).unwrap(), encrypted);
+
+        assert!(matches!(
+            decrypt(b"machine-b", &encrypted),
+            Err(EncryptionError::Decrypt)
+        ));
+        let mut tampered = encrypted.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(decrypt(b"machine-a", &tampered).is_err());
+        assert!(decrypt(b"machine-a", b"short").is_err());
+    }
+}
@@ -0,0 +1,513 @@
+use super::auth_flow::AuthMessageProvider;
+use super::base::{AuthProvider, AuthResultData, AuthState};
+use super::user_info::UserInfo;
+use crate::lang::LangMessage;
+use crate::vendor::minecraft_msa_auth::{MinecraftAuthorizationError, MinecraftAuthorizationFlow};
+use async_trait::async_trait;
+use log::warn;
+use oauth2::basic::BasicClient;
+use oauth2::{
+    AuthUrl, ClientId, DeviceAuthorizationUrl, EndpointNotSet, EndpointSet, Scope,
+    StandardDeviceAuthorizationResponse, TokenUrl,
+};
+use reqwest::{Client, StatusCode, Url};
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+
+const MSA_DEVICE_CODE_URL: &str = "https://login.live.com/oauth20_connect.srf";
+const MSA_TOKEN_URL: &str = "https://login.live.com/oauth20_token.srf";
+const MSA_CLIENT_ID: &str = "00000000441cc96b";
+const MSA_SCOPE: &str = "service::user.auth.xboxlive.com::MBI_SSL";
+const MINECRAFT_PROFILE_URL: &str = "https://api.minecraftservices.com/minecraft/profile";
+
+pub const CREATE_PROFILE_URL: &str = "https://www.minecraft.net/msaprofile/mygames/editprofile";
+pub const FAMILY_SETTINGS_URL: &str = "https://account.microsoft.com/family/";
+
+#[derive(thiserror::Error, Debug)]
+pub enum AuthError {
+    #[error("Timeout during authentication")]
+    AuthTimeout,
+    #[error("Authorization was denied")]
+    AccessDenied,
+    #[error("Device code error: {0}")]
+    DeviceCode(String),
+    #[error("Refresh token error: {0}")]
+    RefreshToken(String),
+    #[error("Account has no Minecraft profile")]
+    NoMinecraftProfile,
+    #[error("Account belongs to a child and needs family consent")]
+    FamilyConsentRequired,
+}
+
+pub struct MicrosoftAuthProvider {}
+
+#[derive(Deserialize)]
+struct MinecraftProfileResponse {
+    id: String,
+    name: String,
+}
+
+fn async_http_client() -> Result<reqwest::Client, reqwest::Error> {
+    reqwest::ClientBuilder::new()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+}
+
+fn get_oauth_client()
+-> BasicClient<EndpointSet, EndpointSet, EndpointNotSet, EndpointNotSet, EndpointSet> {
+    BasicClient::new(ClientId::new(MSA_CLIENT_ID.to_string()))
+        .set_auth_uri(AuthUrl::new(MSA_DEVICE_CODE_URL.to_string()).unwrap())
+        .set_token_uri(TokenUrl::new(MSA_TOKEN_URL.to_string()).unwrap())
+        .set_device_authorization_url(
+            DeviceAuthorizationUrl::new(MSA_DEVICE_CODE_URL.to_string()).unwrap(),
+        )
+}
+
+async fn get_ms_token(message_provider: &AuthMessageProvider) -> anyhow::Result<AuthResultData> {
+    let client = get_oauth_client();
+
+    let details: StandardDeviceAuthorizationResponse = client
+        .exchange_device_code()
+        .add_scope(Scope::new(MSA_SCOPE.to_string()))
+        .add_extra_param("response_type", "device_code")
+        .request_async(&async_http_client()?)
+        .await?;
+
+    let code = details.user_code().secret().to_string();
+    let url =
+        Url::parse_with_params(details.verification_uri(), &[("otc", code.clone())])?.to_string();
+
+    let timeout = Duration::from_secs(60 * 5).min(details.expires_in());
+
+    let _ = open::that(&url);
+    message_provider
+        .set_message_with_expiry(LangMessage::DeviceAuthMessage { url, code }, timeout)
+        .await;
+
+    poll_device_token(
+        &async_http_client()?,
+        MSA_TOKEN_URL,
+        details.device_code().secret(),
+        details.interval(),
+        Instant::now() + timeout,
+        message_provider,
+    )
+    .await
+}
+
+#[derive(Deserialize)]
+struct MsaTokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct MsaTokenErrorResponse {
+    error: String,
+}
+
+enum PollResult {
+    Done(AuthResultData),
+    Pending,
+    SlowDown,
+    // network errors and 5xx responses, the device code is still valid
+    Transient(anyhow::Error),
+}
+
+async fn poll_device_token_once(
+    http_client: &Client,
+    token_url: &str,
+    device_code: &str,
+) -> anyhow::Result<PollResult> {
+    let response = match http_client
+        .post(token_url)
+        .form(&[
+            ("grant_type", "urn:ietf:params:oauth:grant-type:device_code"),
+            ("device_code", device_code),
+            ("client_id", MSA_CLIENT_ID),
+        ])
+        .send()
+        .await
+    {
+        Ok(response) => response,
+        Err(e) => return Ok(PollResult::Transient(e.into())),
+    };
+
+    let status = response.status();
+    if status.is_server_error() {
+        return Ok(PollResult::Transient(anyhow::anyhow!(
+            "Token endpoint returned {status}"
+        )));
+    }
+    let body = match response.bytes().await {
+        Ok(body) => body,
+        Err(e) => return Ok(PollResult::Transient(e.into())),
+    };
+
+    if status.is_success() {
+        let token: MsaTokenResponse = serde_json::from_slice(&body)?;
+        return Ok(PollResult::Done(AuthResultData {
+            access_token: token.access_token,
+            refresh_token: token.refresh_token,
+        }));
+    }
+
+    let error: MsaTokenErrorResponse = serde_json::from_slice(&body)?;
+    match error.error.as_str() {
+        "authorization_pending" => Ok(PollResult::Pending),
+        "slow_down" => Ok(PollResult::SlowDown),
+        "expired_token" => Err(AuthError::AuthTimeout.into()),
+        "access_denied" => Err(AuthError::AccessDenied.into()),
+        _ => Err(AuthError::DeviceCode(error.error).into()),
+    }
+}
+
+// keeps polling with the same device code through network errors until it expires
+async fn poll_device_token(
+    http_client: &Client,
+    token_url: &str,
+    device_code: &str,
+    mut interval: Duration,
+    expires_at: Instant,
+    message_provider: &AuthMessageProvider,
+) -> anyhow::Result<AuthResultData> {
+    loop {
+        if Instant::now() >= expires_at {
+            return Err(AuthError::AuthTimeout.into());
+        }
+
+        let result = poll_device_token_once(http_client, token_url, device_code).await;
+        message_provider
+            .set_connection_lost(matches!(result, Ok(PollResult::Transient(_))))
+            .await;
+        match result? {
+            PollResult::Done(data) => return Ok(data),
+            PollResult::Pending => {}
+            PollResult::SlowDown => interval += Duration::from_secs(5),
+            PollResult::Transient(e) => warn!("Device code polling failed, retrying: {e}"),
+        }
+
+        tokio::time::sleep(interval.min(expires_at.saturating_duration_since(Instant::now())))
+            .await;
+    }
+}
+
+// None if the refresh token was revoked or expired and the user has to sign in again
+async fn refresh_ms_token(
+    http_client: &Client,
+    token_url: &str,
+    refresh_token: &str,
+) -> anyhow::Result<Option<AuthResultData>> {
+    let response = http_client
+        .post(token_url)
+        .form(&[
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token),
+            ("client_id", MSA_CLIENT_ID),
+            ("scope", MSA_SCOPE),
+        ])
+        .send()
+        .await?;
+
+    let status = response.status();
+    if status.is_server_error() {
+        return Err(anyhow::anyhow!("Token endpoint returned {status}"));
+    }
+    let body = response.bytes().await?;
+    if status.is_success() {
+        let token: MsaTokenResponse = serde_json::from_slice(&body)?;
+        return Ok(Some(AuthResultData {
+            access_token: token.access_token,
+            // the old refresh token stays valid if no new one is issued
+            refresh_token: token
+                .refresh_token
+                .or_else(|| Some(refresh_token.to_string())),
+        }));
+    }
+
+    let error: MsaTokenErrorResponse = serde_json::from_slice(&body)?;
+    match error.error.as_str() {
+        "invalid_grant" => Ok(None),
+        _ => Err(AuthError::RefreshToken(error.error).into()),
+    }
+}
+
+fn map_xbox_error(e: MinecraftAuthorizationError) -> anyhow::Error {
+    match e {
+        MinecraftAuthorizationError::AddToFamily => AuthError::FamilyConsentRequired.into(),
+        e => e.into(),
+    }
+}
+
+// Game Pass accounts that never launched the game get 404 after a successful XSTS auth
+async fn get_minecraft_profile(
+    http_client: &Client,
+    profile_url: &str,
+    token: &str,
+) -> anyhow::Result<UserInfo> {
+    let response = http_client
+        .get(profile_url)
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await?;
+    if response.status() == StatusCode::NOT_FOUND {
+        return Err(AuthError::NoMinecraftProfile.into());
+    }
+    let resp: MinecraftProfileResponse = response.error_for_status()?.json().await?;
+    Ok(UserInfo {
+        uuid: resp.id,
+        username: resp.name,
+    })
+}
+
+impl MicrosoftAuthProvider {
+    pub fn new() -> Self {
+        MicrosoftAuthProvider {}
+    }
+}
+
+#[async_trait]
+impl AuthProvider for MicrosoftAuthProvider {
+    async fn authenticate(
+        &self,
+        message_provider: &AuthMessageProvider,
+    ) -> anyhow::Result<AuthState> {
+        let ms_token = get_ms_token(message_provider).await?;
+        message_provider.clear().await;
+        let mc_flow = MinecraftAuthorizationFlow::new(Client::new());
+        let mc_token = mc_flow
+            .exchange_microsoft_token(ms_token.access_token)
+            .await
+            .map_err(map_xbox_error)?
+            .access_token()
+            .clone()
+            .0;
+
+        Ok(AuthState::UserInfo(AuthResultData {
+            access_token: mc_token,
+            refresh_token: Some(ms_token.refresh_token.unwrap()),
+        }))
+    }
+
+    // network errors are returned as is, only a rejected token falls back to the device code
+    async fn refresh(&self, refresh_token: String) -> anyhow::Result<AuthState> {
+        let Some(ms_token) =
+            refresh_ms_token(&async_http_client()?, MSA_TOKEN_URL, &refresh_token).await?
+        else {
+            warn!("Microsoft refresh token was rejected, signing in again");
+            return Ok(AuthState::Auth);
+        };
+
+        let mc_flow = MinecraftAuthorizationFlow::new(Client::new());
+        let mc_token = mc_flow
+            .exchange_microsoft_token(ms_token.access_token)
+            .await
+            .map_err(map_xbox_error)?
+            .access_token()
+            .clone()
+            .0;
+
+        Ok(AuthState::UserInfo(AuthResultData {
+            access_token: mc_token,
+            refresh_token: ms_token.refresh_token,
+        }))
+    }
+
+    async fn get_user_info(&self, token: &str) -> anyhow::Result<AuthState> {
+        let user_info = get_minecraft_profile(&Client::new(), MINECRAFT_PROFILE_URL, token).await?;
+        Ok(AuthState::Success(user_info))
+    }
+
+    fn get_auth_url(&self) -> Option<String> {
+        None
+    }
+
+    fn get_name(&self) -> String {
+        "Microsoft".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vendor::minecraft_msa_auth::parse_xsts_error;
+    use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+    use tokio::net::TcpListener;
+
+    enum MockResponse {
+        Drop,
+        Json(u16, &'static str),
+    }
+
+    // serves the responses in order, one per connection
+    async fn start_token_endpoint(responses: Vec<MockResponse>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            for response in responses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = [0; 4096];
+                let _ = socket.read(&mut buf).await;
+                if let MockResponse::Json(status, body) = response {
+                    let response = format!(
+                        "HTTP/1.1 {status} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                        body.len()
+                    );
+                    socket.write_all(response.as_bytes()).await.unwrap();
+                }
+            }
+        });
+        format!("http://{addr}/token")
+    }
+
+    async fn poll(responses: Vec<MockResponse>) -> anyhow::Result<AuthResultData> {
+        let token_url = start_token_endpoint(responses).await;
+        let message_provider = AuthMessageProvider::new(|| {});
+        let result = poll_device_token(
+            &Client::new(),
+            &token_url,
+            "device-code",
+            Duration::ZERO,
+            Instant::now() + Duration::from_secs(10),
+            &message_provider,
+        )
+        .await;
+        assert!(!message_provider.is_connection_lost().await);
+        result
+    }
+
+    #[tokio::test]
+    async fn test_device_polling_survives_connection_errors() {
+        let data = poll(vec![
+            MockResponse::Json(400, r#"{"error":"authorization_pending"}"#),
+            MockResponse::Drop,
+            MockResponse::Drop,
+            MockResponse::Json(503, ""),
+            MockResponse::Json(400, r#"{"error":"authorization_pending"}"#),
+            MockResponse::Json(
+                200,
+                r#"{"access_token":"access","refresh_token":"refresh"}"#,
+            ),
+        ])
+        .await
+        .unwrap();
+        assert_eq!(data.access_token, "access");
+        assert_eq!(data.refresh_token.as_deref(), Some("refresh"));
+    }
+
+    #[tokio::test]
+    async fn test_device_polling_aborts_on_fatal_errors() {
+        let err = poll(vec![
+            MockResponse::Drop,
+            MockResponse::Json(400, r#"{"error":"access_denied"}"#),
+        ])
+        .await
+        .err()
+        .unwrap();
+        assert!(matches!(
+            err.downcast_ref::<AuthError>(),
+            Some(AuthError::AccessDenied)
+        ));
+
+        let err = poll(vec![MockResponse::Json(
+            400,
+            r#"{"error":"expired_token"}"#,
+        )])
+        .await
+        .err()
+        .unwrap();
+        assert!(matches!(
+            err.downcast_ref::<AuthError>(),
+            Some(AuthError::AuthTimeout)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_refresh_ms_token() {
+        let url = start_token_endpoint(vec![
+            MockResponse::Json(200, r#"{"access_token":"access","refresh_token":"new"}"#),
+            MockResponse::Json(200, r#"{"access_token":"access"}"#),
+            MockResponse::Json(
+                400,
+                r#"{"error":"invalid_grant","error_description":"The refresh token has expired."}"#,
+            ),
+            MockResponse::Json(400, r#"{"error":"invalid_client"}"#),
+            MockResponse::Json(503, ""),
+        ])
+        .await;
+        let client = Client::new();
+
+        let data = refresh_ms_token(&client, &url, "old")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(data.access_token, "access");
+        assert_eq!(data.refresh_token.as_deref(), Some("new"));
+        let data = refresh_ms_token(&client, &url, "old")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(data.refresh_token.as_deref(), Some("old"));
+
+        // only a rejected token leads to the device code flow
+        assert!(
+            refresh_ms_token(&client, &url, "old")
+                .await
+                .unwrap()
+                .is_none()
+        );
+        let err = refresh_ms_token(&client, &url, "old").await.err().unwrap();
+        assert!(matches!(
+            err.downcast_ref::<AuthError>(),
+            Some(AuthError::RefreshToken(error)) if error == "invalid_client"
+        ));
+        assert!(refresh_ms_token(&client, &url, "old").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_account_error_mapping() {
+        let url = start_token_endpoint(vec![
+            MockResponse::Json(
+                404,
+                r#"{"path":"/minecraft/profile","errorType":"NOT_FOUND","error":"NOT_FOUND","errorMessage":"The server has not found anything matching the request URI","developerMessage":"The server has not found anything matching the request URI"}"#,
+            ),
+            MockResponse::Json(
+                200,
+                r#"{"id":"069a79f444e94726a5befca90e38aaf5","name":"Notch","skins":[],"capes":[]}"#,
+            ),
+            MockResponse::Json(401, ""),
+        ])
+        .await;
+        let client = Client::new();
+        let err = get_minecraft_profile(&client, &url, "token")
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(
+            err.downcast_ref::<AuthError>(),
+            Some(AuthError::NoMinecraftProfile)
+        ));
+        let user_info = get_minecraft_profile(&client, &url, "token").await.unwrap();
+        assert_eq!(user_info.username, "Notch");
+        let err = get_minecraft_profile(&client, &url, "token")
+            .await
+            .err()
+            .unwrap();
+        assert!(err.downcast_ref::<AuthError>().is_none());
+
+        let err = map_xbox_error(parse_xsts_error(
+            br#"{"Identity":"0","XErr":2148916238,"Message":"","Redirect":"https://start.ui.xboxlive.com/AddChildToFamily"}"#,
+        ));
+        assert!(matches!(
+            err.downcast_ref::<AuthError>(),
+            Some(AuthError::FamilyConsentRequired)
+        ));
+        let err = map_xbox_error(parse_xsts_error(
+            br#"{"Identity":"0","XErr":2148916233,"Message":"","Redirect":"https://start.ui.xboxlive.com/CreateAccount"}"#,
+        ));
+        assert!(matches!(
+            err.downcast_ref::<MinecraftAuthorizationError>(),
+            Some(MinecraftAuthorizationError::NoXbox)
+        ));
+    }
+}
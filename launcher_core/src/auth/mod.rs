@@ -0,0 +1,14 @@
+pub mod auth_flow;
+pub mod auth_storage;
+pub mod base;
+mod elyby;
+mod encryption;
+mod microsoft;
+mod offline;
+pub mod skins;
+mod telegram;
+pub mod user_info;
+
+pub use microsoft::{AuthError, CREATE_PROFILE_URL, FAMILY_SETTINGS_URL};
+pub use offline::{OfflineAccount, OfflineUuidStrategy, get_offline_account};
+pub use telegram::{DEFAULT_MAX_POLL_INTERVAL, TGAuthProvider};
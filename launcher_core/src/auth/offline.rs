@@ -0,0 +1,212 @@
+use super::{
+    auth_flow::AuthMessageProvider,
+    base::{AuthProvider, AuthResultData, AuthState},
+    user_info::{AuthData, UserInfo},
+};
+use async_trait::async_trait;
+use md5::{Digest, Md5};
+use uuid::Uuid;
+
+// servers differ in which uuid they expect for an offline nickname
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum OfflineUuidStrategy {
+    Mojang,
+    Random,
+    Manual,
+}
+
+impl OfflineUuidStrategy {
+    fn get_id(self) -> &'static str {
+        match self {
+            OfflineUuidStrategy::Mojang => "mojang",
+            OfflineUuidStrategy::Random => "random",
+            OfflineUuidStrategy::Manual => "manual",
+        }
+    }
+
+    fn from_id(id: &str) -> Option<Self> {
+        match id {
+            "mojang" => Some(OfflineUuidStrategy::Mojang),
+            "random" => Some(OfflineUuidStrategy::Random),
+            "manual" => Some(OfflineUuidStrategy::Manual),
+            _ => None,
+        }
+    }
+
+    // prior_uuid is kept for random uuids, None for an invalid manual uuid
+    pub fn get_uuid(
+        self,
+        nickname: &str,
+        manual_uuid: &str,
+        prior_uuid: Option<&str>,
+    ) -> Option<Uuid> {
+        match self {
+            OfflineUuidStrategy::Mojang => Some(get_mojang_offline_uuid(nickname)),
+            OfflineUuidStrategy::Random => Some(
+                prior_uuid
+                    .and_then(|uuid| Uuid::parse_str(uuid).ok())
+                    .unwrap_or_else(|| {
+                        uuid::Builder::from_random_bytes(rand::random()).into_uuid()
+                    }),
+            ),
+            OfflineUuidStrategy::Manual => Uuid::parse_str(manual_uuid.trim()).ok(),
+        }
+    }
+}
+
+pub struct OfflineAccount {
+    pub nickname: String,
+    pub uuid: Uuid,
+    pub strategy: OfflineUuidStrategy,
+}
+
+// UUID.nameUUIDFromBytes("OfflinePlayer:" + name) used by offline-mode servers
+pub fn get_mojang_offline_uuid(nickname: &str) -> Uuid {
+    let hash = Md5::digest(format!("OfflinePlayer:{nickname}").as_bytes());
+    uuid::Builder::from_md5_bytes(hash.into()).into_uuid()
+}
+
+// the token is the whole account data of an offline account, "nickname:uuid:strategy".
+// accounts added before the uuid was chosen only have the nickname and ones added before
+// the strategy was stored have no strategy, their uuid is kept as a manual one
+fn make_token(account: &OfflineAccount) -> String {
+    format!(
+        "{}:{}:{}",
+        account.nickname,
+        account.uuid,
+        account.strategy.get_id()
+    )
+}
+
+fn parse_token(token: &str) -> OfflineAccount {
+    let mut parts = token.splitn(3, ':');
+    if let Some(nickname) = parts.next()
+        && let Some(Ok(uuid)) = parts.next().map(Uuid::parse_str)
+    {
+        return OfflineAccount {
+            nickname: nickname.to_string(),
+            uuid,
+            strategy: parts
+                .next()
+                .and_then(OfflineUuidStrategy::from_id)
+                .unwrap_or(OfflineUuidStrategy::Manual),
+        };
+    }
+    OfflineAccount {
+        nickname: token.to_string(),
+        uuid: Uuid::new_v3(&Uuid::NAMESPACE_DNS, token.as_bytes()),
+        strategy: OfflineUuidStrategy::Manual,
+    }
+}
+
+// re-adding an account keeps its strategy and uuid
+pub fn get_offline_account(auth_data: &AuthData) -> OfflineAccount {
+    parse_token(&auth_data.access_token)
+}
+
+pub struct OfflineAuthProvider {}
+
+impl OfflineAuthProvider {
+    pub fn new() -> Self {
+        OfflineAuthProvider {}
+    }
+}
+
+#[async_trait]
+impl AuthProvider for OfflineAuthProvider {
+    async fn authenticate(
+        &self,
+        message_provider: &AuthMessageProvider,
+    ) -> anyhow::Result<AuthState> {
+        let account = message_provider.request_offline_account().await;
+        Ok(AuthState::UserInfo(AuthResultData {
+            access_token: make_token(&account),
+            refresh_token: None,
+        }))
+    }
+
+    async fn refresh(&self, _: String) -> anyhow::Result<AuthState> {
+        Ok(AuthState::Auth)
+    }
+
+    async fn get_user_info(&self, token: &str) -> anyhow::Result<AuthState> {
+        let account = parse_token(token);
+        Ok(AuthState::Success(UserInfo {
+            uuid: account.uuid.to_string(),
+            username: account.nickname,
+        }))
+    }
+
+    fn get_auth_url(&self) -> Option<String> {
+        None
+    }
+
+    fn get_name(&self) -> String {
+        "Offline".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offline_uuid() {
+        let mojang_uuid = get_mojang_offline_uuid("Notch");
+        assert_eq!(
+            mojang_uuid.to_string(),
+            "b50ad385-829d-3141-a216-7e7d7539ba7f"
+        );
+        assert_eq!(
+            OfflineUuidStrategy::Mojang.get_uuid("Notch", "", None),
+            Some(mojang_uuid)
+        );
+
+        let prior_uuid = Uuid::parse_str("069a79f4-44e9-4726-a5be-fca90e38aaf5").unwrap();
+        assert_eq!(
+            OfflineUuidStrategy::Random.get_uuid(
+                "Notch",
+                "",
+                Some("069a79f4-44e9-4726-a5be-fca90e38aaf5")
+            ),
+            Some(prior_uuid)
+        );
+        assert_ne!(
+            OfflineUuidStrategy::Random.get_uuid("Notch", "", None),
+            OfflineUuidStrategy::Random.get_uuid("Notch", "", None)
+        );
+        assert_eq!(
+            OfflineUuidStrategy::Manual.get_uuid(
+                "Notch",
+                " 069a79f444e94726a5befca90e38aaf5 ",
+                None
+            ),
+            Some(prior_uuid)
+        );
+        assert_eq!(
+            OfflineUuidStrategy::Manual.get_uuid("Notch", "not-a-uuid", None),
+            None
+        );
+
+        let account = parse_token(&make_token(&OfflineAccount {
+            nickname: "Notch".to_string(),
+            uuid: prior_uuid,
+            strategy: OfflineUuidStrategy::Random,
+        }));
+        assert_eq!(account.nickname, "Notch");
+        assert_eq!(account.uuid, prior_uuid);
+        assert_eq!(account.strategy, OfflineUuidStrategy::Random);
+
+        // tokens of existing accounts keep their uuid
+        let account = parse_token("Notch:069a79f4-44e9-4726-a5be-fca90e38aaf5");
+        assert_eq!(account.uuid, prior_uuid);
+        assert_eq!(account.strategy, OfflineUuidStrategy::Manual);
+        let account = parse_token("Notch");
+        assert_eq!(account.nickname, "Notch");
+        assert_eq!(
+            account.uuid,
+            Uuid::new_v3(&Uuid::NAMESPACE_DNS, "Notch".as_bytes())
+        );
+        assert_eq!(account.strategy, OfflineUuidStrategy::Manual);
+    }
+}
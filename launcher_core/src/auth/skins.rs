@@ -0,0 +1,200 @@
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use shared::version::extra_version_metadata::AuthBackend;
+
+use super::user_info::UserInfo;
+
+const MOJANG_SESSION_PROFILE_URL: &str =
+    "https://sessionserver.mojang.com/session/minecraft/profile";
+const ELY_BY_TEXTURES_URL: &str = "https://skinsystem.ely.by/textures";
+// the yggdrasil root of the telegram backend, as passed to authlib-injector
+const YGGDRASIL_SESSION_PROFILE_PATH: &str = "sessionserver/session/minecraft/profile";
+
+#[derive(Deserialize)]
+struct SkinTexture {
+    url: String,
+}
+
+#[derive(Deserialize)]
+struct Textures {
+    #[serde(rename = "SKIN")]
+    skin: Option<SkinTexture>,
+}
+
+#[derive(Deserialize)]
+struct TexturesProperty {
+    textures: Textures,
+}
+
+#[derive(Deserialize)]
+struct ProfileProperty {
+    name: String,
+    value: String,
+}
+
+#[derive(Deserialize)]
+struct SessionProfile {
+    #[serde(default)]
+    properties: Vec<ProfileProperty>,
+}
+
+// None if the profile has no custom skin
+async fn get_session_skin_url(
+    client: &Client,
+    profile_url: &str,
+) -> anyhow::Result<Option<String>> {
+    let response = client.get(profile_url).send().await?;
+    if response.status() == StatusCode::NO_CONTENT || response.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    let profile: SessionProfile = response.error_for_status()?.json().await?;
+    let Some(property) = profile
+        .properties
+        .into_iter()
+        .find(|property| property.name == "textures")
+    else {
+        return Ok(None);
+    };
+    let textures: TexturesProperty = serde_json::from_slice(&BASE64.decode(property.value)?)?;
+    Ok(textures.textures.skin.map(|skin| skin.url))
+}
+
+// ely.by serves the textures object directly, without the session profile around it
+async fn get_ely_by_skin_url(
+    client: &Client,
+    textures_url: &str,
+) -> anyhow::Result<Option<String>> {
+    let response = client.get(textures_url).send().await?;
+    if response.status() == StatusCode::NO_CONTENT || response.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    let textures: Textures = response.error_for_status()?.json().await?;
+    Ok(textures.skin.map(|skin| skin.url))
+}
+
+// the png of the account skin, None for offline accounts and default skins
+pub async fn fetch_skin(
+    client: &Client,
+    auth_backend: &AuthBackend,
+    user_info: &UserInfo,
+) -> anyhow::Result<Option<Vec<u8>>> {
+    let uuid = user_info.uuid.replace('-', "");
+    let skin_url = match auth_backend {
+        AuthBackend::Microsoft => {
+            get_session_skin_url(client, &format!("{MOJANG_SESSION_PROFILE_URL}/{uuid}")).await?
+        }
+        AuthBackend::ElyBy(_) => {
+            get_ely_by_skin_url(
+                client,
+                &format!("{ELY_BY_TEXTURES_URL}/{}", user_info.username),
+            )
+            .await?
+        }
+        AuthBackend::Telegram(auth_data) => {
+            let base_url = auth_data.auth_base_url.trim_end_matches('/');
+            get_session_skin_url(
+                client,
+                &format!("{base_url}/{YGGDRASIL_SESSION_PROFILE_PATH}/{uuid}"),
+            )
+            .await?
+        }
+        AuthBackend::Offline => None,
+    };
+    let Some(skin_url) = skin_url else {
+        return Ok(None);
+    };
+    let data = client
+        .get(&skin_url)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    Ok(Some(data.to_vec()))
+}
+
+#[cfg(test)]
+mod tests {
+    use shared::version::extra_version_metadata::TelegramAuthBackend;
+    use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    // answers by the request path, unknown paths get 404
+    fn serve(listener: TcpListener, routes: Vec<(String, String)>) {
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = [0; 4096];
+                let n = socket.read(&mut buf).await.unwrap();
+                let request = String::from_utf8_lossy(&buf[..n]);
+                let path = request.split(' ').nth(1).unwrap_or_default();
+                let (status, body) = match routes.iter().find(|(route, _)| route == path) {
+                    Some((_, body)) => ("200 OK", body.as_str()),
+                    None => ("404 Not Found", ""),
+                };
+                let response = format!(
+                    "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+    }
+
+    #[tokio::test]
+    async fn test_fetch_skin() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let textures = BASE64.encode(format!(
+            r#"{{"profileId":"069a79f444e94726a5befca90e38aaf5","textures":{{"SKIN":{{"url":"{base_url}/skins/steve.png"}}}}}}"#
+        ));
+        let profile = format!(
+            r#"{{"id":"069a79f444e94726a5befca90e38aaf5","name":"Steve","properties":[{{"name":"textures","value":"{textures}"}}]}}"#
+        );
+        serve(
+            listener,
+            vec![
+                (
+                    format!("/{YGGDRASIL_SESSION_PROFILE_PATH}/069a79f444e94726a5befca90e38aaf5"),
+                    profile,
+                ),
+                ("/skins/steve.png".to_string(), "png".to_string()),
+            ],
+        );
+
+        let client = Client::new();
+        let telegram = AuthBackend::Telegram(TelegramAuthBackend {
+            auth_base_url: format!("{base_url}/"),
+        });
+        let user_info = UserInfo {
+            uuid: "069a79f4-44e9-4726-a5be-fca90e38aaf5".to_string(),
+            username: "Steve".to_string(),
+        };
+        assert_eq!(
+            fetch_skin(&client, &telegram, &user_info).await.unwrap(),
+            Some(b"png".to_vec())
+        );
+
+        // unknown profiles get the default skin
+        let unknown = UserInfo {
+            uuid: "00000000000000000000000000000000".to_string(),
+            username: "Nobody".to_string(),
+        };
+        assert!(
+            fetch_skin(&client, &telegram, &unknown)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            fetch_skin(&client, &AuthBackend::Offline, &user_info)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,296 @@
+use crate::lang::LangMessage;
+
+use super::{
+    auth_flow::{AuthError, AuthMessageProvider},
+    base::{AuthProvider, AuthResultData, AuthState},
+    user_info::UserInfo,
+};
+use async_trait::async_trait;
+use log::warn;
+use reqwest::{Client, StatusCode, header::RETRY_AFTER};
+use serde::Deserialize;
+use std::time::Duration;
+
+const MIN_POLL_INTERVAL: Duration = Duration::from_secs(1);
+pub const DEFAULT_MAX_POLL_INTERVAL: Duration = Duration::from_secs(10);
+// used when a 429 response has no Retry-After
+const RATE_LIMIT_DELAY: Duration = Duration::from_secs(30);
+
+#[derive(Deserialize)]
+struct LoginStartResponse {
+    code: String,
+    intermediate_token: String,
+}
+
+#[derive(Deserialize)]
+struct BotInfo {
+    bot_username: String,
+}
+
+#[derive(Deserialize)]
+struct PollUser {
+    access_token: String,
+}
+
+#[derive(Deserialize)]
+struct PollResponse {
+    // missing until the user confirms the login in the bot
+    user: Option<PollUser>,
+}
+
+enum PollResult {
+    Done(String),
+    Pending,
+    RateLimited(Option<Duration>),
+}
+
+// doubles from MIN_POLL_INTERVAL up to the ceiling
+fn get_poll_backoff(attempt: u32, max_interval: Duration) -> Duration {
+    MIN_POLL_INTERVAL
+        .saturating_mul(1 << attempt.min(16))
+        .min(max_interval)
+}
+
+// spreads the polls of clients that started at the same time
+fn with_jitter(delay: Duration) -> Duration {
+    delay.mul_f64(rand::random_range(0.5..=1.0))
+}
+
+pub struct TGAuthProvider {
+    client: Client,
+    base_url: String,
+    max_poll_interval: Duration,
+}
+
+impl TGAuthProvider {
+    pub fn new(base_url: &str) -> Self {
+        TGAuthProvider {
+            client: Client::new(),
+            base_url: base_url.to_string(),
+            max_poll_interval: DEFAULT_MAX_POLL_INTERVAL,
+        }
+    }
+
+    pub fn with_max_poll_interval(mut self, max_poll_interval: Duration) -> Self {
+        self.max_poll_interval = max_poll_interval.max(MIN_POLL_INTERVAL);
+        self
+    }
+
+    async fn poll_once(&self, intermediate_token: &str) -> anyhow::Result<PollResult> {
+        let response = match self
+            .client
+            .post(format!("{}/login/poll", self.base_url))
+            .json(&serde_json::json!({
+                "intermediate_token": intermediate_token
+            }))
+            .send()
+            .await
+        {
+            Ok(response) => response,
+            // the server holds the request until the login is confirmed
+            Err(e) if e.is_timeout() => return Ok(PollResult::Pending),
+            Err(e) => return Err(e.into()),
+        };
+        match response.status() {
+            StatusCode::TOO_MANY_REQUESTS => {
+                let retry_after = response
+                    .headers()
+                    .get(RETRY_AFTER)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.parse().ok())
+                    .map(Duration::from_secs);
+                return Ok(PollResult::RateLimited(retry_after));
+            }
+            StatusCode::REQUEST_TIMEOUT | StatusCode::GATEWAY_TIMEOUT => {
+                return Ok(PollResult::Pending);
+            }
+            _ => {}
+        }
+        let poll_response: PollResponse = response.error_for_status()?.json().await?;
+        Ok(match poll_response.user {
+            Some(user) => PollResult::Done(user.access_token),
+            None => PollResult::Pending,
+        })
+    }
+
+    // returns within one poll interval after the auth is cancelled
+    async fn poll_access_token(
+        &self,
+        intermediate_token: &str,
+        message_provider: &AuthMessageProvider,
+    ) -> anyhow::Result<String> {
+        let mut attempt = 0;
+        loop {
+            let result = tokio::select! {
+                result = self.poll_once(intermediate_token) => result?,
+                _ = message_provider.cancelled() => return Err(AuthError::Cancelled.into()),
+            };
+            let delay = match result {
+                PollResult::Done(access_token) => return Ok(access_token),
+                PollResult::Pending => {
+                    with_jitter(get_poll_backoff(attempt, self.max_poll_interval))
+                }
+                PollResult::RateLimited(retry_after) => {
+                    warn!("Telegram auth polling is rate limited");
+                    retry_after
+                        .unwrap_or(RATE_LIMIT_DELAY)
+                        .max(self.max_poll_interval)
+                }
+            };
+            attempt += 1;
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {}
+                _ = message_provider.cancelled() => return Err(AuthError::Cancelled.into()),
+            }
+        }
+    }
+
+    async fn get_bot_name(&self) -> anyhow::Result<String> {
+        let body = self
+            .client
+            .get(format!("{}/info", self.base_url))
+            .send()
+            .await?
+            .text()
+            .await?;
+
+        let bot_info: BotInfo = serde_json::from_str(&body)?;
+        Ok(bot_info.bot_username)
+    }
+}
+
+#[async_trait]
+impl AuthProvider for TGAuthProvider {
+    async fn authenticate(
+        &self,
+        message_provider: &AuthMessageProvider,
+    ) -> anyhow::Result<AuthState> {
+        let bot_name = self.get_bot_name().await?;
+        let body = self
+            .client
+            .post(format!("{}/login/start", self.base_url))
+            .send()
+            .await?
+            .text()
+            .await?;
+        let start_resp: LoginStartResponse = serde_json::from_str(&body)?;
+
+        let tg_deeplink = format!("https://t.me/{}?start={}", bot_name, start_resp.code);
+        let _ = open::that(&tg_deeplink);
+        message_provider
+            .set_message(LangMessage::AuthMessage { url: tg_deeplink })
+            .await;
+
+        let access_token = self
+            .poll_access_token(&start_resp.intermediate_token, message_provider)
+            .await?;
+
+        Ok(AuthState::UserInfo(AuthResultData {
+            access_token,
+            refresh_token: None,
+        }))
+    }
+
+    async fn refresh(&self, _: String) -> anyhow::Result<AuthState> {
+        Ok(AuthState::Auth)
+    }
+
+    async fn get_user_info(&self, token: &str) -> anyhow::Result<AuthState> {
+        let resp: UserInfo = self
+            .client
+            .get(format!("{}/login/profile", self.base_url))
+            .header("Authorization", format!("Bearer {token}"))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(AuthState::Success(resp))
+    }
+
+    fn get_auth_url(&self) -> Option<String> {
+        Some(self.base_url.clone())
+    }
+
+    fn get_name(&self) -> String {
+        "Telegram".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    // serves the responses in order, one per connection
+    async fn start_poll_endpoint(responses: Vec<&'static str>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            for response in responses.iter().cycle() {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = [0; 4096];
+                let _ = socket.read(&mut buf).await;
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        format!("http://{addr}")
+    }
+
+    const PENDING: &str =
+        "HTTP/1.1 200 OK\r\nContent-Length: 13\r\nConnection: close\r\n\r\n{\"user\":null}";
+
+    #[test]
+    fn test_get_poll_backoff() {
+        let max = Duration::from_secs(10);
+        assert_eq!(get_poll_backoff(0, max), Duration::from_secs(1));
+        assert_eq!(get_poll_backoff(3, max), Duration::from_secs(8));
+        assert_eq!(get_poll_backoff(4, max), max);
+        assert_eq!(get_poll_backoff(100, max), max);
+        let delay = with_jitter(max);
+        assert!(delay >= max / 2 && delay <= max);
+    }
+
+    #[tokio::test]
+    async fn test_poll_access_token() {
+        let base_url = start_poll_endpoint(vec![
+            PENDING,
+            "HTTP/1.1 429 Too Many Requests\r\nRetry-After: 0\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            "HTTP/1.1 200 OK\r\nContent-Length: 34\r\nConnection: close\r\n\r\n{\"user\":{\"access_token\":\"access\"}}",
+        ])
+        .await;
+        // rate limiting waits for at least the ceiling
+        let provider =
+            TGAuthProvider::new(&base_url).with_max_poll_interval(Duration::from_millis(1));
+        let message_provider = AuthMessageProvider::new(|| {});
+        let access_token = provider
+            .poll_access_token("token", &message_provider)
+            .await
+            .unwrap();
+        assert_eq!(access_token, "access");
+
+        let base_url = start_poll_endpoint(vec![PENDING]).await;
+        let provider = TGAuthProvider::new(&base_url);
+        let message_provider = AuthMessageProvider::new(|| {});
+        let cancellation_token = message_provider.get_cancellation_token();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            cancellation_token.cancel();
+        });
+        let start = Instant::now();
+        let err = provider
+            .poll_access_token("token", &message_provider)
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(
+            err.downcast_ref::<AuthError>(),
+            Some(AuthError::Cancelled)
+        ));
+        assert!(start.elapsed() < DEFAULT_MAX_POLL_INTERVAL);
+    }
+}
@@ -0,0 +1,60 @@
+use base64::Engine as _;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64_URL;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct UserInfo {
+    pub uuid: String,
+    pub username: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct AuthData {
+    pub access_token: String,
+    // accounts saved by older versions don't have it
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    pub user_info: UserInfo,
+    // unix timestamp of the last successful authorization
+    #[serde(default)]
+    pub last_used: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct TokenClaims {
+    exp: u64,
+}
+
+impl AuthData {
+    // unix timestamp, None if the token is not a jwt, like the offline and telegram ones
+    pub fn get_token_expiry(&self) -> Option<u64> {
+        let payload = self.access_token.split('.').nth(1)?;
+        let claims: TokenClaims = serde_json::from_slice(&BASE64_URL.decode(payload).ok()?).ok()?;
+        Some(claims.exp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_token_expiry() {
+        let mut auth_data = AuthData {
+            access_token: format!(
+                "{}.{}.signature",
+                BASE64_URL.encode(r#"{"alg":"HS256"}"#),
+                BASE64_URL.encode(r#"{"sub":"uuid","exp":1700000000}"#)
+            ),
+            refresh_token: None,
+            user_info: UserInfo {
+                uuid: String::new(),
+                username: "Steve".to_string(),
+            },
+            last_used: None,
+        };
+        assert_eq!(auth_data.get_token_expiry(), Some(1700000000));
+        auth_data.access_token = "opaque-token".to_string();
+        assert_eq!(auth_data.get_token_expiry(), None);
+    }
+}
@@ -12,6 +12,22 @@ pub fn get_default_version_manifest_url() -> String {
     VERSION_MANIFEST_URL.to_string()
 }
 
+pub fn get_manifest_public_key() -> Option<String> {
+    MANIFEST_PUBLIC_KEY.map(|key| key.to_string())
+}
+
+pub fn get_news_url() -> Option<String> {
+    NEWS_URL.map(|url| url.to_string())
+}
+
+pub fn get_discord_client_id() -> Option<String> {
+    DISCORD_CLIENT_ID.map(|id| id.to_string())
+}
+
+pub fn get_discord_large_image() -> Option<String> {
+    DISCORD_LARGE_IMAGE.map(|key| key.to_string())
+}
+
 pub fn get_backend_api_base() -> Option<String> {
     BACKEND_API_BASE.map(|url| url.trim_end_matches('/').to_string())
 }
@@ -25,5 +41,3 @@ pub const LIBRARY_OVERRIDES: &str = include_str!("../../meta/library-overrides.j
 pub const MOJANG_LIBRARY_PATCHES: &str = include_str!("../../meta/mojang-library-patches.json");
 
 pub const LWJGL_VERSION_MATCHES: &str = include_str!("../../meta/lwjgl-version-matches.json");
-
-include!(concat!(env!("OUT_DIR"), "/icon_file_bytes.rs"));
@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use log::{info, warn};
+use serde::Deserialize;
+
+use super::runtime_config::{AuthProfile, Config};
+use crate::lang::Lang;
+
+// bump when a change to Config can't be read from the previous layout,
+// and keep a struct for that layout below
+pub const CURRENT_CONFIG_VERSION: u32 = 1;
+
+// the layout of the first releases, before the per-instance settings
+#[derive(Deserialize)]
+struct LegacyConfig {
+    java_paths: HashMap<String, PathBuf>,
+    assets_dir: Option<PathBuf>,
+    data_dir: Option<PathBuf>,
+    xmx: String,
+    use_native_glfw: bool,
+    selected_modpack_name: Option<String>,
+    lang: Lang,
+    hide_launcher_after_launch: bool,
+    auth_profile: Option<AuthProfile>,
+    version_manifest_url: Option<String>,
+}
+
+// the global settings were only used for the selected instance
+fn migrate_legacy(legacy: LegacyConfig) -> Config {
+    let mut config = Config::new_default();
+    config.java_paths = legacy.java_paths;
+    config.assets_dir = legacy.assets_dir;
+    config.data_dir = legacy.data_dir;
+    config.lang = legacy.lang;
+    config.hide_launcher_after_launch = legacy.hide_launcher_after_launch;
+    if let Some(url) = legacy.version_manifest_url {
+        config.selected_version_manifest_url = url;
+    }
+    if let Some(instance_name) = &legacy.selected_modpack_name {
+        config.xmx.insert(instance_name.clone(), legacy.xmx);
+        config
+            .use_native_glfw
+            .insert(instance_name.clone(), legacy.use_native_glfw);
+        if let Some(auth_profile) = legacy.auth_profile {
+            config
+                .auth_profiles
+                .insert(instance_name.clone(), auth_profile);
+        }
+    }
+    config.selected_instance_name = legacy.selected_modpack_name;
+    config
+}
+
+// tries the current layout, then the older ones, None if nothing matches
+pub fn parse_config(data: &[u8]) -> Option<Config> {
+    let current_error = match serde_json::from_slice::<Config>(data) {
+        Ok(config) => {
+            if config.config_version > CURRENT_CONFIG_VERSION {
+                warn!(
+                    "Config version {} is newer than {CURRENT_CONFIG_VERSION}, \
+                     unknown settings will be dropped",
+                    config.config_version
+                );
+            }
+            return Some(config);
+        }
+        Err(e) => e,
+    };
+    if let Ok(legacy) = serde_json::from_slice::<LegacyConfig>(data) {
+        info!("Migrating the config from the legacy layout");
+        return Some(migrate_legacy(legacy));
+    }
+    warn!("Failed to parse the config:\n{current_error}");
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let config = parse_config(include_str!("fixtures/config_v1.json").as_bytes()).unwrap();
+        assert_eq!(config.config_version, 1);
+        assert_eq!(config.selected_instance_name.as_deref(), Some("survival"));
+        assert_eq!(config.auth_profiles["survival"].username, "Steve");
+        assert_eq!(config.xmx["survival"], "4096M");
+        assert_eq!(config.rollout_id, "0123456789abcdef");
+
+        let config = parse_config(include_str!("fixtures/config_legacy.json").as_bytes()).unwrap();
+        assert_eq!(config.selected_instance_name.as_deref(), Some("survival"));
+        assert_eq!(config.auth_profiles["survival"].username, "Steve");
+        assert_eq!(config.xmx["survival"], "3072M");
+        assert!(config.use_native_glfw["survival"]);
+        assert_eq!(
+            config.selected_version_manifest_url,
+            "https://mc.example.com/version_manifest.json"
+        );
+        assert!(config.lang == Lang::Russian);
+        assert_eq!(config.config_version, CURRENT_CONFIG_VERSION);
+
+        assert!(parse_config(br#"{"xmx": 1}"#).is_none());
+        assert!(parse_config(b"not json").is_none());
+    }
+}
@@ -1,2 +1,3 @@
 pub mod build_config;
+pub mod migration;
 pub mod runtime_config;
@@ -0,0 +1,655 @@
+use log::warn;
+use serde::{Deserialize, Serialize};
+use shared::adaptive_download::{DEFAULT_MAX_CONNECTIONS, MirrorRanking};
+use shared::files::{HASH_BUFFER_SIZE, HashLimits};
+use shared::paths::get_logs_dir;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::build_config;
+use super::migration::{self, CURRENT_CONFIG_VERSION};
+use crate::{
+    constants,
+    lang::Lang,
+    launcher::{display::DisplayServer, shared_data::SharedDataConfig},
+    utils::{get_available_memory_mb, get_data_dir},
+    version::{rollout, sync::SyncSettings},
+};
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct AuthProfile {
+    pub auth_backend_id: String,
+    pub username: String,
+}
+
+// an instance with an account and setting overrides, switched to with one click
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct LaunchProfile {
+    pub name: String,
+    pub manifest_url: String,
+    pub instance_name: String,
+    pub auth_profile: AuthProfile,
+    #[serde(default)]
+    pub xmx: Option<String>,
+    #[serde(default)]
+    pub window_size: Option<(u32, u32)>,
+}
+
+// the config is written at most once per interval, see Config::save
+pub const SAVE_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Default)]
+struct PendingSave {
+    dirty: bool,
+    last_write: Option<Instant>,
+}
+
+#[derive(Default)]
+pub struct SaveState(Mutex<PendingSave>);
+
+// the config of the last deferred save, for exits that can't reach the Config,
+// see write_unsaved_config
+static UNSAVED_CONFIG: Mutex<Option<String>> = Mutex::new(None);
+
+// writes the config if a save is still waiting for save_if_due, e.g. in the Ctrl-C handler
+pub fn write_unsaved_config() {
+    let mut unsaved_config = UNSAVED_CONFIG.lock().unwrap();
+    if let Some(config_str) = unsaved_config.take() {
+        write_config_str(&get_config_path(), &config_str);
+    }
+}
+
+fn write_config_str(config_path: &Path, config_str: &str) {
+    // an interrupted write leaves the old config in place
+    let temp_path = config_path.with_extension("json.tmp");
+    std::fs::write(&temp_path, config_str).expect("Failed to write config file");
+    std::fs::rename(&temp_path, config_path).expect("Failed to write config file");
+}
+
+fn provide_default_version_manifest_url() -> String {
+    build_config::get_default_version_manifest_url()
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Config {
+    pub java_paths: HashMap<String, PathBuf>,
+    pub assets_dir: Option<PathBuf>,
+    pub data_dir: Option<PathBuf>,
+    pub xmx: HashMap<String, String>,
+    pub use_native_glfw: HashMap<String, bool>,
+    pub selected_instance_name: Option<String>,
+    pub lang: Lang,
+    pub hide_launcher_after_launch: bool,
+    pub auth_profiles: HashMap<String, AuthProfile>,
+    #[serde(default)]
+    pub extra_version_manifest_urls: Vec<String>,
+    #[serde(default = "provide_default_version_manifest_url")]
+    pub selected_version_manifest_url: String,
+    #[serde(default)]
+    pub manifest_public_keys: HashMap<String, String>, // manifest url -> public key
+    #[serde(default)]
+    pub manifest_news_urls: HashMap<String, String>, // manifest url -> news feed url
+    // news feed url -> ids of the entries seen by the user
+    #[serde(default)]
+    pub read_news: HashMap<String, HashSet<String>>,
+    #[serde(default)]
+    pub rotational_disk: bool,
+    #[serde(default)]
+    pub shared_data: HashMap<String, SharedDataConfig>,
+    // overrides the per-instance auto-sync flags
+    #[serde(default)]
+    pub pause_auto_sync: bool,
+    #[serde(default)]
+    pub deduplicate_files: bool,
+    // downloads mods into a store shared by the instances and links them from there
+    #[serde(default)]
+    pub use_file_store: bool,
+    #[serde(default)]
+    pub launch_profiles: Vec<LaunchProfile>,
+    #[serde(default)]
+    pub last_launch_profile: Option<String>,
+    // sets __GL_THREADED_OPTIMIZATIONS=0, works around crashes with some nvidia drivers
+    #[serde(default)]
+    pub disable_gl_threaded_optimizations: HashMap<String, bool>,
+    // shows debugging actions
+    #[serde(default)]
+    pub advanced_mode: bool,
+    // random machine id, decides which staged rollouts apply
+    #[serde(default)]
+    pub rollout_id: String,
+    // shows the running instance as the discord activity, needs the discord feature
+    #[serde(default)]
+    pub discord_presence: bool,
+    // override the values detected from the disk type and the available memory
+    #[serde(default)]
+    pub hash_concurrency: Option<usize>,
+    #[serde(default)]
+    pub hash_buffer_size_kb: Option<usize>,
+    // extra jvm args of the user, they win over the recommended ones
+    #[serde(default)]
+    pub jvm_args: HashMap<String, String>,
+    #[serde(default)]
+    pub disable_recommended_jvm_args: HashMap<String, bool>,
+    // pings the servers of the instances to sort and badge them, off by default
+    #[serde(default)]
+    pub server_status: bool,
+    // set for the game process on top of the launcher environment, in the listed order
+    #[serde(default)]
+    pub env_vars: HashMap<String, Vec<(String, String)>>,
+    // linux only, applied through env vars and the native GLFW
+    #[serde(default)]
+    pub display_server: HashMap<String, DisplayServer>,
+    #[serde(default)]
+    pub prefer_discrete_gpu: HashMap<String, bool>,
+    // keeps the saved accounts in a file encrypted with a machine-bound key
+    #[serde(default)]
+    pub encrypt_auth_data: bool,
+    // the ceiling of parallel download connections, the downloader still lowers it on timeouts
+    #[serde(default)]
+    pub max_connections: Option<usize>,
+    // how many times the game is relaunched after exiting with an error, unset is 0
+    #[serde(default)]
+    pub restart_on_crash: HashMap<String, u32>,
+    // by the download server base, reused by syncs until MIRROR_RANKING_TTL passes
+    #[serde(default)]
+    pub mirror_rankings: HashMap<String, MirrorRanking>,
+    // missing in the configs written before the migrations were added
+    #[serde(default)]
+    pub config_version: u32,
+    #[serde(skip)]
+    pub save_state: SaveState,
+    // set when the config couldn't be read and was reset, the old file was copied there
+    #[serde(skip)]
+    pub reset_config_backup: Option<PathBuf>,
+}
+
+const CONFIG_FILENAME: &str = "config.json";
+
+fn rename_key<V>(map: &mut HashMap<String, V>, old_name: &str, new_name: &str) {
+    if let Some(value) = map.remove(old_name) {
+        map.insert(new_name.to_string(), value);
+    }
+}
+
+fn get_config_path() -> PathBuf {
+    get_data_dir().join(CONFIG_FILENAME)
+}
+
+impl Config {
+    pub fn load() -> Config {
+        let config_path = get_config_path();
+        if !config_path.exists() {
+            return Config::new_default();
+        }
+        let data = std::fs::read(&config_path).expect("Failed to read config file");
+        let Some(mut config) = migration::parse_config(&data) else {
+            let mut config = Config::new_default();
+            // the next save would overwrite the old settings
+            let backup_path = config_path.with_extension("json.bak");
+            match std::fs::copy(&config_path, &backup_path) {
+                Ok(_) => config.reset_config_backup = Some(backup_path),
+                Err(e) => warn!("Failed to back up the unreadable config:\n{e}"),
+            }
+            return config;
+        };
+        let mut changed = config.config_version != CURRENT_CONFIG_VERSION;
+        config.config_version = CURRENT_CONFIG_VERSION;
+        // configs from older versions don't have it yet
+        if config.rollout_id.is_empty() {
+            config.rollout_id = rollout::generate_rollout_id();
+            changed = true;
+        }
+        if changed {
+            config.save_now();
+        }
+        config
+    }
+
+    pub(super) fn new_default() -> Config {
+        Config {
+            java_paths: HashMap::new(),
+            assets_dir: None,
+            data_dir: None,
+            xmx: HashMap::new(),
+            use_native_glfw: HashMap::new(),
+            selected_instance_name: None,
+            lang: constants::DEFAULT_LANG,
+            hide_launcher_after_launch: true,
+            auth_profiles: HashMap::new(),
+            extra_version_manifest_urls: Vec::new(),
+            selected_version_manifest_url: build_config::get_default_version_manifest_url(),
+            manifest_public_keys: HashMap::new(),
+            manifest_news_urls: HashMap::new(),
+            read_news: HashMap::new(),
+            rotational_disk: false,
+            shared_data: HashMap::new(),
+            pause_auto_sync: false,
+            deduplicate_files: false,
+            use_file_store: false,
+            launch_profiles: Vec::new(),
+            last_launch_profile: None,
+            disable_gl_threaded_optimizations: HashMap::new(),
+            advanced_mode: false,
+            rollout_id: rollout::generate_rollout_id(),
+            discord_presence: false,
+            hash_concurrency: None,
+            hash_buffer_size_kb: None,
+            jvm_args: HashMap::new(),
+            disable_recommended_jvm_args: HashMap::new(),
+            server_status: false,
+            env_vars: HashMap::new(),
+            display_server: HashMap::new(),
+            prefer_discrete_gpu: HashMap::new(),
+            encrypt_auth_data: false,
+            max_connections: None,
+            restart_on_crash: HashMap::new(),
+            mirror_rankings: HashMap::new(),
+            config_version: CURRENT_CONFIG_VERSION,
+            save_state: SaveState::default(),
+            reset_config_backup: None,
+        }
+    }
+
+    pub fn get_launcher_dir(&self) -> PathBuf {
+        let data_dir = match &self.data_dir {
+            None => dirs::data_dir()
+                .expect("Failed to get data directory")
+                .join(build_config::get_lower_launcher_name()),
+
+            Some(dir) => dir.clone(),
+        };
+        if !data_dir.exists() {
+            std::fs::create_dir_all(&data_dir).expect("Failed to create launcher directory");
+        }
+        data_dir
+    }
+
+    pub fn get_assets_dir(&self) -> PathBuf {
+        let assets_dir = match &self.assets_dir {
+            Some(dir) => dir.clone(),
+            None => self.get_launcher_dir().join("assets"),
+        };
+        if !assets_dir.exists() {
+            std::fs::create_dir_all(&assets_dir).expect("Failed to create assets directory");
+        }
+        assets_dir
+    }
+
+    pub fn get_rollout_bucket(&self) -> u8 {
+        rollout::get_rollout_bucket(&self.rollout_id)
+    }
+
+    pub fn get_hash_limits(&self) -> HashLimits {
+        let low_memory = get_available_memory_mb()
+            .is_some_and(|available| available < constants::LOW_MEMORY_THRESHOLD);
+        let concurrency = self.hash_concurrency.unwrap_or_else(|| {
+            if self.rotational_disk {
+                constants::ROTATIONAL_DISK_HASH_CONCURRENCY
+            } else if low_memory {
+                num_cpus::get().min(constants::LOW_MEMORY_HASH_CONCURRENCY)
+            } else {
+                num_cpus::get()
+            }
+        });
+        let buffer_size = match self.hash_buffer_size_kb {
+            Some(size_kb) => size_kb * 1024,
+            None if low_memory => constants::LOW_MEMORY_HASH_BUFFER_SIZE,
+            None => HASH_BUFFER_SIZE,
+        };
+        HashLimits {
+            concurrency: concurrency.max(1),
+            buffer_size: buffer_size.max(1024),
+        }
+    }
+
+    pub fn get_max_connections(&self) -> usize {
+        self.max_connections
+            .unwrap_or(DEFAULT_MAX_CONNECTIONS)
+            .clamp(1, DEFAULT_MAX_CONNECTIONS)
+    }
+
+    pub fn get_sync_settings(&self) -> SyncSettings {
+        SyncSettings {
+            max_connections: self.get_max_connections(),
+            use_file_store: self.use_file_store,
+        }
+    }
+
+    pub fn get_effective_version_manifest_url(&self) -> &str {
+        &self.selected_version_manifest_url
+    }
+
+    pub fn get_manifest_public_key(&self, manifest_url: &str) -> Option<String> {
+        if manifest_url == build_config::get_default_version_manifest_url() {
+            build_config::get_manifest_public_key()
+        } else {
+            self.manifest_public_keys.get(manifest_url).cloned()
+        }
+    }
+
+    pub fn get_news_url(&self, manifest_url: &str) -> Option<String> {
+        if manifest_url == build_config::get_default_version_manifest_url() {
+            build_config::get_news_url()
+        } else {
+            self.manifest_news_urls.get(manifest_url).cloned()
+        }
+    }
+
+    pub fn add_version_manifest_url(&mut self, url: String, public_key: String, news_url: String) {
+        let url_trimmed = url.trim().to_string();
+        if url_trimmed.is_empty() {
+            return;
+        }
+        if url_trimmed == build_config::get_default_version_manifest_url() {
+            return;
+        }
+        if !self
+            .extra_version_manifest_urls
+            .iter()
+            .any(|u| u == &url_trimmed)
+        {
+            let public_key = public_key.trim().to_string();
+            if !public_key.is_empty() {
+                self.manifest_public_keys
+                    .insert(url_trimmed.clone(), public_key);
+            }
+            let news_url = news_url.trim().to_string();
+            if !news_url.is_empty() {
+                self.manifest_news_urls
+                    .insert(url_trimmed.clone(), news_url);
+            }
+            self.extra_version_manifest_urls.push(url_trimmed);
+            self.save();
+        }
+    }
+
+    pub fn remove_version_manifest_url(&mut self, url: &str) {
+        self.extra_version_manifest_urls.retain(|u| u != url);
+        self.manifest_public_keys.remove(url);
+        if let Some(news_url) = self.manifest_news_urls.remove(url) {
+            self.read_news.remove(&news_url);
+        }
+        if self.selected_version_manifest_url == url {
+            self.selected_version_manifest_url = build_config::get_default_version_manifest_url();
+        }
+        self.save();
+    }
+
+    pub fn get_selected_auth_profile(&self) -> Option<&AuthProfile> {
+        self.auth_profiles
+            .get(self.selected_instance_name.as_ref()?)
+    }
+
+    pub fn set_selected_auth_profile(&mut self, auth_profile: AuthProfile) {
+        if let Some(selected_instance_name) = &self.selected_instance_name {
+            self.auth_profiles
+                .insert(selected_instance_name.clone(), auth_profile);
+            self.save();
+        } else {
+            warn!("Failed to set selected auth profile: no selected instance name");
+        }
+    }
+
+    pub fn clear_selected_auth_profile(&mut self) {
+        if let Some(selected_instance_name) = &self.selected_instance_name {
+            self.auth_profiles.remove(selected_instance_name);
+            self.save();
+        } else {
+            warn!("Failed to clear selected auth profile: no selected instance name");
+        }
+    }
+
+    // sorted names of the instances that have the account selected
+    pub fn get_instances_using_account(&self, auth_profile: &AuthProfile) -> Vec<String> {
+        let mut instance_names: Vec<_> = self
+            .auth_profiles
+            .iter()
+            .filter(|(_, profile)| *profile == auth_profile)
+            .map(|(instance_name, _)| instance_name.clone())
+            .collect();
+        instance_names.sort();
+        instance_names
+    }
+
+    pub fn clear_auth_profile(&mut self, auth_profile: &AuthProfile) {
+        self.auth_profiles
+            .retain(|_, profile| profile != auth_profile);
+        self.save();
+    }
+
+    pub fn get_launch_profile(&self, name: &str) -> Option<&LaunchProfile> {
+        self.launch_profiles
+            .iter()
+            .find(|profile| profile.name == name)
+    }
+
+    // the last used profile, unless the instance or account was changed since
+    pub fn get_active_launch_profile(&self) -> Option<&LaunchProfile> {
+        let profile = self.get_launch_profile(self.last_launch_profile.as_ref()?)?;
+        (profile.manifest_url == self.selected_version_manifest_url
+            && self.selected_instance_name.as_ref() == Some(&profile.instance_name)
+            && self.get_selected_auth_profile() == Some(&profile.auth_profile))
+        .then_some(profile)
+    }
+
+    // switches the selected instance and account, false if there is no such profile
+    pub fn select_launch_profile(&mut self, name: &str) -> bool {
+        let Some(profile) = self.get_launch_profile(name).cloned() else {
+            return false;
+        };
+        self.selected_version_manifest_url = profile.manifest_url;
+        self.selected_instance_name = Some(profile.instance_name);
+        self.last_launch_profile = Some(profile.name);
+        self.set_selected_auth_profile(profile.auth_profile);
+        true
+    }
+
+    pub fn remove_launch_profile(&mut self, name: &str) {
+        self.launch_profiles.retain(|profile| profile.name != name);
+        if self.last_launch_profile.as_deref() == Some(name) {
+            self.last_launch_profile = None;
+        }
+        self.save();
+    }
+
+    // moves the per-instance settings to the new name, saved by the caller
+    pub fn rename_instance(&mut self, old_name: &str, new_name: &str) {
+        rename_key(&mut self.java_paths, old_name, new_name);
+        rename_key(&mut self.xmx, old_name, new_name);
+        rename_key(&mut self.use_native_glfw, old_name, new_name);
+        rename_key(&mut self.auth_profiles, old_name, new_name);
+        rename_key(&mut self.shared_data, old_name, new_name);
+        rename_key(
+            &mut self.disable_gl_threaded_optimizations,
+            old_name,
+            new_name,
+        );
+        rename_key(&mut self.jvm_args, old_name, new_name);
+        rename_key(&mut self.disable_recommended_jvm_args, old_name, new_name);
+        rename_key(&mut self.env_vars, old_name, new_name);
+        rename_key(&mut self.display_server, old_name, new_name);
+        rename_key(&mut self.prefer_discrete_gpu, old_name, new_name);
+        rename_key(&mut self.restart_on_crash, old_name, new_name);
+        for profile in &mut self.launch_profiles {
+            if profile.instance_name == old_name {
+                profile.instance_name = new_name.to_string();
+            }
+        }
+        if self.selected_instance_name.as_deref() == Some(old_name) {
+            self.selected_instance_name = Some(new_name.to_string());
+        }
+    }
+
+    // saves only a ranking that changed, a reused one is already there
+    pub fn cache_mirror_ranking(&mut self, download_server_base: &str, ranking: &MirrorRanking) {
+        if self.mirror_rankings.get(download_server_base) != Some(ranking) {
+            self.mirror_rankings
+                .insert(download_server_base.to_string(), ranking.clone());
+            self.save();
+        }
+    }
+
+    pub fn get_xmx(&self, instance_name: &str) -> Option<&String> {
+        self.get_active_launch_profile()
+            .filter(|profile| profile.instance_name == instance_name)
+            .and_then(|profile| profile.xmx.as_ref())
+            .or_else(|| self.xmx.get(instance_name))
+    }
+
+    // written right away unless the config was written less than SAVE_INTERVAL ago,
+    // then save_if_due writes it later
+    pub fn save(&self) {
+        self.save_state.0.lock().unwrap().dirty = true;
+        if !self.save_if_due_at(&get_config_path(), Instant::now()) {
+            *UNSAVED_CONFIG.lock().unwrap() = Some(self.serialize());
+        }
+    }
+
+    // should be called periodically while there is a pending save
+    pub fn save_if_due(&self) {
+        self.save_if_due_at(&get_config_path(), Instant::now());
+    }
+
+    fn save_if_due_at(&self, config_path: &Path, now: Instant) -> bool {
+        let mut state = self.save_state.0.lock().unwrap();
+        if !state.dirty
+            || state
+                .last_write
+                .is_some_and(|last_write| now.duration_since(last_write) < SAVE_INTERVAL)
+        {
+            return false;
+        }
+        state.dirty = false;
+        state.last_write = Some(now);
+        self.write(config_path);
+        true
+    }
+
+    pub fn has_pending_save(&self) -> bool {
+        self.save_state.0.lock().unwrap().dirty
+    }
+
+    // for exiting, when there won't be another save_if_due
+    pub fn save_pending(&self) {
+        if self.has_pending_save() {
+            self.save_now();
+        }
+    }
+
+    // skips the debounce, for changes that must not be lost
+    pub fn save_now(&self) {
+        let mut state = self.save_state.0.lock().unwrap();
+        state.dirty = false;
+        state.last_write = Some(Instant::now());
+        self.write(&get_config_path());
+    }
+
+    fn serialize(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Failed to serialize config")
+    }
+
+    fn write(&self, config_path: &Path) {
+        let mut unsaved_config = UNSAVED_CONFIG.lock().unwrap();
+        *unsaved_config = None;
+        write_config_str(config_path, &self.serialize());
+    }
+}
+
+const LOGS_FILENAME: &str = "launcher.log";
+
+pub fn get_logs_path() -> PathBuf {
+    let logs_dir = get_logs_dir(&get_data_dir());
+    if !logs_dir.exists() {
+        std::fs::create_dir_all(&logs_dir).expect("Failed to create logs directory");
+    }
+    logs_dir.join(LOGS_FILENAME)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debounced_save() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        let config_path = dir.join(CONFIG_FILENAME);
+
+        let mut config: Config = serde_json::from_str(
+            r#"{
+                "java_paths": {},
+                "xmx": {},
+                "use_native_glfw": {},
+                "lang": "English",
+                "hide_launcher_after_launch": true,
+                "auth_profiles": {}
+            }"#,
+        )
+        .unwrap();
+
+        // a slider dragged for two seconds
+        let start = Instant::now();
+        let mut writes = 0;
+        for i in 0..100 {
+            config
+                .xmx
+                .insert("instance".to_string(), format!("{}M", 1024 + i));
+            config.save_state.0.lock().unwrap().dirty = true;
+            if config.save_if_due_at(&config_path, start + Duration::from_millis(i * 20)) {
+                writes += 1;
+            }
+        }
+        assert!(writes <= 2, "{writes} writes");
+        assert!(config.has_pending_save());
+
+        assert!(config.save_if_due_at(&config_path, start + Duration::from_secs(5)));
+        assert!(!config.has_pending_save());
+        assert!(!config.save_if_due_at(&config_path, start + Duration::from_secs(10)));
+
+        let saved: Config =
+            serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+        assert_eq!(saved.xmx["instance"], "1123M");
+        assert!(!config_path.with_extension("json.tmp").exists());
+    }
+
+    #[test]
+    fn test_non_ascii_paths() {
+        // the data dir of users with cyrillic or cjk names
+        let temp_dir = tempfile::Builder::new()
+            .prefix("launcher_core_config_Пользователь_ユーザー_")
+            .tempdir()
+            .unwrap();
+        let dir = temp_dir.path();
+        let config_path = dir.join(CONFIG_FILENAME);
+
+        let mut config: Config = serde_json::from_str(
+            r#"{
+                "java_paths": {"old": "C:\\Users\\Иван\\java.exe"},
+                "xmx": {},
+                "use_native_glfw": {},
+                "lang": "Russian",
+                "hide_launcher_after_launch": true,
+                "auth_profiles": {}
+            }"#,
+        )
+        .unwrap();
+        let java_path = dir.join("java").join("bin").join("java");
+        config
+            .java_paths
+            .insert("Сборка".to_string(), java_path.clone());
+        config.data_dir = Some(dir.join("data"));
+        config.write(&config_path);
+
+        let saved: Config =
+            serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+        assert_eq!(saved.java_paths["Сборка"], java_path);
+        assert_eq!(
+            saved.java_paths["old"],
+            PathBuf::from("C:\\Users\\Иван\\java.exe")
+        );
+        assert_eq!(saved.get_launcher_dir(), dir.join("data"));
+        assert!(dir.join("data").is_dir());
+    }
+}
@@ -0,0 +1,41 @@
+use crate::lang::Lang;
+
+pub const DEFAULT_LANG: Lang = Lang::English;
+
+pub const XMX_MIN: u64 = 512;
+pub const XMX_MAX: u64 = 65536;
+pub const XMX_STEP: u64 = 512;
+pub const XMX_DEFAULT: u64 = 4096;
+// left for the OS and other apps when capping Xmx
+pub const XMX_RESERVED_MEMORY: u64 = 2048;
+// values above this share of physical memory are shown with a warning
+pub const XMX_WARNING_RATIO: f64 = 0.75;
+// a 32-bit JVM can't reserve a larger heap
+pub const XMX_MAX_32_BIT: u64 = 1536;
+
+// seek-bound drives get slower when many files are read at once
+pub const ROTATIONAL_DISK_HASH_CONCURRENCY: usize = 2;
+
+// with less available memory (MB) hashing backs off, so the launcher and the game don't swap
+pub const LOW_MEMORY_THRESHOLD: u64 = 2048;
+pub const LOW_MEMORY_HASH_CONCURRENCY: usize = 2;
+pub const LOW_MEMORY_HASH_BUFFER_SIZE: usize = 32 * 1024;
+
+// game window size unless a launch profile overrides it
+pub const DEFAULT_WINDOW_SIZE: (u32, u32) = (925, 530);
+
+// token and certificate validation tolerates only a small clock difference
+pub const CLOCK_SKEW_WARNING_SECS: i64 = 5 * 60;
+
+// graphics initialization failures crash the game before this
+pub const GPU_ERROR_EARLY_EXIT_SECS: u64 = 15;
+
+// the limit of the per-instance automatic restarts after a crash
+pub const MAX_RESTARTS_ON_CRASH: u32 = 10;
+// the game running this long resets the used restarts
+pub const RESTART_BUDGET_RESET_SECS: u64 = 5 * 60;
+// the account is refreshed before a restart after a crash, the crash is shown if it takes longer
+pub const RESTART_AUTH_TIMEOUT_SECS: u64 = 30;
+
+// antivirus scanning makes the first launch after a sync this large slow
+pub const ANTIVIRUS_LARGE_SYNC_FILES: usize = 1000;
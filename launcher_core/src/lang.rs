@@ -0,0 +1,2008 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::hash::{BuildHasherDefault, Hasher};
+use std::mem::Discriminant;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lang {
+    English,
+    Russian,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum LangMessage {
+    AuthMessage {
+        url: String,
+    },
+    DeviceAuthMessage {
+        url: String,
+        code: String,
+    },
+    AuthTimeout,
+    UnknownAuthError,
+    AuthorizeUsing(String),
+    Authorizing,
+    SelectInstance,
+    NotSelected,
+    NoInstances,
+    CheckingFiles,
+    DownloadingFiles,
+    SyncInstance,
+    InstanceNotSynced,
+    InstanceSynced,
+    NoConnectionToSyncServer,
+    InstanceSyncError,
+    CheckingJava,
+    DownloadingJava,
+    JavaInstalled {
+        version: String,
+    },
+    NeedJava {
+        version: String,
+    },
+    UnknownErrorDownloadingJava,
+    NoConnectionToJavaServer,
+    UnknownJavaVersion,
+    Settings,
+    InstanceSettings,
+    SelectedJavaPath,
+    NoJavaPath,
+    AllocatedMemory,
+    SelectJavaPath,
+    #[cfg(target_os = "linux")]
+    UseNativeGlfw,
+    Launch,
+    LaunchError,
+    ProcessErrorCode(String),
+    Running,
+    LanguageName,
+    DownloadingUpdate,
+    CheckingForUpdates,
+    Launching,
+    ErrorCheckingForUpdates,
+    ErrorDownloadingUpdate,
+    NoConnectionToUpdateServer,
+    ErrorReadOnly,
+    ProceedToLauncher,
+    Authorization,
+    ForceOverwrite,
+    ForceOverwriteWarning,
+    KillMinecraft,
+    HideLauncherAfterLaunch,
+    DownloadAndLaunch,
+    CancelLaunch,
+    CancelDownload,
+    Retry,
+    OpenLogs,
+    LoadingMetadata,
+    MetadataErrorOffline,
+    MetadataFetchError,
+    NewInstance,
+    NewInstanceName,
+    GameVersion,
+    Loader,
+    LoaderVersion,
+    InstanceNameExists,
+    CreateInstance,
+    CreatingInstance,
+    Cancel,
+    InstanceGenerateErrorOffline,
+    InstanceGenerateError,
+    LongTimeWarning,
+    DeleteInstance,
+    SelectInstanceToDelete,
+    ConfirmDelete,
+    Delete,
+    AddAccount,
+    SelectAccount,
+    AddAndAuthenticate,
+    Offline,
+    FetchingRemote,
+    ErrorFetchingRemote,
+    InstanceSyncProgress,
+    AddOfflineAccount,
+    EnterNickname,
+    GettingMetadata,
+    NoMetadata,
+    ReadLocalRemoteError,
+    ReadLocalOffline,
+    ErrorGettingMetadata,
+    InvalidJavaInstallation,
+    AddManifestUrl,
+    ManifestSource,
+    Default,
+    CustomManifests,
+    EnterManifestUrl,
+    Add,
+    OfflineMode,
+    DeleteExtraFiles,
+    DeleteExtraFilesWarning,
+    AlwaysAllowForInstance,
+    KeepFiles,
+    ManifestPublicKey,
+    InvalidManifestSignature,
+    InvalidMetadataSignature,
+    ContentHash,
+    CopyContentHash,
+    LauncherOutdated(String),
+    UpdateLauncher,
+    RotationalDisk,
+    RotationalDiskHint,
+    CopyLink,
+    CopyCode,
+    AuthCodeExpiresIn(String),
+    AuthConnectionLost,
+    OpenLauncherFolder,
+    OpenInstanceFolder,
+    RemoveManifest,
+    RemoveAccount,
+    Refresh,
+    InstanceDescription,
+    DescriptionOffline,
+    ErrorGettingDescription,
+    XmxTooHigh,
+    Java32BitXmxLimit(String),
+    SharedDataDir,
+    SharedDataDirNotSet,
+    SelectSharedDataDir,
+    StopSharingData,
+    InstanceRunningSync,
+    KillAndSync,
+    InstanceRunningElsewhere,
+    AutoSyncOnStartup,
+    PauseAutoSync,
+    AutoSyncingInstance(String),
+    DeduplicateFiles,
+    DeduplicateFilesHint,
+    DeduplicatingFiles,
+    BackgroundTasks(usize),
+    NoBackgroundTasks,
+    TaskFetchManifest,
+    TaskFetchMetadata(String),
+    TaskSyncInstance(String),
+    TaskCheckJava(String),
+    TaskDownloadJava(String),
+    CheckOnly,
+    NoUpdatesFound,
+    UpdateAvailable {
+        files: usize,
+        size: u64,
+        size_unknown: bool,
+    },
+    InstanceCheckError,
+    TaskCheckInstance(String),
+    SelectLaunchProfile,
+    NoLaunchProfiles,
+    InvalidLaunchProfile,
+    DeleteLaunchProfile,
+    NewLaunchProfile,
+    LaunchProfileName,
+    LaunchProfileNameExists,
+    WindowSize,
+    ClockSkewAuthError,
+    ClockSkewWarning(i64),
+    PinVersion,
+    PinnedUnpinToUpdate,
+    GpuErrorTitle,
+    GpuErrorDescription(String),
+    DisableGlThreadedOptimizations,
+    EnableNativeGlfwAndRelaunch,
+    AntivirusNoticeTitle,
+    AntivirusNotice,
+    OpenWindowsSecurity,
+    AdvancedMode,
+    CopyLaunchCommand,
+    CopyLaunchCommandHint,
+    ArchiveInstance,
+    ArchiveInstanceHint,
+    ArchivingInstance,
+    RestoringInstance,
+    TaskArchiveInstance(String),
+    TaskRestoreInstance(String),
+    InstanceArchived,
+    RestoreInstance,
+    ArchiveInstanceError,
+    RestoreInstanceError,
+    Archived,
+    ArchivedInstances,
+    NoArchivedInstances,
+    ArchiveSize {
+        instance_name: String,
+        size: u64,
+    },
+    DownloadServerUnstable {
+        host: String,
+        failure_percent: u64,
+    },
+    FromVersionJsonUrl,
+    VersionJsonUrl,
+    IncompleteVersionMetadata {
+        missing_fields: String,
+    },
+    Accounts,
+    NoAccounts,
+    AccountUsedByInstances,
+    AccountNotUsed,
+    AccountInstances {
+        instance_names: String,
+    },
+    AccountLastUsed {
+        days_ago: Option<u64>,
+    },
+    GameOptions,
+    GameLanguage,
+    MaxFps,
+    Unlimited,
+    Fullscreen,
+    Vsync,
+    GameOptionsError,
+    InstanceUpToDate,
+    InstanceOutdated,
+    InstanceNotInstalled,
+    InstanceLocalOnly,
+    ErrorDetails,
+    SourceUrl,
+    JsonPath,
+    ErrorMessage,
+    CopyDetails,
+    OpenInBrowser,
+    News,
+    NoNews,
+    NewsUnread,
+    NewsPublished {
+        days_ago: u64,
+    },
+    NewsUrl,
+    UpdatePendingRollout,
+    Screenshots,
+    NoScreenshots,
+    OpenScreenshot,
+    CopyScreenshot,
+    DeleteScreenshot,
+    TemporaryFiles {
+        size: u64,
+    },
+    PurgeTemporaryFiles,
+    NoMinecraftProfile,
+    NoMinecraftProfileHint,
+    FamilyConsentRequired,
+    FamilyConsentRequiredHint,
+    OfflineUuid,
+    OfflineUuidMojang,
+    OfflineUuidRandom,
+    OfflineUuidManual,
+    InvalidUuid,
+    AccountUuid {
+        uuid: String,
+    },
+    DiscordPresence,
+    HashingProgress {
+        files: u64,
+        bytes_per_sec: u64,
+    },
+    ImportInstance,
+    ImportFromArchive,
+    ImportFromFolder,
+    Import,
+    ImportingInstance,
+    NotExportedInstance,
+    NotMinecraftFolder,
+    ImportError,
+    RecommendedJvmArgs,
+    UseRecommendedJvmArgs,
+    JvmArgs,
+    FromMrpack,
+    SelectMrpack,
+    InvalidModpack(String),
+    ShowServerStatus,
+    ServerPlayers {
+        online: u32,
+        max: u32,
+    },
+    ServerOffline,
+    Changelog,
+    EnvVars,
+    AddEnvVar,
+    InvalidEnvVarName,
+    RemoveEnvVar,
+    DisplayServer,
+    DisplayServerAuto,
+    PreferDiscreteGpu,
+    ConfigResetTitle,
+    ConfigResetNotice,
+    AccountSessionActive,
+    AccountSessionSaved,
+    AccountTokenExpiry {
+        expires_in_secs: i64,
+    },
+    Reauthenticate,
+    EncryptAuthData,
+    EncryptAuthDataHint,
+    MaxConnections,
+    MaxConnectionsHint,
+    UseFileStore,
+    UseFileStoreHint,
+    CleanFileStore,
+    FileStoreCleaned {
+        size: u64,
+    },
+    DiskUsage,
+    InstanceSize {
+        size: u64,
+    },
+    LibrariesSize {
+        size: u64,
+    },
+    AssetsSize {
+        size: u64,
+        total: u64,
+    },
+    SharedFilesHint,
+    FolderSize {
+        dir: String,
+        size: u64,
+    },
+    ClearFolder,
+    ConfirmClearFolders,
+    ManageJava,
+    JavaRuntimes,
+    DownloadedJava,
+    NoDownloadedJava,
+    JavaRuntime {
+        name: String,
+        version: Option<String>,
+        valid: bool,
+    },
+    DeleteJava,
+    JavaUsedBy {
+        instances: String,
+    },
+    SystemJava,
+    RedetectSystemJava,
+    NoSystemJava,
+    DetectedJava,
+    GameCrashed,
+    GameLogTail,
+    OpenCrashReport,
+    CopyCrashReport,
+    NoCrashReport,
+    ShowCrashDetails,
+    GameOutput,
+    FilterGameOutput,
+    RestartOnCrash,
+    RestartOnCrashHint,
+    RestartedAfterCrash {
+        attempt: u32,
+        max: u32,
+    },
+    Playtime {
+        hours: u64,
+        minutes: u64,
+    },
+    RenameInstance,
+    RenameOnlyLocalInstances,
+    InvalidInstanceName,
+    Rename,
+    CachedManifest {
+        hours_ago: u64,
+    },
+    DownloadingFrom(String),
+    ExportInstance,
+    ExportInstanceHint,
+    ExportingInstance,
+    TaskExportInstance(String),
+    ExportInstanceError,
+    RestartingAfterCrash {
+        attempt: u32,
+        max: u32,
+    },
+}
+
+// the largest unit that keeps the value at least 1
+fn format_size(size: u64, lang: Lang) -> String {
+    let units = match lang {
+        Lang::English => ["B", "KB", "MB", "GB"],
+        Lang::Russian => ["Б", "КБ", "МБ", "ГБ"],
+    };
+    let mut value = size as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < units.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{size} {}", units[0])
+    } else {
+        format!("{value:.1} {}", units[unit])
+    }
+}
+
+impl LangMessage {
+    pub fn to_string(&self, lang: Lang) -> String {
+        match self {
+            LangMessage::AuthMessage { url: _ } => match lang {
+                Lang::English => {
+                    "Authorize in the browser window.\nOr open the link manually.".to_string()
+                }
+                Lang::Russian => {
+                    "Авторизуйтесь в открывшемся окне браузера.\nИли откройте ссылку вручную."
+                        .to_string()
+                }
+            },
+            LangMessage::DeviceAuthMessage { url: _, code } => match lang {
+                Lang::English => {
+                    format!("Authorize in the browser window.\nOr open the link manually and enter the code: {code}")
+                }
+                Lang::Russian => {
+                    format!("Авторизуйтесь в открывшемся окне браузера.\nИли откройте ссылку вручную и введите код: {code}")
+                }
+            },
+            LangMessage::AuthTimeout => match lang {
+                Lang::English => "Authorization timeout".to_string(),
+                Lang::Russian => "Превышено время авторизации".to_string(),
+            },
+            LangMessage::UnknownAuthError => match lang {
+                Lang::English => "Authorization error".to_string(),
+                Lang::Russian => "Ошибка авторизации".to_string(),
+            },
+            LangMessage::AuthorizeUsing(app_name) => match lang {
+                Lang::English => format!("Authorize using {app_name}"),
+                Lang::Russian => format!("Авторизуйтесь через {app_name}"),
+            },
+            LangMessage::Authorizing => match lang {
+                Lang::English => "Authorizing...".to_string(),
+                Lang::Russian => "Авторизация...".to_string(),
+            },
+            LangMessage::SelectInstance => match lang {
+                Lang::English => "Select instance:".to_string(),
+                Lang::Russian => "Выберите версию:".to_string(),
+            },
+            LangMessage::NotSelected => match lang {
+                Lang::English => "Not selected".to_string(),
+                Lang::Russian => "Не выбрано".to_string(),
+            },
+            LangMessage::NoInstances => match lang {
+                Lang::English => "No instances fetched".to_string(),
+                Lang::Russian => "Список версий пуст".to_string(),
+            },
+            LangMessage::CheckingFiles => match lang {
+                Lang::English => "Checking files...".to_string(),
+                Lang::Russian => "Проверка файлов...".to_string(),
+            },
+            LangMessage::DownloadingFiles => match lang {
+                Lang::English => "Downloading files...".to_string(),
+                Lang::Russian => "Загрузка файлов...".to_string(),
+            },
+            LangMessage::SyncInstance => match lang {
+                Lang::English => "Sync instance".to_string(),
+                Lang::Russian => "Синхронизировать версию".to_string(),
+            },
+            LangMessage::InstanceNotSynced => match lang {
+                Lang::English => "Instance not synced".to_string(),
+                Lang::Russian => "Версия не синхронизирована".to_string(),
+            },
+            LangMessage::InstanceSynced => match lang {
+                Lang::English => "Instance up-to-date".to_string(),
+                Lang::Russian => "Версия синхронизирована".to_string(),
+            },
+            LangMessage::NoConnectionToSyncServer => match lang {
+                Lang::English => "No connection to instance sync server".to_string(),
+                Lang::Russian => "Нет подключения к серверу синхронизации версий".to_string(),
+            },
+            LangMessage::InstanceSyncError => match lang {
+                Lang::English => "Error syncing instance".to_string(),
+                Lang::Russian => "Ошибка синхронизации версии".to_string(),
+            },
+            LangMessage::CheckingJava => match lang {
+                Lang::English => "Checking Java...".to_string(),
+                Lang::Russian => "Проверка Java...".to_string(),
+            },
+            LangMessage::DownloadingJava => match lang {
+                Lang::English => "Downloading Java...".to_string(),
+                Lang::Russian => "Загрузка Java...".to_string(),
+            },
+            LangMessage::JavaInstalled { version } => match lang {
+                Lang::English => format!("Java {version} installed"),
+                Lang::Russian => format!("Java {version} установлена"),
+            },
+            LangMessage::NeedJava { version } => match lang {
+                Lang::English => format!("Java {version} not installed"),
+                Lang::Russian => format!("Java {version} не установлена"),
+            },
+            LangMessage::UnknownErrorDownloadingJava => match lang {
+                Lang::English => "Error downloading Java".to_string(),
+                Lang::Russian => "Ошибка загрузки Java".to_string(),
+            },
+            LangMessage::NoConnectionToJavaServer => match lang {
+                Lang::English => "No connection to Java download server".to_string(),
+                Lang::Russian => "Нет подключения к серверу загрузки Java".to_string(),
+            },
+            LangMessage::UnknownJavaVersion => match lang {
+                Lang::English => "Unknown Java version".to_string(),
+                Lang::Russian => "Неизвестная версия Java".to_string(),
+            },
+            LangMessage::Settings => match lang {
+                Lang::English => "Settings".to_string(),
+                Lang::Russian => "Настройки".to_string(),
+            },
+            LangMessage::InstanceSettings => match lang {
+                Lang::English => "Instance settings".to_string(),
+                Lang::Russian => "Настройки версии".to_string(),
+            },
+            LangMessage::SelectedJavaPath => match lang {
+                Lang::English => "Selected Java path:".to_string(),
+                Lang::Russian => "Выбранный путь к Java:".to_string(),
+            },
+            LangMessage::NoJavaPath => match lang {
+                Lang::English => "No Java path selected".to_string(),
+                Lang::Russian => "Путь к Java не выбран".to_string(),
+            },
+            LangMessage::AllocatedMemory => match lang {
+                Lang::English => "Allocated memory".to_string(),
+                Lang::Russian => "Выделенная память".to_string(),
+            },
+            LangMessage::SelectJavaPath => match lang {
+                Lang::English => "Select Java path".to_string(),
+                Lang::Russian => "Выберите путь к Java".to_string(),
+            },
+            #[cfg(target_os = "linux")]
+            LangMessage::UseNativeGlfw => match lang {
+                Lang::English => "Use native GLFW".to_string(),
+                Lang::Russian => "Использовать нативную версию GLFW".to_string(),
+            },
+            LangMessage::Launch => match lang {
+                Lang::English => "Launch".to_string(),
+                Lang::Russian => "Запустить".to_string(),
+            },
+            LangMessage::LaunchError => match lang {
+                Lang::English => "Error launching".to_string(),
+                Lang::Russian => "Ошибка запуска".to_string(),
+            },
+            LangMessage::ProcessErrorCode(e) => match lang {
+                Lang::English => format!("Process exited with code: {e}"),
+                Lang::Russian => format!("Процесс завершился с кодом: {e}"),
+            },
+            LangMessage::Running => match lang {
+                Lang::English => "Running...".to_string(),
+                Lang::Russian => "Запущено...".to_string(),
+            },
+            LangMessage::LanguageName => match lang {
+                Lang::English => "English".to_string(),
+                Lang::Russian => "Русский".to_string(),
+            },
+            LangMessage::DownloadingUpdate => match lang {
+                Lang::English => "Downloading update...".to_string(),
+                Lang::Russian => "Загрузка обновления...".to_string(),
+            },
+            LangMessage::CheckingForUpdates => match lang {
+                Lang::English => "Checking for updates...".to_string(),
+                Lang::Russian => "Проверка обновлений...".to_string(),
+            },
+            LangMessage::Launching => match lang {
+                Lang::English => "Launching...".to_string(),
+                Lang::Russian => "Запуск...".to_string(),
+            },
+            LangMessage::ErrorCheckingForUpdates => match lang {
+                Lang::English => "Error checking for updates".to_string(),
+                Lang::Russian => "Ошибка проверки обновлений".to_string(),
+            },
+            LangMessage::ErrorDownloadingUpdate => match lang {
+                Lang::English => "Error downloading update".to_string(),
+                Lang::Russian => "Ошибка загрузки обновления".to_string(),
+            },
+            LangMessage::NoConnectionToUpdateServer => match lang {
+                Lang::English => "No connection to update server".to_string(),
+                Lang::Russian => "Нет подключения к серверу обновлений".to_string(),
+            },
+            LangMessage::ErrorReadOnly => match lang {
+                Lang::English => {
+                    if cfg!(target_os = "macos") {
+                        "Error: read-only mode. If running from a disk image, copy to Applications"
+                            .to_string()
+                    } else {
+                        "Error: read-only mode".to_string()
+                    }
+                }
+                Lang::Russian => {
+                    if cfg!(target_os = "macos") {
+                        "Ошибка: режим только для чтения. Если лаунчер запущен из образа диска, скопируйте в Applications".to_string()
+                    } else {
+                        "Ошибка: режим только для чтения".to_string()
+                    }
+                }
+            },
+            LangMessage::ProceedToLauncher => match lang {
+                Lang::English => "Proceed to launcher".to_string(),
+                Lang::Russian => "Перейти к лаунчеру".to_string(),
+            },
+            LangMessage::Authorization => match lang {
+                Lang::English => "Authorization".to_string(),
+                Lang::Russian => "Авторизация".to_string(),
+            },
+            LangMessage::ForceOverwrite => match lang {
+                Lang::English => "Overwrite optional files".to_string(),
+                Lang::Russian => "Перезаписать необязательные файлы".to_string(),
+            },
+            LangMessage::ForceOverwriteWarning => match lang {
+                Lang::English => "Warning: this may overwrite such files as configs, server list, etc.".to_string(),
+                Lang::Russian => "Внимание: это может перезаписать такие файлы как настройки, список серверов и т.д.".to_string(),
+            },
+            LangMessage::KillMinecraft => match lang {
+                Lang::English => "Kill Minecraft".to_string(),
+                Lang::Russian => "Закрыть Minecraft".to_string(),
+            },
+            LangMessage::HideLauncherAfterLaunch => match lang {
+                Lang::English => "Hide launcher after launch".to_string(),
+                Lang::Russian => "Скрыть лаунчер после запуска".to_string(),
+            },
+            LangMessage::DownloadAndLaunch => match lang {
+                Lang::English => "Download and launch".to_string(),
+                Lang::Russian => "Загрузить и запустить".to_string(),
+            },
+            LangMessage::CancelLaunch => match lang {
+                Lang::English => "Cancel launch".to_string(),
+                Lang::Russian => "Отменить запуск".to_string(),
+            },
+            LangMessage::CancelDownload => match lang {
+                Lang::English => "Cancel download".to_string(),
+                Lang::Russian => "Отменить загрузку".to_string(),
+            },
+            LangMessage::Retry => match lang {
+                Lang::English => "Retry".to_string(),
+                Lang::Russian => "Попробовать снова".to_string(),
+            },
+            LangMessage::OpenLogs => match lang {
+                Lang::English => "Open logs folder".to_string(),
+                Lang::Russian => "Открыть папку с логами".to_string(),
+            },
+            LangMessage::LoadingMetadata => match lang {
+                Lang::English => "Loading metadata...".to_string(),
+                Lang::Russian => "Загрузка метаданных...".to_string(),
+            },
+            LangMessage::MetadataErrorOffline => match lang {
+                Lang::English => "No connection to metadata server".to_string(),
+                Lang::Russian => "Нет подключения к серверу метаданных".to_string(),
+            },
+            LangMessage::MetadataFetchError => match lang {
+                Lang::English => "Error fetching metadata".to_string(),
+                Lang::Russian => "Ошибка получения метаданных".to_string(),
+            },
+            LangMessage::NewInstance => match lang {
+                Lang::English => "New instance".to_string(),
+                Lang::Russian => "Новая версия".to_string(),
+            },
+            LangMessage::NewInstanceName => match lang {
+                Lang::English => "New instance name".to_string(),
+                Lang::Russian => "Название новой версии".to_string(),
+            },
+            LangMessage::GameVersion => match lang {
+                Lang::English => "Game version".to_string(),
+                Lang::Russian => "Версия игры".to_string(),
+            },
+            LangMessage::Loader => match lang {
+                Lang::English => "Loader".to_string(),
+                Lang::Russian => "Лоадер".to_string(),
+            },
+            LangMessage::LoaderVersion => match lang {
+                Lang::English => "Loader version".to_string(),
+                Lang::Russian => "Версия лоадера".to_string(),
+            },
+            LangMessage::InstanceNameExists => match lang {
+                Lang::English => "Instance name already exists".to_string(),
+                Lang::Russian => "Версия с таким именем уже существует".to_string(),
+            },
+            LangMessage::CreateInstance => match lang {
+                Lang::English => "Create instance".to_string(),
+                Lang::Russian => "Создать версию".to_string(),
+            },
+            LangMessage::CreatingInstance => match lang {
+                Lang::English => "Creating instance...".to_string(),
+                Lang::Russian => "Создание версии...".to_string(),
+            },
+            LangMessage::Cancel => match lang {
+                Lang::English => "Cancel".to_string(),
+                Lang::Russian => "Отмена".to_string(),
+            },
+            LangMessage::InstanceGenerateErrorOffline => match lang {
+                Lang::English => "Error generating instance: no connection".to_string(),
+                Lang::Russian => "Ошибка создания версии: нет подключения".to_string(),
+            },
+            LangMessage::InstanceGenerateError => match lang {
+                Lang::English => "Error generating instance".to_string(),
+                Lang::Russian => "Ошибка создания версии".to_string(),
+            },
+            LangMessage::LongTimeWarning => match lang {
+                Lang::English => "This may take a couple of minutes".to_string(),
+                Lang::Russian => "Это может занять несколько минут".to_string(),
+            },
+            LangMessage::DeleteInstance => match lang {
+                Lang::English => "Delete instance".to_string(),
+                Lang::Russian => "Удалить версию".to_string(),
+            },
+            LangMessage::SelectInstanceToDelete => match lang {
+                Lang::English => "Select instance to delete".to_string(),
+                Lang::Russian => "Выберите версию для удаления".to_string(),
+            },
+            LangMessage::ConfirmDelete => match lang {
+                Lang::English => "I understand that this action is irreversible".to_string(),
+                Lang::Russian => "Я понимаю, что назад пути нет".to_string(),
+            },
+            LangMessage::Delete => match lang {
+                Lang::English => "Delete".to_string(),
+                Lang::Russian => "Удалить".to_string(),
+            },
+            LangMessage::AddAccount => match lang {
+                Lang::English => "Add account".to_string(),
+                Lang::Russian => "Добавить аккаунт".to_string(),
+            },
+            LangMessage::SelectAccount => match lang {
+                Lang::English => "Select account".to_string(),
+                Lang::Russian => "Выберите аккаунт".to_string(),
+            },
+            LangMessage::AddAndAuthenticate => match lang {
+                Lang::English => "Add and authenticate".to_string(),
+                Lang::Russian => "Добавить и авторизоваться".to_string(),
+            },
+            LangMessage::Offline => match lang {
+                Lang::English => "Offline".to_string(),
+                Lang::Russian => "Офлайн".to_string(),
+            },
+            LangMessage::FetchingRemote => match lang {
+                Lang::English => "Fetching...".to_string(),
+                Lang::Russian => "Загрузка...".to_string(),
+            },
+            LangMessage::ErrorFetchingRemote => match lang {
+                Lang::English => "Error fetching".to_string(),
+                Lang::Russian => "Ошибка загрузки".to_string(),
+            },
+            LangMessage::InstanceSyncProgress => match lang {
+                Lang::English => "Instance sync progress".to_string(),
+                Lang::Russian => "Прогресс синхронизации версии".to_string(),
+            },
+            LangMessage::AddOfflineAccount => match lang {
+                Lang::English => "Add offline account".to_string(),
+                Lang::Russian => "Добавить офлайн аккаунт".to_string(),
+            },
+            LangMessage::EnterNickname => match lang {
+                Lang::English => "Enter nickname".to_string(),
+                Lang::Russian => "Введите никнейм".to_string(),
+            },
+            LangMessage::GettingMetadata => match lang {
+                Lang::English => "Getting metadata...".to_string(),
+                Lang::Russian => "Получение метаданных...".to_string(),
+            },
+            LangMessage::NoMetadata => match lang {
+                Lang::English => "No metadata".to_string(),
+                Lang::Russian => "Метаданные отсутствуют".to_string(),
+            },
+            LangMessage::ReadLocalRemoteError => match lang {
+                Lang::English => "Local metadata (fetch error)".to_string(),
+                Lang::Russian => "Локальные метаданные (ошибка загрузки)".to_string(),
+            },
+            LangMessage::ReadLocalOffline => match lang {
+                Lang::English => "Local metadata (offline)".to_string(),
+                Lang::Russian => "Локальные метаданные (офлайн)".to_string(),
+            },
+            LangMessage::ErrorGettingMetadata => match lang {
+                Lang::English => "Error getting metadata".to_string(),
+                Lang::Russian => "Ошибка получения метаданных".to_string(),
+            },
+            LangMessage::InvalidJavaInstallation => match lang {
+                Lang::English => "Invalid Java Installation".to_string(),
+                Lang::Russian => "Неверная установка Java".to_string(),
+            },
+            LangMessage::AddManifestUrl => match lang {
+                Lang::English => "➕ Add manifest URL".to_string(),
+                Lang::Russian => "➕ Добавить URL-адрес манифеста".to_string(),
+            },
+            LangMessage::ManifestSource => match lang {
+                Lang::English => "Manifest source".to_string(),
+                Lang::Russian => "Источник манифеста".to_string(),
+            },
+            LangMessage::Default => match lang {
+                Lang::English => "Default".to_string(),
+                Lang::Russian => "По умолчанию".to_string(),
+            },
+            LangMessage::CustomManifests => match lang {
+                Lang::English => "Custom manifests:".to_string(),
+                Lang::Russian => "Custom manifests:".to_string(),
+            },
+            LangMessage::EnterManifestUrl => match lang {
+                Lang::English => "Enter manifest URL".to_string(),
+                Lang::Russian => "Введите URL-адрес манифеста".to_string(),
+            },
+            LangMessage::Add => match lang {
+                Lang::English => "Add".to_string(),
+                Lang::Russian => "Добавить".to_string(),
+            },
+            LangMessage::OfflineMode => match lang {
+                Lang::English => "Offline mode".to_string(),
+                Lang::Russian => "Офлайн режим".to_string(),
+            },
+            LangMessage::DeleteExtraFiles => match lang {
+                Lang::English => "Delete extra files".to_string(),
+                Lang::Russian => "Удаление лишних файлов".to_string(),
+            },
+            LangMessage::DeleteExtraFilesWarning => match lang {
+                Lang::English => "These files are not part of the instance and will be deleted:".to_string(),
+                Lang::Russian => "Эти файлы не входят в версию и будут удалены:".to_string(),
+            },
+            LangMessage::AlwaysAllowForInstance => match lang {
+                Lang::English => "Always allow for this instance".to_string(),
+                Lang::Russian => "Всегда разрешать для этой версии".to_string(),
+            },
+            LangMessage::KeepFiles => match lang {
+                Lang::English => "Keep files".to_string(),
+                Lang::Russian => "Оставить файлы".to_string(),
+            },
+            LangMessage::ManifestPublicKey => match lang {
+                Lang::English => "Public key (optional)".to_string(),
+                Lang::Russian => "Публичный ключ (необязательно)".to_string(),
+            },
+            LangMessage::InvalidManifestSignature => match lang {
+                Lang::English => "invalid manifest signature".to_string(),
+                Lang::Russian => "неверная подпись манифеста".to_string(),
+            },
+            LangMessage::InvalidMetadataSignature => match lang {
+                Lang::English => "Metadata signature verification failed".to_string(),
+                Lang::Russian => "Не удалось проверить подпись метаданных".to_string(),
+            },
+            LangMessage::ContentHash => match lang {
+                Lang::English => "Content hash:".to_string(),
+                Lang::Russian => "Хеш содержимого:".to_string(),
+            },
+            LangMessage::CopyContentHash => match lang {
+                Lang::English => "Copy full content hash".to_string(),
+                Lang::Russian => "Скопировать полный хеш содержимого".to_string(),
+            },
+            LangMessage::LauncherOutdated(min_version) => match lang {
+                Lang::English => {
+                    format!("This instance requires launcher version {min_version} or newer")
+                }
+                Lang::Russian => {
+                    format!("Для этой сборки нужен лаунчер версии {min_version} или новее")
+                }
+            },
+            LangMessage::UpdateLauncher => match lang {
+                Lang::English => "Update launcher".to_string(),
+                Lang::Russian => "Обновить лаунчер".to_string(),
+            },
+            LangMessage::RotationalDisk => match lang {
+                Lang::English => "Launcher data is on an HDD".to_string(),
+                Lang::Russian => "Данные лаунчера на HDD".to_string(),
+            },
+            LangMessage::RotationalDiskHint => match lang {
+                Lang::English => "Check fewer files at once to speed up syncing on hard drives"
+                    .to_string(),
+                Lang::Russian => {
+                    "Проверять меньше файлов одновременно, чтобы ускорить синхронизацию на жёстких дисках"
+                        .to_string()
+                }
+            },
+            LangMessage::CopyLink => match lang {
+                Lang::English => "Copy link".to_string(),
+                Lang::Russian => "Скопировать ссылку".to_string(),
+            },
+            LangMessage::CopyCode => match lang {
+                Lang::English => "Copy code".to_string(),
+                Lang::Russian => "Скопировать код".to_string(),
+            },
+            LangMessage::AuthCodeExpiresIn(remaining) => match lang {
+                Lang::English => format!("The code expires in {remaining}"),
+                Lang::Russian => format!("Код истечёт через {remaining}"),
+            },
+            LangMessage::AuthConnectionLost => match lang {
+                Lang::English => "Connection lost, retrying...".to_string(),
+                Lang::Russian => "Соединение потеряно, повторяем попытку...".to_string(),
+            },
+            LangMessage::OpenLauncherFolder => match lang {
+                Lang::English => "Open launcher folder".to_string(),
+                Lang::Russian => "Открыть папку лаунчера".to_string(),
+            },
+            LangMessage::OpenInstanceFolder => match lang {
+                Lang::English => "Open instance folder".to_string(),
+                Lang::Russian => "Открыть папку версии".to_string(),
+            },
+            LangMessage::RemoveManifest => match lang {
+                Lang::English => "Remove manifest".to_string(),
+                Lang::Russian => "Удалить манифест".to_string(),
+            },
+            LangMessage::RemoveAccount => match lang {
+                Lang::English => "Remove account".to_string(),
+                Lang::Russian => "Удалить аккаунт".to_string(),
+            },
+            LangMessage::Refresh => match lang {
+                Lang::English => "Refresh (Ctrl+R)".to_string(),
+                Lang::Russian => "Обновить (Ctrl+R)".to_string(),
+            },
+            LangMessage::InstanceDescription => match lang {
+                Lang::English => "Description".to_string(),
+                Lang::Russian => "Описание".to_string(),
+            },
+            LangMessage::DescriptionOffline => match lang {
+                Lang::English => "Couldn't load the description, showing the saved copy".to_string(),
+                Lang::Russian => "Не удалось загрузить описание, показана сохранённая копия".to_string(),
+            },
+            LangMessage::ErrorGettingDescription => match lang {
+                Lang::English => "Error getting the description".to_string(),
+                Lang::Russian => "Ошибка получения описания".to_string(),
+            },
+            LangMessage::XmxTooHigh => match lang {
+                Lang::English => {
+                    "More than 75% of RAM is allocated, the game or the system may freeze"
+                        .to_string()
+                }
+                Lang::Russian => {
+                    "Выделено больше 75% памяти, игра или система могут зависать".to_string()
+                }
+            },
+            LangMessage::Java32BitXmxLimit(max) => match lang {
+                Lang::English => format!("32-bit Java can't use more than {max} of memory"),
+                Lang::Russian => format!("32-битная Java не может использовать больше {max} памяти"),
+            },
+            LangMessage::SharedDataDir => match lang {
+                Lang::English => "Shared data folder:".to_string(),
+                Lang::Russian => "Общая папка данных:".to_string(),
+            },
+            LangMessage::SharedDataDirNotSet => match lang {
+                Lang::English => "Not set, all data is kept in the instance folder".to_string(),
+                Lang::Russian => "Не выбрана, все данные хранятся в папке версии".to_string(),
+            },
+            LangMessage::SelectSharedDataDir => match lang {
+                Lang::English => "Select shared folder".to_string(),
+                Lang::Russian => "Выбрать общую папку".to_string(),
+            },
+            LangMessage::StopSharingData => match lang {
+                Lang::English => "Stop sharing".to_string(),
+                Lang::Russian => "Не использовать".to_string(),
+            },
+            LangMessage::InstanceRunningSync => match lang {
+                Lang::English => {
+                    "The game is running, syncing now can break the instance".to_string()
+                }
+                Lang::Russian => "Игра запущена, синхронизация сейчас может сломать версию"
+                    .to_string(),
+            },
+            LangMessage::KillAndSync => match lang {
+                Lang::English => "Kill and sync".to_string(),
+                Lang::Russian => "Закрыть игру и синхронизировать".to_string(),
+            },
+            LangMessage::InstanceRunningElsewhere => match lang {
+                Lang::English => {
+                    "The instance is running in another launcher, close the game to sync"
+                        .to_string()
+                }
+                Lang::Russian => {
+                    "Версия запущена в другом лаунчере, закройте игру для синхронизации"
+                        .to_string()
+                }
+            },
+            LangMessage::AutoSyncOnStartup => match lang {
+                Lang::English => "Sync automatically on startup".to_string(),
+                Lang::Russian => "Синхронизировать автоматически при запуске".to_string(),
+            },
+            LangMessage::PauseAutoSync => match lang {
+                Lang::English => "Pause all automatic syncs".to_string(),
+                Lang::Russian => "Приостановить автоматическую синхронизацию".to_string(),
+            },
+            LangMessage::AutoSyncingInstance(instance_name) => match lang {
+                Lang::English => format!("Automatically syncing {instance_name}"),
+                Lang::Russian => format!("Автоматическая синхронизация {instance_name}"),
+            },
+            LangMessage::DeduplicateFiles => match lang {
+                Lang::English => "Share identical files between instances".to_string(),
+                Lang::Russian => "Не дублировать одинаковые файлы версий".to_string(),
+            },
+            LangMessage::DeduplicateFilesHint => match lang {
+                Lang::English => "Identical mods and libraries are hard-linked after syncing to save disk space. Does nothing on file systems without hard links".to_string(),
+                Lang::Russian => "После синхронизации одинаковые моды и библиотеки заменяются жёсткими ссылками для экономии места. Не работает на файловых системах без жёстких ссылок".to_string(),
+            },
+            LangMessage::DeduplicatingFiles => match lang {
+                Lang::English => "Deduplicating files...".to_string(),
+                Lang::Russian => "Удаление дубликатов файлов...".to_string(),
+            },
+            LangMessage::BackgroundTasks(count) => match lang {
+                Lang::English => format!("Background tasks ({count})"),
+                Lang::Russian => format!("Фоновые задачи ({count})"),
+            },
+            LangMessage::NoBackgroundTasks => match lang {
+                Lang::English => "No running tasks".to_string(),
+                Lang::Russian => "Нет запущенных задач".to_string(),
+            },
+            LangMessage::TaskFetchManifest => match lang {
+                Lang::English => "Fetching the instance list".to_string(),
+                Lang::Russian => "Загрузка списка версий".to_string(),
+            },
+            LangMessage::TaskFetchMetadata(instance_name) => match lang {
+                Lang::English => format!("Getting metadata of {instance_name}"),
+                Lang::Russian => format!("Получение метаданных {instance_name}"),
+            },
+            LangMessage::TaskSyncInstance(instance_name) => match lang {
+                Lang::English => format!("Syncing {instance_name}"),
+                Lang::Russian => format!("Синхронизация {instance_name}"),
+            },
+            LangMessage::TaskCheckJava(version) => match lang {
+                Lang::English => format!("Checking Java {version}"),
+                Lang::Russian => format!("Проверка Java {version}"),
+            },
+            LangMessage::TaskDownloadJava(version) => match lang {
+                Lang::English => format!("Downloading Java {version}"),
+                Lang::Russian => format!("Загрузка Java {version}"),
+            },
+            LangMessage::CheckOnly => match lang {
+                Lang::English => "Check only".to_string(),
+                Lang::Russian => "Только проверить".to_string(),
+            },
+            LangMessage::NoUpdatesFound => match lang {
+                Lang::English => "No updates found".to_string(),
+                Lang::Russian => "Обновлений нет".to_string(),
+            },
+            LangMessage::UpdateAvailable {
+                files,
+                size,
+                size_unknown,
+            } => {
+                let size = *size as f64 / (1024.0 * 1024.0);
+                match (lang, size_unknown) {
+                    (Lang::English, false) => {
+                        format!("Update available: {files} files, {size:.2} MB")
+                    }
+                    (Lang::English, true) => {
+                        format!("Update available: {files} files, at least {size:.2} MB")
+                    }
+                    (Lang::Russian, false) => {
+                        format!("Доступно обновление: файлов: {files}, {size:.2} МБ")
+                    }
+                    (Lang::Russian, true) => {
+                        format!("Доступно обновление: файлов: {files}, не менее {size:.2} МБ")
+                    }
+                }
+            }
+            LangMessage::InstanceCheckError => match lang {
+                Lang::English => "Error checking for instance updates".to_string(),
+                Lang::Russian => "Ошибка проверки обновлений версии".to_string(),
+            },
+            LangMessage::TaskCheckInstance(instance_name) => match lang {
+                Lang::English => format!("Checking {instance_name} for updates"),
+                Lang::Russian => format!("Проверка обновлений {instance_name}"),
+            },
+            LangMessage::SelectLaunchProfile => match lang {
+                Lang::English => "Select a profile".to_string(),
+                Lang::Russian => "Выберите профиль".to_string(),
+            },
+            LangMessage::NoLaunchProfiles => match lang {
+                Lang::English => "No profiles".to_string(),
+                Lang::Russian => "Нет профилей".to_string(),
+            },
+            LangMessage::InvalidLaunchProfile => match lang {
+                Lang::English => "instance or account removed".to_string(),
+                Lang::Russian => "версия или аккаунт удалены".to_string(),
+            },
+            LangMessage::DeleteLaunchProfile => match lang {
+                Lang::English => "Delete profile".to_string(),
+                Lang::Russian => "Удалить профиль".to_string(),
+            },
+            LangMessage::NewLaunchProfile => match lang {
+                Lang::English => "Save as a profile".to_string(),
+                Lang::Russian => "Сохранить как профиль".to_string(),
+            },
+            LangMessage::LaunchProfileName => match lang {
+                Lang::English => "Profile name:".to_string(),
+                Lang::Russian => "Название профиля:".to_string(),
+            },
+            LangMessage::LaunchProfileNameExists => match lang {
+                Lang::English => "A profile with this name already exists".to_string(),
+                Lang::Russian => "Профиль с таким названием уже существует".to_string(),
+            },
+            LangMessage::WindowSize => match lang {
+                Lang::English => "Window size".to_string(),
+                Lang::Russian => "Размер окна".to_string(),
+            },
+            LangMessage::ClockSkewAuthError => match lang {
+                Lang::English => "wrong system clock".to_string(),
+                Lang::Russian => "неверное системное время".to_string(),
+            },
+            LangMessage::ClockSkewWarning(skew_secs) => {
+                let minutes = skew_secs.unsigned_abs().div_ceil(60);
+                match lang {
+                    Lang::English => format!(
+                        "Your system clock is {minutes} min {}, authorization may fail. Enable automatic time sync in the system settings",
+                        if *skew_secs > 0 { "behind" } else { "ahead" }
+                    ),
+                    Lang::Russian => format!(
+                        "Системное время {} на {minutes} мин, авторизация может не работать. Включите автоматическую синхронизацию времени в настройках системы",
+                        if *skew_secs > 0 { "отстаёт" } else { "спешит" }
+                    ),
+                }
+            }
+            LangMessage::PinVersion => match lang {
+                Lang::English => "Pin the installed version".to_string(),
+                Lang::Russian => "Закрепить установленную версию".to_string(),
+            },
+            LangMessage::PinnedUnpinToUpdate => match lang {
+                Lang::English => "Pinned — unpin to update".to_string(),
+                Lang::Russian => "Закреплена — открепите для обновления".to_string(),
+            },
+            LangMessage::GpuErrorTitle => match lang {
+                Lang::English => "Graphics driver problem".to_string(),
+                Lang::Russian => "Проблема с графическим драйвером".to_string(),
+            },
+            LangMessage::GpuErrorDescription(signature) => match lang {
+                Lang::English => format!(
+                    "The game closed right after starting because the game window couldn't be created ({signature}). This usually means the bundled GLFW library doesn't work with your graphics driver or Wayland, using the system GLFW library often fixes it"
+                ),
+                Lang::Russian => format!(
+                    "Игра закрылась сразу после запуска, потому что не удалось создать окно игры ({signature}). Обычно это значит, что встроенная библиотека GLFW не работает с вашим графическим драйвером или Wayland, системная библиотека GLFW часто решает проблему"
+                ),
+            },
+            LangMessage::DisableGlThreadedOptimizations => match lang {
+                Lang::English => "Also disable threaded optimizations (NVIDIA)".to_string(),
+                Lang::Russian => "Также отключить многопоточную оптимизацию (NVIDIA)".to_string(),
+            },
+            LangMessage::EnableNativeGlfwAndRelaunch => match lang {
+                Lang::English => "Use native GLFW and relaunch".to_string(),
+                Lang::Russian => "Использовать нативную GLFW и перезапустить".to_string(),
+            },
+            LangMessage::AntivirusNoticeTitle => match lang {
+                Lang::English => "Antivirus may slow down the game".to_string(),
+                Lang::Russian => "Антивирус может замедлять игру".to_string(),
+            },
+            LangMessage::AntivirusNotice => match lang {
+                Lang::English => "Windows Security or another antivirus seems to scan or remove the game files. This makes the first launch very slow and can break mods. Consider adding the launcher folder to the antivirus exclusions:".to_string(),
+                Lang::Russian => "Похоже, Безопасность Windows или другой антивирус проверяет или удаляет файлы игры. Из-за этого первый запуск очень медленный, а моды могут перестать работать. Попробуйте добавить папку лаунчера в исключения антивируса:".to_string(),
+            },
+            LangMessage::OpenWindowsSecurity => match lang {
+                Lang::English => "Open Windows Security".to_string(),
+                Lang::Russian => "Открыть Безопасность Windows".to_string(),
+            },
+            LangMessage::AdvancedMode => match lang {
+                Lang::English => "Advanced mode".to_string(),
+                Lang::Russian => "Расширенный режим".to_string(),
+            },
+            LangMessage::CopyLaunchCommand => match lang {
+                Lang::English => "Copy launch command".to_string(),
+                Lang::Russian => "Скопировать команду запуска".to_string(),
+            },
+            LangMessage::CopyLaunchCommandHint => match lang {
+                Lang::English => "The command the launcher would run, with the access token hidden".to_string(),
+                Lang::Russian => "Команда, которую выполнил бы лаунчер, без токена доступа".to_string(),
+            },
+            LangMessage::ArchiveInstance => match lang {
+                Lang::English => "Archive".to_string(),
+                Lang::Russian => "Архивировать".to_string(),
+            },
+            LangMessage::ArchiveInstanceHint => match lang {
+                Lang::English => "Compress the game files to save disk space, the instance can be restored later".to_string(),
+                Lang::Russian => "Сжать файлы игры для экономии места, сборку можно будет восстановить".to_string(),
+            },
+            LangMessage::ArchivingInstance => match lang {
+                Lang::English => "Archiving instance...".to_string(),
+                Lang::Russian => "Архивация сборки...".to_string(),
+            },
+            LangMessage::RestoringInstance => match lang {
+                Lang::English => "Restoring instance...".to_string(),
+                Lang::Russian => "Восстановление сборки...".to_string(),
+            },
+            LangMessage::TaskArchiveInstance(instance_name) => match lang {
+                Lang::English => format!("Archiving {instance_name}"),
+                Lang::Russian => format!("Архивация {instance_name}"),
+            },
+            LangMessage::TaskRestoreInstance(instance_name) => match lang {
+                Lang::English => format!("Restoring {instance_name}"),
+                Lang::Russian => format!("Восстановление {instance_name}"),
+            },
+            LangMessage::InstanceArchived => match lang {
+                Lang::English => "This instance is archived".to_string(),
+                Lang::Russian => "Эта сборка в архиве".to_string(),
+            },
+            LangMessage::RestoreInstance => match lang {
+                Lang::English => "Restore".to_string(),
+                Lang::Russian => "Восстановить".to_string(),
+            },
+            LangMessage::ArchiveInstanceError => match lang {
+                Lang::English => "Error archiving instance".to_string(),
+                Lang::Russian => "Ошибка архивации сборки".to_string(),
+            },
+            LangMessage::RestoreInstanceError => match lang {
+                Lang::English => "Error restoring instance".to_string(),
+                Lang::Russian => "Ошибка восстановления сборки".to_string(),
+            },
+            LangMessage::Archived => match lang {
+                Lang::English => "archived".to_string(),
+                Lang::Russian => "в архиве".to_string(),
+            },
+            LangMessage::ArchivedInstances => match lang {
+                Lang::English => "Archived instances".to_string(),
+                Lang::Russian => "Архивированные сборки".to_string(),
+            },
+            LangMessage::NoArchivedInstances => match lang {
+                Lang::English => "No archived instances".to_string(),
+                Lang::Russian => "Нет архивированных сборок".to_string(),
+            },
+            LangMessage::ArchiveSize {
+                instance_name,
+                size,
+            } => {
+                let size = *size as f64 / (1024.0 * 1024.0);
+                match lang {
+                    Lang::English => format!("{instance_name}: {size:.2} MB"),
+                    Lang::Russian => format!("{instance_name}: {size:.2} МБ"),
+                }
+            }
+            LangMessage::DownloadServerUnstable {
+                host,
+                failure_percent,
+            } => match lang {
+                Lang::English => format!(
+                    "Download server {host} appears unstable: {failure_percent}% of requests failing"
+                ),
+                Lang::Russian => format!(
+                    "Сервер загрузки {host} работает нестабильно: {failure_percent}% запросов завершаются ошибкой"
+                ),
+            },
+            LangMessage::FromVersionJsonUrl => match lang {
+                Lang::English => "Advanced: from version JSON URL".to_string(),
+                Lang::Russian => "Дополнительно: из URL JSON версии".to_string(),
+            },
+            LangMessage::VersionJsonUrl => match lang {
+                Lang::English => "Version JSON URL:".to_string(),
+                Lang::Russian => "URL JSON версии:".to_string(),
+            },
+            LangMessage::IncompleteVersionMetadata { missing_fields } => match lang {
+                Lang::English => {
+                    format!("Version metadata is missing required fields: {missing_fields}")
+                }
+                Lang::Russian => {
+                    format!("В метаданных версии нет обязательных полей: {missing_fields}")
+                }
+            },
+            LangMessage::Accounts => match lang {
+                Lang::English => "Accounts".to_string(),
+                Lang::Russian => "Аккаунты".to_string(),
+            },
+            LangMessage::NoAccounts => match lang {
+                Lang::English => "No accounts".to_string(),
+                Lang::Russian => "Нет аккаунтов".to_string(),
+            },
+            LangMessage::AccountUsedByInstances => match lang {
+                Lang::English => {
+                    "The account will also be removed from these instances:".to_string()
+                }
+                Lang::Russian => "Аккаунт также будет отвязан от этих сборок:".to_string(),
+            },
+            LangMessage::AccountNotUsed => match lang {
+                Lang::English => "Not used by any instance".to_string(),
+                Lang::Russian => "Не используется ни в одной сборке".to_string(),
+            },
+            LangMessage::AccountInstances { instance_names } => match lang {
+                Lang::English => format!("Instances: {instance_names}"),
+                Lang::Russian => format!("Сборки: {instance_names}"),
+            },
+            LangMessage::AccountLastUsed { days_ago } => match (lang, days_ago) {
+                (Lang::English, None) => "Last used: never".to_string(),
+                (Lang::English, Some(0)) => "Last used: today".to_string(),
+                (Lang::English, Some(days)) => format!("Last used: {days} days ago"),
+                (Lang::Russian, None) => "Последнее использование: никогда".to_string(),
+                (Lang::Russian, Some(0)) => "Последнее использование: сегодня".to_string(),
+                (Lang::Russian, Some(days)) => {
+                    format!("Последнее использование: {days} дн. назад")
+                }
+            },
+            LangMessage::GameOptions => match lang {
+                Lang::English => "Game options".to_string(),
+                Lang::Russian => "Настройки игры".to_string(),
+            },
+            LangMessage::GameLanguage => match lang {
+                Lang::English => "Game language:".to_string(),
+                Lang::Russian => "Язык игры:".to_string(),
+            },
+            LangMessage::MaxFps => match lang {
+                Lang::English => "Max FPS:".to_string(),
+                Lang::Russian => "Макс. FPS:".to_string(),
+            },
+            LangMessage::Unlimited => match lang {
+                Lang::English => "Unlimited".to_string(),
+                Lang::Russian => "Без ограничений".to_string(),
+            },
+            LangMessage::Fullscreen => match lang {
+                Lang::English => "Fullscreen".to_string(),
+                Lang::Russian => "Полноэкранный режим".to_string(),
+            },
+            LangMessage::Vsync => match lang {
+                Lang::English => "VSync".to_string(),
+                Lang::Russian => "Вертикальная синхронизация".to_string(),
+            },
+            LangMessage::GameOptionsError => match lang {
+                Lang::English => "Error reading or writing options.txt".to_string(),
+                Lang::Russian => "Ошибка чтения или записи options.txt".to_string(),
+            },
+            LangMessage::InstanceUpToDate => match lang {
+                Lang::English => "Installed and up to date".to_string(),
+                Lang::Russian => "Установлена, обновлений нет".to_string(),
+            },
+            LangMessage::InstanceOutdated => match lang {
+                Lang::English => "Update available".to_string(),
+                Lang::Russian => "Доступно обновление".to_string(),
+            },
+            LangMessage::InstanceNotInstalled => match lang {
+                Lang::English => "Not installed".to_string(),
+                Lang::Russian => "Не установлена".to_string(),
+            },
+            LangMessage::InstanceLocalOnly => match lang {
+                Lang::English => "Local instance".to_string(),
+                Lang::Russian => "Локальная сборка".to_string(),
+            },
+            LangMessage::ErrorDetails => match lang {
+                Lang::English => "Error details".to_string(),
+                Lang::Russian => "Подробности ошибки".to_string(),
+            },
+            LangMessage::SourceUrl => match lang {
+                Lang::English => "URL".to_string(),
+                Lang::Russian => "Адрес".to_string(),
+            },
+            LangMessage::JsonPath => match lang {
+                Lang::English => "JSON path".to_string(),
+                Lang::Russian => "Путь в JSON".to_string(),
+            },
+            LangMessage::ErrorMessage => match lang {
+                Lang::English => "Error".to_string(),
+                Lang::Russian => "Ошибка".to_string(),
+            },
+            LangMessage::CopyDetails => match lang {
+                Lang::English => "Copy details".to_string(),
+                Lang::Russian => "Скопировать подробности".to_string(),
+            },
+            LangMessage::OpenInBrowser => match lang {
+                Lang::English => "Open in browser".to_string(),
+                Lang::Russian => "Открыть в браузере".to_string(),
+            },
+            LangMessage::News => match lang {
+                Lang::English => "News".to_string(),
+                Lang::Russian => "Новости".to_string(),
+            },
+            LangMessage::NoNews => match lang {
+                Lang::English => "No news yet".to_string(),
+                Lang::Russian => "Новостей пока нет".to_string(),
+            },
+            LangMessage::NewsUnread => match lang {
+                Lang::English => "new".to_string(),
+                Lang::Russian => "новое".to_string(),
+            },
+            LangMessage::NewsPublished { days_ago } => match (lang, days_ago) {
+                (Lang::English, 0) => "Published today".to_string(),
+                (Lang::English, days) => format!("Published {days} days ago"),
+                (Lang::Russian, 0) => "Опубликовано сегодня".to_string(),
+                (Lang::Russian, days) => format!("Опубликовано {days} дн. назад"),
+            },
+            LangMessage::NewsUrl => match lang {
+                Lang::English => "News feed URL (optional)".to_string(),
+                Lang::Russian => "URL ленты новостей (необязательно)".to_string(),
+            },
+            LangMessage::UpdatePendingRollout => match lang {
+                Lang::English => {
+                    "An update is being rolled out gradually and will reach you later".to_string()
+                }
+                Lang::Russian => {
+                    "Обновление выходит постепенно и станет доступно позже".to_string()
+                }
+            },
+            LangMessage::Screenshots => match lang {
+                Lang::English => "Screenshots".to_string(),
+                Lang::Russian => "Скриншоты".to_string(),
+            },
+            LangMessage::NoScreenshots => match lang {
+                Lang::English => "No screenshots yet. Press F2 in the game to take one".to_string(),
+                Lang::Russian => "Скриншотов пока нет. Нажмите F2 в игре, чтобы сделать скриншот"
+                    .to_string(),
+            },
+            LangMessage::OpenScreenshot => match lang {
+                Lang::English => "Open".to_string(),
+                Lang::Russian => "Открыть".to_string(),
+            },
+            LangMessage::CopyScreenshot => match lang {
+                Lang::English => "Copy to clipboard".to_string(),
+                Lang::Russian => "Копировать в буфер обмена".to_string(),
+            },
+            LangMessage::DeleteScreenshot => match lang {
+                Lang::English => "Delete screenshot".to_string(),
+                Lang::Russian => "Удалить скриншот".to_string(),
+            },
+            LangMessage::TemporaryFiles { size } => {
+                let size = *size as f64 / (1024.0 * 1024.0);
+                match lang {
+                    Lang::English => format!("Temporary files: {size:.2} MB"),
+                    Lang::Russian => format!("Временные файлы: {size:.2} МБ"),
+                }
+            }
+            LangMessage::PurgeTemporaryFiles => match lang {
+                Lang::English => "Delete temporary files".to_string(),
+                Lang::Russian => "Удалить временные файлы".to_string(),
+            },
+            LangMessage::NoMinecraftProfile => match lang {
+                Lang::English => "no Minecraft profile".to_string(),
+                Lang::Russian => "нет профиля Minecraft".to_string(),
+            },
+            LangMessage::NoMinecraftProfileHint => match lang {
+                Lang::English => "This Microsoft account has no Minecraft profile yet. This happens with Xbox Game Pass accounts that have never launched the game. Create a profile on minecraft.net and log in again".to_string(),
+                Lang::Russian => "У этого аккаунта Microsoft ещё нет профиля Minecraft. Так бывает с аккаунтами Xbox Game Pass, в которых игра ни разу не запускалась. Создайте профиль на minecraft.net и войдите снова".to_string(),
+            },
+            LangMessage::FamilyConsentRequired => match lang {
+                Lang::English => "family consent required".to_string(),
+                Lang::Russian => "нужно согласие родителя".to_string(),
+            },
+            LangMessage::FamilyConsentRequiredHint => match lang {
+                Lang::English => "This account belongs to a child. An adult needs to add it to a Microsoft family and allow it to play, then log in again".to_string(),
+                Lang::Russian => "Этот аккаунт принадлежит ребёнку. Взрослый должен добавить его в семейную группу Microsoft и разрешить игру, после чего войдите снова".to_string(),
+            },
+            LangMessage::OfflineUuid => match lang {
+                Lang::English => "UUID:".to_string(),
+                Lang::Russian => "UUID:".to_string(),
+            },
+            LangMessage::OfflineUuidMojang => match lang {
+                Lang::English => "Offline server formula".to_string(),
+                Lang::Russian => "Как на офлайн-серверах".to_string(),
+            },
+            LangMessage::OfflineUuidRandom => match lang {
+                Lang::English => "Random, saved for this nickname".to_string(),
+                Lang::Russian => "Случайный, сохраняется для ника".to_string(),
+            },
+            LangMessage::OfflineUuidManual => match lang {
+                Lang::English => "Enter manually".to_string(),
+                Lang::Russian => "Ввести вручную".to_string(),
+            },
+            LangMessage::InvalidUuid => match lang {
+                Lang::English => "Invalid UUID".to_string(),
+                Lang::Russian => "Неверный UUID".to_string(),
+            },
+            LangMessage::AccountUuid { uuid } => match lang {
+                Lang::English => format!("UUID: {uuid}"),
+                Lang::Russian => format!("UUID: {uuid}"),
+            },
+            LangMessage::DiscordPresence => match lang {
+                Lang::English => "Show the game in Discord status".to_string(),
+                Lang::Russian => "Показывать игру в статусе Discord".to_string(),
+            },
+            LangMessage::HashingProgress {
+                files,
+                bytes_per_sec,
+            } => {
+                let speed = *bytes_per_sec as f64 / (1024.0 * 1024.0);
+                match lang {
+                    Lang::English => format!("Checked {files} files, {speed:.1} MB/s"),
+                    Lang::Russian => format!("Проверено файлов: {files}, {speed:.1} МБ/с"),
+                }
+            }
+            LangMessage::ImportInstance => match lang {
+                Lang::English => "Import instance".to_string(),
+                Lang::Russian => "Импортировать версию".to_string(),
+            },
+            LangMessage::ImportFromArchive => match lang {
+                Lang::English => "Choose archive".to_string(),
+                Lang::Russian => "Выбрать архив".to_string(),
+            },
+            LangMessage::ImportFromFolder => match lang {
+                Lang::English => "Choose .minecraft folder".to_string(),
+                Lang::Russian => "Выбрать папку .minecraft".to_string(),
+            },
+            LangMessage::Import => match lang {
+                Lang::English => "Import".to_string(),
+                Lang::Russian => "Импортировать".to_string(),
+            },
+            LangMessage::ImportingInstance => match lang {
+                Lang::English => "Importing instance...".to_string(),
+                Lang::Russian => "Импорт версии...".to_string(),
+            },
+            LangMessage::NotExportedInstance => match lang {
+                Lang::English => "The file is not an exported instance".to_string(),
+                Lang::Russian => "Файл не является экспортированной версией".to_string(),
+            },
+            LangMessage::NotMinecraftFolder => match lang {
+                Lang::English => "No installed versions found in the folder".to_string(),
+                Lang::Russian => "В папке не найдено установленных версий".to_string(),
+            },
+            LangMessage::ImportError => match lang {
+                Lang::English => "Error importing instance".to_string(),
+                Lang::Russian => "Ошибка импорта версии".to_string(),
+            },
+            LangMessage::RecommendedJvmArgs => match lang {
+                Lang::English => "JVM arguments recommended by the instance:".to_string(),
+                Lang::Russian => "Аргументы JVM, рекомендованные версией:".to_string(),
+            },
+            LangMessage::UseRecommendedJvmArgs => match lang {
+                Lang::English => "Use recommended JVM arguments".to_string(),
+                Lang::Russian => "Использовать рекомендованные аргументы JVM".to_string(),
+            },
+            LangMessage::JvmArgs => match lang {
+                Lang::English => "JVM arguments (override the recommended ones):".to_string(),
+                Lang::Russian => "Аргументы JVM (заменяют рекомендованные):".to_string(),
+            },
+            LangMessage::FromMrpack => match lang {
+                Lang::English => "Import .mrpack".to_string(),
+                Lang::Russian => "Импорт .mrpack".to_string(),
+            },
+            LangMessage::SelectMrpack => match lang {
+                Lang::English => "Select Modrinth modpack".to_string(),
+                Lang::Russian => "Выбрать сборку Modrinth".to_string(),
+            },
+            LangMessage::InvalidModpack(error) => match lang {
+                Lang::English => format!("Unsupported modpack: {error}"),
+                Lang::Russian => format!("Неподдерживаемая сборка: {error}"),
+            },
+            LangMessage::ShowServerStatus => match lang {
+                Lang::English => "Show server status and list online servers first".to_string(),
+                Lang::Russian => "Показывать статус серверов и выводить доступные первыми".to_string(),
+            },
+            LangMessage::ServerPlayers { online, max } => match lang {
+                Lang::English => format!("{online}/{max} online"),
+                Lang::Russian => format!("{online}/{max} онлайн"),
+            },
+            LangMessage::ServerOffline => match lang {
+                Lang::English => "offline".to_string(),
+                Lang::Russian => "офлайн".to_string(),
+            },
+            LangMessage::Changelog => match lang {
+                Lang::English => "Changelog".to_string(),
+                Lang::Russian => "Список изменений".to_string(),
+            },
+            LangMessage::EnvVars => match lang {
+                Lang::English => "Environment variables".to_string(),
+                Lang::Russian => "Переменные окружения".to_string(),
+            },
+            LangMessage::AddEnvVar => match lang {
+                Lang::English => "Add variable".to_string(),
+                Lang::Russian => "Добавить переменную".to_string(),
+            },
+            LangMessage::InvalidEnvVarName => match lang {
+                Lang::English => "The name must not be empty or contain =".to_string(),
+                Lang::Russian => "Имя не должно быть пустым или содержать =".to_string(),
+            },
+            LangMessage::RemoveEnvVar => match lang {
+                Lang::English => "Remove variable".to_string(),
+                Lang::Russian => "Удалить переменную".to_string(),
+            },
+            LangMessage::DisplayServer => match lang {
+                Lang::English => "Display server:".to_string(),
+                Lang::Russian => "Графический сервер:".to_string(),
+            },
+            LangMessage::DisplayServerAuto => match lang {
+                Lang::English => "Auto".to_string(),
+                Lang::Russian => "Автоматически".to_string(),
+            },
+            LangMessage::PreferDiscreteGpu => match lang {
+                Lang::English => "Use the discrete GPU".to_string(),
+                Lang::Russian => "Использовать дискретную видеокарту".to_string(),
+            },
+            LangMessage::ConfigResetTitle => match lang {
+                Lang::English => "Settings were reset".to_string(),
+                Lang::Russian => "Настройки сброшены".to_string(),
+            },
+            LangMessage::ConfigResetNotice => match lang {
+                Lang::English => "The launcher settings couldn't be read and were reset to the defaults. The old settings file was saved to:".to_string(),
+                Lang::Russian => "Не удалось прочитать настройки лаунчера, поэтому они сброшены. Старый файл настроек сохранён в:".to_string(),
+            },
+            LangMessage::AccountSessionActive => match lang {
+                Lang::English => "Signed in this session".to_string(),
+                Lang::Russian => "Вход выполнен в этом сеансе".to_string(),
+            },
+            LangMessage::AccountSessionSaved => match lang {
+                Lang::English => "Saved, not checked in this session".to_string(),
+                Lang::Russian => "Сохранён, в этом сеансе не проверялся".to_string(),
+            },
+            LangMessage::AccountTokenExpiry { expires_in_secs } => {
+                let hours = expires_in_secs / 3600;
+                let minutes = expires_in_secs % 3600 / 60;
+                match (lang, *expires_in_secs <= 0) {
+                    (Lang::English, true) => "Token expired".to_string(),
+                    (Lang::English, false) => {
+                        format!("Token expires in {hours} h {minutes} min")
+                    }
+                    (Lang::Russian, true) => "Токен истёк".to_string(),
+                    (Lang::Russian, false) => format!("Токен истекает через {hours} ч {minutes} мин"),
+                }
+            }
+            LangMessage::Reauthenticate => match lang {
+                Lang::English => "Re-authenticate".to_string(),
+                Lang::Russian => "Войти заново".to_string(),
+            },
+            LangMessage::EncryptAuthData => match lang {
+                Lang::English => "Encrypt saved accounts".to_string(),
+                Lang::Russian => "Шифровать сохранённые аккаунты".to_string(),
+            },
+            LangMessage::EncryptAuthDataHint => match lang {
+                Lang::English => "The tokens are encrypted with a key kept in the system keyring, or tied to this computer and user if there is no keyring. Copying the launcher folder to another computer will require signing in again.".to_string(),
+                Lang::Russian => "Токены шифруются ключом из системного хранилища паролей, а если его нет, ключом, привязанным к этому компьютеру и пользователю. После переноса папки лаунчера на другой компьютер потребуется войти заново.".to_string(),
+            },
+            LangMessage::MaxConnections => match lang {
+                Lang::English => "Max download connections".to_string(),
+                Lang::Russian => "Макс. число соединений при загрузке".to_string(),
+            },
+            LangMessage::MaxConnectionsHint => match lang {
+                Lang::English => "Lower it if downloads make the internet unusable for other devices. The launcher may use fewer connections if the server is slow.".to_string(),
+                Lang::Russian => "Уменьшите, если во время загрузки интернет перестаёт работать на других устройствах. Лаунчер может использовать меньше соединений, если сервер отвечает медленно.".to_string(),
+            },
+            LangMessage::UseFileStore => match lang {
+                Lang::English => "Download mods into a shared store".to_string(),
+                Lang::Russian => "Загружать моды в общее хранилище".to_string(),
+            },
+            LangMessage::UseFileStoreHint => match lang {
+                Lang::English => "Each mod is downloaded once and hard-linked into the instances that use it, or copied where hard links aren't supported. Deleting an instance keeps the store".to_string(),
+                Lang::Russian => "Каждый мод загружается один раз и подключается в версии жёсткой ссылкой, или копируется, если жёсткие ссылки не поддерживаются. При удалении версии хранилище сохраняется".to_string(),
+            },
+            LangMessage::CleanFileStore => match lang {
+                Lang::English => "Clean unused cache".to_string(),
+                Lang::Russian => "Очистить неиспользуемый кэш".to_string(),
+            },
+            LangMessage::FileStoreCleaned { size } => {
+                let size = *size as f64 / (1024.0 * 1024.0);
+                match lang {
+                    Lang::English => format!("Freed {size:.2} MB"),
+                    Lang::Russian => format!("Освобождено {size:.2} МБ"),
+                }
+            }
+            LangMessage::DiskUsage => match lang {
+                Lang::English => "Disk usage".to_string(),
+                Lang::Russian => "Место на диске".to_string(),
+            },
+            LangMessage::InstanceSize { size } => {
+                let size = format_size(*size, lang);
+                match lang {
+                    Lang::English => format!("Instance folder: {size}"),
+                    Lang::Russian => format!("Папка версии: {size}"),
+                }
+            }
+            LangMessage::LibrariesSize { size } => {
+                let size = format_size(*size, lang);
+                match lang {
+                    Lang::English => format!("Libraries used: {size}"),
+                    Lang::Russian => format!("Используемые библиотеки: {size}"),
+                }
+            }
+            LangMessage::AssetsSize { size, total } => {
+                let size = format_size(*size, lang);
+                let total = format_size(*total, lang);
+                match lang {
+                    Lang::English => format!("Assets used: {size} of {total}"),
+                    Lang::Russian => format!("Используемые ресурсы: {size} из {total}"),
+                }
+            }
+            LangMessage::SharedFilesHint => match lang {
+                Lang::English => "Shared with other instances of the same Minecraft version".to_string(),
+                Lang::Russian => "Общие для версий с той же версией Minecraft".to_string(),
+            },
+            LangMessage::FolderSize { dir, size } => format!("{dir}: {}", format_size(*size, lang)),
+            LangMessage::ClearFolder => match lang {
+                Lang::English => "Clear folder".to_string(),
+                Lang::Russian => "Очистить папку".to_string(),
+            },
+            LangMessage::ConfirmClearFolders => match lang {
+                Lang::English => "Allow clearing folders".to_string(),
+                Lang::Russian => "Разрешить очистку папок".to_string(),
+            },
+            LangMessage::ManageJava => match lang {
+                Lang::English => "Manage Java".to_string(),
+                Lang::Russian => "Управление Java".to_string(),
+            },
+            LangMessage::JavaRuntimes => match lang {
+                Lang::English => "Java runtimes".to_string(),
+                Lang::Russian => "Установки Java".to_string(),
+            },
+            LangMessage::DownloadedJava => match lang {
+                Lang::English => "Downloaded by the launcher:".to_string(),
+                Lang::Russian => "Загруженные лаунчером:".to_string(),
+            },
+            LangMessage::NoDownloadedJava => match lang {
+                Lang::English => "No downloaded Java".to_string(),
+                Lang::Russian => "Нет загруженных Java".to_string(),
+            },
+            LangMessage::JavaRuntime {
+                name,
+                version,
+                valid,
+            } => match (lang, version, valid) {
+                (Lang::English, Some(version), true) => format!("Java {name}: {version}"),
+                (Lang::English, Some(version), false) => {
+                    format!("Java {name}: {version}, doesn't match")
+                }
+                (Lang::English, None, _) => format!("Java {name}: broken"),
+                (Lang::Russian, Some(version), true) => format!("Java {name}: {version}"),
+                (Lang::Russian, Some(version), false) => {
+                    format!("Java {name}: {version}, не подходит")
+                }
+                (Lang::Russian, None, _) => format!("Java {name}: повреждена"),
+            },
+            LangMessage::DeleteJava => match lang {
+                Lang::English => "Delete, only unused runtimes can be deleted".to_string(),
+                Lang::Russian => "Удалить, можно удалить только неиспользуемые".to_string(),
+            },
+            LangMessage::JavaUsedBy { instances } => match (lang, instances.is_empty()) {
+                (Lang::English, true) => "Not used by any instance".to_string(),
+                (Lang::English, false) => format!("Used by: {instances}"),
+                (Lang::Russian, true) => "Не используется версиями".to_string(),
+                (Lang::Russian, false) => format!("Используется: {instances}"),
+            },
+            LangMessage::SystemJava => match lang {
+                Lang::English => "Installed in the system:".to_string(),
+                Lang::Russian => "Установленные в системе:".to_string(),
+            },
+            LangMessage::RedetectSystemJava => match lang {
+                Lang::English => "Re-detect system Java".to_string(),
+                Lang::Russian => "Найти Java в системе заново".to_string(),
+            },
+            LangMessage::NoSystemJava => match lang {
+                Lang::English => "No Java found".to_string(),
+                Lang::Russian => "Java не найдена".to_string(),
+            },
+            LangMessage::DetectedJava => match lang {
+                Lang::English => "Detected Java".to_string(),
+                Lang::Russian => "Найденная Java".to_string(),
+            },
+            LangMessage::GameCrashed => match lang {
+                Lang::English => "The game crashed".to_string(),
+                Lang::Russian => "Игра вылетела".to_string(),
+            },
+            LangMessage::GameLogTail => match lang {
+                Lang::English => "End of the game log:".to_string(),
+                Lang::Russian => "Конец лога игры:".to_string(),
+            },
+            LangMessage::OpenCrashReport => match lang {
+                Lang::English => "Open crash report".to_string(),
+                Lang::Russian => "Открыть отчёт о сбое".to_string(),
+            },
+            LangMessage::CopyCrashReport => match lang {
+                Lang::English => "Copy crash report".to_string(),
+                Lang::Russian => "Скопировать отчёт о сбое".to_string(),
+            },
+            LangMessage::NoCrashReport => match lang {
+                Lang::English => "The game didn't write a crash report".to_string(),
+                Lang::Russian => "Игра не создала отчёт о сбое".to_string(),
+            },
+            LangMessage::ShowCrashDetails => match lang {
+                Lang::English => "Crash details".to_string(),
+                Lang::Russian => "Подробности сбоя".to_string(),
+            },
+            LangMessage::GameOutput => match lang {
+                Lang::English => "Game output".to_string(),
+                Lang::Russian => "Вывод игры".to_string(),
+            },
+            LangMessage::FilterGameOutput => match lang {
+                Lang::English => "Filter".to_string(),
+                Lang::Russian => "Фильтр".to_string(),
+            },
+            LangMessage::RestartOnCrash => match lang {
+                Lang::English => "Restart on crash, up to (times)".to_string(),
+                Lang::Russian => "Перезапускать при сбое, не более (раз)".to_string(),
+            },
+            LangMessage::RestartOnCrashHint => match lang {
+                Lang::English => "The game is launched again if it exits with an error. The count resets once the game runs for a few minutes. 0 turns it off.".to_string(),
+                Lang::Russian => "Игра запускается снова, если завершилась с ошибкой. Счётчик сбрасывается, если игра проработала несколько минут. 0 отключает перезапуск.".to_string(),
+            },
+            LangMessage::RestartedAfterCrash { attempt, max } => match lang {
+                Lang::English => format!("Running, restarted after a crash (attempt {attempt}/{max})"),
+                Lang::Russian => format!("Запущено, перезапущено после сбоя (попытка {attempt}/{max})"),
+            },
+            LangMessage::Playtime { hours, minutes } => match lang {
+                Lang::English => format!("Played: {hours}h {minutes}m"),
+                Lang::Russian => format!("Сыграно: {hours} ч {minutes} мин"),
+            },
+            LangMessage::RenameInstance => match lang {
+                Lang::English => "Rename instance".to_string(),
+                Lang::Russian => "Переименовать версию".to_string(),
+            },
+            LangMessage::RenameOnlyLocalInstances => match lang {
+                Lang::English => "Only instances created or imported in the launcher can be renamed".to_string(),
+                Lang::Russian => "Переименовать можно только версии, созданные или импортированные в лаунчере".to_string(),
+            },
+            LangMessage::InvalidInstanceName => match lang {
+                Lang::English => "The name can't be empty, start or end with a space or contain / \\ : * ? \" < > |".to_string(),
+                Lang::Russian => "Имя не может быть пустым, начинаться или заканчиваться пробелом или содержать / \\ : * ? \" < > |".to_string(),
+            },
+            LangMessage::Rename => match lang {
+                Lang::English => "Rename".to_string(),
+                Lang::Russian => "Переименовать".to_string(),
+            },
+            LangMessage::CachedManifest { hours_ago } => match (lang, hours_ago) {
+                (Lang::English, 0) => {
+                    "Showing the cached instance list from less than an hour ago".to_string()
+                }
+                (Lang::English, hours) if *hours < 48 => {
+                    format!("Showing the cached instance list from {hours} hours ago")
+                }
+                (Lang::English, hours) => {
+                    format!("Showing the cached instance list from {} days ago", hours / 24)
+                }
+                (Lang::Russian, 0) => "Показан сохранённый список сборок менее чем часовой давности".to_string(),
+                (Lang::Russian, hours) if *hours < 48 => {
+                    format!("Показан сохранённый список сборок от {hours} ч. назад")
+                }
+                (Lang::Russian, hours) => {
+                    format!("Показан сохранённый список сборок от {} дн. назад", hours / 24)
+                }
+            },
+            LangMessage::DownloadingFrom(server) => match lang {
+                Lang::English => format!("Downloading from {server}"),
+                Lang::Russian => format!("Загрузка с {server}"),
+            },
+            LangMessage::ExportInstance => match lang {
+                Lang::English => "Export".to_string(),
+                Lang::Russian => "Экспортировать".to_string(),
+            },
+            LangMessage::ExportInstanceHint => match lang {
+                Lang::English => "Save the instance to a zip that can be imported by the launcher".to_string(),
+                Lang::Russian => "Сохранить сборку в zip, который можно импортировать в лаунчер".to_string(),
+            },
+            LangMessage::ExportingInstance => match lang {
+                Lang::English => "Exporting instance...".to_string(),
+                Lang::Russian => "Экспорт сборки...".to_string(),
+            },
+            LangMessage::TaskExportInstance(instance_name) => match lang {
+                Lang::English => format!("Exporting {instance_name}"),
+                Lang::Russian => format!("Экспорт {instance_name}"),
+            },
+            LangMessage::ExportInstanceError => match lang {
+                Lang::English => "Error exporting instance".to_string(),
+                Lang::Russian => "Ошибка экспорта сборки".to_string(),
+            },
+            LangMessage::RestartingAfterCrash { attempt, max } => match lang {
+                Lang::English => format!("Restarting after a crash (attempt {attempt}/{max})..."),
+                Lang::Russian => format!("Перезапуск после сбоя (попытка {attempt}/{max})..."),
+            },
+        }
+    }
+}
+
+// FNV-1a, the keys are small and not attacker controlled
+struct FnvHasher(u64);
+
+impl Default for FnvHasher {
+    fn default() -> Self {
+        FnvHasher(0xcbf29ce484222325)
+    }
+}
+
+impl Hasher for FnvHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ *byte as u64).wrapping_mul(0x100000001b3);
+        }
+    }
+}
+
+type FnvHashMap<K, V> = HashMap<K, V, BuildHasherDefault<FnvHasher>>;
+
+#[derive(Default)]
+struct MessageCache {
+    // None for variants with parameters, learned on first use
+    unit_strings: FnvHashMap<(Discriminant<LangMessage>, Lang), Option<Arc<str>>>,
+    // parameterized messages are only cached where callers keep the parameters stable
+    param_strings: FnvHashMap<Lang, FnvHashMap<LangMessage, Arc<str>>>,
+}
+
+// egui draws on one thread, so no locking on the hot path
+thread_local! {
+    static MESSAGE_CACHE: RefCell<MessageCache> = RefCell::new(MessageCache::default());
+}
+
+impl LangMessage {
+    // for labels redrawn every frame, formats each message once
+    pub fn to_cached_string(&self, lang: Lang) -> Arc<str> {
+        MESSAGE_CACHE.with_borrow_mut(|cache| {
+            let unit_string = cache
+                .unit_strings
+                .entry((std::mem::discriminant(self), lang))
+                .or_insert_with(|| {
+                    (!format!("{self:?}").contains(['(', '{'])).then(|| self.to_string(lang).into())
+                });
+            if let Some(string) = unit_string {
+                return string.clone();
+            }
+            if !matches!(
+                self,
+                LangMessage::NeedJava { .. }
+                    | LangMessage::ProcessErrorCode(_)
+                    | LangMessage::AuthorizeUsing(_)
+            ) {
+                return self.to_string(lang).into();
+            }
+            let strings = cache.param_strings.entry(lang).or_default();
+            if let Some(string) = strings.get(self) {
+                return string.clone();
+            }
+            let string: Arc<str> = self.to_string(lang).into();
+            strings.insert(self.clone(), string.clone());
+            string
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512, Lang::English), "512 B");
+        assert_eq!(format_size(1536, Lang::English), "1.5 KB");
+        assert_eq!(format_size(5 * 1024 * 1024 * 1024, Lang::Russian), "5.0 ГБ");
+    }
+
+    #[test]
+    fn test_cached_string() {
+        let first = LangMessage::SelectInstance.to_cached_string(Lang::English);
+        let second = LangMessage::SelectInstance.to_cached_string(Lang::English);
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(
+            &*first,
+            LangMessage::SelectInstance.to_string(Lang::English)
+        );
+        assert!(!Arc::ptr_eq(
+            &first,
+            &LangMessage::SelectInstance.to_cached_string(Lang::Russian)
+        ));
+
+        let need_java = LangMessage::NeedJava {
+            version: "21".to_string(),
+        };
+        assert!(Arc::ptr_eq(
+            &need_java.to_cached_string(Lang::English),
+            &need_java.to_cached_string(Lang::English)
+        ));
+
+        // other parameterized messages change too often to be kept
+        let progress = LangMessage::ArchiveSize {
+            instance_name: "main".to_string(),
+            size: 1024,
+        };
+        assert!(!Arc::ptr_eq(
+            &progress.to_cached_string(Lang::English),
+            &progress.to_cached_string(Lang::English)
+        ));
+    }
+
+    // cargo test -p launcher_core --release -- --ignored --nocapture bench_labels
+    #[test]
+    #[ignore]
+    fn bench_labels() {
+        // roughly the labels of one frame of the main window
+        let frame = [
+            LangMessage::SelectInstance,
+            LangMessage::SelectAccount,
+            LangMessage::Authorizing,
+            LangMessage::InstanceSynced,
+            LangMessage::CheckingJava,
+            LangMessage::NeedJava {
+                version: "21".to_string(),
+            },
+            LangMessage::AuthorizeUsing("Microsoft".to_string()),
+        ];
+        const FRAMES: u32 = 100_000;
+        for (name, render) in [
+            (
+                "to_string",
+                (|message: &LangMessage| message.to_string(Lang::English).len())
+                    as fn(&LangMessage) -> usize,
+            ),
+            ("to_cached_string", |message: &LangMessage| {
+                message.to_cached_string(Lang::English).len()
+            }),
+        ] {
+            let start = std::time::Instant::now();
+            let mut total = 0;
+            for _ in 0..FRAMES {
+                for message in &frame {
+                    total += render(message);
+                }
+            }
+            println!(
+                "{name}: {:?} per frame ({total} bytes)",
+                start.elapsed() / FRAMES
+            );
+        }
+    }
+}
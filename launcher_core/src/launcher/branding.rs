@@ -0,0 +1,121 @@
+use std::path::Path;
+
+use log::{info, warn};
+use shared::utils::get_safe_path;
+use shared::version::extra_version_metadata::WindowBranding;
+
+// vanilla hardcodes the window title and icon, they can only be changed by
+// branding mods, which read these locations
+const WINDOW_TITLE_PROPERTY: &str = "minecraft.windowTitle";
+const WINDOW_ICON_PATH: &str = "config/window_icon.png";
+
+// main classes of loaders that can load branding mods
+const LOADER_MAIN_CLASSES: &[&str] = &[
+    "net.fabricmc.loader.impl.launch.knot.KnotClient",
+    "net.fabricmc.loader.launch.knot.KnotClient",
+    "org.quiltmc.loader.impl.launch.knot.KnotClient",
+    "cpw.mods.bootstraplauncher.BootstrapLauncher",
+    "cpw.mods.modlauncher.Launcher",
+    "net.minecraftforge.bootstrap.ForgeBootstrap",
+    "net.minecraft.launchwrapper.Launch",
+];
+
+fn is_branding_supported(main_class: &str) -> bool {
+    LOADER_MAIN_CLASSES.contains(&main_class)
+}
+
+pub fn get_window_title_options(branding: &WindowBranding, main_class: &str) -> Vec<String> {
+    let Some(window_title) = &branding.window_title else {
+        return vec![];
+    };
+    if !is_branding_supported(main_class) {
+        info!("Window title is not supported without a mod loader, skipping");
+        return vec![];
+    }
+    vec![format!("-D{WINDOW_TITLE_PROPERTY}={window_title}")]
+}
+
+// a missing icon never fails the launch
+pub fn apply_window_icon(minecraft_dir: &Path, branding: &WindowBranding, main_class: &str) {
+    let Some(window_icon) = &branding.window_icon else {
+        return;
+    };
+    if !is_branding_supported(main_class) {
+        info!("Window icon is not supported without a mod loader, skipping");
+        return;
+    }
+
+    // the path comes from the instance metadata and must not point outside the instance
+    let Some(source) = get_safe_path(minecraft_dir, Path::new(window_icon)) else {
+        warn!("Window icon path {window_icon} is outside of the instance, skipping");
+        return;
+    };
+    let target = minecraft_dir.join(WINDOW_ICON_PATH);
+    if source == target {
+        return;
+    }
+    let result = target
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::copy(&source, &target));
+    if let Err(e) = result {
+        warn!(
+            "Failed to copy window icon {}, skipping:\n{e:?}",
+            source.display()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window_branding() {
+        // the outside file is placed next to the minecraft dir
+        let temp_dir = tempfile::tempdir().unwrap();
+        let minecraft_dir = temp_dir.path().join("minecraft");
+        std::fs::create_dir_all(minecraft_dir.join("branding")).unwrap();
+        std::fs::write(minecraft_dir.join("branding/icon.png"), b"icon").unwrap();
+        let branding = WindowBranding {
+            window_title: Some("Potato Server".to_string()),
+            window_icon: Some("branding/icon.png".to_string()),
+        };
+
+        let vanilla = "net.minecraft.client.main.Main";
+        assert!(get_window_title_options(&branding, vanilla).is_empty());
+        apply_window_icon(&minecraft_dir, &branding, vanilla);
+        assert!(!minecraft_dir.join(WINDOW_ICON_PATH).exists());
+
+        let fabric = "net.fabricmc.loader.impl.launch.knot.KnotClient";
+        assert_eq!(
+            get_window_title_options(&branding, fabric),
+            vec!["-Dminecraft.windowTitle=Potato Server".to_string()]
+        );
+        apply_window_icon(&minecraft_dir, &branding, fabric);
+        assert_eq!(
+            std::fs::read(minecraft_dir.join(WINDOW_ICON_PATH)).unwrap(),
+            b"icon"
+        );
+
+        // a missing icon is skipped
+        std::fs::remove_file(minecraft_dir.join("branding/icon.png")).unwrap();
+        apply_window_icon(&minecraft_dir, &branding, fabric);
+
+        // files outside of the instance are never copied
+        std::fs::remove_file(minecraft_dir.join(WINDOW_ICON_PATH)).unwrap();
+        let outside = temp_dir.path().join("outside.png");
+        std::fs::write(&outside, b"secret").unwrap();
+        for window_icon in [
+            "../outside.png".to_string(),
+            outside.to_string_lossy().to_string(),
+        ] {
+            let branding = WindowBranding {
+                window_title: None,
+                window_icon: Some(window_icon),
+            };
+            apply_window_icon(&minecraft_dir, &branding, fabric);
+            assert!(!minecraft_dir.join(WINDOW_ICON_PATH).exists());
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+// logs can be huge after a mod spams errors, only the end is read
+const LOG_TAIL_MAX_BYTES: u64 = 256 * 1024;
+
+// the last lines of the launch log, empty if it can't be read
+pub fn read_log_tail(path: &Path, max_lines: usize) -> String {
+    let Ok(mut file) = std::fs::File::open(path) else {
+        return String::new();
+    };
+    let len = file.metadata().map_or(0, |metadata| metadata.len());
+    let start = len.saturating_sub(LOG_TAIL_MAX_BYTES);
+    let mut data = vec![];
+    if file.seek(SeekFrom::Start(start)).is_err() || file.read_to_end(&mut data).is_err() {
+        return String::new();
+    }
+    let log = String::from_utf8_lossy(&data);
+    let mut lines: Vec<&str> = log.lines().collect();
+    // the first line is likely cut in the middle
+    if start > 0 && !lines.is_empty() {
+        lines.remove(0);
+    }
+    let skip = lines.len().saturating_sub(max_lines);
+    lines[skip..].join("\n")
+}
+
+// the newest report the game wrote after it was launched
+pub fn find_crash_report(minecraft_dir: &Path, launched_at: SystemTime) -> Option<PathBuf> {
+    std::fs::read_dir(minecraft_dir.join("crash-reports"))
+        .ok()?
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "txt"))
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .filter(|(modified, _)| *modified >= launched_at)
+        .max()
+        .map(|(_, path)| path)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_crash_report() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        let reports_dir = dir.join("crash-reports");
+        std::fs::create_dir_all(&reports_dir).unwrap();
+
+        // file times come from a coarse clock and can be a bit behind
+        let launched_at = SystemTime::now() - Duration::from_secs(10);
+        let old_report = reports_dir.join("crash-2024-01-01_00.00.00-client.txt");
+        std::fs::write(&old_report, "old crash").unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&old_report)
+            .unwrap()
+            .set_modified(launched_at - Duration::from_secs(3600))
+            .unwrap();
+        assert_eq!(find_crash_report(dir, launched_at), None);
+
+        let new_report = reports_dir.join("crash-2024-01-02_00.00.00-client.txt");
+        std::fs::write(&new_report, "new crash").unwrap();
+        assert_eq!(find_crash_report(dir, launched_at), Some(new_report));
+
+        let log_path = dir.join("launch.log");
+        let log: Vec<String> = (0..500).map(|i| format!("line {i}")).collect();
+        std::fs::write(&log_path, log.join("\n")).unwrap();
+        assert_eq!(read_log_tail(&log_path, 2), "line 498\nline 499");
+        assert_eq!(read_log_tail(&dir.join("missing.log"), 2), "");
+    }
+}
@@ -0,0 +1,183 @@
+use std::sync::mpsc;
+use std::time::SystemTime;
+
+use crate::config::build_config;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Presence {
+    Idle,
+    Playing {
+        instance_name: String,
+        started_at: SystemTime,
+    },
+}
+
+// the build has the feature and a discord application to show
+pub fn is_available() -> bool {
+    cfg!(feature = "discord") && build_config::get_discord_client_id().is_some()
+}
+
+// ipc calls block, so they run on a separate thread that lives while presence is enabled
+pub struct DiscordPresence {
+    sender: Option<mpsc::Sender<Presence>>,
+    presence: Presence,
+}
+
+impl DiscordPresence {
+    pub fn new() -> Self {
+        DiscordPresence {
+            sender: None,
+            presence: Presence::Idle,
+        }
+    }
+
+    // cheap to call every frame, only changes are sent to the thread
+    pub fn update(&mut self, enabled: bool, presence: Presence) {
+        let client_id = build_config::get_discord_client_id().filter(|_| enabled);
+        let Some(client_id) = client_id else {
+            // the thread clears the activity once the channel is closed
+            self.sender = None;
+            return;
+        };
+        if self.sender.is_some() && presence == self.presence {
+            return;
+        }
+        let sender = self
+            .sender
+            .get_or_insert_with(|| spawn_presence_thread(client_id));
+        // a dead thread is not restarted, the error is already logged
+        let _ = sender.send(presence.clone());
+        self.presence = presence;
+    }
+}
+
+impl Default for DiscordPresence {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "discord")]
+fn spawn_presence_thread(client_id: String) -> mpsc::Sender<Presence> {
+    let (sender, receiver) = mpsc::channel();
+    let large_image = build_config::get_discord_large_image();
+    if let Err(e) = std::thread::Builder::new()
+        .name("discord-presence".to_string())
+        .spawn(move || ipc::run(&client_id, large_image.as_deref(), receiver))
+    {
+        log::error!("Failed to start the discord presence thread:\n{e:?}");
+    }
+    sender
+}
+
+#[cfg(not(feature = "discord"))]
+fn spawn_presence_thread(_client_id: String) -> mpsc::Sender<Presence> {
+    mpsc::channel().0
+}
+
+#[cfg(feature = "discord")]
+mod ipc {
+    use std::sync::mpsc::{Receiver, RecvTimeoutError};
+    use std::time::{Duration, Instant, UNIX_EPOCH};
+
+    use discord_rich_presence::activity::{Activity, Assets, Timestamps};
+    use discord_rich_presence::error::Error;
+    use discord_rich_presence::{DiscordIpc, DiscordIpcClient};
+    use log::{debug, info, warn};
+
+    use super::Presence;
+    use crate::config::build_config;
+
+    // the activity is re-sent while playing, this is how a closed or restarted discord is noticed
+    const REFRESH_INTERVAL: Duration = Duration::from_secs(15);
+    // presence changes don't bypass this while discord isn't running
+    const RECONNECT_INTERVAL: Duration = Duration::from_secs(15);
+
+    fn send_presence(
+        client: &mut DiscordIpcClient,
+        presence: &Presence,
+        large_image: Option<&str>,
+    ) -> Result<(), Error> {
+        match presence {
+            Presence::Idle => client.clear_activity()?,
+            Presence::Playing {
+                instance_name,
+                started_at,
+            } => {
+                let started_at = started_at
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs() as i64)
+                    .unwrap_or(0);
+                let launcher_name = build_config::get_launcher_name();
+                let mut assets = Assets::new();
+                if let Some(large_image) = large_image {
+                    assets = assets
+                        .large_image(large_image)
+                        .large_text(launcher_name.as_str());
+                }
+                client.set_activity(
+                    Activity::new()
+                        .details(instance_name.as_str())
+                        .state(launcher_name.as_str())
+                        .timestamps(Timestamps::new().start(started_at))
+                        .assets(assets),
+                )?;
+            }
+        }
+        // otherwise the responses pile up in the socket
+        client.recv()?;
+        Ok(())
+    }
+
+    pub fn run(client_id: &str, large_image: Option<&str>, receiver: Receiver<Presence>) {
+        let mut client = DiscordIpcClient::new(client_id);
+        let mut connected = false;
+        let mut last_connect_attempt: Option<Instant> = None;
+        let mut presence = Presence::Idle;
+        loop {
+            let mut changed = match receiver.recv_timeout(REFRESH_INTERVAL) {
+                Ok(new_presence) => {
+                    presence = new_presence;
+                    true
+                }
+                Err(RecvTimeoutError::Timeout) => false,
+                Err(RecvTimeoutError::Disconnected) => break,
+            };
+            while let Ok(new_presence) = receiver.try_recv() {
+                presence = new_presence;
+                changed = true;
+            }
+            if presence == Presence::Idle && !changed {
+                continue;
+            }
+
+            if !connected {
+                // discord shows nothing for a new connection anyway
+                if presence == Presence::Idle {
+                    continue;
+                }
+                if last_connect_attempt.is_some_and(|at| at.elapsed() < RECONNECT_INTERVAL) {
+                    continue;
+                }
+                last_connect_attempt = Some(Instant::now());
+                // discord is often just not running, so this is not worth a warning
+                if let Err(e) = client.connect() {
+                    debug!("Discord is not available: {e}");
+                    continue;
+                }
+                info!("Connected to discord");
+                connected = true;
+            }
+
+            if let Err(e) = send_presence(&mut client, &presence, large_image) {
+                warn!("Lost connection to discord: {e}");
+                let _ = client.close();
+                connected = false;
+            }
+        }
+        if connected {
+            let _ = client.clear_activity();
+            let _ = client.close();
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DisplayServer {
+    // whatever the session and the GLFW build pick
+    #[default]
+    Auto,
+    X11,
+    Wayland,
+}
+
+impl DisplayServer {
+    pub const ALL: [DisplayServer; 3] = [
+        DisplayServer::Auto,
+        DisplayServer::X11,
+        DisplayServer::Wayland,
+    ];
+
+    // the GLFW bundled with minecraft only supports X11
+    pub fn needs_native_glfw(&self) -> bool {
+        *self == DisplayServer::Wayland
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn has_nvidia_driver() -> bool {
+    std::path::Path::new("/proc/driver/nvidia").exists()
+}
+
+// the nvidia vars break GLX without the proprietary driver, so they are only set with it
+#[cfg(target_os = "linux")]
+pub fn get_display_envs(
+    display_server: DisplayServer,
+    prefer_discrete_gpu: bool,
+    has_nvidia_driver: bool,
+) -> Vec<(String, String)> {
+    let mut envs = vec![];
+    let backend = match display_server {
+        DisplayServer::Auto => None,
+        DisplayServer::X11 => Some("x11"),
+        DisplayServer::Wayland => Some("wayland"),
+    };
+    if let Some(backend) = backend {
+        // GLFW 3.4 picks the platform by the session type
+        for name in ["XDG_SESSION_TYPE", "GDK_BACKEND", "SDL_VIDEODRIVER"] {
+            envs.push((name.to_string(), backend.to_string()));
+        }
+    }
+    if prefer_discrete_gpu {
+        envs.push(("DRI_PRIME".to_string(), "1".to_string()));
+        if has_nvidia_driver {
+            envs.push(("__NV_PRIME_RENDER_OFFLOAD".to_string(), "1".to_string()));
+            envs.push((
+                "__GLX_VENDOR_LIBRARY_NAME".to_string(),
+                "nvidia".to_string(),
+            ));
+        }
+    }
+    envs
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    fn env_names(envs: &[(String, String)]) -> Vec<&str> {
+        envs.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_get_display_envs() {
+        assert!(get_display_envs(DisplayServer::Auto, false, true).is_empty());
+
+        let envs = get_display_envs(DisplayServer::Wayland, false, false);
+        assert_eq!(
+            env_names(&envs),
+            vec!["XDG_SESSION_TYPE", "GDK_BACKEND", "SDL_VIDEODRIVER"]
+        );
+        assert!(envs.iter().all(|(_, value)| value == "wayland"));
+        assert!(
+            get_display_envs(DisplayServer::X11, false, false)
+                .iter()
+                .all(|(_, value)| value == "x11")
+        );
+
+        assert_eq!(
+            get_display_envs(DisplayServer::Auto, true, false),
+            vec![("DRI_PRIME".to_string(), "1".to_string())]
+        );
+        assert_eq!(
+            env_names(&get_display_envs(DisplayServer::X11, true, true)),
+            vec![
+                "XDG_SESSION_TYPE",
+                "GDK_BACKEND",
+                "SDL_VIDEODRIVER",
+                "DRI_PRIME",
+                "__NV_PRIME_RENDER_OFFLOAD",
+                "__GLX_VENDOR_LIBRARY_NAME",
+            ]
+        );
+
+        assert!(DisplayServer::Wayland.needs_native_glfw());
+        assert!(!DisplayServer::X11.needs_native_glfw());
+        assert_eq!(
+            serde_json::to_string(&DisplayServer::X11).unwrap(),
+            "\"x11\""
+        );
+    }
+}
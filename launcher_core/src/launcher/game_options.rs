@@ -0,0 +1,281 @@
+use std::path::Path;
+
+const OPTIONS_FILE: &str = "options.txt";
+
+pub const LANG_KEY: &str = "lang";
+pub const MAX_FPS_KEY: &str = "maxFps";
+pub const FULLSCREEN_KEY: &str = "fullscreen";
+pub const VSYNC_KEY: &str = "enableVsync";
+
+// the game treats this as unlimited
+pub const MAX_FPS_UNLIMITED: u32 = 260;
+
+// (locale code, native name)
+pub const GAME_LANGUAGES: &[(&str, &str)] = &[
+    ("en_us", "English (US)"),
+    ("en_gb", "English (UK)"),
+    ("ru_ru", "Русский"),
+    ("uk_ua", "Українська"),
+    ("be_by", "Беларуская"),
+    ("kk_kz", "Қазақша"),
+    ("de_de", "Deutsch"),
+    ("fr_fr", "Français"),
+    ("es_es", "Español"),
+    ("pt_br", "Português (Brasil)"),
+    ("pl_pl", "Polski"),
+    ("zh_cn", "简体中文"),
+    ("ja_jp", "日本語"),
+];
+
+enum Line {
+    Option { key: String, value: String },
+    // blank or malformed lines, kept as they are
+    Other(String),
+}
+
+// options.txt of a game directory, rewritten without touching unknown lines or their order
+pub struct GameOptions {
+    lines: Vec<Line>,
+    crlf: bool,
+    trailing_newline: bool,
+}
+
+impl GameOptions {
+    pub fn new() -> Self {
+        Self {
+            lines: vec![],
+            crlf: false,
+            trailing_newline: true,
+        }
+    }
+
+    pub fn parse(content: &str) -> Self {
+        let crlf = content.contains("\r\n");
+        let trailing_newline = content.ends_with('\n');
+        let content = content.strip_suffix('\n').unwrap_or(content);
+        let lines = if content.is_empty() && trailing_newline {
+            vec![]
+        } else {
+            content
+                .split('\n')
+                .map(|line| {
+                    let line = if crlf {
+                        line.strip_suffix('\r').unwrap_or(line)
+                    } else {
+                        line
+                    };
+                    match line.split_once(':') {
+                        // values may contain colons, e.g. key bindings and resource pack lists
+                        Some((key, value)) if !key.is_empty() => Line::Option {
+                            key: key.to_string(),
+                            value: value.to_string(),
+                        },
+                        _ => Line::Other(line.to_string()),
+                    }
+                })
+                .collect()
+        };
+        Self {
+            lines,
+            crlf,
+            trailing_newline,
+        }
+    }
+
+    pub fn serialize(&self) -> String {
+        let line_ending = if self.crlf { "\r\n" } else { "\n" };
+        let mut content = self
+            .lines
+            .iter()
+            .map(|line| match line {
+                Line::Option { key, value } => format!("{key}:{value}"),
+                Line::Other(line) => line.clone(),
+            })
+            .collect::<Vec<_>>()
+            .join(line_ending);
+        if self.trailing_newline {
+            content.push_str(line_ending);
+        }
+        content
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.lines.iter().find_map(|line| match line {
+            Line::Option { key: k, value } if k == key => Some(value.as_str()),
+            _ => None,
+        })
+    }
+
+    // replaces the value in place, new options are appended
+    pub fn set(&mut self, key: &str, value: &str) {
+        for line in self.lines.iter_mut() {
+            if let Line::Option { key: k, value: v } = line
+                && k == key
+            {
+                *v = value.to_string();
+                return;
+            }
+        }
+        self.lines.push(Line::Option {
+            key: key.to_string(),
+            value: value.to_string(),
+        });
+    }
+
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        self.get(key)?.parse().ok()
+    }
+
+    pub fn set_bool(&mut self, key: &str, value: bool) {
+        self.set(key, &value.to_string());
+    }
+
+    pub fn get_u32(&self, key: &str) -> Option<u32> {
+        self.get(key)?.parse().ok()
+    }
+
+    // lowercase locale code, older versions write the region in uppercase
+    pub fn get_lang(&self) -> Option<String> {
+        Some(self.get(LANG_KEY)?.to_lowercase())
+    }
+
+    pub fn set_lang(&mut self, locale: &str) {
+        let uppercase_region = self
+            .get(LANG_KEY)
+            .is_some_and(|lang| lang.chars().any(|c| c.is_ascii_uppercase()));
+        let locale = match locale.split_once('_') {
+            Some((language, region)) if uppercase_region => {
+                format!("{language}_{}", region.to_uppercase())
+            }
+            _ => locale.to_string(),
+        };
+        self.set(LANG_KEY, &locale);
+    }
+
+    // empty options if the game has not created the file yet
+    pub async fn read(minecraft_dir: &Path) -> anyhow::Result<Self> {
+        match tokio::fs::read_to_string(minecraft_dir.join(OPTIONS_FILE)).await {
+            Ok(content) => Ok(Self::parse(&content)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    // written in place, the file may be a link to the shared data dir
+    pub async fn save(&self, minecraft_dir: &Path) -> anyhow::Result<()> {
+        tokio::fs::write(minecraft_dir.join(OPTIONS_FILE), self.serialize()).await?;
+        Ok(())
+    }
+}
+
+impl Default for GameOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // excerpts of files written by the game, including the line endings
+    const OPTIONS_1_8_9: &str = "invertYMouse:false\n\
+        mouseSensitivity:0.5\n\
+        fov:0.0\n\
+        gamma:0.0\n\
+        renderDistance:12\n\
+        guiScale:0\n\
+        maxFps:120\n\
+        fboEnable:true\n\
+        difficulty:2\n\
+        fancyGraphics:true\n\
+        resourcePacks:[]\n\
+        lastServer:\n\
+        lang:en_US\n\
+        chatVisibility:0\n\
+        fullscreen:false\n\
+        enableVsync:true\n\
+        key_key.attack:-100\n\
+        key_key.use:-99\n\
+        key_key.forward:17\n\
+        soundCategory_master:1.0\n\
+        modelPart_cape:true\n";
+
+    const OPTIONS_1_12_2: &str = "version:1343\r\n\
+        invertYMouse:false\r\n\
+        mouseSensitivity:0.5\r\n\
+        fov:0.0\r\n\
+        renderDistance:12\r\n\
+        maxFps:260\r\n\
+        resourcePacks:[\"vanilla\",\"file/Faithful 1.12.zip\"]\r\n\
+        incompatibleResourcePacks:[]\r\n\
+        lastServer:mc.example.com:25565\r\n\
+        lang:ru_ru\r\n\
+        fullscreen:true\r\n\
+        enableVsync:false\r\n\
+        useVbo:true\r\n\
+        key_key.attack:-100\r\n\
+        key_key.sprint:29\r\n\
+        soundCategory_music:0.0\r\n";
+
+    const OPTIONS_1_20_1: &str = "version:3465\n\
+        autoJump:false\n\
+        operatorItemsTab:false\n\
+        fov:0.0\n\
+        gamma:0.5\n\
+        renderDistance:12\n\
+        simulationDistance:12\n\
+        guiScale:0\n\
+        maxFps:120\n\
+        graphicsMode:1\n\
+        resourcePacks:[\"vanilla\",\"fabric\"]\n\
+        incompatibleResourcePacks:[]\n\
+        lastServer:\n\
+        lang:en_us\n\
+        fullscreen:false\n\
+        enableVsync:true\n\
+        key_key.attack:key.mouse.left\n\
+        key_key.use:key.mouse.right\n\
+        key_key.forward:key.keyboard.w\n\
+        soundCategory_master:1.0\n\
+        modelPart_cape:true\n\
+        mainHand:\"right\"";
+
+    #[test]
+    fn test_round_trip() {
+        for content in [OPTIONS_1_8_9, OPTIONS_1_12_2, OPTIONS_1_20_1] {
+            assert_eq!(GameOptions::parse(content).serialize(), content);
+        }
+
+        let mut options = GameOptions::parse(OPTIONS_1_12_2);
+        assert_eq!(options.get("lastServer"), Some("mc.example.com:25565"));
+        assert_eq!(options.get_u32(MAX_FPS_KEY), Some(MAX_FPS_UNLIMITED));
+        assert_eq!(options.get_bool(FULLSCREEN_KEY), Some(true));
+        options.set_bool(VSYNC_KEY, true);
+        options.set_lang("en_us");
+        let content = options.serialize();
+        assert_eq!(
+            content,
+            OPTIONS_1_12_2
+                .replace("enableVsync:false", "enableVsync:true")
+                .replace("lang:ru_ru", "lang:en_us")
+        );
+
+        let mut options = GameOptions::parse(OPTIONS_1_8_9);
+        assert_eq!(options.get_lang().as_deref(), Some("en_us"));
+        options.set_lang("ru_ru");
+        assert_eq!(options.get(LANG_KEY), Some("ru_RU"));
+
+        let mut options = GameOptions::parse(OPTIONS_1_20_1);
+        options.set(MAX_FPS_KEY, "60");
+        assert_eq!(
+            options.serialize(),
+            OPTIONS_1_20_1.replace("maxFps:120", "maxFps:60")
+        );
+
+        let mut options = GameOptions::new();
+        options.set_lang("ru_ru");
+        options.set_bool(FULLSCREEN_KEY, true);
+        assert_eq!(options.serialize(), "lang:ru_ru\nfullscreen:true\n");
+    }
+}
@@ -0,0 +1,146 @@
+use std::collections::VecDeque;
+use std::io::SeekFrom;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt as _, AsyncSeekExt as _};
+use tokio_util::sync::CancellationToken;
+
+pub const GAME_OUTPUT_MAX_LINES: usize = 5000;
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// the last lines the game printed, shared between the reading task and the console window
+#[derive(Clone, Default)]
+pub struct GameOutput {
+    lines: Arc<Mutex<VecDeque<String>>>,
+}
+
+impl GameOutput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn clear(&self) {
+        self.lines.lock().unwrap().clear();
+    }
+
+    fn push(&self, line: String) {
+        let mut lines = self.lines.lock().unwrap();
+        if lines.len() == GAME_OUTPUT_MAX_LINES {
+            lines.pop_front();
+        }
+        lines.push_back(line);
+    }
+
+    pub fn with_lines<R>(&self, f: impl FnOnce(&VecDeque<String>) -> R) -> R {
+        f(&self.lines.lock().unwrap())
+    }
+}
+
+// splits off the complete lines, an unfinished line stays in pending
+fn take_lines(pending: &mut Vec<u8>) -> Vec<String> {
+    let Some(end) = pending.iter().rposition(|byte| *byte == b'\n') else {
+        return vec![];
+    };
+    let complete: Vec<u8> = pending.drain(..=end).collect();
+    complete[..end]
+        .split(|byte| *byte == b'\n')
+        .map(|line| String::from_utf8_lossy(line.strip_suffix(b"\r").unwrap_or(line)).to_string())
+        .collect()
+}
+
+// follows the launch log the game's stdout and stderr are redirected to. the game writes to
+// the file itself, so its output isn't lost if the launcher is closed while it runs.
+// reads the rest of the file once cancelled, on_update is called after new lines
+pub async fn follow_log(
+    log_path: PathBuf,
+    output: GameOutput,
+    cancellation_token: CancellationToken,
+    on_update: impl Fn(),
+) -> anyhow::Result<()> {
+    let mut file = tokio::fs::File::open(&log_path).await?;
+    let mut position = 0;
+    let mut pending = vec![];
+    loop {
+        let cancelled = cancellation_token.is_cancelled();
+
+        // the log is recreated by the next launch of the same instance
+        let len = tokio::fs::metadata(&log_path).await?.len();
+        if len < position {
+            file = tokio::fs::File::open(&log_path).await?;
+            position = 0;
+            pending.clear();
+        }
+        file.seek(SeekFrom::Start(position)).await?;
+        let read = file.read_to_end(&mut pending).await?;
+        position += read as u64;
+
+        if cancelled && !pending.is_empty() && pending.last() != Some(&b'\n') {
+            pending.push(b'\n');
+        }
+        let lines = take_lines(&mut pending);
+        if !lines.is_empty() {
+            for line in lines {
+                output.push(line);
+            }
+            on_update();
+        }
+
+        if cancelled {
+            return Ok(());
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(POLL_INTERVAL) => {}
+            _ = cancellation_token.cancelled() => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write as _;
+
+    use super::*;
+
+    #[test]
+    fn test_take_lines() {
+        let mut pending = b"first\r\nsecond\nthi".to_vec();
+        assert_eq!(take_lines(&mut pending), ["first", "second"]);
+        assert_eq!(pending, b"thi");
+        assert!(take_lines(&mut pending).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_follow_log() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        let log_path = dir.join("instance.log");
+        let mut file = std::fs::File::create(&log_path).unwrap();
+        write!(file, "[main/INFO]: loading\n[main/WARN]: slow").unwrap();
+
+        let output = GameOutput::new();
+        let cancellation_token = CancellationToken::new();
+        let task = tokio::spawn(follow_log(
+            log_path,
+            output.clone(),
+            cancellation_token.clone(),
+            || {},
+        ));
+        tokio::time::sleep(POLL_INTERVAL * 2).await;
+        assert_eq!(output.with_lines(|lines| lines.len()), 1);
+
+        for i in 0..GAME_OUTPUT_MAX_LINES {
+            writeln!(file, " line {i}").unwrap();
+        }
+        write!(file, "[main/ERROR]: crashed").unwrap();
+        cancellation_token.cancel();
+        task.await.unwrap().unwrap();
+
+        output.with_lines(|lines| {
+            assert_eq!(lines.len(), GAME_OUTPUT_MAX_LINES);
+            assert_eq!(lines[0], " line 1");
+            assert_eq!(lines.back().unwrap(), "[main/ERROR]: crashed");
+        });
+    }
+}
@@ -0,0 +1,54 @@
+use std::time::Duration;
+
+use crate::constants::GPU_ERROR_EARLY_EXIT_SECS;
+
+// messages printed by GLFW and Minecraft when the OpenGL context can't be created,
+// usually fixed by the system GLFW, which supports wayland and newer drivers
+const SIGNATURES: &[&str] = &[
+    "Failed to create GLFW window",
+    "Failed to create the GLFW window",
+    "GLXBadFBConfig",
+    "GLX: Failed to create context",
+    "EGL: Failed to",
+    "Wayland: Failed to",
+    "Failed to connect to the Wayland display",
+    "GLFW error 65542",
+    "GLFW error 65543",
+];
+
+// the signature found in the log of a failed launch, crashes after the game
+// has started are never reported since the window was created fine
+pub fn find_gpu_error(log: &str, run_time: Duration) -> Option<&'static str> {
+    if run_time >= Duration::from_secs(GPU_ERROR_EARLY_EXIT_SECS) {
+        return None;
+    }
+    SIGNATURES
+        .iter()
+        .find(|signature| log.contains(*signature))
+        .copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_gpu_error() {
+        let early = Duration::from_secs(3);
+        let glfw_log = "[Render thread/ERROR]: GLFW error 65543: GLX: Failed to create context: GLXBadFBConfig\n\
+            java.lang.IllegalStateException: GLFW error before init";
+        assert_eq!(find_gpu_error(glfw_log, early), Some("GLXBadFBConfig"));
+        let wayland_log = "Wayland: Failed to connect to display";
+        assert_eq!(
+            find_gpu_error(wayland_log, early),
+            Some("Wayland: Failed to")
+        );
+        assert_eq!(find_gpu_error(glfw_log, Duration::from_secs(60)), None);
+
+        // ordinary mod crashes and harmless wayland warnings
+        let mod_crash_log = "[main/ERROR]: Mixin apply for mod examplemod failed\n\
+            ---- Minecraft Crash Report ----\n\
+            GLFW error 65548: Wayland: The platform does not support setting the window position";
+        assert_eq!(find_gpu_error(mod_crash_log, early), None);
+    }
+}
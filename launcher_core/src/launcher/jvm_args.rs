@@ -0,0 +1,131 @@
+use log::warn;
+
+// -XX flags that pick the garbage collector, the jvm refuses to start with two of them
+const GC_SELECTORS: &[&str] = &[
+    "UseG1GC",
+    "UseParallelGC",
+    "UseSerialGC",
+    "UseShenandoahGC",
+    "UseZGC",
+    "UseConcMarkSweepGC",
+    "UseEpsilonGC",
+];
+const GC_SELECTOR_KEY: &str = "-XX:<gc>";
+
+// flags with a value glued to the name
+const SIZE_FLAGS: &[&str] = &["-Xmx", "-Xms", "-Xss", "-Xmn"];
+
+fn get_xx_name(arg: &str) -> Option<&str> {
+    let option = arg.strip_prefix("-XX:")?;
+    let name = option
+        .strip_prefix('+')
+        .or_else(|| option.strip_prefix('-'))
+        .unwrap_or(option);
+    Some(name.split('=').next().unwrap_or(name))
+}
+
+fn is_gc_selector(arg: &str) -> bool {
+    arg.strip_prefix("-XX:+")
+        .is_some_and(|name| GC_SELECTORS.contains(&name))
+}
+
+// args with the same key set the same thing, the later one wins
+fn get_flag_key(arg: &str) -> String {
+    if is_gc_selector(arg) {
+        return GC_SELECTOR_KEY.to_string();
+    }
+    if let Some(name) = get_xx_name(arg) {
+        return format!("-XX:{name}");
+    }
+    if let Some(flag) = SIZE_FLAGS.iter().find(|flag| arg.starts_with(*flag)) {
+        return flag.to_string();
+    }
+    arg.split(['=', ':']).next().unwrap_or(arg).to_string()
+}
+
+// user input is split like a minecraftArguments string
+pub fn parse_jvm_args(args: &str) -> Vec<String> {
+    args.split_whitespace().map(|arg| arg.to_string()).collect()
+}
+
+// problems that are logged, but don't block the launch
+pub fn find_problems(args: &[String]) -> Vec<String> {
+    let mut problems = vec![];
+    let gc_selectors: Vec<&str> = args
+        .iter()
+        .map(|arg| arg.as_str())
+        .filter(|arg| is_gc_selector(arg))
+        .collect();
+    if gc_selectors.len() > 1 {
+        problems.push(format!(
+            "multiple garbage collectors selected: {}",
+            gc_selectors.join(" ")
+        ));
+    }
+    for arg in args {
+        if !arg.starts_with('-') {
+            problems.push(format!("{arg} is not a jvm option"));
+        }
+    }
+    problems
+}
+
+// each layer overrides the flags of the previous ones, e.g. launcher defaults,
+// args recommended by the instance and args of the user
+pub fn merge_jvm_args(layers: &[(&str, &[String])]) -> Vec<String> {
+    let mut merged: Vec<String> = vec![];
+    for (source, args) in layers {
+        for problem in find_problems(args) {
+            warn!("JVM args from {source}: {problem}");
+        }
+        let keys: Vec<String> = args.iter().map(|arg| get_flag_key(arg)).collect();
+        merged.retain(|arg| !keys.contains(&get_flag_key(arg)));
+        merged.extend(args.iter().cloned());
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &str) -> Vec<String> {
+        parse_jvm_args(args)
+    }
+
+    #[test]
+    fn test_merge_jvm_args() {
+        let defaults = args("-XX:+UseG1GC -XX:G1NewSizePercent=20 -Xms512M -Xmx4096M");
+        let recommended = args("-XX:+UseShenandoahGC -XX:-AlwaysPreTouch -Dfml.readTimeout=120");
+        let user = args("-XX:+AlwaysPreTouch -Xmx8G -Dfml.readTimeout=60");
+
+        assert_eq!(
+            merge_jvm_args(&[("defaults", &defaults), ("recommended", &recommended)]),
+            args(
+                "-XX:G1NewSizePercent=20 -Xms512M -Xmx4096M \
+                 -XX:+UseShenandoahGC -XX:-AlwaysPreTouch -Dfml.readTimeout=120"
+            )
+        );
+        // the user args win over both
+        assert_eq!(
+            merge_jvm_args(&[
+                ("defaults", &defaults),
+                ("recommended", &recommended),
+                ("user", &user),
+            ]),
+            args(
+                "-XX:G1NewSizePercent=20 -Xms512M -XX:+UseShenandoahGC \
+                 -XX:+AlwaysPreTouch -Xmx8G -Dfml.readTimeout=60"
+            )
+        );
+
+        assert!(find_problems(&defaults).is_empty());
+        assert_eq!(
+            find_problems(&args("-XX:+UseZGC -XX:+UseG1GC Xmx4G")),
+            vec![
+                "multiple garbage collectors selected: -XX:+UseZGC -XX:+UseG1GC".to_string(),
+                "Xmx4G is not a jvm option".to_string(),
+            ]
+        );
+    }
+}
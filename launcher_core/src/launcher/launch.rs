@@ -0,0 +1,384 @@
+use log::{debug, info, warn};
+use maplit::hashmap;
+use shared::paths::{
+    get_authlib_injector_path, get_client_jar_path, get_launch_log_path, get_libraries_dir,
+    get_minecraft_dir, get_running_lock_path,
+};
+use shared::utils::path_to_str;
+use shared::version::asset_metadata::AssetsMetadata;
+use shared::version::extra_version_metadata::AuthBackend;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use tokio::process::Child;
+
+use super::branding;
+use super::compat;
+use super::jvm_args;
+use super::launch_command::{self, LaunchCommand};
+use super::running_lock::write_running_lock;
+use super::shared_data;
+use crate::auth::base::get_auth_provider;
+use crate::auth::user_info::AuthData;
+use crate::config::runtime_config::Config;
+use crate::constants;
+use crate::version::complete_version_metadata::CompleteVersionMetadata;
+use crate::version::{archive, natives, os, overrides, sync};
+use shared::version::version_metadata;
+
+const GC_OPTIONS: &[&str] = &[
+    "-XX:+UnlockExperimentalVMOptions",
+    "-XX:+UseG1GC",
+    "-XX:G1NewSizePercent=20",
+    "-XX:G1ReservePercent=20",
+    "-XX:MaxGCPauseMillis=50",
+    "-XX:G1HeapRegionSize=32M",
+    "-XX:+DisableExplicitGC",
+    "-XX:+AlwaysPreTouch",
+    "-XX:+ParallelRefProcEnabled",
+];
+
+#[cfg(target_os = "windows")]
+const PATHSEP: &str = ";";
+#[cfg(not(target_os = "windows"))]
+const PATHSEP: &str = ":";
+
+fn replace_launch_config_variables(
+    argument: String,
+    variables: &HashMap<String, String>,
+) -> String {
+    variables
+        .iter()
+        .fold(argument, |acc, (k, v)| acc.replace(&format!("${{{k}}}"), v))
+}
+
+fn process_args(
+    args: &Vec<version_metadata::VariableArgument>,
+    variables: &HashMap<String, String>,
+) -> Vec<String> {
+    let mut options = vec![];
+    for arg in args {
+        options.extend(
+            arg.get_matching_values(&os::get_os_name(), &os::get_system_arch())
+                .iter()
+                .map(|v| replace_launch_config_variables(v.to_string(), variables)),
+        );
+    }
+    options
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum LaunchError {
+    #[error("Missing authlib injector")]
+    MissingAuthlibInjector,
+    #[error("Missing library {0}")]
+    MissingLibrary(PathBuf),
+    #[error("Java path for version {0} not found")]
+    JavaPathNotFound(String),
+}
+
+// legacy versions get the virtual assets dir, the others the assets root
+async fn get_game_assets_dir(asset_id: &str, assets_dir: &Path, minecraft_dir: &Path) -> PathBuf {
+    match AssetsMetadata::read_local(asset_id, assets_dir).await {
+        Ok(asset_metadata) => asset_metadata
+            .get_virtual_dir(asset_id, assets_dir, minecraft_dir)
+            .unwrap_or_else(|| assets_dir.to_path_buf()),
+        Err(e) => {
+            warn!("Failed to read asset index {asset_id}:\n{e:?}");
+            assets_dir.to_path_buf()
+        }
+    }
+}
+
+// the files a launch needs besides the synced ones: natives, shared data links and the window icon
+pub fn prepare_instance(
+    version_metadata: &CompleteVersionMetadata,
+    config: &Config,
+) -> anyhow::Result<()> {
+    let launcher_dir = config.get_launcher_dir();
+    archive::ensure_not_archived(&launcher_dir, version_metadata.get_name())?;
+    let minecraft_dir = get_minecraft_dir(&launcher_dir, version_metadata.get_name());
+    // instances synced before natives were keyed are extracted here
+    natives::extract_natives(
+        &sync::get_libraries(version_metadata, &launcher_dir)?,
+        &launcher_dir,
+        version_metadata.get_name(),
+    )?;
+
+    shared_data::apply_shared_data(
+        &minecraft_dir,
+        config.shared_data.get(version_metadata.get_name()),
+    )?;
+
+    if let Some(branding) = version_metadata.get_branding() {
+        branding::apply_window_icon(&minecraft_dir, branding, version_metadata.get_main_class());
+    }
+    Ok(())
+}
+
+// builds the command line without touching the instance files, prepare_instance runs before a launch
+pub async fn build_launch_command(
+    version_metadata: &CompleteVersionMetadata,
+    config: &Config,
+    auth_data: &AuthData,
+    online: bool,
+) -> anyhow::Result<LaunchCommand> {
+    let auth_backend = &config
+        .get_selected_auth_profile()
+        .map(|p| AuthBackend::from_id(&p.auth_backend_id));
+    let auth_provider = auth_backend.as_ref().map(|x| get_auth_provider(x));
+
+    let launcher_dir = config.get_launcher_dir();
+    let mut minecraft_dir = get_minecraft_dir(&launcher_dir, version_metadata.get_name());
+    let libraries_dir = get_libraries_dir(&launcher_dir);
+    let natives_dir = natives::get_instance_natives_dir(
+        &sync::get_libraries(version_metadata, &launcher_dir)?,
+        &launcher_dir,
+    );
+
+    let minecraft_dir_short = minecraft_dir.clone();
+    if cfg!(windows) {
+        minecraft_dir = compat::win_get_long_path_name(&minecraft_dir_short)?;
+    }
+
+    let mut used_library_paths = HashSet::new();
+    let mut classpath = vec![];
+    let local_overrides = overrides::read_local_library_overrides(&launcher_dir)?;
+    for library in version_metadata.get_libraries_with_overrides(&local_overrides) {
+        if let Some(path) = library.get_library_path(&libraries_dir) {
+            if !path.is_file() {
+                return Err(LaunchError::MissingLibrary(path.clone()).into());
+            }
+
+            let path_string = path_to_str(&path)?.to_string();
+            if !used_library_paths.contains(&path_string) {
+                // vanilla mojang manifests have duplicates for some reason
+                used_library_paths.insert(path_string.clone());
+                classpath.push(path_string);
+            }
+        }
+    }
+
+    let client_jar_path = get_client_jar_path(&launcher_dir, version_metadata.get_id());
+    if !client_jar_path.exists() {
+        return Err(LaunchError::MissingLibrary(client_jar_path).into());
+    }
+
+    classpath.push(path_to_str(&client_jar_path)?.to_string());
+
+    let mut classpath_str = classpath.join(PATHSEP);
+    if cfg!(windows) {
+        classpath_str = classpath_str.replace("/", "\\");
+    }
+
+    let (window_width, window_height) = config
+        .get_active_launch_profile()
+        .and_then(|profile| profile.window_size)
+        .unwrap_or(constants::DEFAULT_WINDOW_SIZE);
+
+    let assets_dir = config.get_assets_dir();
+    let asset_id = &version_metadata.get_asset_index()?.id;
+    let game_assets_dir = get_game_assets_dir(asset_id, &assets_dir, &minecraft_dir).await;
+
+    let variables: HashMap<String, String> = hashmap! {
+        "natives_directory".to_string() => path_to_str(&natives_dir)?.to_string(),
+        "launcher_name".to_string() => "java-minecraft-launcher".to_string(),
+        "launcher_version".to_string() => "1.6.84-j".to_string(),
+        "classpath".to_string() => classpath_str,
+        "classpath_separator".to_string() => PATHSEP.to_string(),
+        "library_directory".to_string() => path_to_str(&libraries_dir)?.to_string(),
+        "auth_player_name".to_string() => auth_data.user_info.username.clone(),
+        "version_name".to_string() => version_metadata.get_id().to_string(),
+        "game_directory".to_string() => path_to_str(&minecraft_dir)?.to_string(),
+        "assets_root".to_string() => path_to_str(&assets_dir)?.to_string(),
+        "assets_index_name".to_string() => asset_id.to_string(),
+        "game_assets".to_string() => path_to_str(&game_assets_dir)?.to_string(),
+        "auth_uuid".to_string() => auth_data.user_info.uuid.replace("-", ""),
+        "auth_access_token".to_string() => auth_data.access_token.clone(),
+        "clientid".to_string() => "".to_string(),
+        "auth_xuid".to_string() => "".to_string(),
+        "user_type".to_string() => if online { "mojang" } else { "offline" }.to_string(),
+        "version_type".to_string() => "release".to_string(),
+        "resolution_width".to_string() => window_width.to_string(),
+        "resolution_height".to_string() => window_height.to_string(),
+        "user_properties".to_string() => "{}".to_string(),
+    };
+
+    let xmx = config.get_xmx(version_metadata.get_name()).map_or_else(
+        || {
+            warn!(
+                "No Xmx value found for version {}",
+                version_metadata.get_name()
+            );
+            format!("{}M", constants::XMX_DEFAULT)
+        },
+        |s| s.clone(),
+    );
+
+    let default_options = [
+        GC_OPTIONS
+            .iter()
+            .map(|&s| s.to_string())
+            .collect::<Vec<_>>(),
+        vec![
+            "-Xms512M".to_string(),
+            "-Duser.language=en".to_string(),
+            "-Dfile.encoding=UTF-8".to_string(),
+        ],
+    ]
+    .concat();
+    let recommended_options = if *config
+        .disable_recommended_jvm_args
+        .get(version_metadata.get_name())
+        .unwrap_or(&false)
+    {
+        &[]
+    } else {
+        version_metadata.get_recommended_jvm_args()
+    };
+    // the xmx setting is the user's choice too, so it wins over the recommended args
+    let mut user_options = vec![format!("-Xmx{}", xmx)];
+    if let Some(jvm_args) = config.jvm_args.get(version_metadata.get_name()) {
+        user_options.extend(jvm_args::parse_jvm_args(jvm_args));
+    }
+    let mut java_options = jvm_args::merge_jvm_args(&[
+        ("defaults", &default_options),
+        ("instance", recommended_options),
+        ("user", &user_options),
+    ]);
+
+    if online && let Some(auth_url) = auth_provider.and_then(|x| x.get_auth_url()) {
+        let authlib_injector = sync::get_authlib_injector(version_metadata);
+        let authlib_injector_path =
+            get_authlib_injector_path(&launcher_dir, &authlib_injector.sha1);
+        if !authlib_injector_path.exists() {
+            return Err(LaunchError::MissingAuthlibInjector.into());
+        }
+        java_options.insert(
+            0,
+            format!(
+                "-javaagent:{}={}",
+                path_to_str(&authlib_injector_path)?,
+                auth_url,
+            ),
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    let display_server = config
+        .display_server
+        .get(version_metadata.get_name())
+        .copied()
+        .unwrap_or_default();
+    #[cfg(target_os = "linux")]
+    if *config
+        .use_native_glfw
+        .get(version_metadata.get_name())
+        .unwrap_or(&crate::config::build_config::USE_NATIVE_GLFW_DEFAULT)
+        || display_server.needs_native_glfw()
+    {
+        use crate::launcher::compat::linux_find_native_glfw;
+        let glfw_path = linux_find_native_glfw()?;
+        log::info!("Using GLFW at {glfw_path}");
+        java_options.push("-Dorg.lwjgl.glfw.libname=".to_string() + &glfw_path);
+    }
+
+    if let Some(branding) = version_metadata.get_branding() {
+        let main_class = version_metadata.get_main_class();
+        java_options.extend(branding::get_window_title_options(branding, main_class));
+    }
+
+    let arguments = version_metadata.get_arguments()?;
+
+    java_options.extend(process_args(&arguments.jvm, &variables));
+    let minecraft_options = process_args(&arguments.game, &variables);
+
+    let java_path = config
+        .java_paths
+        .get(version_metadata.get_name())
+        .ok_or_else(|| LaunchError::JavaPathNotFound(version_metadata.get_name().to_string()))?;
+
+    let mut envs = vec![];
+    #[cfg(target_os = "linux")]
+    if *config
+        .disable_gl_threaded_optimizations
+        .get(version_metadata.get_name())
+        .unwrap_or(&false)
+    {
+        envs.push(("__GL_THREADED_OPTIMIZATIONS".to_string(), "0".to_string()));
+    }
+    #[cfg(target_os = "linux")]
+    envs.extend(super::display::get_display_envs(
+        display_server,
+        *config
+            .prefer_discrete_gpu
+            .get(version_metadata.get_name())
+            .unwrap_or(&false),
+        super::display::has_nvidia_driver(),
+    ));
+    // added last, so they override the ones set by the launcher
+    for (name, value) in config
+        .env_vars
+        .get(version_metadata.get_name())
+        .into_iter()
+        .flatten()
+    {
+        match launch_command::check_env_var_name(name) {
+            Ok(()) => envs.push((name.clone(), value.clone())),
+            Err(e) => warn!("Skipping environment variable: {e}"),
+        }
+    }
+
+    Ok(LaunchCommand {
+        java_path: java_path.clone(),
+        args: [
+            java_options,
+            vec![version_metadata.get_main_class().to_string()],
+            minecraft_options,
+        ]
+        .concat(),
+        current_dir: minecraft_dir_short,
+        envs,
+        secrets: vec![auth_data.access_token.clone()],
+    })
+}
+
+pub async fn launch(
+    version_metadata: &CompleteVersionMetadata,
+    config: &Config,
+    auth_data: &AuthData,
+    online: bool,
+) -> anyhow::Result<Child> {
+    info!(
+        "Launching {} with content hash {}",
+        version_metadata.get_name(),
+        version_metadata.get_content_hash()
+    );
+
+    prepare_instance(version_metadata, config)?;
+    let launch_command = build_launch_command(version_metadata, config, auth_data, online).await?;
+    debug!("Launch command: {}", launch_command.to_sanitized_string());
+
+    let launcher_dir = config.get_launcher_dir();
+    let mut cmd = launch_command.to_command();
+    let file = std::fs::File::create(get_launch_log_path(
+        &launcher_dir,
+        version_metadata.get_name(),
+    ))?;
+    cmd.stdout(file.try_clone()?);
+    cmd.stderr(file);
+
+    #[cfg(target_os = "windows")]
+    {
+        use winapi::um::winbase::CREATE_NO_WINDOW;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+
+    let child = cmd.spawn()?;
+    if let Some(pid) = child.id() {
+        write_running_lock(
+            &get_running_lock_path(&launcher_dir, version_metadata.get_name()),
+            pid,
+        );
+    }
+    Ok(child)
+}
@@ -0,0 +1,203 @@
+use std::path::PathBuf;
+
+use tokio::process::Command as TokioCommand;
+
+const SECRET_PLACEHOLDER: &str = "<hidden>";
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum EnvVarError {
+    #[error("Environment variable name is empty")]
+    EmptyName,
+    #[error("Environment variable name {0} contains =")]
+    NameContainsEquals(String),
+}
+
+pub fn check_env_var_name(name: &str) -> Result<(), EnvVarError> {
+    if name.is_empty() {
+        return Err(EnvVarError::EmptyName);
+    }
+    if name.contains('=') {
+        return Err(EnvVarError::NameContainsEquals(name.to_string()));
+    }
+    Ok(())
+}
+
+// everything needed to start the game, built without spawning it
+pub struct LaunchCommand {
+    pub java_path: PathBuf,
+    pub args: Vec<String>,
+    pub current_dir: PathBuf,
+    pub envs: Vec<(String, String)>,
+    // values replaced with a placeholder when the command is shown to the user
+    pub secrets: Vec<String>,
+}
+
+fn is_safe_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "-_./:=@%+,".contains(c)
+}
+
+fn quote_posix(arg: &str) -> String {
+    if !arg.is_empty() && arg.chars().all(is_safe_char) {
+        return arg.to_string();
+    }
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+// quoting rules of CommandLineToArgvW, which java uses to parse its command line
+fn quote_windows(arg: &str) -> String {
+    if !arg.is_empty() && arg.chars().all(|c| is_safe_char(c) || c == '\\') {
+        return arg.to_string();
+    }
+    let mut quoted = String::from("\"");
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        match c {
+            '\\' => backslashes += 1,
+            '"' => {
+                quoted.push_str(&"\\".repeat(backslashes * 2 + 1));
+                quoted.push('"');
+                backslashes = 0;
+            }
+            _ => {
+                quoted.push_str(&"\\".repeat(backslashes));
+                quoted.push(c);
+                backslashes = 0;
+            }
+        }
+    }
+    quoted.push_str(&"\\".repeat(backslashes * 2));
+    quoted.push('"');
+    quoted
+}
+
+// cmd expands %NAME% even inside quotes and a caret only escapes outside of them,
+// so every % is put between the quoted parts
+fn quote_cmd(arg: &str) -> String {
+    arg.split('%')
+        .map(quote_windows)
+        .collect::<Vec<_>>()
+        .join("^%")
+}
+
+// set takes everything up to the && as the value, so it's escaped instead of quoted
+fn escape_cmd(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        if "^&|<>()%\"".contains(c) {
+            escaped.push('^');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+impl LaunchCommand {
+    pub fn to_command(&self) -> TokioCommand {
+        let mut cmd = TokioCommand::new(&self.java_path);
+        cmd.args(&self.args)
+            .current_dir(&self.current_dir)
+            .envs(self.envs.iter().map(|(key, value)| (key, value)));
+        cmd
+    }
+
+    fn sanitize(&self, arg: &str) -> String {
+        self.secrets
+            .iter()
+            .filter(|secret| !secret.is_empty())
+            .fold(arg.to_string(), |arg, secret| {
+                arg.replace(secret.as_str(), SECRET_PLACEHOLDER)
+            })
+    }
+
+    fn to_shell_line(&self, windows: bool) -> String {
+        let quote = if windows { quote_cmd } else { quote_posix };
+        let mut parts = vec![];
+        if windows {
+            parts.push(format!(
+                "cd /d {} &&",
+                quote(&self.current_dir.to_string_lossy())
+            ));
+            for (key, value) in &self.envs {
+                parts.push(format!("set {}&&", escape_cmd(&format!("{key}={value}"))));
+            }
+        } else {
+            parts.push(format!(
+                "cd {} &&",
+                quote(&self.current_dir.to_string_lossy())
+            ));
+            for (key, value) in &self.envs {
+                parts.push(format!("{key}={}", quote(value)));
+            }
+        }
+        parts.push(quote(&self.java_path.to_string_lossy()));
+        parts.extend(self.args.iter().map(|arg| quote(&self.sanitize(arg))));
+        parts.join(" ")
+    }
+
+    // a single line for the shell of this OS, with the secrets hidden
+    pub fn to_sanitized_string(&self) -> String {
+        self.to_shell_line(cfg!(windows))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitized_shell_line() {
+        let command = LaunchCommand {
+            java_path: PathBuf::from("/usr/bin/java"),
+            args: vec![
+                "-Xmx4096M".to_string(),
+                "-Dminecraft.launcher.brand=My Launcher".to_string(),
+                "--accessToken".to_string(),
+                "secret-token".to_string(),
+                "it's".to_string(),
+            ],
+            current_dir: PathBuf::from("/home/user/instance"),
+            envs: vec![("__GL_THREADED_OPTIMIZATIONS".to_string(), "0".to_string())],
+            secrets: vec!["secret-token".to_string()],
+        };
+        assert_eq!(
+            command.to_shell_line(false),
+            "cd /home/user/instance && __GL_THREADED_OPTIMIZATIONS=0 /usr/bin/java -Xmx4096M \
+             '-Dminecraft.launcher.brand=My Launcher' --accessToken '<hidden>' 'it'\\''s'"
+        );
+
+        assert_eq!(
+            quote_windows(r"C:\Program Files\Java\"),
+            r#""C:\Program Files\Java\\""#
+        );
+        assert_eq!(quote_windows(r#"say "hi""#), r#""say \"hi\"""#);
+        assert_eq!(
+            quote_windows(r"C:\java\bin\java.exe"),
+            r"C:\java\bin\java.exe"
+        );
+    }
+
+    #[test]
+    fn test_cmd_shell_line() {
+        let command = LaunchCommand {
+            java_path: PathBuf::from(r"C:\Program Files\Java\bin\java.exe"),
+            args: vec!["-Dname=%USERNAME% test".to_string(), "50%".to_string()],
+            current_dir: PathBuf::from(r"C:\Games\instance"),
+            envs: vec![("OPTS".to_string(), "a b&%PATH%".to_string())],
+            secrets: vec![],
+        };
+        assert_eq!(
+            command.to_shell_line(true),
+            r#"cd /d C:\Games\instance && set OPTS=a b^&^%PATH^%&& "C:\Program Files\Java\bin\java.exe" -Dname=^%USERNAME^%" test" 50^%"""#
+        );
+    }
+
+    #[test]
+    fn test_check_env_var_name() {
+        assert_eq!(check_env_var_name("MESA_GL_VERSION_OVERRIDE"), Ok(()));
+        assert_eq!(check_env_var_name(""), Err(EnvVarError::EmptyName));
+        assert_eq!(
+            check_env_var_name("A=B"),
+            Err(EnvVarError::NameContainsEquals("A=B".to_string()))
+        );
+    }
+}
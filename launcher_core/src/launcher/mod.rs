@@ -0,0 +1,16 @@
+pub mod branding;
+pub mod compat;
+pub mod crash_report;
+pub mod discord;
+pub mod display;
+pub mod game_options;
+pub mod game_output;
+pub mod gpu_errors;
+pub mod jvm_args;
+pub mod launch;
+pub mod launch_command;
+pub mod news;
+pub mod playtime;
+pub mod running_lock;
+pub mod shared_data;
+pub mod update;
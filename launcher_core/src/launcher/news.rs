@@ -0,0 +1,92 @@
+use serde::Deserialize;
+
+use super::update::{is_launcher_version_at_most, is_launcher_version_supported};
+use crate::config::build_config;
+use shared::json;
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct NewsEntry {
+    pub id: String,
+    pub title: String,
+    // markdown
+    pub body: String,
+    // unix seconds
+    pub timestamp: u64,
+    #[serde(default)]
+    pub min_launcher_version: Option<String>,
+    #[serde(default)]
+    pub max_launcher_version: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct NewsFeed {
+    entries: Vec<NewsEntry>,
+}
+
+impl NewsEntry {
+    fn is_shown_for(&self, launcher_version: Option<&str>) -> bool {
+        self.min_launcher_version
+            .as_deref()
+            .is_none_or(|min| is_launcher_version_supported(launcher_version, min))
+            && self
+                .max_launcher_version
+                .as_deref()
+                .is_none_or(|max| is_launcher_version_at_most(launcher_version, max))
+    }
+}
+
+// entries for other launcher versions are dropped, the newest entry comes first
+fn parse_news(
+    data: &[u8],
+    url: &str,
+    launcher_version: Option<&str>,
+) -> anyhow::Result<Vec<NewsEntry>> {
+    let feed: NewsFeed = json::from_slice(data, url)?;
+    let mut entries: Vec<_> = feed
+        .entries
+        .into_iter()
+        .filter(|entry| entry.is_shown_for(launcher_version))
+        .collect();
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.timestamp));
+    Ok(entries)
+}
+
+pub async fn fetch_news(url: &str) -> anyhow::Result<Vec<NewsEntry>> {
+    let data = reqwest::get(url).await?.error_for_status()?.bytes().await?;
+    parse_news(&data, url, build_config::get_version().as_deref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_news() {
+        let feed = br#"{"entries": [
+            {"id": "old", "title": "Old", "body": "text", "timestamp": 100},
+            {"id": "new", "title": "New", "body": "**text**", "timestamp": 300},
+            {"id": "future", "title": "Future", "body": "", "timestamp": 200,
+                "min_launcher_version": "2.0"},
+            {"id": "legacy", "title": "Legacy", "body": "", "timestamp": 200,
+                "max_launcher_version": "1.4"}
+        ]}"#;
+        let ids = |version| {
+            parse_news(feed, "news.json", version)
+                .unwrap()
+                .into_iter()
+                .map(|entry| entry.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(Some("1.5")), vec!["new", "old"]);
+        assert_eq!(ids(Some("2.0.1")), vec!["new", "future", "old"]);
+        assert_eq!(ids(Some("1.2")), vec!["new", "legacy", "old"]);
+        // dev builds see everything
+        assert_eq!(ids(None).len(), 4);
+
+        let err = parse_news(br#"{"entries": [{"id": 1}]}"#, "news.json", None).unwrap_err();
+        assert_eq!(
+            json::get_json_parse_error(&err).unwrap().path,
+            "entries[0].id"
+        );
+    }
+}
@@ -0,0 +1,122 @@
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+use shared::paths::{
+    get_instances_dir, get_launch_log_path, get_playtime_path, get_running_lock_path,
+};
+
+use super::running_lock;
+
+// kept in the instance dir, so it moves together with the instance
+#[derive(Serialize, Deserialize, Default)]
+struct Playtime {
+    #[serde(default)]
+    total_secs: u64,
+    // unix time of the launch, left set if the launcher was closed while the game ran
+    #[serde(default)]
+    session_started_at: Option<u64>,
+}
+
+impl Playtime {
+    fn read(launcher_dir: &Path, instance_name: &str) -> Playtime {
+        std::fs::read(get_playtime_path(launcher_dir, instance_name))
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default()
+    }
+
+    fn write(&self, launcher_dir: &Path, instance_name: &str) {
+        let path = get_playtime_path(launcher_dir, instance_name);
+        let result = serde_json::to_vec(self)
+            .map_err(std::io::Error::from)
+            .and_then(|data| std::fs::write(&path, data));
+        if let Err(e) = result {
+            warn!("Failed to write {}: {e}", path.display());
+        }
+    }
+
+    fn end_session(&mut self, ended_at: u64) {
+        if let Some(started_at) = self.session_started_at.take() {
+            self.total_secs += ended_at.saturating_sub(started_at);
+        }
+    }
+}
+
+fn to_unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+pub fn start_session(launcher_dir: &Path, instance_name: &str, started_at: SystemTime) {
+    let mut playtime = Playtime::read(launcher_dir, instance_name);
+    // an unfinished session of a game that outlived the launcher restart is dropped,
+    // its log was just overwritten by this launch
+    playtime.session_started_at = Some(to_unix_secs(started_at));
+    playtime.write(launcher_dir, instance_name);
+}
+
+pub fn end_session(launcher_dir: &Path, instance_name: &str, ended_at: SystemTime) {
+    let mut playtime = Playtime::read(launcher_dir, instance_name);
+    playtime.end_session(to_unix_secs(ended_at));
+    playtime.write(launcher_dir, instance_name);
+}
+
+// counts the sessions of the games that exited while the launcher was closed.
+// the game writes to the launch log until it exits, so the log's mtime is the end of the session
+pub fn reconcile_sessions(launcher_dir: &Path) {
+    let Ok(read_dir) = std::fs::read_dir(get_instances_dir(launcher_dir)) else {
+        return;
+    };
+    for entry in read_dir.filter_map(Result::ok) {
+        let instance_name = entry.file_name().to_string_lossy().to_string();
+        let mut playtime = Playtime::read(launcher_dir, &instance_name);
+        let Some(started_at) = playtime.session_started_at else {
+            continue;
+        };
+        if running_lock::is_locked(&get_running_lock_path(launcher_dir, &instance_name)) {
+            continue;
+        }
+        let ended_at = std::fs::metadata(get_launch_log_path(launcher_dir, &instance_name))
+            .and_then(|metadata| metadata.modified())
+            .map_or(started_at, to_unix_secs);
+        playtime.end_session(ended_at);
+        playtime.write(launcher_dir, &instance_name);
+    }
+}
+
+// finished sessions only
+pub fn get_total(launcher_dir: &Path, instance_name: &str) -> Duration {
+    Duration::from_secs(Playtime::read(launcher_dir, instance_name).total_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_playtime() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let launcher_dir = temp_dir.path();
+        let now = SystemTime::now();
+        let hour = Duration::from_secs(3600);
+
+        start_session(launcher_dir, "instance", now - hour * 3);
+        end_session(launcher_dir, "instance", now - hour * 2);
+        assert_eq!(get_total(launcher_dir, "instance"), hour);
+
+        // the launcher was closed, the game exited when it last wrote the log
+        start_session(launcher_dir, "instance", now - hour * 2);
+        let log_path = get_launch_log_path(launcher_dir, "instance");
+        std::fs::File::create(&log_path)
+            .unwrap()
+            .set_modified(now - hour)
+            .unwrap();
+        assert_eq!(get_total(launcher_dir, "instance"), hour);
+        reconcile_sessions(launcher_dir);
+        assert_eq!(get_total(launcher_dir, "instance"), hour * 2);
+        reconcile_sessions(launcher_dir);
+        assert_eq!(get_total(launcher_dir, "instance"), hour * 2);
+    }
+}
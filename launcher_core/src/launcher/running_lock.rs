@@ -0,0 +1,75 @@
+use std::path::Path;
+
+use log::warn;
+
+// the lock holds the game pid, so a lock left by a crashed game is ignored once the pid is gone
+pub fn write_running_lock(lock_path: &Path, pid: u32) {
+    if let Err(e) = std::fs::write(lock_path, pid.to_string()) {
+        warn!("Failed to write {}: {e}", lock_path.display());
+    }
+}
+
+pub fn remove_running_lock(lock_path: &Path) {
+    if lock_path.exists()
+        && let Err(e) = std::fs::remove_file(lock_path)
+    {
+        warn!("Failed to remove {}: {e}", lock_path.display());
+    }
+}
+
+pub fn is_locked(lock_path: &Path) -> bool {
+    std::fs::read_to_string(lock_path)
+        .ok()
+        .and_then(|pid| pid.trim().parse::<u32>().ok())
+        .is_some_and(is_process_alive)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn is_process_alive(pid: u32) -> bool {
+    std::process::Command::new("kill")
+        .arg("-0")
+        .arg(pid.to_string())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+#[cfg(target_os = "windows")]
+fn is_process_alive(pid: u32) -> bool {
+    use winapi::um::handleapi::CloseHandle;
+    use winapi::um::minwinbase::STILL_ACTIVE;
+    use winapi::um::processthreadsapi::{GetExitCodeProcess, OpenProcess};
+    use winapi::um::winnt::PROCESS_QUERY_LIMITED_INFORMATION;
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if handle.is_null() {
+            return false;
+        }
+        let mut exit_code = 0;
+        let result = GetExitCodeProcess(handle, &mut exit_code);
+        CloseHandle(handle);
+        result != 0 && exit_code == STILL_ACTIVE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_running_lock() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let lock_path = temp_dir.path().join("running.lock");
+
+        write_running_lock(&lock_path, std::process::id());
+        assert!(is_locked(&lock_path));
+
+        // pids are at most 2^22 on linux and 99999 on macos
+        write_running_lock(&lock_path, 4_194_305);
+        assert!(!is_locked(&lock_path));
+
+        remove_running_lock(&lock_path);
+        assert!(!is_locked(&lock_path));
+    }
+}
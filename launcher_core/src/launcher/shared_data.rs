@@ -0,0 +1,331 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum SharedEntry {
+    Saves,
+    ResourcePacks,
+    Screenshots,
+    Options,
+}
+
+impl SharedEntry {
+    pub const ALL: [SharedEntry; 4] = [
+        SharedEntry::Saves,
+        SharedEntry::ResourcePacks,
+        SharedEntry::Screenshots,
+        SharedEntry::Options,
+    ];
+
+    pub fn get_rel_path(&self) -> &'static str {
+        match self {
+            SharedEntry::Saves => "saves",
+            SharedEntry::ResourcePacks => "resourcepacks",
+            SharedEntry::Screenshots => "screenshots",
+            SharedEntry::Options => "options.txt",
+        }
+    }
+
+    fn is_dir(&self) -> bool {
+        *self != SharedEntry::Options
+    }
+}
+
+// entries listed here are linked from the instance minecraft dir into dir
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct SharedDataConfig {
+    pub dir: PathBuf,
+    pub entries: Vec<SharedEntry>,
+}
+
+impl SharedDataConfig {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            entries: SharedEntry::ALL.to_vec(),
+        }
+    }
+}
+
+pub(crate) fn is_link(path: &Path) -> bool {
+    // junctions are reported as symlinks too
+    fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_symlink())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn create_link(target: &Path, link: &Path, _is_dir: bool) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+// a mount point reparse point on an empty dir, what mklink /J does
+#[cfg(target_os = "windows")]
+fn create_junction(target: &Path, link: &Path) -> std::io::Result<()> {
+    use std::os::windows::ffi::OsStrExt;
+    use winapi::um::fileapi::{CreateFileW, OPEN_EXISTING};
+    use winapi::um::handleapi::{CloseHandle, INVALID_HANDLE_VALUE};
+    use winapi::um::ioapiset::DeviceIoControl;
+    use winapi::um::winbase::{FILE_FLAG_BACKUP_SEMANTICS, FILE_FLAG_OPEN_REPARSE_POINT};
+    use winapi::um::winioctl::FSCTL_SET_REPARSE_POINT;
+    use winapi::um::winnt::{GENERIC_WRITE, IO_REPARSE_TAG_MOUNT_POINT};
+
+    let target = std::path::absolute(target)?;
+    let target = target.as_os_str().encode_wide().collect::<Vec<u16>>();
+    // the substitute name is an nt path, the print name is what explorer shows
+    let verbatim_prefix: Vec<u16> = r"\\?\".encode_utf16().collect();
+    let print_name = match target.strip_prefix(verbatim_prefix.as_slice()) {
+        Some(rest) => rest.to_vec(),
+        None => target,
+    };
+    let substitute_name: Vec<u16> = r"\??\"
+        .encode_utf16()
+        .chain(print_name.iter().copied())
+        .collect();
+
+    // MountPointReparseBuffer: the name offsets and lengths in bytes, both names nul-terminated
+    let substitute_len = (substitute_name.len() * 2) as u16;
+    let print_len = (print_name.len() * 2) as u16;
+    let data_len = 8 + substitute_len + 2 + print_len + 2;
+    let mut buffer: Vec<u8> = vec![];
+    buffer.extend(IO_REPARSE_TAG_MOUNT_POINT.to_le_bytes());
+    buffer.extend(data_len.to_le_bytes());
+    buffer.extend(0u16.to_le_bytes());
+    for field in [0, substitute_len, substitute_len + 2, print_len] {
+        buffer.extend(field.to_le_bytes());
+    }
+    for c in substitute_name
+        .iter()
+        .chain(&[0])
+        .chain(&print_name)
+        .chain(&[0])
+    {
+        buffer.extend(c.to_le_bytes());
+    }
+
+    fs::create_dir(link)?;
+    let link_wide: Vec<u16> = link.as_os_str().encode_wide().chain(Some(0)).collect();
+    let result = unsafe {
+        let handle = CreateFileW(
+            link_wide.as_ptr(),
+            GENERIC_WRITE,
+            0,
+            std::ptr::null_mut(),
+            OPEN_EXISTING,
+            FILE_FLAG_OPEN_REPARSE_POINT | FILE_FLAG_BACKUP_SEMANTICS,
+            std::ptr::null_mut(),
+        );
+        if handle == INVALID_HANDLE_VALUE {
+            Err(std::io::Error::last_os_error())
+        } else {
+            let mut returned = 0;
+            let ok = DeviceIoControl(
+                handle,
+                FSCTL_SET_REPARSE_POINT,
+                buffer.as_mut_ptr().cast(),
+                buffer.len() as u32,
+                std::ptr::null_mut(),
+                0,
+                &mut returned,
+                std::ptr::null_mut(),
+            );
+            let result = if ok == 0 {
+                Err(std::io::Error::last_os_error())
+            } else {
+                Ok(())
+            };
+            CloseHandle(handle);
+            result
+        }
+    };
+    if result.is_err() {
+        let _ = fs::remove_dir(link);
+    }
+    result
+}
+
+// symlinks need developer mode on windows, junctions and hard links don't
+#[cfg(target_os = "windows")]
+fn create_link(target: &Path, link: &Path, is_dir: bool) -> std::io::Result<()> {
+    if is_dir {
+        create_junction(target, link)
+    } else {
+        std::os::windows::fs::symlink_file(target, link).or_else(|_| fs::hard_link(target, link))
+    }
+}
+
+fn remove_link(link: &Path, is_dir: bool) -> std::io::Result<()> {
+    if cfg!(windows) && is_dir {
+        fs::remove_dir(link)
+    } else {
+        fs::remove_file(link)
+    }
+}
+
+fn copy_recursive(from: &Path, to: &Path) -> anyhow::Result<()> {
+    if from.is_file() {
+        fs::copy(from, to)?;
+        return Ok(());
+    }
+    for entry in walkdir::WalkDir::new(from) {
+        let entry = entry?;
+        let target = to.join(entry.path().strip_prefix(from)?);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+// rename fails when the shared dir is on another drive, then the data is copied.
+// the source is only removed once the copy is complete
+fn move_path(from: &Path, to: &Path) -> anyhow::Result<()> {
+    match fs::rename(from, to) {
+        Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {
+            info!("Copying {} to {}", from.display(), to.display());
+            if let Err(e) = copy_recursive(from, to) {
+                let _ = fs::remove_dir_all(to).or_else(|_| fs::remove_file(to));
+                return Err(e);
+            }
+            if from.is_dir() {
+                fs::remove_dir_all(from)?;
+            } else {
+                fs::remove_file(from)?;
+            }
+            Ok(())
+        }
+        result => Ok(result?),
+    }
+}
+
+fn link_entry(minecraft_dir: &Path, shared_dir: &Path, entry: SharedEntry) -> anyhow::Result<()> {
+    let instance_path = minecraft_dir.join(entry.get_rel_path());
+    let shared_path = shared_dir.join(entry.get_rel_path());
+
+    if is_link(&instance_path) {
+        if fs::read_link(&instance_path).is_ok_and(|target| target == shared_path) {
+            return Ok(());
+        }
+        remove_link(&instance_path, entry.is_dir())?;
+    }
+
+    if instance_path.exists() {
+        if shared_path.exists() {
+            // never merge, the instance copy is kept next to the link
+            let backup_path = minecraft_dir.join(format!("{}.local", entry.get_rel_path()));
+            warn!(
+                "{} exists in both the instance and the shared directory, moving the instance copy to {}",
+                entry.get_rel_path(),
+                backup_path.display()
+            );
+            fs::rename(&instance_path, &backup_path)?;
+        } else {
+            info!("Moving {} to the shared directory", instance_path.display());
+            fs::create_dir_all(shared_dir)?;
+            move_path(&instance_path, &shared_path).with_context(|| {
+                format!(
+                    "Failed to move {} to {}",
+                    instance_path.display(),
+                    shared_path.display()
+                )
+            })?;
+        }
+    }
+
+    if !shared_path.exists() {
+        if entry.is_dir() {
+            fs::create_dir_all(&shared_path)?;
+        } else {
+            fs::create_dir_all(shared_dir)?;
+            fs::File::create(&shared_path)?;
+        }
+    }
+    create_link(&shared_path, &instance_path, entry.is_dir()).with_context(|| {
+        format!(
+            "Failed to link {} to {}",
+            instance_path.display(),
+            shared_path.display()
+        )
+    })
+}
+
+// links the configured entries into the shared dir and removes links that are no longer configured
+pub fn apply_shared_data(
+    minecraft_dir: &Path,
+    shared_data: Option<&SharedDataConfig>,
+) -> anyhow::Result<()> {
+    for entry in SharedEntry::ALL {
+        match shared_data.filter(|shared_data| shared_data.entries.contains(&entry)) {
+            Some(shared_data) => link_entry(minecraft_dir, &shared_data.dir, entry)?,
+            None => {
+                let instance_path = minecraft_dir.join(entry.get_rel_path());
+                if is_link(&instance_path) {
+                    info!("Unlinking {}", instance_path.display());
+                    remove_link(&instance_path, entry.is_dir())?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+// true if the path is reached through a link inside minecraft_dir, files there belong to the shared dir
+pub fn is_linked_path(minecraft_dir: &Path, path: &Path) -> bool {
+    path.ancestors()
+        .take_while(|ancestor| *ancestor != minecraft_dir && ancestor.starts_with(minecraft_dir))
+        .any(is_link)
+}
+
+#[cfg(all(test, not(target_os = "windows")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_shared_data() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        let minecraft_dir = root.join("minecraft");
+        let shared_dir = root.join("shared");
+        fs::create_dir_all(minecraft_dir.join("saves/world")).unwrap();
+        fs::write(minecraft_dir.join("saves/world/level.dat"), b"world").unwrap();
+
+        let mut shared_data = SharedDataConfig::new(shared_dir.clone());
+        shared_data.entries = vec![SharedEntry::Saves, SharedEntry::Options];
+        apply_shared_data(&minecraft_dir, Some(&shared_data)).unwrap();
+
+        let world_path = minecraft_dir.join("saves/world/level.dat");
+        assert_eq!(
+            fs::read(shared_dir.join("saves/world/level.dat")).unwrap(),
+            b"world"
+        );
+        assert!(is_linked_path(&minecraft_dir, &world_path));
+        assert!(shared_dir.join("options.txt").is_file());
+        assert!(!minecraft_dir.join("screenshots").exists());
+
+        apply_shared_data(&minecraft_dir, None).unwrap();
+        assert!(!minecraft_dir.join("saves").exists());
+        assert!(!is_linked_path(&minecraft_dir, &world_path));
+        assert!(shared_dir.join("saves/world/level.dat").is_file());
+    }
+
+    #[test]
+    fn test_copy_recursive() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let from = temp_dir.path().join("saves");
+        fs::create_dir_all(from.join("world/region")).unwrap();
+        fs::write(from.join("world/region/r.0.0.mca"), b"region").unwrap();
+
+        let to = temp_dir.path().join("shared/saves");
+        fs::create_dir_all(to.parent().unwrap()).unwrap();
+        copy_recursive(&from, &to).unwrap();
+        assert_eq!(
+            fs::read(to.join("world/region/r.0.0.mca")).unwrap(),
+            b"region"
+        );
+    }
+}
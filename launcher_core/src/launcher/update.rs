@@ -1,12 +1,15 @@
 use futures::StreamExt as _;
+use log::warn;
 use reqwest::Client;
 use std::process::Command;
 use std::sync::Arc;
 use std::{env, fs};
 
 use crate::config::build_config;
+use crate::config::runtime_config::write_unsaved_config;
 use crate::lang::LangMessage;
 use crate::utils;
+use shared::clock;
 use shared::progress::ProgressBar;
 
 fn update_url() -> Option<String> {
@@ -34,6 +37,7 @@ async fn fetch_new_version() -> anyhow::Result<String> {
     if let Some(version_url) = version_url() {
         let client = Client::new();
         let response = client.get(version_url).send().await?.error_for_status()?;
+        clock::record_server_date(&response);
         let text = response.text().await?;
         Ok(text.trim().to_string())
     } else {
@@ -47,6 +51,46 @@ pub async fn need_update() -> anyhow::Result<bool> {
     Ok(new_version != current_version)
 }
 
+fn parse_launcher_version(version: &str) -> Option<semver::Version> {
+    let version = version.trim().trim_start_matches('v');
+    let mut padded = version.to_string();
+    for _ in version.split('.').count()..3 {
+        padded.push_str(".0");
+    }
+    semver::Version::parse(&padded).ok()
+}
+
+// unknown or malformed versions are allowed, so dev builds can still launch everything
+pub fn is_launcher_version_supported(current_version: Option<&str>, min_version: &str) -> bool {
+    let Some(min) = parse_launcher_version(min_version) else {
+        warn!("Malformed minimum launcher version {min_version}, ignoring");
+        return true;
+    };
+    let Some(current_version) = current_version else {
+        warn!("Launcher version not set, can't check minimum launcher version {min_version}");
+        return true;
+    };
+    let Some(current) = parse_launcher_version(current_version) else {
+        warn!(
+            "Launcher version {current_version} is not semver, can't check minimum launcher version {min_version}"
+        );
+        return true;
+    };
+    current >= min
+}
+
+// same leniency as is_launcher_version_supported
+pub fn is_launcher_version_at_most(current_version: Option<&str>, max_version: &str) -> bool {
+    let Some(max) = parse_launcher_version(max_version) else {
+        warn!("Malformed maximum launcher version {max_version}, ignoring");
+        return true;
+    };
+    let Some(current) = current_version.and_then(parse_launcher_version) else {
+        return true;
+    };
+    current <= max
+}
+
 pub async fn download_new_launcher(
     progress_bar: Arc<dyn ProgressBar<LangMessage> + Send + Sync>,
 ) -> anyhow::Result<Vec<u8>> {
@@ -99,6 +143,7 @@ pub fn replace_launcher_and_start(new_binary: &[u8]) -> anyhow::Result<()> {
 
     let args: Vec<String> = env::args().collect();
     Command::new(&current_exe).args(&args[1..]).spawn()?;
+    write_unsaved_config();
     std::process::exit(0);
 }
 
@@ -144,5 +189,37 @@ pub fn replace_launcher_and_start(new_archive: &[u8]) -> anyhow::Result<()> {
 
     let args: Vec<String> = env::args().collect();
     Command::new(&current_exe).args(&args[1..]).spawn()?;
+    write_unsaved_config();
     std::process::exit(0);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_equal_version_is_supported() {
+        assert!(is_launcher_version_supported(Some("1.2.0"), "1.2.0"));
+        assert!(is_launcher_version_supported(Some("v1.2"), "1.2.0"));
+    }
+
+    #[test]
+    fn test_older_version_is_not_supported() {
+        assert!(!is_launcher_version_supported(Some("1.1.9"), "1.2.0"));
+        assert!(!is_launcher_version_supported(Some("1.10.0"), "1.10.1"));
+        assert!(is_launcher_version_supported(Some("1.10.0"), "1.9.5"));
+    }
+
+    #[test]
+    fn test_malformed_version_is_allowed() {
+        assert!(is_launcher_version_supported(
+            Some("0123456789abcdef0123456789abcdef01234567"),
+            "1.2.0"
+        ));
+        assert!(is_launcher_version_supported(
+            Some("1.0.0"),
+            "not a version"
+        ));
+        assert!(is_launcher_version_supported(None, "1.2.0"));
+    }
+}
@@ -0,0 +1,56 @@
+//! GUI-free core of the launcher: configuration, instance storage, syncing,
+//! launching and authentication.
+//!
+//! Long-running operations are async and report progress through
+//! [`shared::progress::ProgressBar`], with messages as [`lang::LangMessage`]
+//! so frontends can localize them. A minimal sync of a remote instance:
+//!
+//! ```no_run
+//! # async fn example(version_info: shared::version::version_manifest::VersionInfo) -> anyhow::Result<()> {
+//! use std::sync::Arc;
+//!
+//! use launcher_core::config::runtime_config::Config;
+//! use launcher_core::lang::LangMessage;
+//! use launcher_core::version::{complete_version_metadata::CompleteVersionMetadata, sync};
+//! use shared::adaptive_download::DownloadHealth;
+//! use shared::progress::{NoProgressBar, ProgressBar};
+//!
+//! let config = Config::load();
+//! let launcher_dir = config.get_launcher_dir();
+//! let metadata =
+//!     CompleteVersionMetadata::read_or_download(&version_info, &launcher_dir, None).await?;
+//! let progress_bar: Arc<dyn ProgressBar<LangMessage> + Send + Sync> = Arc::new(NoProgressBar);
+//! let plan = sync::plan_sync(
+//!     &metadata,
+//!     false,
+//!     &launcher_dir,
+//!     &config.get_assets_dir(),
+//!     progress_bar.clone(),
+//!     config.get_hash_limits(),
+//!     &config.mirror_rankings,
+//! )
+//! .await?;
+//! let (delete_extra_sender, _delete_extra_receiver) = tokio::sync::mpsc::unbounded_channel();
+//! let download_health = DownloadHealth::new();
+//! sync::execute_sync(
+//!     &metadata,
+//!     plan,
+//!     &launcher_dir,
+//!     progress_bar,
+//!     delete_extra_sender,
+//!     &download_health,
+//!     config.get_sync_settings(),
+//! )
+//! .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod auth;
+pub mod config;
+pub mod constants;
+pub mod lang;
+pub mod launcher;
+pub mod utils;
+pub mod vendor;
+pub mod version;
@@ -0,0 +1,124 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use log::debug;
+
+// how link_or_copy placed a file
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LinkKind {
+    // copy-on-write, the files share their blocks but are changed independently
+    Reflink,
+    HardLink,
+    Copy,
+}
+
+pub fn get_tmp_path(path: &Path) -> PathBuf {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".link_tmp");
+    PathBuf::from(tmp_path)
+}
+
+pub fn hard_link(original: &Path, link: &Path) -> io::Result<LinkKind> {
+    std::fs::hard_link(original, link)?;
+    Ok(LinkKind::HardLink)
+}
+
+pub fn reflink_or_hard_link(original: &Path, link: &Path) -> io::Result<LinkKind> {
+    if reflink_copy::reflink(original, link).is_ok() {
+        return Ok(LinkKind::Reflink);
+    }
+    hard_link(original, link)
+}
+
+// renames over target, the old file is kept if renaming fails
+fn replace(tmp_path: &Path, target: &Path) -> io::Result<()> {
+    // windows refuses to replace read-only files
+    #[cfg(windows)]
+    if let Ok(metadata) = std::fs::metadata(target)
+        && metadata.permissions().readonly()
+    {
+        let mut permissions = metadata.permissions();
+        #[allow(clippy::permissions_set_readonly_false)]
+        permissions.set_readonly(false);
+        std::fs::set_permissions(target, permissions)?;
+    }
+
+    if let Err(e) = std::fs::rename(tmp_path, target) {
+        let _ = std::fs::remove_file(tmp_path);
+        return Err(e);
+    }
+    Ok(())
+}
+
+// replaces target with a link to original, or a copy if linking fails:
+// other filesystems, FAT and some network drives, or the 1023 links per file limit of NTFS
+pub fn link_or_copy(
+    original: &Path,
+    target: &Path,
+    link: impl Fn(&Path, &Path) -> io::Result<LinkKind>,
+) -> io::Result<LinkKind> {
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let tmp_path = get_tmp_path(target);
+    let _ = std::fs::remove_file(&tmp_path);
+    let kind = match link(original, &tmp_path) {
+        Ok(kind) => kind,
+        Err(e) => {
+            debug!(
+                "Failed to link {} to {}, copying:\n{e:?}",
+                target.display(),
+                original.display()
+            );
+            std::fs::copy(original, &tmp_path)?;
+            LinkKind::Copy
+        }
+    };
+    replace(&tmp_path, target)?;
+    Ok(kind)
+}
+
+// gives a hard-linked file its own copy, so rewriting it doesn't change the other links
+pub fn unlink(path: &Path) -> io::Result<()> {
+    let tmp_path = get_tmp_path(path);
+    std::fs::copy(path, &tmp_path)?;
+    replace(&tmp_path, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_or_copy() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        let original = dir.join("original");
+        std::fs::write(&original, b"mod").unwrap();
+        let target = dir.join("mods/a.jar");
+        std::fs::create_dir_all(target.parent().unwrap()).unwrap();
+        std::fs::write(&target, b"old mod").unwrap();
+
+        // what windows returns for cross-volume links
+        let kind = link_or_copy(&original, &target, |_, _| {
+            Err(io::Error::new(
+                io::ErrorKind::CrossesDevices,
+                "not the same device",
+            ))
+        })
+        .unwrap();
+        assert_eq!(kind, LinkKind::Copy);
+        assert_eq!(std::fs::read(&target).unwrap(), b"mod");
+        assert!(!same_file::is_same_file(&original, &target).unwrap());
+        assert!(!get_tmp_path(&target).exists());
+
+        let kind = link_or_copy(&original, &target, hard_link).unwrap();
+        assert_eq!(kind, LinkKind::HardLink);
+        assert!(same_file::is_same_file(&original, &target).unwrap());
+
+        unlink(&target).unwrap();
+        assert!(!same_file::is_same_file(&original, &target).unwrap());
+        std::fs::write(&target, b"updated mod").unwrap();
+        assert_eq!(std::fs::read(&original).unwrap(), b"mod");
+    }
+}
@@ -0,0 +1,155 @@
+use serde::Deserialize;
+
+use crate::config::build_config;
+use crate::constants::{
+    XMX_DEFAULT, XMX_MAX, XMX_MAX_32_BIT, XMX_MIN, XMX_RESERVED_MEMORY, XMX_WARNING_RATIO,
+};
+use std::fs;
+use std::path::PathBuf;
+
+pub mod file_links;
+pub mod temp_files;
+pub mod windows;
+
+pub fn get_temp_dir() -> PathBuf {
+    let temp_dir = std::env::temp_dir();
+    let temp_dir = temp_dir.join(build_config::get_lower_launcher_name());
+    if !temp_dir.exists() {
+        fs::create_dir_all(&temp_dir).unwrap();
+    }
+    temp_dir
+}
+
+pub fn is_read_only_error(e: &anyhow::Error) -> bool {
+    if let Some(e) = e.downcast_ref::<std::io::Error>() {
+        return e.kind() == std::io::ErrorKind::PermissionDenied || e.raw_os_error() == Some(18);
+    }
+    false
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum SingleOrVec<T> {
+    Single(T),
+    Vec(Vec<T>),
+}
+
+impl<T> From<SingleOrVec<T>> for Vec<T> {
+    fn from(single_or_vec: SingleOrVec<T>) -> Vec<T> {
+        match single_or_vec {
+            SingleOrVec::Single(single) => vec![single],
+            SingleOrVec::Vec(vec) => vec,
+        }
+    }
+}
+
+pub fn get_data_dir() -> PathBuf {
+    let data_dir = dirs::data_dir()
+        .expect("Failed to get data directory")
+        .join(build_config::get_lower_launcher_name());
+    if !data_dir.exists() {
+        std::fs::create_dir_all(&data_dir).expect("Failed to create data directory");
+    }
+    data_dir
+}
+
+pub fn is_valid_minecraft_username(username: &str) -> bool {
+    if username.len() < 3 || username.len() > 16 {
+        return false;
+    }
+    for c in username.chars() {
+        if !c.is_ascii_alphanumeric() && c != '_' {
+            return false;
+        }
+    }
+    true
+}
+
+lazy_static::lazy_static! {
+    static ref total_memory: Option<u64> = sys_info::mem_info().ok().map(|mem_info| mem_info.total);
+}
+
+pub fn get_total_memory() -> Option<u64> {
+    *total_memory
+}
+
+// sampled on every call, unlike the total memory
+pub fn get_available_memory_mb() -> Option<u64> {
+    sys_info::mem_info()
+        .ok()
+        .map(|mem_info| mem_info.avail / 1024)
+}
+
+pub fn map_range(value: f64, from_min: f64, from_max: f64, to_min: f64, to_max: f64) -> f64 {
+    (value - from_min) / (from_max - from_min) * (to_max - to_min) + to_min
+}
+
+fn get_total_memory_mb() -> Option<u64> {
+    get_total_memory().map(|total| total / 1024)
+}
+
+fn get_xmx_limit(total_memory_mb: Option<u64>, java_32_bit: bool) -> u64 {
+    let limit = total_memory_mb.map_or(XMX_MAX, |total| {
+        total
+            .saturating_sub(XMX_RESERVED_MEMORY)
+            .clamp(XMX_MIN, XMX_MAX)
+    });
+    if java_32_bit {
+        limit.min(XMX_MAX_32_BIT)
+    } else {
+        limit
+    }
+}
+
+// the largest Xmx in MB that can be selected on this machine
+pub fn get_xmx_max(java_32_bit: bool) -> u64 {
+    get_xmx_limit(get_total_memory_mb(), java_32_bit)
+}
+
+pub fn is_xmx_too_high(xmx_mb: u64) -> bool {
+    get_total_memory_mb().is_some_and(|total| xmx_mb as f64 > total as f64 * XMX_WARNING_RATIO)
+}
+
+pub fn clamp_xmx(xmx: &str, java_32_bit: bool) -> String {
+    let xmx_mb = parse_xmx_mb(Some(xmx)).min(get_xmx_max(java_32_bit));
+    format!("{xmx_mb}M")
+}
+
+pub fn parse_xmx_mb(xmx: Option<&str>) -> u64 {
+    let mut xmx_mb = XMX_DEFAULT;
+    if let Some(xmx) = xmx {
+        if xmx.ends_with('M') || xmx.ends_with('m') {
+            let xmx = xmx.trim_end_matches(['M', 'm']);
+            if let Ok(xmx) = xmx.parse::<u64>() {
+                xmx_mb = xmx;
+            }
+        } else if xmx.ends_with('G') || xmx.ends_with('g') {
+            let xmx = xmx.trim_end_matches(['G', 'g']);
+            if let Ok(xmx) = xmx.parse::<u64>() {
+                xmx_mb = xmx * 1024;
+            }
+        } else if let Ok(xmx) = xmx.parse::<u64>() {
+            xmx_mb = xmx;
+        }
+    }
+
+    xmx_mb
+}
+
+pub fn format_xmx(xmx: Option<&str>) -> String {
+    let xmx_mb = parse_xmx_mb(xmx).min(get_xmx_max(false));
+    format!("{xmx_mb}M")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xmx_limit() {
+        assert_eq!(get_xmx_limit(Some(16384), false), 14336);
+        assert_eq!(get_xmx_limit(Some(16384), true), XMX_MAX_32_BIT);
+        assert_eq!(get_xmx_limit(Some(1024), false), XMX_MIN);
+        assert_eq!(get_xmx_limit(None, false), XMX_MAX);
+    }
+}
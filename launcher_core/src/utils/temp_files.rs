@@ -0,0 +1,144 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use log::{info, warn};
+use walkdir::WalkDir;
+
+// entries of removed instances are named {prefix}{pid}_{timestamp millis}[_{n}]
+const REMOVED_INSTANCE_PREFIX: &str = "removed_instance_";
+
+// deletion runs in a detached task, younger entries may still be in progress
+pub const ORPHANED_TEMP_FILES_AGE: Duration = Duration::from_secs(60 * 60);
+
+fn get_timestamp_millis(time: SystemTime) -> u128 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0)
+}
+
+// unique path in temp_dir to move a removed instance to
+pub fn get_removed_instance_path(temp_dir: &Path) -> PathBuf {
+    let name = format!(
+        "{REMOVED_INSTANCE_PREFIX}{}_{}",
+        std::process::id(),
+        get_timestamp_millis(SystemTime::now())
+    );
+    let mut path = temp_dir.join(&name);
+    let mut i = 1;
+    while path.exists() {
+        path = temp_dir.join(format!("{name}_{i}"));
+        i += 1;
+    }
+    path
+}
+
+// creation time of an entry made by get_removed_instance_path, None for foreign files
+fn parse_removed_instance_name(name: &str) -> Option<SystemTime> {
+    let mut parts = name.strip_prefix(REMOVED_INSTANCE_PREFIX)?.split('_');
+    let _pid: u32 = parts.next()?.parse().ok()?;
+    let millis: u64 = parts.next()?.parse().ok()?;
+    if let Some(n) = parts.next() {
+        let _: u32 = n.parse().ok()?;
+    }
+    if parts.next().is_some() {
+        return None;
+    }
+    Some(UNIX_EPOCH + Duration::from_millis(millis))
+}
+
+// launcher-created entries older than max_age, the name is used since renaming keeps the mtime
+pub fn find_temp_files(temp_dir: &Path, max_age: Duration) -> Vec<PathBuf> {
+    let Ok(read_dir) = std::fs::read_dir(temp_dir) else {
+        return vec![];
+    };
+    let now = SystemTime::now();
+    let mut paths: Vec<_> = read_dir
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            parse_removed_instance_name(&entry.file_name().to_string_lossy())
+                .is_some_and(|created| now.duration_since(created).unwrap_or_default() >= max_age)
+        })
+        .map(|entry| entry.path())
+        .collect();
+    paths.sort();
+    paths
+}
+
+pub fn get_temp_files_size(temp_dir: &Path) -> u64 {
+    find_temp_files(temp_dir, Duration::ZERO)
+        .iter()
+        .flat_map(WalkDir::new)
+        .filter_map(Result::ok)
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+// failures are logged and the rest is still removed, returns the removed paths
+pub fn remove_temp_files(temp_dir: &Path, max_age: Duration) -> Vec<PathBuf> {
+    let mut removed = vec![];
+    for path in find_temp_files(temp_dir, max_age) {
+        info!("Removing temporary files {}", path.display());
+        let result = if path.is_dir() {
+            std::fs::remove_dir_all(&path)
+        } else {
+            std::fs::remove_file(&path)
+        };
+        match result {
+            Ok(()) => removed.push(path),
+            // the deletion task of this session got to it first
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => warn!("Failed to remove {}:\n{e:?}", path.display()),
+        }
+    }
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_temp_files() {
+        let temp = tempfile::tempdir().unwrap();
+        let temp_dir = temp.path();
+
+        let old_millis = get_timestamp_millis(SystemTime::now() - Duration::from_secs(2 * 60 * 60));
+        let old_dir = temp_dir.join(format!("{REMOVED_INSTANCE_PREFIX}123_{old_millis}"));
+        std::fs::create_dir_all(old_dir.join("minecraft")).unwrap();
+        std::fs::write(old_dir.join("minecraft/options.txt"), "lang:en_us").unwrap();
+        let old_dir_2 = temp_dir.join(format!("{REMOVED_INSTANCE_PREFIX}123_{old_millis}_1"));
+        std::fs::create_dir(&old_dir_2).unwrap();
+
+        let new_dir = get_removed_instance_path(temp_dir);
+        std::fs::create_dir(&new_dir).unwrap();
+        assert_ne!(get_removed_instance_path(temp_dir), new_dir);
+
+        // files of other programs and other launcher temp files are never touched
+        for name in [
+            "java_download.zip",
+            "new_launcher",
+            &format!("{REMOVED_INSTANCE_PREFIX}abc_{old_millis}"),
+            &format!("{REMOVED_INSTANCE_PREFIX}123_{old_millis}_1_2"),
+        ] {
+            std::fs::write(temp_dir.join(name), "").unwrap();
+        }
+
+        assert_eq!(
+            find_temp_files(temp_dir, ORPHANED_TEMP_FILES_AGE),
+            vec![old_dir.clone(), old_dir_2.clone()]
+        );
+        assert_eq!(get_temp_files_size(temp_dir), 10);
+
+        assert_eq!(
+            remove_temp_files(temp_dir, ORPHANED_TEMP_FILES_AGE),
+            vec![old_dir.clone(), old_dir_2]
+        );
+        assert!(!old_dir.exists());
+        assert!(new_dir.exists());
+
+        assert_eq!(remove_temp_files(temp_dir, Duration::ZERO), vec![new_dir]);
+        assert_eq!(std::fs::read_dir(temp_dir).unwrap().count(), 4);
+    }
+}
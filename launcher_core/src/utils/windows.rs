@@ -0,0 +1,130 @@
+use std::path::Path;
+
+use crate::constants::ANTIVIRUS_LARGE_SYNC_FILES;
+
+// what a finished sync tells about antivirus interference
+pub struct AntivirusSignals {
+    pub downloaded_files: usize,
+    // the same content was synced before, so the downloaded files were changed or removed since
+    pub synced_before: bool,
+    // None if the Defender settings can't be read, which needs admin rights
+    pub excluded: Option<bool>,
+}
+
+pub fn should_warn_about_antivirus(signals: &AntivirusSignals) -> bool {
+    if signals.excluded == Some(true) || signals.downloaded_files == 0 {
+        return false;
+    }
+    // Defender quarantines jars and deletes files it's still scanning
+    if signals.synced_before {
+        return true;
+    }
+    // every file of a large sync is scanned, the first launch is very slow then
+    signals.excluded == Some(false) && signals.downloaded_files >= ANTIVIRUS_LARGE_SYNC_FILES
+}
+
+fn normalize_path(path: &str) -> String {
+    path.replace('/', "\\")
+        .trim_end_matches('\\')
+        .to_lowercase()
+}
+
+// exclusions cover their subdirectories, windows paths are case-insensitive
+pub fn is_path_excluded(exclusions: &[String], path: &str) -> bool {
+    let path = normalize_path(path);
+    exclusions.iter().any(|exclusion| {
+        let exclusion = normalize_path(exclusion);
+        !exclusion.is_empty()
+            && path
+                .strip_prefix(&exclusion)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('\\'))
+    })
+}
+
+#[cfg(target_os = "windows")]
+pub fn is_excluded_from_defender(path: &Path) -> Option<bool> {
+    use winreg::RegKey;
+    use winreg::enums::*;
+
+    let key = RegKey::predef(HKEY_LOCAL_MACHINE)
+        .open_subkey_with_flags(
+            r"SOFTWARE\Microsoft\Windows Defender\Exclusions\Paths",
+            KEY_READ,
+        )
+        .ok()?;
+    let exclusions: Vec<String> = key
+        .enum_values()
+        .filter_map(Result::ok)
+        .map(|(name, _)| name)
+        .collect();
+    Some(is_path_excluded(&exclusions, &path.to_string_lossy()))
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn is_excluded_from_defender(_path: &Path) -> Option<bool> {
+    None
+}
+
+// release builds have no console of their own, so the output would be lost
+// when they are run from a terminal
+#[cfg(target_os = "windows")]
+pub fn attach_console() {
+    use winapi::um::consoleapi::AllocConsole;
+    use winapi::um::wincon::{ATTACH_PARENT_PROCESS, AttachConsole};
+
+    unsafe {
+        if AttachConsole(ATTACH_PARENT_PROCESS) == 0 {
+            AllocConsole();
+        }
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn attach_console() {}
+
+pub fn open_windows_security() -> anyhow::Result<()> {
+    open::that("windowsdefender://threatsettings")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_antivirus_decision() {
+        let signals = |downloaded_files, synced_before, excluded| AntivirusSignals {
+            downloaded_files,
+            synced_before,
+            excluded,
+        };
+        assert!(should_warn_about_antivirus(&signals(3, true, None)));
+        assert!(should_warn_about_antivirus(&signals(
+            5000,
+            false,
+            Some(false)
+        )));
+        assert!(!should_warn_about_antivirus(&signals(3, true, Some(true))));
+        assert!(!should_warn_about_antivirus(&signals(0, true, None)));
+        assert!(!should_warn_about_antivirus(&signals(5000, false, None)));
+        assert!(!should_warn_about_antivirus(&signals(
+            10,
+            false,
+            Some(false)
+        )));
+
+        let exclusions = vec![r"C:\Users\User\AppData\Roaming\Launcher\".to_string()];
+        assert!(is_path_excluded(
+            &exclusions,
+            r"c:\users\user\appdata\roaming\launcher\instances"
+        ));
+        assert!(is_path_excluded(
+            &exclusions,
+            "C:/Users/User/AppData/Roaming/Launcher"
+        ));
+        assert!(!is_path_excluded(
+            &exclusions,
+            r"C:\Users\User\AppData\Roaming\Launcher2"
+        ));
+    }
+}
@@ -0,0 +1,415 @@
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use log::warn;
+use shared::paths::{get_archives_dir, get_instance_archive_path, get_rel_instance_dir};
+use shared::progress::ProgressBar;
+use walkdir::WalkDir;
+
+use crate::lang::LangMessage;
+use crate::launcher::running_lock::{is_locked, remove_running_lock, write_running_lock};
+
+use super::instance_storage::{InstanceStatus, LocalInstance};
+
+const ZSTD_LEVEL: i32 = 3;
+const ARCHIVE_EXTENSION: &str = ".tar.zst";
+const TAR_BLOCK_SIZE: u64 = 512;
+
+#[derive(thiserror::Error, Debug)]
+pub enum ArchiveError {
+    #[error("Instance {0} is archived")]
+    InstanceArchived(String),
+    #[error("Instance {0} is being archived")]
+    InstanceArchiving(String),
+    #[error("Instance {0} has no game files")]
+    NoGameFiles(String),
+    #[error("Archive of instance {0} not found")]
+    ArchiveNotFound(String),
+    #[error("Cancelled")]
+    Cancelled,
+}
+
+// get_minecraft_dir would create the directory
+fn get_game_dir(launcher_dir: &Path, instance_name: &str) -> PathBuf {
+    launcher_dir
+        .join(get_rel_instance_dir(instance_name))
+        .join("minecraft")
+}
+
+fn get_restoring_dir(launcher_dir: &Path, instance_name: &str) -> PathBuf {
+    launcher_dir
+        .join(get_rel_instance_dir(instance_name))
+        .join("minecraft.restoring")
+}
+
+// holds the launcher pid from the start of archiving until the instance is marked as archived
+fn get_archiving_lock_path(launcher_dir: &Path, instance_name: &str) -> PathBuf {
+    launcher_dir
+        .join(get_rel_instance_dir(instance_name))
+        .join("archiving.lock")
+}
+
+fn get_temp_archive_path(launcher_dir: &Path, instance_name: &str) -> PathBuf {
+    get_archives_dir(launcher_dir).join(format!("{instance_name}{ARCHIVE_EXTENSION}.tmp"))
+}
+
+// reads the status from the instance meta, so it works without an InstanceStorage
+pub fn is_archived(launcher_dir: &Path, instance_name: &str) -> bool {
+    let meta_path = launcher_dir
+        .join(get_rel_instance_dir(instance_name))
+        .join("meta.json");
+    fs::read(meta_path)
+        .ok()
+        .and_then(|data| serde_json::from_slice::<LocalInstance>(&data).ok())
+        .is_some_and(|instance| instance.status == InstanceStatus::Archived)
+}
+
+pub fn is_archiving(launcher_dir: &Path, instance_name: &str) -> bool {
+    is_locked(&get_archiving_lock_path(launcher_dir, instance_name))
+}
+
+// the game files can't be synced or launched while they are archived or being archived
+pub fn ensure_not_archived(launcher_dir: &Path, instance_name: &str) -> anyhow::Result<()> {
+    if is_archived(launcher_dir, instance_name) {
+        return Err(ArchiveError::InstanceArchived(instance_name.to_string()).into());
+    }
+    if is_archiving(launcher_dir, instance_name) {
+        return Err(ArchiveError::InstanceArchiving(instance_name.to_string()).into());
+    }
+    Ok(())
+}
+
+// called once the archived status is saved, it blocks syncs and launches from then on
+pub fn finish_archiving(launcher_dir: &Path, instance_name: &str) {
+    remove_running_lock(&get_archiving_lock_path(launcher_dir, instance_name));
+}
+
+// counts the bytes going through and stops the work once the task is dropped
+struct ProgressIo<'a, T> {
+    inner: T,
+    progress_bar: &'a dyn ProgressBar<LangMessage>,
+    cancelled: &'a AtomicBool,
+}
+
+impl<T> ProgressIo<'_, T> {
+    fn check_cancelled(&self) -> io::Result<()> {
+        if self.cancelled.load(Ordering::SeqCst) {
+            return Err(io::Error::other(ArchiveError::Cancelled));
+        }
+        Ok(())
+    }
+}
+
+impl<T: Read> Read for ProgressIo<'_, T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.check_cancelled()?;
+        let read = self.inner.read(buf)?;
+        self.progress_bar.inc(read as u64);
+        Ok(read)
+    }
+}
+
+impl<T: Write> Write for ProgressIo<'_, T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.check_cancelled()?;
+        let written = self.inner.write(buf)?;
+        self.progress_bar.inc(written as u64);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+struct CancelOnDrop(Arc<AtomicBool>);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+// blocking work that is stopped when the calling future is dropped
+pub(super) async fn run_blocking<T, F>(f: F) -> anyhow::Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&AtomicBool) -> anyhow::Result<T> + Send + 'static,
+{
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let _cancel_on_drop = CancelOnDrop(cancelled.clone());
+    tokio::task::spawn_blocking(move || f(&cancelled)).await?
+}
+
+fn write_archive(
+    game_dir: &Path,
+    archive_path: &Path,
+    progress_bar: &dyn ProgressBar<LangMessage>,
+    cancelled: &AtomicBool,
+) -> anyhow::Result<()> {
+    // progress is counted in tar stream bytes: a header block per entry,
+    // file data padded to whole blocks and two empty blocks at the end
+    let total_size: u64 = WalkDir::new(game_dir)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter_map(|entry| entry.metadata().ok())
+        .map(|metadata| {
+            let data_size = if metadata.is_file() {
+                metadata.len().div_ceil(TAR_BLOCK_SIZE) * TAR_BLOCK_SIZE
+            } else {
+                0
+            };
+            TAR_BLOCK_SIZE + data_size
+        })
+        .sum();
+    progress_bar.set_length(total_size + 2 * TAR_BLOCK_SIZE);
+
+    let encoder = zstd::Encoder::new(File::create(archive_path)?, ZSTD_LEVEL)?;
+    let mut builder = tar::Builder::new(ProgressIo {
+        inner: encoder,
+        progress_bar,
+        cancelled,
+    });
+    // shared data links must stay links after a restore
+    builder.follow_symlinks(false);
+    builder.append_dir_all(".", game_dir)?;
+    let file = builder.into_inner()?.inner.finish()?;
+    file.sync_all()?;
+    Ok(())
+}
+
+// compresses the game files of an instance into its archive, returns the archive size.
+// the caller marks the instance as archived, the game files are removed after that.
+// syncs and launches are refused until then, see finish_archiving
+pub async fn archive_instance(
+    launcher_dir: &Path,
+    instance_name: &str,
+    progress_bar: Arc<dyn ProgressBar<LangMessage> + Send + Sync>,
+) -> anyhow::Result<u64> {
+    let game_dir = get_game_dir(launcher_dir, instance_name);
+    let temp_path = get_temp_archive_path(launcher_dir, instance_name);
+    let archive_path = get_instance_archive_path(launcher_dir, instance_name);
+    let lock_path = get_archiving_lock_path(launcher_dir, instance_name);
+    let instance_name = instance_name.to_string();
+    progress_bar.set_message(LangMessage::ArchivingInstance);
+
+    run_blocking(move |cancelled| {
+        if !game_dir.is_dir() {
+            return Err(ArchiveError::NoGameFiles(instance_name).into());
+        }
+        write_running_lock(&lock_path, std::process::id());
+        let result = write_archive(&game_dir, &temp_path, progress_bar.as_ref(), cancelled)
+            .and_then(|()| Ok(fs::rename(&temp_path, &archive_path)?));
+        if result.is_err() {
+            let _ = fs::remove_file(&temp_path);
+            remove_running_lock(&lock_path);
+        }
+        result?;
+        Ok(fs::metadata(&archive_path)?.len())
+    })
+    .await
+}
+
+// unpacks into a separate directory first, so an interrupted restore leaves the archive
+// as the only copy of the game files and can simply be started again
+pub async fn restore_instance(
+    launcher_dir: &Path,
+    instance_name: &str,
+    progress_bar: Arc<dyn ProgressBar<LangMessage> + Send + Sync>,
+) -> anyhow::Result<()> {
+    let game_dir = get_game_dir(launcher_dir, instance_name);
+    let restoring_dir = get_restoring_dir(launcher_dir, instance_name);
+    let archive_path = get_instance_archive_path(launcher_dir, instance_name);
+    let instance_name = instance_name.to_string();
+    progress_bar.set_message(LangMessage::RestoringInstance);
+
+    run_blocking(move |cancelled| {
+        if !archive_path.is_file() {
+            return Err(ArchiveError::ArchiveNotFound(instance_name).into());
+        }
+        if restoring_dir.exists() {
+            fs::remove_dir_all(&restoring_dir)?;
+        }
+        // progress is counted in compressed bytes
+        progress_bar.set_length(fs::metadata(&archive_path)?.len());
+        let reader = ProgressIo {
+            inner: File::open(&archive_path)?,
+            progress_bar: progress_bar.as_ref(),
+            cancelled,
+        };
+        let result = zstd::Decoder::new(reader)
+            .map(tar::Archive::new)
+            .and_then(|mut archive| archive.unpack(&restoring_dir));
+        if let Err(e) = result {
+            let _ = fs::remove_dir_all(&restoring_dir);
+            return Err(e.into());
+        }
+        if game_dir.exists() {
+            fs::remove_dir_all(&game_dir)?;
+        }
+        fs::rename(&restoring_dir, &game_dir)?;
+        Ok(())
+    })
+    .await
+}
+
+pub async fn remove_game_files(launcher_dir: &Path, instance_name: &str) -> io::Result<()> {
+    let game_dir = get_game_dir(launcher_dir, instance_name);
+    if game_dir.exists() {
+        tokio::fs::remove_dir_all(&game_dir).await?;
+    }
+    Ok(())
+}
+
+pub async fn remove_archive(launcher_dir: &Path, instance_name: &str) -> io::Result<()> {
+    let archive_path = get_instance_archive_path(launcher_dir, instance_name);
+    if archive_path.exists() {
+        tokio::fs::remove_file(&archive_path).await?;
+    }
+    Ok(())
+}
+
+fn remove_leftover(path: &Path) {
+    let result = if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else if path.exists() {
+        fs::remove_file(path)
+    } else {
+        Ok(())
+    };
+    if let Err(e) = result {
+        warn!("Failed to remove {path:?}: {e:?}");
+    }
+}
+
+// finishes or rolls back an archive or a restore interrupted by a crash,
+// returns true if the instance meta has changed
+pub fn recover(launcher_dir: &Path, instance: &mut LocalInstance) -> bool {
+    let instance_name = instance.version_info.get_name();
+    remove_leftover(&get_temp_archive_path(launcher_dir, &instance_name));
+    remove_leftover(&get_restoring_dir(launcher_dir, &instance_name));
+    remove_leftover(&get_archiving_lock_path(launcher_dir, &instance_name));
+
+    let archive_path = get_instance_archive_path(launcher_dir, &instance_name);
+    if instance.status == InstanceStatus::Archived {
+        if !archive_path.exists() {
+            warn!(
+                "Archive of instance {instance_name} is missing, the instance will be synced again"
+            );
+            instance.status = InstanceStatus::Outdated;
+            instance.status_before_archive = None;
+            return true;
+        }
+        // the status was saved before the game files were removed
+        remove_leftover(&get_game_dir(launcher_dir, &instance_name));
+    } else {
+        // the archive was written, but the instance wasn't marked as archived,
+        // or it was restored, but the archive wasn't removed
+        remove_leftover(&archive_path);
+    }
+    false
+}
+
+// sizes of the archives on disk by instance name
+pub fn get_archive_sizes(launcher_dir: &Path) -> Vec<(String, u64)> {
+    let Ok(read_dir) = fs::read_dir(get_archives_dir(launcher_dir)) else {
+        return Vec::new();
+    };
+    let mut sizes: Vec<(String, u64)> = read_dir
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let instance_name = file_name.strip_suffix(ARCHIVE_EXTENSION)?.to_string();
+            Some((instance_name, entry.metadata().ok()?.len()))
+        })
+        .collect();
+    sizes.sort();
+    sizes
+}
+
+#[cfg(test)]
+mod tests {
+    use shared::progress::NoProgressBar;
+    use shared::version::version_manifest::VersionInfo;
+
+    use super::*;
+
+    fn local_instance(name: &str) -> LocalInstance {
+        LocalInstance {
+            version_info: VersionInfo {
+                id: "1.20.1".to_string(),
+                url: "https://example.com/1.20.1.json".to_string(),
+                sha1: "sha1".to_string(),
+                name: Some(name.to_string()),
+                inherits_from: vec![],
+                extra_metadata_url: None,
+                extra_metadata_sha1: None,
+                rollout_percent: None,
+                server_address: None,
+            },
+            status: InstanceStatus::UpToDate,
+            manifest_url: None,
+            always_allow_delete_extra: false,
+            content_hash: None,
+            auto_sync: false,
+            pinned: false,
+            status_before_archive: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_archive_and_restore() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let launcher_dir = temp_dir.path();
+        let game_dir = get_game_dir(launcher_dir, "test");
+        fs::create_dir_all(game_dir.join("saves/world")).unwrap();
+        fs::write(game_dir.join("options.txt"), "fov:90").unwrap();
+        fs::write(game_dir.join("saves/world/level.dat"), [1, 2, 3]).unwrap();
+        let progress_bar: Arc<dyn ProgressBar<LangMessage> + Send + Sync> = Arc::new(NoProgressBar);
+
+        archive_instance(launcher_dir, "test", progress_bar.clone())
+            .await
+            .unwrap();
+        // the instance isn't marked as archived yet
+        assert!(ensure_not_archived(launcher_dir, "test").is_err());
+        let mut instance = local_instance("test");
+        // crashed before the instance was marked as archived
+        assert!(!recover(launcher_dir, &mut instance));
+        assert!(get_archive_sizes(launcher_dir).is_empty());
+        assert!(game_dir.join("options.txt").exists());
+        assert!(ensure_not_archived(launcher_dir, "test").is_ok());
+
+        archive_instance(launcher_dir, "test", progress_bar.clone())
+            .await
+            .unwrap();
+        instance.status = InstanceStatus::Archived;
+        // crashed before the game files were removed
+        assert!(!recover(launcher_dir, &mut instance));
+        assert!(!game_dir.exists());
+        assert_eq!(get_archive_sizes(launcher_dir)[0].0, "test");
+
+        // an interrupted restore is started over
+        fs::create_dir_all(get_restoring_dir(launcher_dir, "test")).unwrap();
+        fs::write(get_restoring_dir(launcher_dir, "test").join("partial"), "").unwrap();
+        restore_instance(launcher_dir, "test", progress_bar)
+            .await
+            .unwrap();
+        assert_eq!(
+            fs::read_to_string(game_dir.join("options.txt")).unwrap(),
+            "fov:90"
+        );
+        assert_eq!(
+            fs::read(game_dir.join("saves/world/level.dat")).unwrap(),
+            [1, 2, 3]
+        );
+        assert!(!game_dir.join("partial").exists());
+
+        // the archive is gone but the meta still says archived
+        remove_archive(launcher_dir, "test").await.unwrap();
+        assert!(recover(launcher_dir, &mut instance));
+        assert!(instance.status == InstanceStatus::Outdated);
+    }
+}
@@ -1,10 +1,17 @@
 use shared::{
     adaptive_download::download_files,
     files::{self, CheckEntry},
-    paths::{get_client_jar_path, get_versions_dir, get_versions_extra_dir},
-    progress,
+    json,
+    paths::{
+        get_client_jar_path, get_extra_metadata_path, get_versions_dir, get_versions_extra_dir,
+    },
+    progress, signing,
     version::{
-        extra_version_metadata::{AuthBackend, ExtraVersionMetadata},
+        content_hash,
+        extra_version_metadata::{
+            AuthBackend, AuthlibInjector, ExtraVersionMetadata, InstanceDescription,
+            LibraryOverride, WindowBranding,
+        },
         version_manifest::VersionInfo,
         version_metadata::{Arguments, AssetIndex, Library, VersionMetadata},
     },
@@ -12,13 +19,18 @@ use shared::{
 use std::collections::HashMap;
 use std::path::Path;
 
-use super::{os, overrides::with_overrides};
+use super::{
+    os,
+    overrides::{with_library_overrides, with_overrides},
+};
 
 pub struct CompleteVersionMetadata {
     version_name: String,
     // ordered from parent to child
     base: Vec<VersionMetadata>,
     extra: Option<ExtraVersionMetadata>,
+    // resolved once, the fallback is logged
+    java_version: String,
 }
 
 const DEFAULT_RESOURCES_URL_BASE: &str = "https://resources.download.minecraft.net";
@@ -32,13 +44,25 @@ pub enum VersionMetadataError {
 }
 
 impl CompleteVersionMetadata {
-    pub async fn read_local(version_info: &VersionInfo, data_dir: &Path) -> anyhow::Result<Self> {
+    pub async fn read_local(
+        version_info: &VersionInfo,
+        data_dir: &Path,
+        public_key: Option<&str>,
+    ) -> anyhow::Result<Self> {
         let versions_dir = get_versions_dir(data_dir);
 
+        let metadata_info = version_info.get_metadata_info();
         let mut base = vec![];
         let mut version_id = version_info.id.to_string();
         loop {
-            let current_metadata = VersionMetadata::read_local(&versions_dir, &version_id).await?;
+            let current_metadata = VersionMetadata::read_local(&versions_dir, &version_id)
+                .await
+                .map_err(
+                    |e| match metadata_info.iter().find(|info| info.id == version_id) {
+                        Some(info) => json::with_url(e, &info.url),
+                        None => e,
+                    },
+                )?;
             let parent_id = current_metadata.inherits_from.clone();
             base.push(current_metadata);
             if let Some(id) = parent_id {
@@ -50,18 +74,27 @@ impl CompleteVersionMetadata {
         base = base.into_iter().rev().collect();
 
         let versions_extra_dir = get_versions_extra_dir(data_dir);
+        if let Some(public_key) = public_key
+            && version_info.extra_metadata_url.is_some()
+        {
+            let path = get_extra_metadata_path(&versions_extra_dir, &version_info.get_name());
+            signing::verify_file(&path, public_key).await?;
+        }
         let extra = ExtraVersionMetadata::read_local(version_info, &versions_extra_dir).await?;
 
+        let java_version = base[0].get_java_version();
         Ok(Self {
             version_name: version_info.get_name(),
             base,
             extra,
+            java_version,
         })
     }
 
     pub async fn read_or_download(
         version_info: &VersionInfo,
         data_dir: &Path,
+        public_key: Option<&str>,
     ) -> anyhow::Result<Self> {
         let versions_dir = get_versions_dir(data_dir);
         let versions_extra_dir = get_versions_extra_dir(data_dir);
@@ -83,7 +116,15 @@ impl CompleteVersionMetadata {
             files::get_download_entries(check_entries, progress::no_progress_bar()).await?;
         download_files(download_entries, progress::no_progress_bar()).await?;
 
-        Self::read_local(version_info, data_dir).await
+        if public_key.is_some()
+            && let Some(extra_metadata_url) = &version_info.extra_metadata_url
+        {
+            let signature = signing::fetch_signature(extra_metadata_url).await?;
+            let path = get_extra_metadata_path(&versions_extra_dir, &version_info.get_name());
+            tokio::fs::write(signing::get_signature_path(&path), signature).await?;
+        }
+
+        Self::read_local(version_info, data_dir, public_key).await
     }
 
     pub fn get_resources_url_base(&self) -> &str {
@@ -98,11 +139,7 @@ impl CompleteVersionMetadata {
     }
 
     pub fn get_java_version(&self) -> String {
-        self.base[0]
-            .java_version
-            .as_ref()
-            .map(|x| x.major_version.to_string())
-            .unwrap_or("8".to_string())
+        self.java_version.clone()
     }
 
     pub fn get_name(&self) -> &str {
@@ -123,15 +160,33 @@ impl CompleteVersionMetadata {
         self.extra.as_ref()?.auth_backend.as_ref()
     }
 
-    pub fn get_libraries_with_overrides(&self) -> Vec<Library> {
+    // None if the instance uses the launcher's authlib-injector
+    pub fn get_authlib_injector(&self) -> Option<AuthlibInjector> {
+        self.extra.as_ref()?.get_authlib_injector()
+    }
+
+    // local_overrides come from the launcher dir and take priority over the instance ones
+    pub fn get_libraries_with_overrides(
+        &self,
+        local_overrides: &[LibraryOverride],
+    ) -> Vec<Library> {
         let all_libraries = self
             .base
             .iter()
             .rev() // prioritize child libraries
-            .flat_map(|metadata| with_overrides(&metadata.libraries, &metadata.id));
+            .flat_map(|metadata| with_overrides(&metadata.libraries, &metadata.id))
+            .collect();
+        let overrides: Vec<&LibraryOverride> = self
+            .extra
+            .iter()
+            .flat_map(|extra| &extra.overrides)
+            .chain(local_overrides)
+            .collect();
+        let all_libraries = with_library_overrides(all_libraries, &overrides);
 
         let mut existing_names = HashMap::new();
         all_libraries
+            .into_iter()
             .filter(|library| library.applies_to_os(&os::get_os_name(), &os::get_system_arch()))
             .filter(|library| {
                 // Newer NeoForge versions add duplicate asm library
@@ -150,10 +205,6 @@ impl CompleteVersionMetadata {
         &self.base.last().unwrap().id
     }
 
-    pub fn get_parent_id(&self) -> &str {
-        &self.base[0].id
-    }
-
     pub fn get_asset_index(&self) -> anyhow::Result<&AssetIndex> {
         Ok(self.base[0]
             .asset_index
@@ -180,6 +231,15 @@ impl CompleteVersionMetadata {
         self.base.last().unwrap().main_class.as_str()
     }
 
+    pub fn get_content_hash(&self) -> String {
+        let ids: Vec<&str> = self
+            .base
+            .iter()
+            .map(|metadata| metadata.id.as_str())
+            .collect();
+        content_hash::get_content_hash(&ids, self.extra.as_ref())
+    }
+
     pub fn get_extra(&self) -> Option<&ExtraVersionMetadata> {
         self.extra.as_ref()
     }
@@ -191,9 +251,36 @@ impl CompleteVersionMetadata {
             .unwrap_or_default()
     }
 
+    pub fn get_min_launcher_version(&self) -> Option<&str> {
+        self.extra
+            .as_ref()
+            .and_then(|extra| extra.min_launcher_version.as_deref())
+    }
+
     pub fn get_recommended_xmx(&self) -> Option<&str> {
         self.extra
             .as_ref()
             .and_then(|extra| extra.recommended_xmx.as_deref())
     }
+
+    pub fn get_recommended_jvm_args(&self) -> &[String] {
+        self.extra
+            .as_ref()
+            .map(|extra| extra.recommended_jvm_args.as_slice())
+            .unwrap_or_default()
+    }
+
+    pub fn get_description(&self) -> Option<&InstanceDescription> {
+        self.extra
+            .as_ref()
+            .map(|extra| &extra.description)
+            .filter(|description| !description.is_empty())
+    }
+
+    pub fn get_branding(&self) -> Option<&WindowBranding> {
+        self.extra
+            .as_ref()
+            .map(|extra| &extra.branding)
+            .filter(|branding| !branding.is_empty())
+    }
 }
@@ -0,0 +1,201 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use shared::files::{self, HashLimits};
+use shared::paths::{get_dedup_database_path, get_instances_dir, get_libraries_dir};
+use shared::progress;
+use tokio::fs;
+
+use crate::launcher::shared_data::is_link;
+use crate::utils::file_links::{self, LinkKind};
+
+// hard-linked files, reflinks are copy-on-write and aren't tracked
+#[derive(Serialize, Deserialize, Default)]
+struct DedupDatabase {
+    linked: BTreeSet<PathBuf>,
+}
+
+impl DedupDatabase {
+    async fn load(launcher_dir: &Path) -> Self {
+        let path = get_dedup_database_path(launcher_dir);
+        match fs::read(&path).await {
+            Ok(data) => serde_json::from_slice(&data).unwrap_or_else(|e| {
+                warn!("Failed to parse dedup database, starting over:\n{e:?}");
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    async fn save(&self, launcher_dir: &Path) -> anyhow::Result<()> {
+        let path = get_dedup_database_path(launcher_dir);
+        fs::write(path, serde_json::to_vec(self)?).await?;
+        Ok(())
+    }
+}
+
+// FAT filesystems and some network drives on windows can't hard link
+async fn is_hard_link_supported(launcher_dir: &Path) -> bool {
+    let probe_path = launcher_dir.join(".dedup_probe");
+    let link_path = file_links::get_tmp_path(&probe_path);
+    let _ = fs::remove_file(&link_path).await;
+    let supported = fs::write(&probe_path, b"probe").await.is_ok()
+        && fs::hard_link(&probe_path, &link_path).await.is_ok()
+        && same_file::is_same_file(&probe_path, &link_path).unwrap_or(false);
+    let _ = fs::remove_file(&link_path).await;
+    let _ = fs::remove_file(&probe_path).await;
+    supported
+}
+
+fn get_candidate_files(launcher_dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let mut dirs = vec![get_libraries_dir(launcher_dir)];
+    let instances_dir = get_instances_dir(launcher_dir);
+    if let Ok(entries) = std::fs::read_dir(&instances_dir) {
+        for entry in entries.flatten() {
+            let minecraft_dir = entry.path().join("minecraft");
+            let mods_dir = minecraft_dir.join("mods");
+            if !is_link(&minecraft_dir) && !is_link(&mods_dir) {
+                dirs.push(mods_dir);
+            }
+        }
+    }
+
+    let mut result = vec![];
+    for dir in dirs {
+        for file in files::get_files_ignore_paths(&dir, &HashSet::new())? {
+            if !is_link(&file) {
+                result.push(file);
+            }
+        }
+    }
+    Ok(result)
+}
+
+// replaces duplicate with a link to original
+async fn link_file(original: &Path, duplicate: &Path) -> anyhow::Result<LinkKind> {
+    let (original, duplicate) = (original.to_path_buf(), duplicate.to_path_buf());
+    Ok(tokio::task::spawn_blocking(move || {
+        file_links::link_or_copy(&original, &duplicate, file_links::reflink_or_hard_link)
+    })
+    .await??)
+}
+
+// links files with the same sha1 across instance mods and libraries
+pub async fn deduplicate_files(launcher_dir: &Path, hash_limits: HashLimits) -> anyhow::Result<()> {
+    if !is_hard_link_supported(launcher_dir).await {
+        info!("Hard links are not supported in the launcher directory, skipping deduplication");
+        return Ok(());
+    }
+
+    let mut by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+    for path in get_candidate_files(launcher_dir)? {
+        let size = fs::metadata(&path).await?.len();
+        by_size.entry(size).or_default().push(path);
+    }
+    let mut paths: Vec<PathBuf> = by_size
+        .into_values()
+        .filter(|paths| paths.len() > 1)
+        .flatten()
+        .collect();
+    paths.sort();
+    let hashes =
+        files::hash_files_with_limits(paths.clone(), progress::no_progress_bar(), hash_limits)
+            .await?;
+
+    let mut by_hash: HashMap<String, Vec<PathBuf>> = HashMap::new();
+    for (path, hash) in paths.into_iter().zip(hashes) {
+        by_hash.entry(hash).or_default().push(path);
+    }
+
+    let mut database = DedupDatabase::load(launcher_dir).await;
+    database.linked.retain(|path| path.exists());
+    let mut linked_count = 0;
+    for paths in by_hash.values().filter(|paths| paths.len() > 1) {
+        let original = &paths[0];
+        for duplicate in &paths[1..] {
+            if same_file::is_same_file(original, duplicate).unwrap_or(false) {
+                continue;
+            }
+            match link_file(original, duplicate).await {
+                Ok(LinkKind::Copy) => {}
+                Ok(kind) => {
+                    if kind == LinkKind::HardLink {
+                        database.linked.insert(original.clone());
+                        database.linked.insert(duplicate.clone());
+                    }
+                    linked_count += 1;
+                }
+                Err(e) => warn!(
+                    "Failed to link {} to {}:\n{e:?}",
+                    duplicate.display(),
+                    original.display()
+                ),
+            }
+        }
+    }
+    database.save(launcher_dir).await?;
+
+    info!("Deduplicated {linked_count} files");
+    Ok(())
+}
+
+// gives hard-linked files a private copy so rewriting them doesn't change other instances
+pub async fn break_links(launcher_dir: &Path, paths: &[PathBuf]) -> anyhow::Result<()> {
+    let mut database = DedupDatabase::load(launcher_dir).await;
+    let linked: Vec<&PathBuf> = paths
+        .iter()
+        .filter(|path| database.linked.contains(*path))
+        .collect();
+    if linked.is_empty() {
+        return Ok(());
+    }
+
+    for path in linked {
+        if path.exists() {
+            let path = path.clone();
+            tokio::task::spawn_blocking(move || file_links::unlink(&path)).await??;
+        }
+        database.linked.remove(path);
+    }
+    database.save(launcher_dir).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::paths::get_minecraft_dir;
+
+    #[tokio::test]
+    async fn test_deduplicate_files() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let launcher_dir = temp_dir.path();
+        let mods = ["first", "second"].map(|name| {
+            let mods_dir = get_minecraft_dir(launcher_dir, name).join("mods");
+            std::fs::create_dir_all(&mods_dir).unwrap();
+            let path = mods_dir.join("mod.jar");
+            std::fs::write(&path, b"same mod").unwrap();
+            path
+        });
+
+        deduplicate_files(launcher_dir, HashLimits::default())
+            .await
+            .unwrap();
+        // reflinks are used instead where supported
+        let hard_linked = DedupDatabase::load(launcher_dir)
+            .await
+            .linked
+            .contains(&mods[1]);
+        assert_eq!(
+            same_file::is_same_file(&mods[0], &mods[1]).unwrap(),
+            hard_linked
+        );
+        assert_eq!(std::fs::read(&mods[1]).unwrap(), b"same mod");
+
+        break_links(launcher_dir, &[mods[1].clone()]).await.unwrap();
+        assert!(!same_file::is_same_file(&mods[0], &mods[1]).unwrap());
+        std::fs::write(&mods[1], b"updated mod").unwrap();
+        assert_eq!(std::fs::read(&mods[0]).unwrap(), b"same mod");
+    }
+}
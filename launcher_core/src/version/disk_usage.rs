@@ -0,0 +1,144 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use shared::files;
+use shared::paths::{get_instance_dir, get_libraries_dir, get_minecraft_dir};
+use shared::version::asset_metadata::AssetsMetadata;
+use walkdir::WalkDir;
+
+use super::complete_version_metadata::CompleteVersionMetadata;
+use super::{os, sync};
+
+// game dir subfolders the game and the loaders recreate when needed
+pub const CLEANABLE_DIRS: &[&str] = &["crash-reports", "logs", ".fabric/remappedJars"];
+
+#[derive(thiserror::Error, Debug)]
+pub enum DiskUsageError {
+    #[error("{0} is not a folder that can be cleared")]
+    NotCleanable(String),
+}
+
+pub struct DiskUsage {
+    // files behind shared data links aren't counted
+    pub instance: u64,
+    // libraries and assets can be shared with other instances
+    pub libraries: u64,
+    pub assets: u64,
+    pub assets_total: u64,
+    // sizes of the existing CLEANABLE_DIRS
+    pub cleanable: Vec<(&'static str, u64)>,
+}
+
+fn get_dir_size(path: &Path) -> u64 {
+    WalkDir::new(path)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+fn get_files_size(paths: HashSet<PathBuf>) -> u64 {
+    paths
+        .iter()
+        .filter_map(|path| std::fs::metadata(path).ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+pub fn get_cleanable_sizes(minecraft_dir: &Path) -> Vec<(&'static str, u64)> {
+    CLEANABLE_DIRS
+        .iter()
+        .filter(|dir| minecraft_dir.join(dir).exists())
+        .map(|dir| (*dir, get_dir_size(&minecraft_dir.join(dir))))
+        .collect()
+}
+
+// walks the whole instance dir, so it shouldn't run on the ui thread
+pub async fn get_disk_usage(
+    version_metadata: &CompleteVersionMetadata,
+    launcher_dir: &Path,
+    assets_dir: &Path,
+) -> anyhow::Result<DiskUsage> {
+    let instance_name = version_metadata.get_name();
+    let instance_dir = get_instance_dir(launcher_dir, instance_name);
+    let minecraft_dir = get_minecraft_dir(launcher_dir, instance_name);
+
+    let libraries_dir = get_libraries_dir(launcher_dir);
+    let os_name = os::get_os_name();
+    let arch = os::get_system_arch();
+    let library_paths: HashSet<PathBuf> = sync::get_libraries(version_metadata, launcher_dir)?
+        .iter()
+        .flat_map(|library| library.get_check_entries(&libraries_dir, Some((&os_name, &arch))))
+        .map(|entry| entry.path)
+        .collect();
+
+    // the index is missing until the first sync
+    let asset_id = &version_metadata.get_asset_index()?.id;
+    let asset_paths: HashSet<PathBuf> = match AssetsMetadata::read_local(asset_id, assets_dir).await
+    {
+        Ok(metadata) => metadata
+            .get_check_entries(assets_dir, "", false)?
+            .into_iter()
+            .map(|entry| entry.path)
+            .collect(),
+        Err(_) => HashSet::new(),
+    };
+
+    let assets_dir = assets_dir.to_path_buf();
+    Ok(tokio::task::spawn_blocking(move || DiskUsage {
+        instance: get_dir_size(&instance_dir),
+        libraries: get_files_size(library_paths),
+        assets: get_files_size(asset_paths),
+        assets_total: get_dir_size(&assets_dir),
+        cleanable: get_cleanable_sizes(&minecraft_dir),
+    })
+    .await?)
+}
+
+// only one of CLEANABLE_DIRS, the game can't be running
+pub async fn clean_dir(launcher_dir: &Path, instance_name: &str, dir: &str) -> anyhow::Result<()> {
+    if !CLEANABLE_DIRS.contains(&dir) {
+        return Err(DiskUsageError::NotCleanable(dir.to_string()).into());
+    }
+    sync::ensure_not_running(launcher_dir, instance_name)?;
+    let minecraft_dir = get_minecraft_dir(launcher_dir, instance_name);
+    files::remove_file_or_dir(&minecraft_dir.join(dir)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_clean_dir() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let launcher_dir = temp_dir.path();
+        let minecraft_dir = get_minecraft_dir(launcher_dir, "instance");
+        for (path, size) in [
+            ("logs/latest.log", 10),
+            ("logs/2024-01-01-1.log.gz", 5),
+            (".fabric/remappedJars/minecraft/client.jar", 20),
+            ("mods/a.jar", 7),
+        ] {
+            let path = minecraft_dir.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, vec![0; size]).unwrap();
+        }
+
+        assert_eq!(
+            get_cleanable_sizes(&minecraft_dir),
+            vec![("logs", 15), (".fabric/remappedJars", 20)]
+        );
+
+        clean_dir(launcher_dir, "instance", "logs").await.unwrap();
+        assert!(!minecraft_dir.join("logs").exists());
+        assert!(clean_dir(launcher_dir, "instance", "mods").await.is_err());
+        assert!(minecraft_dir.join("mods/a.jar").exists());
+        assert_eq!(
+            get_cleanable_sizes(&minecraft_dir),
+            vec![(".fabric/remappedJars", 20)]
+        );
+    }
+}
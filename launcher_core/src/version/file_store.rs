@@ -0,0 +1,352 @@
+// mods are downloaded once per sha1 into the store and hard linked into the instances.
+// the dedup database only tracks the links deduplicate_files creates: store links are
+// already the same file as their object, so deduplicate_files skips them, and a sync
+// replaces them by renaming instead of writing through the link
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use log::{debug, info, warn};
+use shared::files::{self, DownloadEntry};
+use shared::paths::{
+    get_file_store_dir, get_instance_meta_path, get_instances_dir, get_versions_extra_dir,
+};
+use shared::version::extra_version_metadata::ExtraVersionMetadata;
+use tokio::fs;
+
+use crate::utils::file_links::{self, LinkKind};
+
+use super::instance_storage::LocalInstance;
+
+#[derive(thiserror::Error, Debug)]
+pub enum FileStoreError {
+    #[error("Downloaded file {path} has sha1 {actual}, expected {expected}")]
+    HashMismatch {
+        path: PathBuf,
+        expected: String,
+        actual: String,
+    },
+}
+
+// a target waiting for its object to be downloaded into the store
+pub struct StoreLink {
+    object: PathBuf,
+    target: PathBuf,
+    sha1: String,
+}
+
+fn get_object_path(store_dir: &Path, sha1: &str) -> PathBuf {
+    store_dir.join(&sha1[..2]).join(sha1)
+}
+
+// the game can write to configs in place, which would change the file of every instance
+fn is_store_candidate(minecraft_dir: &Path, entry: &DownloadEntry) -> bool {
+    entry.path.starts_with(minecraft_dir.join("mods"))
+        && entry
+            .sha1
+            .as_ref()
+            .is_some_and(|sha1| sha1.len() == 40 && sha1.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+// hard links rather than reflinks, so placed files are recognized by is_same_file.
+// true if it's a hard link
+async fn place_file(object: PathBuf, target: PathBuf) -> anyhow::Result<bool> {
+    if same_file::is_same_file(&object, &target).unwrap_or(false) {
+        return Ok(true);
+    }
+    let kind = tokio::task::spawn_blocking(move || {
+        file_links::link_or_copy(&object, &target, file_links::hard_link)
+    })
+    .await??;
+    Ok(kind == LinkKind::HardLink)
+}
+
+// links mods that are already in the store, the missing ones are downloaded into the store
+// once per sha1 and linked by finish_downloads
+pub async fn prepare_downloads(
+    launcher_dir: &Path,
+    minecraft_dir: &Path,
+    entries: Vec<DownloadEntry>,
+) -> anyhow::Result<(Vec<DownloadEntry>, Vec<StoreLink>)> {
+    let store_dir = get_file_store_dir(launcher_dir);
+    let mut download_entries = vec![];
+    let mut links = vec![];
+    let mut queued_objects = HashSet::new();
+    let mut placed_count = 0;
+    for entry in entries {
+        if !is_store_candidate(minecraft_dir, &entry) {
+            download_entries.push(entry);
+            continue;
+        }
+        let sha1 = entry.sha1.clone().unwrap().to_lowercase();
+        let object = get_object_path(&store_dir, &sha1);
+
+        let object_size = fs::metadata(&object)
+            .await
+            .ok()
+            .map(|metadata| metadata.len());
+        if object_size.is_some() && entry.size.is_none_or(|size| Some(size) == object_size) {
+            match place_file(object.clone(), entry.path.clone()).await {
+                Ok(_) => {
+                    placed_count += 1;
+                    continue;
+                }
+                Err(e) => warn!(
+                    "Failed to place {} from the file store:\n{e:?}",
+                    entry.path.display()
+                ),
+            }
+        }
+
+        if queued_objects.insert(object.clone()) {
+            download_entries.push(DownloadEntry {
+                urls: entry.urls.clone(),
+                path: object.clone(),
+                size: entry.size,
+                sha1: Some(sha1.clone()),
+            });
+        }
+        links.push(StoreLink {
+            object,
+            target: entry.path,
+            sha1,
+        });
+    }
+    debug!(
+        "Placed {placed_count} files from the file store, {} to download into it",
+        queued_objects.len()
+    );
+    Ok((download_entries, links))
+}
+
+// objects with a wrong hash are removed, so they can't end up in other instances
+pub async fn finish_downloads(links: Vec<StoreLink>) -> anyhow::Result<()> {
+    let mut checked_objects = HashSet::new();
+    let mut copied_count = 0;
+    for link in links {
+        if checked_objects.insert(link.object.clone()) {
+            let actual = files::hash_file(&link.object).await?;
+            if actual != link.sha1 {
+                let _ = fs::remove_file(&link.object).await;
+                return Err(FileStoreError::HashMismatch {
+                    path: link.target,
+                    expected: link.sha1,
+                    actual,
+                }
+                .into());
+            }
+        }
+        if !place_file(link.object, link.target).await? {
+            copied_count += 1;
+        }
+    }
+    if copied_count > 0 {
+        info!("Copied {copied_count} files from the file store, hard links aren't available");
+    }
+    Ok(())
+}
+
+async fn get_referenced_hashes(launcher_dir: &Path) -> anyhow::Result<HashSet<String>> {
+    let versions_extra_dir = get_versions_extra_dir(launcher_dir);
+    let mut hashes = HashSet::new();
+    let Ok(mut entries) = fs::read_dir(get_instances_dir(launcher_dir)).await else {
+        return Ok(hashes);
+    };
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let meta_path = get_instance_meta_path(launcher_dir, &name);
+        let Ok(data) = fs::read(&meta_path).await else {
+            continue;
+        };
+        // instance files are links or copies, so a missed reference only costs a download
+        let instance = match serde_json::from_slice::<LocalInstance>(&data) {
+            Ok(instance) => instance,
+            Err(e) => {
+                warn!("Failed to read {}:\n{e:?}", meta_path.display());
+                continue;
+            }
+        };
+        match ExtraVersionMetadata::read_local(&instance.version_info, &versions_extra_dir).await {
+            Ok(Some(extra)) => hashes.extend(
+                extra
+                    .include
+                    .iter()
+                    .flat_map(|rule| &rule.objects)
+                    .map(|object| object.sha1.to_lowercase()),
+            ),
+            Ok(None) => {}
+            Err(e) => warn!("Failed to read extra metadata of {name}:\n{e:?}"),
+        }
+    }
+    Ok(hashes)
+}
+
+// removes store objects no instance meta refers to, returns the freed size
+pub async fn clean_unused_objects(launcher_dir: &Path) -> anyhow::Result<u64> {
+    let referenced = get_referenced_hashes(launcher_dir).await?;
+    let store_dir = get_file_store_dir(launcher_dir);
+    if !store_dir.exists() {
+        return Ok(0);
+    }
+
+    let mut freed = 0;
+    let mut removed_count = 0;
+    for path in files::get_files_ignore_paths(&store_dir, &HashSet::new())? {
+        let Some(name) = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+        else {
+            continue;
+        };
+        if referenced.contains(&name) {
+            continue;
+        }
+        let size = fs::metadata(&path)
+            .await
+            .map_or(0, |metadata| metadata.len());
+        // windows can't remove a link of a file the game has open
+        match fs::remove_file(&path).await {
+            Ok(()) => {
+                freed += size;
+                removed_count += 1;
+            }
+            Err(e) => warn!("Failed to remove {}:\n{e:?}", path.display()),
+        }
+    }
+    files::remove_empty_dirs(&store_dir).await?;
+    info!("Removed {removed_count} unused files from the file store");
+    Ok(freed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::paths::get_minecraft_dir;
+
+    fn get_entry(minecraft_dir: &Path, name: &str, sha1: &str) -> DownloadEntry {
+        DownloadEntry {
+            urls: vec![format!("https://example.com/{name}")],
+            path: minecraft_dir.join("mods").join(name),
+            size: None,
+            sha1: Some(sha1.to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_file_store() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let launcher_dir = temp_dir.path();
+        let content = b"same mod";
+        std::fs::create_dir_all(launcher_dir).unwrap();
+        std::fs::write(launcher_dir.join("mod.jar"), content).unwrap();
+        let sha1 = files::hash_file(&launcher_dir.join("mod.jar"))
+            .await
+            .unwrap();
+        let [first_dir, second_dir] =
+            ["first", "second"].map(|name| get_minecraft_dir(launcher_dir, name));
+
+        // the same mod in two instances is downloaded into the same object
+        let mut entries = vec![
+            get_entry(&first_dir, "a.jar", &sha1),
+            get_entry(&second_dir, "a.jar", &sha1),
+        ];
+        entries.push(DownloadEntry {
+            path: first_dir.join("config/a.toml"),
+            ..get_entry(&first_dir, "a.toml", &sha1)
+        });
+        let (mut first_entries, first_links) =
+            prepare_downloads(launcher_dir, &first_dir, entries.drain(..1).collect())
+                .await
+                .unwrap();
+        let (second_entries, second_links) =
+            prepare_downloads(launcher_dir, &second_dir, entries.drain(..1).collect())
+                .await
+                .unwrap();
+        let (config_entries, _) = prepare_downloads(launcher_dir, &first_dir, entries)
+            .await
+            .unwrap();
+        // configs are downloaded in place
+        assert_eq!(config_entries[0].path, first_dir.join("config/a.toml"));
+        let object = first_entries.remove(0).path;
+        assert_eq!(object, second_entries[0].path);
+
+        // a broken download isn't kept in the store
+        std::fs::create_dir_all(object.parent().unwrap()).unwrap();
+        std::fs::write(&object, b"broken").unwrap();
+        assert!(finish_downloads(first_links).await.is_err());
+        assert!(!object.exists());
+
+        std::fs::write(&object, content).unwrap();
+        finish_downloads(second_links).await.unwrap();
+        let second_mod = second_dir.join("mods/a.jar");
+        assert!(same_file::is_same_file(&object, &second_mod).unwrap());
+
+        // already stored objects are linked without downloading
+        let (entries, links) = prepare_downloads(
+            launcher_dir,
+            &first_dir,
+            vec![get_entry(&first_dir, "a.jar", &sha1)],
+        )
+        .await
+        .unwrap();
+        assert!(entries.is_empty() && links.is_empty());
+        let first_mod = first_dir.join("mods/a.jar");
+        assert!(same_file::is_same_file(&object, &first_mod).unwrap());
+
+        // deleting an instance leaves the store intact, the object is still used by the other one
+        std::fs::remove_dir_all(get_instances_dir(launcher_dir).join("first")).unwrap();
+        assert_eq!(std::fs::read(&object).unwrap(), content);
+    }
+
+    #[tokio::test]
+    async fn test_clean_unused_objects() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let launcher_dir = temp_dir.path();
+        let used_sha1 = "a".repeat(40);
+        let unused_sha1 = "b".repeat(40);
+
+        let meta = serde_json::json!({
+            "version_info": {
+                "id": "1.20.1",
+                "url": "https://example.com/1.20.1.json",
+                "sha1": "0".repeat(40),
+                "name": "instance",
+                "extra_metadata_url": "https://example.com/instance.json",
+                "extra_metadata_sha1": "0".repeat(40),
+            },
+            "status": "UpToDate",
+            "manifest_url": null,
+        });
+        let instance: LocalInstance = serde_json::from_value(meta).unwrap();
+        std::fs::write(
+            get_instance_meta_path(launcher_dir, "instance"),
+            serde_json::to_vec(&instance).unwrap(),
+        )
+        .unwrap();
+        let extra = serde_json::json!({
+            "include": [{"path": "mods", "objects": [{
+                "path": "mods/a.jar",
+                "sha1": used_sha1,
+                "url": "https://example.com/a.jar",
+                "size": null,
+            }]}],
+            "recommended_xmx": null,
+        });
+        let extra: ExtraVersionMetadata = serde_json::from_value(extra).unwrap();
+        extra
+            .save("instance", &get_versions_extra_dir(launcher_dir))
+            .await
+            .unwrap();
+
+        let store_dir = get_file_store_dir(launcher_dir);
+        for sha1 in [&used_sha1, &unused_sha1] {
+            let object = get_object_path(&store_dir, sha1);
+            std::fs::create_dir_all(object.parent().unwrap()).unwrap();
+            std::fs::write(object, b"mod").unwrap();
+        }
+
+        assert_eq!(clean_unused_objects(launcher_dir).await.unwrap(), 3);
+        assert!(get_object_path(&store_dir, &used_sha1).exists());
+        assert!(!get_object_path(&store_dir, &unused_sha1).exists());
+        assert!(!store_dir.join("bb").exists());
+    }
+}
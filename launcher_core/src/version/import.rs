@@ -0,0 +1,727 @@
+use std::fs::{self, File};
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use log::warn;
+use shared::generate::manifest::get_version_info;
+use shared::paths::{
+    get_rel_extra_metadata_path, get_rel_instance_dir, get_rel_metadata_path, get_rel_versions_dir,
+    get_rel_versions_extra_dir,
+};
+use shared::progress::ProgressBar;
+use shared::utils::{get_safe_path, path_to_slash_str};
+use shared::version::version_metadata::VersionMetadata;
+use walkdir::WalkDir;
+
+use crate::lang::LangMessage;
+
+use super::archive::{ArchiveError, run_blocking};
+use super::instance_storage::{InstanceStatus, LocalInstance};
+
+// an exported instance is a zip written by export_instance:
+//   meta.json                    the LocalInstance, its name is suggested on import
+//   minecraft/...                the game files, without the linked shared data
+//   versions/<id>/<id>.json      metadata of the version and the versions it inherits from
+//   versions_extra/<name>.json   extra metadata of the instance, if it has one
+// the metadata is only used on import if the launcher doesn't have a version with the same id
+const EXPORT_META_NAME: &str = "meta.json";
+const EXPORT_MINECRAFT_DIR: &str = "minecraft";
+// metadata of an archive is extracted here first and moved out once everything is extracted
+const IMPORT_STAGING_DIR: &str = "import_metadata";
+
+// entries of a .minecraft directory that the launcher keeps outside of instances
+const FOLDER_SKIPPED_ENTRIES: &[&str] = &["versions", "libraries", "assets", "runtime"];
+
+#[derive(thiserror::Error, Debug)]
+pub enum ImportError {
+    #[error("Invalid instance name {0}")]
+    InvalidName(String),
+    #[error("Directory of instance {0} already exists")]
+    InstanceDirExists(String),
+    #[error("{0} is not an exported instance")]
+    NotExportedInstance(PathBuf),
+    #[error("Version {0} not found in the imported directory")]
+    VersionNotFound(String),
+    #[error("Unsafe path in the archive: {0}")]
+    UnsafeEntryPath(String),
+    #[error("Cancelled")]
+    Cancelled,
+}
+
+pub enum ImportSource {
+    // zip made by the export
+    Archive(PathBuf),
+    // .minecraft-style directory and the id of the version to launch it with
+    Folder { path: PathBuf, version_id: String },
+}
+
+fn check_instance_name(instance_name: &str) -> Result<(), ImportError> {
+    let mut components = Path::new(instance_name).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(name)), None) if name == instance_name => Ok(()),
+        _ => Err(ImportError::InvalidName(instance_name.to_string())),
+    }
+}
+
+fn check_cancelled(cancelled: &AtomicBool) -> Result<(), ImportError> {
+    if cancelled.load(Ordering::SeqCst) {
+        return Err(ImportError::Cancelled);
+    }
+    Ok(())
+}
+
+// the instance the archive was exported from, is used to suggest a name
+pub fn read_archive_instance(archive_path: &Path) -> anyhow::Result<LocalInstance> {
+    let mut archive = zip::ZipArchive::new(File::open(archive_path)?)?;
+    let meta = archive
+        .by_name(EXPORT_META_NAME)
+        .map_err(|_| ImportError::NotExportedInstance(archive_path.to_path_buf()))?;
+    Ok(serde_json::from_reader(meta)?)
+}
+
+// ids of the versions installed in a .minecraft-style directory, empty for other directories
+pub fn list_folder_versions(path: &Path) -> Vec<String> {
+    let versions_dir = path.join(get_rel_versions_dir());
+    let Ok(read_dir) = fs::read_dir(&versions_dir) else {
+        return vec![];
+    };
+    let mut version_ids: Vec<_> = read_dir
+        .filter_map(Result::ok)
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|id| versions_dir.join(get_rel_metadata_path(id)).is_file())
+        .collect();
+    version_ids.sort();
+    version_ids
+}
+
+fn write_file(data: &mut impl io::Read, path: &Path) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    io::copy(data, &mut File::create(path)?)?;
+    Ok(())
+}
+
+// the metadata is written to the dirs shared by all instances last, so a failed import only
+// leaves files in its own instance dir. Metadata of other instances with the same id is never
+// replaced, the copied files are removed again if one of them fails
+fn install_metadata(files: &[(PathBuf, PathBuf)]) -> io::Result<()> {
+    let mut installed = vec![];
+    for (source, target) in files {
+        if target.exists() {
+            continue;
+        }
+        if let Err(e) = write_file(&mut File::open(source)?, target) {
+            for path in installed.into_iter().chain([target]) {
+                let _ = fs::remove_file(path);
+            }
+            return Err(e);
+        }
+        installed.push(target);
+    }
+    Ok(())
+}
+
+fn extract_archive(
+    archive_path: &Path,
+    launcher_dir: &Path,
+    instance_name: &str,
+    cancelled: &AtomicBool,
+) -> anyhow::Result<LocalInstance> {
+    let mut instance = read_archive_instance(archive_path)?;
+    let exported_name = instance.version_info.get_name();
+    let rel_extra_metadata_path = get_rel_extra_metadata_path(&exported_name)
+        .to_string_lossy()
+        .to_string();
+    let instance_dir = launcher_dir.join(get_rel_instance_dir(instance_name));
+    let minecraft_dir = instance_dir.join(EXPORT_MINECRAFT_DIR);
+    let staging_dir = instance_dir.join(IMPORT_STAGING_DIR);
+    let versions_dir = launcher_dir.join(get_rel_versions_dir());
+    let versions_extra_dir = launcher_dir.join(get_rel_versions_extra_dir());
+    fs::create_dir_all(&minecraft_dir)?;
+    // (staged path, path in the launcher dir)
+    let mut metadata_files = vec![];
+
+    let mut archive = zip::ZipArchive::new(File::open(archive_path)?)?;
+    for i in 0..archive.len() {
+        check_cancelled(cancelled)?;
+        let mut entry = archive.by_index(i)?;
+        let entry_path = entry
+            .enclosed_name()
+            .ok_or_else(|| ImportError::UnsafeEntryPath(entry.name().to_string()))?;
+        if entry.is_symlink() {
+            return Err(ImportError::UnsafeEntryPath(entry.name().to_string()).into());
+        }
+
+        if let Ok(rel_path) = entry_path.strip_prefix(EXPORT_MINECRAFT_DIR) {
+            let path = get_safe_path(&minecraft_dir, rel_path)
+                .ok_or_else(|| ImportError::UnsafeEntryPath(entry.name().to_string()))?;
+            if entry.is_dir() {
+                fs::create_dir_all(&path)?;
+            } else {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                io::copy(&mut entry, &mut File::create(&path)?)?;
+            }
+        } else if entry.is_dir() {
+            continue;
+        } else if let Ok(rel_path) = entry_path.strip_prefix(get_rel_versions_dir()) {
+            let staged_path = get_safe_path(&staging_dir, &entry_path)
+                .ok_or_else(|| ImportError::UnsafeEntryPath(entry.name().to_string()))?;
+            let path = get_safe_path(&versions_dir, rel_path)
+                .ok_or_else(|| ImportError::UnsafeEntryPath(entry.name().to_string()))?;
+            write_file(&mut entry, &staged_path)?;
+            metadata_files.push((staged_path, path));
+        } else if let Ok(rel_path) = entry_path.strip_prefix(get_rel_versions_extra_dir())
+            && rel_path == Path::new(&rel_extra_metadata_path)
+        {
+            let staged_path = staging_dir.join(&entry_path);
+            // extra metadata is looked up by the instance name
+            let path = versions_extra_dir.join(get_rel_extra_metadata_path(instance_name));
+            write_file(&mut entry, &staged_path)?;
+            metadata_files.push((staged_path, path));
+        }
+    }
+    check_cancelled(cancelled)?;
+    install_metadata(&metadata_files)?;
+    fs::remove_dir_all(&staging_dir).or_else(|e| match e.kind() {
+        io::ErrorKind::NotFound => Ok(()),
+        _ => Err(e),
+    })?;
+
+    if instance_name != exported_name {
+        instance.version_info.name = Some(instance_name.to_string());
+        // the remote manifest doesn't know the new name
+        instance.manifest_url = None;
+    }
+    instance.status = InstanceStatus::UpToDate;
+    instance.status_before_archive = None;
+    Ok(instance)
+}
+
+fn copy_folder(
+    folder_path: &Path,
+    minecraft_dir: &Path,
+    cancelled: &AtomicBool,
+) -> anyhow::Result<()> {
+    let walker = WalkDir::new(folder_path).into_iter().filter_entry(|entry| {
+        entry.depth() != 1
+            || !FOLDER_SKIPPED_ENTRIES.contains(&entry.file_name().to_string_lossy().as_ref())
+    });
+    for entry in walker {
+        check_cancelled(cancelled)?;
+        let entry = entry?;
+        let path = minecraft_dir.join(entry.path().strip_prefix(folder_path)?);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&path)?;
+        } else if entry.file_type().is_file() {
+            fs::copy(entry.path(), &path)?;
+        }
+    }
+    Ok(())
+}
+
+// the version and its parents, the version itself is the last one.
+// also returns the (folder path, path in the launcher dir) of every metadata file
+async fn read_folder_metadata(
+    folder_path: &Path,
+    launcher_dir: &Path,
+    version_id: &str,
+) -> anyhow::Result<(Vec<VersionMetadata>, Vec<(PathBuf, PathBuf)>)> {
+    let folder_versions_dir = folder_path.join(get_rel_versions_dir());
+    let versions_dir = launcher_dir.join(get_rel_versions_dir());
+    let mut version_metadata = vec![];
+    let mut metadata_files = vec![];
+    let mut next_id = Some(version_id.to_string());
+    while let Some(id) = next_id {
+        let rel_metadata_path = get_rel_metadata_path(&id);
+        let path = folder_versions_dir.join(&rel_metadata_path);
+        if !path.is_file() {
+            return Err(ImportError::VersionNotFound(id).into());
+        }
+        let metadata: VersionMetadata = serde_json::from_slice(&tokio::fs::read(&path).await?)?;
+        metadata_files.push((path, versions_dir.join(&rel_metadata_path)));
+        next_id = metadata.inherits_from.clone();
+        version_metadata.insert(0, metadata);
+    }
+    Ok((version_metadata, metadata_files))
+}
+
+// unpacks the source into a new instance directory and returns the instance to register.
+// nothing is left in the instance directory if the import fails or is cancelled
+pub async fn import_instance(
+    launcher_dir: &Path,
+    source: ImportSource,
+    instance_name: &str,
+) -> anyhow::Result<LocalInstance> {
+    check_instance_name(instance_name)?;
+    let instance_dir = launcher_dir.join(get_rel_instance_dir(instance_name));
+    if instance_dir.exists() {
+        return Err(ImportError::InstanceDirExists(instance_name.to_string()).into());
+    }
+    let launcher_dir = launcher_dir.to_path_buf();
+    let instance_name = instance_name.to_string();
+
+    let result = match source {
+        ImportSource::Archive(archive_path) => {
+            let instance_dir = instance_dir.clone();
+            run_blocking(move |cancelled| {
+                let result =
+                    extract_archive(&archive_path, &launcher_dir, &instance_name, cancelled);
+                if result.is_err() {
+                    remove_instance_dir(&instance_dir);
+                }
+                result
+            })
+            .await
+        }
+        ImportSource::Folder { path, version_id } => {
+            // version info is built before any game files are copied
+            let (version_metadata, metadata_files) =
+                read_folder_metadata(&path, &launcher_dir, &version_id).await?;
+            // hashed where they are, they are copied only after the game files
+            let replaced_metadata = version_metadata
+                .iter()
+                .zip(&metadata_files)
+                .map(|(metadata, (folder_path, _))| (metadata.id.clone(), folder_path.clone()))
+                .collect();
+            let version_info = get_version_info(
+                &launcher_dir,
+                &version_metadata,
+                &instance_name,
+                None,
+                &replaced_metadata,
+            )
+            .await?;
+            let minecraft_dir = instance_dir.join(EXPORT_MINECRAFT_DIR);
+            let instance_dir = instance_dir.clone();
+            run_blocking(move |cancelled| {
+                let result = copy_folder(&path, &minecraft_dir, cancelled)
+                    .and_then(|()| Ok(install_metadata(&metadata_files)?));
+                if result.is_err() {
+                    remove_instance_dir(&instance_dir);
+                }
+                result
+            })
+            .await
+            .map(|()| LocalInstance {
+                version_info,
+                status: InstanceStatus::UpToDate,
+                manifest_url: None,
+                always_allow_delete_extra: false,
+                content_hash: None,
+                auto_sync: false,
+                pinned: false,
+                status_before_archive: None,
+            })
+        }
+    };
+    // the blocking task may have not started at all
+    if result.is_err() {
+        remove_instance_dir(&instance_dir);
+    }
+    result
+}
+
+// (path in the archive, path on disk) of every file of an export
+fn list_export_files(
+    launcher_dir: &Path,
+    instance: &LocalInstance,
+) -> anyhow::Result<Vec<(PathBuf, PathBuf)>> {
+    let instance_name = instance.version_info.get_name();
+    let minecraft_dir = launcher_dir
+        .join(get_rel_instance_dir(&instance_name))
+        .join(EXPORT_MINECRAFT_DIR);
+    if !minecraft_dir.is_dir() {
+        return Err(ArchiveError::NoGameFiles(instance_name).into());
+    }
+    let mut files = vec![];
+    // links to the shared data aren't followed, they belong to all instances
+    for entry in WalkDir::new(&minecraft_dir) {
+        let entry = entry?;
+        if entry.file_type().is_file() {
+            let rel_path = entry.path().strip_prefix(&minecraft_dir)?;
+            files.push((
+                Path::new(EXPORT_MINECRAFT_DIR).join(rel_path),
+                entry.path().to_path_buf(),
+            ));
+        }
+    }
+
+    let version_ids = instance
+        .version_info
+        .inherits_from
+        .iter()
+        .map(|metadata_info| metadata_info.id.as_str())
+        .chain([instance.version_info.id.as_str()]);
+    for id in version_ids {
+        let rel_path = get_rel_versions_dir().join(get_rel_metadata_path(id));
+        let path = launcher_dir.join(&rel_path);
+        if path.is_file() {
+            files.push((rel_path, path));
+        }
+    }
+    let rel_path = get_rel_versions_extra_dir().join(get_rel_extra_metadata_path(&instance_name));
+    let path = launcher_dir.join(&rel_path);
+    if path.is_file() {
+        files.push((rel_path, path));
+    }
+    Ok(files)
+}
+
+fn write_export(
+    launcher_dir: &Path,
+    instance: &LocalInstance,
+    archive_path: &Path,
+    progress_bar: &dyn ProgressBar<LangMessage>,
+    cancelled: &AtomicBool,
+) -> anyhow::Result<()> {
+    let files = list_export_files(launcher_dir, instance)?;
+    let sizes = files
+        .iter()
+        .map(|(_, path)| Ok(fs::metadata(path)?.len()))
+        .collect::<io::Result<Vec<_>>>()?;
+    progress_bar.set_length(sizes.iter().sum());
+
+    let mut zip = zip::ZipWriter::new(File::create(archive_path)?);
+    let options = zip::write::SimpleFileOptions::default();
+    zip.start_file(EXPORT_META_NAME, options)?;
+    serde_json::to_writer(&mut zip, instance)?;
+    for ((rel_path, path), size) in files.iter().zip(sizes) {
+        check_cancelled(cancelled)?;
+        zip.start_file(
+            path_to_slash_str(rel_path)?,
+            options.large_file(size >= u32::MAX as u64),
+        )?;
+        io::copy(&mut File::open(path)?, &mut zip)?;
+        progress_bar.inc(size);
+    }
+    zip.finish()?.sync_all()?;
+    Ok(())
+}
+
+// writes the instance to a zip that import_instance accepts, see the format above.
+// the archive is written next to archive_path first, so a failed export leaves nothing
+pub async fn export_instance(
+    launcher_dir: &Path,
+    instance: LocalInstance,
+    archive_path: &Path,
+    progress_bar: Arc<dyn ProgressBar<LangMessage> + Send + Sync>,
+) -> anyhow::Result<()> {
+    let launcher_dir = launcher_dir.to_path_buf();
+    let archive_path = archive_path.to_path_buf();
+    let mut temp_path = archive_path.clone().into_os_string();
+    temp_path.push(".tmp");
+    let temp_path = PathBuf::from(temp_path);
+    progress_bar.set_message(LangMessage::ExportingInstance);
+
+    run_blocking(move |cancelled| {
+        let result = write_export(
+            &launcher_dir,
+            &instance,
+            &temp_path,
+            progress_bar.as_ref(),
+            cancelled,
+        );
+        if result.is_err() {
+            let _ = fs::remove_file(&temp_path);
+        }
+        result?;
+        fs::rename(&temp_path, &archive_path)?;
+        Ok(())
+    })
+    .await
+}
+
+fn remove_instance_dir(instance_dir: &Path) {
+    if instance_dir.exists()
+        && let Err(e) = fs::remove_dir_all(instance_dir)
+    {
+        warn!("Failed to remove {instance_dir:?}: {e:?}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use shared::version::version_manifest::VersionInfo;
+
+    use super::*;
+
+    fn exported_instance(name: &str) -> LocalInstance {
+        LocalInstance {
+            version_info: VersionInfo {
+                id: "1.20.1".to_string(),
+                url: "empty-url/versions/1.20.1/1.20.1.json".to_string(),
+                sha1: "sha1".to_string(),
+                name: Some(name.to_string()),
+                inherits_from: vec![],
+                extra_metadata_url: None,
+                extra_metadata_sha1: None,
+                rollout_percent: None,
+                server_address: None,
+            },
+            status: InstanceStatus::Archived,
+            manifest_url: Some("https://example.com/manifest.json".to_string()),
+            always_allow_delete_extra: false,
+            content_hash: None,
+            auto_sync: false,
+            pinned: false,
+            status_before_archive: Some(InstanceStatus::UpToDate),
+        }
+    }
+
+    fn write_zip(path: &Path, entries: &[(&str, &[u8])]) {
+        let mut writer = zip::ZipWriter::new(File::create(path).unwrap());
+        for (name, data) in entries {
+            writer
+                .start_file(*name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_import_archive() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        let launcher_dir = dir.join("launcher");
+        fs::create_dir_all(&launcher_dir).unwrap();
+        let meta = serde_json::to_vec(&exported_instance("Exported")).unwrap();
+
+        let archive_path = dir.join("exported.zip");
+        write_zip(
+            &archive_path,
+            &[
+                (EXPORT_META_NAME, &meta),
+                ("minecraft/options.txt", b"lang:en_us"),
+                ("minecraft/mods/mod.jar", b"mod"),
+                ("versions/1.20.1/1.20.1.json", b"{}"),
+                ("versions_extra/Exported.json", b"{}"),
+                ("versions_extra/Other.json", b"{}"),
+            ],
+        );
+        assert_eq!(
+            read_archive_instance(&archive_path)
+                .unwrap()
+                .version_info
+                .get_name(),
+            "Exported"
+        );
+
+        let instance = import_instance(
+            &launcher_dir,
+            ImportSource::Archive(archive_path.clone()),
+            "Imported",
+        )
+        .await
+        .unwrap();
+        assert_eq!(instance.version_info.get_name(), "Imported");
+        assert!(instance.status == InstanceStatus::UpToDate);
+        assert!(instance.manifest_url.is_none());
+        let minecraft_dir = launcher_dir.join("instances/Imported/minecraft");
+        assert_eq!(
+            fs::read(minecraft_dir.join("mods/mod.jar")).unwrap(),
+            b"mod"
+        );
+        assert!(launcher_dir.join("versions/1.20.1/1.20.1.json").is_file());
+        assert!(launcher_dir.join("versions_extra/Imported.json").is_file());
+        assert!(!launcher_dir.join("versions_extra/Other.json").exists());
+
+        assert!(
+            import_instance(
+                &launcher_dir,
+                ImportSource::Archive(archive_path),
+                "Imported"
+            )
+            .await
+            .is_err()
+        );
+        assert!(minecraft_dir.join("options.txt").is_file());
+
+        // nothing is left of a failed import
+        let corrupt_path = dir.join("corrupt.zip");
+        write_zip(
+            &corrupt_path,
+            &[
+                (EXPORT_META_NAME, &meta),
+                ("versions/1.19/1.19.json", b"{}"),
+                ("minecraft/options.txt", b"lang:en_us"),
+                ("minecraft/../../escape.txt", b""),
+            ],
+        );
+        assert!(
+            import_instance(
+                &launcher_dir,
+                ImportSource::Archive(corrupt_path),
+                "Corrupt"
+            )
+            .await
+            .is_err()
+        );
+        assert!(!launcher_dir.join("instances/Corrupt").exists());
+        assert!(!launcher_dir.join("versions/1.19").exists());
+
+        for name in ["", "..", "a/b"] {
+            assert!(check_instance_name(name).is_err(), "{name}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_export_instance() {
+        let dir = tempfile::tempdir().unwrap();
+        let launcher_dir = dir.path().join("launcher");
+        let mut instance = exported_instance("Exported");
+        instance.status = InstanceStatus::UpToDate;
+        for (path, data) in [
+            ("instances/Exported/minecraft/options.txt", "lang:en_us"),
+            ("instances/Exported/minecraft/mods/mod.jar", "mod"),
+            ("instances/Exported/meta.json", "{}"),
+            ("versions/1.20.1/1.20.1.json", "{}"),
+            ("versions/1.19/1.19.json", "{}"),
+            ("versions_extra/Exported.json", "{}"),
+        ] {
+            let path = launcher_dir.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, data).unwrap();
+        }
+        #[cfg(unix)]
+        {
+            let shared_dir = launcher_dir.join("shared/saves");
+            fs::create_dir_all(&shared_dir).unwrap();
+            fs::write(shared_dir.join("level.dat"), "level").unwrap();
+            std::os::unix::fs::symlink(
+                &shared_dir,
+                launcher_dir.join("instances/Exported/minecraft/saves"),
+            )
+            .unwrap();
+        }
+
+        let archive_path = dir.path().join("exported.zip");
+        export_instance(
+            &launcher_dir,
+            instance,
+            &archive_path,
+            Arc::new(shared::progress::NoProgressBar),
+        )
+        .await
+        .unwrap();
+        assert!(!dir.path().join("exported.zip.tmp").exists());
+
+        let mut archive = zip::ZipArchive::new(File::open(&archive_path).unwrap()).unwrap();
+        let mut names: Vec<_> = archive.file_names().collect();
+        names.sort();
+        assert_eq!(
+            names,
+            [
+                EXPORT_META_NAME,
+                "minecraft/mods/mod.jar",
+                "minecraft/options.txt",
+                "versions/1.20.1/1.20.1.json",
+                "versions_extra/Exported.json",
+            ]
+        );
+        assert!(archive.by_name(EXPORT_META_NAME).is_ok());
+
+        let other_launcher_dir = dir.path().join("other_launcher");
+        let imported = import_instance(
+            &other_launcher_dir,
+            ImportSource::Archive(archive_path),
+            "Exported",
+        )
+        .await
+        .unwrap();
+        assert_eq!(imported.version_info.get_name(), "Exported");
+        let minecraft_dir = other_launcher_dir.join("instances/Exported/minecraft");
+        assert_eq!(
+            fs::read(minecraft_dir.join("mods/mod.jar")).unwrap(),
+            b"mod"
+        );
+        assert!(
+            !other_launcher_dir
+                .join("instances/Exported")
+                .join(IMPORT_STAGING_DIR)
+                .exists()
+        );
+        assert!(
+            other_launcher_dir
+                .join("versions/1.20.1/1.20.1.json")
+                .is_file()
+        );
+        assert!(
+            other_launcher_dir
+                .join("versions_extra/Exported.json")
+                .is_file()
+        );
+
+        let missing = export_instance(
+            &launcher_dir,
+            exported_instance("Missing"),
+            &dir.path().join("missing.zip"),
+            Arc::new(shared::progress::NoProgressBar),
+        )
+        .await;
+        assert!(missing.is_err());
+        assert!(!dir.path().join("missing.zip.tmp").exists());
+    }
+
+    #[tokio::test]
+    async fn test_import_folder() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        let launcher_dir = dir.join("launcher");
+        let folder = dir.join(".minecraft");
+        fs::create_dir_all(&launcher_dir).unwrap();
+        for (path, data) in [
+            ("options.txt", "lang:en_us"),
+            ("mods/mod.jar", "mod"),
+            ("libraries/lib.jar", "lib"),
+            (
+                "versions/1.20.1/1.20.1.json",
+                r#"{"id": "1.20.1", "libraries": [], "mainClass": "Main"}"#,
+            ),
+            (
+                "versions/fabric/fabric.json",
+                r#"{"id": "fabric", "libraries": [], "mainClass": "Main", "inheritsFrom": "1.20.1"}"#,
+            ),
+            (
+                "versions/broken/broken.json",
+                r#"{"id": "broken", "libraries": [], "mainClass": "Main", "inheritsFrom": "1.19"}"#,
+            ),
+        ] {
+            fs::create_dir_all(folder.join(path).parent().unwrap()).unwrap();
+            fs::write(folder.join(path), data).unwrap();
+        }
+        assert_eq!(
+            list_folder_versions(&folder),
+            vec!["1.20.1", "broken", "fabric"]
+        );
+        assert!(list_folder_versions(dir).is_empty());
+
+        let source = |version_id: &str| ImportSource::Folder {
+            path: folder.clone(),
+            version_id: version_id.to_string(),
+        };
+        let instance = import_instance(&launcher_dir, source("fabric"), "Fabric")
+            .await
+            .unwrap();
+        assert_eq!(instance.version_info.id, "fabric");
+        assert_eq!(instance.version_info.inherits_from[0].id, "1.20.1");
+        let minecraft_dir = launcher_dir.join("instances/Fabric/minecraft");
+        assert!(minecraft_dir.join("mods/mod.jar").is_file());
+        assert!(!minecraft_dir.join("libraries").exists());
+        assert!(!minecraft_dir.join("versions").exists());
+
+        assert!(
+            import_instance(&launcher_dir, source("broken"), "Broken")
+                .await
+                .is_err()
+        );
+        assert!(!launcher_dir.join("instances/Broken").exists());
+    }
+}
@@ -0,0 +1,982 @@
+use std::{collections::HashSet, path::Path};
+
+use log::{error, warn};
+use serde::{Deserialize, Serialize};
+use shared::{
+    loader_generator::forge::{ABANDONED_INSTALLER_DIR_AGE, remove_abandoned_installer_dirs},
+    paths::{
+        get_description_cache_path, get_extra_metadata_path, get_instance_archive_path,
+        get_instance_dir, get_instance_meta_path, get_instances_dir, get_launch_log_path,
+        get_local_instances_path, get_minecraft_dir, get_rel_instance_dir,
+        get_screenshot_thumbnails_dir, get_versions_extra_dir,
+    },
+    version::version_manifest::{VersionInfo, VersionManifest},
+};
+use tokio::task;
+
+use crate::{
+    config::{build_config, runtime_config::Config},
+    utils::{get_temp_dir, temp_files::get_removed_instance_path},
+};
+
+use super::{archive, natives, rollout, sync};
+
+#[derive(thiserror::Error, Debug)]
+pub enum RenameError {
+    #[error("Instance {0} not found")]
+    NotFound(String),
+    // the name has to keep matching the manifest
+    #[error("Instance {0} comes from a version manifest and can't be renamed")]
+    FromManifest(String),
+    #[error("{0} is not a valid instance name")]
+    InvalidName(String),
+    #[error("Instance {0} already exists")]
+    NameTaken(String),
+}
+
+// the name is used as a folder and file name on every platform
+pub fn is_valid_instance_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.trim() == name
+        && name != "."
+        && name != ".."
+        && !name.ends_with('.')
+        && !name
+            .chars()
+            .any(|c| c.is_control() || "/\\:*?\"<>|".contains(c))
+}
+
+async fn move_if_exists(from: &Path, to: &Path) {
+    if tokio::fs::try_exists(from).await.unwrap_or(false)
+        && let Err(e) = tokio::fs::rename(from, to).await
+    {
+        warn!("Failed to move {:?} -> {:?}: {e:?}", from, to);
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum InstanceStatus {
+    Missing,
+    Outdated,
+    UpToDate,
+    // the game files are compressed into an archive
+    Archived,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LocalInstance {
+    pub version_info: VersionInfo,
+    pub status: InstanceStatus,
+    pub manifest_url: Option<String>,
+    #[serde(default)]
+    pub always_allow_delete_extra: bool,
+    #[serde(default)]
+    pub content_hash: Option<String>,
+    #[serde(default)]
+    pub auto_sync: bool,
+    // keeps the installed version even if the remote manifest has a newer one
+    #[serde(default)]
+    pub pinned: bool,
+    // restored when an archived instance is unpacked
+    #[serde(default)]
+    pub status_before_archive: Option<InstanceStatus>,
+}
+
+pub struct InstanceStorage {
+    instances: Vec<LocalInstance>,
+    remote_manifest: Option<VersionManifest>,
+    remote_manifest_url: Option<String>,
+    rollout_bucket: u8,
+}
+
+impl InstanceStorage {
+    async fn migrate_from_local_instances(launcher_dir: &std::path::Path) -> Vec<LocalInstance> {
+        let local_instances_path = get_local_instances_path(launcher_dir);
+        if !local_instances_path.exists() {
+            return Vec::new();
+        }
+        match tokio::fs::read(&local_instances_path).await {
+            Ok(data) => match serde_json::from_slice::<Vec<LocalInstance>>(&data) {
+                Ok(mut from_legacy) => {
+                    for instance in &mut from_legacy {
+                        instance.manifest_url =
+                            Some(build_config::get_default_version_manifest_url());
+                        let meta_path =
+                            get_instance_meta_path(launcher_dir, &instance.version_info.get_name());
+                        if let Ok(serialized) = serde_json::to_vec_pretty(instance)
+                            && let Err(e) = tokio::fs::write(&meta_path, serialized).await
+                        {
+                            warn!(
+                                "Failed to write per-instance meta for {}: {e:?}",
+                                instance.version_info.get_name()
+                            );
+                        }
+                    }
+                    if let Err(e) = tokio::fs::remove_file(&local_instances_path).await {
+                        warn!("Failed to remove legacy local_instances.json: {e:?}");
+                    }
+                    from_legacy
+                }
+                Err(e) => {
+                    warn!("Failed to parse legacy local_instances.json, skipping migration: {e:?}");
+                    Vec::new()
+                }
+            },
+            Err(e) => {
+                warn!("Failed to read legacy local_instances.json, skipping migration: {e:?}");
+                Vec::new()
+            }
+        }
+    }
+
+    pub async fn load(config: &Config) -> InstanceStorage {
+        let launcher_dir = config.get_launcher_dir();
+        let mut instances: Vec<LocalInstance> = Vec::new();
+        let mut recovered = false;
+        let instances_dir = get_instances_dir(&launcher_dir);
+        let mut read_dir = match tokio::fs::read_dir(&instances_dir).await {
+            Ok(rd) => rd,
+            Err(_) => {
+                if let Err(e) = tokio::fs::create_dir_all(&instances_dir).await {
+                    error!("Failed to create instances directory: {e:?}");
+                }
+                match tokio::fs::read_dir(&instances_dir).await {
+                    Ok(rd) => rd,
+                    Err(e) => {
+                        error!("Failed to read instances directory: {e:?}");
+                        return InstanceStorage {
+                            instances: Vec::new(),
+                            remote_manifest: None,
+                            remote_manifest_url: None,
+                            rollout_bucket: config.get_rollout_bucket(),
+                        };
+                    }
+                }
+            }
+        };
+        while let Ok(Some(entry)) = read_dir.next_entry().await {
+            let path = entry.path();
+            if path.is_dir()
+                && let Some(name_os) = path.file_name()
+            {
+                let dir_name = name_os.to_string_lossy().to_string();
+                let meta_path = get_instance_meta_path(&launcher_dir, &dir_name);
+                if meta_path.exists() {
+                    if let Ok(data) = tokio::fs::read(&meta_path).await
+                        && let Ok(mut instance) = serde_json::from_slice::<LocalInstance>(&data)
+                    {
+                        recovered |= archive::recover(&launcher_dir, &mut instance);
+                        instances.push(instance);
+                    }
+                    continue;
+                }
+
+                let mut dir_read = match tokio::fs::read_dir(&path).await {
+                    Ok(rd) => rd,
+                    Err(e) => {
+                        warn!("Failed to read instance dir {:?}: {e:?}", &path);
+                        continue;
+                    }
+                };
+                let mut entries_to_move = Vec::new();
+                while let Ok(Some(child)) = dir_read.next_entry().await {
+                    let child_path = child.path();
+                    if let Some(child_name) = child_path.file_name()
+                        && child_name != "minecraft"
+                    {
+                        entries_to_move.push((child_name.to_os_string(), child_path));
+                    }
+                }
+                let mc_dir = get_minecraft_dir(&launcher_dir, &dir_name);
+                for (name, from_path) in entries_to_move {
+                    let to_path = mc_dir.join(name);
+                    if let Err(e) = tokio::fs::rename(&from_path, &to_path).await {
+                        warn!("Failed to move {:?} -> {:?}: {e:?}", from_path, to_path);
+                    }
+                }
+            }
+        }
+
+        if instances.is_empty() {
+            let migrated = Self::migrate_from_local_instances(&launcher_dir).await;
+            if !migrated.is_empty() {
+                instances = migrated;
+            }
+        }
+
+        let storage = InstanceStorage {
+            instances,
+            remote_manifest: None,
+            remote_manifest_url: None,
+            rollout_bucket: config.get_rollout_bucket(),
+        };
+        if recovered {
+            storage.safe_save(config).await;
+        }
+        storage
+    }
+
+    pub async fn safe_save(&self, config: &Config) {
+        let launcher_dir = config.get_launcher_dir();
+        let mut join_set = task::JoinSet::new();
+        for instance in self.instances.clone() {
+            let launcher_dir = launcher_dir.clone();
+            let name = instance.version_info.get_name().to_string();
+            let data = match serde_json::to_vec_pretty(&instance) {
+                Ok(d) => d,
+                Err(e) => {
+                    error!("Failed to serialize instance {}: {e:?}", name);
+                    continue;
+                }
+            };
+            join_set.spawn(async move {
+                let meta_path = get_instance_meta_path(&launcher_dir, &name);
+                if let Err(e) = tokio::fs::write(&meta_path, data).await {
+                    error!("Failed to save instance meta for {}: {e:?}", name);
+                }
+            });
+        }
+        while let Some(res) = join_set.join_next().await {
+            let _ = res;
+        }
+    }
+
+    pub fn set_remote_manifest(&mut self, manifest: VersionManifest, manifest_url: &str) {
+        self.remote_manifest = Some(manifest);
+        self.remote_manifest_url = Some(manifest_url.to_string());
+    }
+
+    fn get_remote_versions(&self) -> Vec<&VersionInfo> {
+        if let Some(remote_manifest) = &self.remote_manifest {
+            remote_manifest.versions.iter().collect()
+        } else {
+            Vec::new()
+        }
+    }
+
+    pub fn get_all_names_for_manifest_url(&self, url: &str) -> (Vec<String>, Vec<String>) {
+        let local_names: HashSet<String> = self
+            .instances
+            .iter()
+            .filter(|instance| instance.manifest_url.as_deref().unwrap_or(url) == url)
+            .map(|x| x.version_info.get_name())
+            .collect();
+        let remote_names: Vec<String> = self
+            .get_remote_versions()
+            .iter()
+            .map(|x| x.get_name())
+            .filter(|x| !local_names.contains(x))
+            .collect();
+
+        let mut local_names: Vec<String> = local_names.into_iter().collect();
+        local_names.sort();
+
+        (local_names, remote_names)
+    }
+
+    pub fn count_instances_with_manifest_url(&self, url: &str) -> usize {
+        self.instances
+            .iter()
+            .filter(|i| i.manifest_url.as_deref() == Some(url))
+            .count()
+    }
+
+    pub async fn add_local_instance(&mut self, config: &Config, version_info: VersionInfo) {
+        self.instances.push(LocalInstance {
+            version_info,
+            status: InstanceStatus::Outdated,
+            manifest_url: None,
+            always_allow_delete_extra: false,
+            content_hash: None,
+            auto_sync: false,
+            pinned: false,
+            status_before_archive: None,
+        });
+        self.safe_save(config).await;
+    }
+
+    // the files are already in place, see import::import_instance
+    pub async fn add_imported_instance(&mut self, config: &Config, instance: LocalInstance) {
+        self.instances.push(instance);
+        self.safe_save(config).await;
+    }
+
+    fn find_local_instance(&self, version_name: &str) -> Option<&LocalInstance> {
+        self.instances
+            .iter()
+            .find(|instance| instance.version_info.get_name() == version_name)
+    }
+
+    fn find_remote_version(&self, version_name: &str) -> Option<&VersionInfo> {
+        self.get_remote_versions()
+            .into_iter()
+            .find(|x| x.get_name() == version_name)
+    }
+
+    // true if the remote manifest doesn't apply to the local instance
+    fn keeps_local_version(&self, local_instance: &LocalInstance) -> bool {
+        // TODO: allow different manifest urls for the same instance name
+        if let Some(manifest_url) = &self.remote_manifest_url
+            && let Some(instance_manifest_url) = &local_instance.manifest_url
+            && manifest_url != instance_manifest_url
+        {
+            return true;
+        }
+        // archived instances keep their version until they are restored
+        local_instance.pinned
+            || local_instance.status == InstanceStatus::Archived
+            || self.is_rollout_withheld(local_instance)
+    }
+
+    // the remote version hasn't been rolled out to this machine yet
+    fn is_rollout_withheld(&self, local_instance: &LocalInstance) -> bool {
+        self.find_remote_version(&local_instance.version_info.get_name())
+            .is_some_and(|remote_version_info| {
+                !remote_version_info.is_same_version(&local_instance.version_info)
+                    && !rollout::is_rolled_out(
+                        remote_version_info.rollout_percent,
+                        self.rollout_bucket,
+                    )
+            })
+    }
+
+    pub fn is_rollout_pending(&self, version_name: &str) -> bool {
+        self.find_local_instance(version_name)
+            .is_some_and(|local_instance| {
+                !local_instance.pinned && self.is_rollout_withheld(local_instance)
+            })
+    }
+
+    // the remote manifest has the current address, local-only instances keep their own
+    pub fn get_server_address(&self, version_name: &str) -> Option<&str> {
+        self.find_remote_version(version_name)
+            .or_else(|| {
+                self.find_local_instance(version_name)
+                    .map(|instance| &instance.version_info)
+            })
+            .and_then(|version_info| version_info.server_address.as_deref())
+    }
+
+    fn get_remote_status(
+        local_instance: Option<&LocalInstance>,
+        remote_version_info: &VersionInfo,
+    ) -> InstanceStatus {
+        match local_instance {
+            Some(instance) if !remote_version_info.is_same_version(&instance.version_info) => {
+                InstanceStatus::Outdated
+            }
+            Some(_) => InstanceStatus::UpToDate,
+            None => InstanceStatus::Missing,
+        }
+    }
+
+    // the status get_instance would report, without cloning the instance
+    pub fn get_status(&self, version_name: &str) -> Option<InstanceStatus> {
+        let local_instance = self.find_local_instance(version_name);
+        if let Some(local_instance) = local_instance
+            && self.keeps_local_version(local_instance)
+        {
+            return Some(local_instance.status);
+        }
+        match self.find_remote_version(version_name) {
+            Some(remote_version_info) => {
+                Some(Self::get_remote_status(local_instance, remote_version_info))
+            }
+            None => local_instance.map(|instance| instance.status),
+        }
+    }
+
+    // created locally or removed from the remote manifest
+    pub fn is_local_only(&self, version_name: &str) -> bool {
+        self.find_local_instance(version_name).is_some()
+            && self.find_remote_version(version_name).is_none()
+    }
+
+    pub fn get_instance(&self, version_name: &str) -> Option<LocalInstance> {
+        let local_instance = self.find_local_instance(version_name).cloned();
+        if let Some(local_instance) = &local_instance
+            && self.keeps_local_version(local_instance)
+        {
+            return Some(local_instance.clone());
+        }
+        let remote_version_info = self.find_remote_version(version_name);
+
+        if let Some(remote_version_info) = remote_version_info {
+            let always_allow_delete_extra = local_instance
+                .as_ref()
+                .is_some_and(|instance| instance.always_allow_delete_extra);
+            let content_hash = local_instance
+                .as_ref()
+                .and_then(|instance| instance.content_hash.clone());
+            let auto_sync = local_instance
+                .as_ref()
+                .is_some_and(|instance| instance.auto_sync);
+            let remote_instance = LocalInstance {
+                version_info: remote_version_info.clone(),
+                status: Self::get_remote_status(local_instance.as_ref(), remote_version_info),
+                manifest_url: self.remote_manifest_url.clone(),
+                always_allow_delete_extra,
+                content_hash,
+                auto_sync,
+                pinned: false,
+                status_before_archive: None,
+            };
+            Some(remote_instance)
+        } else {
+            local_instance
+        }
+    }
+
+    pub async fn mark_downloaded(
+        &mut self,
+        config: &Config,
+        version_name: &str,
+        content_hash: String,
+    ) {
+        let keeps_local_version = self
+            .find_local_instance(version_name)
+            .is_some_and(|instance| instance.pinned || self.is_rollout_withheld(instance));
+        let remote_versions = self.get_remote_versions();
+        let remote_version = remote_versions
+            .into_iter()
+            .find(|v| v.get_name() == version_name)
+            .filter(|_| !keeps_local_version)
+            .cloned();
+
+        if let Some(remote_version) = remote_version {
+            let local_instance = self
+                .instances
+                .iter()
+                .find(|instance| instance.version_info.get_name() == version_name);
+            let always_allow_delete_extra =
+                local_instance.is_some_and(|instance| instance.always_allow_delete_extra);
+            let auto_sync = local_instance.is_some_and(|instance| instance.auto_sync);
+            self.instances
+                .retain(|instance| instance.version_info.get_name() != version_name);
+            self.instances.push(LocalInstance {
+                version_info: remote_version,
+                status: InstanceStatus::UpToDate,
+                manifest_url: self.remote_manifest_url.clone(),
+                always_allow_delete_extra,
+                content_hash: Some(content_hash),
+                auto_sync,
+                pinned: false,
+                status_before_archive: None,
+            });
+            self.safe_save(config).await;
+        } else if let Some(instance) = self
+            .instances
+            .iter_mut()
+            .find(|instance| instance.version_info.get_name() == version_name)
+        {
+            instance.status = InstanceStatus::UpToDate;
+            instance.content_hash = Some(content_hash);
+            self.safe_save(config).await;
+        } else {
+            warn!("Tried to mark non-existent version as downloaded: {version_name}");
+        }
+    }
+
+    pub async fn set_always_allow_delete_extra(&mut self, config: &Config, version_name: &str) {
+        if let Some(instance) = self
+            .instances
+            .iter_mut()
+            .find(|instance| instance.version_info.get_name() == version_name)
+        {
+            instance.always_allow_delete_extra = true;
+            self.safe_save(config).await;
+        } else {
+            warn!("Tried to allow deleting extra files for non-existent version: {version_name}");
+        }
+    }
+
+    pub async fn set_auto_sync(&mut self, config: &Config, version_name: &str, auto_sync: bool) {
+        if let Some(instance) = self
+            .instances
+            .iter_mut()
+            .find(|instance| instance.version_info.get_name() == version_name)
+        {
+            instance.auto_sync = auto_sync;
+            self.safe_save(config).await;
+        } else {
+            warn!("Tried to change auto-sync for non-existent version: {version_name}");
+        }
+    }
+
+    pub async fn set_pinned(&mut self, config: &Config, version_name: &str, pinned: bool) {
+        if let Some(instance) = self
+            .instances
+            .iter_mut()
+            .find(|instance| instance.version_info.get_name() == version_name)
+        {
+            instance.pinned = pinned;
+            self.safe_save(config).await;
+        } else {
+            warn!("Tried to pin non-existent version: {version_name}");
+        }
+    }
+
+    // the game files are removed after the status is saved, see archive::recover
+    pub async fn mark_archived(&mut self, config: &Config, version_name: &str) {
+        if let Some(instance) = self
+            .instances
+            .iter_mut()
+            .find(|instance| instance.version_info.get_name() == version_name)
+        {
+            if instance.status != InstanceStatus::Archived {
+                instance.status_before_archive = Some(std::mem::replace(
+                    &mut instance.status,
+                    InstanceStatus::Archived,
+                ));
+            }
+            self.safe_save(config).await;
+            let launcher_dir = config.get_launcher_dir();
+            archive::finish_archiving(&launcher_dir, version_name);
+            let version_name = version_name.to_string();
+            task::spawn(async move {
+                if let Err(e) = archive::remove_game_files(&launcher_dir, &version_name).await {
+                    error!("Error deleting archived game files:\n{e:?}");
+                }
+            });
+        } else {
+            warn!("Tried to archive non-existent version: {version_name}");
+            archive::finish_archiving(&config.get_launcher_dir(), version_name);
+        }
+    }
+
+    pub async fn mark_restored(&mut self, config: &Config, version_name: &str) {
+        if let Some(instance) = self
+            .instances
+            .iter_mut()
+            .find(|instance| instance.version_info.get_name() == version_name)
+        {
+            instance.status = instance
+                .status_before_archive
+                .take()
+                .unwrap_or(InstanceStatus::Outdated);
+            self.safe_save(config).await;
+            if let Err(e) = archive::remove_archive(&config.get_launcher_dir(), version_name).await
+            {
+                error!("Error deleting instance archive:\n{e:?}");
+            }
+        } else {
+            warn!("Tried to restore non-existent version: {version_name}");
+        }
+    }
+
+    // instances with auto-sync enabled that are behind the remote manifest
+    pub fn get_auto_sync_instances(&self) -> Vec<LocalInstance> {
+        let mut names: Vec<String> = self
+            .instances
+            .iter()
+            .filter(|instance| instance.auto_sync && !instance.pinned)
+            .map(|instance| instance.version_info.get_name())
+            .collect();
+        names.sort();
+        names
+            .into_iter()
+            .filter_map(|name| self.get_instance(&name))
+            .filter(|instance| {
+                instance.status == InstanceStatus::Outdated
+                    && instance.manifest_url.is_some()
+                    && instance.manifest_url == self.remote_manifest_url
+            })
+            .collect()
+    }
+
+    // only instances created or imported locally, others keep the manifest name
+    pub fn can_rename(&self, version_name: &str) -> bool {
+        self.find_local_instance(version_name)
+            .is_some_and(|instance| instance.manifest_url.is_none())
+    }
+
+    pub fn check_rename(
+        &self,
+        launcher_dir: &Path,
+        old_name: &str,
+        new_name: &str,
+    ) -> Result<(), RenameError> {
+        if self.find_local_instance(old_name).is_none() {
+            return Err(RenameError::NotFound(old_name.to_string()));
+        }
+        if !self.can_rename(old_name) {
+            return Err(RenameError::FromManifest(old_name.to_string()));
+        }
+        if !is_valid_instance_name(new_name) {
+            return Err(RenameError::InvalidName(new_name.to_string()));
+        }
+        if self.find_local_instance(new_name).is_some()
+            || self.find_remote_version(new_name).is_some()
+            || launcher_dir.join(get_rel_instance_dir(new_name)).exists()
+        {
+            return Err(RenameError::NameTaken(new_name.to_string()));
+        }
+        Ok(())
+    }
+
+    // moves the instance dir and the files named after the instance, then the settings
+    pub async fn rename_instance(
+        &mut self,
+        config: &mut Config,
+        old_name: &str,
+        new_name: &str,
+    ) -> anyhow::Result<()> {
+        let launcher_dir = config.get_launcher_dir();
+        self.check_rename(&launcher_dir, old_name, new_name)?;
+        sync::ensure_not_running(&launcher_dir, old_name)?;
+
+        // get_instance_dir would create the new dir
+        tokio::fs::rename(
+            get_instance_dir(&launcher_dir, old_name),
+            launcher_dir.join(get_rel_instance_dir(new_name)),
+        )
+        .await?;
+        let versions_extra_dir = get_versions_extra_dir(&launcher_dir);
+        for (from, to) in [
+            (
+                get_extra_metadata_path(&versions_extra_dir, old_name),
+                get_extra_metadata_path(&versions_extra_dir, new_name),
+            ),
+            (
+                get_instance_archive_path(&launcher_dir, old_name),
+                get_instance_archive_path(&launcher_dir, new_name),
+            ),
+            (
+                get_launch_log_path(&launcher_dir, old_name),
+                get_launch_log_path(&launcher_dir, new_name),
+            ),
+            (
+                get_description_cache_path(&launcher_dir, old_name),
+                get_description_cache_path(&launcher_dir, new_name),
+            ),
+        ] {
+            move_if_exists(&from, &to).await;
+        }
+        // regenerated when the screenshots are opened
+        let thumbnails_dir = get_screenshot_thumbnails_dir(&launcher_dir, old_name);
+        if let Err(e) = tokio::fs::remove_dir_all(&thumbnails_dir).await {
+            warn!("Failed to remove screenshot thumbnails:\n{e:?}");
+        }
+
+        if let Some(instance) = self
+            .instances
+            .iter_mut()
+            .find(|instance| instance.version_info.get_name() == old_name)
+        {
+            instance.version_info.name = Some(new_name.to_string());
+        }
+        self.safe_save(config).await;
+        config.rename_instance(old_name, new_name);
+        Ok(())
+    }
+
+    async fn remove_instance_files(&self, launcher_dir: &Path, version_name: &str) {
+        let instance_dir = get_instance_dir(launcher_dir, version_name);
+        if instance_dir.exists() {
+            let unique_temp_dir = get_removed_instance_path(&get_temp_dir());
+
+            if let Err(e) = tokio::fs::rename(&instance_dir, &unique_temp_dir).await {
+                error!("Error moving instance directory:\n{e:?}");
+            } else {
+                task::spawn(async move {
+                    if let Err(e) = tokio::fs::remove_dir_all(&unique_temp_dir).await {
+                        error!("Error deleting temporary directory:\n{e:?}");
+                    }
+                });
+            }
+        }
+    }
+
+    pub async fn delete_instance(&mut self, config: &Config, version_name: &str) {
+        let launcher_dir = config.get_launcher_dir();
+
+        let instance = self
+            .instances
+            .iter()
+            .find(|instance| instance.version_info.get_name() == version_name);
+        if let Some(instance) = instance {
+            if instance.status != InstanceStatus::Outdated {
+                self.remove_instance_files(&launcher_dir, version_name)
+                    .await;
+            }
+            if let Err(e) = archive::remove_archive(&launcher_dir, version_name).await {
+                error!("Error deleting instance archive:\n{e:?}");
+            }
+            let thumbnails_dir = get_screenshot_thumbnails_dir(&launcher_dir, version_name);
+            if let Err(e) = tokio::fs::remove_dir_all(&thumbnails_dir).await {
+                warn!("Failed to remove screenshot thumbnails:\n{e:?}");
+            }
+            self.instances
+                .retain(|instance| instance.version_info.get_name() != version_name);
+            self.safe_save(config).await;
+            if let Err(e) = natives::remove_unused_natives(&launcher_dir) {
+                warn!("Failed to remove unused natives:\n{e:?}");
+            }
+            if let Err(e) =
+                remove_abandoned_installer_dirs(&launcher_dir, ABANDONED_INSTALLER_DIR_AGE)
+            {
+                warn!("Failed to remove abandoned installer dirs:\n{e:?}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST_URL: &str = "https://example.com/manifest.json";
+
+    fn version_info(name: &str, sha1: &str) -> VersionInfo {
+        VersionInfo {
+            id: "1.20.1".to_string(),
+            url: "https://example.com/1.20.1.json".to_string(),
+            sha1: sha1.to_string(),
+            name: Some(name.to_string()),
+            inherits_from: vec![],
+            extra_metadata_url: None,
+            extra_metadata_sha1: None,
+            rollout_percent: None,
+            server_address: None,
+        }
+    }
+
+    fn local_instance(name: &str, auto_sync: bool) -> LocalInstance {
+        LocalInstance {
+            version_info: version_info(name, "old"),
+            status: InstanceStatus::UpToDate,
+            manifest_url: Some(MANIFEST_URL.to_string()),
+            always_allow_delete_extra: false,
+            content_hash: None,
+            auto_sync,
+            pinned: false,
+            status_before_archive: None,
+        }
+    }
+
+    #[test]
+    fn test_get_auto_sync_instances() {
+        let mut up_to_date = local_instance("up_to_date", true);
+        up_to_date.version_info.sha1 = "new".to_string();
+        let mut storage = InstanceStorage {
+            instances: vec![
+                local_instance("outdated", true),
+                local_instance("manual", false),
+                up_to_date,
+            ],
+            remote_manifest: None,
+            remote_manifest_url: None,
+            rollout_bucket: 0,
+        };
+        let remote_versions = ["outdated", "manual", "up_to_date"]
+            .into_iter()
+            .map(|name| version_info(name, "new"))
+            .collect();
+        storage.set_remote_manifest(
+            VersionManifest {
+                versions: remote_versions,
+            },
+            MANIFEST_URL,
+        );
+
+        let names: Vec<String> = storage
+            .get_auto_sync_instances()
+            .into_iter()
+            .map(|instance| instance.version_info.get_name())
+            .collect();
+        assert_eq!(names, vec!["outdated".to_string()]);
+        assert_eq!(
+            storage.get_auto_sync_instances()[0].version_info.sha1,
+            "new"
+        );
+    }
+
+    #[test]
+    fn test_pinned_instance() {
+        let mut pinned = local_instance("pinned", true);
+        pinned.pinned = true;
+        let mut storage = InstanceStorage {
+            instances: vec![pinned],
+            remote_manifest: None,
+            remote_manifest_url: None,
+            rollout_bucket: 0,
+        };
+        storage.set_remote_manifest(
+            VersionManifest {
+                versions: vec![version_info("pinned", "new")],
+            },
+            MANIFEST_URL,
+        );
+
+        let instance = storage.get_instance("pinned").unwrap();
+        assert_eq!(instance.version_info.sha1, "old");
+        assert!(instance.status == InstanceStatus::UpToDate);
+        assert!(storage.get_auto_sync_instances().is_empty());
+
+        storage.instances[0].pinned = false;
+        let instance = storage.get_instance("pinned").unwrap();
+        assert_eq!(instance.version_info.sha1, "new");
+        assert!(instance.status == InstanceStatus::Outdated);
+    }
+
+    #[test]
+    fn test_get_status() {
+        let mut up_to_date = local_instance("up_to_date", false);
+        up_to_date.version_info.sha1 = "new".to_string();
+        let mut storage = InstanceStorage {
+            instances: vec![
+                local_instance("outdated", false),
+                up_to_date,
+                local_instance("local", false),
+            ],
+            remote_manifest: None,
+            remote_manifest_url: None,
+            rollout_bucket: 0,
+        };
+        storage.set_remote_manifest(
+            VersionManifest {
+                versions: ["outdated", "up_to_date", "missing"]
+                    .into_iter()
+                    .map(|name| version_info(name, "new"))
+                    .collect(),
+            },
+            MANIFEST_URL,
+        );
+
+        for name in ["outdated", "up_to_date", "missing", "local", "unknown"] {
+            assert!(
+                storage.get_status(name) == storage.get_instance(name).map(|i| i.status),
+                "{name}"
+            );
+        }
+        assert!(storage.get_status("missing") == Some(InstanceStatus::Missing));
+        assert!(storage.get_status("unknown").is_none());
+        assert!(storage.is_local_only("local"));
+        assert!(!storage.is_local_only("outdated"));
+        assert!(!storage.is_local_only("missing"));
+    }
+
+    #[tokio::test]
+    async fn test_rename_instance() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let launcher_dir = temp_dir.path();
+        let mut config: Config = serde_json::from_str(
+            r#"{
+                "java_paths": {"local": "/usr/bin/java"},
+                "xmx": {"local": "2048M", "other": "1024M"},
+                "use_native_glfw": {},
+                "selected_instance_name": "local",
+                "lang": "English",
+                "hide_launcher_after_launch": true,
+                "auth_profiles": {}
+            }"#,
+        )
+        .unwrap();
+        config.data_dir = Some(launcher_dir.to_path_buf());
+
+        let mut local = local_instance("local", false);
+        local.manifest_url = None;
+        let mut storage = InstanceStorage {
+            instances: vec![local, local_instance("remote", false)],
+            remote_manifest: None,
+            remote_manifest_url: None,
+            rollout_bucket: 0,
+        };
+        storage.safe_save(&config).await;
+        std::fs::write(
+            get_minecraft_dir(launcher_dir, "local").join("options.txt"),
+            "lang:en_us",
+        )
+        .unwrap();
+        std::fs::write(get_launch_log_path(launcher_dir, "local"), "log").unwrap();
+
+        for (old_name, new_name) in [
+            ("missing", "new"),
+            ("remote", "new"),
+            ("local", "bad/name"),
+            ("local", " new"),
+            ("local", "remote"),
+        ] {
+            assert!(
+                storage
+                    .rename_instance(&mut config, old_name, new_name)
+                    .await
+                    .is_err(),
+                "{old_name} -> {new_name}"
+            );
+        }
+
+        storage
+            .rename_instance(&mut config, "local", "Новая сборка")
+            .await
+            .unwrap();
+        assert!(storage.get_instance("local").is_none());
+        assert!(storage.get_instance("Новая сборка").is_some());
+        assert!(!launcher_dir.join(get_rel_instance_dir("local")).exists());
+        assert!(
+            get_minecraft_dir(launcher_dir, "Новая сборка")
+                .join("options.txt")
+                .exists()
+        );
+        assert!(get_launch_log_path(launcher_dir, "Новая сборка").exists());
+        assert_eq!(config.xmx["Новая сборка"], "2048M");
+        assert_eq!(config.xmx["other"], "1024M");
+        assert!(config.java_paths.contains_key("Новая сборка"));
+        assert_eq!(
+            config.selected_instance_name.as_deref(),
+            Some("Новая сборка")
+        );
+
+        let reloaded = InstanceStorage::load(&config).await;
+        assert!(reloaded.get_instance("Новая сборка").is_some());
+        assert!(reloaded.get_instance("local").is_none());
+    }
+
+    #[test]
+    fn test_staged_rollout() {
+        let mut storage = InstanceStorage {
+            instances: vec![local_instance("installed", false)],
+            remote_manifest: None,
+            remote_manifest_url: None,
+            rollout_bucket: 42,
+        };
+        let set_rollout_percent = |storage: &mut InstanceStorage, rollout_percent| {
+            let versions = ["installed", "missing"]
+                .into_iter()
+                .map(|name| VersionInfo {
+                    rollout_percent,
+                    ..version_info(name, "new")
+                })
+                .collect();
+            storage.set_remote_manifest(VersionManifest { versions }, MANIFEST_URL);
+        };
+
+        set_rollout_percent(&mut storage, Some(10));
+        let instance = storage.get_instance("installed").unwrap();
+        assert_eq!(instance.version_info.sha1, "old");
+        assert!(instance.status == InstanceStatus::UpToDate);
+        assert!(storage.get_status("installed") == Some(InstanceStatus::UpToDate));
+        assert!(storage.is_rollout_pending("installed"));
+        // nothing to keep without an installed version
+        let instance = storage.get_instance("missing").unwrap();
+        assert_eq!(instance.version_info.sha1, "new");
+        assert!(instance.status == InstanceStatus::Missing);
+        assert!(!storage.is_rollout_pending("missing"));
+
+        set_rollout_percent(&mut storage, Some(43));
+        let instance = storage.get_instance("installed").unwrap();
+        assert_eq!(instance.version_info.sha1, "new");
+        assert!(instance.status == InstanceStatus::Outdated);
+        assert!(!storage.is_rollout_pending("installed"));
+
+        // bumping the percent of the installed version doesn't make it outdated
+        storage.instances[0].version_info = VersionInfo {
+            rollout_percent: Some(43),
+            ..version_info("installed", "new")
+        };
+        set_rollout_percent(&mut storage, Some(100));
+        assert!(storage.get_status("installed") == Some(InstanceStatus::UpToDate));
+        set_rollout_percent(&mut storage, None);
+        assert!(storage.get_status("installed") == Some(InstanceStatus::UpToDate));
+    }
+}
@@ -0,0 +1,98 @@
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use shared::paths::get_manifest_cache_dir;
+use shared::version::version_manifest::VersionManifest;
+
+// the last manifest fetched from the url, shown while the server can't be reached
+#[derive(Serialize, Deserialize)]
+pub struct CachedManifest {
+    // unix time
+    pub fetched_at: u64,
+    pub manifest: VersionManifest,
+}
+
+fn get_cache_path(launcher_dir: &Path, manifest_url: &str) -> PathBuf {
+    let url_hash = Sha1::digest(manifest_url.as_bytes());
+    get_manifest_cache_dir(launcher_dir).join(format!("{url_hash:x}.json"))
+}
+
+// None if the url was never fetched or the cache is unreadable
+pub fn read_cached_manifest(launcher_dir: &Path, manifest_url: &str) -> Option<CachedManifest> {
+    let data = std::fs::read(get_cache_path(launcher_dir, manifest_url)).ok()?;
+    serde_json::from_slice(&data).ok()
+}
+
+// the manifest is already verified, an interrupted write leaves the old cache in place
+pub async fn write_cached_manifest(
+    launcher_dir: &Path,
+    manifest_url: &str,
+    manifest: &VersionManifest,
+) -> anyhow::Result<()> {
+    let cached = CachedManifest {
+        fetched_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs()),
+        manifest: manifest.clone(),
+    };
+    let path = get_cache_path(launcher_dir, manifest_url);
+    let temp_path = path.with_extension("json.tmp");
+    tokio::fs::write(&temp_path, serde_json::to_vec(&cached)?).await?;
+    tokio::fs::rename(&temp_path, &path).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use shared::version::version_manifest::VersionInfo;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_manifest_cache() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let launcher_dir = temp_dir.path();
+        let url = "https://example.com/manifest.json";
+        assert!(read_cached_manifest(launcher_dir, url).is_none());
+
+        let manifest = VersionManifest {
+            versions: vec![VersionInfo {
+                id: "1.20.1".to_string(),
+                url: "https://example.com/1.20.1.json".to_string(),
+                sha1: "sha1".to_string(),
+                name: Some("instance".to_string()),
+                inherits_from: vec![],
+                extra_metadata_url: None,
+                extra_metadata_sha1: None,
+                rollout_percent: None,
+                server_address: None,
+            }],
+        };
+        write_cached_manifest(launcher_dir, url, &manifest)
+            .await
+            .unwrap();
+        write_cached_manifest(
+            launcher_dir,
+            "https://example.com/other.json",
+            &VersionManifest::empty(),
+        )
+        .await
+        .unwrap();
+
+        let cached = read_cached_manifest(launcher_dir, url).unwrap();
+        assert_eq!(cached.manifest.versions[0].get_name(), "instance");
+        assert!(cached.fetched_at > 0);
+        let cache_files: Vec<_> = std::fs::read_dir(get_manifest_cache_dir(launcher_dir))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(cache_files.len(), 2);
+        assert!(
+            cache_files
+                .iter()
+                .all(|path| path.extension().unwrap() == "json")
+        );
+    }
+}
@@ -0,0 +1,13 @@
+pub mod archive;
+pub mod complete_version_metadata;
+pub mod dedup;
+pub mod disk_usage;
+pub mod file_store;
+pub mod import;
+pub mod instance_storage;
+pub mod manifest_cache;
+pub mod natives;
+pub mod os;
+pub mod overrides;
+pub mod rollout;
+pub mod sync;
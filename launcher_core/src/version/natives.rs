@@ -0,0 +1,186 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+use log::{info, warn};
+use sha1::{Digest, Sha1};
+use shared::paths::{
+    get_instances_dir, get_libraries_dir, get_natives_dir, get_natives_key_path,
+    get_natives_root_dir, get_running_lock_path,
+};
+use shared::version::version_metadata::Library;
+use zip::ZipArchive;
+
+use crate::launcher::running_lock::is_locked;
+
+use super::os;
+
+const EXTRACTED_MARKER_FILE: &str = ".extracted";
+const NATIVES_KEY_LEN: usize = 16;
+
+// instances with the same parent version can still use different natives,
+// so the directory is keyed by the natives themselves
+fn get_natives_key(libraries: &[Library]) -> String {
+    let os_name = os::get_os_name();
+    let arch = os::get_system_arch();
+    let mut sha1s: Vec<&str> = libraries
+        .iter()
+        .filter_map(|library| library.get_os_native_download(&os_name, &arch))
+        .map(|download| download.sha1.as_str())
+        .collect();
+    sha1s.sort();
+    sha1s.dedup();
+
+    let mut hasher = Sha1::new();
+    for sha1 in sha1s {
+        hasher.update(sha1);
+        hasher.update(b"\n");
+    }
+    format!("{:x}", hasher.finalize())[..NATIVES_KEY_LEN].to_string()
+}
+
+fn extract_files(src: &Path, dest: &Path) -> anyhow::Result<()> {
+    let file = fs::File::open(src)?;
+    let mut zip = ZipArchive::new(file)?;
+
+    for i in 0..zip.len() {
+        let mut entry = zip.by_index(i)?;
+        if let Some(file_path) = entry.enclosed_name() {
+            let output_path = dest.join(file_path);
+            if entry.is_file() {
+                if let Some(parent) = output_path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                let mut outfile = fs::File::create(&output_path)?;
+                std::io::copy(&mut entry, &mut outfile)?;
+            } else if entry.is_dir() {
+                fs::create_dir_all(&output_path)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// where extract_natives puts the natives of these libraries
+pub fn get_instance_natives_dir(libraries: &[Library], launcher_dir: &Path) -> PathBuf {
+    get_natives_dir(launcher_dir, &get_natives_key(libraries))
+}
+
+// returns the natives directory of the instance, extracting into it if the natives changed
+pub fn extract_natives(
+    libraries: &[Library],
+    launcher_dir: &Path,
+    instance_name: &str,
+) -> anyhow::Result<PathBuf> {
+    let natives_key = get_natives_key(libraries);
+    // recorded first so a cleanup doesn't remove the directory while it's being filled
+    fs::write(
+        get_natives_key_path(launcher_dir, instance_name),
+        &natives_key,
+    )?;
+
+    let natives_dir = get_instance_natives_dir(libraries, launcher_dir);
+    let marker_path = natives_dir.join(EXTRACTED_MARKER_FILE);
+    if marker_path.exists() {
+        return Ok(natives_dir);
+    }
+
+    info!("Extracting natives to {}", natives_dir.display());
+    let libraries_dir = get_libraries_dir(launcher_dir);
+    for library in libraries {
+        if let Some(natives_path) =
+            library.get_os_native_path(&libraries_dir, &os::get_os_name(), &os::get_system_arch())
+        {
+            extract_files(&natives_path, &natives_dir)
+                .with_context(|| format!("Failed to extract {}", natives_path.display()))?;
+        }
+    }
+    fs::write(marker_path, b"")?;
+    Ok(natives_dir)
+}
+
+// removes natives directories that no installed instance uses,
+// including the ones keyed by version id by older launcher versions
+pub fn remove_unused_natives(launcher_dir: &Path) -> anyhow::Result<()> {
+    let mut used_keys = HashSet::new();
+    for entry in fs::read_dir(get_instances_dir(launcher_dir))?.flatten() {
+        if !entry.path().is_dir() {
+            continue;
+        }
+        let instance_name = entry.file_name().to_string_lossy().to_string();
+        match fs::read_to_string(get_natives_key_path(launcher_dir, &instance_name)) {
+            Ok(natives_key) => {
+                used_keys.insert(natives_key.trim().to_string());
+            }
+            Err(_) if is_locked(&get_running_lock_path(launcher_dir, &instance_name)) => {
+                info!("{instance_name} was started before natives were keyed, skipping cleanup");
+                return Ok(());
+            }
+            Err(_) => {}
+        }
+    }
+
+    for entry in fs::read_dir(get_natives_root_dir(launcher_dir))?.flatten() {
+        let natives_key = entry.file_name().to_string_lossy().to_string();
+        if used_keys.contains(&natives_key) {
+            continue;
+        }
+        info!("Removing unused natives {natives_key}");
+        // natives loaded by a running game can't be removed on windows
+        if let Err(e) = fs::remove_dir_all(entry.path()) {
+            warn!("Failed to remove natives {natives_key}:\n{e:?}");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_natives_keys() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let launcher_dir = temp_dir.path();
+
+        let native_library = |sha1: &str| {
+            let mut library = Library::from_download(
+                "org.lwjgl.lwjgl:lwjgl-platform:2.9.4".to_string(),
+                String::new(),
+                String::new(),
+            );
+            let os_name = os::get_os_name();
+            library.natives = Some(
+                ["", "-arm32", "-arm64"]
+                    .iter()
+                    .map(|suffix| (format!("{os_name}{suffix}"), "natives".to_string()))
+                    .collect(),
+            );
+            library.set_native_download("natives", String::new(), sha1.to_string());
+            library
+        };
+        let old_libraries = vec![native_library("aaa")];
+        let new_libraries = vec![native_library("bbb")];
+        assert_ne!(
+            get_natives_key(&old_libraries),
+            get_natives_key(&new_libraries)
+        );
+        assert_eq!(
+            get_natives_key(&[native_library("aaa"), native_library("bbb")]),
+            get_natives_key(&[native_library("bbb"), native_library("aaa")])
+        );
+
+        // an instance without natives still gets an empty directory
+        let natives_dir = extract_natives(&[], launcher_dir, "first").unwrap();
+        assert!(natives_dir.join(EXTRACTED_MARKER_FILE).exists());
+        let legacy_dir = get_natives_dir(launcher_dir, "1.20.1");
+        let unused_dir = get_natives_dir(launcher_dir, &get_natives_key(&old_libraries));
+
+        remove_unused_natives(launcher_dir).unwrap();
+        assert!(natives_dir.exists());
+        assert!(!legacy_dir.exists());
+        assert!(!unused_dir.exists());
+    }
+}
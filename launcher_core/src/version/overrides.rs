@@ -1,7 +1,11 @@
-use log::info;
+use anyhow::Context as _;
+use log::{info, warn};
 use serde::Deserialize;
+use shared::paths::{get_library_overrides_example_path, get_library_overrides_path};
+use shared::version::extra_version_metadata::LibraryOverride;
 use shared::version::version_metadata::{Library, LibraryDownloads, Rule};
 use std::collections::{HashMap, HashSet};
+use std::path::Path;
 
 use crate::config::build_config;
 
@@ -127,3 +131,110 @@ pub fn with_overrides(libraries: &Vec<Library>, version_id: &str) -> Vec<Library
 
     result
 }
+
+const LIBRARY_OVERRIDES_EXAMPLE: &str = r#"[
+  {
+    "name": "org.lwjgl:lwjgl:3.3.1",
+    "url": "https://mirror.example.com/org/lwjgl/lwjgl/3.3.1/lwjgl-3.3.1.jar",
+    "sha1": "ae58664f88e18a9bb2c77b063833ca7aaec484cb"
+  },
+  {
+    "name": "org.lwjgl:*:3.2.2",
+    "classifier": "natives-linux",
+    "url": "https://mirror.example.com/lwjgl-natives-linux-3.2.2.jar",
+    "sha1": "0000000000000000000000000000000000000000"
+  }
+]
+"#;
+
+// user hotfixes for broken library downloads, copy the example to library_overrides.json to use it
+pub fn read_local_library_overrides(launcher_dir: &Path) -> anyhow::Result<Vec<LibraryOverride>> {
+    let path = get_library_overrides_path(launcher_dir);
+    if !path.exists() {
+        let example_path = get_library_overrides_example_path(launcher_dir);
+        if !example_path.exists()
+            && let Err(e) = std::fs::write(&example_path, LIBRARY_OVERRIDES_EXAMPLE)
+        {
+            warn!("Failed to write library overrides example:\n{e:?}");
+        }
+        return Ok(vec![]);
+    }
+
+    let data = std::fs::read(&path)?;
+    serde_json::from_slice(&data).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+// later overrides win, so local ones are passed last
+pub fn with_library_overrides(
+    libraries: Vec<Library>,
+    overrides: &[&LibraryOverride],
+) -> Vec<Library> {
+    libraries
+        .into_iter()
+        .map(|mut library| {
+            for override_ in overrides {
+                if !override_.matches(&library) {
+                    continue;
+                }
+                warn!(
+                    "Overriding library {}{} with {} (sha1 {})",
+                    library.get_full_name(),
+                    override_
+                        .classifier
+                        .as_ref()
+                        .map(|classifier| format!(" ({classifier})"))
+                        .unwrap_or_default(),
+                    override_.url,
+                    override_.sha1
+                );
+                override_.apply(&mut library);
+            }
+            library
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_library_overrides() {
+        let libraries: Vec<Library> = serde_json::from_str(
+            r#"[
+                {"name": "org.lwjgl:lwjgl:3.3.1", "downloads": {"artifact": {"url": "https://broken/lwjgl.jar", "sha1": "old"}}},
+                {"name": "com.mojang:brigadier:1.0.18", "url": "https://libraries.minecraft.net/"}
+            ]"#,
+        )
+        .unwrap();
+        let example: Vec<LibraryOverride> =
+            serde_json::from_str(LIBRARY_OVERRIDES_EXAMPLE).unwrap();
+        let local = LibraryOverride {
+            name: "org.lwjgl:*".to_string(),
+            url: "https://local/lwjgl.jar".to_string(),
+            sha1: "local".to_string(),
+            classifier: None,
+        };
+
+        let libraries = with_library_overrides(libraries, &[&example[0]]);
+        let artifact = libraries[0]
+            .downloads
+            .as_ref()
+            .unwrap()
+            .artifact
+            .as_ref()
+            .unwrap();
+        assert_eq!(artifact.sha1, example[0].sha1);
+        assert!(libraries[1].downloads.is_none());
+
+        let libraries = with_library_overrides(libraries, &[&example[0], &local]);
+        let artifact = libraries[0]
+            .downloads
+            .as_ref()
+            .unwrap()
+            .artifact
+            .as_ref()
+            .unwrap();
+        assert_eq!(artifact.url, "https://local/lwjgl.jar");
+    }
+}
@@ -0,0 +1,49 @@
+use sha1::{Digest, Sha1};
+
+pub fn generate_rollout_id() -> String {
+    uuid::Builder::from_random_bytes(rand::random())
+        .into_uuid()
+        .to_string()
+}
+
+// 0..100, the same for every launch on this machine
+pub fn get_rollout_bucket(rollout_id: &str) -> u8 {
+    let hash = Sha1::digest(rollout_id.as_bytes());
+    let value = u32::from_be_bytes([hash[0], hash[1], hash[2], hash[3]]);
+    (value % 100) as u8
+}
+
+// versions without a rollout percent are available to everyone
+pub fn is_rolled_out(rollout_percent: Option<u8>, bucket: u8) -> bool {
+    rollout_percent.is_none_or(|percent| bucket < percent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rollout_bucket() {
+        let id = "0b7e1b5c-54c9-4b39-9f0c-7c4e0f6f2a7d";
+        let bucket = get_rollout_bucket(id);
+        assert_eq!(bucket, get_rollout_bucket(id));
+        assert!(bucket < 100);
+
+        let buckets: Vec<u8> = (0..1000)
+            .map(|_| get_rollout_bucket(&generate_rollout_id()))
+            .collect();
+        assert!(buckets.iter().all(|bucket| *bucket < 100));
+        // roughly uniform, 10% of the machines get a 10% rollout
+        let in_rollout = buckets
+            .iter()
+            .filter(|bucket| is_rolled_out(Some(10), **bucket))
+            .count();
+        assert!((50..150).contains(&in_rollout), "{in_rollout}");
+
+        assert!(is_rolled_out(None, 99));
+        assert!(is_rolled_out(Some(100), 99));
+        assert!(!is_rolled_out(Some(0), 0));
+        assert!(is_rolled_out(Some(50), 49));
+        assert!(!is_rolled_out(Some(50), 50));
+    }
+}
@@ -0,0 +1,789 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures::stream::StreamExt;
+use log::{debug, info, warn};
+use rand::SeedableRng as _;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom as _;
+use shared::adaptive_download::{
+    DEFAULT_MAX_CONNECTIONS, DownloadHealth, MirrorRanking, download_files_with_health,
+    rank_mirrors,
+};
+use shared::checksums::Checksums;
+use shared::hash_cache::HashCache;
+use shared::paths::{
+    get_authlib_injector_path, get_hash_cache_path, get_libraries_dir, get_minecraft_dir,
+    get_running_lock_path,
+};
+use shared::version::asset_metadata::AssetsMetadata;
+use tokio::fs as tokio_fs;
+use tokio::sync::{mpsc, oneshot};
+
+use shared::files::{self, CheckEntry, DownloadEntry, HashLimits};
+use shared::progress::ProgressBar;
+use shared::version::extra_version_metadata::{
+    AuthBackend, AuthlibInjector, DownloadMirrors, ExtraVersionMetadata,
+};
+use shared::version::version_metadata;
+
+use crate::lang::LangMessage;
+use crate::launcher::running_lock::is_locked;
+use crate::launcher::shared_data::is_linked_path;
+
+use super::complete_version_metadata::CompleteVersionMetadata;
+use super::{archive, dedup, file_store, natives, os, overrides};
+
+const COMPLETION_MARKER_FILE: &str = ".download_complete";
+const SYNC_PLAN_MAX_AGE: Duration = Duration::from_secs(5 * 60);
+
+#[derive(thiserror::Error, Debug)]
+pub enum SyncError {
+    #[error("Instance {0} is running")]
+    InstanceRunning(String),
+}
+
+pub struct ExtraFilesPreview {
+    pub rule_path: String,
+    pub paths: Vec<PathBuf>,
+}
+
+pub struct DeleteExtraRequest {
+    pub previews: Vec<ExtraFilesPreview>,
+    pub response: oneshot::Sender<bool>,
+}
+
+struct ObjectsEntries {
+    check_entries: Vec<CheckEntry>,
+    extra_files: Vec<ExtraFilesPreview>,
+}
+
+// on a case-sensitive filesystem a directory with another casing would be duplicated
+// by the download and its files deleted as extra, so it's renamed to the metadata casing
+fn normalize_dir_casing(minecraft_dir: &Path, rel_dir: &Path) -> std::io::Result<()> {
+    let mut dir = minecraft_dir.to_path_buf();
+    for component in rel_dir.components() {
+        let Component::Normal(name) = component else {
+            return Ok(());
+        };
+        let path = dir.join(name);
+        if !path.exists() {
+            let Ok(read_dir) = std::fs::read_dir(&dir) else {
+                return Ok(());
+            };
+            let name = name.to_string_lossy().to_lowercase();
+            let matching: Vec<PathBuf> = read_dir
+                .filter_map(Result::ok)
+                .filter(|entry| entry.file_name().to_string_lossy().to_lowercase() == name)
+                .map(|entry| entry.path())
+                .filter(|path| path.is_dir())
+                .collect();
+            // with several candidates there is no way to tell which one is right
+            let [existing] = matching.as_slice() else {
+                return Ok(());
+            };
+            warn!(
+                "Directory {} differs from the instance metadata by case, renaming it to {}",
+                existing.display(),
+                path.display()
+            );
+            std::fs::rename(existing, &path)?;
+        }
+        dir = path;
+    }
+    Ok(())
+}
+
+fn get_objects_entries(
+    extra_version_metadata: &ExtraVersionMetadata,
+    force_overwrite: bool,
+    minecraft_dir: &Path,
+) -> anyhow::Result<ObjectsEntries> {
+    let include = &extra_version_metadata.include;
+
+    // sorted, so parents are renamed before their children are looked up
+    let object_dirs: BTreeSet<&Path> = include
+        .iter()
+        .flat_map(|rule| &rule.objects)
+        .filter_map(|object| Path::new(&object.path).parent())
+        .collect();
+    for dir in object_dirs {
+        normalize_dir_casing(minecraft_dir, dir)?;
+    }
+
+    let mut check_entries = vec![];
+    let mut extra_files = vec![];
+    let mut extra_paths = HashSet::new();
+    let mut used_paths = HashSet::new();
+    for rule in include {
+        let objects = &rule.objects;
+
+        let objects_paths = rule
+            .objects
+            .iter()
+            .map(|object| minecraft_dir.join(&object.path))
+            .collect::<HashSet<_>>();
+
+        if rule.overwrite && rule.delete_extra || force_overwrite {
+            let rule_path = minecraft_dir.join(&rule.path);
+            let files_in_dir = files::get_files_ignore_paths(&rule_path, &used_paths)?;
+            let mut paths = vec![];
+            for file in files_in_dir {
+                // files behind a link belong to the shared data dir
+                if !objects_paths.contains(&file)
+                    && !is_linked_path(minecraft_dir, &file)
+                    && extra_paths.insert(file.clone())
+                {
+                    paths.push(file);
+                }
+            }
+            if !paths.is_empty() {
+                paths.sort();
+                extra_files.push(ExtraFilesPreview {
+                    rule_path: rule.path.clone(),
+                    paths,
+                });
+            }
+        }
+
+        if rule.overwrite || force_overwrite {
+            check_entries.extend(objects.iter().map(|object| CheckEntry {
+                urls: vec![object.url.clone()],
+                remote_sha1: Some(object.sha1.clone()),
+                remote_size: object.size,
+                path: minecraft_dir.join(&object.path),
+            }));
+        } else if rule.recursive
+            || !minecraft_dir.join(&rule.path).exists()
+            || !minecraft_dir
+                .join(&rule.path)
+                .join(COMPLETION_MARKER_FILE)
+                .exists()
+        {
+            check_entries.extend(objects.iter().filter_map(|object| {
+                let path = minecraft_dir.join(&object.path);
+                if !path.exists() {
+                    Some(CheckEntry {
+                        urls: vec![object.url.clone()],
+                        remote_sha1: Some(object.sha1.clone()),
+                        remote_size: object.size,
+                        path,
+                    })
+                } else {
+                    None
+                }
+            }));
+        }
+
+        used_paths.extend(objects_paths);
+    }
+
+    Ok(ObjectsEntries {
+        check_entries,
+        extra_files,
+    })
+}
+
+async fn delete_extra_files(
+    extra_files: Vec<ExtraFilesPreview>,
+    force_overwrite: bool,
+    delete_extra_sender: &mpsc::UnboundedSender<DeleteExtraRequest>,
+) -> anyhow::Result<()> {
+    if extra_files.is_empty() {
+        return Ok(());
+    }
+
+    let paths: Vec<_> = extra_files
+        .iter()
+        .flat_map(|preview| preview.paths.clone())
+        .collect();
+
+    if !force_overwrite {
+        let (response, response_receiver) = oneshot::channel();
+        let request = DeleteExtraRequest {
+            previews: extra_files,
+            response,
+        };
+        // a dropped request or sender counts as a refusal
+        let confirmed =
+            delete_extra_sender.send(request).is_ok() && response_receiver.await.unwrap_or(false);
+        if !confirmed {
+            info!("Skipping deletion of {} extra files", paths.len());
+            return Ok(());
+        }
+    }
+
+    for path in paths {
+        tokio_fs::remove_file(path).await?;
+    }
+
+    Ok(())
+}
+
+async fn fetch_hashes(
+    sha1_urls: HashMap<PathBuf, String>,
+) -> anyhow::Result<HashMap<PathBuf, String>> {
+    let client = reqwest::Client::new();
+
+    async fn fetch_single_hash(
+        client: reqwest::Client,
+        path: PathBuf,
+        url: String,
+    ) -> anyhow::Result<(PathBuf, String)> {
+        let response = client.get(&url).send().await?.error_for_status()?;
+        let bytes = response.bytes().await?;
+        let sha1 = String::from_utf8(bytes.to_vec())?;
+        Ok((path, sha1))
+    }
+
+    const MAX_CONCURRENT_HASH_FETCHES: usize = 20;
+
+    let mut tasks = futures::stream::FuturesUnordered::new();
+    let mut urls_iter = sha1_urls.into_iter();
+
+    for _ in 0..MAX_CONCURRENT_HASH_FETCHES {
+        if let Some((path, url)) = urls_iter.next() {
+            tasks.push(fetch_single_hash(client.clone(), path, url));
+        }
+    }
+
+    let mut hashes = HashMap::new();
+
+    while let Some(result) = tasks.next().await {
+        match result {
+            Ok((path, sha1)) => {
+                hashes.insert(path, sha1);
+            }
+            Err(e) => {
+                if let Some(se) = e.downcast_ref::<reqwest::Error>() {
+                    if se.status() == Some(reqwest::StatusCode::NOT_FOUND) {
+                        warn!("SHA1 hash not found: {:?}", se.url().unwrap());
+                    } else {
+                        return Err(e);
+                    }
+                } else {
+                    return Err(e);
+                }
+            }
+        }
+
+        if let Some((path, url)) = urls_iter.next() {
+            tasks.push(fetch_single_hash(client.clone(), path, url));
+        }
+    }
+
+    Ok(hashes)
+}
+
+async fn get_libraries_entries(
+    libraries: &Vec<version_metadata::Library>,
+    libraries_dir: &Path,
+) -> anyhow::Result<Vec<CheckEntry>> {
+    let mut sha1_urls = HashMap::<PathBuf, String>::new();
+    let mut check_download_entries: Vec<CheckEntry> = Vec::new();
+
+    for library in libraries {
+        for entry in library.get_check_entries(
+            libraries_dir,
+            Some((&os::get_os_name(), &os::get_system_arch())),
+        ) {
+            if entry.remote_sha1.is_some() || !entry.path.exists() {
+                if entry.urls.iter().all(String::is_empty) {
+                    info!("Skipping library with no URL: {:?}", entry.path);
+                    continue;
+                }
+                check_download_entries.push(entry);
+            } else {
+                sha1_urls.insert(entry.path.clone(), library.get_sha1_url());
+                check_download_entries.push(CheckEntry {
+                    remote_sha1: None,
+                    ..entry
+                });
+            }
+        }
+    }
+
+    let missing_hashes = fetch_hashes(sha1_urls).await?;
+
+    let check_download_entries: Vec<_> = check_download_entries
+        .into_iter()
+        .map(|entry| {
+            if entry.remote_sha1.is_none()
+                && let Some(sha1) = missing_hashes.get(&entry.path)
+            {
+                return CheckEntry {
+                    remote_sha1: Some(sha1.clone()),
+                    ..entry
+                };
+            }
+            entry
+        })
+        .collect();
+
+    Ok(check_download_entries)
+}
+
+pub const AUTHLIB_INJECTOR_URL: &str = "https://github.com/yushijinhun/authlib-injector/releases/download/v1.2.5/authlib-injector-1.2.5.jar";
+pub const AUTHLIB_INJECTOR_SHA1: &str = "1eca6aa7faf7ac6e3211862afa6e43fe2eedd07b";
+
+// the one from the instance metadata if it has one
+pub fn get_authlib_injector(version_metadata: &CompleteVersionMetadata) -> AuthlibInjector {
+    version_metadata
+        .get_authlib_injector()
+        .unwrap_or_else(|| AuthlibInjector {
+            url: AUTHLIB_INJECTOR_URL.to_string(),
+            sha1: AUTHLIB_INJECTOR_SHA1.to_string(),
+        })
+}
+
+fn get_authlib_injector_entry(
+    version_metadata: &CompleteVersionMetadata,
+    launcher_dir: &Path,
+) -> Option<CheckEntry> {
+    if let Some(auth_backend) = version_metadata.get_auth_backend()
+        && auth_backend == &AuthBackend::Microsoft
+    {
+        None
+    } else {
+        let authlib_injector = get_authlib_injector(version_metadata);
+        Some(CheckEntry {
+            path: get_authlib_injector_path(launcher_dir, &authlib_injector.sha1),
+            urls: vec![authlib_injector.url],
+            remote_sha1: Some(authlib_injector.sha1),
+            remote_size: None,
+        })
+    }
+}
+
+// sizes from the checksums the builder published, so the download size is known
+// for the files without a size in the metadata
+async fn fill_published_sizes(
+    check_entries: &mut [CheckEntry],
+    version_metadata: &CompleteVersionMetadata,
+) {
+    if check_entries
+        .iter()
+        .all(|entry| entry.remote_size.is_some())
+    {
+        return;
+    }
+    let Some(checksums_url) = version_metadata
+        .get_extra()
+        .and_then(|extra| extra.checksums_url.as_deref())
+    else {
+        return;
+    };
+    let checksums = match Checksums::fetch(checksums_url).await {
+        Ok(checksums) => checksums,
+        Err(e) => {
+            warn!("Failed to fetch {checksums_url}, some download sizes are unknown:\n{e:?}");
+            return;
+        }
+    };
+
+    let sizes = checksums.get_sizes_by_sha1();
+    for entry in check_entries
+        .iter_mut()
+        .filter(|entry| entry.remote_size.is_none())
+    {
+        if let Some(size) = entry
+            .remote_sha1
+            .as_deref()
+            .and_then(|sha1| sizes.get(sha1))
+        {
+            entry.remote_size = Some(*size);
+        }
+    }
+}
+
+// the best server first, the others are used if it fails mid-download.
+// a fresh cached ranking is reused instead of probing the servers again
+async fn add_mirror_urls(
+    check_entries: &mut [CheckEntry],
+    download_mirrors: &DownloadMirrors,
+    cached_ranking: Option<&MirrorRanking>,
+) -> MirrorRanking {
+    let bases = download_mirrors.get_bases();
+    let ranking = match cached_ranking.filter(|ranking| ranking.is_fresh_for(&bases)) {
+        Some(ranking) => ranking.clone(),
+        None => rank_mirrors(bases).await,
+    };
+    info!("Download servers by rank: {}", ranking.bases.join(", "));
+    if let Some(best) = ranking.get_best() {
+        info!("Using download server {best}");
+    }
+    for entry in check_entries.iter_mut() {
+        if let [url] = entry.urls.as_slice() {
+            entry.urls = download_mirrors.get_urls(url, &ranking.bases);
+        }
+    }
+    ranking
+}
+
+async fn mark_download_complete(
+    version_metadata: &CompleteVersionMetadata,
+    minecraft_dir: &Path,
+) -> anyhow::Result<String> {
+    let extra = version_metadata.get_extra();
+    if let Some(extra) = extra {
+        for rule in &extra.include {
+            let path = minecraft_dir.join(&rule.path);
+            if !rule.overwrite && !rule.recursive && path.is_dir() {
+                let completion_marker_path = path.join(COMPLETION_MARKER_FILE);
+                tokio_fs::write(completion_marker_path, b"").await?;
+            }
+        }
+    }
+
+    Ok(version_metadata.get_content_hash())
+}
+
+// files a sync would download, found by checking the local files
+// legacy asset layouts are copied out of the objects dir after the download
+struct VirtualAssets {
+    metadata: AssetsMetadata,
+    assets_dir: PathBuf,
+    target_dir: PathBuf,
+}
+
+// how execute_sync downloads, from the launcher config
+#[derive(Clone, Copy)]
+pub struct SyncSettings {
+    pub max_connections: usize,
+    // mods are downloaded into the file store and linked from there
+    pub use_file_store: bool,
+}
+
+impl Default for SyncSettings {
+    fn default() -> Self {
+        Self {
+            max_connections: DEFAULT_MAX_CONNECTIONS,
+            use_file_store: false,
+        }
+    }
+}
+
+pub struct SyncPlan {
+    instance_name: String,
+    content_hash: String,
+    force_overwrite: bool,
+    download_entries: Vec<DownloadEntry>,
+    extra_files: Vec<ExtraFilesPreview>,
+    virtual_assets: Option<VirtualAssets>,
+    // keyed by the download server base, like Config::mirror_rankings
+    mirror_ranking: Option<(String, MirrorRanking)>,
+    created_at: Instant,
+}
+
+impl SyncPlan {
+    pub fn get_instance_name(&self) -> &str {
+        &self.instance_name
+    }
+
+    pub fn get_file_count(&self) -> usize {
+        self.download_entries.len()
+    }
+
+    // files without a known size aren't counted
+    pub fn get_download_size(&self) -> u64 {
+        self.download_entries
+            .iter()
+            .filter_map(|entry| entry.size)
+            .sum()
+    }
+
+    // the download server base and the ranking of its mirrors, to be cached by the caller
+    pub fn get_mirror_ranking(&self) -> Option<(&str, &MirrorRanking)> {
+        self.mirror_ranking
+            .as_ref()
+            .map(|(base, ranking)| (base.as_str(), ranking))
+    }
+
+    pub fn has_unknown_sizes(&self) -> bool {
+        self.download_entries
+            .iter()
+            .any(|entry| entry.size.is_none())
+    }
+
+    // files can change after a while, so only a fresh plan replaces the check
+    pub fn is_valid_for(
+        &self,
+        version_metadata: &CompleteVersionMetadata,
+        force_overwrite: bool,
+    ) -> bool {
+        self.instance_name == version_metadata.get_name()
+            && self.content_hash == version_metadata.get_content_hash()
+            && self.force_overwrite == force_overwrite
+            && self.created_at.elapsed() < SYNC_PLAN_MAX_AGE
+    }
+}
+
+// replacing jars under a running game breaks it
+pub fn ensure_not_running(launcher_dir: &Path, instance_name: &str) -> anyhow::Result<()> {
+    if is_locked(&get_running_lock_path(launcher_dir, instance_name)) {
+        return Err(SyncError::InstanceRunning(instance_name.to_string()).into());
+    }
+    Ok(())
+}
+
+pub fn get_libraries(
+    version_metadata: &CompleteVersionMetadata,
+    launcher_dir: &Path,
+) -> anyhow::Result<Vec<version_metadata::Library>> {
+    let local_overrides = overrides::read_local_library_overrides(launcher_dir)?;
+    let mut libraries = version_metadata.get_libraries_with_overrides(&local_overrides);
+    libraries.extend(version_metadata.get_extra_forge_libs());
+    Ok(libraries)
+}
+
+// the check phase of a sync, only the asset index can be downloaded.
+// mirror_rankings are the cached rankings by download server base
+pub async fn plan_sync(
+    version_metadata: &CompleteVersionMetadata,
+    force_overwrite: bool,
+    launcher_dir: &Path,
+    assets_dir: &Path,
+    progress_bar: Arc<dyn ProgressBar<LangMessage> + Send + Sync>,
+    hash_limits: HashLimits,
+    mirror_rankings: &HashMap<String, MirrorRanking>,
+) -> anyhow::Result<SyncPlan> {
+    archive::ensure_not_archived(launcher_dir, version_metadata.get_name())?;
+    let libraries_dir = get_libraries_dir(launcher_dir);
+    let minecraft_dir = get_minecraft_dir(launcher_dir, version_metadata.get_name());
+
+    let mut check_entries = vec![];
+
+    check_entries.push(version_metadata.get_client_check_entry(launcher_dir)?);
+
+    let libraries = get_libraries(version_metadata, launcher_dir)?;
+    check_entries.extend(get_libraries_entries(&libraries, &libraries_dir).await?);
+
+    let mut extra_files = vec![];
+    if let Some(extra) = version_metadata.get_extra() {
+        let objects_entries = get_objects_entries(extra, force_overwrite, &minecraft_dir)?;
+        extra_files = objects_entries.extra_files;
+        check_entries.extend(objects_entries.check_entries);
+    }
+
+    if let Some(authlib_injector) = get_authlib_injector_entry(version_metadata, launcher_dir) {
+        check_entries.push(authlib_injector);
+    }
+
+    let asset_index = version_metadata.get_asset_index()?;
+    let asset_metadata = AssetsMetadata::read_or_download(asset_index, assets_dir).await?;
+
+    check_entries.extend(asset_metadata.get_check_entries(
+        assets_dir,
+        version_metadata.get_resources_url_base(),
+        force_overwrite,
+    )?);
+    let virtual_assets = asset_metadata
+        .get_virtual_dir(&asset_index.id, assets_dir, &minecraft_dir)
+        .map(|target_dir| VirtualAssets {
+            metadata: asset_metadata,
+            assets_dir: assets_dir.to_path_buf(),
+            target_dir,
+        });
+
+    fill_published_sizes(&mut check_entries, version_metadata).await;
+    let mut mirror_ranking = None;
+    if let Some(download_mirrors) = version_metadata
+        .get_extra()
+        .and_then(|extra| extra.download_mirrors.as_ref())
+    {
+        let base = &download_mirrors.download_server_base;
+        let ranking = add_mirror_urls(
+            &mut check_entries,
+            download_mirrors,
+            mirror_rankings.get(base),
+        )
+        .await;
+        mirror_ranking = Some((base.clone(), ranking));
+    }
+
+    info!("Got {} check download entries", check_entries.len());
+    progress_bar.set_message(LangMessage::CheckingFiles);
+    let hash_cache_path = get_hash_cache_path(launcher_dir);
+    let mut hash_cache = HashCache::load(&hash_cache_path).await;
+    let download_entries = files::get_download_entries_with_cache(
+        check_entries,
+        progress_bar,
+        hash_limits,
+        &mut hash_cache,
+        force_overwrite,
+    )
+    .await?;
+    if let Err(e) = hash_cache.save(&hash_cache_path).await {
+        warn!("Failed to save the hash cache:\n{e:?}");
+    }
+
+    info!("Got {} download entries", download_entries.len());
+
+    Ok(SyncPlan {
+        instance_name: version_metadata.get_name().to_string(),
+        content_hash: version_metadata.get_content_hash(),
+        force_overwrite,
+        download_entries,
+        extra_files,
+        virtual_assets,
+        mirror_ranking,
+        created_at: Instant::now(),
+    })
+}
+
+// downloads the files of a plan, returns the content hash of the synced instance.
+// download_health is reset and collects the request stats of the download servers
+pub async fn execute_sync(
+    version_metadata: &CompleteVersionMetadata,
+    plan: SyncPlan,
+    launcher_dir: &Path,
+    progress_bar: Arc<dyn ProgressBar<LangMessage> + Send + Sync>,
+    delete_extra_sender: mpsc::UnboundedSender<DeleteExtraRequest>,
+    download_health: &DownloadHealth,
+    settings: SyncSettings,
+) -> anyhow::Result<String> {
+    let version_name = version_metadata.get_name();
+    ensure_not_running(launcher_dir, version_name)?;
+    archive::ensure_not_archived(launcher_dir, version_name)?;
+
+    let minecraft_dir = get_minecraft_dir(launcher_dir, version_name);
+
+    delete_extra_files(plan.extra_files, plan.force_overwrite, &delete_extra_sender).await?;
+
+    let mut download_entries = plan.download_entries;
+    let mut rng = StdRng::from_os_rng();
+    download_entries.shuffle(&mut rng);
+
+    let paths = download_entries
+        .iter()
+        .map(|x| x.path.clone())
+        .collect::<Vec<_>>();
+    debug!("Paths to download: {paths:?}");
+    dedup::break_links(launcher_dir, &paths).await?;
+
+    let mut store_links = vec![];
+    if settings.use_file_store {
+        (download_entries, store_links) =
+            file_store::prepare_downloads(launcher_dir, &minecraft_dir, download_entries).await?;
+    }
+
+    progress_bar.set_message(LangMessage::DownloadingFiles);
+    download_health.reset();
+    download_health.set_mirror(
+        plan.mirror_ranking
+            .as_ref()
+            .and_then(|(_, ranking)| ranking.get_best())
+            .map(str::to_string),
+    );
+    let result = download_files_with_health(
+        download_entries,
+        progress_bar,
+        download_health,
+        settings.max_connections,
+    )
+    .await;
+    // logged even if the sync fails, so bug reports include it
+    download_health.log_summary();
+    result?;
+    file_store::finish_downloads(store_links).await?;
+
+    if let Some(virtual_assets) = plan.virtual_assets {
+        info!(
+            "Copying legacy assets to {}",
+            virtual_assets.target_dir.display()
+        );
+        virtual_assets
+            .metadata
+            .virtualize(&virtual_assets.assets_dir, &virtual_assets.target_dir)
+            .await?;
+    }
+
+    let libraries = get_libraries(version_metadata, launcher_dir)?;
+    natives::extract_natives(&libraries, launcher_dir, version_name)?;
+    if let Err(e) = natives::remove_unused_natives(launcher_dir) {
+        warn!("Failed to remove unused natives:\n{e:?}");
+    }
+
+    mark_download_complete(version_metadata, &minecraft_dir).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn extra_with_paths(paths: &[&str]) -> ExtraVersionMetadata {
+        let objects: Vec<_> = paths
+            .iter()
+            .map(|path| {
+                serde_json::json!({
+                    "path": path,
+                    "sha1": "0".repeat(40),
+                    "url": format!("https://example.com/{path}"),
+                    "size": null,
+                })
+            })
+            .collect();
+        serde_json::from_value(serde_json::json!({
+            "include": [{"path": "", "objects": objects}],
+            "recommended_xmx": null,
+        }))
+        .unwrap()
+    }
+
+    fn get_dir_names(dir: &Path) -> Vec<String> {
+        let mut names: Vec<_> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_normalize_dir_casing() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        // nothing to normalize on a case-insensitive filesystem
+        std::fs::write(dir.join("probe"), "").unwrap();
+        if dir.join("PROBE").exists() {
+            return;
+        }
+
+        for (disk_dir, metadata_dir) in [("Config", "config"), ("config", "Config")] {
+            let minecraft_dir = dir.join(format!("minecraft_{disk_dir}"));
+            std::fs::create_dir_all(minecraft_dir.join(disk_dir).join("Sub")).unwrap();
+            std::fs::write(minecraft_dir.join(disk_dir).join("Sub/a.json"), "{}").unwrap();
+
+            let extra = extra_with_paths(&[
+                &format!("{metadata_dir}/sub/a.json"),
+                &format!("{metadata_dir}/b.json"),
+            ]);
+            let entries = get_objects_entries(&extra, false, &minecraft_dir).unwrap();
+
+            assert_eq!(get_dir_names(&minecraft_dir), vec![metadata_dir]);
+            assert_eq!(
+                get_dir_names(&minecraft_dir.join(metadata_dir)),
+                vec!["sub"]
+            );
+            assert!(minecraft_dir.join(metadata_dir).join("sub/a.json").exists());
+            let paths: Vec<_> = entries.check_entries.iter().map(|e| &e.path).collect();
+            assert_eq!(
+                paths,
+                vec![
+                    &minecraft_dir.join(format!("{metadata_dir}/sub/a.json")),
+                    &minecraft_dir.join(format!("{metadata_dir}/b.json")),
+                ]
+            );
+        }
+
+        // two candidates are left alone
+        let minecraft_dir = dir.join("minecraft_ambiguous");
+        for name in ["CONFIG", "Config"] {
+            std::fs::create_dir_all(minecraft_dir.join(name)).unwrap();
+        }
+        get_objects_entries(&extra_with_paths(&["config/a.json"]), false, &minecraft_dir).unwrap();
+        assert_eq!(get_dir_names(&minecraft_dir), vec!["CONFIG", "Config"]);
+    }
+}
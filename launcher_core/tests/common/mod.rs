@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+use tokio::net::TcpListener;
+
+pub struct FixtureFile {
+    pub body: Vec<u8>,
+    // content encoding -> encoded body, sent when the request accepts the encoding
+    pub encoded: HashMap<&'static str, Vec<u8>>,
+    pub status: &'static str,
+    pub headers: Vec<(&'static str, String)>,
+    // responses to the following requests of the same path, the last one repeats
+    pub then: Vec<FixtureFile>,
+}
+
+impl From<Vec<u8>> for FixtureFile {
+    fn from(body: Vec<u8>) -> Self {
+        FixtureFile {
+            body,
+            encoded: HashMap::new(),
+            status: "200 OK",
+            headers: vec![],
+            then: vec![],
+        }
+    }
+}
+
+impl FixtureFile {
+    fn get_response(&self, request_index: usize) -> &FixtureFile {
+        match request_index {
+            0 => self,
+            _ => self
+                .then
+                .get(request_index - 1)
+                .or(self.then.last())
+                .unwrap_or(self),
+        }
+    }
+}
+
+fn get_header<'a>(request: &'a str, name: &str) -> Option<&'a str> {
+    request.lines().find_map(|line| {
+        let (header, value) = line.split_once(':')?;
+        header.eq_ignore_ascii_case(name).then_some(value.trim())
+    })
+}
+
+// serves files by path until the test ends, the urls in them depend on the port
+pub async fn start_file_server(
+    get_files: impl FnOnce(&str) -> HashMap<String, FixtureFile>,
+) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let files = Arc::new(get_files(&base_url));
+    let request_counts = Arc::new(Mutex::new(HashMap::<String, usize>::new()));
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let files = files.clone();
+            let request_counts = request_counts.clone();
+            tokio::spawn(async move {
+                let mut buf = [0; 4096];
+                let len = socket.read(&mut buf).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..len]);
+                let mut request_line = request.split_whitespace();
+                let method = request_line.next().unwrap_or_default();
+                let path = request_line.next().unwrap_or_default();
+                let accept_encoding = get_header(&request, "accept-encoding").unwrap_or_default();
+                let file = files.get(path).map(|file| {
+                    let mut request_counts = request_counts.lock().unwrap();
+                    let request_index = request_counts.entry(path.to_string()).or_default();
+                    *request_index += 1;
+                    file.get_response(*request_index - 1)
+                });
+                let (status, encoding, body) = match file {
+                    Some(file) => match file
+                        .encoded
+                        .iter()
+                        .find(|(encoding, _)| accept_encoding.contains(*encoding))
+                    {
+                        Some((encoding, body)) => (file.status, Some(*encoding), body.clone()),
+                        None => (file.status, None, file.body.clone()),
+                    },
+                    None => ("404 Not Found", None, vec![]),
+                };
+                let mut header = format!(
+                    "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n",
+                    body.len()
+                );
+                if let Some(encoding) = encoding {
+                    header.push_str(&format!("Content-Encoding: {encoding}\r\n"));
+                }
+                for (name, value) in file.map(|file| file.headers.as_slice()).unwrap_or_default() {
+                    header.push_str(&format!("{name}: {value}\r\n"));
+                }
+                header.push_str("\r\n");
+                let _ = socket.write_all(header.as_bytes()).await;
+                if method != "HEAD" {
+                    let _ = socket.write_all(&body).await;
+                }
+            });
+        }
+    });
+    base_url
+}
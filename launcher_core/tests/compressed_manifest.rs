@@ -0,0 +1,78 @@
+mod common;
+
+use std::collections::HashMap;
+use std::io::Write as _;
+
+use common::{FixtureFile, start_file_server};
+use flate2::{Compression, write::GzEncoder};
+use shared::compression::compress_zstd;
+use shared::version::version_manifest::VersionManifest;
+
+fn manifest_json(id: &str) -> Vec<u8> {
+    format!(r#"{{"versions": [{{"id": "{id}", "url": "", "sha1": ""}}]}}"#).into_bytes()
+}
+
+fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn encoded(body: Vec<u8>, encoding: &'static str, encoded_body: Vec<u8>) -> FixtureFile {
+    FixtureFile {
+        encoded: HashMap::from([(encoding, encoded_body)]),
+        ..body.into()
+    }
+}
+
+async fn fetch_id(base_url: &str, path: &str) -> String {
+    let manifest = VersionManifest::fetch(&format!("{base_url}{path}"))
+        .await
+        .unwrap();
+    manifest.versions[0].id.clone()
+}
+
+#[tokio::test]
+async fn test_fetch_compressed_manifest() {
+    let base_url = start_file_server(|_| {
+        HashMap::from([
+            (
+                "/gzip.json".to_string(),
+                encoded(manifest_json("plain"), "gzip", gzip(&manifest_json("gzip"))),
+            ),
+            (
+                "/zstd.json".to_string(),
+                encoded(
+                    manifest_json("plain"),
+                    "zstd",
+                    compress_zstd(&manifest_json("zstd")).unwrap(),
+                ),
+            ),
+            (
+                "/broken_zstd.json".to_string(),
+                encoded(manifest_json("plain"), "zstd", b"not zstd".to_vec()),
+            ),
+            ("/variant.json".to_string(), manifest_json("plain").into()),
+            (
+                "/variant.json.zst".to_string(),
+                compress_zstd(&manifest_json("variant")).unwrap().into(),
+            ),
+            (
+                "/broken_variant.json".to_string(),
+                manifest_json("plain").into(),
+            ),
+            (
+                "/broken_variant.json.zst".to_string(),
+                b"not zstd".to_vec().into(),
+            ),
+        ])
+    })
+    .await;
+
+    assert_eq!(fetch_id(&base_url, "/gzip.json").await, "gzip");
+    assert_eq!(fetch_id(&base_url, "/zstd.json").await, "zstd");
+    assert_eq!(fetch_id(&base_url, "/variant.json").await, "variant");
+    // corrupted payloads fall back to the uncompressed manifest
+    assert_eq!(fetch_id(&base_url, "/broken_zstd.json").await, "plain");
+    assert_eq!(fetch_id(&base_url, "/broken_variant.json").await, "plain");
+}
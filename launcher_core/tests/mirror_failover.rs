@@ -0,0 +1,48 @@
+mod common;
+
+use std::collections::HashMap;
+
+use common::start_file_server;
+use shared::adaptive_download::{download_files, rank_mirrors};
+use shared::files::DownloadEntry;
+use shared::progress::no_progress_bar;
+
+const MOD: &[u8] = b"mod jar";
+
+// nothing listens on the port once the listener is dropped
+fn get_dead_base_url() -> String {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    format!("http://{}", listener.local_addr().unwrap())
+}
+
+#[tokio::test]
+async fn test_mirror_failover() {
+    let base_url =
+        start_file_server(|_| HashMap::from([("/mods/a.jar".to_string(), MOD.to_vec().into())]))
+            .await;
+    let dead_base_url = get_dead_base_url();
+
+    let ranking = rank_mirrors(vec![dead_base_url.clone(), base_url.clone()]).await;
+    assert_eq!(ranking.bases, vec![base_url.clone(), dead_base_url.clone()]);
+    assert_eq!(ranking.get_best(), Some(base_url.as_str()));
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let dir = temp_dir.path();
+
+    // the first mirror refuses connections
+    download_files(
+        vec![DownloadEntry {
+            urls: vec![
+                format!("{dead_base_url}/mods/a.jar"),
+                format!("{base_url}/mods/a.jar"),
+            ],
+            path: dir.join("mods/a.jar"),
+            size: None,
+            sha1: None,
+        }],
+        no_progress_bar(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(std::fs::read(dir.join("mods/a.jar")).unwrap(), MOD);
+}
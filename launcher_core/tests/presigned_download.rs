@@ -0,0 +1,78 @@
+mod common;
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use common::{FixtureFile, start_file_server};
+use shared::adaptive_download::{INDIRECTION_HEADER, download_files};
+use shared::files::DownloadEntry;
+use shared::progress::no_progress_bar;
+
+const LIBRARY: &[u8] = b"library jar";
+
+fn redirect(location: &str) -> FixtureFile {
+    FixtureFile {
+        status: "302 Found",
+        headers: vec![("Location", location.to_string())],
+        ..Vec::new().into()
+    }
+}
+
+fn forbidden() -> FixtureFile {
+    FixtureFile {
+        status: "403 Forbidden",
+        ..Vec::new().into()
+    }
+}
+
+fn get_entry(base_url: &str, path: &str, dir: &Path) -> DownloadEntry {
+    DownloadEntry {
+        urls: vec![format!("{base_url}{path}")],
+        path: dir.join(path.trim_start_matches('/')),
+        size: None,
+        sha1: None,
+    }
+}
+
+#[tokio::test]
+async fn test_presigned_url_expiry() {
+    let base_url = start_file_server(|_| {
+        // the first signature has expired by the time it's used
+        let mut signing = redirect("/signed/expired.jar");
+        signing.then = vec![redirect("/signed/fresh.jar")];
+        let mut indirection: FixtureFile = br#"{"url": "/signed/indirect.jar"}"#.to_vec().into();
+        indirection.headers = vec![(INDIRECTION_HEADER, "url".to_string())];
+        HashMap::from([
+            ("/redirect.jar".to_string(), signing),
+            ("/signed/expired.jar".to_string(), forbidden()),
+            ("/signed/fresh.jar".to_string(), LIBRARY.to_vec().into()),
+            ("/indirect.jar".to_string(), indirection),
+            ("/signed/indirect.jar".to_string(), LIBRARY.to_vec().into()),
+            ("/forbidden.jar".to_string(), forbidden()),
+        ])
+    })
+    .await;
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let dir = temp_dir.path();
+
+    download_files(
+        vec![
+            get_entry(&base_url, "/redirect.jar", dir),
+            get_entry(&base_url, "/indirect.jar", dir),
+        ],
+        no_progress_bar(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(std::fs::read(dir.join("redirect.jar")).unwrap(), LIBRARY);
+    assert_eq!(std::fs::read(dir.join("indirect.jar")).unwrap(), LIBRARY);
+
+    // without a redirect a 403 is final
+    let result = download_files(
+        vec![get_entry(&base_url, "/forbidden.jar", dir)],
+        no_progress_bar(),
+    )
+    .await;
+    assert!(result.is_err());
+}
@@ -0,0 +1,248 @@
+mod common;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use common::{FixtureFile, start_file_server};
+use launcher_core::auth::user_info::{AuthData, UserInfo};
+use launcher_core::config::runtime_config::Config;
+use launcher_core::lang::LangMessage;
+use launcher_core::launcher::launch::build_launch_command;
+use launcher_core::version::complete_version_metadata::CompleteVersionMetadata;
+use launcher_core::version::instance_storage::InstanceStorage;
+use launcher_core::version::sync::{self, SyncSettings};
+use sha1::{Digest, Sha1};
+use shared::adaptive_download::DownloadHealth;
+use shared::files::HashLimits;
+use shared::paths::{get_client_jar_path, get_libraries_dir, get_minecraft_dir};
+use shared::progress::{NoProgressBar, ProgressBar};
+use shared::version::version_manifest::VersionInfo;
+
+fn sha1(data: &[u8]) -> String {
+    format!("{:x}", Sha1::digest(data))
+}
+
+const CLIENT: &[u8] = b"client jar";
+const LIBRARY: &[u8] = b"library jar";
+const ASSET_INDEX: &[u8] = br#"{"objects": {}}"#;
+// microsoft auth doesn't need authlib-injector, which would be downloaded from github
+const EXTRA_METADATA: &[u8] = br#"{"auth_backend": {"type": "microsoft"}}"#;
+
+fn get_fixture_files(base_url: &str) -> HashMap<String, FixtureFile> {
+    let metadata = format!(
+        r#"{{
+            "id": "fixture",
+            "mainClass": "net.minecraft.client.main.Main",
+            "minecraftArguments": "--username ${{auth_player_name}} --gameDir ${{game_directory}}",
+            "downloads": {{
+                "client": {{"url": "{base_url}/client.jar", "sha1": "{}", "size": {}}}
+            }},
+            "assetIndex": {{"id": "fixture", "url": "{base_url}/assets.json", "sha1": "{}"}},
+            "libraries": [{{
+                "name": "com.example:library:1.0",
+                "downloads": {{
+                    "artifact": {{"url": "{base_url}/library.jar", "sha1": "{}", "size": {}}}
+                }}
+            }}]
+        }}"#,
+        sha1(CLIENT),
+        CLIENT.len(),
+        sha1(ASSET_INDEX),
+        sha1(LIBRARY),
+        LIBRARY.len(),
+    );
+    HashMap::from([
+        ("/version.json".to_string(), metadata.into_bytes().into()),
+        ("/extra.json".to_string(), EXTRA_METADATA.to_vec().into()),
+        ("/client.jar".to_string(), CLIENT.to_vec().into()),
+        ("/library.jar".to_string(), LIBRARY.to_vec().into()),
+        ("/assets.json".to_string(), ASSET_INDEX.to_vec().into()),
+    ])
+}
+
+async fn start_fixture_server() -> VersionInfo {
+    let mut metadata_sha1 = String::new();
+    let base_url = start_file_server(|base_url| {
+        let files = get_fixture_files(base_url);
+        metadata_sha1 = sha1(&files["/version.json"].body);
+        files
+    })
+    .await;
+    VersionInfo {
+        id: "fixture".to_string(),
+        url: format!("{base_url}/version.json"),
+        sha1: metadata_sha1,
+        name: None,
+        inherits_from: vec![],
+        extra_metadata_url: Some(format!("{base_url}/extra.json")),
+        extra_metadata_sha1: Some(sha1(EXTRA_METADATA)),
+        rollout_percent: None,
+        server_address: None,
+    }
+}
+
+#[tokio::test]
+async fn test_sync_fixture_instance() {
+    let version_info = start_fixture_server().await;
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let launcher_dir = temp_dir.path();
+    let assets_dir = launcher_dir.join("assets");
+    let progress_bar: Arc<dyn ProgressBar<LangMessage> + Send + Sync> = Arc::new(NoProgressBar);
+
+    let metadata = CompleteVersionMetadata::read_or_download(&version_info, launcher_dir, None)
+        .await
+        .unwrap();
+    let plan = sync::plan_sync(
+        &metadata,
+        false,
+        launcher_dir,
+        &assets_dir,
+        progress_bar.clone(),
+        HashLimits {
+            concurrency: 2,
+            ..HashLimits::default()
+        },
+        &HashMap::new(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(plan.get_file_count(), 2);
+
+    let (delete_extra_sender, _delete_extra_receiver) = tokio::sync::mpsc::unbounded_channel();
+    sync::execute_sync(
+        &metadata,
+        plan,
+        launcher_dir,
+        progress_bar.clone(),
+        delete_extra_sender,
+        &DownloadHealth::new(),
+        SyncSettings::default(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        std::fs::read(get_client_jar_path(launcher_dir, "fixture")).unwrap(),
+        CLIENT
+    );
+    assert_eq!(
+        std::fs::read(
+            get_libraries_dir(launcher_dir).join("com/example/library/1.0/library-1.0.jar")
+        )
+        .unwrap(),
+        LIBRARY
+    );
+
+    // everything is in place now
+    let plan = sync::plan_sync(
+        &metadata,
+        false,
+        launcher_dir,
+        &assets_dir,
+        progress_bar,
+        HashLimits {
+            concurrency: 2,
+            ..HashLimits::default()
+        },
+        &HashMap::new(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(plan.get_file_count(), 0);
+}
+
+// users with cyrillic or cjk names have them in the launcher dir
+#[tokio::test]
+async fn test_non_ascii_launcher_dir() {
+    let version_info = VersionInfo {
+        name: Some("Сборка 1".to_string()),
+        ..start_fixture_server().await
+    };
+    let temp_dir = tempfile::Builder::new()
+        .prefix("launcher_core_Пользователь_ユーザー_")
+        .tempdir()
+        .unwrap();
+    let launcher_dir = temp_dir.path();
+    let mut config: Config = serde_json::from_value(serde_json::json!({
+        "java_paths": {"Сборка 1": launcher_dir.join("java/bin/java")},
+        "data_dir": launcher_dir,
+        "assets_dir": launcher_dir.join("assets"),
+        "xmx": {},
+        "use_native_glfw": {},
+        "lang": "Russian",
+        "hide_launcher_after_launch": true,
+        "auth_profiles": {},
+    }))
+    .unwrap();
+    assert_eq!(config.get_launcher_dir(), launcher_dir);
+
+    let mut instance_storage = InstanceStorage::load(&config).await;
+    instance_storage
+        .add_local_instance(&config, version_info.clone())
+        .await;
+    let instance_storage = InstanceStorage::load(&config).await;
+    assert!(instance_storage.get_instance("Сборка 1").is_some());
+
+    let metadata = CompleteVersionMetadata::read_or_download(&version_info, launcher_dir, None)
+        .await
+        .unwrap();
+    let progress_bar: Arc<dyn ProgressBar<LangMessage> + Send + Sync> = Arc::new(NoProgressBar);
+    let plan = sync::plan_sync(
+        &metadata,
+        false,
+        launcher_dir,
+        &config.get_assets_dir(),
+        progress_bar.clone(),
+        HashLimits::default(),
+        &HashMap::new(),
+    )
+    .await
+    .unwrap();
+    let (delete_extra_sender, _delete_extra_receiver) = tokio::sync::mpsc::unbounded_channel();
+    sync::execute_sync(
+        &metadata,
+        plan,
+        launcher_dir,
+        progress_bar,
+        delete_extra_sender,
+        &DownloadHealth::new(),
+        SyncSettings::default(),
+    )
+    .await
+    .unwrap();
+
+    config.use_native_glfw.insert("Сборка 1".to_string(), false);
+    let auth_data = AuthData {
+        access_token: "token".to_string(),
+        refresh_token: None,
+        user_info: UserInfo {
+            uuid: "00000000-0000-0000-0000-000000000000".to_string(),
+            username: "Игрок".to_string(),
+        },
+        last_used: None,
+    };
+    let command = build_launch_command(&metadata, &config, &auth_data, false)
+        .await
+        .unwrap();
+    assert_eq!(command.java_path, launcher_dir.join("java/bin/java"));
+    let minecraft_dir = get_minecraft_dir(launcher_dir, "Сборка 1");
+    assert_eq!(command.current_dir, minecraft_dir);
+    let game_dir_index = command
+        .args
+        .iter()
+        .position(|arg| arg == "--gameDir")
+        .unwrap();
+    assert_eq!(
+        command.args[game_dir_index + 1],
+        minecraft_dir.to_str().unwrap()
+    );
+    assert!(command.args.contains(&"Игрок".to_string()));
+    let classpath = &command.args[command.args.iter().position(|arg| arg == "-cp").unwrap() + 1];
+    assert!(
+        classpath.contains(
+            get_client_jar_path(launcher_dir, "fixture")
+                .to_str()
+                .unwrap()
+        )
+    );
+}
@@ -153,7 +153,7 @@ done
 if [[ "$DO_BUILD" -eq 1 ]]; then
   log "Triggering remote build via docker exec in container: $CONTAINER"
   remote_exec=( "${ssh_base[@]}" "$REMOTE" )
-  docker_exec_cmd="docker exec ${CONTAINER} instance_builder -s ${CONTAINER_SPEC} ${CONTAINER_GENERATED} ${CONTAINER_WORKDIR}"
+  docker_exec_cmd="docker exec ${CONTAINER} instance_builder generate -s ${CONTAINER_SPEC} ${CONTAINER_GENERATED} ${CONTAINER_WORKDIR}"
   if [[ -n "$DOCKER_HOST_REMOTE" ]]; then
     remote_cmd="DOCKER_HOST=$(printf %q "$DOCKER_HOST_REMOTE") ${docker_exec_cmd}"
   else
@@ -0,0 +1,64 @@
+// compares the old 1 KB read buffer with the current one on a synthetic instance tree
+use std::path::{Path, PathBuf};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use shared::files::{hash_files_with_limits, HashLimits, HASH_BUFFER_SIZE};
+use shared::progress::no_progress_bar;
+
+const OLD_HASH_BUFFER_SIZE: usize = 1024;
+
+// many small configs, mid-sized mods and a few large jars, like a modpack
+fn create_tree(dir: &Path) -> (Vec<PathBuf>, u64) {
+    let mut paths = vec![];
+    let mut total = 0;
+    for (subdir, count, size) in [
+        ("config", 200, 4 * 1024),
+        ("mods", 60, 512 * 1024),
+        ("libraries", 4, 16 * 1024 * 1024),
+    ] {
+        std::fs::create_dir_all(dir.join(subdir)).unwrap();
+        for i in 0..count {
+            let path = dir.join(subdir).join(format!("file_{i}"));
+            let data: Vec<u8> = (0..size).map(|j| (i * 31 + j) as u8).collect();
+            std::fs::write(&path, data).unwrap();
+            paths.push(path);
+            total += size as u64;
+        }
+    }
+    (paths, total)
+}
+
+fn bench_hash_files(c: &mut Criterion) {
+    let dir = tempfile::tempdir().unwrap();
+    let (paths, total) = create_tree(dir.path());
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+
+    let mut group = c.benchmark_group("hash_files");
+    group.throughput(Throughput::Bytes(total));
+    group.sample_size(20);
+    for buffer_size in [OLD_HASH_BUFFER_SIZE, HASH_BUFFER_SIZE] {
+        let limits = HashLimits {
+            buffer_size,
+            ..HashLimits::default()
+        };
+        group.bench_with_input(
+            BenchmarkId::from_parameter(buffer_size),
+            &limits,
+            |b, limits| {
+                b.iter(|| {
+                    runtime
+                        .block_on(hash_files_with_limits(
+                            paths.clone(),
+                            no_progress_bar(),
+                            *limits,
+                        ))
+                        .unwrap()
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_hash_files);
+criterion_main!(benches);
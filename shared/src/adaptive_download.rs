@@ -1,12 +1,17 @@
 use futures::stream::{FuturesUnordered, StreamExt};
-use log::{debug, warn};
-use reqwest::Client;
-use std::collections::VecDeque;
+use log::{debug, info, warn};
+use reqwest::header::LOCATION;
+use reqwest::redirect::Policy;
+use reqwest::{Client, Response, StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
 use std::sync::{
     atomic::{AtomicUsize, Ordering},
     Arc,
 };
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::io::AsyncWriteExt;
 use tokio::sync::Mutex;
 
@@ -14,12 +19,26 @@ use crate::files::{self, DownloadEntry};
 use crate::progress::ProgressBar;
 use crate::utils::is_connect_error;
 
-const MAX_CONCURRENCY: usize = 50;
+// the ceiling unless the user sets a lower one, too many connections choke some routers
+pub const DEFAULT_MAX_CONNECTIONS: usize = 50;
 const MIN_CONCURRENCY: usize = 1;
+const INITIAL_CONCURRENCY: usize = 4;
 const WINDOW_DURATION: Duration = Duration::from_secs(2);
 const UPDATE_CONCURRENCY_EVERY: usize = 5;
 const REQUEST_TIMEOUT: Duration = Duration::from_secs(4);
 const MAX_TIMEOUTS_AT_MIN_CONCURRENCY: usize = 2;
+// a host is reported as unstable once enough of its requests fail
+const UNSTABLE_FAILURE_RATE: f64 = 0.1;
+const UNSTABLE_MIN_REQUESTS: u64 = 10;
+const MAX_REDIRECTS: usize = 10;
+// all probes of a ranking share it, so a dead mirror doesn't delay the sync
+const PROBE_BUDGET: Duration = Duration::from_secs(1);
+const PROBE_COUNT: usize = 3;
+pub const MIRROR_RANKING_TTL: Duration = Duration::from_secs(3 * 60 * 60);
+
+// servers that hand out presigned urls in a json body instead of a redirect set this
+// header on the json response, its value is the name of the field with the url
+pub const INDIRECTION_HEADER: &str = "X-Download-Url-Field";
 
 struct DownloadRecord {
     timestamp: Instant,
@@ -96,10 +115,286 @@ impl SlidingWindow {
     }
 }
 
-async fn download_file(client: &Client, entry: &DownloadEntry) -> anyhow::Result<u128> {
+#[derive(Clone, Default, Debug)]
+pub struct HostHealth {
+    pub host: String,
+    pub successes: u64,
+    pub failures: u64,
+    total_latency_ms: u128,
+    pub last_error: Option<String>,
+}
+
+impl HostHealth {
+    pub fn get_failure_rate(&self) -> f64 {
+        let requests = self.successes + self.failures;
+        if requests == 0 {
+            return 0.0;
+        }
+        self.failures as f64 / requests as f64
+    }
+
+    pub fn get_average_latency_ms(&self) -> Option<u64> {
+        (self.successes > 0).then(|| (self.total_latency_ms / self.successes as u128) as u64)
+    }
+
+    pub fn is_unstable(&self) -> bool {
+        self.successes + self.failures >= UNSTABLE_MIN_REQUESTS
+            && self.get_failure_rate() >= UNSTABLE_FAILURE_RATE
+    }
+}
+
+// per-host statistics of download requests, shared with whoever displays them
+#[derive(Clone, Default)]
+pub struct DownloadHealth {
+    hosts: Arc<std::sync::Mutex<HashMap<String, HostHealth>>>,
+    // the download server tried first, only set for downloads with mirrors
+    mirror: Arc<std::sync::Mutex<Option<String>>>,
+}
+
+impl DownloadHealth {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn reset(&self) {
+        self.hosts.lock().unwrap().clear();
+        *self.mirror.lock().unwrap() = None;
+    }
+
+    pub fn set_mirror(&self, mirror: Option<String>) {
+        *self.mirror.lock().unwrap() = mirror;
+    }
+
+    pub fn get_mirror(&self) -> Option<String> {
+        self.mirror.lock().unwrap().clone()
+    }
+
+    fn get_host(url: &str) -> String {
+        Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+            .unwrap_or_default()
+    }
+
+    fn update_host(&self, url: &str, update: impl FnOnce(&mut HostHealth)) {
+        let host = Self::get_host(url);
+        let mut hosts = self.hosts.lock().unwrap();
+        let stats = hosts.entry(host.clone()).or_insert_with(|| HostHealth {
+            host,
+            ..Default::default()
+        });
+        update(stats);
+    }
+
+    fn record_success(&self, url: &str, latency_ms: u128) {
+        self.update_host(url, |stats| {
+            stats.successes += 1;
+            stats.total_latency_ms += latency_ms;
+        });
+    }
+
+    fn record_failure(&self, url: &str, error: &anyhow::Error) {
+        self.update_host(url, |stats| {
+            stats.failures += 1;
+            stats.last_error = Some(error.to_string());
+        });
+    }
+
+    // the first url whose host isn't unstable, so a failing mirror is skipped by all files
+    fn pick_url<'a>(&self, urls: &'a [String]) -> &'a str {
+        let hosts = self.hosts.lock().unwrap();
+        urls.iter()
+            .find(|url| {
+                !hosts
+                    .get(&Self::get_host(url))
+                    .is_some_and(HostHealth::is_unstable)
+            })
+            .or(urls.first())
+            .map_or("", String::as_str)
+    }
+
+    // sorted by host
+    pub fn get_hosts(&self) -> Vec<HostHealth> {
+        let mut hosts: Vec<HostHealth> = self.hosts.lock().unwrap().values().cloned().collect();
+        hosts.sort_by(|a, b| a.host.cmp(&b.host));
+        hosts
+    }
+
+    // the host with the highest failure rate, if it crosses the threshold
+    pub fn get_unstable_host(&self) -> Option<HostHealth> {
+        self.get_hosts()
+            .into_iter()
+            .filter(HostHealth::is_unstable)
+            .max_by(|a, b| a.get_failure_rate().total_cmp(&b.get_failure_rate()))
+    }
+
+    pub fn log_summary(&self) {
+        for stats in self.get_hosts() {
+            info!(
+                "Download stats for {}: {} succeeded, {} failed ({:.0}%), average latency {}, last error: {}",
+                stats.host,
+                stats.successes,
+                stats.failures,
+                stats.get_failure_rate() * 100.0,
+                stats
+                    .get_average_latency_ms()
+                    .map(|latency| format!("{latency} ms"))
+                    .unwrap_or_else(|| "unknown".to_string()),
+                stats.last_error.as_deref().unwrap_or("none"),
+            );
+        }
+    }
+}
+
+// follows redirects and indirections by hand, so every attempt starts from the original url
+// and never reuses a presigned url that may have expired. Returns whether the url changed
+async fn send_request(client: &Client, url: &str) -> anyhow::Result<(Response, bool)> {
+    let mut current_url = Url::parse(url)?;
+    let mut redirected = false;
+    for _ in 0..MAX_REDIRECTS {
+        let response = client.get(current_url.clone()).send().await?;
+        if response.status().is_redirection() {
+            let location = response
+                .headers()
+                .get(LOCATION)
+                .and_then(|location| location.to_str().ok())
+                .ok_or_else(|| AdaptiveDownloadError::InvalidRedirect(url.to_string()))?;
+            current_url = current_url.join(location)?;
+            redirected = true;
+            continue;
+        }
+        let url_field = response
+            .headers()
+            .get(INDIRECTION_HEADER)
+            .and_then(|field| field.to_str().ok())
+            .filter(|_| response.status().is_success())
+            .map(str::to_string);
+        if let Some(url_field) = url_field {
+            let body: Value = response.json().await?;
+            let location = body
+                .get(&url_field)
+                .and_then(Value::as_str)
+                .ok_or_else(|| AdaptiveDownloadError::InvalidRedirect(url.to_string()))?;
+            current_url = current_url.join(location)?;
+            redirected = true;
+            continue;
+        }
+        return Ok((response, redirected));
+    }
+    Err(AdaptiveDownloadError::TooManyRedirects(url.to_string()).into())
+}
+
+fn get_unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_secs())
+        .unwrap_or(0)
+}
+
+// download servers ordered by how fast and reliably they answered the probes
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct MirrorRanking {
+    pub bases: Vec<String>,
+    // unix seconds
+    pub ranked_at: u64,
+}
+
+impl MirrorRanking {
+    pub fn get_best(&self) -> Option<&str> {
+        self.bases.first().map(String::as_str)
+    }
+
+    // a ranking of the same servers that isn't older than MIRROR_RANKING_TTL
+    pub fn is_fresh_for(&self, bases: &[String]) -> bool {
+        let mut ranked = self.bases.clone();
+        ranked.sort();
+        let mut bases = bases.to_vec();
+        bases.sort();
+        ranked == bases
+            && get_unix_time().saturating_sub(self.ranked_at) < MIRROR_RANKING_TTL.as_secs()
+    }
+}
+
+#[derive(Default)]
+struct ProbeStats {
+    successes: u32,
+    failures: u32,
+    total_latency: Duration,
+}
+
+impl ProbeStats {
+    // the average latency divided by the success rate, so a server failing half of the probes
+    // ranks like one twice as slow. Servers that never answered are last
+    fn get_score(&self) -> f64 {
+        if self.successes == 0 {
+            return f64::INFINITY;
+        }
+        let average_latency = self.total_latency.as_secs_f64() / self.successes as f64;
+        let attempts = self.successes + self.failures;
+        average_latency * attempts as f64 / self.successes as f64
+    }
+}
+
+async fn probe_base(client: &Client, base: &str, deadline: tokio::time::Instant) -> ProbeStats {
+    let mut stats = ProbeStats::default();
+    for _ in 0..PROBE_COUNT {
+        let start = Instant::now();
+        // any response counts, the base itself doesn't have to be a file
+        match tokio::time::timeout_at(deadline, client.head(base).send()).await {
+            Ok(Ok(_)) => {
+                stats.successes += 1;
+                stats.total_latency += start.elapsed();
+            }
+            Ok(Err(e)) => {
+                debug!("Failed to probe {base}: {e}");
+                stats.failures += 1;
+            }
+            // out of budget, a server without a single answer by now is ranked last anyway
+            Err(_) => break,
+        }
+    }
+    stats
+}
+
+// probes every base a few times within PROBE_BUDGET, the ranking keeps the order of ties
+pub async fn rank_mirrors(bases: Vec<String>) -> MirrorRanking {
+    let ranked_at = get_unix_time();
+    if bases.len() < 2 {
+        return MirrorRanking { bases, ranked_at };
+    }
+    let client = Client::builder()
+        .redirect(Policy::none())
+        .build()
+        .unwrap_or_default();
+    let deadline = tokio::time::Instant::now() + PROBE_BUDGET;
+    let stats =
+        futures::future::join_all(bases.iter().map(|base| probe_base(&client, base, deadline)))
+            .await;
+
+    let mut bases: Vec<_> = bases
+        .into_iter()
+        .zip(stats.iter().map(ProbeStats::get_score))
+        .collect();
+    // stable, so the unreachable ones keep their order
+    bases.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+    MirrorRanking {
+        bases: bases.into_iter().map(|(base, _)| base).collect(),
+        ranked_at,
+    }
+}
+
+async fn download_file(client: &Client, url: &str, entry: &DownloadEntry) -> anyhow::Result<u128> {
     let start = Instant::now();
 
-    let response = client.get(&entry.url).send().await?.error_for_status()?;
+    let (response, redirected) = send_request(client, url).await?;
+    // the presigned url expired between signing and the request, get a fresh one
+    let response = if redirected && response.status() == StatusCode::FORBIDDEN {
+        debug!("Redirect of {url} was rejected, retrying");
+        send_request(client, url).await?.0
+    } else {
+        response
+    };
+    let response = response.error_for_status()?;
     let mut stream = response.bytes_stream();
 
     if let Some(parent_dir) = entry.path.parent() {
@@ -160,21 +455,28 @@ fn is_timeout_error(e: &anyhow::Error) -> bool {
 /// Download a single file, returning (success, latency_ms).
 /// On success, we return Ok(Some(latency_ms)).
 /// If it's a timeout, we return Ok(None). If it's another error, we return Err(e).
-async fn do_download(client: &Client, entry: &DownloadEntry) -> anyhow::Result<Option<u128>> {
-    let latency_ms = match download_file(client, entry).await {
+async fn do_download(
+    client: &Client,
+    entry: &DownloadEntry,
+    health: &DownloadHealth,
+) -> anyhow::Result<Option<u128>> {
+    let url = health.pick_url(&entry.urls);
+    let latency_ms = match download_file(client, url, entry).await {
         Ok(r) => r,
         Err(e) => {
+            health.record_failure(url, &e);
             // If it's a timeout, we return Ok(None), else Err
             if is_timeout_error(&e) || is_connect_error(&e) {
-                debug!("Timeout downloading {}", entry.url);
+                debug!("Timeout downloading {url}");
                 return Ok(None);
             } else {
-                debug!("Error downloading {}: {:?}", entry.url, e);
+                debug!("Error downloading {url}: {e:?}");
                 return Err(e);
             }
         }
     };
 
+    health.record_success(url, latency_ms);
     Ok(Some(latency_ms))
 }
 
@@ -182,17 +484,72 @@ async fn do_download(client: &Client, entry: &DownloadEntry) -> anyhow::Result<O
 pub enum AdaptiveDownloadError {
     #[error("Connection timed out")]
     ConnectionTimeout,
+    #[error("Invalid redirect for {0}")]
+    InvalidRedirect(String),
+    #[error("Too many redirects for {0}")]
+    TooManyRedirects(String),
 }
 
 pub async fn download_files<M>(
     download_entries: Vec<DownloadEntry>,
     progress_bar: Arc<dyn ProgressBar<M> + Send + Sync>,
 ) -> anyhow::Result<()> {
-    progress_bar.set_length(download_entries.len() as u64);
+    download_files_with_max_connections(download_entries, progress_bar, DEFAULT_MAX_CONNECTIONS)
+        .await
+}
 
-    let client = Client::builder().connect_timeout(REQUEST_TIMEOUT).build()?;
+pub async fn download_files_with_max_connections<M>(
+    download_entries: Vec<DownloadEntry>,
+    progress_bar: Arc<dyn ProgressBar<M> + Send + Sync>,
+    max_connections: usize,
+) -> anyhow::Result<()> {
+    download_files_with_health(
+        download_entries,
+        progress_bar,
+        &DownloadHealth::new(),
+        max_connections,
+    )
+    .await
+}
 
-    let desired_concurrency = Arc::new(AtomicUsize::new(4));
+pub async fn download_files_with_health<M>(
+    download_entries: Vec<DownloadEntry>,
+    progress_bar: Arc<dyn ProgressBar<M> + Send + Sync>,
+    health: &DownloadHealth,
+    max_connections: usize,
+) -> anyhow::Result<()> {
+    let client = Client::builder()
+        .connect_timeout(REQUEST_TIMEOUT)
+        .redirect(Policy::none())
+        .build()?;
+
+    run_downloads(
+        download_entries,
+        progress_bar,
+        max_connections,
+        |entry| async {
+            let result = do_download(&client, &entry, health).await;
+            (result, entry)
+        },
+    )
+    .await
+}
+
+// the concurrency adapts to the timeouts, but never goes above max_connections
+async fn run_downloads<M, F, Fut>(
+    download_entries: Vec<DownloadEntry>,
+    progress_bar: Arc<dyn ProgressBar<M> + Send + Sync>,
+    max_connections: usize,
+    download: F,
+) -> anyhow::Result<()>
+where
+    F: Fn(DownloadEntry) -> Fut,
+    Fut: Future<Output = (anyhow::Result<Option<u128>>, DownloadEntry)>,
+{
+    progress_bar.set_length(download_entries.len() as u64);
+
+    let max_connections = max_connections.max(MIN_CONCURRENCY);
+    let desired_concurrency = Arc::new(AtomicUsize::new(INITIAL_CONCURRENCY.min(max_connections)));
 
     let sliding_window = Arc::new(Mutex::new(SlidingWindow::new()));
 
@@ -206,11 +563,7 @@ pub async fn download_files<M>(
     let spawn_if_possible = |active: &mut FuturesUnordered<_>, cur_entries: &mut Vec<_>| {
         while can_spawn_more(active.len(), &desired_concurrency) {
             if let Some(entry) = cur_entries.pop() {
-                let fut = async {
-                    let result = do_download(&client, &entry).await;
-                    (result, entry)
-                };
-                active.push(fut);
+                active.push(download(entry));
             } else {
                 break;
             }
@@ -223,7 +576,7 @@ pub async fn download_files<M>(
 
     let mut next_concurrency_update = UPDATE_CONCURRENCY_EVERY;
     loop {
-        let Some((result, entry)) = active.next().await else {
+        let Some((result, mut entry)) = active.next().await else {
             break;
         };
 
@@ -233,6 +586,8 @@ pub async fn download_files<M>(
                 (true, latency_ms)
             }
             Ok(None) => {
+                // the next attempt starts from the next mirror
+                entry.urls.rotate_left(1);
                 cur_entries.push(entry);
                 (false, 0)
             }
@@ -253,7 +608,7 @@ pub async fn download_files<M>(
             let mut new_value = current;
             if success {
                 if success_rate > 0.9 && avg_latency < 2000.0 {
-                    new_value = (current + 1).min(MAX_CONCURRENCY);
+                    new_value = (current + 1).min(max_connections);
                 }
             } else {
                 if current == MIN_CONCURRENCY {
@@ -280,3 +635,113 @@ pub async fn download_files<M>(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::progress::no_progress_bar;
+
+    #[tokio::test]
+    async fn test_max_connections() {
+        for max_connections in [1, 3, 8] {
+            // counts the downloads holding a connection, like a semaphore would
+            let in_flight = AtomicUsize::new(0);
+            let max_in_flight = AtomicUsize::new(0);
+            let entries = (0..100)
+                .map(|i| DownloadEntry {
+                    urls: vec![format!("https://example.com/{i}.jar")],
+                    path: PathBuf::from(format!("{i}.jar")),
+                    size: None,
+                    sha1: None,
+                })
+                .collect();
+            run_downloads(entries, no_progress_bar(), max_connections, |entry| async {
+                let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                max_in_flight.fetch_max(current, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(2)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                (Ok(Some(2)), entry)
+            })
+            .await
+            .unwrap();
+            // ramps up to the ceiling with fast successful downloads
+            assert_eq!(max_in_flight.load(Ordering::SeqCst), max_connections);
+        }
+    }
+
+    #[test]
+    fn test_probe_score() {
+        let fast = ProbeStats {
+            successes: 3,
+            failures: 0,
+            total_latency: Duration::from_millis(150),
+        };
+        let flaky = ProbeStats {
+            successes: 1,
+            failures: 2,
+            total_latency: Duration::from_millis(20),
+        };
+        let dead = ProbeStats {
+            successes: 0,
+            failures: 3,
+            total_latency: Duration::ZERO,
+        };
+        // 50 ms vs 20 ms failing two thirds of the probes
+        assert!(fast.get_score() < flaky.get_score());
+        assert!(flaky.get_score() < dead.get_score());
+    }
+
+    #[test]
+    fn test_mirror_ranking_freshness() {
+        let ranking = MirrorRanking {
+            bases: vec![
+                "https://b.example.com".to_string(),
+                "https://a.example.com".to_string(),
+            ],
+            ranked_at: get_unix_time(),
+        };
+        assert!(ranking.is_fresh_for(&[
+            "https://a.example.com".to_string(),
+            "https://b.example.com".to_string()
+        ]));
+        assert!(!ranking.is_fresh_for(&["https://a.example.com".to_string()]));
+
+        let outdated = MirrorRanking {
+            ranked_at: ranking.ranked_at - MIRROR_RANKING_TTL.as_secs(),
+            ..ranking
+        };
+        assert!(!outdated.is_fresh_for(&outdated.bases));
+    }
+
+    #[test]
+    fn test_unstable_host() {
+        let health = DownloadHealth::new();
+        let error = anyhow::anyhow!("connection reset");
+        for _ in 0..8 {
+            health.record_success("https://stable.example.com/a.jar", 100);
+            health.record_success("https://flaky.example.com/a.jar", 300);
+        }
+        health.record_failure("https://stable.example.com/a.jar", &error);
+        // not enough requests yet
+        health.record_failure("https://flaky.example.com/a.jar", &error);
+        assert!(health.get_unstable_host().is_none());
+
+        health.record_failure("https://flaky.example.com/b.jar", &error);
+        let unstable = health.get_unstable_host().unwrap();
+        assert_eq!(unstable.host, "flaky.example.com");
+        assert_eq!(unstable.get_average_latency_ms(), Some(300));
+        assert_eq!(unstable.last_error.as_deref(), Some("connection reset"));
+
+        let urls = [
+            "https://flaky.example.com/a.jar".to_string(),
+            "https://stable.example.com/a.jar".to_string(),
+        ];
+        assert_eq!(health.pick_url(&urls), "https://stable.example.com/a.jar");
+
+        health.reset();
+        assert!(health.get_hosts().is_empty());
+        assert_eq!(health.pick_url(&urls), "https://flaky.example.com/a.jar");
+    }
+}
@@ -0,0 +1,107 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::compression;
+use crate::files::{get_files_in_dir, hash_files};
+use crate::json;
+use crate::progress::no_progress_bar;
+use crate::utils::path_to_slash_str;
+
+pub const CHECKSUMS_FILENAME: &str = "checksums.json";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FileChecksum {
+    pub sha1: String,
+    pub size: u64,
+}
+
+// every published file of the output dir, keyed by the path relative to it
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq)]
+pub struct Checksums {
+    pub files: BTreeMap<String, FileChecksum>,
+}
+
+impl Checksums {
+    pub async fn from_dir(dir: &Path) -> anyhow::Result<Self> {
+        let checksums_path = dir.join(CHECKSUMS_FILENAME);
+        let mut paths = get_files_in_dir(dir)?;
+        paths.retain(|path| path != &checksums_path);
+        let hashes = hash_files(paths.clone(), no_progress_bar()).await?;
+
+        let mut files = BTreeMap::new();
+        for (path, sha1) in paths.into_iter().zip(hashes) {
+            let size = tokio::fs::metadata(&path).await?.len();
+            files.insert(
+                path_to_slash_str(path.strip_prefix(dir)?)?,
+                FileChecksum { sha1, size },
+            );
+        }
+        Ok(Self { files })
+    }
+
+    pub async fn read_local(path: &Path) -> anyhow::Result<Self> {
+        let data = tokio::fs::read(path).await?;
+        Ok(json::from_slice(&data, &path.display().to_string())?)
+    }
+
+    pub async fn save(&self, path: &Path) -> anyhow::Result<()> {
+        tokio::fs::write(path, serde_json::to_string(self)?).await?;
+        Ok(())
+    }
+
+    pub async fn fetch(url: &str) -> anyhow::Result<Self> {
+        let data = compression::fetch_bytes(&Client::new(), url).await?;
+        Ok(json::from_slice(&data, url)?)
+    }
+
+    // the size of a file only depends on its content, so it's known for every listed hash
+    pub fn get_sizes_by_sha1(&self) -> HashMap<&str, u64> {
+        self.files
+            .values()
+            .map(|checksum| (checksum.sha1.as_str(), checksum.size))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_checksums_from_dir() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        std::fs::create_dir_all(dir.join("mods")).unwrap();
+        std::fs::write(dir.join("mods/a.jar"), "hello").unwrap();
+        std::fs::write(dir.join("version_manifest.json"), "{}").unwrap();
+        // the previous checksums are not listed in the new ones
+        std::fs::write(dir.join(CHECKSUMS_FILENAME), "{}").unwrap();
+
+        let checksums = Checksums::from_dir(dir).await.unwrap();
+        assert_eq!(
+            checksums.files.keys().collect::<Vec<_>>(),
+            vec!["mods/a.jar", "version_manifest.json"]
+        );
+        assert_eq!(
+            checksums.files["mods/a.jar"],
+            FileChecksum {
+                sha1: "aaf4c61ddcc5e8a2dabede0f3b482cd9aea9434d".to_string(),
+                size: 5,
+            }
+        );
+        assert_eq!(
+            checksums.get_sizes_by_sha1()["aaf4c61ddcc5e8a2dabede0f3b482cd9aea9434d"],
+            5
+        );
+
+        let checksums_path = dir.join(CHECKSUMS_FILENAME);
+        checksums.save(&checksums_path).await.unwrap();
+        assert_eq!(
+            Checksums::read_local(&checksums_path).await.unwrap(),
+            checksums
+        );
+    }
+}
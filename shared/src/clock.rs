@@ -0,0 +1,51 @@
+use std::sync::OnceLock;
+use std::time::SystemTime;
+
+// server time minus local time in seconds, taken from the first response with a Date header
+static CLOCK_SKEW: OnceLock<i64> = OnceLock::new();
+
+fn get_skew_secs(server_time: SystemTime, local_time: SystemTime) -> i64 {
+    match server_time.duration_since(local_time) {
+        Ok(ahead) => ahead.as_secs() as i64,
+        Err(e) => -(e.duration().as_secs() as i64),
+    }
+}
+
+pub fn record_server_date(response: &reqwest::Response) {
+    if CLOCK_SKEW.get().is_some() {
+        return;
+    }
+    let Some(server_time) = response
+        .headers()
+        .get(reqwest::header::DATE)
+        .and_then(|date| date.to_str().ok())
+        .and_then(|date| httpdate::parse_http_date(date).ok())
+    else {
+        return;
+    };
+    let _ = CLOCK_SKEW.set(get_skew_secs(server_time, SystemTime::now()));
+}
+
+// positive if the local clock is behind
+pub fn get_clock_skew() -> Option<i64> {
+    CLOCK_SKEW.get().copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_get_skew_secs() {
+        let server_time = httpdate::parse_http_date("Fri, 16 Oct 2026 12:00:00 GMT").unwrap();
+        assert_eq!(
+            get_skew_secs(server_time, server_time - Duration::from_secs(600)),
+            600
+        );
+        assert_eq!(
+            get_skew_secs(server_time, server_time + Duration::from_secs(90)),
+            -90
+        );
+    }
+}
@@ -0,0 +1,81 @@
+use std::path::{Path, PathBuf};
+
+use log::warn;
+use reqwest::header::{ACCEPT_ENCODING, CONTENT_ENCODING};
+use reqwest::Client;
+
+use crate::clock;
+
+// gzip is decoded by reqwest itself
+const ACCEPT_ENCODINGS: &str = "zstd, gzip";
+const ZSTD_LEVEL: i32 = 19;
+
+pub fn get_zstd_url(url: &str) -> String {
+    format!("{url}.zst")
+}
+
+pub fn get_zstd_path(path: &Path) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(".zst");
+    PathBuf::from(path)
+}
+
+pub fn compress_zstd(data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    Ok(zstd::encode_all(data, ZSTD_LEVEL)?)
+}
+
+pub fn decompress_zstd(data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    Ok(zstd::decode_all(data)?)
+}
+
+// a body the server failed to compress correctly is fetched again uncompressed
+pub async fn fetch_bytes(client: &Client, url: &str) -> anyhow::Result<Vec<u8>> {
+    let response = client
+        .get(url)
+        .header(ACCEPT_ENCODING, ACCEPT_ENCODINGS)
+        .send()
+        .await?
+        .error_for_status()?;
+    clock::record_server_date(&response);
+    let zstd_encoded = response
+        .headers()
+        .get(CONTENT_ENCODING)
+        .is_some_and(|encoding| encoding == "zstd");
+    let data = response.bytes().await?;
+    if !zstd_encoded {
+        return Ok(data.to_vec());
+    }
+    match decompress_zstd(&data) {
+        Ok(data) => Ok(data),
+        Err(e) => {
+            warn!("Failed to decode the response of {url}, fetching it uncompressed:\n{e:?}");
+            let response = client
+                .get(url)
+                .header(ACCEPT_ENCODING, "identity")
+                .send()
+                .await?
+                .error_for_status()?;
+            Ok(response.bytes().await?.to_vec())
+        }
+    }
+}
+
+// the .zst file written next to the original one, None if the server doesn't have it
+pub async fn fetch_zstd_variant(client: &Client, url: &str) -> Option<Vec<u8>> {
+    let zstd_url = get_zstd_url(url);
+    let head_response = client.head(&zstd_url).send().await.ok()?;
+    if !head_response.status().is_success() {
+        return None;
+    }
+    let response = client.get(&zstd_url).send().await.ok()?;
+    let response = response.error_for_status().ok()?;
+    clock::record_server_date(&response);
+    let data = response.bytes().await.ok()?;
+    match decompress_zstd(&data) {
+        Ok(data) => Some(data),
+        Err(e) => {
+            warn!("Failed to decode {zstd_url}:\n{e:?}");
+            None
+        }
+    }
+}
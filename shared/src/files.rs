@@ -1,4 +1,5 @@
-use futures::stream::{FuturesUnordered, StreamExt};
+use futures::stream::{self, StreamExt};
+use log::debug;
 use sha1::{Digest, Sha1};
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
@@ -8,6 +9,7 @@ use tokio::io::AsyncReadExt as _;
 use tokio::{fs, io};
 use walkdir::WalkDir;
 
+use crate::hash_cache::HashCache;
 use crate::progress::{run_tasks_with_progress, ProgressBar};
 
 pub fn get_files_in_dir(path: &Path) -> anyhow::Result<Vec<PathBuf>> {
@@ -42,10 +44,31 @@ pub fn get_files_ignore_paths(
     Ok(files)
 }
 
-pub async fn hash_file(path: &Path) -> anyhow::Result<String> {
+// large enough to keep the syscall overhead low, every concurrent hashing task has its own
+pub const HASH_BUFFER_SIZE: usize = 128 * 1024;
+
+// memory used by hashing is about concurrency * buffer_size
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct HashLimits {
+    pub concurrency: usize,
+    pub buffer_size: usize,
+}
+
+impl Default for HashLimits {
+    fn default() -> Self {
+        Self {
+            concurrency: num_cpus::get(),
+            buffer_size: HASH_BUFFER_SIZE,
+        }
+    }
+}
+
+// returns the hash and the number of bytes read
+async fn hash_file_counting(path: &Path, buffer_size: usize) -> anyhow::Result<(String, u64)> {
     let mut file = fs::File::open(path).await?;
     let mut hasher = Sha1::new();
-    let mut buffer = [0; 1024];
+    let mut buffer = vec![0; buffer_size.max(1)];
+    let mut total = 0;
 
     loop {
         let n = file.read(&mut buffer).await?;
@@ -53,22 +76,45 @@ pub async fn hash_file(path: &Path) -> anyhow::Result<String> {
             break;
         }
         hasher.update(&buffer[..n]);
+        total += n as u64;
     }
 
-    Ok(format!("{:x}", hasher.finalize()))
+    Ok((format!("{:x}", hasher.finalize()), total))
+}
+
+pub async fn hash_file_with_buffer(path: &Path, buffer_size: usize) -> anyhow::Result<String> {
+    Ok(hash_file_counting(path, buffer_size).await?.0)
+}
+
+pub async fn hash_file(path: &Path) -> anyhow::Result<String> {
+    hash_file_with_buffer(path, HASH_BUFFER_SIZE).await
 }
 
 pub async fn hash_files<M>(
     files: Vec<PathBuf>,
     progress_bar: Arc<dyn ProgressBar<M> + Send + Sync>,
+) -> anyhow::Result<Vec<String>> {
+    hash_files_with_limits(files, progress_bar, HashLimits::default()).await
+}
+
+pub async fn hash_files_with_limits<M>(
+    files: Vec<PathBuf>,
+    progress_bar: Arc<dyn ProgressBar<M> + Send + Sync>,
+    limits: HashLimits,
 ) -> anyhow::Result<Vec<String>> {
     let tasks_count = files.len() as u64;
 
-    let tasks = files
-        .into_iter()
-        .map(|path| async move { hash_file(&path).await });
+    let bytes_progress_bar = progress_bar.clone();
+    let tasks = files.into_iter().map(move |path| {
+        let progress_bar = bytes_progress_bar.clone();
+        async move {
+            let (hash, bytes) = hash_file_counting(&path, limits.buffer_size).await?;
+            progress_bar.inc_bytes(bytes);
+            Ok(hash)
+        }
+    });
 
-    run_tasks_with_progress(tasks, progress_bar, tasks_count, num_cpus::get()).await
+    run_tasks_with_progress(tasks, progress_bar, tasks_count, limits.concurrency).await
 }
 
 pub async fn remove_file_or_dir(path: &Path) -> anyhow::Result<()> {
@@ -82,14 +128,18 @@ pub async fn remove_file_or_dir(path: &Path) -> anyhow::Result<()> {
 
 #[derive(Debug)]
 pub struct DownloadEntry {
-    pub url: String,
+    // mirrors of the same file after the first url
+    pub urls: Vec<String>,
     pub path: PathBuf,
+    pub size: Option<u64>,
+    pub sha1: Option<String>,
 }
 
 #[derive(Debug)]
 pub struct CheckEntry {
-    pub url: String,
+    pub urls: Vec<String>,
     pub remote_sha1: Option<String>,
+    pub remote_size: Option<u64>,
     pub path: PathBuf,
 }
 
@@ -99,31 +149,80 @@ pub enum CheckDownloadError {
     HashMissing(PathBuf),
 }
 
+// a file with a different size can't have the same hash, no need to read it
+fn is_size_mismatch(entry: &CheckEntry) -> bool {
+    entry.remote_size.is_some_and(|remote_size| {
+        std::fs::metadata(&entry.path).map_or(true, |metadata| metadata.len() != remote_size)
+    })
+}
+
+fn get_paths_to_hash(check_entries: &[CheckEntry]) -> Vec<PathBuf> {
+    check_entries
+        .iter()
+        .filter(|entry| {
+            entry.path.is_file() && entry.remote_sha1.is_some() && !is_size_mismatch(entry)
+        })
+        .map(|entry| entry.path.clone())
+        .collect()
+}
+
 pub async fn get_download_entries<M>(
     check_entries: Vec<CheckEntry>,
     progress_bar: Arc<dyn ProgressBar<M> + Send + Sync>,
 ) -> anyhow::Result<Vec<DownloadEntry>> {
-    let to_hash: Vec<_> = check_entries
-        .iter()
-        .filter_map(|entry| {
-            if entry.path.is_file() && entry.remote_sha1.is_some() {
-                Some(entry.path.clone())
-            } else {
-                None
-            }
-        })
-        .collect();
+    get_download_entries_with_limits(check_entries, progress_bar, HashLimits::default()).await
+}
 
-    let hashes = hash_files(to_hash.clone(), progress_bar.clone()).await?;
-    let hashes = to_hash
-        .into_iter()
-        .zip(hashes.into_iter())
-        .collect::<HashMap<_, _>>();
+pub async fn get_download_entries_with_limits<M>(
+    check_entries: Vec<CheckEntry>,
+    progress_bar: Arc<dyn ProgressBar<M> + Send + Sync>,
+    hash_limits: HashLimits,
+) -> anyhow::Result<Vec<DownloadEntry>> {
+    get_download_entries_with_cache(
+        check_entries,
+        progress_bar,
+        hash_limits,
+        &mut HashCache::default(),
+        false,
+    )
+    .await
+}
+
+// files with the size and mtime from the cache aren't hashed again unless force_hash is set
+pub async fn get_download_entries_with_cache<M>(
+    check_entries: Vec<CheckEntry>,
+    progress_bar: Arc<dyn ProgressBar<M> + Send + Sync>,
+    hash_limits: HashLimits,
+    hash_cache: &mut HashCache,
+    force_hash: bool,
+) -> anyhow::Result<Vec<DownloadEntry>> {
+    let mut hashes = HashMap::new();
+    let mut to_hash = vec![];
+    for path in get_paths_to_hash(&check_entries) {
+        match hash_cache.get(&path).filter(|_| !force_hash) {
+            Some(sha1) => {
+                hashes.insert(path, sha1.to_string());
+            }
+            None => to_hash.push(path),
+        }
+    }
+    let cached_count = hashes.len();
+
+    let new_hashes =
+        hash_files_with_limits(to_hash.clone(), progress_bar.clone(), hash_limits).await?;
+    debug!(
+        "Hashed {} files, skipped {cached_count} with unchanged size and mtime",
+        to_hash.len()
+    );
+    for (path, sha1) in to_hash.into_iter().zip(new_hashes) {
+        hash_cache.insert(path.clone(), sha1.clone());
+        hashes.insert(path, sha1);
+    }
 
     let mut download_entries = HashMap::new();
     for entry in check_entries {
         let mut need_download = false;
-        if !entry.path.is_file() {
+        if !entry.path.is_file() || is_size_mismatch(&entry) {
             need_download = true;
         } else if let Some(remote_sha1) = &entry.remote_sha1 {
             if remote_sha1
@@ -136,11 +235,14 @@ pub async fn get_download_entries<M>(
         }
 
         if need_download {
+            hash_cache.invalidate(&entry.path);
             download_entries.insert(
                 entry.path.clone(),
                 DownloadEntry {
-                    url: entry.url.clone(),
+                    urls: entry.urls.clone(),
                     path: entry.path.clone(),
+                    size: entry.remote_size,
+                    sha1: entry.remote_sha1.clone(),
                 },
             );
         }
@@ -149,7 +251,7 @@ pub async fn get_download_entries<M>(
     Ok(download_entries.into_values().collect())
 }
 
-async fn remove_empty_dirs(path: &Path) -> anyhow::Result<()> {
+pub async fn remove_empty_dirs(path: &Path) -> anyhow::Result<()> {
     let root = path;
 
     for entry in WalkDir::new(path)
@@ -184,20 +286,51 @@ pub enum CopyFilesError {
     InvalidPath,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Default)]
 pub struct SyncMappingStats {
     pub total_files: usize,
     pub copied_files: usize,
+    pub copied_bytes: u64,
     pub deleted_files: usize,
 }
 
-// copy mapped files and directories
-// and delete all other files and directores in the target directory
-// mapping: target -> source
-pub async fn sync_mapping(
+// a mapped file that is missing or differs in the target directory
+#[derive(Debug, Clone)]
+pub struct SyncCopy {
+    pub target: PathBuf,
+    pub source: PathBuf,
+    pub size: u64,
+    // None if the target doesn't exist yet
+    pub target_size: Option<u64>,
+}
+
+#[derive(Debug, Default)]
+pub struct SyncMappingPlan {
+    pub total_files: usize,
+    pub to_copy: Vec<SyncCopy>,
+    pub to_delete: Vec<PathBuf>,
+    pub deleted_bytes: u64,
+}
+
+impl SyncMappingPlan {
+    // how much the target directory grows, negative if it shrinks
+    pub fn get_bytes_delta(&self) -> i64 {
+        let copied: i64 = self
+            .to_copy
+            .iter()
+            .map(|copy| copy.size as i64 - copy.target_size.unwrap_or(0) as i64)
+            .sum();
+        copied - self.deleted_bytes as i64
+    }
+}
+
+const MAX_CONCURRENT_FILE_OPERATIONS: usize = 50;
+
+// expand mapped directories into target file -> source file
+fn get_mapping_files(
     target_dir: &Path,
     mapping: &HashMap<PathBuf, PathBuf>,
-) -> anyhow::Result<SyncMappingStats> {
+) -> anyhow::Result<HashMap<PathBuf, PathBuf>> {
     let mut mappings_files = HashMap::new();
     for (target, source) in mapping {
         if !target.starts_with(target_dir) {
@@ -216,76 +349,123 @@ pub async fn sync_mapping(
             return Err(CopyFilesError::SourceEntryMissing(source.clone()).into());
         }
     }
+    Ok(mappings_files)
+}
 
-    let mut deleted_files: usize = 0;
-    let paths = get_files_in_dir(target_dir)?;
-    for path in paths {
-        if !mappings_files.contains_key(&path) {
-            fs::remove_file(&path).await?;
-            deleted_files += 1;
-        }
-    }
+async fn is_copy_needed(target: &Path, source: &Path) -> anyhow::Result<bool> {
+    Ok(!target.is_file() || hash_file(source).await? != hash_file(target).await?)
+}
 
-    remove_empty_dirs(target_dir).await?;
+async fn plan_copy(target: PathBuf, source: PathBuf) -> anyhow::Result<Option<SyncCopy>> {
+    if !is_copy_needed(&target, &source).await? {
+        return Ok(None);
+    }
+    let target_size = if target.is_file() {
+        Some(fs::metadata(&target).await?.len())
+    } else {
+        None
+    };
+    Ok(Some(SyncCopy {
+        size: fs::metadata(&source).await?.len(),
+        target,
+        source,
+        target_size,
+    }))
+}
 
-    async fn copy_file_if_needed(target: PathBuf, source: PathBuf) -> anyhow::Result<bool> {
-        fs::create_dir_all(target.parent().ok_or(CopyFilesError::InvalidPath)?).await?;
-        if target.is_dir() {
-            fs::remove_dir(&target).await?;
-        }
-        let should_copy =
-            !target.exists() || hash_file(&source).await? != hash_file(&target).await?;
-        if should_copy {
-            // copy and let umask set the permissions instead of fs::copy
-            let mut src = File::open(&source).await?;
-            let mut dst = File::create(&target).await?;
-            io::copy(&mut src, &mut dst).await?;
+// what sync_mapping would do, without modifying the target directory
+// mapping: target -> source
+pub async fn plan_sync_mapping(
+    target_dir: &Path,
+    mapping: &HashMap<PathBuf, PathBuf>,
+) -> anyhow::Result<SyncMappingPlan> {
+    let mappings_files = get_mapping_files(target_dir, mapping)?;
+
+    let mut plan = SyncMappingPlan {
+        total_files: mappings_files.len(),
+        ..Default::default()
+    };
+    if target_dir.is_dir() {
+        for path in get_files_in_dir(target_dir)? {
+            if !mappings_files.contains_key(&path) {
+                plan.deleted_bytes += fs::metadata(&path).await?.len();
+                plan.to_delete.push(path);
+            }
         }
-        Ok(should_copy)
     }
 
-    const MAX_CONCURRENT_FILE_OPERATIONS: usize = 50;
-
-    let total_files = mappings_files.len();
-    let mut copied_files: usize = 0;
+    let mut copies = stream::iter(mappings_files)
+        .map(|(target, source)| plan_copy(target, source))
+        .buffer_unordered(MAX_CONCURRENT_FILE_OPERATIONS);
+    while let Some(copy) = copies.next().await {
+        plan.to_copy.extend(copy?);
+    }
 
-    let mut tasks = FuturesUnordered::new();
-    let mut mapping_iter = mappings_files.iter();
+    plan.to_copy.sort_by(|a, b| a.target.cmp(&b.target));
+    plan.to_delete.sort();
+    Ok(plan)
+}
 
-    for _ in 0..MAX_CONCURRENT_FILE_OPERATIONS.min(mappings_files.len()) {
-        if let Some((target, source)) = mapping_iter.next() {
-            tasks.push(copy_file_if_needed(target.clone(), source.clone()));
-        }
+async fn apply_copy(copy: &SyncCopy) -> anyhow::Result<u64> {
+    fs::create_dir_all(copy.target.parent().ok_or(CopyFilesError::InvalidPath)?).await?;
+    if copy.target.is_dir() {
+        fs::remove_dir(&copy.target).await?;
     }
+    // copy and let umask set the permissions instead of fs::copy
+    let mut src = File::open(&copy.source).await?;
+    let mut dst = File::create(&copy.target).await?;
+    Ok(io::copy(&mut src, &mut dst).await?)
+}
 
-    while let Some(result) = tasks.next().await {
-        if result? {
-            copied_files += 1;
-        }
+// deletes and copies the files of the plan, the sources must not change in between
+pub async fn apply_sync_plan(
+    target_dir: &Path,
+    plan: &SyncMappingPlan,
+) -> anyhow::Result<SyncMappingStats> {
+    for path in &plan.to_delete {
+        fs::remove_file(path).await?;
+    }
+    if target_dir.is_dir() {
+        remove_empty_dirs(target_dir).await?;
+    }
 
-        if let Some((target, source)) = mapping_iter.next() {
-            tasks.push(copy_file_if_needed(target.clone(), source.clone()));
-        }
+    let mut copied_bytes = 0;
+    let mut copies = stream::iter(&plan.to_copy)
+        .map(apply_copy)
+        .buffer_unordered(MAX_CONCURRENT_FILE_OPERATIONS);
+    while let Some(bytes) = copies.next().await {
+        copied_bytes += bytes?;
     }
 
     Ok(SyncMappingStats {
-        total_files,
-        copied_files,
-        deleted_files,
+        total_files: plan.total_files,
+        copied_files: plan.to_copy.len(),
+        copied_bytes,
+        deleted_files: plan.to_delete.len(),
     })
 }
 
+// copy mapped files and directories
+// and delete all other files and directores in the target directory
+// mapping: target -> source
+pub async fn sync_mapping(
+    target_dir: &Path,
+    mapping: &HashMap<PathBuf, PathBuf>,
+) -> anyhow::Result<SyncMappingStats> {
+    let plan = plan_sync_mapping(target_dir, mapping).await?;
+    apply_sync_plan(target_dir, &plan).await
+}
+
 #[cfg(test)]
 mod tests {
-    use std::env;
-
     use maplit::hashmap;
 
     use super::*;
 
     #[tokio::test]
     async fn test_sync_mapping() {
-        let temp_dir = env::temp_dir().join("instance_builder_test");
+        let temp = tempfile::tempdir().unwrap();
+        let temp_dir = temp.path();
         let source_dir = temp_dir.join("source");
         let target_dir = temp_dir.join("target");
         let file1 = source_dir.join("file1");
@@ -326,8 +506,117 @@ mod tests {
         assert!(target_dir.join("dir2").join("file3").exists());
         assert!(!file4.exists());
         assert!(!file5.exists());
+    }
+
+    #[tokio::test]
+    async fn test_plan_sync_mapping() {
+        let temp = tempfile::tempdir().unwrap();
+        let temp_dir = temp.path();
+        let source_dir = temp_dir.join("source");
+        let target_dir = temp_dir.join("target");
+        fs::create_dir_all(&source_dir).await.unwrap();
+        fs::create_dir_all(&target_dir).await.unwrap();
+        fs::write(source_dir.join("added"), "12345").await.unwrap();
+        fs::write(source_dir.join("changed"), "123").await.unwrap();
+        fs::write(source_dir.join("same"), "same").await.unwrap();
+        fs::write(target_dir.join("changed"), "1").await.unwrap();
+        fs::write(target_dir.join("same"), "same").await.unwrap();
+        fs::write(target_dir.join("deleted"), "1234").await.unwrap();
+
+        let mappings = hashmap! {
+            target_dir.join("added") => source_dir.join("added"),
+            target_dir.join("changed") => source_dir.join("changed"),
+            target_dir.join("same") => source_dir.join("same"),
+        };
+        let plan = plan_sync_mapping(&target_dir, &mappings).await.unwrap();
+        assert_eq!(plan.total_files, 3);
+        let copied: Vec<_> = plan
+            .to_copy
+            .iter()
+            .map(|copy| (copy.target.clone(), copy.target_size))
+            .collect();
+        assert_eq!(
+            copied,
+            vec![
+                (target_dir.join("added"), None),
+                (target_dir.join("changed"), Some(1)),
+            ]
+        );
+        assert_eq!(plan.to_delete, vec![target_dir.join("deleted")]);
+        // 5 added, 2 more in the changed file, 4 deleted
+        assert_eq!(plan.get_bytes_delta(), 3);
+        // nothing is touched until the plan is applied
+        assert!(target_dir.join("deleted").exists());
+
+        let stats = apply_sync_plan(&target_dir, &plan).await.unwrap();
+        assert_eq!(stats.copied_files, 2);
+        assert_eq!(stats.copied_bytes, 8);
+        assert_eq!(stats.deleted_files, 1);
+        assert!(plan_sync_mapping(&target_dir, &mappings)
+            .await
+            .unwrap()
+            .to_copy
+            .is_empty());
+    }
 
-        fs::remove_dir_all(&source_dir).await.unwrap();
-        fs::remove_dir_all(&target_dir).await.unwrap();
+    #[tokio::test]
+    async fn test_download_entries_skip_hashing_on_size_mismatch() {
+        let temp = tempfile::tempdir().unwrap();
+        let temp_dir = temp.path();
+
+        let mut check_entries = vec![];
+        let mut expected_downloads = HashSet::new();
+        for i in 0..10 {
+            let path = temp_dir.join(format!("file{i}"));
+            let content = format!("content{i}");
+            fs::write(&path, &content).await.unwrap();
+            let sha1 = hash_file(&path).await.unwrap();
+            let (remote_sha1, remote_size) = match i % 4 {
+                // up to date, size matches
+                0 => (sha1, Some(content.len() as u64)),
+                // changed, size differs
+                1 => ("0".repeat(40), Some(content.len() as u64 + 1)),
+                // changed, same size
+                2 => ("0".repeat(40), Some(content.len() as u64)),
+                // up to date, size unknown
+                _ => (sha1, None),
+            };
+            if i % 4 == 1 || i % 4 == 2 {
+                expected_downloads.insert(path.clone());
+            }
+            check_entries.push(CheckEntry {
+                urls: vec![format!("https://example.com/file{i}")],
+                remote_sha1: Some(remote_sha1),
+                remote_size,
+                path,
+            });
+        }
+        let missing_path = temp_dir.join("missing");
+        expected_downloads.insert(missing_path.clone());
+        check_entries.push(CheckEntry {
+            urls: vec!["https://example.com/missing".to_string()],
+            remote_sha1: Some("0".repeat(40)),
+            remote_size: Some(1),
+            path: missing_path,
+        });
+
+        // files 1, 5 and 9 have a different size and the last one is missing
+        assert_eq!(get_paths_to_hash(&check_entries).len(), 7);
+
+        let download_entries = get_download_entries_with_limits(
+            check_entries,
+            crate::progress::no_progress_bar(),
+            HashLimits {
+                concurrency: 2,
+                buffer_size: 1024,
+            },
+        )
+        .await
+        .unwrap();
+        let downloaded_paths: HashSet<_> = download_entries
+            .into_iter()
+            .map(|entry| entry.path)
+            .collect();
+        assert_eq!(downloaded_paths, expected_downloads);
     }
 }
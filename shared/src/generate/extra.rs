@@ -8,14 +8,17 @@ use crate::{
     files,
     paths::{get_libraries_dir, get_rel_minecraft_dir, get_versions_extra_dir},
     progress::{self, NoProgressBar, ProgressBar as _},
-    utils::{url_from_path, url_from_rel_path},
+    utils::{path_to_slash_str, path_to_str, url_from_path, url_from_rel_path},
     version::{
-        extra_version_metadata::{AuthBackend, ExtraVersionMetadata, Include, Object},
+        extra_version_metadata::{
+            AuthBackend, AuthlibInjector, DownloadMirrors, ExtraVersionMetadata, Include,
+            InstanceDescription, LibraryOverride, Object, WindowBranding,
+        },
         version_metadata::Library,
     },
 };
 use log::info;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 async fn get_objects(
     copy_from: &Path,
@@ -39,9 +42,10 @@ async fn get_objects(
             download_server_base,
         )?;
         objects.push(Object {
-            path: rel_path.to_string_lossy().to_string().replace('\\', "/"),
+            path: path_to_slash_str(rel_path)?,
             sha1: hash.clone(),
             url,
+            size: Some(tokio::fs::metadata(copy_from.join(rel_path)).await?.len()),
         });
     }
 
@@ -75,8 +79,8 @@ async fn get_extra_forge_libs(
             let parts = path
                 .strip_prefix(&libraries_dir)?
                 .components()
-                .map(|x| x.as_os_str().to_string_lossy().replace('\\', "/"))
-                .collect::<Vec<_>>();
+                .map(|x| path_to_str(Path::new(x.as_os_str())))
+                .collect::<Result<Vec<_>, _>>()?;
             let version = parts[parts.len() - 2].to_string();
             let name = parts[parts.len() - 3].to_string();
             let group = parts
@@ -86,10 +90,7 @@ async fn get_extra_forge_libs(
                 .collect::<Vec<_>>()
                 .join(".");
 
-            let filename = path
-                .file_name()
-                .unwrap()
-                .to_string_lossy()
+            let filename = path_to_str(Path::new(path.file_name().unwrap()))?
                 .strip_suffix(".jar")
                 .unwrap()
                 .to_string();
@@ -119,7 +120,7 @@ fn yes() -> bool {
     true
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
 pub struct IncludeRule {
     pub path: String,
 
@@ -146,6 +147,15 @@ pub struct ExtraMetadataGenerator {
     extra_forge_libs_paths: Vec<PathBuf>,
     auth_backend: Option<AuthBackend>,
     recommended_xmx: Option<String>,
+    recommended_jvm_args: Vec<String>,
+    min_launcher_version: Option<String>,
+    description: InstanceDescription,
+    overrides: Vec<LibraryOverride>,
+    branding: WindowBranding,
+    shared_include: Vec<Include>,
+    checksums_url: Option<String>,
+    authlib_injector: Option<AuthlibInjector>,
+    download_mirrors: Option<DownloadMirrors>,
 }
 
 impl ExtraMetadataGenerator {
@@ -155,6 +165,8 @@ impl ExtraMetadataGenerator {
         extra_forge_libs_paths: Vec<PathBuf>,
         auth_backend: Option<AuthBackend>,
         recommended_xmx: Option<String>,
+        min_launcher_version: Option<String>,
+        description: InstanceDescription,
     ) -> Self {
         Self {
             version_name,
@@ -162,9 +174,54 @@ impl ExtraMetadataGenerator {
             extra_forge_libs_paths,
             auth_backend,
             recommended_xmx,
+            recommended_jvm_args: vec![],
+            min_launcher_version,
+            description,
+            overrides: vec![],
+            branding: Default::default(),
+            shared_include: vec![],
+            checksums_url: None,
+            authlib_injector: None,
+            download_mirrors: None,
         }
     }
 
+    pub fn with_overrides(mut self, overrides: Vec<LibraryOverride>) -> Self {
+        self.overrides = overrides;
+        self
+    }
+
+    pub fn with_recommended_jvm_args(mut self, recommended_jvm_args: Vec<String>) -> Self {
+        self.recommended_jvm_args = recommended_jvm_args;
+        self
+    }
+
+    pub fn with_branding(mut self, branding: WindowBranding) -> Self {
+        self.branding = branding;
+        self
+    }
+
+    // includes generated for another version, put before the own rules with their urls kept
+    pub fn with_shared_include(mut self, shared_include: Vec<Include>) -> Self {
+        self.shared_include = shared_include;
+        self
+    }
+
+    pub fn with_checksums_url(mut self, checksums_url: Option<String>) -> Self {
+        self.checksums_url = checksums_url;
+        self
+    }
+
+    pub fn with_authlib_injector(mut self, authlib_injector: Option<AuthlibInjector>) -> Self {
+        self.authlib_injector = authlib_injector;
+        self
+    }
+
+    pub fn with_download_mirrors(mut self, download_mirrors: Option<DownloadMirrors>) -> Self {
+        self.download_mirrors = download_mirrors;
+        self
+    }
+
     pub async fn generate(self, work_dir: &Path) -> anyhow::Result<GeneratorResult> {
         info!(
             "Generating extra metadata for instance {}",
@@ -172,11 +229,20 @@ impl ExtraMetadataGenerator {
         );
 
         let mut extra_metadata = ExtraVersionMetadata {
-            include: vec![],
+            include: self.shared_include,
             resources_url_base: None,
             auth_backend: self.auth_backend,
             extra_forge_libs: vec![],
             recommended_xmx: self.recommended_xmx,
+            recommended_jvm_args: self.recommended_jvm_args,
+            min_launcher_version: self.min_launcher_version,
+            description: self.description,
+            overrides: self.overrides,
+            branding: self.branding,
+            checksums_url: self.checksums_url,
+            authlib_injector_url: self.authlib_injector.as_ref().map(|x| x.url.clone()),
+            authlib_injector_sha1: self.authlib_injector.map(|x| x.sha1),
+            download_mirrors: self.download_mirrors,
         };
 
         let mut include_mapping = HashMap::new();
@@ -216,7 +282,7 @@ impl ExtraMetadataGenerator {
                 existing_paths.insert(from);
             }
 
-            extra_metadata.include = include;
+            extra_metadata.include.extend(include);
             extra_metadata.resources_url_base = include_config.resources_url_base;
             extra_metadata.extra_forge_libs = extra_forge_libs;
         }
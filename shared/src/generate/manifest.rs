@@ -66,5 +66,7 @@ pub async fn get_version_info(
         inherits_from: metadata_info,
         extra_metadata_url,
         extra_metadata_sha1,
+        rollout_percent: None,
+        server_address: None,
     })
 }
@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use log::debug;
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+struct CachedHash {
+    size: u64,
+    mtime_ns: u64,
+    sha1: String,
+}
+
+// sha1 of the files checked by earlier syncs, trusted while their size and mtime don't change
+#[derive(Serialize, Deserialize, Default)]
+pub struct HashCache {
+    files: HashMap<PathBuf, CachedHash>,
+}
+
+fn get_size_and_mtime(path: &Path) -> Option<(u64, u64)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    let mtime = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some((metadata.len(), mtime.as_nanos() as u64))
+}
+
+impl HashCache {
+    // a missing or corrupt cache is the same as an empty one
+    pub async fn load(path: &Path) -> Self {
+        let Ok(data) = fs::read(path).await else {
+            return Self::default();
+        };
+        serde_json::from_slice(&data).unwrap_or_else(|e| {
+            debug!("Discarding the hash cache {}: {e}", path.display());
+            Self::default()
+        })
+    }
+
+    // removed files are dropped
+    pub async fn save(&mut self, path: &Path) -> anyhow::Result<()> {
+        self.files.retain(|path, _| path.is_file());
+        // an interrupted write leaves the old cache
+        let mut tmp_path = path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+        fs::write(&tmp_path, serde_json::to_vec(self)?).await?;
+        fs::rename(&tmp_path, path).await?;
+        Ok(())
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&str> {
+        let cached = self.files.get(path)?;
+        (get_size_and_mtime(path)? == (cached.size, cached.mtime_ns))
+            .then_some(cached.sha1.as_str())
+    }
+
+    pub fn insert(&mut self, path: PathBuf, sha1: String) {
+        if let Some((size, mtime_ns)) = get_size_and_mtime(&path) {
+            self.files.insert(
+                path,
+                CachedHash {
+                    size,
+                    mtime_ns,
+                    sha1,
+                },
+            );
+        }
+    }
+
+    // the file is going to be rewritten
+    pub fn invalidate(&mut self, path: &Path) {
+        self.files.remove(path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::files::{get_download_entries_with_cache, CheckEntry, HashLimits};
+    use crate::progress::no_progress_bar;
+
+    fn get_check_entry(path: &Path, remote_sha1: &str) -> CheckEntry {
+        CheckEntry {
+            urls: vec!["https://example.com/a.jar".to_string()],
+            remote_sha1: Some(remote_sha1.to_string()),
+            remote_size: None,
+            path: path.to_path_buf(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hash_cache() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        let path = dir.join("a.jar");
+        std::fs::write(&path, b"jar").unwrap();
+        let cache_path = dir.join("hash_cache.json");
+
+        // a hash that doesn't match the content shows that the file wasn't read
+        let remote_sha1 = "0".repeat(40);
+        let mut cache = HashCache::default();
+        cache.insert(path.clone(), remote_sha1.clone());
+        cache.save(&cache_path).await.unwrap();
+        let mut cache = HashCache::load(&cache_path).await;
+        assert_eq!(cache.get(&path), Some(remote_sha1.as_str()));
+
+        let entries = get_download_entries_with_cache(
+            vec![get_check_entry(&path, &remote_sha1)],
+            no_progress_bar(),
+            HashLimits::default(),
+            &mut cache,
+            false,
+        )
+        .await
+        .unwrap();
+        assert!(entries.is_empty());
+
+        // forced checks hash everything, the file is downloaded and its hash forgotten
+        let entries = get_download_entries_with_cache(
+            vec![get_check_entry(&path, &remote_sha1)],
+            no_progress_bar(),
+            HashLimits::default(),
+            &mut cache,
+            true,
+        )
+        .await
+        .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(cache.get(&path), None);
+
+        cache.insert(path.clone(), remote_sha1.clone());
+        std::fs::write(&path, b"another jar").unwrap();
+        assert_eq!(cache.get(&path), None);
+
+        std::fs::write(&cache_path, b"{\"files\": ").unwrap();
+        assert!(HashCache::load(&cache_path).await.files.is_empty());
+    }
+}
@@ -1,8 +1,10 @@
 use flate2::read::GzDecoder;
 use futures::StreamExt;
+use log::info;
 use regex::Regex;
 use reqwest::{Client, Url};
 use serde::Deserialize;
+use std::collections::HashSet;
 use std::fs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
@@ -18,12 +20,23 @@ use winreg::RegKey;
 
 use crate::progress::ProgressBar;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Clone, PartialEq)]
 pub struct JavaInstallation {
     pub version: String,
     pub path: PathBuf,
 }
 
+// a runtime downloaded into the java dir, named after the major version it was downloaded for
+#[derive(Debug, Clone)]
+pub struct DownloadedJava {
+    pub dir: PathBuf,
+    pub path: PathBuf,
+    // None if the runtime doesn't start
+    pub version: Option<String>,
+    // still matches the major version of the dir
+    pub valid: bool,
+}
+
 lazy_static::lazy_static! {
     static ref JAVA_VERSION_RGX: Regex = Regex::new(r#""(.*)?""#).unwrap();
 }
@@ -34,44 +47,109 @@ const JAVA_BINARY_NAME: &str = "java.exe";
 #[cfg(not(target_os = "windows"))]
 const JAVA_BINARY_NAME: &str = "java";
 
-async fn get_installation(path: &Path) -> Option<JavaInstallation> {
-    let path = if path.is_file() {
-        path.to_path_buf()
-    } else {
-        which::which(path).ok()?
+const DEFAULT_JAVA_VERSION: u64 = 8;
+
+// first minecraft version of each range and the java major it needs, newest first
+const MINECRAFT_JAVA_VERSIONS: &[((u32, u32, u32), u64)] =
+    &[((1, 20, 5), 21), ((1, 18, 0), 17), ((1, 17, 0), 16)];
+
+// leading numeric part of the id, so "1.20.4-OptiFine" and "1.20.5-pre1" are handled too
+fn parse_minecraft_version(minecraft_version: &str) -> Option<(u32, u32, u32)> {
+    let end = minecraft_version
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(minecraft_version.len());
+    let mut parts = minecraft_version[..end]
+        .split('.')
+        .map(|part| part.parse::<u32>().ok());
+    let major = parts.next()??;
+    let minor = parts.next()??;
+    let patch = parts.next().unwrap_or(Some(0))?;
+    Some((major, minor, patch))
+}
+
+fn get_java_version_for_minecraft(minecraft_version: &str) -> u64 {
+    let Some(version) = parse_minecraft_version(minecraft_version) else {
+        return DEFAULT_JAVA_VERSION;
     };
+    MINECRAFT_JAVA_VERSIONS
+        .iter()
+        .find(|(first_version, _)| version >= *first_version)
+        .map_or(DEFAULT_JAVA_VERSION, |(_, java_version)| *java_version)
+}
 
-    let mut cmd = Command::new(&path);
+// for metadata without javaVersion, which is common for custom and older loader versions
+pub fn get_fallback_java_version(minecraft_version: &str) -> String {
+    let java_version = get_java_version_for_minecraft(minecraft_version);
+    info!("No Java version in metadata of {minecraft_version}, using Java {java_version}");
+    java_version.to_string()
+}
+
+// java prints the version and settings to stderr
+async fn run_java(path: &Path, args: &[&str]) -> Option<String> {
+    let mut cmd = Command::new(path);
     #[cfg(target_os = "windows")]
     {
         use winapi::um::winbase::CREATE_NO_WINDOW;
 
         cmd.creation_flags(CREATE_NO_WINDOW);
     }
-    let output = cmd.arg("-version").output().await.ok()?;
+    let output = cmd.args(args).output().await.ok()?;
+    Some(String::from_utf8_lossy(&output.stderr).to_string())
+}
+
+async fn get_version_output(path: &Path) -> Option<(PathBuf, String)> {
+    let path = if path.is_file() {
+        path.to_path_buf()
+    } else {
+        which::which(path).ok()?
+    };
+
+    let version_result = run_java(&path, &["-version"]).await?;
+    Some((path, version_result))
+}
 
-    let version_result = String::from_utf8_lossy(&output.stderr);
+async fn get_installation(path: &Path) -> Option<JavaInstallation> {
+    let (path, version_result) = get_version_output(path).await?;
     let captures = JAVA_VERSION_RGX.captures(&version_result)?;
 
     let version = captures.get(1)?.as_str().to_string();
     Some(JavaInstallation { version, path })
 }
 
-#[cfg(not(target_os = "windows"))]
-fn check_arch(java_version_output: &str) -> bool {
-    let arch = std::env::consts::ARCH;
-    match arch {
-        "x86_64" | "amd64" => java_version_output.contains("x86-64"),
-        "aarch64" => {
-            java_version_output.contains("aarch64") || java_version_output.contains("arm64")
-        }
+// 64-bit JVMs report "64-Bit Server VM", 32-bit ones only "Client VM" or "Server VM"
+fn is_32_bit_output(java_version_output: &str) -> bool {
+    java_version_output.contains(" VM") && !java_version_output.contains("64-Bit")
+}
+
+pub async fn is_java_32_bit(path: &Path) -> bool {
+    get_version_output(path)
+        .await
+        .is_some_and(|(_, output)| is_32_bit_output(&output))
+}
+
+// the os.arch line of -XshowSettings:properties
+fn parse_os_arch(java_settings_output: &str) -> Option<&str> {
+    java_settings_output.lines().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        (key.trim() == "os.arch").then(|| value.trim())
+    })
+}
+
+fn is_native_arch(host_arch: &str, java_arch: &str) -> bool {
+    match host_arch {
+        "x86_64" | "amd64" => matches!(java_arch, "x86_64" | "amd64"),
+        "aarch64" => matches!(java_arch, "aarch64" | "arm64"),
         _ => false,
     }
 }
 
-#[cfg(target_os = "windows")]
-fn check_arch(_: &str) -> bool {
-    true
+// asks the jvm itself, the file command is missing on windows and minimal linux installs
+async fn check_arch(path: &Path) -> bool {
+    let Some(output) = run_java(path, &["-XshowSettings:properties", "-version"]).await else {
+        return false;
+    };
+    parse_os_arch(&output)
+        .is_some_and(|java_arch| is_native_arch(std::env::consts::ARCH, java_arch))
 }
 
 async fn does_match(java: &JavaInstallation, required_version: &str) -> bool {
@@ -81,16 +159,11 @@ async fn does_match(java: &JavaInstallation, required_version: &str) -> bool {
         return false;
     }
 
+    // x86_64 java runs emulated on arm, but much slower
     if std::env::consts::ARCH != "aarch64" {
         return true;
     }
-    let output = Command::new("file").arg(&java.path).output().await;
-    if let Ok(output) = output {
-        let output = String::from_utf8_lossy(&output.stdout);
-        check_arch(&output)
-    } else {
-        false
-    }
+    check_arch(&java.path).await
 }
 
 pub async fn check_java(required_version: &str, path: &Path) -> bool {
@@ -158,10 +231,26 @@ async fn find_java_installations() -> Vec<JavaInstallation> {
         res.extend(find_java_in_registry(key, subkey_suffix, java_dir_key));
     }
 
+    // zip installs aren't in the registry
+    for program_files in ["ProgramFiles", "ProgramFiles(x86)"] {
+        let Some(program_files) = std::env::var_os(program_files) else {
+            continue;
+        };
+        for vendor in [
+            "Java",
+            "Eclipse Adoptium",
+            "AdoptOpenJDK",
+            "Zulu",
+            "Microsoft",
+            "BellSoft",
+        ] {
+            res.extend(find_java_in_dir(&Path::new(&program_files).join(vendor), "", "").await);
+        }
+    }
+
     res
 }
 
-#[cfg(not(target_os = "windows"))]
 async fn find_java_in_dir(dir: &Path, suffix: &str, startswith: &str) -> Vec<JavaInstallation> {
     let mut res = Vec::new();
 
@@ -181,7 +270,7 @@ async fn find_java_in_dir(dir: &Path, suffix: &str, startswith: &str) -> Vec<Jav
                 continue;
             }
             if let Some(java) =
-                get_installation(&subdir.join(suffix).join("bin").join("java")).await
+                get_installation(&subdir.join(suffix).join("bin").join(JAVA_BINARY_NAME)).await
             {
                 res.push(java);
             }
@@ -232,8 +321,12 @@ enum JavaDownloadError {
     UnsupportedArchitecture,
     #[error("Unsupported operating system")]
     UnsupportedOS,
-    #[error("No Java versions available")]
-    NoJavaVersionsAvailable,
+    #[error("No Java {version} build available for {os} {arch}")]
+    NoJavaForPlatform {
+        version: String,
+        os: String,
+        arch: String,
+    },
     #[error("Invalid downloaded Java")]
     InvalidDownloadedJava,
     #[error("No versions array")]
@@ -246,25 +339,80 @@ enum JavaDownloadError {
     NoFileExtensionInURL,
 }
 
-fn get_java_download_params(required_version: &str, archive_type: &str) -> anyhow::Result<String> {
-    let arch = match std::env::consts::ARCH {
+const JAVA_PACKAGES_URL: &str = "https://api.azul.com/metadata/v1/zulu/packages/";
+
+// windows builds are only published as zip
+fn get_archive_types(os: &str) -> &'static [&'static str] {
+    match os {
+        "windows" => &["zip"],
+        _ => &["tar.gz", "zip"],
+    }
+}
+
+// some arm builds only come as a jdk
+const PACKAGE_TYPES: &[&str] = &["jre", "jdk"];
+
+fn get_java_packages_url(
+    required_version: &str,
+    os: &str,
+    arch: &str,
+    archive_type: &str,
+    package_type: &str,
+) -> anyhow::Result<String> {
+    let arch = match arch {
         "x86_64" | "amd64" => "x64",
         "aarch64" => "aarch64",
         _ => return Err(JavaDownloadError::UnsupportedArchitecture.into()),
     };
 
-    let os = match std::env::consts::OS {
+    let os = match os {
         "windows" => "windows",
         "linux" => "linux-glibc",
         "macos" => "macos",
         _ => return Err(JavaDownloadError::UnsupportedOS.into()),
     };
 
-    let params = format!(
-        "java_version={required_version}&os={os}&arch={arch}&archive_type={archive_type}&java_package_type=jre&javafx_bundled=false&latest=true&release_status=ga"
-    );
+    Ok(format!(
+        "{JAVA_PACKAGES_URL}?java_version={required_version}&os={os}&arch={arch}&archive_type={archive_type}&java_package_type={package_type}&javafx_bundled=false&latest=true&release_status=ga"
+    ))
+}
+
+// (download url, archive type) of the first matching package
+async fn find_java_package(
+    client: &Client,
+    required_version: &str,
+) -> anyhow::Result<(String, &'static str)> {
+    let (os, arch) = (std::env::consts::OS, std::env::consts::ARCH);
+    for package_type in PACKAGE_TYPES {
+        for archive_type in get_archive_types(os) {
+            let versions_url =
+                get_java_packages_url(required_version, os, arch, archive_type, package_type)?;
+
+            let response = client.get(&versions_url).send().await?;
+            let body = response.text().await?;
+            let versions: Value = serde_json::from_str(&body)?;
+
+            if versions
+                .as_array()
+                .ok_or(JavaDownloadError::NoVersionsArray)?
+                .is_empty()
+            {
+                continue;
+            }
+
+            let version_url = versions[0]["download_url"]
+                .as_str()
+                .ok_or(JavaDownloadError::NoDownloadURL)?;
+            return Ok((version_url.to_string(), archive_type));
+        }
+    }
 
-    Ok(params)
+    Err(JavaDownloadError::NoJavaForPlatform {
+        version: required_version.to_string(),
+        os: os.to_string(),
+        arch: arch.to_string(),
+    }
+    .into())
 }
 
 pub fn get_temp_dir() -> PathBuf {
@@ -283,76 +431,104 @@ pub async fn download_java<M>(
 ) -> anyhow::Result<JavaInstallation> {
     let client = Client::new();
 
-    for archive_type in ["tar.gz", "zip"] {
-        let query_str = get_java_download_params(required_version, archive_type)?;
+    let (version_url, archive_type) = find_java_package(&client, required_version).await?;
+    let response = client.get(&version_url).send().await?;
 
-        let versions_url = format!("https://api.azul.com/metadata/v1/zulu/packages/?{query_str}");
+    let java_download_path = get_temp_dir().join(format!("java_download.{archive_type}"));
+    let mut file = fs::File::create(&java_download_path)?;
 
-        let response = client.get(&versions_url).send().await?;
-        let body = response.text().await?;
-        let versions: Value = serde_json::from_str(&body)?;
-
-        if versions
-            .as_array()
-            .ok_or(JavaDownloadError::NoVersionsArray)?
-            .is_empty()
-        {
-            continue;
-        }
+    let total_size = response.content_length().unwrap_or(0);
+    progress_bar.set_length(total_size);
 
-        let version_url = versions[0]["download_url"]
-            .as_str()
-            .ok_or(JavaDownloadError::NoDownloadURL)?;
-        let response = client.get(version_url).send().await?;
-
-        let java_download_path = get_temp_dir().join(format!("java_download.{archive_type}"));
-        let mut file = fs::File::create(&java_download_path)?;
-
-        let total_size = response.content_length().unwrap_or(0);
-        progress_bar.set_length(total_size);
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        file.write_all(&chunk)?;
+        progress_bar.inc(chunk.len() as u64);
+    }
+    progress_bar.finish();
 
-        let mut stream = response.bytes_stream();
-        while let Some(chunk) = stream.next().await {
-            let chunk = chunk?;
-            file.write_all(&chunk)?;
-            progress_bar.inc(chunk.len() as u64);
-        }
-        progress_bar.finish();
+    let target_dir = java_dir.join(required_version);
+    if target_dir.exists() {
+        fs::remove_dir_all(&target_dir)?;
+    }
 
-        let target_dir = java_dir.join(required_version);
-        if target_dir.exists() {
-            fs::remove_dir_all(&target_dir)?;
-        }
+    let archive = fs::File::open(&java_download_path)?;
+    if archive_type == "tar.gz" {
+        let tar = GzDecoder::new(archive);
+        let mut archive = Archive::new(tar);
+        archive.unpack(java_dir)?;
+    } else {
+        let mut archive = zip::ZipArchive::new(archive)?;
+        archive.extract(java_dir)?;
+    }
 
-        let archive = fs::File::open(&java_download_path)?;
-        if archive_type == "tar.gz" {
-            let tar = GzDecoder::new(archive);
-            let mut archive = Archive::new(tar);
-            archive.unpack(java_dir)?;
-        } else {
-            let mut archive = zip::ZipArchive::new(archive)?;
-            archive.extract(java_dir)?;
-        }
+    let url = Url::parse(&version_url)?;
+    let filename = url
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .ok_or(JavaDownloadError::NoFileNameInURL)?
+        .strip_suffix(&format!(".{archive_type}"))
+        .ok_or(JavaDownloadError::NoFileExtensionInURL)?;
+    fs::rename(java_dir.join(filename), &target_dir)?;
+
+    let java_path = target_dir.join("bin").join(JAVA_BINARY_NAME);
+    if !check_java(required_version, &java_path).await {
+        return Err(JavaDownloadError::InvalidDownloadedJava.into());
+    }
+    get_installation(&java_path)
+        .await
+        .ok_or(JavaDownloadError::InvalidDownloadedJava.into())
+}
 
-        let url = Url::parse(version_url)?;
-        let filename = url
-            .path_segments()
-            .and_then(|mut segments| segments.next_back())
-            .ok_or(JavaDownloadError::NoFileNameInURL)?
-            .strip_suffix(&format!(".{archive_type}"))
-            .ok_or(JavaDownloadError::NoFileExtensionInURL)?;
-        fs::rename(java_dir.join(filename), &target_dir)?;
-
-        let java_path = target_dir.join("bin").join(JAVA_BINARY_NAME);
-        if !check_java(required_version, &java_path).await {
-            return Err(JavaDownloadError::InvalidDownloadedJava.into());
-        }
-        if let Some(installation) = get_installation(&java_path).await {
-            return Ok(installation);
-        }
+// JAVA_HOME, the usual install locations and the one on PATH, without duplicates
+pub async fn find_system_java() -> Vec<JavaInstallation> {
+    let mut candidates = vec![];
+    if let Some(java_home) = std::env::var_os("JAVA_HOME") {
+        let path = Path::new(&java_home).join("bin").join(JAVA_BINARY_NAME);
+        candidates.extend(get_installation(&path).await);
     }
+    candidates.extend(find_java_installations().await);
+    candidates.extend(get_installation(Path::new(JAVA_BINARY_NAME)).await);
+
+    // the java on PATH is usually a symlink into one of the install dirs
+    let mut seen = HashSet::new();
+    candidates
+        .into_iter()
+        .filter(|installation| {
+            seen.insert(fs::canonicalize(&installation.path).unwrap_or(installation.path.clone()))
+        })
+        .collect()
+}
 
-    Err(JavaDownloadError::NoJavaVersionsAvailable.into())
+// runtimes that download_java put into java_dir
+pub async fn list_downloaded_java(java_dir: &Path) -> Vec<DownloadedJava> {
+    let Ok(entries) = fs::read_dir(java_dir) else {
+        return vec![];
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    dirs.sort();
+
+    let mut result = vec![];
+    for dir in dirs {
+        let path = dir.join("bin").join(JAVA_BINARY_NAME);
+        let major_version = dir.file_name().unwrap_or_default().to_string_lossy();
+        let valid = check_java(&major_version, &path).await;
+        let version = get_installation(&path)
+            .await
+            .map(|installation| installation.version);
+        result.push(DownloadedJava {
+            dir,
+            path,
+            version,
+            valid,
+        });
+    }
+    result
 }
 
 pub async fn get_java(required_version: &str, java_dir: &Path) -> Option<JavaInstallation> {
@@ -376,3 +552,153 @@ pub async fn get_java(required_version: &str, java_dir: &Path) -> Option<JavaIns
 
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_java_packages_url() {
+        let matrix = [
+            (
+                "linux",
+                "x86_64",
+                "tar.gz",
+                "os=linux-glibc&arch=x64&archive_type=tar.gz",
+            ),
+            (
+                "linux",
+                "aarch64",
+                "tar.gz",
+                "os=linux-glibc&arch=aarch64&archive_type=tar.gz",
+            ),
+            (
+                "windows",
+                "x86_64",
+                "zip",
+                "os=windows&arch=x64&archive_type=zip",
+            ),
+            (
+                "windows",
+                "aarch64",
+                "zip",
+                "os=windows&arch=aarch64&archive_type=zip",
+            ),
+            (
+                "macos",
+                "x86_64",
+                "tar.gz",
+                "os=macos&arch=x64&archive_type=tar.gz",
+            ),
+            (
+                "macos",
+                "aarch64",
+                "zip",
+                "os=macos&arch=aarch64&archive_type=zip",
+            ),
+        ];
+        for (os, arch, archive_type, expected) in matrix {
+            let url = get_java_packages_url("17", os, arch, archive_type, "jre").unwrap();
+            assert_eq!(
+                url,
+                format!(
+                    "{JAVA_PACKAGES_URL}?java_version=17&{expected}&java_package_type=jre&javafx_bundled=false&latest=true&release_status=ga"
+                )
+            );
+            assert!(get_archive_types(os).contains(&archive_type));
+        }
+        assert_eq!(get_archive_types("windows"), ["zip"]);
+
+        let err = get_java_packages_url("17", "linux", "riscv64", "tar.gz", "jre").unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<JavaDownloadError>(),
+            Some(JavaDownloadError::UnsupportedArchitecture)
+        ));
+        let err = get_java_packages_url("17", "freebsd", "x86_64", "tar.gz", "jre").unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<JavaDownloadError>(),
+            Some(JavaDownloadError::UnsupportedOS)
+        ));
+
+        let output = "Property settings:\n    file.separator = /\n    os.arch = aarch64\n    os.name = Linux\n";
+        assert_eq!(parse_os_arch(output), Some("aarch64"));
+        assert!(is_native_arch("aarch64", "aarch64"));
+        assert!(!is_native_arch("aarch64", "amd64"));
+        assert!(is_native_arch("x86_64", "amd64"));
+    }
+
+    // a shell script that answers like java
+    #[cfg(unix)]
+    fn write_fake_java(java_dir: &Path, dir: &str, version: &str) {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = java_dir.join(dir).join("bin").join(JAVA_BINARY_NAME);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(
+            &path,
+            format!(
+                "#!/bin/sh\necho 'openjdk version \"{version}\"' >&2\necho \"    os.arch = $(uname -m)\" >&2\n"
+            ),
+        )
+        .unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_list_downloaded_java() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let java_dir = temp_dir.path();
+        write_fake_java(java_dir, "17", "17.0.2");
+        write_fake_java(java_dir, "21", "17.0.2");
+        fs::create_dir_all(java_dir.join("8")).unwrap();
+
+        let runtimes = list_downloaded_java(java_dir).await;
+        let runtimes: Vec<_> = runtimes
+            .iter()
+            .map(|runtime| {
+                (
+                    runtime.dir.file_name().unwrap().to_str().unwrap(),
+                    runtime.version.as_deref(),
+                    runtime.valid,
+                )
+            })
+            .collect();
+        assert_eq!(
+            runtimes,
+            [
+                ("17", Some("17.0.2"), true),
+                ("21", Some("17.0.2"), false),
+                ("8", None, false)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_java_version_for_minecraft() {
+        for (minecraft_version, java_version) in [
+            ("1.8.9", 8),
+            ("1.16.5", 8),
+            ("1.17", 16),
+            ("1.17.1", 16),
+            ("1.18", 17),
+            ("1.18-pre1", 17),
+            ("1.20.4", 17),
+            ("1.20.4-OptiFine", 17),
+            ("1.20.4-forge-49.0.30", 17),
+            ("1.20.5", 21),
+            ("1.20.5-pre1", 21),
+            ("1.21", 21),
+            ("1.21.1-fabric", 21),
+            ("24w14a", 8),
+            ("b1.7.3", 8),
+            ("", 8),
+        ] {
+            assert_eq!(
+                get_java_version_for_minecraft(minecraft_version),
+                java_version,
+                "{minecraft_version}"
+            );
+        }
+    }
+}
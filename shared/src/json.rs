@@ -0,0 +1,83 @@
+use serde::de::DeserializeOwned;
+
+// json that doesn't match the expected structure, with the location of the offending value
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+#[error("Invalid JSON in {url} at {path}: {message}")]
+pub struct JsonParseError {
+    // url, or the local path if the file was read from disk
+    pub url: String,
+    pub path: String,
+    pub message: String,
+}
+
+impl JsonParseError {
+    pub fn get_details(&self) -> String {
+        format!(
+            "URL: {}\nJSON path: {}\nError: {}",
+            self.url, self.path, self.message
+        )
+    }
+}
+
+pub fn from_slice<T: DeserializeOwned>(data: &[u8], url: &str) -> Result<T, JsonParseError> {
+    let deserializer = &mut serde_json::Deserializer::from_slice(data);
+    serde_path_to_error::deserialize(deserializer).map_err(|e| JsonParseError {
+        url: url.to_string(),
+        path: e.path().to_string(),
+        message: e.inner().to_string(),
+    })
+}
+
+pub fn get_json_parse_error(e: &anyhow::Error) -> Option<&JsonParseError> {
+    e.chain().find_map(|e| e.downcast_ref::<JsonParseError>())
+}
+
+// files are parsed after being downloaded, this points the error back at the remote url
+pub fn with_url(e: anyhow::Error, url: &str) -> anyhow::Error {
+    match e.downcast::<JsonParseError>() {
+        Ok(parse_error) => JsonParseError {
+            url: url.to_string(),
+            ..parse_error
+        }
+        .into(),
+        Err(e) => e,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Library {
+        name: String,
+    }
+
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Metadata {
+        libraries: Vec<Library>,
+        downloads: HashMap<String, u64>,
+    }
+
+    #[test]
+    fn test_parse_error_path() {
+        let data = br#"{"libraries": [{"name": "a"}, {"name": 1}], "downloads": {}}"#;
+        let error = from_slice::<Metadata>(data, "https://example.com/version.json").unwrap_err();
+        assert_eq!(error.url, "https://example.com/version.json");
+        assert_eq!(error.path, "libraries[1].name");
+        assert!(error.message.starts_with("invalid type: integer `1`"));
+
+        let error = from_slice::<Metadata>(br#"{"libraries": []}"#, "local.json").unwrap_err();
+        let error = with_url(error.into(), "https://example.com/version.json");
+        let error = get_json_parse_error(&error).unwrap();
+        assert_eq!(error.url, "https://example.com/version.json");
+        assert_eq!(error.path, ".");
+        assert!(error.message.starts_with("missing field `downloads`"));
+    }
+}
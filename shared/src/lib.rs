@@ -1,10 +1,17 @@
 pub mod adaptive_download;
+pub mod checksums;
+pub mod clock;
+pub mod compression;
 pub mod files;
 pub mod generate;
+pub mod hash_cache;
 pub mod java;
+pub mod json;
 pub mod loader_generator;
 pub mod logs;
+pub mod mcping;
 pub mod paths;
 pub mod progress;
+pub mod signing;
 pub mod utils;
 pub mod version;
@@ -5,7 +5,7 @@ use crate::{
     version::{version_manifest::VersionInfo, version_metadata::VersionMetadata},
 };
 use async_trait::async_trait;
-use log::info;
+use log::{info, warn};
 use reqwest::Client;
 use serde::Deserialize;
 
@@ -16,6 +16,8 @@ const FABRIC_META_BASE_URL: &str = "https://meta.fabricmc.net/v2/versions/loader
 #[derive(Deserialize)]
 struct FabricVersionLoader {
     version: String,
+    #[serde(default)]
+    stable: bool,
 }
 
 #[derive(Deserialize)]
@@ -52,6 +54,19 @@ impl FabricVersionsMeta {
     pub fn get_latest_version(&self) -> Option<&str> {
         self.get_versions().first().copied()
     }
+
+    pub fn get_latest_stable_version(&self) -> Option<&str> {
+        self.versions
+            .iter()
+            .find(|version| version.loader.stable)
+            .map(|version| version.loader.version.as_str())
+    }
+
+    pub fn is_stable(&self, loader_version: &str) -> bool {
+        self.versions
+            .iter()
+            .any(|version| version.loader.version == loader_version && version.loader.stable)
+    }
 }
 
 async fn download_fabric_metadata(
@@ -114,13 +129,18 @@ impl VersionGenerator for FabricGenerator {
             Some(loader_version) => loader_version.clone(),
             None => {
                 let meta = FabricVersionsMeta::fetch(&minecraft_version).await?;
-                let version =
-                    meta.get_latest_version()
-                        .ok_or(FabricGeneratorError::NoVersionsFound(
-                            minecraft_version.to_string(),
-                        ))?;
-                info!("Loader version not specified, using latest version: {version}");
-                version.to_string()
+                if let Some(version) = meta.get_latest_stable_version() {
+                    info!("Loader version not specified, using latest stable version: {version}");
+                    version.to_string()
+                } else {
+                    let version =
+                        meta.get_latest_version()
+                            .ok_or(FabricGeneratorError::NoVersionsFound(
+                                minecraft_version.to_string(),
+                            ))?;
+                    warn!("No stable loader versions found, using latest version: {version}");
+                    version.to_string()
+                }
             }
         };
 
@@ -136,3 +156,26 @@ impl VersionGenerator for FabricGenerator {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latest_stable_version() {
+        let versions: Vec<FabricVersionMeta> = serde_json::from_str(
+            r#"[
+                {"loader": {"version": "0.16.0-beta.1", "stable": false}},
+                {"loader": {"version": "0.15.11", "stable": true}},
+                {"loader": {"version": "0.15.10", "stable": true}}
+            ]"#,
+        )
+        .unwrap();
+        let meta = FabricVersionsMeta { versions };
+
+        assert_eq!(meta.get_latest_version(), Some("0.16.0-beta.1"));
+        assert_eq!(meta.get_latest_stable_version(), Some("0.15.11"));
+        assert!(!meta.is_stable("0.16.0-beta.1"));
+        assert!(meta.is_stable("0.15.10"));
+    }
+}
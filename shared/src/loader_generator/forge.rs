@@ -3,7 +3,8 @@ use std::{
     fmt::{Debug, Display},
     io::Write as _,
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{Arc, LazyLock, Mutex},
+    time::{Duration, SystemTime},
 };
 
 use crate::{
@@ -11,6 +12,7 @@ use crate::{
     java::{download_java, get_java},
     paths::{get_java_dir, get_libraries_dir, get_metadata_path, get_versions_dir},
     progress::ProgressBar,
+    utils::path_to_str,
     version::{version_manifest::VersionInfo, version_metadata::VersionMetadata},
 };
 use async_trait::async_trait;
@@ -87,18 +89,49 @@ pub struct NeoforgeMavenMetadata {
     versioning: Versioning,
 }
 
+// versions for 1.20.1 were published as net.neoforged:forge with forge-style coordinates
+const NEOFORGE_LEGACY_MAVEN_METADATA_URL: &str =
+    "https://maven.neoforged.net/releases/net/neoforged/forge/maven-metadata.xml";
+
+fn is_legacy_neoforge_version(version: &str) -> bool {
+    version.starts_with("1.")
+}
+
+// (numeric parts, is release)
+fn get_neoforge_version_key(version: &str) -> (Vec<u32>, bool) {
+    let (numbers, suffix) = match version.split_once('-') {
+        Some((numbers, suffix)) => (numbers, Some(suffix)),
+        None => (version, None),
+    };
+    let parts = numbers
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect();
+    (parts, suffix.is_none())
+}
+
 impl NeoforgeMavenMetadata {
+    async fn fetch_versions(client: &Client, url: &str) -> anyhow::Result<Vec<String>> {
+        let response = client.get(url).send().await?.error_for_status()?;
+        let metadata: NeoforgeMavenMetadata = serde_xml_rs::from_str(&response.text().await?)?;
+        Ok(metadata.versioning.versions.version)
+    }
+
     pub async fn fetch() -> anyhow::Result<Self> {
         let client = Client::new();
-        let response = client
-            .get(NEOFORGE_MAVEN_METADATA_URL)
-            .send()
-            .await?
-            .error_for_status()?;
-        let metadata: NeoforgeMavenMetadata = serde_xml_rs::from_str(&response.text().await?)?;
-        Ok(metadata)
+        let (mut versions, legacy_versions) = tokio::try_join!(
+            Self::fetch_versions(&client, NEOFORGE_MAVEN_METADATA_URL),
+            Self::fetch_versions(&client, NEOFORGE_LEGACY_MAVEN_METADATA_URL),
+        )?;
+        versions.extend(legacy_versions);
+        Ok(NeoforgeMavenMetadata {
+            versioning: Versioning {
+                versions: Versions { version: versions },
+            },
+        })
     }
 
+    // sorted from newest to oldest
     pub fn get_matching_versions(&self, minecraft_version: &str) -> Vec<String> {
         let mut mc_version_parts: Vec<&str> = minecraft_version.split('.').collect();
         if mc_version_parts.len() < 2 {
@@ -108,31 +141,32 @@ impl NeoforgeMavenMetadata {
             mc_version_parts.push("0");
         }
 
-        let mc_version_prefix = format!("{}.{}", mc_version_parts[1], mc_version_parts[2]);
-        self.versioning
+        let legacy_prefix = format!("{minecraft_version}-");
+        let modern_prefix = format!("{}.{}.", mc_version_parts[1], mc_version_parts[2]);
+        let mut versions: Vec<(String, (Vec<u32>, bool))> = self
+            .versioning
             .versions
             .version
             .iter()
-            .rev()
-            .filter(|&version| version.starts_with(&mc_version_prefix))
-            .cloned()
-            .collect()
+            .filter_map(|version| {
+                let build = if is_legacy_neoforge_version(version) {
+                    version.strip_prefix(&legacy_prefix)?
+                } else if version.starts_with(&modern_prefix) {
+                    version.as_str()
+                } else {
+                    return None;
+                };
+                Some((version.clone(), get_neoforge_version_key(build)))
+            })
+            .collect();
+        versions.sort_by(|(_, a), (_, b)| b.cmp(a));
+        versions.into_iter().map(|(version, _)| version).collect()
     }
 
     pub fn get_latest_matching_version(&self, minecraft_version: &str) -> Option<String> {
         self.get_matching_versions(minecraft_version)
             .into_iter()
-            .max_by(|a, b| {
-                let a_parts: Vec<u32> = a
-                    .split(|c: char| !c.is_ascii_digit())
-                    .filter_map(|s| s.parse().ok())
-                    .collect();
-                let b_parts: Vec<u32> = b
-                    .split(|c: char| !c.is_ascii_digit())
-                    .filter_map(|s| s.parse().ok())
-                    .collect();
-                a_parts.cmp(&b_parts)
-            })
+            .next()
     }
 
     pub fn has_version(&self, version: &str) -> bool {
@@ -176,16 +210,31 @@ const FORGE_INSTALLER_BASE_URL: &str = "https://maven.minecraftforge.net/net/min
 const NEOFORGE_INSTALLER_BASE_URL: &str =
     "https://maven.neoforged.net/releases/net/neoforged/neoforge/";
 
+const NEOFORGE_LEGACY_INSTALLER_BASE_URL: &str =
+    "https://maven.neoforged.net/releases/net/neoforged/forge/";
+
 async fn download_forge_installer(
     full_version: &str,
     work_dir: &Path,
     loader: &Loader,
 ) -> anyhow::Result<PathBuf> {
-    let filename = format!("{loader:?}-{full_version}-installer.jar");
-    let forge_installer_url = match loader {
-        Loader::Forge => format!("{FORGE_INSTALLER_BASE_URL}{full_version}/{filename}"),
-        Loader::Neoforge => format!("{NEOFORGE_INSTALLER_BASE_URL}{full_version}/{filename}"),
-    };
+    let forge_installer_url;
+    let filename;
+    match loader {
+        Loader::Forge => {
+            filename = format!("{loader:?}-{full_version}-installer.jar");
+            forge_installer_url = format!("{FORGE_INSTALLER_BASE_URL}{full_version}/{filename}");
+        }
+        Loader::Neoforge if is_legacy_neoforge_version(full_version) => {
+            filename = format!("forge-{full_version}-installer.jar");
+            forge_installer_url =
+                format!("{NEOFORGE_LEGACY_INSTALLER_BASE_URL}{full_version}/{filename}");
+        }
+        Loader::Neoforge => {
+            filename = format!("{loader:?}-{full_version}-installer.jar");
+            forge_installer_url = format!("{NEOFORGE_INSTALLER_BASE_URL}{full_version}/{filename}");
+        }
+    }
     let forge_installer_path = work_dir.join(filename);
 
     let client = Client::new();
@@ -268,6 +317,8 @@ pub enum ForgeError {
     ForgeVersionNotFound(String, String),
     #[error("No forge profiles found")]
     NoForgeProfiles,
+    #[error("Forge installer did not create version {0}")]
+    MissingInstalledVersion(String),
 }
 
 pub async fn get_forge_version(
@@ -332,15 +383,6 @@ pub async fn get_forge_version(
     )
 }
 
-pub async fn get_vanilla_java_version(
-    vanilla_metadata: &VersionMetadata,
-) -> anyhow::Result<Option<String>> {
-    Ok(vanilla_metadata
-        .java_version
-        .as_ref()
-        .map(|v| v.major_version.to_string()))
-}
-
 // trick forge installer into thinking that the folder is actually a minecraft instance folder
 pub fn trick_forge(forge_work_dir: &Path, minecraft_version: &str) -> anyhow::Result<()> {
     std::fs::create_dir_all(forge_work_dir.join("versions").join(minecraft_version))?;
@@ -356,7 +398,7 @@ pub fn get_full_version(minecraft_version: &str, forge_version: &str) -> String
 // workaround for windows weirdness
 fn to_abs_path_str(path: &Path) -> anyhow::Result<String> {
     let canonical = path.canonicalize()?;
-    let path_str = canonical.to_string_lossy();
+    let path_str = path_to_str(&canonical)?;
 
     #[cfg(windows)]
     {
@@ -413,6 +455,119 @@ async fn run_forge_command(
     Ok(())
 }
 
+const INSTALLER_LOCK_FILE: &str = "forge.lock";
+
+// unfinished installer work dirs older than this are left over from a crash or a failed install
+pub const ABANDONED_INSTALLER_DIR_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+const LOADERS: [Loader; 2] = [Loader::Forge, Loader::Neoforge];
+
+fn get_installer_dir(work_dir: &Path, loader: &Loader) -> PathBuf {
+    work_dir.join(format!(".{loader:?}"))
+}
+
+pub fn get_installer_work_dir(work_dir: &Path, loader: &Loader, full_version: &str) -> PathBuf {
+    get_installer_dir(work_dir, loader).join(full_version)
+}
+
+fn get_installed_metadata_path(forge_work_dir: &Path, id: &str) -> PathBuf {
+    forge_work_dir
+        .join("versions")
+        .join(id)
+        .join(format!("{id}.json"))
+}
+
+fn read_profile_id(forge_work_dir: &Path) -> anyhow::Result<String> {
+    let launcher_profiles_path = forge_work_dir.join("launcher_profiles.json");
+    let launcher_profiles_content = std::fs::read_to_string(&launcher_profiles_path)?;
+    let launcher_profiles: LauncherProfiles = serde_json::from_str(&launcher_profiles_content)?;
+
+    Ok(launcher_profiles
+        .profiles
+        .values()
+        .next()
+        .ok_or(ForgeError::NoForgeProfiles)?
+        .last_version_id
+        .clone())
+}
+
+// the lock is only written after a complete install, the version is checked in case it was removed since
+fn get_installed_id(forge_work_dir: &Path) -> Option<String> {
+    if !forge_work_dir.join(INSTALLER_LOCK_FILE).exists() {
+        return None;
+    }
+    match read_profile_id(forge_work_dir) {
+        Ok(id) if get_installed_metadata_path(forge_work_dir, &id).exists() => Some(id),
+        Ok(id) => {
+            warn!("Installed version {id} is missing, reinstalling");
+            None
+        }
+        Err(e) => {
+            warn!("Failed to read installed profile, reinstalling:\n{e:?}");
+            None
+        }
+    }
+}
+
+// installer work dirs without a lock that haven't been touched for max_age
+pub fn find_abandoned_installer_dirs(
+    work_dir: &Path,
+    max_age: Duration,
+) -> anyhow::Result<Vec<PathBuf>> {
+    let now = SystemTime::now();
+    let mut abandoned = vec![];
+    for loader in LOADERS {
+        let installer_dir = get_installer_dir(work_dir, &loader);
+        if !installer_dir.is_dir() {
+            continue;
+        }
+        for entry in std::fs::read_dir(installer_dir)?.flatten() {
+            let path = entry.path();
+            if !path.is_dir() || path.join(INSTALLER_LOCK_FILE).exists() {
+                continue;
+            }
+            let age = entry
+                .metadata()?
+                .modified()
+                .ok()
+                .and_then(|modified| now.duration_since(modified).ok())
+                .unwrap_or_default();
+            if age >= max_age {
+                abandoned.push(path);
+            }
+        }
+    }
+    abandoned.sort();
+    Ok(abandoned)
+}
+
+pub fn remove_abandoned_installer_dirs(
+    work_dir: &Path,
+    max_age: Duration,
+) -> anyhow::Result<Vec<PathBuf>> {
+    let abandoned = find_abandoned_installer_dirs(work_dir, max_age)?;
+    for path in &abandoned {
+        info!("Removing abandoned installer dir {}", path.display());
+        std::fs::remove_dir_all(path)?;
+    }
+    Ok(abandoned)
+}
+
+// the lock file is only written after a complete install, so installs of versions
+// generated concurrently are serialized by their work dir here
+static WORK_DIR_LOCKS: LazyLock<Mutex<HashMap<PathBuf, Arc<tokio::sync::Mutex<()>>>>> =
+    LazyLock::new(Default::default);
+
+async fn lock_work_dir(dir: &Path) -> tokio::sync::OwnedMutexGuard<()> {
+    let lock = WORK_DIR_LOCKS
+        .lock()
+        .unwrap()
+        .entry(dir.to_path_buf())
+        .or_default()
+        .clone();
+    lock.lock_owned().await
+}
+
 pub async fn install_forge<M>(
     forge_work_dir: &Path,
     java_dir: &Path,
@@ -421,65 +576,59 @@ pub async fn install_forge<M>(
     loader: &Loader,
     progress_bar: Arc<dyn ProgressBar<M>>,
 ) -> anyhow::Result<String> {
+    if let Some(id) = get_installed_id(forge_work_dir) {
+        info!("Forge {forge_version} already present, skipping installation");
+        return Ok(id);
+    }
+
+    // leftovers of a failed install would confuse the installer
+    if forge_work_dir.exists() {
+        info!(
+            "Removing incomplete installation in {}",
+            forge_work_dir.display()
+        );
+        std::fs::remove_dir_all(forge_work_dir)?;
+    }
     std::fs::create_dir_all(forge_work_dir)?;
 
     let minecraft_version = &vanilla_metadata.id;
 
-    let lock_file = forge_work_dir.join("forge.lock");
-
-    if !lock_file.exists() {
-        let java_version = get_vanilla_java_version(vanilla_metadata)
-            .await?
-            .unwrap_or_else(|| {
-                warn!("Java version not found, using default");
-                "8".to_string()
-            });
-
-        info!("Getting java {}", &java_version);
-        let java_installation;
-        if let Some(existing_java_installation) = get_java(&java_version, java_dir).await {
-            java_installation = existing_java_installation;
-        } else {
-            info!("Java installation not found, downloading");
-
-            java_installation = download_java(&java_version, java_dir, progress_bar).await?;
-        }
-
-        info!("Downloading forge installer");
-        let full_version = match loader {
-            Loader::Forge => get_full_version(minecraft_version, forge_version),
-            Loader::Neoforge => forge_version.to_string(),
-        };
-        let forge_installer_path =
-            download_forge_installer(&full_version, forge_work_dir, loader).await?;
+    let java_version = vanilla_metadata.get_java_version();
 
-        trick_forge(forge_work_dir, minecraft_version)?;
-
-        run_forge_command(
-            &java_installation.path,
-            &forge_installer_path,
-            forge_work_dir,
-        )
-        .await?;
+    info!("Getting java {}", &java_version);
+    let java_guard = lock_work_dir(java_dir).await;
+    let java_installation;
+    if let Some(existing_java_installation) = get_java(&java_version, java_dir).await {
+        java_installation = existing_java_installation;
     } else {
-        info!("Forge {forge_version} already present, skipping installation");
+        info!("Java installation not found, downloading");
+
+        java_installation = download_java(&java_version, java_dir, progress_bar).await?;
     }
+    drop(java_guard);
 
-    let launcher_profiles_path = forge_work_dir.join("launcher_profiles.json");
-    let launcher_profiles_content = std::fs::read_to_string(&launcher_profiles_path)?;
-    let launcher_profiles: LauncherProfiles = serde_json::from_str(&launcher_profiles_content)?;
+    info!("Downloading forge installer");
+    let full_version = match loader {
+        Loader::Forge => get_full_version(minecraft_version, forge_version),
+        Loader::Neoforge => forge_version.to_string(),
+    };
+    let forge_installer_path =
+        download_forge_installer(&full_version, forge_work_dir, loader).await?;
 
-    let id = launcher_profiles
-        .profiles
-        .values()
-        .next()
-        .ok_or(ForgeError::NoForgeProfiles)?
-        .last_version_id
-        .clone();
+    trick_forge(forge_work_dir, minecraft_version)?;
+
+    run_forge_command(
+        &java_installation.path,
+        &forge_installer_path,
+        forge_work_dir,
+    )
+    .await?;
 
-    if !lock_file.exists() {
-        std::fs::File::create(lock_file)?;
+    let id = read_profile_id(forge_work_dir)?;
+    if !get_installed_metadata_path(forge_work_dir, &id).exists() {
+        return Err(ForgeError::MissingInstalledVersion(id).into());
     }
+    std::fs::File::create(forge_work_dir.join(INSTALLER_LOCK_FILE))?;
 
     Ok(id)
 }
@@ -506,9 +655,13 @@ impl VersionGenerator for ForgeGenerator {
 
         info!("Using {} version {}", self.loader, &forge_version);
 
-        let installer_work_dir = work_dir
-            .join(format!(".{:?}", self.loader))
-            .join(get_full_version(&minecraft_version, &forge_version));
+        let installer_work_dir = get_installer_work_dir(
+            work_dir,
+            &self.loader,
+            &get_full_version(&minecraft_version, &forge_version),
+        );
+        // held while copying too, the other version waits and reuses the install
+        let _install_guard = lock_work_dir(&installer_work_dir).await;
         let id = install_forge(
             &installer_work_dir,
             &get_java_dir(work_dir),
@@ -519,11 +672,10 @@ impl VersionGenerator for ForgeGenerator {
         )
         .await?;
 
-        let versions_dir_from = installer_work_dir.join("versions");
         let versions_dir_to = get_versions_dir(work_dir);
 
         info!("Copying version metadata");
-        let metadata_from = versions_dir_from.join(&id).join(format!("{id}.json"));
+        let metadata_from = get_installed_metadata_path(&installer_work_dir, &id);
         let metadata_to = get_metadata_path(&versions_dir_to, &id);
         std::fs::copy(metadata_from, metadata_to)?;
 
@@ -580,3 +732,149 @@ impl VersionGenerator for ForgeGenerator {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_metadata(versions: &[&str]) -> NeoforgeMavenMetadata {
+        NeoforgeMavenMetadata {
+            versioning: Versioning {
+                versions: Versions {
+                    version: versions.iter().map(|v| v.to_string()).collect(),
+                },
+            },
+        }
+    }
+
+    fn get_test_metadata() -> NeoforgeMavenMetadata {
+        make_metadata(&[
+            "20.2.3-beta",
+            "20.2.86",
+            "20.4.0-beta",
+            "20.4.80-beta",
+            "20.4.237",
+            "20.4.239",
+            "20.6.119",
+            "21.0.0-beta",
+            "21.0.10-beta",
+            "21.0.167",
+            "21.1.1",
+            "21.1.172",
+            "1.20.1-47.1.3",
+            "1.20.1-47.1.99",
+            "1.20.1-47.1.106",
+        ])
+    }
+
+    #[test]
+    fn test_neoforge_1_20_1() {
+        let metadata = get_test_metadata();
+        assert_eq!(
+            metadata.get_matching_versions("1.20.1"),
+            vec!["1.20.1-47.1.106", "1.20.1-47.1.99", "1.20.1-47.1.3"]
+        );
+        assert_eq!(
+            metadata.get_latest_matching_version("1.20.1").as_deref(),
+            Some("1.20.1-47.1.106")
+        );
+    }
+
+    #[test]
+    fn test_neoforge_1_20_4() {
+        let metadata = get_test_metadata();
+        assert_eq!(
+            metadata.get_matching_versions("1.20.4"),
+            vec!["20.4.239", "20.4.237", "20.4.80-beta", "20.4.0-beta"]
+        );
+        assert_eq!(
+            metadata.get_latest_matching_version("1.20.4").as_deref(),
+            Some("20.4.239")
+        );
+    }
+
+    #[test]
+    fn test_neoforge_1_21() {
+        let metadata = get_test_metadata();
+        assert_eq!(
+            metadata.get_matching_versions("1.21"),
+            vec!["21.0.167", "21.0.10-beta", "21.0.0-beta"]
+        );
+        assert_eq!(
+            metadata.get_latest_matching_version("1.21").as_deref(),
+            Some("21.0.167")
+        );
+    }
+
+    fn write_installer_output(forge_work_dir: &Path, id: &str) {
+        std::fs::create_dir_all(forge_work_dir.join("versions").join(id)).unwrap();
+        std::fs::write(
+            forge_work_dir.join("launcher_profiles.json"),
+            format!(r#"{{"profiles": {{"forge": {{"lastVersionId": "{id}"}}}}}}"#),
+        )
+        .unwrap();
+        std::fs::write(get_installed_metadata_path(forge_work_dir, id), "{}").unwrap();
+    }
+
+    #[test]
+    fn test_installer_work_dir_reuse() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let work_dir = temp_dir.path();
+        let id = "1.20.1-forge-47.3.0";
+        let forge_work_dir = get_installer_work_dir(work_dir, &Loader::Forge, "1.20.1-47.3.0");
+
+        // crashed after the installer ran, before the profile was read
+        write_installer_output(&forge_work_dir, id);
+        assert_eq!(get_installed_id(&forge_work_dir), None);
+        assert_eq!(
+            find_abandoned_installer_dirs(work_dir, Duration::ZERO).unwrap(),
+            vec![forge_work_dir.clone()]
+        );
+        assert!(
+            find_abandoned_installer_dirs(work_dir, ABANDONED_INSTALLER_DIR_AGE)
+                .unwrap()
+                .is_empty()
+        );
+
+        std::fs::File::create(forge_work_dir.join(INSTALLER_LOCK_FILE)).unwrap();
+        assert_eq!(get_installed_id(&forge_work_dir).as_deref(), Some(id));
+        assert!(find_abandoned_installer_dirs(work_dir, Duration::ZERO)
+            .unwrap()
+            .is_empty());
+
+        // locked, but the installed version is gone
+        std::fs::remove_file(get_installed_metadata_path(&forge_work_dir, id)).unwrap();
+        assert_eq!(get_installed_id(&forge_work_dir), None);
+
+        let neoforge_work_dir = get_installer_work_dir(work_dir, &Loader::Neoforge, "21.0.167");
+        std::fs::create_dir_all(&neoforge_work_dir).unwrap();
+        assert_eq!(
+            remove_abandoned_installer_dirs(work_dir, Duration::ZERO).unwrap(),
+            vec![neoforge_work_dir.clone()]
+        );
+        assert!(!neoforge_work_dir.exists());
+        assert!(forge_work_dir.exists());
+    }
+
+    #[tokio::test]
+    async fn test_lock_work_dir() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let work_dir = temp_dir.path();
+        let forge_work_dir = get_installer_work_dir(work_dir, &Loader::Forge, "1.20.1-47.3.0");
+
+        let guard = lock_work_dir(&forge_work_dir).await;
+        let waiter = tokio::spawn({
+            let forge_work_dir = forge_work_dir.clone();
+            async move { lock_work_dir(&forge_work_dir).await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!waiter.is_finished());
+
+        // other versions are installed at the same time
+        let neoforge_work_dir = get_installer_work_dir(work_dir, &Loader::Neoforge, "21.0.167");
+        drop(lock_work_dir(&neoforge_work_dir).await);
+
+        drop(guard);
+        drop(waiter.await.unwrap());
+    }
+}
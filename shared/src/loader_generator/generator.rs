@@ -11,6 +11,6 @@ pub struct GeneratorResult {
 }
 
 #[async_trait]
-pub trait VersionGenerator {
+pub trait VersionGenerator: Send + Sync {
     async fn generate(&self, work_dir: &Path) -> anyhow::Result<GeneratorResult>;
 }
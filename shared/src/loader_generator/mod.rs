@@ -1,4 +1,6 @@
 pub mod fabric;
 pub mod forge;
 pub mod generator;
+pub mod mrpack;
+pub mod url;
 pub mod vanilla;
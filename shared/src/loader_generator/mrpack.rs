@@ -0,0 +1,400 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use log::info;
+use serde::Deserialize;
+
+use crate::{
+    progress::NoProgressBar,
+    utils::{get_vanilla_version_info, is_safe_rel_path},
+    version::{
+        extra_version_metadata::{Include, Object},
+        version_manifest::VersionManifest,
+    },
+};
+
+use super::{
+    fabric::FabricGenerator,
+    forge::{ForgeGenerator, Loader},
+    generator::{GeneratorResult, VersionGenerator},
+    url::UrlGenerator,
+    vanilla::VanillaGenerator,
+};
+
+pub const MRPACK_INDEX_NAME: &str = "modrinth.index.json";
+
+// client-overrides are extracted last, so they win over overrides
+const OVERRIDES_DIRS: &[&str] = &["overrides", "client-overrides"];
+
+// quilt publishes fabric-like profiles that inherit from the vanilla version
+const QUILT_META_BASE_URL: &str = "https://meta.quiltmc.org/v3/versions/loader/";
+
+#[derive(thiserror::Error, Debug)]
+pub enum MrpackError {
+    #[error("Modpack is made for {0}, not minecraft")]
+    UnsupportedGame(String),
+    #[error("Unsupported modpack format version {0}")]
+    UnsupportedFormatVersion(u32),
+    #[error("Modpack doesn't specify the minecraft version")]
+    MissingMinecraftVersion,
+    #[error("Unsupported modpack dependency {0}")]
+    UnsupportedDependency(String),
+    #[error("Modpack requires more than one loader")]
+    MultipleLoaders,
+    #[error("Unsafe modpack file path {0}")]
+    UnsafePath(String),
+    #[error("Modpack file {0} has no download urls")]
+    NoDownloads(String),
+}
+
+#[derive(Deserialize)]
+struct MrpackHashes {
+    sha1: String,
+}
+
+#[derive(Deserialize)]
+struct MrpackEnv {
+    client: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MrpackFile {
+    path: String,
+    hashes: MrpackHashes,
+    #[serde(default)]
+    env: Option<MrpackEnv>,
+    downloads: Vec<String>,
+    file_size: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MrpackIndex {
+    format_version: u32,
+    game: String,
+    pub name: String,
+    pub version_id: String,
+    #[serde(default)]
+    pub summary: Option<String>,
+    files: Vec<MrpackFile>,
+    dependencies: HashMap<String, String>,
+}
+
+impl MrpackIndex {
+    pub fn read(pack_path: &Path) -> anyhow::Result<Self> {
+        let mut archive = zip::ZipArchive::new(File::open(pack_path)?)?;
+        let index: MrpackIndex = serde_json::from_reader(archive.by_name(MRPACK_INDEX_NAME)?)?;
+        if index.game != "minecraft" {
+            return Err(MrpackError::UnsupportedGame(index.game).into());
+        }
+        if index.format_version != 1 {
+            return Err(MrpackError::UnsupportedFormatVersion(index.format_version).into());
+        }
+        Ok(index)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum MrpackLoader {
+    Vanilla,
+    Fabric(String),
+    Quilt(String),
+    Forge(String),
+    Neoforge(String),
+}
+
+// the relative path is used as is under the minecraft dir
+fn check_rel_path(path: &str) -> Result<(), MrpackError> {
+    if path.is_empty() || !is_safe_rel_path(Path::new(path)) {
+        return Err(MrpackError::UnsafePath(path.to_string()));
+    }
+    Ok(())
+}
+
+pub struct MrpackGenerator {
+    version_name: String,
+    index: MrpackIndex,
+    vanilla_manifest: VersionManifest,
+}
+
+impl MrpackGenerator {
+    pub fn new(
+        version_name: String,
+        index: MrpackIndex,
+        vanilla_manifest: VersionManifest,
+    ) -> Self {
+        Self {
+            version_name,
+            index,
+            vanilla_manifest,
+        }
+    }
+
+    pub fn get_minecraft_version(&self) -> Result<&str, MrpackError> {
+        self.index
+            .dependencies
+            .get("minecraft")
+            .map(|version| version.as_str())
+            .ok_or(MrpackError::MissingMinecraftVersion)
+    }
+
+    pub fn get_loader(&self) -> Result<MrpackLoader, MrpackError> {
+        let mut loader = MrpackLoader::Vanilla;
+        for (name, version) in &self.index.dependencies {
+            let version = version.clone();
+            let dependency_loader = match name.as_str() {
+                "minecraft" => continue,
+                "fabric-loader" => MrpackLoader::Fabric(version),
+                "quilt-loader" => MrpackLoader::Quilt(version),
+                "forge" => MrpackLoader::Forge(version),
+                "neoforge" => MrpackLoader::Neoforge(version),
+                _ => return Err(MrpackError::UnsupportedDependency(name.clone())),
+            };
+            if loader != MrpackLoader::Vanilla {
+                return Err(MrpackError::MultipleLoaders);
+            }
+            loader = dependency_loader;
+        }
+        Ok(loader)
+    }
+
+    // one rule per top level directory, files added by the user next to them are kept
+    pub fn get_include(&self) -> Result<Vec<Include>, MrpackError> {
+        let mut include: BTreeMap<String, Include> = BTreeMap::new();
+        for file in &self.index.files {
+            if file
+                .env
+                .as_ref()
+                .is_some_and(|env| env.client == "unsupported")
+            {
+                continue;
+            }
+            check_rel_path(&file.path)?;
+            let url = file
+                .downloads
+                .first()
+                .ok_or_else(|| MrpackError::NoDownloads(file.path.clone()))?;
+            let rule_path = file.path.split('/').next().unwrap_or(&file.path);
+            include
+                .entry(rule_path.to_string())
+                .or_insert_with(|| Include {
+                    path: rule_path.to_string(),
+                    overwrite: true,
+                    delete_extra: false,
+                    recursive: false,
+                    objects: vec![],
+                })
+                .objects
+                .push(Object {
+                    path: file.path.clone(),
+                    sha1: file.hashes.sha1.clone(),
+                    url: url.clone(),
+                    size: Some(file.file_size),
+                });
+        }
+        Ok(include.into_values().collect())
+    }
+}
+
+#[async_trait]
+impl VersionGenerator for MrpackGenerator {
+    async fn generate(&self, work_dir: &Path) -> anyhow::Result<GeneratorResult> {
+        let minecraft_version = self.get_minecraft_version()?;
+        let loader = self.get_loader()?;
+        info!(
+            "Generating modpack {} {}, minecraft version {}, loader {:?}",
+            self.index.name, self.index.version_id, minecraft_version, loader
+        );
+
+        let vanilla_info = get_vanilla_version_info(&self.vanilla_manifest, minecraft_version)?;
+        let version_name = self.version_name.clone();
+        let generator: Box<dyn VersionGenerator + Send> = match loader {
+            MrpackLoader::Vanilla => Box::new(VanillaGenerator::new(version_name, vanilla_info)),
+            MrpackLoader::Fabric(loader_version) => Box::new(FabricGenerator::new(
+                version_name,
+                vanilla_info,
+                Some(loader_version),
+            )),
+            MrpackLoader::Quilt(loader_version) => Box::new(UrlGenerator::new(
+                version_name,
+                format!("{QUILT_META_BASE_URL}{minecraft_version}/{loader_version}/profile/json"),
+                self.vanilla_manifest.clone(),
+            )),
+            MrpackLoader::Forge(loader_version) => Box::new(ForgeGenerator::new(
+                version_name,
+                vanilla_info,
+                Loader::Forge,
+                Some(loader_version),
+                Arc::new(NoProgressBar),
+            )),
+            MrpackLoader::Neoforge(loader_version) => Box::new(ForgeGenerator::new(
+                version_name,
+                vanilla_info,
+                Loader::Neoforge,
+                Some(loader_version),
+                Arc::new(NoProgressBar),
+            )),
+        };
+        generator.generate(work_dir).await
+    }
+}
+
+// files of the overrides dirs are copied to the minecraft dir as is
+pub fn extract_overrides(pack_path: &Path, minecraft_dir: &Path) -> anyhow::Result<()> {
+    let mut archive = zip::ZipArchive::new(File::open(pack_path)?)?;
+    for overrides_dir in OVERRIDES_DIRS {
+        for i in 0..archive.len() {
+            let mut entry = archive.by_index(i)?;
+            let Some(entry_path) = entry.enclosed_name() else {
+                return Err(MrpackError::UnsafePath(entry.name().to_string()).into());
+            };
+            let Ok(rel_path) = entry_path.strip_prefix(overrides_dir) else {
+                continue;
+            };
+            if entry.is_symlink() {
+                return Err(MrpackError::UnsafePath(entry.name().to_string()).into());
+            }
+            let path = minecraft_dir.join(rel_path);
+            if entry.is_dir() {
+                fs::create_dir_all(&path)?;
+            } else {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                io::copy(&mut entry, &mut File::create(&path)?)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use zip::write::SimpleFileOptions;
+
+    use super::*;
+
+    fn write_pack(path: &Path, index: serde_json::Value) {
+        let mut zip = zip::ZipWriter::new(File::create(path).unwrap());
+        let options = SimpleFileOptions::default();
+        zip.start_file(MRPACK_INDEX_NAME, options).unwrap();
+        zip.write_all(index.to_string().as_bytes()).unwrap();
+        for (name, data) in [
+            ("overrides/config/mod.toml", "overrides"),
+            ("overrides/options.txt", "lang:en_us"),
+            ("client-overrides/config/mod.toml", "client"),
+            ("README.md", "not extracted"),
+        ] {
+            zip.start_file(name, options).unwrap();
+            zip.write_all(data.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    fn file(path: &str, client: &str) -> serde_json::Value {
+        serde_json::json!({
+            "path": path,
+            "hashes": {"sha1": format!("sha1 of {path}"), "sha512": "unused"},
+            "env": {"client": client, "server": "required"},
+            "downloads": [format!("https://cdn.modrinth.com/{path}")],
+            "fileSize": 10,
+        })
+    }
+
+    #[test]
+    fn test_mrpack() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        let pack_path = dir.join("pack.mrpack");
+        write_pack(
+            &pack_path,
+            serde_json::json!({
+                "formatVersion": 1,
+                "game": "minecraft",
+                "versionId": "1.0.0",
+                "name": "Pack",
+                "files": [
+                    file("mods/sodium.jar", "required"),
+                    file("mods/server-only.jar", "unsupported"),
+                    file("resourcepacks/pack.zip", "optional"),
+                ],
+                "dependencies": {"minecraft": "1.20.1", "fabric-loader": "0.15.0"},
+            }),
+        );
+
+        let index = MrpackIndex::read(&pack_path).unwrap();
+        assert_eq!(index.name, "Pack");
+        let generator = MrpackGenerator::new(
+            "Pack".to_string(),
+            index,
+            VersionManifest { versions: vec![] },
+        );
+        assert_eq!(generator.get_minecraft_version().unwrap(), "1.20.1");
+        assert_eq!(
+            generator.get_loader().unwrap(),
+            MrpackLoader::Fabric("0.15.0".to_string())
+        );
+
+        let include = generator.get_include().unwrap();
+        let paths: Vec<_> = include
+            .iter()
+            .map(|include| {
+                let objects: Vec<_> = include.objects.iter().map(|o| o.path.as_str()).collect();
+                (include.path.as_str(), objects)
+            })
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                ("mods", vec!["mods/sodium.jar"]),
+                ("resourcepacks", vec!["resourcepacks/pack.zip"]),
+            ]
+        );
+        let object = &include[0].objects[0];
+        assert_eq!(object.url, "https://cdn.modrinth.com/mods/sodium.jar");
+        assert_eq!(object.sha1, "sha1 of mods/sodium.jar");
+        assert_eq!(object.size, Some(10));
+
+        let minecraft_dir = dir.join("minecraft");
+        extract_overrides(&pack_path, &minecraft_dir).unwrap();
+        assert_eq!(
+            fs::read_to_string(minecraft_dir.join("config/mod.toml")).unwrap(),
+            "client"
+        );
+        assert!(minecraft_dir.join("options.txt").exists());
+        assert!(!minecraft_dir.join("README.md").exists());
+
+        let unsafe_pack_path = dir.join("unsafe.mrpack");
+        write_pack(
+            &unsafe_pack_path,
+            serde_json::json!({
+                "formatVersion": 1,
+                "game": "minecraft",
+                "versionId": "1.0.0",
+                "name": "Unsafe",
+                "files": [file("../mods/evil.jar", "required")],
+                "dependencies": {"minecraft": "1.20.1", "forge": "47.2.0", "neoforge": "47.1.0"},
+            }),
+        );
+        let generator = MrpackGenerator::new(
+            "Unsafe".to_string(),
+            MrpackIndex::read(&unsafe_pack_path).unwrap(),
+            VersionManifest { versions: vec![] },
+        );
+        assert!(matches!(
+            generator.get_include(),
+            Err(MrpackError::UnsafePath(_))
+        ));
+        assert!(matches!(
+            generator.get_loader(),
+            Err(MrpackError::MultipleLoaders)
+        ));
+    }
+}
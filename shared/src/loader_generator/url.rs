@@ -0,0 +1,190 @@
+use std::path::Path;
+
+use crate::{
+    compression,
+    paths::get_versions_dir,
+    version::{version_manifest::VersionManifest, version_metadata::VersionMetadata},
+};
+use async_trait::async_trait;
+use log::info;
+use serde_json::Value;
+
+use super::generator::{GeneratorResult, VersionGenerator};
+
+#[derive(thiserror::Error, Debug)]
+pub enum UrlGeneratorError {
+    #[error("Version metadata is missing required fields: {}", .0.join(", "))]
+    IncompleteMetadata(Vec<String>),
+    #[error("Inherited version {0} not found in the vanilla manifest")]
+    InheritedVersionNotFound(String),
+    #[error("Version id {0} is not a valid file name")]
+    InvalidId(String),
+    #[error("Version id {0} is the id of a vanilla version")]
+    VanillaId(String),
+}
+
+// the metadata is saved as versions/<id>/<id>.json next to the vanilla ones,
+// so the id must not escape the versions dir or overwrite a vanilla version
+fn check_id(id: &str, vanilla_manifest: &VersionManifest) -> Result<(), UrlGeneratorError> {
+    if id.is_empty() || id == "." || id.contains("..") || id.contains(['/', '\\']) {
+        return Err(UrlGeneratorError::InvalidId(id.to_string()));
+    }
+    if vanilla_manifest.versions.iter().any(|v| v.id == id) {
+        return Err(UrlGeneratorError::VanillaId(id.to_string()));
+    }
+    Ok(())
+}
+
+// inherited metadata gets the client, assets and arguments from the vanilla parent
+fn get_missing_fields(metadata: &Value) -> Vec<String> {
+    let has_field = |pointer: &str| metadata.pointer(pointer).is_some_and(|v| !v.is_null());
+
+    let mut missing_fields = vec![];
+    for (pointer, name) in [
+        ("/id", "id"),
+        ("/mainClass", "mainClass"),
+        ("/libraries", "libraries"),
+    ] {
+        if !has_field(pointer) {
+            missing_fields.push(name.to_string());
+        }
+    }
+    if !has_field("/inheritsFrom") {
+        for (pointer, name) in [
+            ("/downloads/client", "downloads.client"),
+            ("/assetIndex", "assetIndex"),
+        ] {
+            if !has_field(pointer) {
+                missing_fields.push(name.to_string());
+            }
+        }
+        if !has_field("/arguments") && !has_field("/minecraftArguments") {
+            missing_fields.push("arguments".to_string());
+        }
+    }
+    missing_fields
+}
+
+pub struct UrlGenerator {
+    version_name: String,
+    metadata_url: String,
+    vanilla_manifest: VersionManifest,
+}
+
+impl UrlGenerator {
+    pub fn new(
+        version_name: String,
+        metadata_url: String,
+        vanilla_manifest: VersionManifest,
+    ) -> Self {
+        Self {
+            version_name,
+            metadata_url,
+            vanilla_manifest,
+        }
+    }
+}
+
+#[async_trait]
+impl VersionGenerator for UrlGenerator {
+    async fn generate(&self, work_dir: &Path) -> anyhow::Result<GeneratorResult> {
+        info!(
+            "Generating version \"{}\" from {}",
+            self.version_name, self.metadata_url
+        );
+
+        let client = reqwest::Client::new();
+        let data = compression::fetch_bytes(&client, &self.metadata_url).await?;
+        let metadata: Value = serde_json::from_slice(&data)?;
+        let missing_fields = get_missing_fields(&metadata);
+        if !missing_fields.is_empty() {
+            return Err(UrlGeneratorError::IncompleteMetadata(missing_fields).into());
+        }
+        let metadata: VersionMetadata = serde_json::from_value(metadata)?;
+        check_id(&metadata.id, &self.vanilla_manifest)?;
+
+        let versions_dir = get_versions_dir(work_dir);
+        let mut result_metadata = vec![];
+        if let Some(parent_id) = &metadata.inherits_from {
+            let parent_info = self
+                .vanilla_manifest
+                .versions
+                .iter()
+                .find(|v| &v.id == parent_id)
+                .ok_or_else(|| UrlGeneratorError::InheritedVersionNotFound(parent_id.clone()))?;
+
+            info!("Downloading inherited version metadata");
+            let parent_metadata = VersionMetadata::read_or_download(
+                &parent_info.get_parent_metadata_info(),
+                &versions_dir,
+            )
+            .await?;
+            result_metadata.push(parent_metadata);
+        }
+
+        metadata.save(&versions_dir).await?;
+        result_metadata.push(metadata);
+
+        info!("Version \"{}\" generated", self.version_name);
+
+        Ok(GeneratorResult {
+            metadata: result_metadata,
+            extra_libs_paths: vec![],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_fields() {
+        let complete = serde_json::json!({
+            "id": "1.20.1",
+            "mainClass": "net.minecraft.client.main.Main",
+            "libraries": [],
+            "downloads": {"client": {"url": "https://example.com/client.jar", "sha1": "0"}},
+            "assetIndex": {"id": "5", "url": "https://example.com/5.json", "sha1": "0"},
+            "minecraftArguments": "--username ${auth_player_name}"
+        });
+        assert!(get_missing_fields(&complete).is_empty());
+
+        let incomplete = serde_json::json!({
+            "id": "1.20.1",
+            "libraries": [],
+            "downloads": {}
+        });
+        assert_eq!(
+            get_missing_fields(&incomplete),
+            vec!["mainClass", "downloads.client", "assetIndex", "arguments"]
+        );
+
+        let inherited = serde_json::json!({
+            "id": "fabric-loader-0.15.11-1.20.1",
+            "inheritsFrom": "1.20.1",
+            "mainClass": "net.fabricmc.loader.impl.launch.knot.KnotClient",
+            "libraries": []
+        });
+        assert!(get_missing_fields(&inherited).is_empty());
+    }
+
+    #[test]
+    fn test_check_id() {
+        let vanilla_manifest: VersionManifest = serde_json::from_value(serde_json::json!({
+            "versions": [{"id": "1.20.1", "url": "https://example.com/1.20.1.json", "sha1": "0"}]
+        }))
+        .unwrap();
+        assert!(check_id("fabric-loader-0.15.11-1.20.1", &vanilla_manifest).is_ok());
+        assert!(matches!(
+            check_id("1.20.1", &vanilla_manifest),
+            Err(UrlGeneratorError::VanillaId(_))
+        ));
+        for id in ["", ".", "..", "../1.20.1", "a/b", "a\\b", "a..b"] {
+            assert!(matches!(
+                check_id(id, &vanilla_manifest),
+                Err(UrlGeneratorError::InvalidId(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+pub const DEFAULT_PORT: u16 = 25565;
+
+// any protocol version is fine for a status request
+const PROTOCOL_VERSION: i32 = -1;
+const STATUS_NEXT_STATE: i32 = 1;
+const MAX_RESPONSE_SIZE: usize = 1024 * 1024;
+
+#[derive(thiserror::Error, Debug)]
+pub enum PingError {
+    #[error("Invalid server address {0}")]
+    InvalidAddress(String),
+    #[error("Invalid server status response")]
+    InvalidResponse,
+    #[error("Server status request timed out")]
+    Timeout,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct Players {
+    pub online: u32,
+    pub max: u32,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct ServerStatus {
+    pub players: Players,
+}
+
+// host[:port], srv records are not resolved
+fn parse_address(address: &str) -> Result<(&str, u16), PingError> {
+    let invalid = || PingError::InvalidAddress(address.to_string());
+    let (host, port) = match address.rsplit_once(':') {
+        Some((host, port)) => (host, port.parse().map_err(|_| invalid())?),
+        None => (address, DEFAULT_PORT),
+    };
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    if host.is_empty() {
+        return Err(invalid());
+    }
+    Ok((host, port))
+}
+
+fn write_varint(buf: &mut Vec<u8>, value: i32) {
+    let mut value = value as u32;
+    loop {
+        if value & !0x7f == 0 {
+            buf.push(value as u8);
+            return;
+        }
+        buf.push((value & 0x7f | 0x80) as u8);
+        value >>= 7;
+    }
+}
+
+async fn read_varint(reader: &mut (impl AsyncRead + Unpin)) -> anyhow::Result<i32> {
+    let mut value = 0u32;
+    for i in 0..5 {
+        let byte = reader.read_u8().await?;
+        value |= ((byte & 0x7f) as u32) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok(value as i32);
+        }
+    }
+    Err(PingError::InvalidResponse.into())
+}
+
+fn get_packet(id: i32, data: &[u8]) -> Vec<u8> {
+    let mut body = vec![];
+    write_varint(&mut body, id);
+    body.extend_from_slice(data);
+    let mut packet = vec![];
+    write_varint(&mut packet, body.len() as i32);
+    packet.extend(body);
+    packet
+}
+
+async fn request_status(host: &str, port: u16) -> anyhow::Result<ServerStatus> {
+    let mut stream = TcpStream::connect((host, port)).await?;
+
+    let mut handshake = vec![];
+    write_varint(&mut handshake, PROTOCOL_VERSION);
+    write_varint(&mut handshake, host.len() as i32);
+    handshake.extend_from_slice(host.as_bytes());
+    handshake.extend_from_slice(&port.to_be_bytes());
+    write_varint(&mut handshake, STATUS_NEXT_STATE);
+    stream.write_all(&get_packet(0, &handshake)).await?;
+    stream.write_all(&get_packet(0, &[])).await?;
+
+    let mut reader = BufReader::new(stream);
+    let _packet_length = read_varint(&mut reader).await?;
+    if read_varint(&mut reader).await? != 0 {
+        return Err(PingError::InvalidResponse.into());
+    }
+    let json_length = usize::try_from(read_varint(&mut reader).await?)
+        .ok()
+        .filter(|length| *length <= MAX_RESPONSE_SIZE)
+        .ok_or(PingError::InvalidResponse)?;
+    let mut json = vec![0; json_length];
+    reader.read_exact(&mut json).await?;
+    Ok(serde_json::from_slice(&json)?)
+}
+
+// server list ping, the same request the multiplayer screen sends
+pub async fn ping(address: &str, timeout: Duration) -> anyhow::Result<ServerStatus> {
+    let (host, port) = parse_address(address)?;
+    tokio::time::timeout(timeout, request_status(host, port))
+        .await
+        .map_err(|_| PingError::Timeout)?
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_address() {
+        assert_eq!(
+            parse_address("mc.example.com").unwrap(),
+            ("mc.example.com", DEFAULT_PORT)
+        );
+        assert_eq!(
+            parse_address("mc.example.com:25566").unwrap(),
+            ("mc.example.com", 25566)
+        );
+        assert_eq!(parse_address("[::1]:25566").unwrap(), ("::1", 25566));
+        assert!(parse_address("mc.example.com:port").is_err());
+        assert!(parse_address(":25565").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_ping() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut reader = BufReader::new(stream);
+            // handshake
+            let length = read_varint(&mut reader).await.unwrap();
+            let mut handshake = vec![0; length as usize];
+            reader.read_exact(&mut handshake).await.unwrap();
+            assert_eq!(*handshake.last().unwrap(), STATUS_NEXT_STATE as u8);
+            // status request
+            assert_eq!(read_varint(&mut reader).await.unwrap(), 1);
+            assert_eq!(read_varint(&mut reader).await.unwrap(), 0);
+
+            let json = r#"{"version":{"name":"1.20.1","protocol":763},"players":{"max":20,"online":3},"description":"A server"}"#;
+            let mut data = vec![];
+            write_varint(&mut data, json.len() as i32);
+            data.extend_from_slice(json.as_bytes());
+            reader
+                .get_mut()
+                .write_all(&get_packet(0, &data))
+                .await
+                .unwrap();
+        });
+
+        let status = ping(&format!("127.0.0.1:{port}"), Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(status.players, Players { online: 3, max: 20 });
+        server.await.unwrap();
+    }
+}
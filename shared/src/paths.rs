@@ -55,10 +55,79 @@ pub fn get_instance_meta_path(data_dir: &Path, dir_name: &str) -> PathBuf {
     parent_created(get_instance_dir(data_dir, dir_name).join("meta.json"))
 }
 
+pub fn get_natives_key_path(data_dir: &Path, dir_name: &str) -> PathBuf {
+    parent_created(get_instance_dir(data_dir, dir_name).join("natives_key"))
+}
+
+pub fn get_running_lock_path(data_dir: &Path, dir_name: &str) -> PathBuf {
+    parent_created(get_instance_dir(data_dir, dir_name).join("running.lock"))
+}
+
+pub fn get_playtime_path(data_dir: &Path, dir_name: &str) -> PathBuf {
+    parent_created(get_instance_dir(data_dir, dir_name).join("playtime.json"))
+}
+
+pub fn get_archives_dir(data_dir: &Path) -> PathBuf {
+    created(data_dir.join("archives"))
+}
+
+// compressed game files of an archived instance
+pub fn get_instance_archive_path(data_dir: &Path, dir_name: &str) -> PathBuf {
+    get_archives_dir(data_dir).join(format!("{dir_name}.tar.zst"))
+}
+
+pub fn get_description_cache_path(data_dir: &Path, version_name: &str) -> PathBuf {
+    parent_created(
+        data_dir
+            .join("descriptions")
+            .join(format!("{version_name}.md")),
+    )
+}
+
+pub fn get_screenshot_thumbnails_dir(data_dir: &Path, version_name: &str) -> PathBuf {
+    created(data_dir.join("screenshot_thumbnails").join(version_name))
+}
+
+// keyed by the account uuid
+pub fn get_skin_cache_path(data_dir: &Path, uuid: &str) -> PathBuf {
+    parent_created(data_dir.join("skins").join(format!("{uuid}.png")))
+}
+
+pub fn get_library_overrides_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("library_overrides.json")
+}
+
+pub fn get_library_overrides_example_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("library_overrides.example.json")
+}
+
+pub fn get_dedup_database_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("dedup.json")
+}
+
+pub fn get_hash_cache_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("hash_cache.json")
+}
+
+// downloaded files by sha1, linked into the instances
+pub fn get_file_store_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join("store")
+}
+
+// the last fetched manifest of every manifest url
+pub fn get_manifest_cache_dir(data_dir: &Path) -> PathBuf {
+    created(data_dir.join("manifest_cache"))
+}
+
 pub fn get_auth_data_path(data_dir: &Path) -> PathBuf {
     parent_created(data_dir.join("auth_data.json"))
 }
 
+// used instead of auth_data.json when the encryption is on
+pub fn get_encrypted_auth_data_path(data_dir: &Path) -> PathBuf {
+    parent_created(data_dir.join("auth_data.enc"))
+}
+
 pub fn get_java_dir(data_dir: &Path) -> PathBuf {
     created(data_dir.join("java"))
 }
@@ -67,12 +136,25 @@ pub fn get_logs_dir(data_dir: &Path) -> PathBuf {
     created(data_dir.join("logs"))
 }
 
+// stdout and stderr of the last launch of the instance, so running instances don't share it
+pub fn get_launch_log_path(data_dir: &Path, dir_name: &str) -> PathBuf {
+    parent_created(
+        get_logs_dir(data_dir)
+            .join("instances")
+            .join(format!("{dir_name}.log")),
+    )
+}
+
 pub fn get_libraries_dir(data_dir: &Path) -> PathBuf {
     created(data_dir.join("libraries"))
 }
 
-pub fn get_natives_dir(data_dir: &Path, id: &str) -> PathBuf {
-    created(data_dir.join("natives").join(id))
+pub fn get_natives_root_dir(data_dir: &Path) -> PathBuf {
+    created(data_dir.join("natives"))
+}
+
+pub fn get_natives_dir(data_dir: &Path, natives_key: &str) -> PathBuf {
+    created(get_natives_root_dir(data_dir).join(natives_key))
 }
 
 pub fn get_rel_versions_dir() -> PathBuf {
@@ -127,8 +209,13 @@ pub fn get_assets_object_path(assets_dir: &Path) -> PathBuf {
     created(assets_dir.join("objects"))
 }
 
-const AUTHLIB_INJECTOR_NAME: &str = "authlib-injector.jar";
+const AUTHLIB_INJECTOR_DIR: &str = "authlib-injector";
 
-pub fn get_authlib_injector_path(data_dir: &Path) -> PathBuf {
-    parent_created(get_libraries_dir(data_dir).join(AUTHLIB_INJECTOR_NAME))
+// one file per build, instances with different builds don't redownload each other's
+pub fn get_authlib_injector_path(data_dir: &Path, sha1: &str) -> PathBuf {
+    parent_created(
+        get_libraries_dir(data_dir)
+            .join(AUTHLIB_INJECTOR_DIR)
+            .join(format!("authlib-injector-{sha1}.jar")),
+    )
 }
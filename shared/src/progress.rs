@@ -1,6 +1,8 @@
 use futures::stream::{FuturesUnordered, StreamExt};
+use serde::{Deserialize, Serialize};
 use std::future::Future;
-use std::sync::Arc;
+use std::io::Write;
+use std::sync::{Arc, LazyLock, Mutex};
 
 #[derive(Clone)]
 pub struct Unit {
@@ -15,6 +17,9 @@ pub trait ProgressBar<M>: Sync + Send {
 
     fn inc(&self, amount: u64);
 
+    // bytes processed by the tasks, only used to show the throughput
+    fn inc_bytes(&self, _bytes: u64) {}
+
     fn finish(&self);
 
     fn reset(&self) {
@@ -42,6 +47,188 @@ pub fn no_progress_bar() -> Arc<dyn ProgressBar<i32> + Send + Sync> {
     Arc::new(NoProgressBar)
 }
 
+const BAR_TEMPLATE: &str = "{msg} {bar:40.cyan/blue} {pos}/{len}";
+const BYTES_BAR_TEMPLATE: &str = "{msg} {bar:40.cyan/blue} {bytes}/{total_bytes}";
+
+// prefixed bars belong to concurrent tasks, each one is drawn on its own line
+static MULTI_PROGRESS: LazyLock<indicatif::MultiProgress> =
+    LazyLock::new(indicatif::MultiProgress::new);
+
+fn get_bar_style(template: &str, prefixed: bool) -> indicatif::ProgressStyle {
+    let template = if prefixed {
+        format!("[{{prefix}}] {template}")
+    } else {
+        template.to_string()
+    };
+    indicatif::ProgressStyle::default_bar()
+        .template(&template)
+        .unwrap()
+}
+
+pub struct TerminalProgressBar {
+    bar: indicatif::ProgressBar,
+}
+
+impl TerminalProgressBar {
+    pub fn new() -> Self {
+        let bar = indicatif::ProgressBar::new(0);
+        bar.set_style(get_bar_style(BAR_TEMPLATE, false));
+        Self { bar }
+    }
+
+    pub fn with_prefix(prefix: &str) -> Self {
+        let bar = MULTI_PROGRESS.add(indicatif::ProgressBar::new(0));
+        bar.set_style(get_bar_style(BAR_TEMPLATE, true));
+        bar.set_prefix(prefix.to_string());
+        Self { bar }
+    }
+
+    // printed above the bar, also when the output isn't a terminal and the bar is hidden
+    pub fn println(&self, line: &str) {
+        self.bar.suspend(|| println!("{line}"));
+    }
+}
+
+impl Default for TerminalProgressBar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProgressBar<&str> for TerminalProgressBar {
+    fn set_message(&self, message: &str) {
+        self.bar.set_message(message.to_string());
+    }
+
+    fn set_length(&self, length: u64) {
+        self.bar.set_length(length);
+    }
+
+    fn inc(&self, amount: u64) {
+        self.bar.inc(amount);
+    }
+
+    fn finish(&self) {
+        self.bar.finish();
+    }
+
+    fn reset(&self) {
+        self.bar.set_length(0);
+        self.bar.set_position(0);
+    }
+
+    // units are only set for byte counts, indicatif formats those itself
+    fn set_unit(&self, _: Unit) {
+        self.bar.set_style(get_bar_style(
+            BYTES_BAR_TEMPLATE,
+            !self.bar.prefix().is_empty(),
+        ));
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ProgressFormat {
+    Human,
+    Json,
+}
+
+// a single line of the json progress output
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct ProgressEvent {
+    pub phase: String,
+    pub current: u64,
+    pub total: u64,
+    pub message: String,
+}
+
+impl ProgressEvent {
+    pub fn new(phase: &str, current: u64, total: u64, message: &str) -> Self {
+        Self {
+            phase: phase.to_string(),
+            current,
+            total,
+            message: message.to_string(),
+        }
+    }
+
+    fn write_line(&self, writer: &mut dyn Write) {
+        if let Ok(line) = serde_json::to_string(self) {
+            let _ = writeln!(writer, "{line}");
+            let _ = writer.flush();
+        }
+    }
+}
+
+// only emitted in json mode, human output relies on logs
+pub fn emit_progress_event(format: ProgressFormat, event: ProgressEvent) {
+    if format == ProgressFormat::Json {
+        event.write_line(&mut std::io::stdout().lock());
+    }
+}
+
+pub struct JsonProgressBar {
+    state: Mutex<ProgressEvent>,
+    writer: Mutex<Box<dyn Write + Send>>,
+}
+
+impl JsonProgressBar {
+    pub fn new(phase: &str) -> Self {
+        Self::with_writer(phase, Box::new(std::io::stdout()))
+    }
+
+    pub fn with_writer(phase: &str, writer: Box<dyn Write + Send>) -> Self {
+        Self {
+            state: Mutex::new(ProgressEvent::new(phase, 0, 0, "")),
+            writer: Mutex::new(writer),
+        }
+    }
+
+    fn update(&self, f: impl FnOnce(&mut ProgressEvent)) {
+        let mut state = self.state.lock().unwrap();
+        f(&mut state);
+        state.write_line(&mut **self.writer.lock().unwrap());
+    }
+}
+
+impl ProgressBar<&str> for JsonProgressBar {
+    fn set_message(&self, message: &str) {
+        self.update(|state| state.message = message.to_string());
+    }
+
+    fn set_length(&self, length: u64) {
+        self.update(|state| state.total = length);
+    }
+
+    fn inc(&self, amount: u64) {
+        self.update(|state| state.current += amount);
+    }
+
+    fn finish(&self) {
+        self.update(|state| state.current = state.total);
+    }
+
+    fn reset(&self) {
+        self.update(|state| {
+            state.current = 0;
+            state.total = 0;
+        });
+    }
+
+    fn set_unit(&self, _: Unit) {}
+}
+
+// the prefix tells apart the bars of versions generated concurrently
+pub fn terminal_progress_bar(
+    format: ProgressFormat,
+    phase: &str,
+    prefix: &str,
+) -> Arc<dyn ProgressBar<&'static str>> {
+    match format {
+        ProgressFormat::Human => Arc::new(TerminalProgressBar::with_prefix(prefix)),
+        ProgressFormat::Json => Arc::new(JsonProgressBar::new(phase)),
+    }
+}
+
 async fn create_indexed_task<T, Fut>(index: usize, task: Fut) -> (usize, anyhow::Result<T>)
 where
     Fut: Future<Output = anyhow::Result<T>>,
@@ -253,6 +440,48 @@ mod tests {
         assert_eq!(progress_bar.get_finished_count(), 1);
     }
 
+    #[derive(Clone)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_json_progress_events() {
+        let buffer = SharedBuffer(Arc::new(Mutex::new(vec![])));
+        let progress_bar = JsonProgressBar::with_writer("sync", Box::new(buffer.clone()));
+
+        progress_bar.set_message("Downloading files...");
+        progress_bar.set_length(3);
+        progress_bar.inc(1);
+        progress_bar.inc(1);
+        progress_bar.finish();
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let events: Vec<ProgressEvent> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(events.len(), 5);
+        assert_eq!(
+            events[0],
+            ProgressEvent::new("sync", 0, 0, "Downloading files...")
+        );
+        assert_eq!(
+            events[3],
+            ProgressEvent::new("sync", 2, 3, "Downloading files...")
+        );
+        assert_eq!(events[4].current, 3);
+    }
+
     #[tokio::test]
     async fn test_order_preservation_with_different_completion_times() {
         let progress_bar = Arc::new(TestProgressBar::new());
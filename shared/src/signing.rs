@@ -0,0 +1,101 @@
+use std::path::{Path, PathBuf};
+
+use ed25519_dalek::{Signature, Signer as _, SigningKey, Verifier as _, VerifyingKey};
+use reqwest::Client;
+
+#[derive(thiserror::Error, Debug)]
+pub enum SigningError {
+    #[error("Invalid key")]
+    InvalidKey,
+    #[error("Invalid signature format")]
+    InvalidSignatureFormat,
+    #[error("Signature not found")]
+    MissingSignature,
+    #[error("Signature verification failed")]
+    VerificationFailed,
+}
+
+pub fn get_signature_path(path: &Path) -> PathBuf {
+    let mut signature_path = path.as_os_str().to_owned();
+    signature_path.push(".sig");
+    PathBuf::from(signature_path)
+}
+
+pub fn get_signature_url(url: &str) -> String {
+    format!("{url}.sig")
+}
+
+// keys and signatures are stored as hex strings
+fn decode_hex<const N: usize>(value: &str) -> Option<[u8; N]> {
+    hex::decode(value.trim()).ok()?.try_into().ok()
+}
+
+pub fn read_signing_key(value: &str) -> anyhow::Result<SigningKey> {
+    let seed = decode_hex::<32>(value).ok_or(SigningError::InvalidKey)?;
+    Ok(SigningKey::from_bytes(&seed))
+}
+
+pub fn get_public_key(signing_key: &SigningKey) -> String {
+    hex::encode(signing_key.verifying_key().to_bytes())
+}
+
+pub async fn sign_file(path: &Path, signing_key: &SigningKey) -> anyhow::Result<()> {
+    let data = tokio::fs::read(path).await?;
+    let signature = signing_key.sign(&data);
+    tokio::fs::write(get_signature_path(path), hex::encode(signature.to_bytes())).await?;
+    Ok(())
+}
+
+pub fn verify(data: &[u8], signature: &str, public_key: &str) -> anyhow::Result<()> {
+    let public_key = decode_hex::<32>(public_key).ok_or(SigningError::InvalidKey)?;
+    let public_key = VerifyingKey::from_bytes(&public_key).map_err(|_| SigningError::InvalidKey)?;
+    let signature = decode_hex::<64>(signature).ok_or(SigningError::InvalidSignatureFormat)?;
+    public_key
+        .verify(data, &Signature::from_bytes(&signature))
+        .map_err(|_| SigningError::VerificationFailed)?;
+    Ok(())
+}
+
+pub async fn verify_file(path: &Path, public_key: &str) -> anyhow::Result<()> {
+    let data = tokio::fs::read(path).await?;
+    let signature = tokio::fs::read_to_string(get_signature_path(path))
+        .await
+        .map_err(|_| SigningError::MissingSignature)?;
+    verify(&data, &signature, public_key)
+}
+
+pub async fn fetch_signature(url: &str) -> anyhow::Result<String> {
+    let client = Client::new();
+    let response = client.get(get_signature_url(url)).send().await?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Err(SigningError::MissingSignature.into());
+    }
+    Ok(response.error_for_status()?.text().await?)
+}
+
+pub fn is_signature_error(e: &anyhow::Error) -> bool {
+    e.downcast_ref::<SigningError>().is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEED: &str = "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60";
+
+    #[test]
+    fn test_sign_and_verify() {
+        let signing_key = read_signing_key(SEED).unwrap();
+        let public_key = get_public_key(&signing_key);
+        let data = br#"{"versions": []}"#;
+        let signature = hex::encode(signing_key.sign(data).to_bytes());
+
+        assert!(verify(data, &signature, &public_key).is_ok());
+
+        let err = verify(br#"{"versions": [{}]}"#, &signature, &public_key).unwrap_err();
+        assert!(is_signature_error(&err));
+
+        let err = verify(data, "not a signature", &public_key).unwrap_err();
+        assert!(is_signature_error(&err));
+    }
+}
@@ -1,4 +1,6 @@
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
+
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
 
 use crate::version::version_manifest::{VersionInfo, VersionManifest};
 
@@ -23,11 +25,68 @@ pub fn get_vanilla_version_info(
     Ok(version_info.clone())
 }
 
+// characters that can't appear as is in a path segment of a url
+const PATH_SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'/')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'[')
+    .add(b'\\')
+    .add(b']')
+    .add(b'^')
+    .add(b'`')
+    .add(b'{')
+    .add(b'|')
+    .add(b'}');
+
+#[derive(thiserror::Error, Debug)]
+#[error("Path {0:?} is not valid unicode")]
+pub struct NonUnicodePathError(pub PathBuf);
+
+// for places that need a string, a lossy path would point to another file
+pub fn path_to_str(path: &Path) -> Result<&str, NonUnicodePathError> {
+    path.to_str()
+        .ok_or_else(|| NonUnicodePathError(path.to_path_buf()))
+}
+
+// relative path with forward slashes, as stored in metadata
+pub fn path_to_slash_str(path: &Path) -> Result<String, NonUnicodePathError> {
+    Ok(path_to_str(path)?.replace('\\', "/"))
+}
+
+// false if joining the path could end up outside of the dir it's joined to
+pub fn is_safe_rel_path(rel_path: &Path) -> bool {
+    rel_path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
+
+// for paths read from archives and metadata, None if the path would end up outside of dir
+pub fn get_safe_path(dir: &Path, rel_path: &Path) -> Option<PathBuf> {
+    if !is_safe_rel_path(rel_path) {
+        return None;
+    }
+    let mut path = dir.to_path_buf();
+    for component in rel_path.components() {
+        if let Component::Normal(part) = component {
+            path.push(part);
+        }
+    }
+    Some(path)
+}
+
+// paths are kept as is everywhere else, they are only encoded here
 pub fn url_from_rel_path(rel_path: &Path, download_server_base: &str) -> anyhow::Result<String> {
-    let path_str = rel_path
-        .to_string_lossy()
-        .replace('\\', "/")
-        .replace('%', "%25");
+    let path_str = path_to_slash_str(rel_path)?
+        .split('/')
+        .map(|segment| utf8_percent_encode(segment, PATH_SEGMENT).to_string())
+        .collect::<Vec<_>>()
+        .join("/");
 
     Ok(format!(
         "{}/{}",
@@ -45,6 +104,12 @@ pub fn url_from_path(
     url_from_rel_path(rel_path, download_server_base)
 }
 
+// a certificate that isn't valid yet means the local clock is behind
+pub fn is_clock_skew_error(e: &anyhow::Error) -> bool {
+    let error_str = format!("{e:?}");
+    error_str.contains("NotValidYet") || error_str.contains("certificate not valid yet")
+}
+
 pub fn is_connect_error(e: &anyhow::Error) -> bool {
     if let Some(e) = e.downcast_ref::<reqwest::Error>() {
         return e.is_connect() || e.status().is_some_and(|s| s.as_u16() == 523);
@@ -64,3 +129,45 @@ pub fn is_connect_error(e: &anyhow::Error) -> bool {
         || error_str.contains("Network is unreachable")
         || error_str.contains("Connection timed out")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_safe_path() {
+        let dir = Path::new("instance");
+        assert_eq!(
+            get_safe_path(dir, Path::new("./mods/a.jar")),
+            Some(dir.join("mods").join("a.jar"))
+        );
+        assert!(get_safe_path(dir, Path::new("/etc/passwd")).is_none());
+        assert!(get_safe_path(dir, Path::new("a/../../b")).is_none());
+    }
+
+    #[test]
+    fn test_url_from_rel_path() {
+        assert_eq!(
+            url_from_rel_path(
+                Path::new("instances/Сборка (Full)/minecraft/mods/100%#?.jar"),
+                "https://example.com/data/"
+            )
+            .unwrap(),
+            "https://example.com/data/instances/%D0%A1%D0%B1%D0%BE%D1%80%D0%BA%D0%B0%20(Full)/minecraft/mods/100%25%23%3F.jar"
+        );
+        assert_eq!(
+            url_from_rel_path(Path::new("versions\\1.20.1\\1.20.1.json"), "").unwrap(),
+            "/versions/1.20.1/1.20.1.json"
+        );
+
+        #[cfg(unix)]
+        {
+            use std::ffi::OsStr;
+            use std::os::unix::ffi::OsStrExt as _;
+
+            let path = Path::new(OsStr::from_bytes(b"mods/\xff.jar"));
+            assert!(path_to_str(path).is_err());
+            assert!(url_from_rel_path(path, "").is_err());
+        }
+    }
+}
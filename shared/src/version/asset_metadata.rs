@@ -1,6 +1,6 @@
 use std::{
     collections::HashMap,
-    path::{Path, PathBuf},
+    path::{Component, Path, PathBuf},
 };
 
 use crate::{
@@ -10,17 +10,35 @@ use crate::{
     progress,
     version::version_metadata::AssetIndex,
 };
+use log::warn;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize)]
 pub struct ObjectData {
     pub hash: String,
+    pub size: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct AssetsMetadata {
     pub objects: HashMap<String, ObjectData>,
+
+    // pre-1.6 versions read the assets from the resources dir of the game directory
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub map_to_resources: bool,
+
+    // 1.6 to 1.7.2 read them by name from assets/virtual/<index id>
+    #[serde(
+        default,
+        rename = "virtual",
+        skip_serializing_if = "std::ops::Not::not"
+    )]
+    pub is_virtual: bool,
+}
+
+fn get_object_path(assets_dir: &Path, hash: &str) -> PathBuf {
+    assets_dir.join("objects").join(&hash[..2]).join(hash)
 }
 
 impl AssetsMetadata {
@@ -47,8 +65,9 @@ impl AssetsMetadata {
     ) -> anyhow::Result<Self> {
         let asset_index_path = get_asset_index_path(assets_dir, &asset_index.id);
         let check_entry = CheckEntry {
-            url: asset_index.url.clone(),
+            urls: vec![asset_index.url.clone()],
             remote_sha1: Some(asset_index.sha1.clone()),
+            remote_size: None,
             path: asset_index_path.clone(),
         };
         let check_entries = vec![check_entry];
@@ -66,32 +85,138 @@ impl AssetsMetadata {
     ) -> anyhow::Result<Vec<CheckEntry>> {
         let mut download_entries = vec![];
 
-        download_entries.extend(self.objects.values().map(|object| {
-            CheckEntry {
-                url: format!(
-                    "{}/{}/{}",
-                    resources_url_base,
-                    &object.hash[..2],
-                    object.hash
-                ),
-                path: assets_dir
-                    .join("objects")
-                    .join(&object.hash[..2])
-                    .join(&object.hash),
-                remote_sha1: if check_hashes {
-                    Some(object.hash.clone())
-                } else {
-                    None
-                },
-            }
+        download_entries.extend(self.objects.values().map(|object| CheckEntry {
+            urls: vec![format!(
+                "{}/{}/{}",
+                resources_url_base,
+                &object.hash[..2],
+                object.hash
+            )],
+            path: get_object_path(assets_dir, &object.hash),
+            remote_sha1: if check_hashes {
+                Some(object.hash.clone())
+            } else {
+                None
+            },
+            remote_size: object.size,
         }));
 
         Ok(download_entries)
     }
 
+    // where legacy versions expect the assets by name, None for the hashed objects layout
+    pub fn get_virtual_dir(
+        &self,
+        asset_id: &str,
+        assets_dir: &Path,
+        minecraft_dir: &Path,
+    ) -> Option<PathBuf> {
+        if self.map_to_resources {
+            Some(minecraft_dir.join("resources"))
+        } else if self.is_virtual {
+            Some(assets_dir.join("virtual").join(asset_id))
+        } else {
+            None
+        }
+    }
+
+    // copies the downloaded objects to their names, files already in place are kept
+    pub async fn virtualize(&self, assets_dir: &Path, target_dir: &Path) -> anyhow::Result<()> {
+        for (name, object) in &self.objects {
+            if !Path::new(name)
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)))
+            {
+                warn!("Skipping asset with unsafe name {name}");
+                continue;
+            }
+            let target_path = target_dir.join(name);
+            if let Ok(target_metadata) = tokio::fs::metadata(&target_path).await {
+                if object.size.is_none_or(|size| target_metadata.len() == size) {
+                    continue;
+                }
+            }
+            if let Some(parent) = target_path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            tokio::fs::copy(get_object_path(assets_dir, &object.hash), &target_path).await?;
+        }
+        Ok(())
+    }
+
     pub async fn save_to_file(&self, asset_id: &str, assets_dir: &Path) -> anyhow::Result<()> {
         let data = serde_json::to_vec(self)?;
         tokio::fs::write(Self::get_path(assets_dir, asset_id).await?, data).await?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOUND: &[u8] = b"sound";
+    const SOUND_HASH: &str = "a6d1cd1ce1d2cff8d0a0e2a1e3ef6a0d85c6ec43";
+
+    fn get_index(extra_fields: &str) -> AssetsMetadata {
+        serde_json::from_str(&format!(
+            r#"{{{extra_fields}"objects": {{"sound/step/grass1.ogg": {{"hash": "{SOUND_HASH}", "size": {}}}}}}}"#,
+            SOUND.len()
+        ))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_virtualize_legacy_layouts() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        let assets_dir = dir.join("assets");
+        let minecraft_dir = dir.join("minecraft");
+        let object_path = get_object_path(&assets_dir, SOUND_HASH);
+        std::fs::create_dir_all(object_path.parent().unwrap()).unwrap();
+        std::fs::write(&object_path, SOUND).unwrap();
+
+        let modern = get_index("");
+        assert_eq!(
+            modern.get_virtual_dir("1.20", &assets_dir, &minecraft_dir),
+            None
+        );
+        assert_eq!(
+            serde_json::to_value(&modern)
+                .unwrap()
+                .as_object()
+                .unwrap()
+                .len(),
+            1
+        );
+
+        for (extra_fields, asset_id, expected_dir) in [
+            (
+                r#""map_to_resources": true, "#,
+                "pre-1.6",
+                minecraft_dir.join("resources"),
+            ),
+            (
+                r#""virtual": true, "#,
+                "legacy",
+                assets_dir.join("virtual").join("legacy"),
+            ),
+        ] {
+            let index = get_index(extra_fields);
+            let virtual_dir = index
+                .get_virtual_dir(asset_id, &assets_dir, &minecraft_dir)
+                .unwrap();
+            assert_eq!(virtual_dir, expected_dir);
+
+            index.virtualize(&assets_dir, &virtual_dir).await.unwrap();
+            let asset_path = virtual_dir.join("sound/step/grass1.ogg");
+            assert_eq!(std::fs::read(&asset_path).unwrap(), SOUND);
+
+            // a second sync keeps the materialized files
+            std::fs::remove_file(&object_path).unwrap();
+            index.virtualize(&assets_dir, &virtual_dir).await.unwrap();
+            assert_eq!(std::fs::read(&asset_path).unwrap(), SOUND);
+            std::fs::write(&object_path, SOUND).unwrap();
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use sha1::{Digest, Sha1};
+
+use super::extra_version_metadata::ExtraVersionMetadata;
+
+pub const SHORT_CONTENT_HASH_LEN: usize = 8;
+
+// metadata ids must be ordered from parent to child
+pub fn get_content_hash(metadata_ids: &[&str], extra: Option<&ExtraVersionMetadata>) -> String {
+    let mut objects: Vec<String> = extra
+        .map(|extra| {
+            extra
+                .include
+                .iter()
+                .flat_map(|rule| &rule.objects)
+                .map(|object| format!("{}:{}", object.path, object.sha1))
+                .collect()
+        })
+        .unwrap_or_default();
+    objects.sort();
+    objects.dedup();
+
+    let mut hasher = Sha1::new();
+    for id in metadata_ids {
+        hasher.update(format!("id:{id}\n"));
+    }
+    for object in objects {
+        hasher.update(object);
+        hasher.update(b"\n");
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+pub fn get_short_content_hash(content_hash: &str) -> &str {
+    &content_hash[..SHORT_CONTENT_HASH_LEN.min(content_hash.len())]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::version::extra_version_metadata::{Include, Object};
+
+    fn make_extra(objects: Vec<(&str, &str)>) -> ExtraVersionMetadata {
+        let objects = objects
+            .into_iter()
+            .map(|(path, sha1)| Object {
+                path: path.to_string(),
+                sha1: sha1.to_string(),
+                url: format!("https://example.com/{path}"),
+                size: None,
+            })
+            .collect();
+        ExtraVersionMetadata {
+            include: vec![Include {
+                path: "mods".to_string(),
+                overwrite: true,
+                delete_extra: true,
+                recursive: false,
+                objects,
+            }],
+            auth_backend: None,
+            resources_url_base: None,
+            extra_forge_libs: vec![],
+            recommended_xmx: None,
+            recommended_jvm_args: vec![],
+            min_launcher_version: None,
+            description: Default::default(),
+            overrides: vec![],
+            branding: Default::default(),
+            checksums_url: None,
+            authlib_injector_url: None,
+            authlib_injector_sha1: None,
+            download_mirrors: None,
+        }
+    }
+
+    #[test]
+    fn test_content_hash_ignores_object_order() {
+        let a = make_extra(vec![("mods/a.jar", "1"), ("mods/b.jar", "2")]);
+        let b = make_extra(vec![("mods/b.jar", "2"), ("mods/a.jar", "1")]);
+        let c = make_extra(vec![("mods/a.jar", "1"), ("mods/b.jar", "3")]);
+        let ids = ["1.20.1", "fabric-loader-0.15.0-1.20.1"];
+
+        assert_eq!(
+            get_content_hash(&ids, Some(&a)),
+            get_content_hash(&ids, Some(&b))
+        );
+        assert_ne!(
+            get_content_hash(&ids, Some(&a)),
+            get_content_hash(&ids, Some(&c))
+        );
+        assert_ne!(
+            get_content_hash(&ids, Some(&a)),
+            get_content_hash(&ids[..1], Some(&a))
+        );
+    }
+}
@@ -3,15 +3,16 @@ use std::path::Path;
 use log::warn;
 use serde::{Deserialize, Serialize};
 
-use crate::{files::CheckEntry, paths::get_extra_metadata_path};
+use crate::{files::CheckEntry, json, paths::get_extra_metadata_path};
 
 use super::{version_manifest::VersionInfo, version_metadata::Library};
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Object {
     pub path: String,
     pub sha1: String,
     pub url: String,
+    pub size: Option<u64>,
 }
 
 #[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
@@ -72,7 +73,7 @@ fn yes() -> bool {
     true
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Clone)]
 pub struct Include {
     pub path: String,
 
@@ -89,6 +90,132 @@ pub struct Include {
     pub objects: Vec<Object>,
 }
 
+// shown in the launcher when the instance is selected
+#[derive(Deserialize, Serialize, Clone, Default, PartialEq, Debug)]
+pub struct InstanceDescription {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description_markdown: Option<String>,
+
+    // fetched by the launcher, takes precedence over description_markdown
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description_url: Option<String>,
+
+    // opened in the browser, the launcher doesn't fetch it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub changelog_url: Option<String>,
+}
+
+impl InstanceDescription {
+    pub fn is_empty(&self) -> bool {
+        self.description_markdown.is_none()
+            && self.description_url.is_none()
+            && self.changelog_url.is_none()
+    }
+}
+
+// custom game window, applied by the launcher when the loader supports it
+#[derive(Deserialize, Serialize, Clone, Default, PartialEq, Debug)]
+pub struct WindowBranding {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window_title: Option<String>,
+
+    // png path relative to the minecraft dir, delivered via include rules
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window_icon: Option<String>,
+}
+
+impl WindowBranding {
+    pub fn is_empty(&self) -> bool {
+        self.window_title.is_none() && self.window_icon.is_none()
+    }
+}
+
+// points libraries with a broken upstream download somewhere else
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+pub struct LibraryOverride {
+    // full library name, `*` matches any characters, e.g. "org.lwjgl:lwjgl:3.3.1:natives-*"
+    pub name: String,
+    pub url: String,
+    pub sha1: String,
+
+    // replaces the native with this classifier instead of the main artifact
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub classifier: Option<String>,
+}
+
+// `*` matches any characters, including `/`
+pub fn matches_pattern(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+impl LibraryOverride {
+    pub fn matches(&self, library: &Library) -> bool {
+        matches_pattern(&self.name, &library.get_full_name())
+    }
+
+    pub fn apply(&self, library: &mut Library) {
+        match &self.classifier {
+            Some(classifier) => {
+                library.set_native_download(classifier, self.url.clone(), self.sha1.clone())
+            }
+            None => library.set_artifact_download(self.url.clone(), self.sha1.clone()),
+        }
+    }
+}
+
+// a custom authlib-injector build, e.g. a newer one than the launcher has
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+pub struct AuthlibInjector {
+    pub url: String,
+    pub sha1: String,
+}
+
+// servers with the same files as the download server
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+pub struct DownloadMirrors {
+    pub download_server_base: String,
+    pub mirrors: Vec<String>,
+}
+
+impl DownloadMirrors {
+    // the download server base first
+    pub fn get_bases(&self) -> Vec<String> {
+        std::iter::once(self.download_server_base.clone())
+            .chain(self.mirrors.iter().cloned())
+            .collect()
+    }
+
+    // the url on each of the bases in their order, urls of other servers are kept as is
+    pub fn get_urls(&self, url: &str, bases: &[String]) -> Vec<String> {
+        let download_server_base = self.download_server_base.trim_end_matches('/');
+        match url
+            .strip_prefix(download_server_base)
+            .filter(|rel_url| rel_url.starts_with('/'))
+        {
+            Some(rel_url) => bases
+                .iter()
+                .map(|base| format!("{}{rel_url}", base.trim_end_matches('/')))
+                .collect(),
+            None => vec![url.to_string()],
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize)]
 pub struct ExtraVersionMetadata {
     #[serde(default)]
@@ -104,22 +231,71 @@ pub struct ExtraVersionMetadata {
     pub extra_forge_libs: Vec<Library>,
 
     pub recommended_xmx: Option<String>,
+
+    #[serde(default)]
+    pub recommended_jvm_args: Vec<String>,
+
+    #[serde(default)]
+    pub min_launcher_version: Option<String>,
+
+    #[serde(flatten)]
+    pub description: InstanceDescription,
+
+    #[serde(default)]
+    pub overrides: Vec<LibraryOverride>,
+
+    #[serde(flatten)]
+    pub branding: WindowBranding,
+
+    // sizes and hashes of the published files, lets the launcher skip size lookups
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksums_url: Option<String>,
+
+    // the launcher uses its own version unless both are set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authlib_injector_url: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authlib_injector_sha1: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub download_mirrors: Option<DownloadMirrors>,
 }
 
 impl ExtraVersionMetadata {
+    pub fn get_authlib_injector(&self) -> Option<AuthlibInjector> {
+        match (&self.authlib_injector_url, &self.authlib_injector_sha1) {
+            (Some(url), Some(sha1)) => Some(AuthlibInjector {
+                url: url.clone(),
+                sha1: sha1.clone(),
+            }),
+            (None, None) => None,
+            _ => {
+                warn!("authlib_injector_url and authlib_injector_sha1 must be set together");
+                None
+            }
+        }
+    }
+
     pub async fn read_local(
         version_info: &VersionInfo,
         versions_extra_dir: &Path,
     ) -> anyhow::Result<Option<Self>> {
-        if version_info.extra_metadata_url.is_none() || version_info.extra_metadata_sha1.is_none() {
+        let (Some(extra_metadata_url), Some(_)) = (
+            &version_info.extra_metadata_url,
+            &version_info.extra_metadata_sha1,
+        ) else {
             return Ok(None);
-        }
+        };
 
         let extra_version_metadata_path =
             get_extra_metadata_path(versions_extra_dir, &version_info.get_name());
         let extra_version_metadata_file = tokio::fs::read(extra_version_metadata_path).await?;
 
-        Ok(Some(serde_json::from_slice(&extra_version_metadata_file)?))
+        Ok(Some(json::from_slice(
+            &extra_version_metadata_file,
+            extra_metadata_url,
+        )?))
     }
 
     pub fn get_check_entry(
@@ -134,8 +310,9 @@ impl ExtraVersionMetadata {
         let sha1 = version_info.extra_metadata_sha1.as_ref().unwrap();
 
         Some(CheckEntry {
-            url: url.clone(),
+            urls: vec![url.clone()],
             remote_sha1: Some(sha1.clone()),
+            remote_size: None,
             path: get_extra_metadata_path(versions_extra_dir, &version_info.get_name()),
         })
     }
@@ -148,3 +325,102 @@ impl ExtraVersionMetadata {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_pattern() {
+        assert!(matches_pattern(
+            "org.lwjgl:lwjgl:3.3.1",
+            "org.lwjgl:lwjgl:3.3.1"
+        ));
+        assert!(!matches_pattern(
+            "org.lwjgl:lwjgl:3.3.1",
+            "org.lwjgl:lwjgl:3.3.1:natives-linux"
+        ));
+        assert!(matches_pattern(
+            "org.lwjgl:*:3.3.1:natives-*",
+            "org.lwjgl:lwjgl-glfw:3.3.1:natives-linux"
+        ));
+        assert!(!matches_pattern(
+            "org.lwjgl:*:3.3.1:natives-*",
+            "org.lwjgl:lwjgl-glfw:3.3.2:natives-linux"
+        ));
+        assert!(matches_pattern("*", "com.mojang:brigadier:1.0.18"));
+    }
+
+    #[test]
+    fn test_download_mirrors() {
+        let mirrors = DownloadMirrors {
+            download_server_base: "https://example.com/data/".to_string(),
+            mirrors: vec!["https://eu.example.com/data".to_string()],
+        };
+        let bases = vec![
+            "https://eu.example.com/data".to_string(),
+            "https://example.com/data/".to_string(),
+        ];
+        assert_eq!(
+            mirrors.get_urls("https://example.com/data/mods/a.jar", &bases),
+            vec![
+                "https://eu.example.com/data/mods/a.jar",
+                "https://example.com/data/mods/a.jar",
+            ]
+        );
+        // not under the base
+        for url in [
+            "https://libraries.minecraft.net/a.jar",
+            "https://example.com/database/a.jar",
+        ] {
+            assert_eq!(mirrors.get_urls(url, &bases), vec![url]);
+        }
+    }
+
+    #[test]
+    fn test_instance_description() {
+        // metadata of older builders has no description fields
+        let extra: ExtraVersionMetadata =
+            serde_json::from_str(r#"{"recommended_xmx": null}"#).unwrap();
+        assert!(extra.description.is_empty());
+
+        let extra: ExtraVersionMetadata = serde_json::from_str(
+            r#"{"recommended_xmx": null, "changelog_url": "https://example.com/changelog"}"#,
+        )
+        .unwrap();
+        assert!(!extra.description.is_empty());
+        let json = serde_json::to_value(&extra).unwrap();
+        assert_eq!(json["changelog_url"], "https://example.com/changelog");
+        assert!(json.get("description_url").is_none());
+    }
+
+    #[test]
+    fn test_get_authlib_injector() {
+        let extra: ExtraVersionMetadata =
+            serde_json::from_str(r#"{"recommended_xmx": null}"#).unwrap();
+        assert_eq!(extra.get_authlib_injector(), None);
+        assert!(serde_json::to_value(&extra)
+            .unwrap()
+            .get("authlib_injector_url")
+            .is_none());
+
+        let extra: ExtraVersionMetadata = serde_json::from_str(
+            r#"{"recommended_xmx": null, "authlib_injector_url": "https://example.com/authlib-injector.jar", "authlib_injector_sha1": "abc"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            extra.get_authlib_injector(),
+            Some(AuthlibInjector {
+                url: "https://example.com/authlib-injector.jar".to_string(),
+                sha1: "abc".to_string(),
+            })
+        );
+
+        // a url without the hash can't be verified
+        let extra: ExtraVersionMetadata = serde_json::from_str(
+            r#"{"recommended_xmx": null, "authlib_injector_url": "https://example.com/authlib-injector.jar"}"#,
+        )
+        .unwrap();
+        assert_eq!(extra.get_authlib_injector(), None);
+    }
+}
@@ -0,0 +1,204 @@
+const DEFAULT_EXTENSION: &str = "jar";
+
+// group:artifact:version[:classifier][@extension], the gradle style notation used in version metadata
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct MavenCoordinate {
+    pub group: String,
+    pub artifact: String,
+    pub version: String,
+    pub classifier: Option<String>,
+    pub extension: String,
+}
+
+impl MavenCoordinate {
+    // malformed names give empty parts instead of failing, the paths are still unique per name
+    pub fn parse(name: &str) -> Self {
+        let (name, extension) = match name.rsplit_once('@') {
+            Some((name, extension)) if !extension.is_empty() => (name, extension),
+            _ => (name.trim_end_matches('@'), DEFAULT_EXTENSION),
+        };
+        let mut parts = name.split(':');
+        let mut next_part = || parts.next().unwrap_or_default().to_string();
+        let group = next_part();
+        let artifact = next_part();
+        let version = next_part();
+        let classifier = Some(next_part()).filter(|classifier| !classifier.is_empty());
+        MavenCoordinate {
+            group,
+            artifact,
+            version,
+            classifier,
+            extension: extension.to_string(),
+        }
+    }
+
+    pub fn get_path(&self) -> String {
+        let group_path = self.group.replace('.', "/");
+        let MavenCoordinate {
+            artifact, version, ..
+        } = self;
+        let classifier = match &self.classifier {
+            Some(classifier) => format!("-{classifier}"),
+            None => String::new(),
+        };
+        format!(
+            "{group_path}/{artifact}/{version}/{artifact}-{version}{classifier}.{}",
+            self.extension
+        )
+    }
+
+    // identifies the library regardless of its version
+    pub fn get_name_without_version(&self) -> String {
+        let mut name = format!("{}:{}", self.group, self.artifact);
+        if let Some(classifier) = &self.classifier {
+            name.push(':');
+            name.push_str(classifier);
+        }
+        if self.extension != DEFAULT_EXTENSION {
+            name.push('@');
+            name.push_str(&self.extension);
+        }
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // taken from vanilla, forge, neoforge and fabric metadata
+    const CORPUS: &[(&str, &str)] = &[
+        (
+            "com.mojang:authlib:6.0.54",
+            "com/mojang/authlib/6.0.54/authlib-6.0.54.jar",
+        ),
+        (
+            "org.lwjgl:lwjgl-glfw:3.3.3:natives-windows-x86",
+            "org/lwjgl/lwjgl-glfw/3.3.3/lwjgl-glfw-3.3.3-natives-windows-x86.jar",
+        ),
+        (
+            "org.lwjgl:lwjgl:3.3.3:natives-macos-arm64",
+            "org/lwjgl/lwjgl/3.3.3/lwjgl-3.3.3-natives-macos-arm64.jar",
+        ),
+        (
+            "ca.weblite:java-objc-bridge:1.1",
+            "ca/weblite/java-objc-bridge/1.1/java-objc-bridge-1.1.jar",
+        ),
+        (
+            "org.lwjgl.lwjgl:lwjgl-platform:2.9.4-nightly-20150209:natives-linux",
+            "org/lwjgl/lwjgl/lwjgl-platform/2.9.4-nightly-20150209/lwjgl-platform-2.9.4-nightly-20150209-natives-linux.jar",
+        ),
+        (
+            "net.minecraftforge:forge:1.20.1-47.2.0:universal",
+            "net/minecraftforge/forge/1.20.1-47.2.0/forge-1.20.1-47.2.0-universal.jar",
+        ),
+        (
+            "net.minecraftforge:fmlloader:1.20.1-47.2.0",
+            "net/minecraftforge/fmlloader/1.20.1-47.2.0/fmlloader-1.20.1-47.2.0.jar",
+        ),
+        (
+            "cpw.mods:securejarhandler:2.1.10",
+            "cpw/mods/securejarhandler/2.1.10/securejarhandler-2.1.10.jar",
+        ),
+        (
+            "net.neoforged.fancymodloader:loader:2.0.17@jar",
+            "net/neoforged/fancymodloader/loader/2.0.17/loader-2.0.17.jar",
+        ),
+        (
+            "net.neoforged:neoforge:20.4.237:universal@jar",
+            "net/neoforged/neoforge/20.4.237/neoforge-20.4.237-universal.jar",
+        ),
+        (
+            "de.oceanlabs.mcp:mcp_config:1.20.1-20230612.114412@zip",
+            "de/oceanlabs/mcp/mcp_config/1.20.1-20230612.114412/mcp_config-1.20.1-20230612.114412.zip",
+        ),
+        (
+            "net.minecraft:client:1.20.1-20230612.114412:mappings@txt",
+            "net/minecraft/client/1.20.1-20230612.114412/client-1.20.1-20230612.114412-mappings.txt",
+        ),
+        (
+            "net.fabricmc:fabric-loader:0.15.11",
+            "net/fabricmc/fabric-loader/0.15.11/fabric-loader-0.15.11.jar",
+        ),
+        (
+            "net.fabricmc:intermediary:1.20.1",
+            "net/fabricmc/intermediary/1.20.1/intermediary-1.20.1.jar",
+        ),
+        (
+            "net.fabricmc:sponge-mixin:0.13.3+mixin.0.8.5",
+            "net/fabricmc/sponge-mixin/0.13.3+mixin.0.8.5/sponge-mixin-0.13.3+mixin.0.8.5.jar",
+        ),
+        (
+            "io.github.llamalad7:mixinextras-forge:0.3.5",
+            "io/github/llamalad7/mixinextras-forge/0.3.5/mixinextras-forge-0.3.5.jar",
+        ),
+        (
+            "com.github.oshi:oshi-core:6.4.10",
+            "com/github/oshi/oshi-core/6.4.10/oshi-core-6.4.10.jar",
+        ),
+    ];
+
+    // paths produced by the parser before MavenCoordinate, changing any of them
+    // would make existing installs download the library again
+    const PATH_SNAPSHOT: &[(&str, &str)] = &[
+        (
+            "com.mojang:authlib:6.0.54",
+            "com/mojang/authlib/6.0.54/authlib-6.0.54.jar",
+        ),
+        (
+            "org.lwjgl:lwjgl:3.3.3:natives-macos-arm64",
+            "org/lwjgl/lwjgl/3.3.3/lwjgl-3.3.3-natives-macos-arm64.jar",
+        ),
+        (
+            "net.minecraftforge:forge:1.20.1-47.2.0:universal",
+            "net/minecraftforge/forge/1.20.1-47.2.0/forge-1.20.1-47.2.0-universal.jar",
+        ),
+        (
+            "net.neoforged.fancymodloader:loader:2.0.17@jar",
+            "net/neoforged/fancymodloader/loader/2.0.17/loader-2.0.17.jar",
+        ),
+        (
+            "net.fabricmc:sponge-mixin:0.13.3+mixin.0.8.5",
+            "net/fabricmc/sponge-mixin/0.13.3+mixin.0.8.5/sponge-mixin-0.13.3+mixin.0.8.5.jar",
+        ),
+        (
+            "org.apache.logging.log4j:log4j-slf4j2-impl:2.22.1",
+            "org/apache/logging/log4j/log4j-slf4j2-impl/2.22.1/log4j-slf4j2-impl-2.22.1.jar",
+        ),
+    ];
+
+    #[test]
+    fn test_parse_corpus() {
+        for (name, path) in CORPUS.iter().chain(PATH_SNAPSHOT) {
+            assert_eq!(MavenCoordinate::parse(name).get_path(), *path, "{name}");
+        }
+
+        assert_eq!(
+            MavenCoordinate::parse("net.minecraft:client:1.20.1-20230612.114412:mappings@txt"),
+            MavenCoordinate {
+                group: "net.minecraft".to_string(),
+                artifact: "client".to_string(),
+                version: "1.20.1-20230612.114412".to_string(),
+                classifier: Some("mappings".to_string()),
+                extension: "txt".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_name_without_version() {
+        let name_without_version = |name| MavenCoordinate::parse(name).get_name_without_version();
+        assert_eq!(
+            name_without_version("org.ow2.asm:asm:9.7"),
+            name_without_version("org.ow2.asm:asm:9.6@jar")
+        );
+        assert_eq!(
+            name_without_version("org.lwjgl:lwjgl:3.3.3:natives-linux"),
+            "org.lwjgl:lwjgl:natives-linux"
+        );
+        assert_eq!(
+            name_without_version("de.oceanlabs.mcp:mcp_config:1.20.1@zip"),
+            "de.oceanlabs.mcp:mcp_config@zip"
+        );
+    }
+}
@@ -1,4 +1,6 @@
 pub mod asset_metadata;
+pub mod content_hash;
 pub mod extra_version_metadata;
+pub mod maven_coordinate;
 pub mod version_manifest;
 pub mod version_metadata;
@@ -1,8 +1,11 @@
 use std::path::Path;
 
+use log::warn;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
+use crate::{compression, json, signing};
+
 #[derive(Clone, Serialize, Deserialize, PartialEq)]
 pub struct MetadataInfo {
     pub id: String,
@@ -23,6 +26,14 @@ pub struct VersionInfo {
 
     pub extra_metadata_url: Option<String>,
     pub extra_metadata_sha1: Option<String>,
+
+    // share of the launchers that get this version, the rest keep the installed one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rollout_percent: Option<u8>,
+
+    // minecraft server the instance is made for, pinged to show its status
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_address: Option<String>,
 }
 
 impl VersionInfo {
@@ -33,6 +44,17 @@ impl VersionInfo {
         }
     }
 
+    // the rollout percent changes without changing the version itself
+    pub fn is_same_version(&self, other: &VersionInfo) -> bool {
+        self.id == other.id
+            && self.url == other.url
+            && self.sha1 == other.sha1
+            && self.name == other.name
+            && self.inherits_from == other.inherits_from
+            && self.extra_metadata_url == other.extra_metadata_url
+            && self.extra_metadata_sha1 == other.extra_metadata_sha1
+    }
+
     pub fn get_parent_metadata_info(&self) -> MetadataInfo {
         match self.inherits_from.first() {
             Some(parent_info) => parent_info.clone(),
@@ -70,21 +92,40 @@ impl VersionManifest {
         }
     }
 
-    pub async fn fetch(url: &str) -> anyhow::Result<Self> {
+    // prefers the compressed manifest if the server has one, returns the json it was parsed from
+    async fn fetch_data(url: &str) -> anyhow::Result<(Self, Vec<u8>)> {
         let client = Client::new();
-        let res = client
-            .get(url)
-            .send()
-            .await?
-            .error_for_status()?
-            .json::<Self>()
-            .await?;
-        Ok(res)
+        if let Some(data) = compression::fetch_zstd_variant(&client, url).await {
+            match serde_json::from_slice(&data) {
+                Ok(manifest) => return Ok((manifest, data)),
+                Err(e) => {
+                    warn!("Invalid compressed manifest at {url}, fetching the plain one:\n{e:?}")
+                }
+            }
+        }
+        let data = compression::fetch_bytes(&client, url).await?;
+        Ok((json::from_slice(&data, url)?, data))
+    }
+
+    pub async fn fetch(url: &str) -> anyhow::Result<Self> {
+        Ok(Self::fetch_data(url).await?.0)
+    }
+
+    pub async fn fetch_verified(url: &str, public_key: Option<&str>) -> anyhow::Result<Self> {
+        let Some(public_key) = public_key else {
+            return Self::fetch(url).await;
+        };
+
+        let (manifest, data) = Self::fetch_data(url).await?;
+        let signature = signing::fetch_signature(url).await?;
+        signing::verify(&data, &signature, public_key)?;
+        Ok(manifest)
     }
 
     pub async fn read_local(manifest_path: &Path) -> anyhow::Result<Self> {
         let manifest_file = tokio::fs::read(manifest_path).await?;
-        let manifest: Self = serde_json::from_slice(&manifest_file)?;
+        let manifest: Self =
+            json::from_slice(&manifest_file, &manifest_path.display().to_string())?;
         Ok(manifest)
     }
 
@@ -104,7 +145,9 @@ impl VersionManifest {
     }
 
     pub fn is_up_to_date(&self, version_info: &VersionInfo) -> bool {
-        self.versions.iter().any(|i| i == version_info)
+        self.versions
+            .iter()
+            .any(|i| i.is_same_version(version_info))
     }
 
     pub async fn add_version_and_save(
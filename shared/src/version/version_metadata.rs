@@ -8,11 +8,14 @@ use tokio::{fs, io::AsyncReadExt as _};
 
 use crate::{
     adaptive_download::download_files,
+    compression,
     files::{self, CheckEntry},
+    java, json,
     paths::get_metadata_path,
     progress,
 };
 
+use super::maven_coordinate::MavenCoordinate;
 use super::version_manifest::MetadataInfo;
 
 fn get_arch_os_name(os_name: &str, arch: &str) -> String {
@@ -168,13 +171,15 @@ pub struct JavaVersion {
 pub struct Download {
     pub sha1: String,
     pub url: String,
+    pub size: Option<u64>,
 }
 
 impl Download {
     pub fn get_check_entry(&self, path: &Path) -> CheckEntry {
         CheckEntry {
-            url: self.url.clone(),
+            urls: vec![self.url.clone()],
             remote_sha1: Some(self.sha1.clone()),
+            remote_size: self.size,
             path: path.to_path_buf(),
         }
     }
@@ -207,7 +212,11 @@ impl Library {
         Library {
             name,
             downloads: Some(LibraryDownloads {
-                artifact: Some(Download { url, sha1 }),
+                artifact: Some(Download {
+                    url,
+                    sha1,
+                    size: None,
+                }),
                 classifiers: None,
             }),
             rules: None,
@@ -217,22 +226,12 @@ impl Library {
         }
     }
 
+    pub fn get_coordinate(&self) -> MavenCoordinate {
+        MavenCoordinate::parse(&self.name)
+    }
+
     pub fn get_path_from_name(&self) -> String {
-        let full_name = self.name.clone();
-        let mut parts: Vec<&str> = full_name.split(':').collect();
-        if parts.len() != 4 {
-            parts.push("");
-        }
-        let (pkg, name, version, suffix) = (parts[0], parts[1], parts[2], parts[3]);
-        // neoforge adds "@jar" to the version, so we need to remove it
-        let version = version.split("@jar").next().unwrap();
-        let pkg_path = pkg.replace('.', "/");
-        let suffix = if suffix.is_empty() {
-            "".to_string()
-        } else {
-            format!("-{suffix}")
-        };
-        format!("{pkg_path}/{name}/{version}/{name}-{version}{suffix}.jar")
+        self.get_coordinate().get_path()
     }
 
     pub fn get_library_path(&self, libraries_dir: &Path) -> Option<PathBuf> {
@@ -251,6 +250,15 @@ impl Library {
         self.url.clone().unwrap_or(MOJANG_LIBRARIES_URL.to_string())
     }
 
+    // repository urls may or may not end with a slash
+    fn get_artifact_url(&self) -> String {
+        format!(
+            "{}/{}",
+            self.get_url().trim_end_matches('/'),
+            self.get_path_from_name()
+        )
+    }
+
     fn get_library_dir(&self, libraries_dir: &Path) -> PathBuf {
         let path = libraries_dir.join(self.get_path_from_name());
         path.parent().unwrap_or(libraries_dir).to_path_buf()
@@ -278,6 +286,11 @@ impl Library {
             .join(native_download.get_filename())
     }
 
+    pub fn get_os_native_download(&self, os_name: &str, arch: &str) -> Option<&Download> {
+        let native_name = self.get_native_name(&get_arch_os_name(os_name, arch))?;
+        self.get_native_download(native_name)
+    }
+
     pub fn get_os_native_path(
         &self,
         libraries_dir: &Path,
@@ -302,8 +315,9 @@ impl Library {
             }
         } else {
             Some(CheckEntry {
-                url: format!("{}/{}", self.get_url(), self.get_path_from_name()),
+                urls: vec![self.get_artifact_url()],
                 remote_sha1: self.sha1.clone(),
+                remote_size: None,
                 path: libraries_dir.join(self.get_path_from_name()),
             })
         }
@@ -347,26 +361,47 @@ impl Library {
     }
 
     pub fn get_sha1_url(&self) -> String {
-        self.get_url() + &self.get_path_from_name() + ".sha1"
+        self.get_artifact_url() + ".sha1"
     }
 
     pub fn get_group_id(&self) -> String {
-        let parts: Vec<&str> = self.name.split(':').collect();
-        parts[0].to_string()
+        self.get_coordinate().group
     }
 
     pub fn get_full_name(&self) -> String {
         self.name.clone()
     }
 
+    pub fn set_artifact_download(&mut self, url: String, sha1: String) {
+        let downloads = self.downloads.get_or_insert(LibraryDownloads {
+            artifact: None,
+            classifiers: None,
+        });
+        downloads.artifact = Some(Download {
+            url,
+            sha1,
+            size: None,
+        });
+    }
+
+    pub fn set_native_download(&mut self, classifier: &str, url: String, sha1: String) {
+        let downloads = self.downloads.get_or_insert(LibraryDownloads {
+            artifact: None,
+            classifiers: None,
+        });
+        downloads.classifiers.get_or_insert_default().insert(
+            classifier.to_string(),
+            Download {
+                url,
+                sha1,
+                size: None,
+            },
+        );
+    }
+
     pub fn get_name_and_version(&self) -> (String, String) {
-        let mut parts: Vec<&str> = self.name.split(':').collect();
-        if parts.len() != 4 {
-            parts.push("");
-        }
-        let version = parts[2].to_string();
-        parts.remove(2);
-        (parts.join(":"), version)
+        let coordinate = self.get_coordinate();
+        (coordinate.get_name_without_version(), coordinate.version)
     }
 }
 
@@ -432,20 +467,27 @@ lazy_static::lazy_static! {
 }
 
 impl VersionMetadata {
+    // java major version, guessed from the id if the metadata doesn't specify it
+    pub fn get_java_version(&self) -> String {
+        match &self.java_version {
+            Some(java_version) => java_version.major_version.to_string(),
+            None => java::get_fallback_java_version(&self.id),
+        }
+    }
+
     pub async fn read_local(versions_dir: &Path, version_id: &str) -> anyhow::Result<Self> {
         let version_path = get_metadata_path(versions_dir, version_id);
-        let mut file = fs::File::open(version_path).await?;
+        let mut file = fs::File::open(&version_path).await?;
         let mut content = String::new();
         file.read_to_string(&mut content).await?;
-        let metadata = serde_json::from_str(&content)?;
+        let metadata = json::from_slice(content.as_bytes(), &version_path.display().to_string())?;
         Ok(metadata)
     }
 
     pub async fn fetch(url: &str) -> anyhow::Result<Self> {
         let client = reqwest::Client::new();
-        let response = client.get(url).send().await?.error_for_status()?;
-        let metadata = response.json().await?;
-        Ok(metadata)
+        let data = compression::fetch_bytes(&client, url).await?;
+        Ok(json::from_slice(&data, url)?)
     }
 
     pub fn get_check_entry(metadata_info: &MetadataInfo, versions_dir: &Path) -> CheckEntry {
@@ -453,8 +495,9 @@ impl VersionMetadata {
         let sha1 = metadata_info.sha1.clone();
         let path = get_metadata_path(versions_dir, &metadata_info.id);
         CheckEntry {
-            url,
+            urls: vec![url],
             remote_sha1: Some(sha1),
+            remote_size: None,
             path,
         }
     }